@@ -0,0 +1,552 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * Aggregate
+//!     * Aggregate collects the members of an RcSet or ArcSet by class
+//!       and prints one roll-up block per class.
+//!
+//!     * Counter instances are summed into a single total.  The integer,
+//!       float, and time classes are merged using the merge module, so
+//!       each block shows the combined count, extremes, mean, and higher
+//!       moments of all the members of that class.
+//!
+//!     * Time members are merged only with other members that use the
+//!       same timer frequency.  Members of other classes, and members
+//!       that keep no extremes, are ignored.
+//!
+//!     * The sets create an Aggregate when set_print_aggregates() has been
+//!       invoked, so most users will never need to use this type directly.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::rc_sets::RcSet;
+//!     use rustics::rc_item_mut;
+//!
+//!     let mut set      = RcSet::new("Server", 4, 0, &None);
+//!     let     requests = set.add_counter("Requests", None);
+//!     let     small    = set.add_running_integer("Small Queries", None);
+//!     let     large    = set.add_running_integer("Large Queries", None);
+//!
+//!     for i in 1..=100 {
+//!         rc_item_mut!(requests).record_event();
+//!         rc_item_mut!(small).record_i64(i);
+//!         rc_item_mut!(large).record_i64(i * 1000);
+//!     }
+//!
+//!     // Print the members followed by an aggregate block that
+//!     // combines the two integer statistics.
+//!
+//!     set.set_print_aggregates(true);
+//!     set.print();
+//!```
+
+use super::Rustics;
+use super::Printer;
+use super::Units;
+use super::make_title;
+use super::stats_math::compute_variance;
+use super::stats_math::compute_skewness;
+use super::stats_math::compute_kurtosis;
+use super::stats_math::estimate_moment_3;
+use super::stats_math::EstimateData;
+use super::merge::Export;
+use super::merge::sum_running;
+use super::merge::export_from_stats;
+use super::printable::Printable;
+use super::hier::Hier;
+use super::running_time::RunningTime;
+use super::time_window::TimeWindow;
+use super::sized_time::SizedTime;
+use super::sync_rustics::SyncRustics;
+use super::arc_item;
+
+// Holds the exports for one class of members.
+
+struct ClassSum {
+    hz:       i64,
+    members:  usize,
+    units:    Option<Units>,
+    exports:  Vec<Export>,
+}
+
+impl ClassSum {
+    fn new(hz: i64) -> ClassSum {
+        let members = 0;
+        let units   = None;
+        let exports = Vec::new();
+
+        ClassSum { hz, members, units, exports }
+    }
+
+    fn add(&mut self, member: &dyn Rustics) {
+        // A member without extremes would put placeholder values into
+        // the merged minimum and maximum, so leave it out.
+
+        if !member.int_extremes() && !member.float_extremes() {
+            return;
+        }
+
+        let stats = member.export_stats();
+        let units = stats.printable.units.clone();
+
+        // Keep the units only if all the members agree.
+
+        self.units =
+            match &self.units {
+                None if self.members == 0 => Some(units),
+                Some(current) if current.singular == units.singular
+                        && current.plural == units.plural => Some(units),
+                _ => Some(Units::empty()),
+            };
+
+        self.members += 1;
+
+        if stats.printable.n > 0 {
+            self.exports.push(export_from_stats(&stats));
+        }
+    }
+
+    // Merge the exports and build a Printable from the result.
+
+    fn printable(&self) -> Printable {
+        let units = self.units.clone().unwrap_or_default();
+
+        if self.exports.is_empty() {
+            return Printable {
+                n:           0,          nans:      0,    infinities:  0,
+                min_i64:     i64::MAX,   max_i64:   i64::MIN,
+                min_f64:     f64::MAX,   max_f64:   f64::MIN,
+                mode_value:  0.0,        log_mode:  0,    mean:        0.0,
+                variance:    0.0,        skewness:  0.0,  kurtosis:    0.0,
+                units
+            };
+        }
+
+        let sum        = sum_running(&self.exports);
+        let n          = sum.count;
+        let nans       = sum.nans;
+        let infinities = sum.infinities;
+        let min_i64    = sum.min_i64;
+        let max_i64    = sum.max_i64;
+        let min_f64    = sum.min_f64;
+        let max_f64    = sum.max_f64;
+        let mean       = sum.mean;
+        let moment_2   = sum.moment_2;
+        let variance   = compute_variance(n, moment_2);
+        let kurtosis   = compute_kurtosis(n, moment_2, sum.moment_4);
+
+        let data       = EstimateData { n: n as f64, mean, moment_2, cubes: sum.cubes };
+        let moment_3   = estimate_moment_3(data);
+        let skewness   = compute_skewness(n, moment_2, moment_3);
+
+        let log_mode =
+            if let Some(histogram) = &sum.log_histogram {
+                histogram.borrow().log_mode() as i64
+            } else {
+                0
+            };
+
+        let mode_value =
+            if let Some(histogram) = &sum.float_histogram {
+                histogram.borrow().mode_value()
+            } else {
+                0.0
+            };
+
+        Printable {
+            n,         nans,  infinities,  min_i64,   max_i64,   min_f64,     max_f64,
+            log_mode,  mean,  variance,    skewness,  kurtosis,  mode_value,  units
+        }
+    }
+}
+
+/// Aggregate accumulates set members by class for printing.
+
+pub struct Aggregate {
+    members:   usize,
+    events:    i64,
+    integers:  ClassSum,
+    floats:    ClassSum,
+    times:     Vec<ClassSum>,
+}
+
+impl Aggregate {
+    /// Creates an empty Aggregate.
+
+    pub fn new() -> Aggregate {
+        let members  = 0;
+        let events   = 0;
+        let integers = ClassSum::new(0);
+        let floats   = ClassSum::new(0);
+        let times    = Vec::new();
+
+        Aggregate { members, events, integers, floats, times }
+    }
+
+    /// Adds a set member to the aggregate.
+
+    pub fn add(&mut self, member: &dyn Rustics) {
+        match member.class() {
+            "counter" => {
+                self.members += 1;
+                self.events  += member.count() as i64;
+            }
+
+            "integer" => self.integers.add(member),
+            "float"   => self.floats  .add(member),
+
+            "time"    => {
+                if let Some(hz) = member_hz(member) {
+                    let index = self.times.iter().position(|sum| sum.hz == hz);
+
+                    let index =
+                        if let Some(index) = index {
+                            index
+                        } else {
+                            self.times.push(ClassSum::new(hz));
+                            self.times.len() - 1
+                        };
+
+                    self.times[index].add(member);
+                }
+            }
+
+            _ => { }
+        }
+    }
+
+    /// Prints one block per class that has members.  Each block title
+    /// starts with the given title.
+
+    pub fn print(&self, title: &str, printer: &mut dyn Printer) {
+        let title = make_title(title, "Aggregate");
+
+        if self.members > 0 {
+            printer.print(&make_title(&title, "Counters"));
+            Printable::print_integer("Members", self.members as i64, printer);
+            Printable::print_integer("Count",   self.events,         printer);
+            printer.print("");
+        }
+
+        if self.integers.members > 0 {
+            let printable = self.integers.printable();
+
+            printer.print(&make_title(&title, "Integers"));
+            Printable::print_integer("Members", self.integers.members as i64, printer);
+            printable.print_common_i64(printer);
+            printable.print_common_float(printer);
+            printer.print("");
+        }
+
+        if self.floats.members > 0 {
+            let printable = self.floats.printable();
+
+            printer.print(&make_title(&title, "Floats"));
+            Printable::print_integer("Members", self.floats.members as i64, printer);
+            printable.print_common_f64(printer);
+            printable.print_common_float(printer);
+            printer.print("");
+        }
+
+        for times in self.times.iter() {
+            let printable = times.printable();
+
+            // Only add the frequency to the title if it's needed to
+            // distinguish the blocks.
+
+            let subtitle =
+                if self.times.len() == 1 {
+                    "Times".to_string()
+                } else {
+                    format!("Times ({} hz)", Printable::commas_i64(times.hz))
+                };
+
+            printer.print(&make_title(&title, &subtitle));
+            Printable::print_integer("Members", times.members as i64, printer);
+            printable.print_common_integer_times(times.hz, printer);
+            printable.print_common_float_times(times.hz, printer);
+            printer.print("");
+        }
+    }
+}
+
+impl Default for Aggregate {
+    fn default() -> Self {
+        Aggregate::new()
+    }
+}
+
+// Find the timer frequency for a time member.  The Rustics trait
+// doesn't provide the frequency, so check the time types.
+
+fn member_hz(member: &dyn Rustics) -> Option<i64> {
+    let generic = member.generic();
+
+    if let Some(member) = generic.downcast_ref::<RunningTime>() {
+        Some(member.hz())
+    } else if let Some(member) = generic.downcast_ref::<TimeWindow>() {
+        Some(member.hz())
+    } else if let Some(member) = generic.downcast_ref::<SizedTime>() {
+        Some(member.hz())
+    } else if let Some(member) = generic.downcast_ref::<Hier>() {
+        Some(member.hz() as i64)
+    } else if let Some(member) = generic.downcast_ref::<SyncRustics>() {
+        member_hz(arc_item!(member.member()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use crate::PrintOpts;
+    use crate::rc_sets::RcSet;
+    use crate::arc_sets::ArcSet;
+    use crate::rc_item_mut;
+    use crate::arc_item_mut;
+    use crate::rustics_set::RusticsSet;
+    use crate::tests::check_printer_box;
+    use crate::tests::ContinuingTimer;
+    use crate::timer_box;
+    use crate::running_integer::RunningInteger;
+    use crate::decaying::DecayingInteger;
+
+    fn test_merge() {
+        let mut aggregate = Aggregate::new();
+        let mut merged    = RunningInteger::new("Merged", &None);
+        let mut first     = RunningInteger::new("First",  &None);
+        let mut second    = RunningInteger::new("Second", &None);
+
+        for i in 1..=1000 {
+            first .record_i64(i * 3);
+            second.record_i64(i * 3 + 5000);
+            merged.record_i64(i * 3);
+            merged.record_i64(i * 3 + 5000);
+        }
+
+        aggregate.add(&first );
+        aggregate.add(&second);
+
+        let printable = aggregate.integers.printable();
+
+        assert!(aggregate.integers.members == 2);
+        assert!(printable.n       == merged.count());
+        assert!(printable.min_i64 == merged.min_i64());
+        assert!(printable.max_i64 == merged.max_i64());
+        assert!(printable.mean    == merged.mean());
+
+        assert!((printable.variance - merged.variance()).abs() < 1e-6 * merged.variance());
+        assert!((printable.kurtosis - merged.kurtosis()).abs() < 0.05);
+        assert!( printable.log_mode == merged.log_mode() as i64);
+    }
+
+    fn test_print_output() {
+        let expected =
+            [
+                "Server ==> Requests",
+                "    Count                 100 ",
+                "",
+                "Server ==> Latency",
+                "    Count                   2 ",
+                "    Minimum             1.000 microsecond",
+                "    Maximum             2.000 microseconds",
+                "    Log Mode               10 ",
+                "    Mode Value        768.000 nanoseconds",
+                "    Mean                1.500 microseconds",
+                "    Std Dev           707.107 nanoseconds",
+                "    Variance         +5.00000 e+5  ",
+                "    Skewness         +0.00000 e+0  ",
+                "    Kurtosis         +0.00000 e+0  ",
+                "  Log Histogram",
+                "  -----------------------",
+                "    0:                 0                 0                 0                 0",
+                "    4:                 0                 0                 0                 0",
+                "    8:                 0                 0                 1                 1",
+                "",
+                "Server ==> Sizes",
+                "    Count                   2 ",
+                "    Minimum                 1 ",
+                "    Maximum                 3 ",
+                "    Log Mode                0 ",
+                "    Mode Value              1 ",
+                "    Mean             +2.00000 e+0  ",
+                "    Std Dev          +1.41421 e+0  ",
+                "    Variance         +2.00000 e+0  ",
+                "    Skewness         +0.00000 e+0  ",
+                "    Kurtosis         +0.00000 e+0  ",
+                "  Log Histogram",
+                "  -----------------------",
+                "    0:                 1                 0                 1                 0",
+                "",
+                "Server ==> Aggregate ==> Counters",
+                "    Members                 1 ",
+                "    Count                 100 ",
+                "",
+                "Server ==> Aggregate ==> Integers",
+                "    Members                 1 ",
+                "    Count                   2 ",
+                "    Minimum                 1 ",
+                "    Maximum                 3 ",
+                "    Log Mode                0 ",
+                "    Mode Value              1 ",
+                "    Mean             +2.00000 e+0  ",
+                "    Std Dev          +1.41421 e+0  ",
+                "    Variance         +2.00000 e+0  ",
+                "    Skewness         +0.00000 e+0  ",
+                "    Kurtosis         +0.00000 e+0  ",
+                "",
+                "Server ==> Aggregate ==> Times",
+                "    Members                 1 ",
+                "    Count                   2 ",
+                "    Minimum             1.000 microsecond",
+                "    Maximum             2.000 microseconds",
+                "    Log Mode               10 ",
+                "    Mode Value        768.000 nanoseconds",
+                "    Mean                1.500 microseconds",
+                "    Std Dev           707.107 nanoseconds",
+                "    Variance         +5.00000 e+5  ",
+                "    Skewness         +0.00000 e+0  ",
+                "    Kurtosis         +0.00000 e+0  ",
+                ""
+            ];
+
+        let     printer    = Some(check_printer_box(&expected, true, true));
+        let     title      = None;
+        let     units      = None;
+        let     histo_opts = None;
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
+
+        let mut set        = RcSet::new("Server", 4, 0, &print_opts);
+        let     timer      = timer_box!(ContinuingTimer::new(1_000_000_000));
+        let     requests   = set.add_counter("Requests", None);
+        let     latency    = set.add_running_time("Latency", timer);
+        let     sizes      = set.add_running_integer("Sizes", None);
+
+        for _i in 1..=100 {
+            rc_item_mut!(requests).record_event();
+        }
+
+        rc_item_mut!(latency).record_time(1000);
+        rc_item_mut!(latency).record_time(2000);
+
+        rc_item_mut!(sizes).record_i64(1);
+        rc_item_mut!(sizes).record_i64(3);
+
+        assert!(!set.print_aggregates());
+        set.set_print_aggregates(true);
+        assert!(set.print_aggregates());
+
+        set.print();
+    }
+
+    // Aggregate a set that mixes running statistics with decaying
+    // ones, which keep no extremes.
+
+    fn test_mixed_set() {
+        let mut set       = RcSet::new("Mixed", 4, 0, &None);
+        let     running   = set.add_running_integer("Running", None);
+        let mut decaying  = DecayingInteger::new("Decaying", 10.0, &None);
+
+        for i in 1..=10 {
+            rc_item_mut!(running).record_i64(i);
+            decaying.record_i64(i * 1000);
+        }
+
+        set.add_member(Rc::new(RefCell::new(decaying.clone())));
+
+        let mut aggregate = Aggregate::new();
+
+        set.aggregate(&mut aggregate);
+
+        let printable = aggregate.integers.printable();
+
+        assert!(aggregate.integers.members == 1);
+        assert!(printable.n       == 10 );
+        assert!(printable.min_i64 == 1  );
+        assert!(printable.max_i64 == 10 );
+        assert!(printable.mean    == 5.5);
+
+        // A member without extremes is skipped even if it reaches
+        // the class sum.
+
+        aggregate.integers.add(&decaying);
+
+        assert!(aggregate.integers.members == 1);
+        assert!(aggregate.integers.printable().max_i64 == 10);
+    }
+
+    fn test_arc_set() {
+        let mut set     = ArcSet::new("Arc Server", 4, 1, &None);
+        let     counter = set.add_counter("Events", None);
+        let     floats  = set.add_running_float("Floats", None);
+        let     subset  = set.add_subset("Subset", 4, 0);
+        let     other   = arc_item_mut!(subset).add_running_float("Other Floats", None);
+        let     count   = arc_item_mut!(subset).add_counter("Other Events", None);
+
+        for i in 1..=10 {
+            arc_item_mut!(counter).record_event();
+            arc_item_mut!(count  ).record_event();
+            arc_item_mut!(floats ).record_f64(i as f64);
+            arc_item_mut!(other  ).record_f64(i as f64 + 10.0);
+        }
+
+        // Check that the aggregate includes the subset.
+
+        let mut aggregate = Aggregate::new();
+
+        set.aggregate(&mut aggregate);
+
+        let printable = aggregate.floats.printable();
+
+        assert!(aggregate.members == 2 );
+        assert!(aggregate.events  == 20);
+        assert!(printable.n       == 20);
+        assert!(printable.mean    == 10.5);
+        assert!(printable.min_f64 == 1.0);
+        assert!(printable.max_f64 == 20.0);
+
+        set.set_print_aggregates(true);
+        set.print();
+    }
+
+    fn test_mixed_hz() {
+        let mut aggregate = Aggregate::new();
+        let     fast      = timer_box!(ContinuingTimer::new(1_000_000_000));
+        let     slow      = timer_box!(ContinuingTimer::new(1_000_000));
+        let mut fast      = RunningTime::new("Fast", fast, &None);
+        let mut slow      = RunningTime::new("Slow", slow, &None);
+
+        fast.record_time(1000);
+        slow.record_time(1000);
+
+        aggregate.add(&fast);
+        aggregate.add(&slow);
+
+        assert!(aggregate.times.len() == 2);
+
+        let printer = crate::stdout_printer();
+        let printer = &mut *printer.borrow_mut();
+
+        aggregate.print("Mixed", printer);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_merge       ();
+        test_print_output();
+        test_mixed_set   ();
+        test_arc_set     ();
+        test_mixed_hz    ();
+    }
+}
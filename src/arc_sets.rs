@@ -15,6 +15,33 @@
 //!     * Members of an ArcSet are kept as `Arc<Mutex<...>>` instances to support
 //!       multithreaded applications.
 //!
+//!     * A panic in a Printer instance normally unwinds through the print
+//!       routines while the member locks are held, which poisons the mutexes.
+//!       set_isolate_printing() makes the print routines catch such panics
+//!       and count them instead, so that a failing output sink can't damage
+//!       the set.  The count is available via print_failures().  The panic
+//!       hook still is invoked, so the panic message is written as usual
+//!       unless the application has installed its own hook.
+//!
+//!     * The add_* methods return typed handles, like RunningTimeArc.  A
+//!       handle dereferences to the `Arc<Mutex<dyn Rustics>>` for the
+//!       member, so the arc_item_mut! macro and the other set methods work
+//!       with it directly.  The lock_typed() method gives access to the
+//!       type-specific methods, like set_units(), without downcasting.
+//!       add_typed() returns a handle for an instance created by the
+//!       application, and member_as() and find_as() recover a handle
+//!       from a plain RusticsArc or a path.
+//!       The member() method or into() returns the plain RusticsArc.
+//!
+//!     * Each member has a ClearPolicy, set via set_clear_policy().  A
+//!       member can be cleared each time the set prints it, or every n
+//!       seconds by a Reporter, or it can be kept for the lifetime of the
+//!       set even when a Reporter clears the set after each report.  Thus
+//!       interval and lifetime statistics can share one set.
+//!
+//!     * clear_class() and print_class() operate only on the members of a
+//!       given class, like "time" or "counter", in the set and its subsets.
+//!
 //! ## Example
 //!```
 //!    use std::rc::Rc;
@@ -136,7 +163,14 @@
 //!```
 
 use std::sync::Mutex;
+use std::sync::MutexGuard;
 use std::sync::Arc;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::any::Any;
+use std::cell::Cell;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
 
 use super::Rustics;
 
@@ -156,6 +190,11 @@ use super::float_hier::FloatHier;
 use super::float_hier::FloatHierConfig;
 
 use super::counter::Counter;
+use super::peak_tracker::PeakTracker;
+use super::cardinality::Cardinality;
+use super::running_ratio::RunningRatio;
+use super::correlation::Correlation;
+use super::hier::Hier;
 use super::TimerBox;
 use super::PrinterBox;
 use super::PrinterOption;
@@ -166,11 +205,95 @@ use super::parse_printer;
 use super::parse_title;
 use super::parse_units;
 use super::parse_histo_opts;
+use super::parse_fields;
 use super::make_title;
+use super::printer_mut;
+use super::printable::CsvColumn;
+use super::aggregate::Aggregate;
+use super::rustics_set::RusticsSet;
+use super::eviction::Eviction;
+use super::eviction::EvictionPolicy;
+use super::throttle::PrintThrottle;
+use super::record_hook::RecordHookBox;
+use super::time::TimerFactory;
 
 pub type RusticsArc = Arc<Mutex<dyn Rustics>>;
 pub type ArcSetBox  = Arc<Mutex<ArcSet>>;
 
+/// ArcHandle is a typed handle for a member of an ArcSet.  It
+/// dereferences to the RusticsArc for the member, and also keeps the
+/// concrete type so that type-specific methods can be used without
+/// downcasting.
+
+pub struct ArcHandle<T: Rustics + 'static> {
+    typed:   Arc<Mutex<T>>,
+    member:  RusticsArc,
+}
+
+impl<T: Rustics + 'static> ArcHandle<T> {
+    /// Creates a handle for the given instance.
+
+    pub fn new(typed: Arc<Mutex<T>>) -> ArcHandle<T> {
+        let member: RusticsArc = typed.clone();
+
+        ArcHandle { typed, member }
+    }
+
+    /// Returns the `Arc<Mutex<T>>` for the member.
+
+    pub fn typed(&self) -> Arc<Mutex<T>> {
+        self.typed.clone()
+    }
+
+    /// Locks the member and returns a guard for the concrete type.
+
+    pub fn lock_typed(&self) -> MutexGuard<'_, T> {
+        self.typed.lock().unwrap()
+    }
+
+    /// Returns the member as a plain RusticsArc.
+
+    pub fn member(&self) -> RusticsArc {
+        self.member.clone()
+    }
+}
+
+impl<T: Rustics + 'static> Clone for ArcHandle<T> {
+    fn clone(&self) -> ArcHandle<T> {
+        let typed  = self.typed.clone();
+        let member = self.member.clone();
+
+        ArcHandle { typed, member }
+    }
+}
+
+impl<T: Rustics + 'static> Deref for ArcHandle<T> {
+    type Target = RusticsArc;
+
+    fn deref(&self) -> &RusticsArc {
+        &self.member
+    }
+}
+
+impl<T: Rustics + 'static> From<ArcHandle<T>> for RusticsArc {
+    fn from(handle: ArcHandle<T>) -> RusticsArc {
+        handle.member
+    }
+}
+
+pub type RunningIntegerArc = ArcHandle<RunningInteger>;
+pub type IntegerWindowArc  = ArcHandle<IntegerWindow>;
+pub type RunningTimeArc    = ArcHandle<RunningTime>;
+pub type TimeWindowArc     = ArcHandle<TimeWindow>;
+pub type RunningFloatArc   = ArcHandle<RunningFloat>;
+pub type FloatWindowArc    = ArcHandle<FloatWindow>;
+pub type HierArc           = ArcHandle<Hier>;
+pub type CounterArc        = ArcHandle<Counter>;
+pub type PeakTrackerArc    = ArcHandle<PeakTracker>;
+pub type CardinalityArc    = ArcHandle<Cardinality>;
+pub type RunningRatioArc   = ArcHandle<RunningRatio>;
+pub type CorrelationArc    = ArcHandle<Correlation>;
+
 /// Creates a shareable instance for an ArcSet item.
 
 #[macro_export]
@@ -188,6 +311,40 @@ macro_rules! arc_item_mut { ($x:expr) => { &mut *$x.lock().unwrap() } }
 #[macro_export]
 macro_rules! arc_item { ($x:expr) => { &*$x.lock().unwrap() } }
 
+/// ClearPolicy selects when a member of an ArcSet is cleared
+/// automatically.
+///
+/// * Manual - The member is cleared only by ArcSet::clear(), including
+///   the clear done by a Reporter after each report, if enabled.  This
+///   is the default.
+///
+/// * Never - The member is kept for the lifetime of the set.  It is not
+///   cleared by a Reporter, though ArcSet::clear() still clears it.
+///
+/// * AfterPrint - The member is cleared each time the set prints it.
+///
+/// * Every(n) - The member is cleared every n seconds by a Reporter for
+///   the set.  The first period starts at the first tick after the
+///   policy is set.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ClearPolicy {
+    #[default]
+    Manual,
+    Never,
+    AfterPrint,
+    Every(u64),
+}
+
+// Keep the policy for a member along with the time of the last
+// scheduled clear, in the ticks of the Reporter timer.
+
+#[derive(Clone, Copy, Default)]
+struct ClearState {
+    policy:      ClearPolicy,
+    last_clear:  Option<i64>,
+}
+
 /// The ArcTraverser trait is used by the traverse() method to
 /// call a user-defined function for each member of an ArcSet
 /// and its subsets.
@@ -209,14 +366,23 @@ pub trait ArcTraverser {
 
 #[derive(Clone)]
 pub struct ArcSet {
-    name:       String,
-    title:      String,
-    id:         usize,
-    next_id:    usize,
-    members:    Vec<RusticsArc>,
-    subsets:    Vec<ArcSetBox>,
-    printer:    PrinterBox,
-    print_opts: PrintOption,
+    name:             String,
+    title:            String,
+    id:               usize,
+    next_id:          usize,
+    members:          Vec<RusticsArc>,
+    clear_states:     Vec<ClearState>,
+    typed_members:    Vec<Option<Rc<dyn Any>>>,
+    eviction:         Eviction,
+    subsets:          Vec<ArcSetBox>,
+    printer:          PrinterBox,
+    print_opts:       PrintOption,
+    isolate_printing: bool,
+    print_failures:   Cell<u64>,
+    aggregates:       bool,
+    throttle:         Option<Rc<PrintThrottle>>,
+    hook:             Option<RecordHookBox>,
+    collection:       Option<TimerFactory>,
 }
 
 /// This struct is passed to some constructors that create
@@ -278,7 +444,24 @@ impl ArcSet {
                 parse_title(&print_opts, &name)
             };
 
-        ArcSet { name, title, id, next_id, members, subsets, printer, print_opts }
+        let isolate_printing = false;
+        let print_failures   = Cell::new(0);
+        let aggregates       = false;
+        let throttle         = None;
+        let clear_states     = Vec::with_capacity(configuration.rustics_hint);
+        let typed_members    = Vec::with_capacity(configuration.rustics_hint);
+        let eviction         = Eviction::new(configuration.rustics_hint);
+        let hook             = None;
+        let collection       = None;
+
+        ArcSet {
+            name,           title,      id,
+            next_id,        members,    subsets,
+            printer,        print_opts, isolate_printing,
+            print_failures, aggregates, throttle,
+            clear_states,   typed_members, eviction,
+            hook,           collection
+        }
     }
 
     /// Creates a new ArcSetBox given a configuration.
@@ -325,19 +508,56 @@ impl ArcSet {
     /// desired.
 
     pub fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        self.print_filtered(printer, title, None);
+    }
+
+    /// Prints only the members of the given class, like "time" or
+    /// "counter", in the set and its subsets.  The aggregate block is
+    /// not printed.
+
+    pub fn print_class(&self, class: &str) {
+        self.print_class_opts(class, None, None);
+    }
+
+    /// Prints the members of the given class, and overrides the default
+    /// printer and title as desired.
+
+    pub fn print_class_opts(&self, class: &str, printer: PrinterOption, title: Option<&str>) {
+        self.print_filtered(printer, title, Some(class));
+    }
+
+    // Print the set, or only the members of a given class.
+
+    fn print_filtered(&self, printer: PrinterOption, title: Option<&str>, class: Option<&str>) {
+        if let Some(throttle) = &self.throttle {
+            if !throttle.allow() {
+                return;
+            }
+        }
+
         // Iterate through the Rustics instances.
 
-        for mutex in self.members.iter() {
+        for (mutex, state) in self.members.iter().zip(self.clear_states.iter()) {
             let member  = arc_item_mut!(mutex);
             let printer = printer.clone();
 
+            if let Some(class) = class {
+                if member.class() != class {
+                    continue;
+                }
+            }
+
             if let Some(title) = title {
                 let title = make_title(title, &member.name());
                 let title = Some(title.as_str());
 
-                member.print_opts(printer, title);
+                self.isolate(|| member.print_opts(printer, title));
             } else {
-                member.print_opts(printer, None);
+                self.isolate(|| member.print_opts(printer, None));
+            }
+
+            if state.policy == ClearPolicy::AfterPrint {
+                member.clear();
             }
         }
 
@@ -351,11 +571,116 @@ impl ArcSet {
                 let title = make_title(title, &subset.name());
                 let title = Some(title.as_str());
 
-                subset.print_opts(printer, title);
+                subset.print_filtered(printer, title, class);
             } else {
-                subset.print_opts(printer, None);
+                subset.print_filtered(printer, None, class);
             }
         }
+
+        // Add the aggregate block, if requested.
+
+        if self.aggregates && class.is_none() {
+            let printer_box =
+                if let Some(printer) = printer {
+                    printer
+                } else {
+                    self.printer.clone()
+                };
+
+            let title =
+                if let Some(title) = title {
+                    title
+                } else {
+                    &self.title
+                };
+
+            let mut aggregate = Aggregate::new();
+
+            self.aggregate(&mut aggregate);
+
+            let printer = printer_mut!(printer_box);
+
+            self.isolate(|| aggregate.print(title, printer));
+        }
+    }
+
+    /// Enables or disables printing an aggregate block for each class
+    /// at the end of the print output.  The aggregate covers the set
+    /// and all its subsets.  See the aggregate module.
+
+    pub fn set_print_aggregates(&mut self, aggregates: bool) {
+        self.aggregates = aggregates;
+    }
+
+    /// Returns whether the aggregate blocks are printed.
+
+    pub fn print_aggregates(&self) -> bool {
+        self.aggregates
+    }
+
+    /// Limits how often print() and print_opts() produce output for
+    /// this set.  Prints arriving within the throttle interval of the
+    /// last print are dropped.  Clones of the set share the throttle.
+    /// See the throttle module.
+
+    pub fn set_print_throttle(&mut self, throttle: PrintThrottle) {
+        self.throttle = Some(Rc::new(throttle));
+    }
+
+    /// Returns the print throttle, if one is set.
+
+    pub fn print_throttle(&self) -> Option<&PrintThrottle> {
+        self.throttle.as_deref()
+    }
+
+    /// Enables or disables catching panics raised while printing.  The
+    /// setting is applied to all subsets, and is inherited by subsets
+    /// created later.
+
+    pub fn set_isolate_printing(&mut self, isolate: bool) {
+        self.isolate_printing = isolate;
+
+        for mutex in self.subsets.iter() {
+            let subset = arc_item_mut!(mutex);
+
+            subset.set_isolate_printing(isolate);
+        }
+    }
+
+    /// Returns whether panics during printing are being caught.
+
+    pub fn isolate_printing(&self) -> bool {
+        self.isolate_printing
+    }
+
+    /// Returns the number of print operations that have panicked in
+    /// this set and its subsets while isolation was enabled.
+
+    pub fn print_failures(&self) -> u64 {
+        let mut failures = self.print_failures.get();
+
+        for mutex in self.subsets.iter() {
+            let subset = arc_item!(mutex);
+
+            failures += subset.print_failures();
+        }
+
+        failures
+    }
+
+    // Run a print operation.  When isolation is enabled, a panic is
+    // caught before it can unwind through any of the member locks, so
+    // the mutexes aren't poisoned.
+
+    fn isolate<F: FnOnce()>(&self, operation: F) {
+        if !self.isolate_printing {
+            operation();
+            return;
+        }
+
+        if catch_unwind(AssertUnwindSafe(operation)).is_err() {
+            self.print_failures.set(self.print_failures.get() + 1);
+        }
     }
 
     /// Returns the current title.
@@ -384,6 +709,61 @@ impl ArcSet {
         }
     }
 
+    /// Installs a hook that receives the samples recorded into the
+    /// members of the set and its subsets, or removes the hook if None
+    /// is given.  Members that don't support hooks are skipped.  The
+    /// hook also is installed in members and subsets added later.  See
+    /// the record_hook module.  This takes each member lock, so don't
+    /// call it while holding one.
+
+    pub fn set_record_hook(&mut self, hook: Option<RecordHookBox>) {
+        for mutex in self.subsets.iter() {
+            let subset = arc_item_mut!(mutex);
+
+            subset.set_record_hook(hook.clone());
+        }
+
+        for mutex in self.members.iter() {
+            let member = arc_item_mut!(mutex);
+
+            if member.supports_record_hook() {
+                member.set_record_hook(hook.clone());
+            }
+        }
+
+        self.hook = hook;
+    }
+
+    /// Returns the hook for the set, if any.
+
+    pub fn record_hook(&self) -> Option<RecordHookBox> {
+        self.hook.clone()
+    }
+
+    /// Attaches a collection clock made by the given factory to each
+    /// member of the set and its subsets, or removes the clocks if None
+    /// is given.  Members that don't support a collection clock are
+    /// skipped.  Members and subsets added later get clocks, too.  See
+    /// the collection_time module.
+
+    pub fn set_collection_clocks(&mut self, factory: Option<TimerFactory>) {
+        for mutex in self.subsets.iter() {
+            let subset = arc_item_mut!(mutex);
+
+            subset.set_collection_clocks(factory);
+        }
+
+        for mutex in self.members.iter() {
+            let member = arc_item_mut!(mutex);
+
+            if member.supports_collection_time() {
+                member.set_collection_clock(factory.map(|factory| factory()));
+            }
+        }
+
+        self.collection = factory;
+    }
+
     /// Does a recursive clear of all Rustics instances in the set
     /// and its entire subset hierarchy.
 
@@ -401,66 +781,271 @@ impl ArcSet {
         }
     }
 
+    /// Clears the members of the given class, like "time", in the set
+    /// and its subsets.  For example, the latency statistics can be
+    /// reset after a warmup period while the counters keep their
+    /// totals.
+
+    pub fn clear_class(&mut self, class: &str) {
+        for mutex in self.subsets.iter() {
+            let subset = arc_item_mut!(mutex);
+
+            subset.clear_class(class);
+        }
+
+        for mutex in self.members.iter() {
+            let member = arc_item_mut!(mutex);
+
+            if member.class() == class {
+                member.clear();
+            }
+        }
+    }
+
+    /// Clears the members and subsets as at the end of a reporting
+    /// interval.  Members with the Manual policy are cleared, and the
+    /// other members are left as they are.  Reporter uses this method
+    /// when it is set to clear the set after each report.
+
+    pub fn clear_interval(&mut self) {
+        for mutex in self.subsets.iter() {
+            let subset = arc_item_mut!(mutex);
+
+            subset.clear_interval();
+        }
+
+        for (mutex, state) in self.members.iter().zip(self.clear_states.iter()) {
+            if state.policy == ClearPolicy::Manual {
+                arc_item_mut!(mutex).clear();
+            }
+        }
+    }
+
+    /// Clears the members with an Every(n) policy whose period has
+    /// ended.  The current time is given in ticks of a clock with the
+    /// given frequency.  Reporter invokes this method on each tick.
+
+    pub fn clear_due(&mut self, now: i64, hz: u128) {
+        for mutex in self.subsets.iter() {
+            let subset = arc_item_mut!(mutex);
+
+            subset.clear_due(now, hz);
+        }
+
+        for (mutex, state) in self.members.iter().zip(self.clear_states.iter_mut()) {
+            let ClearPolicy::Every(seconds) = state.policy else {
+                continue;
+            };
+
+            let period = (seconds as u128 * hz) as i64;
+
+            match state.last_clear {
+                Some(last_clear) if now - last_clear >= period => {
+                    arc_item_mut!(mutex).clear();
+                    state.last_clear = Some(now);
+                }
+
+                Some(_) => { }
+                None    => { state.last_clear = Some(now); }
+            }
+        }
+    }
+
+    /// Sets the clear policy for a member of the set.  Returns false
+    /// if the member isn't in the set.
+
+    pub fn set_clear_policy(&mut self, target: &RusticsArc, policy: ClearPolicy) -> bool {
+        match self.members.iter().position(|member| Arc::ptr_eq(member, target)) {
+            Some(i) => {
+                self.clear_states[i] = ClearState { policy, last_clear: None };
+                true
+            }
+
+            None => false
+        }
+    }
+
+    /// Returns the clear policy for a member of the set, if the member
+    /// is in the set.
+
+    pub fn clear_policy(&self, target: &RusticsArc) -> Option<ClearPolicy> {
+        let i = self.members.iter().position(|member| Arc::ptr_eq(member, target))?;
+
+        Some(self.clear_states[i].policy)
+    }
+
     /// Adds a RusticsArc instance to a set.  The user creates the
     /// Rustics instance and passes it in a box.  This is a bit more
     /// manual than add_running_integer() and similar methods.
 
     pub fn add_member(&mut self, member: RusticsArc) {
-        let work  = member.clone();
-        let stat  = arc_item_mut!(work);
-        let title = make_title(&self.title, &stat.name());
+        {
+            let stat  = arc_item_mut!(member);
+            let title = make_title(&self.title, &stat.name());
 
-        stat.set_title(&title);
-        stat.set_id(self.next_id);
-        self.next_id += 1;
+            stat.set_title(&title);
+            stat.set_id(self.next_id);
+            self.next_id += 1;
+
+            if self.hook.is_some() && stat.supports_record_hook() {
+                stat.set_record_hook(self.hook.clone());
+            }
+
+            if let Some(factory) = self.collection {
+                if stat.supports_collection_time() {
+                    stat.set_collection_clock(Some(factory()));
+                }
+            }
+        }
+
+        // The member lock must be released before evicting.
 
         self.members.push(member);
+        self.clear_states.push(ClearState::default());
+        self.typed_members.push(None);
+        self.eviction.push();
+        self.evict(true);
+    }
+
+    /// Limits the number of Rustics instances in the set.  When adding
+    /// a member would exceed the capacity, a member chosen by the policy
+    /// is removed.  If the set already is over the new capacity, members
+    /// are evicted immediately.  None removes the limit.  See the
+    /// eviction module.
+
+    pub fn set_capacity(&mut self, capacity: Option<usize>, policy: EvictionPolicy) {
+        self.eviction.set_capacity(capacity, policy);
+        self.evict(false);
+    }
+
+    /// Returns the capacity of the set, if any.
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.eviction.capacity()
+    }
+
+    /// Returns the number of members evicted from the set.
+
+    pub fn evictions(&self) -> u64 {
+        self.eviction.evictions()
+    }
+
+    /// Sets the eviction priority of a member.  Members with the lowest
+    /// priority are evicted first under EvictionPolicy::Priority.
+    /// Returns false if the member isn't in the set.
+
+    pub fn set_priority(&mut self, target: &RusticsArc, priority: i64) -> bool {
+        match self.members.iter().position(|member| Arc::ptr_eq(member, target)) {
+            Some(i) => {
+                self.eviction.set_priority(i, priority);
+                true
+            }
+
+            None => false
+        }
+    }
+
+    // Remove members until the set is within its capacity.  The
+    // members are locked to get their counts, so the caller must not
+    // hold any member locks.
+
+    fn evict(&mut self, protect_last: bool) {
+        while self.eviction.is_over(self.members.len()) {
+            let counts: Vec<u64> = self.members.iter().map(|mutex| arc_item!(mutex).count()).collect();
+
+            match self.eviction.victim(&counts, protect_last) {
+                Some(i) => { self.remove_index(i); }
+                None    => { break;                }
+            }
+        }
+    }
+
+    // Remove the member at the given index along with its state.
+
+    fn remove_index(&mut self, i: usize) {
+        self.members.remove(i);
+        self.clear_states.remove(i);
+        self.typed_members.remove(i);
+        self.eviction.remove(i);
+    }
+
+    /// Adds a Rustics instance to the set and returns a typed handle
+    /// for it.  This is the typed form of add_member(), for instances
+    /// that the application creates itself.
+
+    pub fn add_typed<T: Rustics + 'static>(&mut self, member: T) -> ArcHandle<T> {
+        let handle = ArcHandle::new(arc_box!(member));
+        let typed  = Rc::new(handle.typed()) as Rc<dyn Any>;
+
+        self.add_member(handle.member());
+        *self.typed_members.last_mut().unwrap() = Some(typed);
+        handle
+    }
+
+    /// Returns a typed handle for a member of the set.  The result is
+    /// None if the member isn't in the set, if it isn't of type T, or
+    /// if it was added with add_member(), which doesn't keep the type.
+
+    pub fn member_as<T: Rustics + 'static>(&self, target: &RusticsArc) -> Option<ArcHandle<T>> {
+        let i     = self.members.iter().position(|member| Arc::ptr_eq(member, target))?;
+        let typed = self.typed_members[i].as_ref()?.downcast_ref::<Arc<Mutex<T>>>()?;
+
+        Some(ArcHandle::new(typed.clone()))
+    }
+
+    /// Finds a member given a path, as find() does, and returns a typed
+    /// handle for it.  See member_as().
+
+    pub fn find_as<T: Rustics + 'static>(&self, path: &str) -> Option<ArcHandle<T>> {
+        let member = self.find(path)?;
+
+        match path.rsplit_once("==>") {
+            Some((subset_path, _)) => {
+                let subset = self.find_subset(subset_path)?;
+                let subset = arc_item!(subset);
+
+                subset.member_as(&member)
+            }
+
+            None => self.member_as(&member)
+        }
     }
 
     /// Creates a RunningInteger instance and adds it to the set.
 
-    pub fn add_running_integer(&mut self, name: &str, units: UnitsOption) -> RusticsArc {
+    pub fn add_running_integer(&mut self, name: &str, units: UnitsOption) -> RunningIntegerArc {
         let mut member = RunningInteger::new(name, &self.print_opts);
 
         if let Some(units) = units {
             member.set_units(units);
         }
 
-        let member = arc_box!(member);
-
-        self.add_member(member.clone());
-        member
+        self.add_typed(member)
     }
 
     /// Creates an IntegerWindow instance and adds it to the set.
 
     pub fn add_integer_window(&mut self, name: &str, window_size: usize, units: UnitsOption)
-            -> RusticsArc {
+            -> IntegerWindowArc {
         let mut member = IntegerWindow::new(name, window_size, &self.print_opts);
 
         if let Some(units) = units {
             member.set_units(units);
         }
 
-        let member = arc_box!(member);
-
-        self.add_member(member.clone());
-        member
+        self.add_typed(member)
     }
 
     /// Creates a Hier using RunningInteger as the base type and adds it to the set.
 
-    pub fn add_integer_hier(&mut self, mut configuration: IntegerHierConfig) -> RusticsArc {
+    pub fn add_integer_hier(&mut self, mut configuration: IntegerHierConfig) -> HierArc {
         let print_opts =
             self.make_print_opts(&configuration.name, &configuration.print_opts);
 
         configuration.print_opts = print_opts;
 
         let member = IntegerHier::new_hier(configuration);
-        let member = arc_box!(member);
-
-        self.add_member(member.clone());
-        member
+        self.add_typed(member)
     }
 
     /// Creates a RunningTime instance and adds it to the set.  The user
@@ -468,100 +1053,124 @@ impl ArcSet {
     /// method and is queried by the print routines to determine the hertz
     /// for the samples.
 
-    pub fn add_running_time(&mut self, name: &str, timer: TimerBox) -> RusticsArc {
+    pub fn add_running_time(&mut self, name: &str, timer: TimerBox) -> RunningTimeArc {
         let member = RunningTime::new(name, timer, &self.print_opts);
-        let member = arc_box!(member);
-
-        self.add_member(member.clone());
-        member
+        self.add_typed(member)
     }
 
     /// Creates a TimeWindow instance and adds it to the set.
 
     pub fn add_time_window(&mut self, name: &str, window_size: usize, timer: TimerBox)
-            -> RusticsArc {
+            -> TimeWindowArc {
         let member = TimeWindow::new(name, window_size, timer, &self.print_opts);
-        let member = arc_box!(member);
-
-        self.add_member(member.clone());
-        member
+        self.add_typed(member)
     }
 
     /// Creates a Hier using RunningTime as the base type and adds it to the set.
 
-    pub fn add_time_hier(&mut self, mut configuration: TimeHierConfig) -> RusticsArc {
+    pub fn add_time_hier(&mut self, mut configuration: TimeHierConfig) -> HierArc {
         let print_opts =
             self.make_print_opts(&configuration.name, &configuration.print_opts);
 
         configuration.print_opts = print_opts;
 
         let member = TimeHier::new_hier(configuration);
-        let member = arc_box!(member);
-
-        self.add_member(member.clone());
-        member
+        self.add_typed(member)
     }
 
     /// Creates a RunningFloat instance and adds it to the set.
 
-    pub fn add_running_float(&mut self, name: &str, units: UnitsOption) -> RusticsArc {
+    pub fn add_running_float(&mut self, name: &str, units: UnitsOption) -> RunningFloatArc {
         let mut member = RunningFloat::new(name, &self.print_opts);
 
         if let Some(units) = units {
             member.set_units(units);
         }
 
-        let member = arc_box!(member);
-
-        self.add_member(member.clone());
-        member
+        self.add_typed(member)
     }
 
     /// Creates a FloatWindow instance and adds it to the set.
 
     pub fn add_float_window(&mut self, name: &str, window_size: usize, units: UnitsOption)
-            -> RusticsArc {
+            -> FloatWindowArc {
         let mut member = FloatWindow::new(name, window_size, &self.print_opts);
 
         if let Some(units) = units {
             member.set_units(units);
         }
 
-        let member = arc_box!(member);
-
-        self.add_member(member.clone());
-        member
+        self.add_typed(member)
     }
 
     /// Creates a Hier using RunningFloat as the base type and adds it to the set.
 
-    pub fn add_float_hier(&mut self, mut configuration: FloatHierConfig) -> RusticsArc {
+    pub fn add_float_hier(&mut self, mut configuration: FloatHierConfig) -> HierArc {
         let print_opts =
             self.make_print_opts(&configuration.name, &configuration.print_opts);
 
         configuration.print_opts = print_opts;
 
         let member = FloatHier::new_hier(configuration);
-        let member = arc_box!(member);
-
-        self.add_member(member.clone());
-        member
+        self.add_typed(member)
     }
 
     /// Creates a Counter instance and adds it to the set.
 
-    pub fn add_counter(&mut self, name: &str, units: UnitsOption) -> RusticsArc {
+    pub fn add_counter(&mut self, name: &str, units: UnitsOption) -> CounterArc {
         let printer    = Some(self.printer.clone());
         let title      = None;
         let histo_opts = None;
+        let fields     = None;
 
-        let print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let print_opts = Some(PrintOpts { printer, title, units, histo_opts, fields });
 
         let member = Counter::new(name, &print_opts);
-        let member = arc_box!(member);
+        self.add_typed(member)
+    }
+
+    /// Creates a PeakTracker instance and adds it to the set.
+
+    pub fn add_peak_tracker(&mut self, name: &str, history_size: usize, units: UnitsOption)
+            -> PeakTrackerArc {
+        let printer    = Some(self.printer.clone());
+        let title      = None;
+        let histo_opts = None;
+        let fields     = None;
+
+        let print_opts = Some(PrintOpts { printer, title, units, histo_opts, fields });
 
-        self.add_member(member.clone());
-        member
+        let member = PeakTracker::new(name, history_size, &print_opts);
+        self.add_typed(member)
+    }
+
+    /// Creates a Cardinality instance and adds it to the set.
+
+    pub fn add_cardinality(&mut self, name: &str, units: UnitsOption) -> CardinalityArc {
+        let mut member = Cardinality::new(name, &self.print_opts);
+
+        if let Some(units) = units {
+            member.set_units(units);
+        }
+
+        self.add_typed(member)
+    }
+
+    /// Creates a RunningRatio instance and adds it to the set.
+    /// Record a success with record_i64(1) and a failure with
+    /// record_i64(0).
+
+    pub fn add_running_ratio(&mut self, name: &str) -> RunningRatioArc {
+        let member = RunningRatio::new(name, &self.print_opts);
+        self.add_typed(member)
+    }
+
+    /// Creates a Correlation instance and adds it to the set.  Record
+    /// pairs via the typed handle, e.g., handle.lock_typed().record_pair().
+
+    pub fn add_correlation(&mut self, name: &str) -> CorrelationArc {
+        let member = Correlation::new(name, &self.print_opts);
+        self.add_typed(member)
     }
 
     // Merge the input print_ops with the title that we generate and the printer
@@ -572,7 +1181,8 @@ impl ArcSet {
         let title      = Some(make_title(&self.title, name));
         let units      = Some(parse_units(print_opts));
         let histo_opts = Some(parse_histo_opts(print_opts));
-        let print_opts = PrintOpts { printer, title, units, histo_opts };
+        let fields     = Some(parse_fields(print_opts));
+        let print_opts = PrintOpts { printer, title, units, histo_opts, fields };
 
         Some(print_opts)
     }
@@ -602,7 +1212,7 @@ impl ArcSet {
         }
 
         if found {
-            self.members.remove(i);
+            self.remove_index(i);
         }
 
         found
@@ -622,6 +1232,10 @@ impl ArcSet {
 
         let subset = ArcSet::new_box_from_config(configuration);
 
+        arc_item_mut!(subset).isolate_printing = self.isolate_printing;
+        arc_item_mut!(subset).set_record_hook(self.hook.clone());
+        arc_item_mut!(subset).set_collection_clocks(self.collection);
+
         self.next_id += 1;
         self.subsets.push(subset.clone());
         subset
@@ -658,6 +1272,56 @@ impl ArcSet {
         found
     }
 
+    /// Finds a member given a path of names relative to this set,
+    /// for example "Network ==> Query Latency".  Each name but the
+    /// last selects a subset, and the last name selects a Rustics
+    /// instance.  If there are duplicate names, the first match is
+    /// used.
+    ///
+    /// The members and subsets along the path are locked during the
+    /// search, so the caller must not hold any of those locks.
+
+    pub fn find(&self, path: &str) -> Option<RusticsArc> {
+        match path.rsplit_once("==>") {
+            Some((subset_path, name)) => {
+                let subset = self.find_subset(subset_path)?;
+                let subset = arc_item!(subset);
+
+                subset.find_member(name.trim())
+            }
+
+            None => self.find_member(path.trim())
+        }
+    }
+
+    /// Finds a subset given a path of subset names relative to this
+    /// set, for example "Network ==> Server".
+
+    pub fn find_subset(&self, path: &str) -> Option<ArcSetBox> {
+        let mut names   = path.split("==>").map(|name| name.trim());
+        let mut current = self.find_child(names.next()?)?;
+
+        for name in names {
+            let next = arc_item!(current).find_child(name)?;
+
+            current = next;
+        }
+
+        Some(current)
+    }
+
+    // Find a Rustics instance in this set by name.
+
+    fn find_member(&self, name: &str) -> Option<RusticsArc> {
+        self.members.iter().find(|mutex| arc_item!(mutex).name() == name).cloned()
+    }
+
+    // Find a subset of this set by name.
+
+    fn find_child(&self, name: &str) -> Option<ArcSetBox> {
+        self.subsets.iter().find(|mutex| arc_item!(mutex).name() == name).cloned()
+    }
+
     // The following method is for internal use only.
 
     fn id(&self) -> usize {
@@ -665,9 +1329,55 @@ impl ArcSet {
     }
 }
 
+impl RusticsSet for ArcSet {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn printer(&self) -> PrinterBox {
+        self.printer.clone()
+    }
+
+    fn visit_members(&self, visitor: &mut dyn FnMut(&dyn Rustics)) {
+        for mutex in self.members.iter() {
+            visitor(arc_item!(mutex));
+        }
+    }
+
+    fn visit_subsets(&self, visitor: &mut dyn FnMut(&dyn RusticsSet)) {
+        for mutex in self.subsets.iter() {
+            visitor(arc_item!(mutex));
+        }
+    }
+
+    // Print each row under the isolation setting for the set.
+
+    fn print_csv(&self, printer: PrinterOption, columns: &[CsvColumn]) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let printer = printer_mut!(printer_box);
+
+        for row in self.export_csv(columns).iter() {
+            self.isolate(|| printer.print(row));
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::time::Instant;
+    use std::any::Any;
+    use std::rc::Rc;
+    use std::cell::RefCell;
 
     use super::*;
     use crate::tests::TestTimer;
@@ -680,9 +1390,15 @@ pub mod tests {
     use crate::hier::Hier;
     use crate::time::Timer;
     use crate::time::DurationTimer;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
     use crate::hier::HierDescriptor;
     use crate::hier::HierDimension;
     use crate::stdout_printer;
+    use crate::printer_box;
+    use crate::Printer;
+    use crate::capture_printer::CapturePrinter;
+    use crate::printable::Printable;
 
     struct TestTraverser {
         pub members:  i64,
@@ -939,18 +1655,18 @@ pub mod tests {
         //
         // First, do the remove operations.
 
-        let found = set.remove_stat(window_mutex.clone());
+        let found = set.remove_stat(window_mutex.member());
         assert!(found);
 
-        let found = set.remove_stat(running_mutex.clone());
+        let found = set.remove_stat(running_mutex.member());
         assert!(found);
 
         // Now check that the stats went away
 
-        let found = set.remove_stat(window_mutex);
+        let found = set.remove_stat(window_mutex.into());
         assert!(!found);
 
-        let found = set.remove_stat(running_mutex);
+        let found = set.remove_stat(running_mutex.into());
         assert!(!found);
     }
 
@@ -1292,7 +2008,7 @@ pub mod tests {
         let histo_opts = None;
         let units      = bytes();
 
-        Some(PrintOpts { printer, title, histo_opts, units })
+        Some(PrintOpts { printer, title, histo_opts, units, ..Default::default() })
     }
 
     fn test_printing() {
@@ -1674,6 +2390,368 @@ pub mod tests {
         assert!(check_printer_count_match(printer.clone()));
     }
 
+    fn test_csv() {
+        let set     = ArcSet::new_box("top", 1, 1, &None);
+        let set     = arc_item_mut!(set);
+        let running  = set.add_running_integer("running", None);
+        let distinct = set.add_cardinality("distinct", None);
+        let subset   = set.add_subset("subset", 1, 0);
+        let window   = arc_item_mut!(subset).add_integer_window("window", 10, None);
+
+        for i in 1..=4 {
+            arc_item_mut!(running ).record_i64(i);
+            arc_item_mut!(distinct).record_i64(i % 2);
+            arc_item_mut!(window  ).record_i64(-i);
+        }
+
+        let columns  = [ CsvColumn::Title, CsvColumn::Count, CsvColumn::Min, CsvColumn::Max ];
+        let expected =
+            [
+                "title,count,min,max",
+                "top ==> running,4,1,4",
+                "top ==> distinct,2,,",
+                "top ==> subset ==> window,4,-4,-1"
+            ];
+
+        let rows = set.export_csv(&columns);
+
+        assert!(rows == expected);
+
+        let printer = check_printer_box(&expected, true, false);
+
+        set.print_csv(Some(printer.clone()), &columns);
+        assert!(check_printer_count_match(printer));
+    }
+
+    // This printer panics on every line to test isolate_printing().
+
+    struct FailingPrinter {
+    }
+
+    impl Printer for FailingPrinter {
+        fn print(&mut self, _output: &str) {
+            panic!("FailingPrinter::print:  failed");
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    fn test_isolate_printing() {
+        let set      = ArcSet::new_box("top", 1, 1, &None);
+        let set      = arc_item_mut!(set);
+        let running  = set.add_running_integer("running", None);
+        let subset   = set.add_subset("subset", 1, 0);
+        let window   = arc_item_mut!(subset).add_integer_window("window", 10, None);
+        let printer  = printer_box!(FailingPrinter { });
+        let printer  = Some(printer as PrinterBox);
+        let columns  = Printable::csv_default_columns();
+
+        arc_item_mut!(running).record_i64(1);
+
+        assert!(!set.isolate_printing());
+
+        set.set_isolate_printing(true);
+
+        assert!(set.isolate_printing());
+        assert!(arc_item!(subset).isolate_printing());
+        assert!(set.add_subset("late", 0, 0).lock().unwrap().isolate_printing());
+
+        // Each member print panics and gets counted.
+
+        set.print_opts(printer.clone(), None);
+
+        assert!(set.print_failures() == 2);
+        assert!(arc_item!(subset).print_failures() == 1);
+
+        set.print_csv(printer, &columns);
+
+        assert!(set.print_failures() == 5);
+
+        // The members still are usable.
+
+        assert!(!running.is_poisoned());
+        assert!(!window .is_poisoned());
+
+        arc_item_mut!(running).record_i64(2);
+        arc_item_mut!(window ).record_i64(2);
+
+        assert!(arc_item!(running).count() == 2);
+        assert!(arc_item!(window ).count() == 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_no_isolation() {
+        let mut set     = ArcSet::new("top", 1, 0, &None);
+        let     _member = set.add_running_integer("running", None);
+        let     printer = printer_box!(FailingPrinter { });
+
+        set.print_opts(Some(printer as PrinterBox), None);
+    }
+
+    fn test_throttle() {
+        let expected =
+            [
+                "Top ==> Events",
+                "    Count                   1 ",
+                "",
+                "Top ==> Events",
+                "    Count                   3 ",
+                ""
+            ];
+
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+        let printer   = check_printer_box(&expected, true, false);
+        let set       = ArcSet::new_box("Top", 1, 0, &None);
+        let set       = arc_item_mut!(set);
+        let events    = set.add_counter("Events", None);
+
+        assert!(set.print_throttle().is_none());
+
+        set.set_print_throttle(PrintThrottle::new(1000, timer));
+
+        // Only the first and the last prints get through.
+
+        for _i in 0..3 {
+            arc_item_mut!(events).record_event();
+            set.print_opts(Some(printer.clone()), None);
+            clock.borrow_mut().current += 600;
+        }
+
+        let throttle = set.print_throttle().unwrap();
+
+        assert!(throttle.allowed()    == 2);
+        assert!(throttle.suppressed() == 1);
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+    }
+
+    fn test_ratio() {
+        let set   = ArcSet::new_box("Top", 1, 0, &None);
+        let ratio = arc_item_mut!(set).add_running_ratio("Hits");
+
+        for i in 0..10 {
+            arc_item_mut!(ratio).record_i64(i % 2);
+        }
+
+        let ratio = arc_item!(ratio);
+
+        assert!(ratio.class() == "ratio");
+        assert!(ratio.count() == 10     );
+        assert!(ratio.mean()  == 0.5    );
+        assert!(ratio.title() == "Top ==> Hits");
+    }
+
+    fn test_find() {
+        let mut set     = ArcSet::new("Top", 1, 1, &None);
+        let     latency = set.add_running_integer("Latency", None);
+        let     network = set.add_subset("Network", 1, 1);
+        let     server  = arc_item_mut!(network).add_subset("Server", 1, 0);
+        let     query   = arc_item_mut!(network).add_running_integer("Query Latency", None);
+        let     bytes   = arc_item_mut!(server).add_counter("Bytes", None);
+
+        assert!(Arc::ptr_eq(&set.find("Latency").unwrap(), &latency));
+        assert!(Arc::ptr_eq(&set.find("Network ==> Query Latency").unwrap(), &query));
+        assert!(Arc::ptr_eq(&set.find("Network ==> Server ==> Bytes").unwrap(), &bytes));
+        assert!(Arc::ptr_eq(&set.find_subset("Network").unwrap(), &network));
+        assert!(Arc::ptr_eq(&set.find_subset("Network ==> Server").unwrap(), &server));
+
+        // The spacing around the separators doesn't matter.
+
+        assert!(Arc::ptr_eq(&set.find("Network==>Server==>Bytes").unwrap(), &bytes));
+
+        assert!(set.find("Query Latency"           ).is_none());
+        assert!(set.find("Network ==> Latency"     ).is_none());
+        assert!(set.find("Server ==> Bytes"        ).is_none());
+        assert!(set.find("Network"                 ).is_none());
+        assert!(set.find_subset("Latency"          ).is_none());
+        assert!(set.find_subset("Network ==> Bytes").is_none());
+
+        // The found member is the same instance.
+
+        arc_item_mut!(set.find("Network ==> Query Latency").unwrap()).record_i64(42);
+
+        assert!(arc_item!(query).max_i64() == 42);
+    }
+
+    fn test_class() {
+        let     capture  = Rc::new(RefCell::new(CapturePrinter::new()));
+        let     printer  = Some(capture.clone() as PrinterBox);
+        let mut set      = ArcSet::new("Top", 2, 1, &None);
+        let     latency  = set.add_running_time("Latency", continuing_box());
+        let     requests = set.add_counter("Requests", None);
+        let     network  = set.add_subset("Network", 2, 0);
+        let     query    = arc_item_mut!(network).add_running_time("Query", continuing_box());
+        let     bytes    = arc_item_mut!(network).add_counter("Bytes", None);
+
+        for member in [ &latency, &query ] {
+            arc_item_mut!(member).record_time(100);
+        }
+
+        for member in [ &requests, &bytes ] {
+            arc_item_mut!(member).record_i64(10);
+        }
+
+        set.set_print_aggregates(true);
+
+        // Print only the counters.  The aggregate block is skipped.
+
+        set.print_class_opts("counter", printer.clone(), None);
+
+        let lines = capture.borrow_mut().take_lines();
+        let names = lines.iter().filter(|line| line.starts_with("Top ==> "));
+        let names = names.cloned().collect::<Vec<String>>();
+
+        assert!(names == [ "Top ==> Requests", "Top ==> Network ==> Bytes" ]);
+
+        // Reset the latency statistics after a warmup, and keep the
+        // counters.
+
+        set.clear_class("time");
+
+        assert!(arc_item!(latency ).count() == 0 );
+        assert!(arc_item!(query   ).count() == 0 );
+        assert!(arc_item!(requests).count() == 10);
+        assert!(arc_item!(bytes   ).count() == 10);
+
+        // An unknown class matches nothing.
+
+        set.clear_class("none");
+        set.print_class_opts("none", printer.clone(), None);
+
+        assert!(capture.borrow().is_empty());
+        assert!(arc_item!(bytes).count() == 10);
+    }
+
+    fn test_typed_handles() {
+        let mut set   = ArcSet::new("Top", 2, 0, &None);
+        let     sizes = set.add_running_integer("Sizes", None);
+        let     ratio = set.add_running_ratio("Hits");
+
+        // The type-specific methods are available without a downcast.
+
+        sizes.lock_typed().set_units(bytes().unwrap());
+        ratio.lock_typed().record_success();
+        ratio.lock_typed().record_failure();
+
+        assert!(ratio.lock_typed().successes() == 1);
+
+        // The handle also works as a RusticsArc.
+
+        arc_item_mut!(sizes).record_i64(100);
+
+        assert!(arc_item!(sizes).count() == 1);
+        assert!(arc_item!(ratio).count() == 2);
+
+        let member: RusticsArc = sizes.clone().into();
+
+        assert!(Arc::ptr_eq(&member, &sizes.member()));
+        assert!(Arc::ptr_eq(&set.find("Sizes").unwrap(), &member));
+        assert!(sizes.typed().lock().unwrap().max_i64() == 100);
+
+        assert!(set.remove_stat(member));
+        assert!(set.find("Sizes").is_none());
+
+        let peaks = set.add_peak_tracker("Peaks", 4, None);
+
+        peaks.lock_typed().record_i64(10);
+        peaks.lock_typed().tick();
+
+        assert!(peaks.lock_typed().peaks() == vec![ Some(10) ]);
+        assert!(set.find("Peaks").is_some());
+
+        // Recover a typed handle from a plain RusticsArc.
+
+        let member = set.find("Hits").unwrap();
+        let typed  = set.member_as::<RunningRatio>(&member).unwrap();
+
+        assert!(typed.lock_typed().successes() == 1);
+        assert!(set.member_as::<Counter>(&member).is_none());
+
+        // add_typed() works for instances the application creates.
+
+        let subset  = set.add_subset("Subset", 1, 0);
+        let counter = arc_item_mut!(subset).add_typed(Counter::new("Events", &None));
+
+        counter.lock_typed().record_i64(5);
+
+        let found = set.find_as::<Counter>("Subset ==> Events").unwrap();
+
+        assert!(found.lock_typed().count() == 5);
+        assert!(arc_item!(found).title() == "Top ==> Subset ==> Events");
+        assert!(set.find_as::<RunningInteger>("Subset ==> Events").is_none());
+
+        // add_member() doesn't keep the type.
+
+        let untyped: RusticsArc = arc_box!(Counter::new("Untyped", &None));
+
+        set.add_member(untyped.clone());
+
+        assert!(set.member_as::<Counter>(&untyped).is_none());
+    }
+
+    fn test_eviction() {
+        let mut set    = ArcSet::new("Top", 4, 0, &None);
+        let     first  = set.add_counter("First",  None);
+        let     second = set.add_counter("Second", None);
+        let     _third = set.add_counter("Third",  None);
+
+        assert!(set.capacity().is_none());
+
+        // Shrinking the capacity evicts immediately.  First has been used
+        // most recently, so the others go.
+
+        arc_item_mut!(first).record_event();
+        set.set_capacity(Some(1), EvictionPolicy::LeastRecent);
+
+        assert!(set.capacity()     == Some(1));
+        assert!(set.evictions()    == 2      );
+        assert!(set.member_count() == 1      );
+        assert!(set.find("First").is_some());
+        assert!(!set.set_priority(&second, 1));
+
+        // A new member is never the victim.
+
+        let fourth = set.add_counter("Fourth", None);
+
+        assert!(set.find("First" ).is_none());
+        assert!(set.find("Fourth").is_some());
+
+        // Under the priority policy, the lowest priority goes first.
+
+        set.set_capacity(Some(2), EvictionPolicy::Priority);
+
+        assert!(set.set_priority(&fourth, 10));
+
+        let _fifth = set.add_counter("Fifth", None);
+        let _sixth = set.add_counter("Sixth", None);
+
+        assert!(set.member_count() == 2);
+        assert!(set.evictions()    == 4);
+        assert!(set.find("Fourth").is_some());
+        assert!(set.find("Fifth" ).is_none());
+        assert!(set.find("Sixth" ).is_some());
+
+        // Removing the limit stops evictions.
+
+        set.set_capacity(None, EvictionPolicy::LeastRecent);
+
+        let _seventh = set.add_counter("Seventh", None);
+
+        assert!(set.member_count() == 3);
+    }
+
     #[test]
     pub fn run_tests() {
         simple_test  ();
@@ -1681,5 +2759,15 @@ pub mod tests {
         documentation();
         test_hier    ();
         test_printing();
+        test_csv     ();
+        test_throttle();
+        test_ratio   ();
+        test_find    ();
+
+        test_class   ();
+
+        test_typed_handles   ();
+        test_isolate_printing();
+        test_eviction        ();
     }
 }
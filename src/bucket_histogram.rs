@@ -0,0 +1,381 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * BucketHistogram
+//!     * BucketHistogram counts samples using bucket edges given by the
+//!       user, rather than the pseudo-log layout of the other histograms.
+//!       When samples cluster in a narrow band, the pseudo-log buckets can
+//!       put nearly everything into one or two buckets, while a set of
+//!       edges chosen for the data gives a useful picture.
+//!
+//!     * The edges must be strictly increasing.  Bucket i counts the
+//!       samples in [edge[i - 1], edge[i]).  Samples below the first edge
+//!       or at or above the last edge are counted in an underflow or an
+//!       overflow bucket, so there is one more bucket than there are
+//!       edges.
+//!
+//!     * linear() and linear_i64() create edges that are evenly spaced.
+//!       new_i64() takes integer edges, and the edges are printed as
+//!       integers.
+//!
+//!     * RunningInteger and RunningFloat instances can maintain a bucket
+//!       histogram in addition to their usual histogram via the
+//!       enable_buckets() method.
+//!
+//!     * NaNs are counted but are not put into a bucket.  Infinite values
+//!       go into the underflow or overflow bucket.
+//!
+//! ## Example
+//!```
+//!     use rustics::bucket_histogram::BucketHistogram;
+//!     use rustics::stdout_printer;
+//!     use rustics::printer_mut;
+//!
+//!     // The samples fall between 1000 and 1100.  Make 10 buckets of
+//!     // width 10 covering that range.
+//!
+//!     let mut histogram = BucketHistogram::linear_i64(1000, 10, 10);
+//!
+//!     for i in 0..100 {
+//!         histogram.record_i64(1000 + i);
+//!     }
+//!
+//!     histogram.record_i64(999);
+//!     histogram.record_i64(2000);
+//!
+//!     // Bucket 0 is the underflow bucket.
+//!
+//!     let counts = histogram.counts();
+//!
+//!     assert!(counts.len() == 12);
+//!     assert!(counts[0]    == 1 );
+//!     assert!(counts[1]    == 10);
+//!     assert!(counts[11]   == 1 );
+//!
+//!     let printer = stdout_printer();
+//!     let printer = printer_mut!(printer);
+//!
+//!     histogram.print(printer);
+//!```
+
+use super::Printer;
+use super::printable::Printable;
+
+/// BucketHistogram counts samples into buckets with user-specified
+/// edges.
+
+#[derive(Clone)]
+pub struct BucketHistogram {
+    edges:      Vec<f64>,
+    counts:     Vec<u64>,
+    integer:    bool,
+    samples:    u64,
+    nans:       u64,
+}
+
+impl BucketHistogram {
+    /// Creates a histogram with the given edges, which must be finite
+    /// and strictly increasing.  At least one edge is required.
+
+    pub fn new(edges: &[f64]) -> BucketHistogram {
+        if edges.is_empty() {
+            panic!("BucketHistogram::new:  At least one edge is required.");
+        }
+
+        if edges.iter().any(|edge| !edge.is_finite()) {
+            panic!("BucketHistogram::new:  The edges must be finite.");
+        }
+
+        if edges.windows(2).any(|pair| pair[0] >= pair[1]) {
+            panic!("BucketHistogram::new:  The edges must be strictly increasing.");
+        }
+
+        let edges   = edges.to_vec();
+        let counts  = vec![0; edges.len() + 1];
+        let integer = false;
+        let samples = 0;
+        let nans    = 0;
+
+        BucketHistogram { edges, counts, integer, samples, nans }
+    }
+
+    /// Creates a histogram with integer edges.
+
+    pub fn new_i64(edges: &[i64]) -> BucketHistogram {
+        let     edges: Vec<f64> = edges.iter().map(|edge| *edge as f64).collect();
+        let mut result          = BucketHistogram::new(&edges);
+
+        result.integer = true;
+        result
+    }
+
+    /// Creates a histogram with the given number of buckets of equal
+    /// width, starting at "start".  Underflow and overflow buckets
+    /// are added.
+
+    pub fn linear(start: f64, width: f64, buckets: usize) -> BucketHistogram {
+        if width.is_nan() || width <= 0.0 || buckets == 0 {
+            panic!("BucketHistogram::linear:  The width and bucket count must be positive.");
+        }
+
+        let edges: Vec<f64> = (0..=buckets).map(|i| start + i as f64 * width).collect();
+
+        BucketHistogram::new(&edges)
+    }
+
+    /// Creates a linear histogram with integer edges.
+
+    pub fn linear_i64(start: i64, width: i64, buckets: usize) -> BucketHistogram {
+        if width <= 0 || buckets == 0 {
+            panic!("BucketHistogram::linear_i64:  The width and bucket count must be positive.");
+        }
+
+        let edges: Vec<i64> = (0..=buckets as i64).map(|i| start + i * width).collect();
+
+        BucketHistogram::new_i64(&edges)
+    }
+
+    /// Returns the edges.
+
+    pub fn edges(&self) -> &[f64] {
+        &self.edges
+    }
+
+    /// Returns the bucket counts.  Index 0 is the underflow bucket,
+    /// and the last entry is the overflow bucket.
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Returns the number of samples recorded, not including NaNs.
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Returns the number of NaNs seen.
+
+    pub fn nans(&self) -> u64 {
+        self.nans
+    }
+
+    /// Returns the index of the bucket for the given sample.
+
+    pub fn bucket_index(&self, sample: f64) -> usize {
+        self.edges.partition_point(|edge| *edge <= sample)
+    }
+
+    /// Records an f64 sample.
+
+    pub fn record(&mut self, sample: f64) {
+        if sample.is_nan() {
+            self.nans += 1;
+            return;
+        }
+
+        let index = self.bucket_index(sample);
+
+        self.counts[index] += 1;
+        self.samples       += 1;
+    }
+
+    /// Records an i64 sample.
+
+    pub fn record_i64(&mut self, sample: i64) {
+        self.record(sample as f64);
+    }
+
+    /// Merges another histogram into this one.  The edges must be
+    /// the same.
+
+    pub fn merge(&mut self, other: &BucketHistogram) {
+        if self.edges != other.edges {
+            panic!("BucketHistogram::merge:  The edges don't match.");
+        }
+
+        for (count, addend) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += *addend;
+        }
+
+        self.samples += other.samples;
+        self.nans    += other.nans;
+    }
+
+    /// Resets the counts to zero.
+
+    pub fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+
+        self.samples = 0;
+        self.nans    = 0;
+    }
+
+    fn format_edge(&self, edge: f64) -> String {
+        if self.integer {
+            Printable::commas_i64(edge as i64)
+        } else {
+            let (mantissa, exponent) = Printable::format_float(edge);
+
+            format!("{} {}", mantissa, exponent.trim_end())
+        }
+    }
+
+    /// Prints the histogram.  Each row gives the range of a bucket
+    /// and its count.  The underflow and overflow buckets are printed
+    /// only if they aren't empty.
+
+    pub fn print(&self, printer: &mut dyn Printer) {
+        let header =
+            format!("  Bucket Histogram:  ({} NaN, {} samples)",
+                Printable::commas_u64(self.nans), Printable::commas_u64(self.samples));
+
+        printer.print(&header);
+        printer.print("  -----------------------");
+
+        let last = self.counts.len() - 1;
+
+        for (i, count) in self.counts.iter().enumerate() {
+            if (i == 0 || i == last) && *count == 0 {
+                continue;
+            }
+
+            let low =
+                if i == 0 {
+                    "-inf".to_string()
+                } else {
+                    self.format_edge(self.edges[i - 1])
+                };
+
+            let high =
+                if i == last {
+                    "+inf".to_string()
+                } else {
+                    self.format_edge(self.edges[i])
+                };
+
+            let row =
+                format!("    {:>16} .. {:<16} {:>14}", low, high, Printable::commas_u64(*count));
+
+            printer.print(&row);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_counters;
+    use crate::printer_mut;
+
+    fn test_buckets() {
+        let mut histogram = BucketHistogram::new(&[ -1.0, 0.0, 0.5, 2.0 ]);
+
+        assert!(histogram.bucket_index(-5.0) == 0);
+        assert!(histogram.bucket_index(-1.0) == 1);
+        assert!(histogram.bucket_index(-0.1) == 1);
+        assert!(histogram.bucket_index( 0.0) == 2);
+        assert!(histogram.bucket_index( 0.5) == 3);
+        assert!(histogram.bucket_index( 2.0) == 4);
+
+        for sample in [ -3.0, -0.5, 0.25, 0.25, 1.0, 7.0, f64::INFINITY, f64::NAN ] {
+            histogram.record(sample);
+        }
+
+        assert!(histogram.counts()  == [ 1, 1, 2, 1, 2 ]);
+        assert!(histogram.samples() == 7);
+        assert!(histogram.nans()    == 1);
+
+        let mut other = histogram.clone();
+
+        other.merge(&histogram);
+
+        assert!(other.counts()  == [ 2, 2, 4, 2, 4 ]);
+        assert!(other.samples() == 14);
+
+        other.clear();
+
+        assert!(other.counts()  == [ 0, 0, 0, 0, 0 ]);
+        assert!(other.samples() == 0);
+    }
+
+    fn test_linear() {
+        let histogram = BucketHistogram::linear(0.5, 0.25, 4);
+
+        assert!(histogram.edges() == [ 0.5, 0.75, 1.0, 1.25, 1.5 ]);
+
+        let histogram = BucketHistogram::linear_i64(-10, 5, 3);
+
+        assert!(histogram.edges() == [ -10.0, -5.0, 0.0, 5.0 ]);
+    }
+
+    fn test_print() {
+        let expected =
+            [
+                "  Bucket Histogram:  (0 NaN, 1,003 samples)",
+                "  -----------------------",
+                "                -inf .. 1,000                         1",
+                "               1,000 .. 1,500                       500",
+                "               1,500 .. 2,000                       500",
+                "               2,000 .. +inf                          2"
+            ];
+
+        let mut histogram = BucketHistogram::new_i64(&[ 1000, 1500, 2000 ]);
+
+        for i in 1000..2000 {
+            histogram.record_i64(i);
+        }
+
+        histogram.record_i64(0);
+        histogram.record_i64(2000);
+        histogram.record_i64(5000);
+
+        let printer = check_printer_box(&expected, true, false);
+
+        histogram.print(printer_mut!(printer));
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+
+        // Check that float edges print without failing.
+
+        let mut histogram = BucketHistogram::linear(0.0, 0.1, 3);
+
+        histogram.record(0.15);
+
+        let printer = crate::stdout_printer();
+
+        histogram.print(printer_mut!(printer));
+    }
+
+    #[test]
+    fn run_tests() {
+        test_buckets();
+        test_linear ();
+        test_print  ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_edges() {
+        let _ = BucketHistogram::new(&[ 1.0, 1.0 ]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_merge() {
+        let mut first  = BucketHistogram::linear(0.0, 1.0, 4);
+        let     second = BucketHistogram::linear(0.0, 2.0, 4);
+
+        first.merge(&second);
+    }
+}
@@ -0,0 +1,216 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * CapturePrinter
+//!     * CapturePrinter implements the Printer trait by saving each line
+//!       in a vector.  An application can retrieve the lines after
+//!       printing and post-process them, for example to embed a report
+//!       in an HTML or markdown document.
+//!
+//!     * The lines are saved without the trailing newline.
+//!
+//!     * The Rustics types take a PrinterBox, which hides the concrete
+//!       type, so keep a typed Rc for the CapturePrinter, or use
+//!       CapturePrinter::lines_from() to retrieve the lines from the
+//!       PrinterBox.
+//!
+//! ## Example
+//!```
+//!     use std::rc::Rc;
+//!     use std::cell::RefCell;
+//!     use rustics::Rustics;
+//!     use rustics::PrintOpts;
+//!     use rustics::PrinterBox;
+//!     use rustics::capture_printer::CapturePrinter;
+//!     use rustics::running_integer::RunningInteger;
+//!
+//!     let capture    = Rc::new(RefCell::new(CapturePrinter::new()));
+//!     let printer    = capture.clone() as PrinterBox;
+//!     let print_opts = Some(PrintOpts::with_printer(printer));
+//!
+//!     let mut sizes = RunningInteger::new("Packet Sizes", &print_opts);
+//!
+//!     for size in 1..=100 {
+//!         sizes.record_i64(size);
+//!     }
+//!
+//!     sizes.print();
+//!
+//!     // Turn the report into a markdown code block.
+//!
+//!     let lines    = capture.borrow_mut().take_lines();
+//!     let markdown = format!("```\n{}\n```", lines.join("\n"));
+//!
+//!     assert!(lines[0] == "Packet Sizes");
+//!     assert!(markdown.contains("Packet Sizes"));
+//!     assert!(capture.borrow().is_empty());
+//!```
+
+use std::any::Any;
+
+use super::Printer;
+use super::PrinterBox;
+use super::printer;
+
+/// CapturePrinter saves the output lines for later retrieval.
+
+#[derive(Clone, Default)]
+pub struct CapturePrinter {
+    lines:  Vec<String>,
+}
+
+impl CapturePrinter {
+    /// Creates an empty CapturePrinter.
+
+    pub fn new() -> CapturePrinter {
+        let lines = Vec::new();
+
+        CapturePrinter { lines }
+    }
+
+    /// Returns the lines captured so far.
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Returns the captured lines and clears the printer.
+
+    pub fn take_lines(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.lines)
+    }
+
+    /// Returns the captured lines as one string, with a newline
+    /// after each line, as a StdioPrinter would print them.
+
+    pub fn text(&self) -> String {
+        let mut result = String::new();
+
+        for line in &self.lines {
+            result.push_str(line);
+            result.push('\n');
+        }
+
+        result
+    }
+
+    /// Returns the number of lines captured.
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns true if no lines have been captured.
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Discards the captured lines.
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Returns a copy of the lines captured by a PrinterBox, or None
+    /// if the printer is not a CapturePrinter.
+
+    pub fn lines_from(printer_box: &PrinterBox) -> Option<Vec<String>> {
+        let printer = printer!(printer_box);
+        let capture = printer.as_any().downcast_ref::<CapturePrinter>()?;
+
+        Some(capture.lines.clone())
+    }
+}
+
+impl Printer for CapturePrinter {
+    fn print(&mut self, output: &str) {
+        self.lines.push(output.to_string());
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use crate::PrintOpts;
+    use crate::Rustics;
+    use crate::printer_box;
+    use crate::stdout_printer;
+    use crate::running_integer::RunningInteger;
+
+    fn test_capture() {
+        let mut printer = CapturePrinter::new();
+
+        assert!(printer.is_empty());
+        assert!(printer.text().is_empty());
+
+        printer.print("first");
+        printer.print("second");
+
+        assert!(printer.len()   == 2);
+        assert!(printer.lines() == ["first", "second"]);
+        assert!(printer.text()  == "first\nsecond\n");
+
+        let lines = printer.take_lines();
+
+        assert!(lines == ["first", "second"]);
+        assert!(printer.is_empty());
+
+        printer.print("third");
+        printer.clear();
+
+        assert!(printer.is_empty());
+    }
+
+    fn test_report() {
+        let printer: PrinterBox = printer_box!(CapturePrinter::new());
+        let print_opts          = Some(PrintOpts::with_printer(printer.clone()));
+
+        let mut stat = RunningInteger::new("Capture Test", &print_opts);
+
+        for i in 1..=10 {
+            stat.record_i64(i);
+        }
+
+        stat.print();
+
+        let lines = CapturePrinter::lines_from(&printer).unwrap();
+
+        assert!(lines[0] == "Capture Test");
+        assert!(lines.iter().any(|line| line.contains("Count") && line.contains("10")));
+        assert!(lines.iter().all(|line| !line.ends_with('\n')));
+
+        // Other printers don't have lines to return.
+
+        assert!(CapturePrinter::lines_from(&stdout_printer()).is_none());
+
+        let capture = Rc::new(RefCell::new(CapturePrinter::new()));
+        let boxed   = capture.clone() as PrinterBox;
+
+        stat.print_opts(Some(boxed), Some("Retitled"));
+
+        assert!(capture.borrow().lines()[0] == "Retitled");
+    }
+
+    #[test]
+    fn run_tests() {
+        test_capture();
+        test_report ();
+    }
+}
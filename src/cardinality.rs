@@ -0,0 +1,658 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * Cardinality
+//!     * Cardinality estimates the number of distinct i64 values that
+//!       have been recorded, for example, the number of unique client
+//!       ids seen in a period.
+//!
+//!     * The implementation uses the HyperLogLog algorithm with 4096
+//!       six-bit registers, so that the memory use is fixed no matter
+//!       how many distinct values are seen.  The standard error of the
+//!       estimate is about 1.6%.
+//!
+//!     * The count() method returns the estimated number of distinct
+//!       values.  The samples() method returns the number of values
+//!       recorded, including duplicates.
+//!
+//!     * Two instances can be merged with merge(), which produces the
+//!       estimate for the union of the two streams.  This operation is
+//!       used by CardinalityHier to support Hier instances.
+//!
+//!     * The summary statistics like the mean don't apply to this type,
+//!       and panic if invoked.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::cardinality::Cardinality;
+//!
+//!     let mut clients = Cardinality::new("Unique Clients", &None);
+//!
+//!     // Record 10,000 requests from 1000 clients.
+//!
+//!     for i in 0..10_000 {
+//!         clients.record_i64(i % 1000);
+//!     }
+//!
+//!     let estimate = clients.count();
+//!
+//!     assert!(clients.samples() == 10_000);
+//!     assert!(estimate > 950 && estimate < 1050);
+//!
+//!     // Merge in data from another period.  Half of the clients
+//!     // are new.
+//!
+//!     let mut other = Cardinality::new("Other Clients", &None);
+//!
+//!     for i in 500..1500 {
+//!         other.record_i64(i);
+//!     }
+//!
+//!     clients.merge(&other);
+//!
+//!     let estimate = clients.count();
+//!
+//!     assert!(estimate > 1425 && estimate < 1575);
+//!
+//!     clients.print();
+//!```
+
+use std::any::Any;
+
+use super::Rustics;
+use super::Histogram;
+use super::Printer;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use super::ExportStats;
+use super::PrinterBox;
+use super::PrinterOption;
+use super::PrintOption;
+use super::Units;
+use super::TimerBox;
+use super::hier::HierExporter;
+use super::printable::Printable;
+use super::parse_print_opts;
+use super::printer_mut;
+
+// The number of bits of the hash used to select a register.
+
+const PRECISION: u32   = 12;
+const REGISTERS: usize = 1 << PRECISION;
+
+/// Mixes the bits of a sample to get a well-distributed hash.  This
+/// is the finalizer from the SplitMix64 generator.
+
+pub fn hash_i64(sample: i64) -> u64 {
+    let mut hash = (sample as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    hash ^ (hash >> 31)
+}
+
+/// Cardinality implements a HyperLogLog distinct-value estimator
+/// that supports the Rustics trait.
+
+#[derive(Clone)]
+pub struct Cardinality {
+    name:       String,
+    title:      String,
+    id:         usize,
+    samples:    u64,
+    registers:  Vec<u8>,
+    printer:    PrinterBox,
+    units:      Units,
+}
+
+/// CardinalityExporter is used by a Hier instance to merge
+/// multiple Cardinality instances.
+
+#[derive(Clone, Default)]
+pub struct CardinalityExporter {
+    samples:    u64,
+    registers:  Vec<u8>,
+    addends:    usize,
+}
+
+impl CardinalityExporter {
+    /// Creates a new CardinalityExporter instance.
+
+    pub fn new() -> CardinalityExporter {
+        let samples   = 0;
+        let registers = vec![0; REGISTERS];
+        let addends   = 0;
+
+        CardinalityExporter { samples, registers, addends }
+    }
+
+    /// Adds a Cardinality instance to the merged data.
+
+    pub fn push(&mut self, addend: &Cardinality) {
+        merge_registers(&mut self.registers, &addend.registers);
+
+        self.samples += addend.samples;
+        self.addends += 1;
+    }
+
+    /// Makes a Cardinality instance based on the merged data.
+
+    pub fn make_member(&mut self, name: &str, print_opts: &PrintOption) -> Cardinality {
+        let mut member = Cardinality::new(name, print_opts);
+
+        member.registers.clone_from(&self.registers);
+        member.samples = self.samples;
+        member
+    }
+
+    // For testing
+
+    #[cfg(test)]
+    pub fn count(&self) -> usize {
+        self.addends
+    }
+}
+
+impl HierExporter for CardinalityExporter {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Combine two sets of registers.  The union keeps the maximum
+// rank seen for each register.
+
+fn merge_registers(sum: &mut [u8], addend: &[u8]) {
+    for (sum, addend) in sum.iter_mut().zip(addend.iter()) {
+        *sum = std::cmp::max(*sum, *addend);
+    }
+}
+
+impl Cardinality {
+    /// Constructs an instance with the given name and print options.
+
+    pub fn new(name: &str, print_opts: &PrintOption) -> Cardinality {
+        let (printer, title, units, _histo_opts) = parse_print_opts(print_opts, name);
+
+        let name      = String::from(name);
+        let id        = usize::MAX;
+        let samples   = 0;
+        let registers = vec![0; REGISTERS];
+
+        Cardinality { name, title, id, samples, registers, printer, units }
+    }
+
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+    }
+
+    /// Returns the number of values recorded, including duplicates.
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Returns the estimated number of distinct values recorded.
+
+    pub fn estimate(&self) -> u64 {
+        let     m     = REGISTERS as f64;
+        let     alpha = 0.7213 / (1.0 + 1.079 / m);
+        let mut sum   = 0.0;
+        let mut zeros = 0;
+
+        for register in self.registers.iter() {
+            sum += 1.0 / (1_u64 << *register) as f64;
+
+            if *register == 0 {
+                zeros += 1;
+            }
+        }
+
+        let mut estimate = alpha * m * m / sum;
+
+        // Use linear counting for small cardinalities, where the raw
+        // estimate is biased.
+
+        if estimate <= 2.5 * m && zeros > 0 {
+            estimate = m * (m / zeros as f64).ln();
+        }
+
+        estimate.round() as u64
+    }
+
+    /// Merges the data from another instance into this one, giving the
+    /// estimate for the union of the two sample streams.
+
+    pub fn merge(&mut self, other: &Cardinality) {
+        merge_registers(&mut self.registers, &other.registers);
+        self.samples += other.samples;
+    }
+}
+
+impl Rustics for Cardinality {
+    /// Adds a value to the set of values seen.
+
+    fn record_i64(&mut self, sample: i64) {
+        let hash  = hash_i64(sample);
+        let index = (hash >> (64 - PRECISION)) as usize;
+
+        // Compute the position of the first one bit in the rest of
+        // the hash.  The guard bit limits the rank.
+
+        let rest  = (hash << PRECISION) | (1 << (PRECISION - 1));
+        let rank  = rest.leading_zeros() as u8 + 1;
+
+        self.registers[index] = std::cmp::max(self.registers[index], rank);
+        self.samples += 1;
+    }
+
+    fn record_f64(&mut self, _sample: f64) {
+        panic!("Cardinality::record_f64:  not supported");
+    }
+
+    fn record_event(&mut self) {
+        panic!("Cardinality::record_event:  not supported");
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        panic!("Cardinality::record_event_report:  not supported");
+    }
+
+    fn record_time(&mut self, _sample: i64) {
+        panic!("Cardinality::record_time:  not supported");
+    }
+
+    fn record_interval(&mut self, _timer: &mut TimerBox) {
+        panic!("Cardinality::record_interval:  not supported");
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn class(&self) -> &str {
+        "cardinality"
+    }
+
+    /// Returns the estimated number of distinct values.
+
+    fn count(&self) -> u64 {
+        self.estimate()
+    }
+
+    fn log_mode(&self) -> isize {
+        panic!("Cardinality::log_mode:  not supported");
+    }
+
+    fn mean(&self) -> f64 {
+        panic!("Cardinality::mean:  not supported");
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        panic!("Cardinality::standard_deviation:  not supported");
+    }
+
+    fn variance(&self) -> f64 {
+        panic!("Cardinality::variance:  not supported");
+    }
+
+    fn skewness(&self) -> f64 {
+        panic!("Cardinality::skewness:  not supported");
+    }
+
+    fn kurtosis(&self) -> f64 {
+        panic!("Cardinality::kurtosis:  not supported");
+    }
+
+    fn int_extremes(&self) -> bool {
+        false
+    }
+
+    fn float_extremes(&self) -> bool {
+        false
+    }
+
+    fn min_i64(&self) -> i64 {
+        panic!("Cardinality::min_i64:  not supported");
+    }
+
+    fn min_f64(&self) -> f64 {
+        panic!("Cardinality::min_f64:  not supported");
+    }
+
+    fn max_i64(&self) -> i64 {
+        panic!("Cardinality::max_i64:  not supported");
+    }
+
+    fn max_f64(&self) -> f64 {
+        panic!("Cardinality::max_f64:  not supported");
+    }
+
+    fn precompute(&mut self) {
+    }
+
+    fn clear(&mut self) {
+        self.samples = 0;
+        self.registers.fill(0);
+    }
+
+    fn print(&self) {
+        self.print_opts(None, None);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        let printer = printer_mut!(printer_box);
+
+        printer.print(title);
+        Printable::print_integer_units("Distinct", self.estimate() as i64, printer, &self.units);
+        Printable::print_integer      ("Samples",  self.samples    as i64, printer);
+        printer.print("");
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = String::from(title);
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+
+    // For internal use only.
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<Cardinality>(other.generic()) {
+            std::ptr::eq(self, other)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    // The exported count is the estimate, as for count().
+
+    fn export_stats(&self) -> ExportStats {
+        let n          = self.estimate();
+        let nans       = 0;
+        let infinities = 0;
+        let min_i64    = i64::MIN;
+        let max_i64    = i64::MAX;
+        let min_f64    = f64::MIN;
+        let max_f64    = f64::MAX;
+        let log_mode   = 0;
+        let mode_value = 0.0;
+        let mean       = 0.0;
+        let variance   = 0.0;
+        let skewness   = 0.0;
+        let kurtosis   = 0.0;
+        let units      = self.units.clone();
+
+        let printable =
+            Printable {
+                n,           nans,      infinities,  min_i64,   max_i64,   min_f64,
+                max_f64,     log_mode,  mean,        variance,  skewness,  kurtosis,
+                mode_value,  units
+            };
+
+        let log_histogram   = None;
+        let float_histogram = None;
+
+        ExportStats { printable, log_histogram, float_histogram }
+    }
+}
+
+// A Cardinality instance has no histogram, so these are null
+// operations.
+
+impl Histogram for Cardinality {
+    fn print_histogram(&self, _printer: &mut dyn Printer) {
+    }
+
+    fn clear_histogram(&mut self) {
+    }
+
+    fn to_log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrintOpts;
+    use crate::counter::Counter;
+    use crate::tests::continuing_box;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_count_match;
+
+    // Check that the estimate is within the given percentage of the
+    // expected value.
+
+    fn check_estimate(stats: &Cardinality, expected: u64, percent: f64) {
+        let estimate = stats.count() as f64;
+        let expected = expected as f64;
+        let error    = (estimate - expected).abs() / expected * 100.0;
+
+        println!("check_estimate:  {} vs {} ({:.2}%)", estimate, expected, error);
+        assert!(error <= percent);
+    }
+
+    fn test_estimates() {
+        let mut stats = Cardinality::new("Test Cardinality", &None);
+
+        assert!(stats.count()   == 0);
+        assert!(stats.class()   == "cardinality");
+        assert!(stats.samples() == 0);
+
+        // Small counts should be nearly exact.
+
+        for i in 1..=10 {
+            stats.record_i64(i);
+            stats.record_i64(i);
+        }
+
+        assert!(stats.count()   == 10);
+        assert!(stats.samples() == 20);
+
+        // Check a range of larger counts.
+
+        let mut expected = 10;
+
+        for limit in [ 1000, 10_000, 100_000, 1_000_000 ] {
+            for i in expected..limit {
+                stats.record_i64(-i * 7919);
+            }
+
+            expected = limit;
+            check_estimate(&stats, expected as u64, 5.0);
+        }
+
+        stats.clear();
+
+        assert!(stats.count()   == 0);
+        assert!(stats.samples() == 0);
+    }
+
+    fn test_merge() {
+        let mut first  = Cardinality::new("First",  &None);
+        let mut second = Cardinality::new("Second", &None);
+
+        for i in 0..20_000 {
+            first.record_i64(i);
+        }
+
+        for i in 10_000..40_000 {
+            second.record_i64(i);
+        }
+
+        first.merge(&second);
+
+        check_estimate(&first, 40_000, 5.0);
+        assert!(first.samples() == 50_000);
+
+        // Now make the same union using an exporter.
+
+        let mut exporter = CardinalityExporter::new();
+        let mut third    = Cardinality::new("Third", &None);
+
+        for i in 0..10_000 {
+            third.record_i64(i);
+        }
+
+        exporter.push(&third );
+        exporter.push(&second);
+
+        assert!(exporter.count() == 2);
+
+        let sum = exporter.make_member("Sum", &None);
+
+        check_estimate(&sum, 40_000, 5.0);
+        assert!(sum.samples() == 40_000);
+        assert!(sum.name()    == "Sum");
+    }
+
+    fn test_rustics() {
+        let mut stats = Cardinality::new("Rustics", &None);
+
+        stats.record_i64(1);
+        stats.precompute();
+        stats.set_title("New Title");
+        stats.set_id(42);
+
+        assert!(stats.title() == "New Title");
+        assert!(stats.name () == "Rustics"  );
+        assert!(stats.id   () == 42         );
+
+        assert!(!stats.int_extremes  ());
+        assert!(!stats.float_extremes());
+
+        assert!(stats.log_histogram  ().is_none());
+        assert!(stats.float_histogram().is_none());
+
+        assert!(stats.export_stats().printable.n == 1);
+
+        let counter = Counter::new("Counter", &None);
+
+        assert!( stats.equals(&stats  ));
+        assert!(!stats.equals(&counter));
+    }
+
+    fn test_print_output() {
+        let expected =
+            [
+                "Test Statistics",
+                "    Distinct               97 clients",
+                "    Samples             1,000 ",
+                ""
+            ];
+
+        let     printer    = Some(check_printer_box(&expected, true, false));
+        let     title      = None;
+        let     singular   = "client".to_string();
+        let     plural     = "clients".to_string();
+        let     units      = Some(Units { singular, plural });
+        let     histo_opts = None;
+        let     print_opts = Some(PrintOpts { printer: printer.clone(), title, units, histo_opts, ..Default::default() });
+
+        let     name       = "Test Statistics";
+        let mut stats      = Cardinality::new(name, &print_opts);
+
+        // The estimate is a bit off for this data.
+
+        for i in 0..1000 {
+            stats.record_i64(i % 100);
+        }
+
+        stats.print();
+        assert!(check_printer_count_match(printer.unwrap()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn record_f64_panic_test() {
+        let mut stats = Cardinality::new("test", &None);
+
+        stats.record_f64(1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn record_event_panic_test() {
+        let mut stats = Cardinality::new("test", &None);
+
+        stats.record_event();
+    }
+
+    #[test]
+    #[should_panic]
+    fn record_interval_panic_test() {
+        let mut stats = Cardinality::new("test", &None);
+        let mut timer = continuing_box();
+
+        stats.record_interval(&mut timer);
+    }
+
+    #[test]
+    #[should_panic]
+    fn mean_panic_test() {
+        let stats = Cardinality::new("test", &None);
+        let _     = stats.mean();
+    }
+
+    #[test]
+    fn run_tests() {
+        test_estimates   ();
+        test_merge       ();
+        test_rustics     ();
+        test_print_output();
+    }
+}
@@ -0,0 +1,301 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * CardinalityHier
+//!     * This module provides support to bridge from the Hier code to
+//!       Cardinality-specific functions.
+//!
+//!     * See the library comments (lib.rs) for an overview of how
+//!       hierarchical types work.
+//!
+//!     * CardinalityHier::new_hier() is the recommended function for
+//!       creating a Hier instance that uses Cardinality instances.  The
+//!       higher levels of the hierarchy estimate the number of distinct
+//!       values seen over all the periods that they summarize.
+//!
+//!     * Windows are not supported for this type.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::hier::HierDescriptor;
+//!     use rustics::hier::HierDimension;
+//!     use rustics::cardinality_hier::CardinalityHier;
+//!     use rustics::cardinality_hier::CardinalityHierConfig;
+//!
+//!     // Sum 4 level 0 instances into each level 1 instance, and keep
+//!     // 8 instances at each level.  Record 1000 samples into each
+//!     // level 0 instance.
+//!
+//!     let dimension_0 = HierDimension::new(4, 8);
+//!     let dimension_1 = HierDimension::new(0, 8);
+//!     let dimensions  = vec![ dimension_0, dimension_1 ];
+//!     let descriptor  = HierDescriptor::new(dimensions, Some(1000));
+//!     let name        = "Unique Clients".to_string();
+//!     let print_opts  = None;
+//!
+//!     let configuration =
+//!         CardinalityHierConfig { descriptor, name, print_opts };
+//!
+//!     let mut clients = CardinalityHier::new_hier(configuration);
+//!
+//!     // Each level 0 period sees 1000 requests from 100 clients, and
+//!     // the clients change with each period.
+//!
+//!     for period in 0..5 {
+//!         for i in 0..1000 {
+//!             clients.record_i64(period * 100 + i % 100);
+//!         }
+//!     }
+//!
+//!     // The current level 0 instance has seen one period, so the
+//!     // estimate should be close to 100.
+//!
+//!     let estimate = clients.count();
+//!
+//!     assert!(estimate >= 95 && estimate <= 105);
+//!
+//!     // The first level 1 instance covers the first four periods.
+//!
+//!     assert!(clients.all_len(1) == 1);
+//!
+//!     clients.print();
+//!```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::Rustics;
+use super::Histogram;
+use super::PrintOption;
+use super::hier_box;
+use super::cardinality::Cardinality;
+use super::cardinality::CardinalityExporter;
+
+use crate::Hier;
+use crate::HierDescriptor;
+use crate::HierConfig;
+use crate::hier::HierWindow;
+use crate::HierGenerator;
+use crate::HierMember;
+use crate::HierExporter;
+use crate::ExporterRc;
+use crate::MemberRc;
+use crate::hier_item;
+
+// Provide for downcasting from a Hier member to a Rustics
+// type or "dyn Any" to get to the Cardinality code.
+
+impl HierMember for Cardinality {
+    fn to_rustics(&self) -> &dyn Rustics {
+        self
+    }
+
+    fn to_rustics_mut(&mut self) -> &mut dyn Rustics {
+        self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn to_histogram(&self) -> &dyn Histogram {
+        self
+    }
+}
+
+/// CardinalityHier provides an interface from the Hier code to
+/// the Cardinality impl code that is not in methods.  Most users
+/// should construct a Hier instance via new_hier().
+
+#[derive(Default)]
+pub struct CardinalityHier {
+}
+
+/// CardinalityHierConfig is used to pass the constructor parameters
+/// for a Hier instance that uses the Cardinality type for recording
+/// and combining data.
+
+pub struct CardinalityHierConfig {
+    pub descriptor:  HierDescriptor,
+    pub name:        String,
+    pub print_opts:  PrintOption,
+}
+
+impl CardinalityHier {
+    /// Make a plain CardinalityHier structure.  Most users should
+    /// call new_hier() create a complete Hier instance.
+
+    pub fn new() -> CardinalityHier  {
+        CardinalityHier { }
+    }
+
+    /// new_hier() creates a new Hier instance from the given
+    /// configuration.
+
+    pub fn new_hier(configuration: CardinalityHierConfig) -> Hier {
+        let generator    = CardinalityHier::new();
+        let generator    = Rc::from(RefCell::new(generator));
+        let class        = "cardinality".to_string();
+
+        let descriptor   = configuration.descriptor;
+        let name         = configuration.name;
+        let print_opts   = configuration.print_opts;
+        let window       = HierWindow::None;
+
+        let config = HierConfig { descriptor, generator, name, window, class, print_opts };
+
+        Hier::new(config)
+    }
+}
+
+impl HierGenerator for CardinalityHier {
+    fn make_member(&self, name: &str, print_opts: &PrintOption) -> MemberRc {
+        let member = Cardinality::new(name, print_opts);
+
+        hier_box!(member)
+    }
+
+    fn make_window(&self, _name: &str, _window: &HierWindow, _print_opts: &PrintOption)
+            -> Box<dyn Rustics> {
+        panic!("CardinalityHier::make_window:  not supported");
+    }
+
+    // Make a member from the merged data.
+
+    fn make_from_exporter(&self, name: &str, print_opts: &PrintOption, exporter: ExporterRc)
+            -> MemberRc {
+        let mut exporter_borrow = exporter.borrow_mut();
+        let     exporter_any    = exporter_borrow.as_any_mut();
+        let     exporter_impl   = exporter_any.downcast_mut::<CardinalityExporter>().unwrap();
+        let     member          = exporter_impl.make_member(name, print_opts);
+
+        hier_box!(member)
+    }
+
+    fn make_exporter(&self) -> ExporterRc {
+        let exporter = CardinalityExporter::new();
+
+        Rc::from(RefCell::new(exporter))
+    }
+
+    // Merge another instance into the exporter.
+
+    fn push(&self, exporter: &mut dyn HierExporter, member_rc: MemberRc) {
+        let exporter_any    = exporter.as_any_mut();
+        let exporter_impl   = exporter_any.downcast_mut::<CardinalityExporter>().unwrap();
+
+        let member_borrow   = hier_item!(member_rc);
+        let member_any      = member_borrow.as_any();
+        let member_impl     = member_any.downcast_ref::<Cardinality>().unwrap();
+
+        exporter_impl.push(member_impl);
+    }
+
+    fn hz(&self) -> u128 {
+        panic!("CardinalityHier::hz:  not supported");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hier::HierDimension;
+    use crate::hier::HierIndex;
+    use crate::hier::HierSet;
+
+    fn make_test_hier(auto_next: i64) -> Hier {
+        let dimension_0 = HierDimension::new(4, 8);
+        let dimension_1 = HierDimension::new(2, 4);
+        let dimension_2 = HierDimension::new(0, 4);
+        let dimensions  = vec![ dimension_0, dimension_1, dimension_2 ];
+        let descriptor  = HierDescriptor::new(dimensions, Some(auto_next));
+        let name        = "test hier".to_string();
+        let print_opts  = None;
+
+        let configuration = CardinalityHierConfig { descriptor, name, print_opts };
+
+        CardinalityHier::new_hier(configuration)
+    }
+
+    fn test_generator() {
+        let generator = CardinalityHier::new();
+        let member_rc = generator.make_member("test member", &None);
+        let exporter  = generator.make_exporter();
+
+        for i in 0..10 {
+            member_rc.borrow_mut().to_rustics_mut().record_i64(i);
+        }
+
+        generator.push(&mut *exporter.borrow_mut(), member_rc.clone());
+        generator.push(&mut *exporter.borrow_mut(), member_rc);
+
+        let sum_rc = generator.make_from_exporter("sum", &None, exporter);
+        let sum    = hier_item!(sum_rc);
+        let sum    = sum.as_any().downcast_ref::<Cardinality>().unwrap();
+
+        assert!(sum.count()   == 10);
+        assert!(sum.samples() == 20);
+    }
+
+    fn test_hier() {
+        let     auto_next = 1000;
+        let mut hier      = make_test_hier(auto_next);
+
+        assert!(hier.class() == "cardinality");
+
+        // Use 100 distinct values in each level 0 period, rotating
+        // through 400 values.
+
+        for period in 0..9 {
+            for i in 0..auto_next {
+                hier.record_i64((period % 4) * 100 + i % 100);
+            }
+        }
+
+        let level_0 = hier.count();
+
+        println!("test_hier:  level 0 count {}", level_0);
+        assert!((95..=105).contains(&level_0));
+
+        assert!(hier.live_len(1)   == 2);
+        assert!(hier.event_count() == 9 * auto_next);
+
+        // Each level 1 instance covers all 400 values.
+
+        for i in 0..2 {
+            let index   = HierIndex::new(HierSet::Live, 1, i);
+            let member  = hier.index(index).unwrap();
+            let level_1 = hier_item!(member).to_rustics().count();
+
+            println!("test_hier:  level 1 count {}", level_1);
+            assert!((390..=410).contains(&level_1));
+        }
+
+        hier.print();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_window() {
+        let generator = CardinalityHier::new();
+        let _         = generator.make_window("window", &HierWindow::Samples(10), &None);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_generator();
+        test_hier     ();
+    }
+}
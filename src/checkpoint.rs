@@ -0,0 +1,822 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Types
+//!
+//! * Checkpoint
+//!     * Checkpoint builds a text encoding of the state of a Rustics
+//!       instance, so that statistics can be saved across a process
+//!       restart and restored or summed later.
+//!
+//!     * The encoding is one field per line.  The first line names the
+//!       type of the instance that was saved and the schema version of
+//!       the encoding.  Each following line contains a field name and
+//!       its value, separated by a space.  Floating-point values are
+//!       written so that they are restored exactly.
+//!
+//! * CheckpointReader
+//!     * CheckpointReader parses the output of a Checkpoint instance.
+//!       The accessor functions return None if a field is missing or
+//!       malformed.
+//!
+//!     * A checkpoint written by a newer version of the crate with an
+//!       incompatible schema is rejected.  A header without a version
+//!       is read as version 1.
+//!
+//! * The RunningInteger, RunningFloat, RunningTime, IntegerWindow,
+//!   FloatWindow, TimeWindow, LogHistogram, and FloatHistogram types
+//!   implement checkpoint() and restore() functions using this module.
+//!   Printers and timers can't be saved, so restore() takes the print
+//!   options and, for time types, the timer to use.
+//!
+//! * Hier::save and Hier::load use the member checkpoints to save and
+//!   restore an entire hierarchy.
+//!
+//! * With the "serde" Cargo feature, the same types implement the serde
+//!   Serialize and Deserialize traits.  The serialized form is a struct
+//!   with the type, the schema version, and a map from each checkpoint
+//!   field to its value as text, so that floating-point values,
+//!   including NaN and the infinities, are restored exactly in formats
+//!   like JSON.  Deserialized instances use the default print options,
+//!   and the time types use a DurationTimer, so their checkpoints must
+//!   have been made with a timer of the same frequency.
+//!
+//! * A Hier instance needs its configuration and generator to be
+//!   restored, so it implements Serialize, and HierSeed implements the
+//!   serde DeserializeSeed trait to load the data into an existing
+//!   instance, as Hier::load does.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::running_integer::RunningInteger;
+//!
+//!     let mut latency = RunningInteger::new("Latency", &None);
+//!
+//!     for i in 1..=1000 {
+//!         latency.record_i64(i);
+//!     }
+//!
+//!     // Save the state as a string, which could be written to a
+//!     // file.
+//!
+//!     let checkpoint = latency.checkpoint();
+//!
+//!     // Now restore it, perhaps in another process.
+//!
+//!     let restored = RunningInteger::restore(&checkpoint, &None).unwrap();
+//!
+//!     assert!(restored.count()    == latency.count()   );
+//!     assert!(restored.mean()     == latency.mean()    );
+//!     assert!(restored.variance() == latency.variance());
+//!     assert!(restored.max_i64()  == latency.max_i64() );
+//!
+//!     // Restore a second copy and sum the two.
+//!
+//!     let mut sum = RunningInteger::restore(&checkpoint, &None).unwrap();
+//!
+//!     sum.merge(&restored);
+//!
+//!     assert!(sum.count() == 2 * latency.count());
+//!```
+
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::PrintOption;
+use super::SCHEMA_VERSION;
+use super::schema_compatible;
+use super::merge::Export;
+use super::log_histogram::LogHistogram;
+use super::float_histogram::FloatHistogram;
+
+// The prefix of the first line of every checkpoint.
+
+const HEADER: &str = "rustics";
+
+/// Checkpoint accumulates the fields to be saved for an instance.
+
+pub struct Checkpoint {
+    lines:  Vec<String>,
+}
+
+impl Checkpoint {
+    /// Starts a checkpoint for an instance of the given type.
+
+    pub fn new(kind: &str) -> Checkpoint {
+        let lines = vec![ format!("{} {} {}", HEADER, kind, SCHEMA_VERSION) ];
+
+        Checkpoint { lines }
+    }
+
+    // Push a field using the Debug format, which is exact for floats
+    // and can be parsed by from_str().
+
+    fn push_value<T: Debug>(&mut self, key: &str, value: T) {
+        self.lines.push(format!("{} {:?}", key, value));
+    }
+
+    fn push_list<T: Debug>(&mut self, key: &str, values: &[T]) {
+        let values: Vec<String> = values.iter().map(|value| format!("{:?}", value)).collect();
+
+        self.lines.push(format!("{} {}", key, values.join(" ")));
+    }
+
+    /// Saves a string field.  Newlines are escaped.
+
+    pub fn push_str(&mut self, key: &str, value: &str) {
+        self.lines.push(format!("{} {}", key, escape(value)));
+    }
+
+    pub fn push_u64(&mut self, key: &str, value: u64) {
+        self.push_value(key, value);
+    }
+
+    pub fn push_i64(&mut self, key: &str, value: i64) {
+        self.push_value(key, value);
+    }
+
+    pub fn push_f64(&mut self, key: &str, value: f64) {
+        self.push_value(key, value);
+    }
+
+    pub fn push_u64s(&mut self, key: &str, values: &[u64]) {
+        self.push_list(key, values);
+    }
+
+    pub fn push_i64s(&mut self, key: &str, values: &[i64]) {
+        self.push_list(key, values);
+    }
+
+    pub fn push_f64s(&mut self, key: &str, values: &[f64]) {
+        self.push_list(key, values);
+    }
+
+    /// Saves the bucket counts of a LogHistogram, using the prefix
+    /// to form the field names.
+
+    pub fn push_log_histogram(&mut self, prefix: &str, histogram: &LogHistogram) {
+        self.push_u64s(&format!("{}_negative", prefix), &histogram.negative);
+        self.push_u64s(&format!("{}_positive", prefix), &histogram.positive);
+    }
+
+    /// Saves the counts kept by a FloatHistogram.
+
+    pub fn push_float_histogram(&mut self, prefix: &str, histogram: &FloatHistogram) {
+        self.push_u64s(&format!("{}_negative",   prefix), &histogram.negative);
+        self.push_u64s(&format!("{}_positive",   prefix), &histogram.positive);
+        self.push_u64 (&format!("{}_nans",       prefix), histogram.nans       as u64);
+        self.push_u64 (&format!("{}_infinities", prefix), histogram.infinities as u64);
+        self.push_u64 (&format!("{}_samples",    prefix), histogram.samples    as u64);
+    }
+
+    /// Saves the contents of an Export instance, which contains the
+    /// summary statistics for the running types.
+
+    pub fn push_export(&mut self, export: &Export) {
+        self.push_u64("count",      export.count     );
+        self.push_u64("nans",       export.nans      );
+        self.push_u64("infinities", export.infinities);
+        self.push_f64("mean",       export.mean      );
+        self.push_f64("moment_2",   export.moment_2  );
+        self.push_f64("cubes",      export.cubes     );
+        self.push_f64("moment_4",   export.moment_4  );
+        self.push_i64("min_i64",    export.min_i64   );
+        self.push_i64("max_i64",    export.max_i64   );
+        self.push_f64("min_f64",    export.min_f64   );
+        self.push_f64("max_f64",    export.max_f64   );
+
+        if let Some(histogram) = &export.log_histogram {
+            self.push_str("histogram", "log");
+            self.push_log_histogram("log", &histogram.borrow());
+        } else if let Some(histogram) = &export.float_histogram {
+            self.push_str("histogram", "float");
+            self.push_float_histogram("float", &histogram.borrow());
+        } else {
+            self.push_str("histogram", "none");
+        }
+    }
+
+    /// Returns the encoded checkpoint.
+
+    pub fn text(&self) -> String {
+        let mut result = self.lines.join("\n");
+
+        result.push('\n');
+        result
+    }
+}
+
+/// CheckpointReader provides access to the fields of a checkpoint.
+
+pub struct CheckpointReader {
+    kind:    String,
+    version: u32,
+    fields:  Vec<(String, String)>,
+}
+
+impl CheckpointReader {
+    /// Parses a checkpoint.  The result is None if the text doesn't
+    /// start with a valid header or the schema version isn't
+    /// supported.
+
+    pub fn new(text: &str) -> Option<CheckpointReader> {
+        let mut lines  = text.lines();
+        let     header = lines.next()?;
+        let     header = header.strip_prefix(HEADER)?.strip_prefix(' ')?;
+
+        let (kind, version) =
+            match header.split_once(' ') {
+                Some((kind, version)) => (kind, version.parse::<u32>().ok()?),
+                None                  => (header, 1),
+            };
+
+        if kind.is_empty() || !schema_compatible(version) {
+            return None;
+        }
+
+        let     kind   = kind.to_string();
+        let mut fields = Vec::new();
+
+        for line in lines {
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+
+            fields.push((key.to_string(), value.to_string()));
+        }
+
+        Some(CheckpointReader { kind, version, fields })
+    }
+
+    /// Parses a checkpoint, checking that it was made for the given
+    /// type.
+
+    pub fn new_kind(text: &str, kind: &str) -> Option<CheckpointReader> {
+        let reader = CheckpointReader::new(text)?;
+
+        if reader.kind != kind {
+            return None;
+        }
+
+        Some(reader)
+    }
+
+    /// Returns the type recorded in the header.
+
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Returns the schema version recorded in the header.
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn raw(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
+    }
+
+    fn parse<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.raw(key)?.parse::<T>().ok()
+    }
+
+    fn parse_list<T: FromStr>(&self, key: &str) -> Option<Vec<T>> {
+        let mut result = Vec::new();
+
+        for value in self.raw(key)?.split_whitespace() {
+            result.push(value.parse::<T>().ok()?);
+        }
+
+        Some(result)
+    }
+
+    pub fn string(&self, key: &str) -> Option<String> {
+        unescape(self.raw(key)?)
+    }
+
+    pub fn u64(&self, key: &str) -> Option<u64> {
+        self.parse(key)
+    }
+
+    pub fn i64(&self, key: &str) -> Option<i64> {
+        self.parse(key)
+    }
+
+    pub fn f64(&self, key: &str) -> Option<f64> {
+        self.parse(key)
+    }
+
+    pub fn usize(&self, key: &str) -> Option<usize> {
+        self.parse(key)
+    }
+
+    pub fn u64s(&self, key: &str) -> Option<Vec<u64>> {
+        self.parse_list(key)
+    }
+
+    pub fn i64s(&self, key: &str) -> Option<Vec<i64>> {
+        self.parse_list(key)
+    }
+
+    pub fn f64s(&self, key: &str) -> Option<Vec<f64>> {
+        self.parse_list(key)
+    }
+
+    /// Restores a LogHistogram saved by push_log_histogram().
+
+    pub fn log_histogram(&self, prefix: &str) -> Option<LogHistogram> {
+        let negative = self.u64s(&format!("{}_negative", prefix))?;
+        let positive = self.u64s(&format!("{}_positive", prefix))?;
+
+        let mut histogram = LogHistogram::new();
+
+        histogram.negative = negative.try_into().ok()?;
+        histogram.positive = positive.try_into().ok()?;
+
+        Some(histogram)
+    }
+
+    /// Restores a FloatHistogram saved by push_float_histogram().  The
+    /// bucket counts must match the current configuration.
+
+    pub fn float_histogram(&self, prefix: &str, print_opts: &PrintOption)
+            -> Option<FloatHistogram> {
+        let negative   = self.u64s (&format!("{}_negative",   prefix))?;
+        let positive   = self.u64s (&format!("{}_positive",   prefix))?;
+        let nans       = self.usize(&format!("{}_nans",       prefix))?;
+        let infinities = self.usize(&format!("{}_infinities", prefix))?;
+        let samples    = self.usize(&format!("{}_samples",    prefix))?;
+
+        let mut histogram = FloatHistogram::new(print_opts);
+
+        if
+            negative.len() != histogram.negative.len()
+        ||  positive.len() != histogram.positive.len() {
+            return None;
+        }
+
+        histogram.negative   = negative;
+        histogram.positive   = positive;
+        histogram.nans       = nans;
+        histogram.infinities = infinities;
+        histogram.samples    = samples;
+
+        Some(histogram)
+    }
+
+    /// Restores an Export instance saved by push_export().  The print
+    /// options are used for any float histogram.
+
+    pub fn export(&self, print_opts: &PrintOption) -> Option<Export> {
+        let count      = self.u64("count"     )?;
+        let nans       = self.u64("nans"      )?;
+        let infinities = self.u64("infinities")?;
+        let mean       = self.f64("mean"      )?;
+        let moment_2   = self.f64("moment_2"  )?;
+        let cubes      = self.f64("cubes"     )?;
+        let moment_4   = self.f64("moment_4"  )?;
+        let min_i64    = self.i64("min_i64"   )?;
+        let max_i64    = self.i64("max_i64"   )?;
+        let min_f64    = self.f64("min_f64"   )?;
+        let max_f64    = self.f64("max_f64"   )?;
+
+        let (log_histogram, float_histogram) =
+            match self.string("histogram")?.as_str() {
+                "log" => {
+                    let histogram = self.log_histogram("log")?;
+
+                    (Some(Rc::from(RefCell::new(histogram))), None)
+                }
+
+                "float" => {
+                    let histogram = self.float_histogram("float", print_opts)?;
+
+                    (None, Some(Rc::from(RefCell::new(histogram))))
+                }
+
+                "none" => { (None, None) }
+                _      => { return None; }
+            };
+
+        let export =
+            Export {
+                count,       nans,           infinities,       mean,
+                moment_2,    cubes,          moment_4,         min_i64,
+                max_i64,     min_f64,        max_f64,          log_histogram,
+                float_histogram
+            };
+
+        Some(export)
+    }
+}
+
+/// SavedState is the serialized form of a checkpoint.  The field
+/// values are kept in the checkpoint text format.
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedState {
+    kind:       String,
+    version:    u32,
+    fields:     std::collections::BTreeMap<String, String>,
+}
+
+#[cfg(feature = "serde")]
+impl SavedState {
+    fn from_text(text: &str) -> Option<SavedState> {
+        let reader  = CheckpointReader::new(text)?;
+        let kind    = reader.kind;
+        let version = reader.version;
+        let fields  = reader.fields.into_iter().collect();
+
+        Some(SavedState { kind, version, fields })
+    }
+
+    fn text(&self) -> String {
+        let mut result = format!("{} {} {}\n", HEADER, self.kind, self.version);
+
+        for (key, value) in self.fields.iter() {
+            result.push_str(&format!("{} {}\n", key, value));
+        }
+
+        result
+    }
+}
+
+/// Serializes a checkpoint produced by one of the checkpoint()
+/// functions.
+
+#[cfg(feature = "serde")]
+pub fn serialize_checkpoint<S: serde::Serializer>(text: &str, serializer: S)
+        -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+    use serde::ser::Error;
+
+    match SavedState::from_text(text) {
+        Some(state) => state.serialize(serializer),
+        None        => Err(S::Error::custom("serialize_checkpoint:  invalid checkpoint")),
+    }
+}
+
+/// Deserializes the text of a checkpoint serialized by
+/// serialize_checkpoint().
+
+#[cfg(feature = "serde")]
+pub fn deserialize_checkpoint<'de, D: serde::Deserializer<'de>>(deserializer: D)
+        -> Result<String, D::Error> {
+    use serde::Deserialize;
+
+    let state = SavedState::deserialize(deserializer)?;
+
+    Ok(state.text())
+}
+
+// Implement Serialize and Deserialize for a type using its
+// checkpoint() function and the given restore function.
+
+#[cfg(feature = "serde")]
+macro_rules! serde_checkpoint {
+    ($type:ty, $restore:expr) => {
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S)
+                    -> Result<S::Ok, S::Error> {
+                serialize_checkpoint(&self.checkpoint(), serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $type {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D)
+                    -> Result<Self, D::Error> {
+                use serde::de::Error;
+
+                let text    = deserialize_checkpoint(deserializer)?;
+                let restore: fn(&str) -> Option<$type> = $restore;
+
+                restore(&text).ok_or_else(|| {
+                    D::Error::custom(concat!(stringify!($type), "::deserialize:  invalid checkpoint"))
+                })
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use super::serialize_checkpoint;
+    use super::deserialize_checkpoint;
+    use crate::time::DurationTimer;
+    use crate::log_histogram::LogHistogram;
+    use crate::float_histogram::FloatHistogram;
+    use crate::running_integer::RunningInteger;
+    use crate::running_float::RunningFloat;
+    use crate::running_time::RunningTime;
+    use crate::integer_window::IntegerWindow;
+    use crate::float_window::FloatWindow;
+    use crate::time_window::TimeWindow;
+
+    serde_checkpoint!(LogHistogram,   |text| LogHistogram  ::restore(text));
+    serde_checkpoint!(FloatHistogram, |text| FloatHistogram::restore(text, &None));
+    serde_checkpoint!(RunningInteger, |text| RunningInteger::restore(text, &None));
+    serde_checkpoint!(RunningFloat,   |text| RunningFloat  ::restore(text, &None));
+    serde_checkpoint!(IntegerWindow,  |text| IntegerWindow ::restore(text, &None));
+    serde_checkpoint!(FloatWindow,    |text| FloatWindow   ::restore(text, &None));
+
+    serde_checkpoint!(RunningTime, |text| {
+        RunningTime::restore(text, DurationTimer::new_box(), &None)
+    });
+
+    serde_checkpoint!(TimeWindow, |text| {
+        TimeWindow::restore(text, DurationTimer::new_box(), &None)
+    });
+}
+
+// Escape backslashes and newlines so that strings fit on one line.
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> Option<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut chars  = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            'n'  => result.push('\n'),
+            '\\' => result.push('\\'),
+            _    => return None,
+        }
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_fields() {
+        let mut checkpoint = Checkpoint::new("test");
+        let     name       = "Name with spaces,\nnewlines, \\ and \\n";
+
+        checkpoint.push_str ("name",    name                           );
+        checkpoint.push_str ("empty",   ""                             );
+        checkpoint.push_u64 ("u64",     u64::MAX                       );
+        checkpoint.push_i64 ("i64",     i64::MIN                       );
+        checkpoint.push_f64 ("f64",     0.1 + 0.2                      );
+        checkpoint.push_f64 ("nan",     f64::NAN                       );
+        checkpoint.push_f64 ("inf",     f64::NEG_INFINITY              );
+        checkpoint.push_f64s("f64s",    &[ 1.0e-300, -2.5, f64::MAX ]  );
+        checkpoint.push_i64s("i64s",    &[ -1, 0, 1 ]                  );
+        checkpoint.push_u64s("none",    &[]                            );
+
+        let text   = checkpoint.text();
+        let reader = CheckpointReader::new(&text).unwrap();
+
+        assert!(reader.kind()                == "test"                        );
+        assert!(reader.string("name").unwrap() == name                        );
+        assert!(reader.string("empty").unwrap() == ""                         );
+        assert!(reader.u64("u64")            == Some(u64::MAX)                );
+        assert!(reader.i64("i64")            == Some(i64::MIN)                );
+        assert!(reader.f64("f64")            == Some(0.1 + 0.2)               );
+        assert!(reader.f64("nan").unwrap().is_nan()                           );
+        assert!(reader.f64("inf")            == Some(f64::NEG_INFINITY)       );
+        assert!(reader.f64s("f64s").unwrap() == vec![ 1.0e-300, -2.5, f64::MAX ]);
+        assert!(reader.i64s("i64s").unwrap() == vec![ -1, 0, 1 ]              );
+        assert!(reader.u64s("none").unwrap().is_empty()                       );
+
+        // Check the failure cases.
+
+        assert!(reader.u64("missing").is_none());
+        assert!(reader.u64("i64"    ).is_none());
+        assert!(reader.u64("f64s"   ).is_none());
+
+        assert!(CheckpointReader::new_kind(&text, "test" ).is_some());
+        assert!(CheckpointReader::new_kind(&text, "other").is_none());
+
+        assert!(CheckpointReader::new(""              ).is_none());
+        assert!(CheckpointReader::new("other test\n"  ).is_none());
+        assert!(CheckpointReader::new("rustics\n"     ).is_none());
+        assert!(CheckpointReader::new("rusticstest\n" ).is_none());
+        assert!(unescape("bad \\escape").is_none());
+        assert!(unescape("trailing \\"  ).is_none());
+    }
+
+    fn test_version() {
+        let text = Checkpoint::new("test").text();
+
+        assert!(text == format!("rustics test {}\n", SCHEMA_VERSION));
+        assert!(CheckpointReader::new(&text).unwrap().version() == SCHEMA_VERSION);
+
+        // A header without a version is version 1.
+
+        let reader = CheckpointReader::new("rustics test\ncount 1\n").unwrap();
+
+        assert!(reader.kind()    == "test" );
+        assert!(reader.version() == 1      );
+        assert!(reader.u64("count") == Some(1));
+
+        // Reject versions that are unknown or malformed.
+
+        let newer = format!("rustics test {}\n", SCHEMA_VERSION + 1);
+
+        assert!(CheckpointReader::new(&newer            ).is_none());
+        assert!(CheckpointReader::new("rustics test 0\n").is_none());
+        assert!(CheckpointReader::new("rustics test x\n").is_none());
+        assert!(CheckpointReader::new("rustics test 1 2\n").is_none());
+    }
+
+    fn test_export() {
+        let mut histogram = LogHistogram::new();
+
+        histogram.record(-4);
+        histogram.record(1_000);
+
+        let log_histogram   = Some(Rc::from(RefCell::new(histogram)));
+        let float_histogram = None;
+
+        let export =
+            Export {
+                count:      2,
+                nans:       0,
+                infinities: 0,
+                mean:       498.0,
+                moment_2:   502_004.0,
+                cubes:      1.0e9 - 64.0,
+                moment_4:   1.0,
+                min_i64:    -4,
+                max_i64:    1_000,
+                min_f64:    0.0,
+                max_f64:    0.0,
+                log_histogram,
+                float_histogram
+            };
+
+        let mut checkpoint = Checkpoint::new("export");
+
+        checkpoint.push_export(&export);
+
+        let reader   = CheckpointReader::new(&checkpoint.text()).unwrap();
+        let restored = reader.export(&None).unwrap();
+
+        assert!(restored.count    == export.count   );
+        assert!(restored.mean     == export.mean    );
+        assert!(restored.moment_2 == export.moment_2);
+        assert!(restored.cubes    == export.cubes   );
+        assert!(restored.min_i64  == export.min_i64 );
+        assert!(restored.max_i64  == export.max_i64 );
+
+        assert!(restored.float_histogram.is_none());
+
+        let restored = restored.log_histogram.unwrap();
+        let original = export.log_histogram.unwrap();
+
+        assert!(restored.borrow().negative == original.borrow().negative);
+        assert!(restored.borrow().positive == original.borrow().positive);
+
+        // Check a float histogram with the wrong number of buckets.
+
+        let mut checkpoint = Checkpoint::new("float");
+        let     histogram  = FloatHistogram::new(&None);
+
+        checkpoint.push_float_histogram("float", &histogram);
+
+        let reader = CheckpointReader::new(&checkpoint.text()).unwrap();
+
+        assert!(reader.float_histogram("float", &None).is_some());
+
+        let text   = checkpoint.text().replace("float_negative 0 ", "float_negative ");
+        let reader = CheckpointReader::new(&text).unwrap();
+
+        assert!(reader.float_histogram("float", &None).is_none());
+    }
+
+    // Round-trip each type through serde_json.
+
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        use crate::Rustics;
+        use crate::time::DurationTimer;
+        use crate::running_integer::RunningInteger;
+        use crate::running_float::RunningFloat;
+        use crate::running_time::RunningTime;
+        use crate::integer_window::IntegerWindow;
+        use crate::float_window::FloatWindow;
+        use crate::time_window::TimeWindow;
+
+        let mut integer      = RunningInteger::new("Integer", &None);
+        let mut float        = RunningFloat  ::new("Float",   &None);
+        let mut time         = RunningTime   ::new("Time",    DurationTimer::new_box(), &None);
+        let mut int_window   = IntegerWindow ::new("Integer Window", 10, &None);
+        let mut float_window = FloatWindow   ::new("Float Window",   10, &None);
+        let mut time_window  = TimeWindow    ::new("Time Window",    10, DurationTimer::new_box(), &None);
+
+        for i in 1..=100 {
+            integer     .record_i64(i);
+            float       .record_f64(i as f64 / 3.0);
+            time        .record_time(i * 1000);
+            int_window  .record_i64(i);
+            float_window.record_f64(i as f64 / 3.0);
+            time_window .record_time(i * 1000);
+        }
+
+        float.record_f64(f64::NAN);
+
+        let json   = serde_json::to_string(&integer).unwrap();
+        let result = serde_json::from_str::<RunningInteger>(&json).unwrap();
+
+        assert!(result.title()    == integer.title()   );
+        assert!(result.count()    == integer.count()   );
+        assert!(result.mean()     == integer.mean()    );
+        assert!(result.variance() == integer.variance());
+        assert!(result.max_i64()  == integer.max_i64() );
+
+        let json   = serde_json::to_string(&float).unwrap();
+        let result = serde_json::from_str::<RunningFloat>(&json).unwrap();
+
+        assert!(result.count()    == float.count()   );
+        assert!(result.nans()     == float.nans()    );
+        assert!(result.mean()     == float.mean()    );
+        assert!(result.variance() == float.variance());
+
+        let json   = serde_json::to_string(&time).unwrap();
+        let result = serde_json::from_str::<RunningTime>(&json).unwrap();
+
+        assert!(result.count() == time.count());
+        assert!(result.mean()  == time.mean() );
+
+        let json   = serde_json::to_string(&int_window).unwrap();
+        let result = serde_json::from_str::<IntegerWindow>(&json).unwrap();
+
+        assert!(result.count() == int_window.count());
+        assert!(result.mean()  == int_window.mean() );
+
+        let json   = serde_json::to_string(&float_window).unwrap();
+        let result = serde_json::from_str::<FloatWindow>(&json).unwrap();
+
+        assert!(result.count() == float_window.count());
+        assert!(result.mean()  == float_window.mean() );
+
+        let json   = serde_json::to_string(&time_window).unwrap();
+        let result = serde_json::from_str::<TimeWindow>(&json).unwrap();
+
+        assert!(result.count() == time_window.count());
+        assert!(result.mean()  == time_window.mean() );
+
+        // Check the histograms.
+
+        let mut histogram = LogHistogram::new();
+
+        histogram.record(-4);
+        histogram.record(1_000);
+
+        let json   = serde_json::to_string(&histogram).unwrap();
+        let result = serde_json::from_str::<LogHistogram>(&json).unwrap();
+
+        assert!(result.negative == histogram.negative);
+        assert!(result.positive == histogram.positive);
+
+        let mut histogram = FloatHistogram::new(&None);
+
+        histogram.record(-4.5);
+        histogram.record(f64::INFINITY);
+
+        let json   = serde_json::to_string(&histogram).unwrap();
+        let result = serde_json::from_str::<FloatHistogram>(&json).unwrap();
+
+        assert!(result.equals(&histogram));
+
+        // Data for another type is rejected.
+
+        let json = serde_json::to_string(&integer).unwrap();
+
+        assert!(serde_json::from_str::<RunningFloat>(&json).is_err());
+        assert!(serde_json::from_str::<RunningInteger>("{}").is_err());
+    }
+
+    #[test]
+    fn run_tests() {
+        test_fields ();
+        test_version();
+        test_export ();
+
+        #[cfg(feature = "serde")]
+        test_serde  ();
+    }
+}
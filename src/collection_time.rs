@@ -0,0 +1,294 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * CollectionTime
+//!     * CollectionTime measures how long an instance has been collecting
+//!       samples, so that reports show the period that the counts and
+//!       means cover.  The period starts when the clock is attached and
+//!       restarts when the instance is cleared.
+//!
+//!     * Once set_collection_clock() has been invoked on an instance,
+//!       print() adds a "Collected" line giving the period, and a
+//!       "Sample Rate" line giving the number of samples recorded per
+//!       second over the period.  collection_seconds() returns the
+//!       period.
+//!
+//!     * RunningInteger, RunningTime, RunningFloat, IntegerWindow,
+//!       TimeWindow, and FloatWindow support a collection clock.  The
+//!       window types compute the rate from all the samples recorded,
+//!       not just those in the window.
+//!
+//!     * The clock is read via finish(), so it should not be shared
+//!       with other users.  RcSet and ArcSet provide
+//!       set_collection_clocks(), which creates a clock for each member
+//!       using a TimerFactory, like time::default_timer_factory().
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::running_integer::RunningInteger;
+//!     use rustics::time::DurationTimer;
+//!
+//!     let mut sizes = RunningInteger::new("Packet Sizes", &None);
+//!
+//!     assert!(sizes.supports_collection_time());
+//!     assert!(sizes.collection_seconds().is_none());
+//!
+//!     sizes.set_collection_clock(Some(DurationTimer::new_box()));
+//!
+//!     for i in 1..=100 {
+//!         sizes.record_i64(i);
+//!     }
+//!
+//!     // The output includes the period covered and the sample rate.
+//!
+//!     sizes.print();
+//!
+//!     assert!(sizes.collection_seconds().unwrap() >= 0.0);
+//!```
+
+use std::cell::Cell;
+
+use super::TimerBox;
+use super::Printer;
+use super::printable::Printable;
+use super::timer_mut;
+
+/// CollectionTime keeps the time since an instance started
+/// collecting samples.
+
+#[derive(Clone)]
+pub struct CollectionTime {
+    clock:    TimerBox,
+    elapsed:  Cell<i64>,
+}
+
+impl CollectionTime {
+    /// Creates an instance, starting the clock.
+
+    pub fn new(clock: TimerBox) -> CollectionTime {
+        timer_mut!(clock).start();
+
+        let elapsed = Cell::new(0);
+
+        CollectionTime { clock, elapsed }
+    }
+
+    /// Returns the clock.
+
+    pub fn clock(&self) -> TimerBox {
+        self.clock.clone()
+    }
+
+    /// Starts a new collection period, as when an instance is cleared.
+
+    pub fn restart(&mut self) {
+        timer_mut!(self.clock).start();
+        self.elapsed.set(0);
+    }
+
+    /// Returns the length of the collection period in clock ticks.
+
+    pub fn ticks(&self) -> i64 {
+        let interval = timer_mut!(self.clock).finish();
+
+        self.elapsed.set(self.elapsed.get() + interval);
+        self.elapsed.get()
+    }
+
+    /// Returns the length of the collection period in seconds.
+
+    pub fn seconds(&self) -> f64 {
+        let ticks = self.ticks();
+        let hz    = self.clock.borrow().hz();
+
+        ticks as f64 / hz as f64
+    }
+
+    /// Returns the number of samples per second over the collection
+    /// period, or None if no time has passed.
+
+    pub fn rate(&self, samples: u64) -> Option<f64> {
+        let seconds = self.seconds();
+
+        if seconds <= 0.0 {
+            return None;
+        }
+
+        Some(samples as f64 / seconds)
+    }
+
+    /// Prints the collection period and the sample rate, given the
+    /// number of samples recorded during the period.
+
+    pub fn print(&self, samples: u64, printer: &mut dyn Printer) {
+        let ticks = self.ticks();
+        let hz    = self.clock.borrow().hz();
+
+        if hz > i64::MAX as u128 {
+            panic!("CollectionTime::print:  The clock hz value is too large.");
+        }
+
+        Printable::print_time("Collected", ticks as f64, hz as i64, printer);
+
+        if ticks > 0 {
+            let rate = samples as f64 / (ticks as f64 / hz as f64);
+
+            Printable::print_float_unit("Sample Rate", rate, "per second", printer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use crate::Rustics;
+    use crate::PrinterBox;
+    use crate::running_integer::RunningInteger;
+    use crate::float_window::FloatWindow;
+    use crate::rc_sets::RcSet;
+    use crate::time::DurationTimer;
+    use crate::capture_printer::CapturePrinter;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
+
+    fn test_collection_time() {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+
+        let mut collection = CollectionTime::new(timer);
+
+        assert!(collection.seconds()   == 0.0);
+        assert!(collection.rate(100).is_none());
+
+        // Move the clock forward three minutes.
+
+        clock.borrow_mut().current += 180_000_000_000;
+
+        assert!(collection.seconds()  == 180.0);
+        assert!(collection.rate(360) == Some(2.0));
+
+        // The period accumulates across reads.
+
+        clock.borrow_mut().current += 20_000_000_000;
+
+        assert!(collection.seconds() == 200.0);
+
+        collection.restart();
+
+        assert!(collection.seconds() == 0.0);
+    }
+
+    fn test_print() {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+        let capture   = Rc::new(RefCell::new(CapturePrinter::new()));
+        let printer   = Some(capture.clone() as PrinterBox);
+
+        let mut stats = RunningInteger::new("Collection Test", &None);
+
+        stats.set_collection_clock(Some(timer));
+
+        for i in 0..192 {
+            stats.record_i64(i);
+        }
+
+        clock.borrow_mut().current += 192_000_000_000;
+
+        assert!(stats.collection_seconds() == Some(192.0));
+
+        stats.print_opts(printer.clone(), None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(lines.iter().any(|line| line == "    Collected           3.200 minutes"));
+        assert!(lines.iter().any(|line| line.starts_with("    Sample Rate      +1.00000 e+0")));
+
+        // Removing the clock removes the lines.
+
+        stats.set_collection_clock(None);
+        stats.print_opts(printer, None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(stats.collection_seconds().is_none());
+        assert!(!lines.iter().any(|line| line.contains("Collected")));
+    }
+
+    fn test_sets() {
+        let mut set     = RcSet::new("Collection Set", 4, 1, &None);
+        let     integer = set.add_running_integer("Integer", None);
+        let     counter = set.add_counter("Counter", None);
+
+        set.set_collection_clocks(Some(DurationTimer::new_box));
+
+        // Members and subsets added later get clocks, too.
+
+        let subset = set.add_subset("Subset", 2, 0);
+        let window = subset.borrow_mut().add_integer_window("Window", 8, None);
+        let time   = set.add_time_window("Time", 8, DurationTimer::new_box());
+
+        assert!(integer.borrow().collection_seconds().is_some());
+        assert!(window .borrow().collection_seconds().is_some());
+        assert!(time   .borrow().collection_seconds().is_some());
+        assert!(counter.borrow().collection_seconds().is_none());
+
+        set.set_collection_clocks(None);
+
+        assert!(integer.borrow().collection_seconds().is_none());
+        assert!(window .borrow().collection_seconds().is_none());
+    }
+
+    fn test_window_rate() {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+        let capture   = Rc::new(RefCell::new(CapturePrinter::new()));
+        let printer   = Some(capture.clone() as PrinterBox);
+
+        let mut window = FloatWindow::new("Window Rate", 4, &None);
+
+        window.set_collection_clock(Some(timer));
+
+        // The rate counts all the samples, not just the four in the
+        // window.
+
+        for i in 0..20 {
+            window.record_f64(i as f64);
+        }
+
+        clock.borrow_mut().current += 10_000_000_000;
+
+        window.print_opts(printer, None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(lines.iter().any(|line| line == "    Collected          10.000 seconds"));
+        assert!(lines.iter().any(|line| line.starts_with("    Sample Rate      +2.00000 e+0")));
+
+        window.clear();
+
+        assert!(window.collection_seconds() == Some(0.0));
+    }
+
+    #[test]
+    fn run_tests() {
+        test_collection_time();
+        test_print          ();
+        test_sets           ();
+        test_window_rate    ();
+    }
+}
@@ -0,0 +1,479 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * ColumnarBatch
+//!     * ColumnarBatch holds a table of statistics as typed columns, in
+//!       the layout of an Arrow record batch:  each column has a name, a
+//!       type, and one value per row.  It is meant for analysis with
+//!       standard data tools rather than for reading.
+//!
+//!     * snapshot_batch() makes a batch with one row per member of a
+//!       SetSnapshot, giving the title, the class, the count, the sum,
+//!       and the summary statistics.
+//!
+//!     * integer_window_batch() and float_window_batch() make a batch
+//!       with one row per sample in a window, from oldest to newest.
+//!
+//!     * Each Column maps directly onto an Arrow array:  Column::U64
+//!       onto a UInt64Array, Column::I64 onto an Int64Array, Column::F64
+//!       onto a Float64Array, and Column::Utf8 onto a StringArray.
+//!       data_type() returns the name of the Arrow type.
+//!
+//!     * With the "arrow" Cargo feature, to_record_batch() converts a
+//!       batch into an Arrow RecordBatch.  With the "parquet" feature,
+//!       write_parquet() writes a batch as a Parquet file.  Without
+//!       these features, the module uses no other crates.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::rc_sets::RcSet;
+//!     use rustics::rustics_set::RusticsSet;
+//!     use rustics::columnar::Column;
+//!     use rustics::columnar::snapshot_batch;
+//!
+//!     let mut set     = RcSet::new("Server", 4, 0, &None);
+//!     let     latency = set.add_running_integer("Latency", None);
+//!     let     sizes   = set.add_running_integer("Sizes",   None);
+//!
+//!     for i in 1..=100 {
+//!         latency.borrow_mut().record_i64(i);
+//!         sizes  .borrow_mut().record_i64(2 * i);
+//!     }
+//!
+//!     let batch = snapshot_batch(&set.snapshot());
+//!
+//!     assert!(batch.num_rows() == 2);
+//!
+//!     // Each column is a typed vector.
+//!
+//!     if let Some(Column::F64(means)) = batch.column("mean") {
+//!         assert!(means == &vec![ 50.5, 101.0 ]);
+//!     } else {
+//!         panic!("The mean column is missing.");
+//!     }
+//!
+//!     assert!(batch.schema()[0] == ("title".to_string(), "Utf8"));
+//!```
+
+use super::Rustics;
+use super::snapshot::SetSnapshot;
+use super::snapshot::MemberState;
+use super::integer_window::IntegerWindow;
+use super::float_window::FloatWindow;
+
+#[cfg(feature = "arrow")]
+use std::sync::Arc;
+#[cfg(feature = "arrow")]
+use arrow_array::ArrayRef;
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatch;
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatchOptions;
+#[cfg(feature = "arrow")]
+use arrow_schema::ArrowError;
+#[cfg(feature = "arrow")]
+use arrow_schema::DataType;
+#[cfg(feature = "arrow")]
+use arrow_schema::Field;
+#[cfg(feature = "arrow")]
+use arrow_schema::Schema;
+#[cfg(feature = "parquet")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parquet")]
+use parquet::errors::ParquetError;
+
+/// Column holds the values of one column of a batch.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    U64(Vec<u64>),
+    I64(Vec<i64>),
+    F64(Vec<f64>),
+    Utf8(Vec<String>),
+}
+
+impl Column {
+    /// Returns the number of values in the column.
+
+    pub fn len(&self) -> usize {
+        match self {
+            Column::U64 (values) => values.len(),
+            Column::I64 (values) => values.len(),
+            Column::F64 (values) => values.len(),
+            Column::Utf8(values) => values.len(),
+        }
+    }
+
+    /// Returns true if the column has no values.
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the name of the corresponding Arrow data type.
+
+    pub fn data_type(&self) -> &'static str {
+        match self {
+            Column::U64 (_) => "UInt64",
+            Column::I64 (_) => "Int64",
+            Column::F64 (_) => "Float64",
+            Column::Utf8(_) => "Utf8",
+        }
+    }
+
+    /// Returns the Arrow data type of the column.
+
+    #[cfg(feature = "arrow")]
+    pub fn arrow_type(&self) -> DataType {
+        match self {
+            Column::U64 (_) => DataType::UInt64,
+            Column::I64 (_) => DataType::Int64,
+            Column::F64 (_) => DataType::Float64,
+            Column::Utf8(_) => DataType::Utf8,
+        }
+    }
+
+    /// Converts the column into an Arrow array.
+
+    #[cfg(feature = "arrow")]
+    pub fn to_array(&self) -> ArrayRef {
+        use arrow_array::UInt64Array;
+        use arrow_array::Int64Array;
+        use arrow_array::Float64Array;
+        use arrow_array::StringArray;
+
+        match self {
+            Column::U64 (values) => Arc::new(UInt64Array ::from(values.clone())),
+            Column::I64 (values) => Arc::new(Int64Array  ::from(values.clone())),
+            Column::F64 (values) => Arc::new(Float64Array::from(values.clone())),
+            Column::Utf8(values) => Arc::new(StringArray ::from(values.clone())),
+        }
+    }
+}
+
+/// ColumnarBatch is a table of named columns of equal length.
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ColumnarBatch {
+    names:    Vec<String>,
+    columns:  Vec<Column>,
+}
+
+impl ColumnarBatch {
+    /// Creates an empty batch.
+
+    pub fn new() -> ColumnarBatch {
+        let names   = Vec::new();
+        let columns = Vec::new();
+
+        ColumnarBatch { names, columns }
+    }
+
+    /// Adds a column.  All the columns must have the same length, and
+    /// the names must be unique.
+
+    pub fn push(&mut self, name: &str, column: Column) {
+        if self.names.iter().any(|existing| existing == name) {
+            panic!("ColumnarBatch::push:  The column \"{}\" already exists.", name);
+        }
+
+        if !self.columns.is_empty() && column.len() != self.num_rows() {
+            panic!("ColumnarBatch::push:  The column \"{}\" has the wrong length.", name);
+        }
+
+        self.names.push(name.to_string());
+        self.columns.push(column);
+    }
+
+    /// Returns the number of rows.
+
+    pub fn num_rows(&self) -> usize {
+        match self.columns.first() {
+            Some(column) => column.len(),
+            None         => 0,
+        }
+    }
+
+    /// Returns the number of columns.
+
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns the column with the given name, if any.
+
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        let index = self.names.iter().position(|existing| existing == name)?;
+
+        Some(&self.columns[index])
+    }
+
+    /// Returns the names and the columns, in order.
+
+    pub fn columns(&self) -> impl Iterator<Item = (&str, &Column)> + '_ {
+        self.names.iter().map(|name| name.as_str()).zip(self.columns.iter())
+    }
+
+    /// Returns the name and the Arrow type name of each column.
+
+    pub fn schema(&self) -> Vec<(String, &'static str)> {
+        self.columns().map(|(name, column)| (name.to_string(), column.data_type())).collect()
+    }
+
+    /// Converts the batch into an Arrow RecordBatch.  None of the
+    /// columns are nullable.
+
+    #[cfg(feature = "arrow")]
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let fields =
+            self.columns().map(|(name, column)| Field::new(name, column.arrow_type(), false));
+
+        let schema  = Arc::new(Schema::new(fields.collect::<Vec<Field>>()));
+        let arrays  = self.columns.iter().map(|column| column.to_array()).collect();
+        let options = RecordBatchOptions::new().with_row_count(Some(self.num_rows()));
+
+        RecordBatch::try_new_with_options(schema, arrays, &options)
+    }
+
+    /// Writes the batch as a Parquet file with the default writer
+    /// properties.
+
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet<W: std::io::Write + Send>(&self, output: W) -> Result<(), ParquetError> {
+        let     batch  = self.to_record_batch()?;
+        let mut writer = ArrowWriter::try_new(output, batch.schema(), None)?;
+
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Makes a batch with one row per member of a snapshot.
+
+pub fn snapshot_batch(snapshot: &SetSnapshot) -> ColumnarBatch {
+    let members = &snapshot.members;
+    let floats  = |value: fn(&MemberState) -> f64| {
+        Column::F64(members.iter().map(value).collect())
+    };
+
+    let titles  = members.iter().map(|member| member.title.clone()).collect();
+    let classes = members.iter().map(|member| member.class.clone()).collect();
+    let counts  = members.iter().map(|member| member.count).collect();
+
+    let mut batch = ColumnarBatch::new();
+
+    batch.push("title",              Column::Utf8(titles ));
+    batch.push("class",              Column::Utf8(classes));
+    batch.push("count",              Column::U64 (counts ));
+    batch.push("sum",                floats(|member| member.sum                       ));
+    batch.push("mean",               floats(|member| member.summary.mean              ));
+    batch.push("standard_deviation", floats(|member| member.summary.standard_deviation));
+    batch.push("variance",           floats(|member| member.summary.variance          ));
+    batch.push("skewness",           floats(|member| member.summary.skewness          ));
+    batch.push("kurtosis",           floats(|member| member.summary.kurtosis          ));
+    batch.push("min",                floats(|member| member.summary.min               ));
+    batch.push("max",                floats(|member| member.summary.max               ));
+    batch
+}
+
+// Make the columns common to the window batches.
+
+fn window_batch(name: &str, samples: Column) -> ColumnarBatch {
+    let rows      = samples.len();
+    let names     = vec![ name.to_string(); rows ];
+    let positions = (0..rows as u64).collect();
+
+    let mut batch = ColumnarBatch::new();
+
+    batch.push("name",     Column::Utf8(names    ));
+    batch.push("position", Column::U64 (positions));
+    batch.push("sample",   samples                );
+    batch
+}
+
+/// Makes a batch with one row per sample in an IntegerWindow.  The
+/// position column counts from the oldest sample.
+
+pub fn integer_window_batch(window: &IntegerWindow) -> ColumnarBatch {
+    let samples = window.iter().copied().collect();
+
+    window_batch(&window.name(), Column::I64(samples))
+}
+
+/// Makes a batch with one row per sample in a FloatWindow.
+
+pub fn float_window_batch(window: &FloatWindow) -> ColumnarBatch {
+    let samples = window.iter().copied().collect();
+
+    window_batch(&window.name(), Column::F64(samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rc_sets::RcSet;
+    use crate::rustics_set::RusticsSet;
+
+    fn test_snapshot_batch() {
+        let mut set     = RcSet::new("Batch Set", 4, 1, &None);
+        let     integer = set.add_running_integer("Integer", None);
+        let     counter = set.add_counter("Counter", None);
+
+        for i in 1..=10 {
+            integer.borrow_mut().record_i64(i);
+            counter.borrow_mut().record_event();
+        }
+
+        let batch = snapshot_batch(&set.snapshot());
+
+        assert!(batch.num_rows()    == 2 );
+        assert!(batch.num_columns() == 11);
+        assert!(batch.column("missing").is_none());
+
+        let schema = batch.schema();
+
+        assert!(schema[2] == ("count".to_string(), "UInt64" ));
+        assert!(schema[3] == ("sum"  .to_string(), "Float64"));
+
+        assert!(batch.column("count") == Some(&Column::U64(vec![ 10, 10 ])));
+        assert!(batch.column("sum")   == Some(&Column::F64(vec![ 55.0, 10.0 ])));
+
+        let classes = Column::Utf8(vec![ "integer".to_string(), "counter".to_string() ]);
+
+        assert!(batch.column("class") == Some(&classes));
+    }
+
+    fn test_window_batches() {
+        let mut integers = IntegerWindow::new("Integers", 3, &None);
+        let mut floats   = FloatWindow::new("Floats", 3, &None);
+
+        for i in 1..=5 {
+            integers.record_i64(i);
+            floats  .record_f64(i as f64 / 2.0);
+        }
+
+        let batch = integer_window_batch(&integers);
+
+        assert!(batch.num_rows() == 3);
+        assert!(batch.column("sample")   == Some(&Column::I64(vec![ 3, 4, 5 ])));
+        assert!(batch.column("position") == Some(&Column::U64(vec![ 0, 1, 2 ])));
+
+        let batch = float_window_batch(&floats);
+
+        assert!(batch.column("sample") == Some(&Column::F64(vec![ 1.5, 2.0, 2.5 ])));
+        assert!(batch.schema()[0]      == ("name".to_string(), "Utf8"));
+
+        let empty = ColumnarBatch::new();
+
+        assert!(empty.num_rows() == 0);
+    }
+
+    #[cfg(feature = "arrow")]
+    fn test_record_batch() {
+        use arrow_array::Array;
+        use arrow_array::Int64Array;
+        use arrow_array::StringArray;
+
+        let mut window = IntegerWindow::new("Integers", 4, &None);
+
+        for i in 1..=3 {
+            window.record_i64(i * 10);
+        }
+
+        let batch  = integer_window_batch(&window);
+        let record = batch.to_record_batch().unwrap();
+        let schema = record.schema();
+
+        assert!(record.num_rows()    == 3);
+        assert!(record.num_columns() == 3);
+        assert!(schema.field(0).name()      == "name"            );
+        assert!(schema.field(1).data_type() == &DataType::UInt64 );
+        assert!(schema.field(2).data_type() == &DataType::Int64  );
+        assert!(!schema.field(2).is_nullable());
+
+        let samples = record.column(2).as_any().downcast_ref::<Int64Array>().unwrap();
+        let names   = record.column(0).as_any().downcast_ref::<StringArray>().unwrap();
+
+        assert!(samples.values().to_vec() == vec![ 10, 20, 30 ]);
+        assert!(names.value(1)            == "Integers"         );
+        assert!(samples.null_count()      == 0                  );
+
+        // An empty batch converts to an empty RecordBatch.
+
+        let empty = ColumnarBatch::new().to_record_batch().unwrap();
+
+        assert!(empty.num_rows()    == 0);
+        assert!(empty.num_columns() == 0);
+    }
+
+    #[cfg(feature = "parquet")]
+    fn test_parquet() {
+        use arrow_array::Float64Array;
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let mut set     = RcSet::new("Parquet Set", 4, 0, &None);
+        let     integer = set.add_running_integer("Integer", None);
+
+        for i in 1..=10 {
+            integer.borrow_mut().record_i64(i);
+        }
+
+        let mut path = std::env::temp_dir();
+
+        path.push(format!("rustics_parquet_{}.parquet", std::process::id()));
+
+        let batch = snapshot_batch(&set.snapshot());
+        let file  = std::fs::File::create(&path).unwrap();
+
+        batch.write_parquet(file).unwrap();
+
+        // Read the file back and check the contents.
+
+        let file    = std::fs::File::open(&path).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        let reader  = builder.build().unwrap();
+        let batches = reader.collect::<Result<Vec<RecordBatch>, ArrowError>>().unwrap();
+
+        let _ = std::fs::remove_file(&path);
+
+        assert!(batches.len()           == 1 );
+        assert!(batches[0].num_rows()    == 1 );
+        assert!(batches[0].num_columns() == 11);
+
+        let record = &batches[0];
+        let means  = record.column_by_name("mean").unwrap();
+        let means  = means.as_any().downcast_ref::<Float64Array>().unwrap();
+
+        assert!(means.value(0) == 5.5);
+        assert!(record.schema() == batch.to_record_batch().unwrap().schema());
+    }
+
+    #[test]
+    fn run_tests() {
+        test_snapshot_batch();
+        test_window_batches();
+
+        #[cfg(feature = "arrow")]
+        test_record_batch();
+
+        #[cfg(feature = "parquet")]
+        test_parquet();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_length_mismatch() {
+        let mut batch = ColumnarBatch::new();
+
+        batch.push("a", Column::U64(vec![ 1, 2 ]));
+        batch.push("b", Column::U64(vec![ 1    ]));
+    }
+}
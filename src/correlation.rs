@@ -0,0 +1,434 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * Correlation
+//!     * Correlation records (x, y) pairs, like a request size and the
+//!       latency of the request, and reports the covariance, the Pearson
+//!       correlation coefficient, and the slope and intercept of the
+//!       least-squares line that predicts y from x.  The raw samples are
+//!       not kept.
+//!
+//!     * Pairs are recorded via record_pair().  The Rustics record
+//!       methods take a single value, so they aren't supported.  In an
+//!       ArcSet, use add_correlation(), which returns a typed handle.  In
+//!       an RcSet, create the instance in an `Rc<RefCell<Correlation>>`
+//!       and pass a clone to add_member().
+//!
+//!     * Via the Rustics interface, count() returns the number of pairs,
+//!       and the summary statistics, like mean() and max_f64(), describe
+//!       the y values.  x_moments() and y_moments() give the statistics
+//!       for each coordinate.
+//!
+//!     * Pairs with a NaN or infinite value are not recorded.  They are
+//!       counted, and rejected() returns the count.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::arc_sets::ArcSet;
+//!
+//!     let mut set     = ArcSet::new("Server", 4, 0, &None);
+//!     let     latency = set.add_correlation("Size vs Latency");
+//!
+//!     // The latency is 100 plus 2 per byte.
+//!
+//!     for size in 1..=100 {
+//!         let size = size as f64;
+//!
+//!         latency.lock_typed().record_pair(size, 100.0 + 2.0 * size);
+//!     }
+//!
+//!     let correlation = latency.lock_typed();
+//!
+//!     assert!(correlation.count() == 100);
+//!     assert!((correlation.correlation().unwrap() - 1.0  ).abs() < 1.0e-12);
+//!     assert!((correlation.slope().unwrap()       - 2.0  ).abs() < 1.0e-12);
+//!     assert!((correlation.intercept().unwrap()   - 100.0).abs() < 1.0e-9 );
+//!
+//!     correlation.print();
+//!```
+
+use super::Rustics;
+use super::Histogram;
+use super::Printer;
+use super::PrintOption;
+use super::PrinterOption;
+use super::ExportStats;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use super::sum::CompensatedSum;
+use super::printable::Printable;
+use super::custom::CustomCommon;
+use super::custom::Moments;
+use super::rustics_common_methods;
+use super::rustics_moment_methods;
+use super::rustics_default_methods;
+use super::hier_member_impl;
+
+/// Correlation keeps the statistics of a stream of (x, y) pairs.
+
+#[derive(Clone)]
+pub struct Correlation {
+    common:     CustomCommon,
+    x:          Moments,
+    y:          Moments,
+    co_moment:  CompensatedSum,
+    rejected:   u64,
+}
+
+impl Correlation {
+    pub fn new(name: &str, print_opts: &PrintOption) -> Correlation {
+        let common    = CustomCommon::new(name, print_opts);
+        let x         = Moments::new();
+        let y         = Moments::new();
+        let co_moment = CompensatedSum::new(0.0);
+        let rejected  = 0;
+
+        Correlation { common, x, y, co_moment, rejected }
+    }
+
+    /// Records a pair.  The co-moment is updated with the distance of
+    /// x from the old mean and of y from the new mean, which gives the
+    /// exact sum of the products of the distances from the means.
+
+    pub fn record_pair(&mut self, x: f64, y: f64) {
+        if !x.is_finite() || !y.is_finite() {
+            self.rejected += 1;
+            return;
+        }
+
+        let distance_x = x - self.x.mean();
+
+        self.x.record(x);
+        self.y.record(y);
+
+        self.co_moment.add(distance_x * (y - self.y.mean()));
+    }
+
+    /// Returns the number of pairs that weren't recorded because a
+    /// value was NaN or infinite.
+
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+
+    /// Returns the statistics for the x values.
+
+    pub fn x_moments(&self) -> &Moments {
+        &self.x
+    }
+
+    /// Returns the statistics for the y values.
+
+    pub fn y_moments(&self) -> &Moments {
+        &self.y
+    }
+
+    /// Returns the sample covariance, or zero if fewer than two pairs
+    /// have been recorded.
+
+    pub fn covariance(&self) -> f64 {
+        if self.x.count() < 2 {
+            return 0.0;
+        }
+
+        self.co_moment.value() / (self.x.count() - 1) as f64
+    }
+
+    /// Returns the Pearson correlation coefficient, or None if either
+    /// coordinate has no variation.
+
+    pub fn correlation(&self) -> Option<f64> {
+        let product = self.x.moment_2() * self.y.moment_2();
+
+        if self.x.count() < 2 || product <= 0.0 {
+            return None;
+        }
+
+        // Rounding can push the result slightly past 1.
+
+        let result = self.co_moment.value() / product.sqrt();
+
+        Some(result.clamp(-1.0, 1.0))
+    }
+
+    /// Returns the slope of the least-squares line for y as a function
+    /// of x, or None if the x values have no variation.
+
+    pub fn slope(&self) -> Option<f64> {
+        let moment_2 = self.x.moment_2();
+
+        if self.x.count() < 2 || moment_2 <= 0.0 {
+            return None;
+        }
+
+        Some(self.co_moment.value() / moment_2)
+    }
+
+    /// Returns the intercept of the least-squares line.
+
+    pub fn intercept(&self) -> Option<f64> {
+        let slope = self.slope()?;
+
+        Some(self.y.mean() - slope * self.x.mean())
+    }
+}
+
+impl Rustics for Correlation {
+    rustics_common_methods!(common);
+    rustics_moment_methods!(y);
+    rustics_default_methods!();
+
+    fn record_i64(&mut self, _sample: i64) {
+        panic!("Correlation::record_i64:  not supported, use record_pair");
+    }
+
+    fn record_f64(&mut self, _sample: f64) {
+        panic!("Correlation::record_f64:  not supported, use record_pair");
+    }
+
+    fn class(&self) -> &str {
+        "correlation"
+    }
+
+    fn clear(&mut self) {
+        self.x.clear();
+        self.y.clear();
+
+        self.co_moment = CompensatedSum::new(0.0);
+        self.rejected  = 0;
+    }
+
+    fn export_stats(&self) -> ExportStats {
+        self.y.export_stats(self.common.units())
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        self.common.print_with(printer, title, |printer| {
+            Printable::print_integer("Count", self.count() as i64, printer);
+
+            if self.rejected > 0 {
+                Printable::print_integer("Rejected", self.rejected as i64, printer);
+            }
+
+            if self.count() == 0 {
+                return;
+            }
+
+            Printable::print_float("X Mean",     self.x.mean(),               printer);
+            Printable::print_float("X Std Dev",  self.x.standard_deviation(), printer);
+            Printable::print_float("Y Mean",     self.y.mean(),               printer);
+            Printable::print_float("Y Std Dev",  self.y.standard_deviation(), printer);
+            Printable::print_float("Covariance", self.covariance(),           printer);
+
+            if let Some(correlation) = self.correlation() {
+                Printable::print_float("Correlation", correlation, printer);
+            }
+
+            if let (Some(slope), Some(intercept)) = (self.slope(), self.intercept()) {
+                Printable::print_float("Slope",     slope,     printer);
+                Printable::print_float("Intercept", intercept, printer);
+            }
+        });
+    }
+}
+
+// Correlation has no histogram.
+
+impl Histogram for Correlation {
+    fn print_histogram(&self, _printer: &mut dyn Printer) {
+    }
+
+    fn clear_histogram(&mut self) {
+    }
+
+    fn to_log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+}
+
+hier_member_impl!(Correlation);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use crate::PrinterBox;
+    use crate::rc_sets::RcSet;
+    use crate::arc_sets::ArcSet;
+    use crate::capture_printer::CapturePrinter;
+
+    // Compute the statistics directly for comparison.
+
+    fn exact(pairs: &[(f64, f64)]) -> (f64, f64, f64) {
+        let n       = pairs.len() as f64;
+        let mean_x  = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y  = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let sum_xy  = pairs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+        let sum_xx  = pairs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+        let sum_yy  = pairs.iter().map(|(_, y)| (y - mean_y).powi(2)).sum::<f64>();
+
+        let covariance  = sum_xy / (n - 1.0);
+        let correlation = sum_xy / (sum_xx * sum_yy).sqrt();
+        let slope       = sum_xy / sum_xx;
+
+        (covariance, correlation, slope)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1.0e-9 * a.abs().max(b.abs()).max(1.0)
+    }
+
+    fn test_statistics() {
+        let mut correlation = Correlation::new("Correlation", &None);
+        let mut pairs       = Vec::new();
+
+        assert!(correlation.covariance()  == 0.0);
+        assert!(correlation.correlation().is_none());
+        assert!(correlation.slope().is_none());
+        assert!(correlation.intercept().is_none());
+
+        for i in 1..=200 {
+            let x = i as f64;
+            let y = 3.0 * x + ((i * 37) % 11) as f64 - 40.0;
+
+            correlation.record_pair(x, y);
+            pairs.push((x, y));
+        }
+
+        let (covariance, expected, slope) = exact(&pairs);
+
+        assert!(correlation.count() == 200);
+        assert!(close(correlation.covariance(),          covariance));
+        assert!(close(correlation.correlation().unwrap(), expected  ));
+        assert!(close(correlation.slope().unwrap(),       slope     ));
+
+        let intercept = correlation.y_moments().mean() - slope * correlation.x_moments().mean();
+
+        assert!(close(correlation.intercept().unwrap(), intercept));
+
+        // The Rustics interface describes the y values.
+
+        assert!(correlation.mean()    == correlation.y_moments().mean());
+        assert!(correlation.max_f64() == pairs.iter().map(|(_, y)| *y).fold(f64::MIN, f64::max));
+        assert!(correlation.class()   == "correlation");
+
+        // A negative relation.
+
+        let mut negative = Correlation::new("Negative", &None);
+
+        for i in 0..10 {
+            negative.record_pair(i as f64, -(i as f64));
+        }
+
+        assert!(negative.correlation() == Some(-1.0));
+        assert!(negative.slope()       == Some(-1.0));
+
+        // No variation in y gives no correlation, but a slope of 0.
+
+        let mut flat = Correlation::new("Flat", &None);
+
+        for i in 0..10 {
+            flat.record_pair(i as f64, 5.0);
+        }
+
+        assert!(flat.correlation().is_none());
+        assert!(flat.slope()     == Some(0.0));
+        assert!(flat.intercept() == Some(5.0));
+    }
+
+    fn test_rejected() {
+        let mut correlation = Correlation::new("Rejected", &None);
+
+        correlation.record_pair(f64::NAN, 1.0          );
+        correlation.record_pair(1.0,      f64::INFINITY);
+        correlation.record_pair(1.0,      2.0          );
+
+        assert!(correlation.count()    == 1);
+        assert!(correlation.rejected() == 2);
+
+        correlation.clear();
+
+        assert!(correlation.count()    == 0);
+        assert!(correlation.rejected() == 0);
+    }
+
+    fn test_print() {
+        let capture = Rc::new(RefCell::new(CapturePrinter::new()));
+        let printer = Some(capture.clone() as PrinterBox);
+
+        let mut correlation = Correlation::new("Print Test", &None);
+
+        correlation.print_opts(printer.clone(), None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(lines.len() == 3);
+        assert!(lines[0]    == "Print Test");
+
+        for i in 0..4 {
+            correlation.record_pair(i as f64, 2.0 * i as f64 + 1.0);
+        }
+
+        correlation.print_opts(printer, Some("Title"));
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(lines[0] == "Title");
+        assert!(lines.iter().any(|line| line.starts_with("    Correlation      +1.00000 e+0")));
+        assert!(lines.iter().any(|line| line.starts_with("    Slope            +2.00000 e+0")));
+        assert!(lines.iter().any(|line| line.starts_with("    Intercept        +1.00000 e+0")));
+    }
+
+    fn test_sets() {
+        let mut set    = ArcSet::new("Arc Set", 4, 0, &None);
+        let     handle = set.add_correlation("Size vs Latency");
+
+        for i in 0..10 {
+            handle.lock_typed().record_pair(i as f64, i as f64);
+        }
+
+        assert!(set.find("Size vs Latency").is_some());
+        assert!(handle.member().lock().unwrap().count() == 10);
+
+        set.print();
+
+        let mut set    = RcSet::new("Rc Set", 4, 0, &None);
+        let     member = Rc::new(RefCell::new(Correlation::new("Pairs", &None)));
+
+        set.add_member(member.clone());
+        member.borrow_mut().record_pair(1.0, 2.0);
+
+        assert!(member.borrow().title() == "Rc Set ==> Pairs");
+
+        set.print();
+    }
+
+    #[test]
+    fn run_tests() {
+        test_statistics();
+        test_rejected  ();
+        test_print     ();
+        test_sets      ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_record_f64() {
+        let mut correlation = Correlation::new("Panic", &None);
+
+        correlation.record_f64(1.0);
+    }
+}
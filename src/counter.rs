@@ -15,6 +15,13 @@
 //!     * It is intended to be used for counting events or summing values
 //!       for which other statistics are not wanted.
 //!
+//!     * Amounts can be added with add().  In decrement mode, record_event(),
+//!       record_i64(), and add() subtract from the counter instead.
+//!
+//!     * A timer can be attached with set_timer().  When a timer is present,
+//!       print() also shows the rate of changes per second since the timer
+//!       was attached or the counter was last cleared.
+//!
 //! ## Example
 //!```
 //!     use rustics::Rustics;
@@ -34,7 +41,7 @@
 //!     let     printer    = None;
 //!     let     title      = None;
 //!     let     histo_opts = None;
-//!     let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+//!     let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 //!     let mut counter    = Counter::new("test counter", &print_opts);
 //!
 //!     // Add some byte counts to the counter.  record_event() adds one,
@@ -67,6 +74,7 @@
 //!```
 
 use std::any::Any;
+use std::cell::Cell;
 
 use super::Rustics;
 use super::LogHistogramBox;
@@ -76,10 +84,15 @@ use super::PrinterBox;
 use super::PrinterOption;
 use super::PrintOption;
 use super::Units;
+use super::Labels;
 use super::TimerBox;
 use super::printable::Printable;
+use super::record_hook::RecordHookBox;
+use super::record_hook::HookSample;
+use super::record_hook::call_hook;
 use super::parse_print_opts;
 use super::printer_mut;
+use super::timer_mut;
 
 /// The Counter type provides a simple counter that implements
 /// the Rustics trait.
@@ -89,9 +102,15 @@ pub struct Counter {
     name:       String,
     title:      String,
     count:      i64,
+    changes:    u64,
+    decrement:  bool,
     id:         usize,
     printer:    PrinterBox,
     units:      Units,
+    timer:      Option<TimerBox>,
+    elapsed:    Cell<i64>,
+    labels:     Labels,
+    hook:       Option<RecordHookBox>,
 }
 
 impl Counter {
@@ -101,18 +120,117 @@ impl Counter {
     pub fn new(name: &str, print_opts: &PrintOption) -> Counter {
         let (printer, title, units, _histo_opts) = parse_print_opts(print_opts, name);
 
-        let name  = String::from(name);
-        let count = 0;
-        let id    = usize::MAX;
-
-
-        Counter { name, count, id, printer, title, units }
+        let name      = String::from(name);
+        let count     = 0;
+        let changes   = 0;
+        let decrement = false;
+        let id        = usize::MAX;
+        let timer     = None;
+        let elapsed   = Cell::new(0);
+        let labels    = Labels::new();
+        let hook      = None;
+
+        Counter {
+            name,  count,  changes,  decrement,  id,  printer,  title,  units,
+            timer, elapsed, labels,  hook
+        }
     }
 
     pub fn set_units(&mut self, units: Units) {
         self.units = units;
     }
 
+    /// Adds the given amount to the counter, or subtracts it when the
+    /// counter is in decrement mode.
+
+    pub fn add(&mut self, amount: u64) {
+        if amount > i64::MAX as u64 {
+            panic!("Counter::add:  The amount is too large.");
+        }
+
+        self.apply(amount as i64);
+    }
+
+    /// Sets decrement mode.  In decrement mode, record_event(), record_i64(),
+    /// and add() subtract from the counter.  The counter is not allowed to
+    /// go below zero.
+
+    pub fn set_decrement(&mut self, decrement: bool) {
+        self.decrement = decrement;
+    }
+
+    /// Returns true if the counter is in decrement mode.
+
+    pub fn decrement(&self) -> bool {
+        self.decrement
+    }
+
+    /// Returns the total of all the amounts applied to the counter,
+    /// whether added or subtracted, since creation or the last clear.
+
+    pub fn changes(&self) -> u64 {
+        self.changes
+    }
+
+    /// Attaches a timer used to compute the rate of changes.  The timer
+    /// is restarted, so the rate is measured from this call.
+
+    pub fn set_timer(&mut self, timer: TimerBox) {
+        timer_mut!(timer).start();
+
+        self.timer = Some(timer);
+        self.elapsed.set(0);
+    }
+
+    /// Returns true if a timer is attached.
+
+    pub fn has_timer(&self) -> bool {
+        self.timer.is_some()
+    }
+
+    /// Returns the number of changes per second since the timer was
+    /// attached or the counter was last cleared.  Returns None if there
+    /// is no timer or no time has passed.
+
+    pub fn rate(&self) -> Option<f64> {
+        let timer = self.timer.as_ref()?;
+        let hz    = timer.borrow().hz();
+
+        self.read_timer();
+
+        let elapsed = self.elapsed.get();
+
+        if elapsed <= 0 {
+            return None;
+        }
+
+        let seconds = elapsed as f64 / hz as f64;
+
+        Some(self.changes as f64 / seconds)
+    }
+
+    fn read_timer(&self) {
+        if let Some(timer) = &self.timer {
+            let interval = timer_mut!(timer).finish();
+
+            self.elapsed.set(self.elapsed.get() + interval);
+        }
+    }
+
+    fn apply(&mut self, amount: i64) {
+        if self.decrement {
+            if amount > self.count {
+                panic!("Counter::apply:  The counter would go negative.");
+            }
+
+            self.count -= amount;
+        } else {
+            self.count += amount;
+        }
+
+        self.changes += amount as u64;
+    }
+
     fn event_increment(&self) -> i64 {
         1
     }
@@ -126,21 +244,24 @@ impl Rustics for Counter {
             panic!("Counter::record_i64:  The sample is negative.");
         }
 
-        self.count += sample;
+        call_hook(&self.hook, &self.name, HookSample::Integer(sample));
+        self.apply(sample);
     }
 
     fn record_f64(&mut self, _sample: f64) {
         panic!("Counter::record_f64:  not supported");
     }
 
-    /// Increments the counter by one.
+    /// Increments the counter by one, or decrements it in decrement mode.
 
     fn record_event(&mut self) {
-        self.count += self.event_increment();
+        call_hook(&self.hook, &self.name, HookSample::Event);
+        self.apply(self.event_increment());
     }
 
     fn record_event_report(&mut self) -> i64 {
-        self.count += self.event_increment();
+        call_hook(&self.hook, &self.name, HookSample::Event);
+        self.apply(self.event_increment());
         self.event_increment()
     }
 
@@ -220,7 +341,14 @@ impl Rustics for Counter {
     }
 
     fn clear(&mut self) {
-        self.count = 0;
+        self.count   = 0;
+        self.changes = 0;
+
+        if let Some(timer) = &self.timer {
+            timer_mut!(timer).start();
+        }
+
+        self.elapsed.set(0);
     }
 
     fn print(&self) {
@@ -242,10 +370,23 @@ impl Rustics for Counter {
                 &self.title
             };
 
+        let rate    = self.rate();
         let printer = printer_mut!(printer_box);
 
         printer.print(title);
         Printable::print_integer_units("Count", self.count, printer, &self.units);
+
+        if let Some(rate) = rate {
+            let unit =
+                if self.units.plural.is_empty() {
+                    "per second".to_string()
+                } else {
+                    format!("{} per second", self.units.plural)
+                };
+
+            Printable::print_float_unit("Rate", rate, &unit, printer);
+        }
+
         printer.print("");
     }
 
@@ -253,6 +394,26 @@ impl Rustics for Counter {
         self.title = String::from(title);
     }
 
+    fn supports_record_hook(&self) -> bool {
+        true
+    }
+
+    fn set_record_hook(&mut self, hook: Option<RecordHookBox>) {
+        self.hook = hook;
+    }
+
+    fn record_hook(&self) -> Option<RecordHookBox> {
+        self.hook.clone()
+    }
+
+    fn set_label(&mut self, key: &str, value: &str) {
+        self.labels.insert(key.to_string(), value.to_string());
+    }
+
+    fn labels(&self) -> Labels {
+        self.labels.clone()
+    }
+
     fn log_histogram(&self) -> Option<LogHistogramBox> {
         None
     }
@@ -321,6 +482,10 @@ mod tests {
     use crate::tests::bytes;
     use crate::tests::continuing_box;
     use crate::tests::check_printer_box;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
+    use std::rc::Rc;
+    use std::cell::RefCell;
 
     fn test_simple_counter() {
         let     test_limit  = 20;
@@ -530,7 +695,7 @@ mod tests {
         let     title      = None;
         let     units      = None;
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 
         let     name       = "Test Statistics";
         let mut stats      = Counter::new(&name, &print_opts);
@@ -543,9 +708,135 @@ mod tests {
         stats.print();
     }
 
+    fn test_add_decrement() {
+        let mut counter = Counter::new("Add Test", &None);
+
+        counter.add(100);
+        counter.record_event();
+        counter.record_i64(9);
+
+        assert!(counter.count()   == 110);
+        assert!(counter.changes() == 110);
+        assert!(!counter.decrement());
+
+        // Now count down.
+
+        counter.set_decrement(true);
+        assert!(counter.decrement());
+
+        counter.add(50);
+        counter.record_event();
+        counter.record_i64(9);
+
+        assert!(counter.count()   == 50 );
+        assert!(counter.changes() == 170);
+
+        // Check that clear resets the changes.
+
+        counter.clear();
+
+        assert!(counter.count()   == 0);
+        assert!(counter.changes() == 0);
+    }
+
+    fn test_rate() {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+
+        let mut counter = Counter::new("Rate Test", &None);
+
+        assert!(!counter.has_timer());
+        assert!(counter.rate().is_none());
+
+        counter.set_timer(timer);
+
+        assert!(counter.has_timer());
+
+        // No time has passed yet.
+
+        counter.add(1000);
+        assert!(counter.rate().is_none());
+
+        // Move the clock forward two seconds.
+
+        clock.borrow_mut().current += 2_000_000_000;
+
+        assert!(counter.rate() == Some(500.0));
+
+        // The elapsed time accumulates across reads.
+
+        counter.add(1000);
+        clock.borrow_mut().current += 2_000_000_000;
+
+        assert!(counter.rate() == Some(500.0));
+
+        // Clear restarts the timer.
+
+        counter.clear();
+        assert!(counter.rate().is_none());
+
+        counter.add(30);
+        clock.borrow_mut().current += 1_000_000_000;
+
+        assert!(counter.rate() == Some(30.0));
+    }
+
+    fn test_rate_output() {
+        let expected =
+            [
+                "Rate Statistics",
+                "    Count               3,000 bytes",
+                "    Rate             +1.50000 e+3  bytes per second",
+                ""
+            ];
+
+        let     current    = 0;
+        let     increment  = 0;
+        let     clock      = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let     timer      = ClockTimer::new_box(clock.clone());
+
+        let     printer    = Some(check_printer_box(&expected, true, true));
+        let     title      = None;
+        let     units      = bytes();
+        let     histo_opts = None;
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
+
+        let mut counter    = Counter::new("Rate Statistics", &print_opts);
+
+        counter.set_timer(timer);
+        counter.add(3000);
+
+        clock.borrow_mut().current += 2_000_000_000;
+
+        counter.print();
+    }
+
     #[test]
     fn run_tests() {
         test_simple_counter();
         test_print_output  ();
+        test_add_decrement ();
+        test_rate          ();
+        test_rate_output   ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn negative_decrement_test() {
+        let mut counter = Counter::new("test counter", &None);
+
+        counter.add(1);
+        counter.set_decrement(true);
+        counter.add(2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn add_overflow_test() {
+        let mut counter = Counter::new("test counter", &None);
+
+        counter.add(u64::MAX);
     }
 }
@@ -0,0 +1,979 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * Custom Rustics Types
+//!     * The Rustics trait has a large surface, and most of it is the
+//!       same for every type:  the name and title, the printer, the id
+//!       used by the sets, and the moment arithmetic.  This module
+//!       provides the pieces so that an application can implement its
+//!       own Rustics type without copying running_float.rs.
+//!
+//!     * CustomCommon holds the name, title, id, printer, units, and
+//!       labels, and parses a PrintOption the way the built-in types do.
+//!
+//!     * Moments keeps the count, mean, moments about the mean, and
+//!       extremes of a stream of f64 samples, and computes the summary
+//!       statistics.  It can print the standard summary lines, and it
+//!       converts to and from an Export, so that a custom type can be
+//!       summed by a Hier instance.
+//!
+//!     * CustomExporter collects Export instances for a HierGenerator and
+//!       sums them.
+//!
+//! * Macros
+//!     * rustics_common_methods!() expands inside an "impl Rustics"
+//!       block to the methods that use a CustomCommon field:  name(),
+//!       title(), set_title(), set_label(), labels(), set_id(), id(),
+//!       print(), equals(), and generic().
+//!
+//!     * rustics_moment_methods!() expands to the methods that use a
+//!       Moments field:  count(), mean(), standard_deviation(),
+//!       variance(), skewness(), kurtosis(), the extremes,
+//!       reset_extremes(), and supports_reset_extremes().  The integer
+//!       extremes and log_mode() are not supported.
+//!
+//!     * rustics_default_methods!() expands to the event and time
+//!       record methods, which panic, and to precompute(),
+//!       log_histogram(), and float_histogram(), which do nothing.
+//!
+//!     * hier_member_impl!() implements HierMember for a type that
+//!       implements Rustics and Histogram.
+//!
+//!     * The type then supplies record_i64(), record_f64(), class(),
+//!       clear(), export_stats(), and print_opts().
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::Histogram;
+//!     use rustics::Printer;
+//!     use rustics::PrintOption;
+//!     use rustics::PrinterOption;
+//!     use rustics::ExportStats;
+//!     use rustics::LogHistogramBox;
+//!     use rustics::FloatHistogramBox;
+//!     use rustics::custom::CustomCommon;
+//!     use rustics::custom::Moments;
+//!     use rustics::rustics_common_methods;
+//!     use rustics::rustics_moment_methods;
+//!     use rustics::rustics_default_methods;
+//!     use rustics::hier_member_impl;
+//!
+//!     // A minimal type that keeps the moments of ratios.
+//!
+//!     struct Ratios {
+//!         common:   CustomCommon,
+//!         moments:  Moments,
+//!     }
+//!
+//!     impl Ratios {
+//!         fn new(name: &str, print_opts: &PrintOption) -> Ratios {
+//!             let common  = CustomCommon::new(name, print_opts);
+//!             let moments = Moments::new();
+//!
+//!             Ratios { common, moments }
+//!         }
+//!     }
+//!
+//!     impl Rustics for Ratios {
+//!         rustics_common_methods!(common);
+//!         rustics_moment_methods!(moments);
+//!         rustics_default_methods!();
+//!
+//!         fn record_i64(&mut self, sample: i64) {
+//!             self.moments.record(sample as f64);
+//!         }
+//!
+//!         fn record_f64(&mut self, sample: f64) {
+//!             self.moments.record(sample);
+//!         }
+//!
+//!         fn class(&self) -> &str {
+//!             "float"
+//!         }
+//!
+//!         fn clear(&mut self) {
+//!             self.moments.clear();
+//!         }
+//!
+//!         fn export_stats(&self) -> ExportStats {
+//!             self.moments.export_stats(self.common.units())
+//!         }
+//!
+//!         fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+//!             self.common.print_with(printer, title, |printer| {
+//!                 self.moments.print(self.common.units(), printer);
+//!             });
+//!         }
+//!     }
+//!
+//!     // Supply a Histogram implementation so that the type can be a
+//!     // Hier member.  This type has no histogram.
+//!
+//!     impl Histogram for Ratios {
+//!         fn print_histogram(&self, _printer: &mut dyn Printer) { }
+//!         fn clear_histogram(&mut self) { }
+//!
+//!         fn to_log_histogram(&self) -> Option<LogHistogramBox> {
+//!             None
+//!         }
+//!
+//!         fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+//!             None
+//!         }
+//!     }
+//!
+//!     hier_member_impl!(Ratios);
+//!
+//!     let mut ratios = Ratios::new("Hit Ratios", &None);
+//!
+//!     for i in 1..=4 {
+//!         ratios.record_f64(i as f64 / 4.0);
+//!     }
+//!
+//!     assert!(ratios.count()   == 4    );
+//!     assert!(ratios.mean()    == 0.625);
+//!     assert!(ratios.max_f64() == 1.0  );
+//!     assert!(ratios.title()   == "Hit Ratios");
+//!
+//!     ratios.print();
+//!```
+
+use std::any::Any;
+
+use super::Printer;
+use super::PrinterBox;
+use super::PrinterOption;
+use super::PrintOption;
+use super::ExportStats;
+use super::Labels;
+use super::Units;
+use super::HierExporter;
+use super::parse_print_opts;
+use super::printer_mut;
+use super::sum::CompensatedSum;
+use super::merge::Export;
+use super::merge::sum_running;
+use super::printable::Printable;
+use super::printable::PrintField;
+use super::printable::PrintFields;
+use super::stats_math::EstimateData;
+use super::stats_math::estimate_moment_3;
+use super::stats_math::compute_variance;
+use super::stats_math::compute_skewness;
+use super::stats_math::compute_kurtosis;
+
+/// CustomCommon holds the data that every Rustics type keeps for
+/// naming and printing.
+
+#[derive(Clone)]
+pub struct CustomCommon {
+    name:       String,
+    title:      String,
+    id:         usize,
+    printer:    PrinterBox,
+    units:      Units,
+    labels:     Labels,
+}
+
+impl CustomCommon {
+    /// Creates the common data from the constructor parameters used by
+    /// the Rustics types.
+
+    pub fn new(name: &str, print_opts: &PrintOption) -> CustomCommon {
+        let (printer, title, units, _histo_opts) = parse_print_opts(print_opts, name);
+
+        let name   = name.to_string();
+        let id     = usize::MAX;
+        let labels = Labels::new();
+
+        CustomCommon { name, title, id, printer, units, labels }
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    /// Returns the default printer.
+
+    pub fn printer(&self) -> PrinterBox {
+        self.printer.clone()
+    }
+
+    pub fn units(&self) -> &Units {
+        &self.units
+    }
+
+    pub fn set_label(&mut self, key: &str, value: &str) {
+        self.labels.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn labels(&self) -> Labels {
+        self.labels.clone()
+    }
+
+    /// Prints a report in the standard layout:  the title, the lines
+    /// produced by the body, and a blank line.  The printer and title
+    /// default to those of the instance, as with Rustics::print_opts.
+
+    pub fn print_with(&self, printer: PrinterOption, title: Option<&str>,
+            body: impl FnOnce(&mut dyn Printer)) {
+        let printer =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        let printer = printer_mut!(printer);
+
+        printer.print(title);
+        body(printer);
+        printer.print("");
+    }
+}
+
+/// Moments keeps the summary statistics for a stream of f64 samples.
+
+#[derive(Clone, Copy, Debug)]
+pub struct Moments {
+    count:      u64,
+    mean:       CompensatedSum,
+    moment_2:   CompensatedSum,
+    cubes:      CompensatedSum,
+    moment_4:   CompensatedSum,
+    min:        f64,
+    max:        f64,
+}
+
+impl Default for Moments {
+    fn default() -> Moments {
+        Moments::new()
+    }
+}
+
+impl Moments {
+    pub fn new() -> Moments {
+        let count    = 0;
+        let mean     = CompensatedSum::new(0.0);
+        let moment_2 = CompensatedSum::new(0.0);
+        let cubes    = CompensatedSum::new(0.0);
+        let moment_4 = CompensatedSum::new(0.0);
+        let min      = f64::MAX;
+        let max      = f64::MIN;
+
+        Moments { count, mean, moment_2, cubes, moment_4, min, max }
+    }
+
+    /// Creates an instance from the sum of a set of exports.
+
+    pub fn from_export(export: &Export) -> Moments {
+        let count    = export.count;
+        let mean     = CompensatedSum::new(export.mean);
+        let moment_2 = CompensatedSum::new(export.moment_2);
+        let cubes    = CompensatedSum::new(export.cubes);
+        let moment_4 = CompensatedSum::new(export.moment_4);
+        let min      = export.min_f64;
+        let max      = export.max_f64;
+
+        Moments { count, mean, moment_2, cubes, moment_4, min, max }
+    }
+
+    /// Records a sample.  The caller handles any non-finite values.
+
+    pub fn record(&mut self, sample: f64) {
+        self.count += 1;
+
+        let distance_mean = sample - self.mean.value();
+
+        self.mean.add(distance_mean / self.count as f64);
+
+        let distance_new_mean = sample - self.mean.value();
+        let square_estimate   = distance_mean * distance_new_mean;
+
+        self.moment_2.add(square_estimate);
+        self.cubes   .add(sample.powi(3));
+        self.moment_4.add(square_estimate * square_estimate);
+
+        self.min = self.min.min(sample);
+        self.max = self.max.max(sample);
+    }
+
+    pub fn clear(&mut self) {
+        *self = Moments::new();
+    }
+
+    /// Resets the extremes while keeping the other statistics.
+
+    pub fn reset_extremes(&mut self) {
+        self.min = f64::MAX;
+        self.max = f64::MIN;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean.value()
+    }
+
+    /// Returns the sum of the squared distances from the mean.
+
+    pub fn moment_2(&self) -> f64 {
+        self.moment_2.value()
+    }
+
+    pub fn variance(&self) -> f64 {
+        compute_variance(self.count, self.moment_2.value())
+    }
+
+    pub fn standard_deviation(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    pub fn skewness(&self) -> f64 {
+        let n        = self.count as f64;
+        let mean     = self.mean.value();
+        let moment_2 = self.moment_2.value();
+        let cubes    = self.cubes.value();
+        let data     = EstimateData { n, mean, moment_2, cubes };
+
+        let moment_3 = estimate_moment_3(data);
+
+        compute_skewness(self.count, moment_2, moment_3)
+    }
+
+    pub fn kurtosis(&self) -> f64 {
+        compute_kurtosis(self.count, self.moment_2.value(), self.moment_4.value())
+    }
+
+    /// Returns the smallest sample, or f64::MAX if there are none.
+
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Returns the largest sample, or f64::MIN if there are none.
+
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Returns the statistics in the form used by the print functions.
+
+    pub fn printable(&self, units: &Units) -> Printable {
+        let n          = self.count;
+        let nans       = 0;
+        let infinities = 0;
+        let min_i64    = i64::MIN;
+        let max_i64    = i64::MAX;
+        let min_f64    = self.min;
+        let max_f64    = self.max;
+        let mode_value = 0.0;
+        let log_mode   = 0;
+        let mean       = self.mean();
+        let variance   = self.variance();
+        let skewness   = self.skewness();
+        let kurtosis   = self.kurtosis();
+        let units      = units.clone();
+
+        Printable {
+            n,         nans,  infinities,  min_i64,   max_i64,   min_f64,  max_f64,
+            log_mode,  mean,  variance,    skewness,  kurtosis,  units,    mode_value
+        }
+    }
+
+    /// Returns the statistics for Rustics::export_stats().  There are
+    /// no histograms.
+
+    pub fn export_stats(&self, units: &Units) -> ExportStats {
+        let printable       = self.printable(units);
+        let log_histogram   = None;
+        let float_histogram = None;
+
+        ExportStats { printable, log_histogram, float_histogram }
+    }
+
+    /// Returns the statistics in the form summed by a Hier instance.
+
+    pub fn export(&self) -> Export {
+        let count           = self.count;
+        let nans            = 0;
+        let infinities      = 0;
+        let mean            = self.mean.value();
+        let moment_2        = self.moment_2.value();
+        let cubes           = self.cubes.value();
+        let moment_4        = self.moment_4.value();
+        let log_histogram   = None;
+        let float_histogram = None;
+        let min_i64         = 0;
+        let max_i64         = 0;
+        let min_f64         = self.min;
+        let max_f64         = self.max;
+
+        Export {
+            count,            nans,           infinities,
+            mean,             moment_2,       cubes,
+            moment_4,         min_i64,        max_i64,
+            min_f64,          max_f64,
+            float_histogram,  log_histogram
+        }
+    }
+
+    /// Prints the standard summary lines:  the count, the extremes,
+    /// and the moment statistics.
+
+    pub fn print(&self, units: &Units, printer: &mut dyn Printer) {
+        let printable = self.printable(units);
+        let fields    = PrintFields::all().without(PrintField::ModeValue);
+
+        printable.print_common_f64_fields  (fields, printer);
+        printable.print_common_float_fields(fields, printer);
+    }
+}
+
+/// CustomExporter collects the exports of the members of a Hier
+/// level so that they can be summed.
+
+#[derive(Default)]
+pub struct CustomExporter {
+    addends:  Vec<Export>,
+}
+
+impl CustomExporter {
+    pub fn new() -> CustomExporter {
+        let addends = Vec::new();
+
+        CustomExporter { addends }
+    }
+
+    pub fn push(&mut self, addend: Export) {
+        self.addends.push(addend);
+    }
+
+    /// Returns the sum of the exports pushed.
+
+    pub fn sum(&self) -> Export {
+        sum_running(&self.addends)
+    }
+
+    pub fn count(&self) -> usize {
+        self.addends.len()
+    }
+}
+
+impl HierExporter for CustomExporter {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Expands to the Rustics methods that use a CustomCommon field.
+
+#[macro_export]
+macro_rules! rustics_common_methods {
+    ($common:ident) => {
+        fn name(&self) -> String {
+            self.$common.name()
+        }
+
+        fn title(&self) -> String {
+            self.$common.title()
+        }
+
+        fn set_title(&mut self, title: &str) {
+            self.$common.set_title(title);
+        }
+
+        fn set_label(&mut self, key: &str, value: &str) {
+            self.$common.set_label(key, value);
+        }
+
+        fn labels(&self) -> $crate::Labels {
+            self.$common.labels()
+        }
+
+        fn set_id(&mut self, id: usize) {
+            self.$common.set_id(id);
+        }
+
+        fn id(&self) -> usize {
+            self.$common.id()
+        }
+
+        fn print(&self) {
+            self.print_opts(None, None);
+        }
+
+        fn equals(&self, other: &dyn $crate::Rustics) -> bool {
+            match <dyn std::any::Any>::downcast_ref::<Self>(other.generic()) {
+                Some(other) => std::ptr::eq(self, other),
+                None        => false,
+            }
+        }
+
+        fn generic(&self) -> &dyn std::any::Any {
+            self as &dyn std::any::Any
+        }
+    }
+}
+
+/// Expands to the Rustics methods that use a Moments field.
+
+#[macro_export]
+macro_rules! rustics_moment_methods {
+    ($moments:ident) => {
+        fn count(&self) -> u64 {
+            self.$moments.count()
+        }
+
+        fn log_mode(&self) -> isize {
+            panic!("Rustics::log_mode:  not supported");
+        }
+
+        fn mean(&self) -> f64 {
+            self.$moments.mean()
+        }
+
+        fn standard_deviation(&self) -> f64 {
+            self.$moments.standard_deviation()
+        }
+
+        fn variance(&self) -> f64 {
+            self.$moments.variance()
+        }
+
+        fn skewness(&self) -> f64 {
+            self.$moments.skewness()
+        }
+
+        fn kurtosis(&self) -> f64 {
+            self.$moments.kurtosis()
+        }
+
+        fn int_extremes(&self) -> bool {
+            false
+        }
+
+        fn float_extremes(&self) -> bool {
+            true
+        }
+
+        fn min_i64(&self) -> i64 {
+            panic!("Rustics::min_i64:  not supported");
+        }
+
+        fn min_f64(&self) -> f64 {
+            self.$moments.min()
+        }
+
+        fn max_i64(&self) -> i64 {
+            panic!("Rustics::max_i64:  not supported");
+        }
+
+        fn max_f64(&self) -> f64 {
+            self.$moments.max()
+        }
+
+        fn supports_reset_extremes(&self) -> bool {
+            true
+        }
+
+        fn reset_extremes(&mut self) {
+            self.$moments.reset_extremes();
+        }
+    }
+}
+
+/// Expands to the Rustics methods that most custom types don't
+/// support.
+
+#[macro_export]
+macro_rules! rustics_default_methods {
+    () => {
+        fn record_event(&mut self) {
+            panic!("Rustics::record_event:  not supported");
+        }
+
+        fn record_event_report(&mut self) -> i64 {
+            panic!("Rustics::record_event_report:  not supported");
+        }
+
+        fn record_time(&mut self, _sample: i64) {
+            panic!("Rustics::record_time:  not supported");
+        }
+
+        fn record_interval(&mut self, _timer: &mut $crate::TimerBox) {
+            panic!("Rustics::record_interval:  not supported");
+        }
+
+        fn precompute(&mut self) {
+        }
+
+        fn log_histogram(&self) -> Option<$crate::LogHistogramBox> {
+            None
+        }
+
+        fn float_histogram(&self) -> Option<$crate::FloatHistogramBox> {
+            None
+        }
+    }
+}
+
+/// Implements HierMember for a type that implements Rustics and
+/// Histogram.
+
+#[macro_export]
+macro_rules! hier_member_impl {
+    ($type:ty) => {
+        impl $crate::hier::HierMember for $type {
+            fn to_rustics(&self) -> &dyn $crate::Rustics {
+                self
+            }
+
+            fn to_rustics_mut(&mut self) -> &mut dyn $crate::Rustics {
+                self
+            }
+
+            fn to_histogram(&self) -> &dyn $crate::Histogram {
+                self
+            }
+
+            fn as_any(&self) -> &dyn std::any::Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+                self
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use crate::Rustics;
+    use crate::Histogram;
+    use crate::LogHistogramBox;
+    use crate::FloatHistogramBox;
+    use crate::PrintOpts;
+    use crate::hier_box;
+    use crate::hier_item;
+    use crate::hier::Hier;
+    use crate::hier::HierConfig;
+    use crate::hier::HierDescriptor;
+    use crate::hier::HierDimension;
+    use crate::hier::HierGenerator;
+    use crate::hier::HierIndex;
+    use crate::hier::HierSet;
+    use crate::hier::HierWindow;
+    use crate::hier::MemberRc;
+    use crate::hier::ExporterRc;
+    use crate::rc_sets::RcSet;
+    use crate::running_float::RunningFloat;
+    use crate::capture_printer::CapturePrinter;
+
+    struct TestCustom {
+        common:   CustomCommon,
+        moments:  Moments,
+    }
+
+    impl TestCustom {
+        fn new(name: &str, print_opts: &PrintOption) -> TestCustom {
+            let common  = CustomCommon::new(name, print_opts);
+            let moments = Moments::new();
+
+            TestCustom { common, moments }
+        }
+    }
+
+    impl Rustics for TestCustom {
+        rustics_common_methods!(common);
+        rustics_moment_methods!(moments);
+        rustics_default_methods!();
+
+        fn record_i64(&mut self, sample: i64) {
+            self.moments.record(sample as f64);
+        }
+
+        fn record_f64(&mut self, sample: f64) {
+            self.moments.record(sample);
+        }
+
+        fn class(&self) -> &str {
+            "float"
+        }
+
+        fn clear(&mut self) {
+            self.moments.clear();
+        }
+
+        fn export_stats(&self) -> ExportStats {
+            self.moments.export_stats(self.common.units())
+        }
+
+        fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+            self.common.print_with(printer, title, |printer| {
+                self.moments.print(self.common.units(), printer);
+            });
+        }
+    }
+
+    impl Histogram for TestCustom {
+        fn print_histogram(&self, _printer: &mut dyn Printer) {
+        }
+
+        fn clear_histogram(&mut self) {
+        }
+
+        fn to_log_histogram(&self) -> Option<LogHistogramBox> {
+            None
+        }
+
+        fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+            None
+        }
+    }
+
+    hier_member_impl!(TestCustom);
+
+    struct TestGenerator {
+    }
+
+    impl HierGenerator for TestGenerator {
+        fn make_member(&self, name: &str, print_opts: &PrintOption) -> MemberRc {
+            hier_box!(TestCustom::new(name, print_opts))
+        }
+
+        fn make_window(&self, _name: &str, _window: &HierWindow, _print_opts: &PrintOption)
+                -> Box<dyn Rustics> {
+            panic!("TestGenerator::make_window:  not supported");
+        }
+
+        fn make_from_exporter(&self, name: &str, print_opts: &PrintOption, exporter: ExporterRc)
+                -> MemberRc {
+            let exporter = exporter.borrow();
+            let exporter = exporter.as_any().downcast_ref::<CustomExporter>().unwrap();
+
+            let mut member = TestCustom::new(name, print_opts);
+
+            member.moments = Moments::from_export(&exporter.sum());
+            hier_box!(member)
+        }
+
+        fn make_exporter(&self) -> ExporterRc {
+            Rc::from(RefCell::new(CustomExporter::new()))
+        }
+
+        fn push(&self, exporter: &mut dyn HierExporter, member_rc: MemberRc) {
+            let exporter = exporter.as_any_mut().downcast_mut::<CustomExporter>().unwrap();
+            let member   = hier_item!(member_rc);
+            let member   = member.as_any().downcast_ref::<TestCustom>().unwrap();
+
+            exporter.push(member.moments.export());
+        }
+
+        fn hz(&self) -> u128 {
+            panic!("TestGenerator::hz:  not supported");
+        }
+    }
+
+    // Compare Moments with RunningFloat, which uses the same
+    // arithmetic.  RunningFloat leaves the first sample out of the
+    // sum of the cubes, so the skewness differs slightly.
+
+    fn test_moments() {
+        let mut moments = Moments::default();
+        let mut running = RunningFloat::new("Running", &None);
+
+        assert!(moments.count() == 0       );
+        assert!(moments.min()   == f64::MAX);
+
+        for i in 1..=100 {
+            let sample = (i * i) as f64 / 7.0;
+
+            moments.record(sample);
+            running.record_f64(sample);
+        }
+
+        assert!(moments.count()              == running.count()             );
+        assert!(moments.mean()               == running.mean()              );
+        assert!(moments.variance()           == running.variance()          );
+        assert!(moments.standard_deviation() == running.standard_deviation());
+        assert!((moments.skewness() - running.skewness()).abs() < 1.0e-6);
+        assert!(moments.kurtosis()           == running.kurtosis()          );
+        assert!(moments.min()                == running.min_f64()           );
+        assert!(moments.max()                == running.max_f64()           );
+
+        let copy = Moments::from_export(&moments.export());
+
+        assert!(copy.count()    == moments.count()   );
+        assert!(copy.mean()     == moments.mean()    );
+        assert!(copy.kurtosis() == moments.kurtosis());
+
+        moments.reset_extremes();
+
+        assert!(moments.max()   == f64::MIN);
+        assert!(moments.count() == 100     );
+
+        moments.clear();
+
+        assert!(moments.count() == 0  );
+        assert!(moments.mean()  == 0.0);
+    }
+
+    fn test_custom_type() {
+        let capture    = Rc::new(RefCell::new(CapturePrinter::new()));
+        let printer    = capture.clone() as PrinterBox;
+        let print_opts = Some(PrintOpts::with_printer(printer));
+
+        let mut custom = TestCustom::new("Custom", &print_opts);
+        let mut float  = RunningFloat::new("Custom", &None);
+
+        for i in 1..=10 {
+            custom.record_i64(i);
+            float .record_f64(i as f64);
+        }
+
+        assert!(custom.count()   == 10  );
+        assert!(custom.mean()    == 5.5 );
+        assert!(custom.min_f64() == 1.0 );
+        assert!(custom.max_f64() == 10.0);
+        assert!(custom.class()   == "float");
+        assert!(custom.equals(&custom));
+        assert!(!custom.equals(&float));
+
+        custom.set_title("Custom Title");
+        custom.set_label("shard", "3");
+        custom.set_id(7);
+
+        assert!(custom.title()           == "Custom Title");
+        assert!(custom.name()            == "Custom");
+        assert!(custom.id()              == 7);
+        assert!(custom.labels()["shard"] == "3");
+
+        // The report matches that of a RunningFloat, apart from the
+        // lines that need a histogram and the skewness.
+
+        custom.print();
+        float.print_opts(Some(capture.clone() as PrinterBox), Some("Custom Title"));
+
+        let lines    = capture.borrow_mut().take_lines();
+        let half     = lines.iter().position(|line| line.is_empty()).unwrap() + 1;
+        let custom   = &lines[..half];
+        let float    = &lines[half..];
+
+        assert!(custom[0] == "Custom Title");
+        assert!(custom.last().unwrap().is_empty());
+
+        for line in custom.iter().filter(|line| !line.contains("Skewness")) {
+            assert!(float.contains(line));
+        }
+
+        let stats = TestCustom::new("Stats", &None).export_stats();
+
+        assert!(stats.printable.n == 0);
+        assert!(stats.log_histogram.is_none());
+    }
+
+    fn test_sets() {
+        let mut set    = RcSet::new("Custom Set", 4, 0, &None);
+        let     custom = Rc::new(RefCell::new(TestCustom::new("Member", &None)));
+
+        set.add_member(custom.clone());
+
+        for i in 0..10 {
+            custom.borrow_mut().record_f64(i as f64);
+        }
+
+        assert!(custom.borrow().title() == "Custom Set ==> Member");
+
+        set.print();
+        set.clear();
+
+        assert!(custom.borrow().count() == 0);
+    }
+
+    fn test_hier() {
+        let dimension_0 = HierDimension::new(4, 8);
+        let dimension_1 = HierDimension::new(0, 4);
+        let dimensions  = vec![ dimension_0, dimension_1 ];
+        let descriptor  = HierDescriptor::new(dimensions, Some(10));
+        let generator   = Rc::from(RefCell::new(TestGenerator { }));
+        let name        = "Custom Hier".to_string();
+        let window      = HierWindow::None;
+        let class       = "float".to_string();
+        let print_opts  = None;
+
+        let config   = HierConfig { descriptor, generator, name, window, class, print_opts };
+        let mut hier = Hier::new(config);
+
+        for i in 0..41 {
+            hier.record_f64(i as f64);
+        }
+
+        let member = hier.index(HierIndex::new(HierSet::All, 1, 0)).unwrap();
+        let member = hier_item!(member);
+        let sum    = member.to_rustics();
+
+        assert!(sum.count()   == 40  );
+        assert!(sum.mean()    == 19.5);
+        assert!(sum.min_f64() == 0.0 );
+        assert!(sum.max_f64() == 39.0);
+
+        hier.print();
+    }
+
+    #[test]
+    fn run_tests() {
+        test_moments    ();
+        test_custom_type();
+        test_sets       ();
+        test_hier       ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_log_mode() {
+        let custom = TestCustom::new("Log Mode", &None);
+
+        let _ = custom.log_mode();
+    }
+}
@@ -0,0 +1,864 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Types
+//!
+//! * DecayingInteger
+//!     * DecayingInteger keeps exponentially-weighted statistics for i64
+//!       samples.  Each time a sample is recorded, the weight of all the
+//!       older samples is multiplied by a decay factor, so recent samples
+//!       dominate the mean and the higher moments.
+//!
+//!     * The decay rate is given as a half-life, in samples.  After that
+//!       many more samples have been recorded, the weight of a sample is
+//!       half of its original value.
+//!
+//!     * The count() method returns the number of samples recorded.  The
+//!       weight() method returns the decayed sample count, which
+//!       approaches the half-life divided by ln 2 as samples are
+//!       recorded.
+//!
+//!     * The extremes aren't decayed, so they aren't kept.  The min and
+//!       max methods panic if invoked.
+//!
+//!     * Both types report the class "decaying", so that code that
+//!       switches on the class doesn't treat them as running statistics.
+//!       The Printable from export_stats() holds the raw sample count
+//!       and placeholder extremes, as for a Counter.
+//!
+//! * DecayingFloat
+//!     * DecayingFloat implements the same statistics for f64 samples.
+//!       NaNs and infinite values are counted, but otherwise ignored.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::decaying::DecayingInteger;
+//!
+//!     // Make an instance where a sample loses half its weight after
+//!     // 100 more samples.
+//!
+//!     let mut latency = DecayingInteger::new("Latency", 100.0, &None);
+//!
+//!     // Record a long run of small values followed by a shorter
+//!     // run of larger values.
+//!
+//!     for _i in 0..10_000 {
+//!         latency.record_i64(10);
+//!     }
+//!
+//!     for _i in 0..200 {
+//!         latency.record_i64(1000);
+//!     }
+//!
+//!     // The recent samples carry 3/4 of the weight, so the mean
+//!     // is much closer to the recent value.
+//!
+//!     let mean = latency.mean();
+//!
+//!     assert!(latency.count() == 10_200);
+//!     assert!(mean > 750.0 && mean < 755.0);
+//!
+//!     latency.print();
+//!```
+
+use std::any::Any;
+
+use super::Rustics;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use super::ExportStats;
+use super::PrinterBox;
+use super::PrinterOption;
+use super::PrintOption;
+use super::Units;
+use super::TimerBox;
+use super::printable::Printable;
+use super::parse_print_opts;
+use super::printer_mut;
+
+// Decay keeps the weighted mean and the weighted moments about the
+// mean that are shared by both decaying types.  Keeping the moments
+// about the mean, rather than sums of powers, avoids the cancellation
+// that loses the variance when the samples have a large offset.
+
+#[derive(Clone)]
+struct Decay {
+    half_life:  f64,
+    factor:     f64,
+    weight:     f64,
+    mean:       f64,
+    moment_2:   f64,
+    moment_3:   f64,
+    moment_4:   f64,
+}
+
+// Contains the summary statistics computed from the weighted moments.
+
+struct DecayStats {
+    mean:       f64,
+    variance:   f64,
+    skewness:   f64,
+    kurtosis:   f64,
+}
+
+impl Decay {
+    fn new(half_life: f64) -> Decay {
+        if !(half_life.is_finite() && half_life > 0.0) {
+            panic!("Decay::new:  The half-life must be positive.");
+        }
+
+        let factor   = 0.5_f64.powf(1.0 / half_life);
+        let weight   = 0.0;
+        let mean     = 0.0;
+        let moment_2 = 0.0;
+        let moment_3 = 0.0;
+        let moment_4 = 0.0;
+
+        Decay { half_life, factor, weight, mean, moment_2, moment_3, moment_4 }
+    }
+
+    // Decay the existing moments, and then combine them with the new
+    // sample, which has a weight of 1, using the pairwise update
+    // formulae for weighted moments.  The higher moments are updated
+    // first, since they use the old values of the lower moments.
+
+    fn record(&mut self, sample: f64) {
+        let factor   = self.factor;
+        let old      = factor * self.weight;
+        let moment_2 = factor * self.moment_2;
+        let moment_3 = factor * self.moment_3;
+        let moment_4 = factor * self.moment_4;
+        let weight   = old + 1.0;
+
+        let distance = sample - self.mean;
+        let ratio    = distance / weight;
+        let term     = distance * ratio * old;
+
+        self.moment_4 =
+              moment_4
+            + term * ratio * ratio * (old * old - old + 1.0)
+            + 6.0 * ratio * ratio * moment_2
+            - 4.0 * ratio * moment_3;
+
+        self.moment_3 =
+              moment_3
+            + term * ratio * (old - 1.0)
+            - 3.0 * ratio * moment_2;
+
+        self.moment_2 = moment_2 + term;
+        self.mean    += ratio;
+        self.weight   = weight;
+    }
+
+    fn clear(&mut self) {
+        self.weight   = 0.0;
+        self.mean     = 0.0;
+        self.moment_2 = 0.0;
+        self.moment_3 = 0.0;
+        self.moment_4 = 0.0;
+    }
+
+    // Compute the weighted statistics.  The weighted sample count
+    // replaces the count in the usual formulae.  The variance is
+    // the weighted population variance, since there is no exact
+    // sample count to use for a correction.
+
+    fn compute(&self) -> DecayStats {
+        if self.weight == 0.0 {
+            return DecayStats { mean: 0.0, variance: 0.0, skewness: 0.0, kurtosis: 0.0 };
+        }
+
+        let n        = self.weight;
+        let mean     = self.mean;
+        let variance = self.moment_2.max(0.0) / n;
+
+        let (skewness, kurtosis) =
+            if variance > 0.0 {
+                let skewness = (self.moment_3 / n) / variance.powf(1.5);
+                let kurtosis = (self.moment_4.max(0.0) / n) / variance.powi(2) - 3.0;
+
+                (skewness, kurtosis)
+            } else {
+                (0.0, 0.0)
+            };
+
+        DecayStats { mean, variance, skewness, kurtosis }
+    }
+}
+
+// Print the data for either of the decaying types.
+
+fn print_decay(printer_box: PrinterBox, title: &str, printable: &Printable, decay: &Decay,
+        floats: bool) {
+    let printer = printer_mut!(printer_box);
+
+    printer.print(title);
+    Printable::print_integer("Count", printable.n as i64, printer);
+
+    if floats {
+        Printable::print_integer("NaNs",       printable.nans       as i64, printer);
+        Printable::print_integer("Infinities", printable.infinities as i64, printer);
+    }
+
+    Printable::print_float("Half-Life", decay.half_life, printer);
+    Printable::print_float("Weight",    decay.weight,    printer);
+    printable.print_common_float(printer);
+    printer.print("");
+}
+
+fn make_printable(count: u64, nans: u64, infinities: u64, decay: &Decay, units: &Units)
+        -> Printable {
+    let stats = decay.compute();
+
+    // The count is the number of samples recorded, not the decayed
+    // weight, and the extremes are placeholders, since they aren't
+    // kept.  The class tells consumers not to use them.
+
+    let n          = count;
+    let min_i64    = i64::MIN;
+    let max_i64    = i64::MAX;
+    let min_f64    = f64::MIN;
+    let max_f64    = f64::MAX;
+    let log_mode   = 0;
+    let mode_value = 0.0;
+    let mean       = stats.mean;
+    let variance   = stats.variance;
+    let skewness   = stats.skewness;
+    let kurtosis   = stats.kurtosis;
+    let units      = units.clone();
+
+    Printable {
+        n,           nans,      infinities,  min_i64,   max_i64,   min_f64,
+        max_f64,     log_mode,  mean,        variance,  skewness,  kurtosis,
+        mode_value,  units
+    }
+}
+
+/// DecayingInteger keeps exponentially-weighted statistics for
+/// i64 samples.
+
+#[derive(Clone)]
+pub struct DecayingInteger {
+    name:       String,
+    title:      String,
+    id:         usize,
+    count:      u64,
+    decay:      Decay,
+    printer:    PrinterBox,
+    units:      Units,
+}
+
+impl DecayingInteger {
+    /// Constructs an instance with the given name, half-life in samples,
+    /// and print options.  The half-life must be positive.
+
+    pub fn new(name: &str, half_life: f64, print_opts: &PrintOption) -> DecayingInteger {
+        let (printer, title, units, _histo_opts) = parse_print_opts(print_opts, name);
+
+        let name  = String::from(name);
+        let id    = usize::MAX;
+        let count = 0;
+        let decay = Decay::new(half_life);
+
+        DecayingInteger { name, title, id, count, decay, printer, units }
+    }
+
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+    }
+
+    /// Returns the half-life, in samples.
+
+    pub fn half_life(&self) -> f64 {
+        self.decay.half_life
+    }
+
+    /// Returns the decayed sample count.
+
+    pub fn weight(&self) -> f64 {
+        self.decay.weight
+    }
+
+    fn get_printable(&self) -> Printable {
+        make_printable(self.count, 0, 0, &self.decay, &self.units)
+    }
+}
+
+impl Rustics for DecayingInteger {
+    fn record_i64(&mut self, sample: i64) {
+        self.count += 1;
+        self.decay.record(sample as f64);
+    }
+
+    fn record_f64(&mut self, _sample: f64) {
+        panic!("DecayingInteger::record_f64:  not supported");
+    }
+
+    fn record_event(&mut self) {
+        panic!("DecayingInteger::record_event:  not supported");
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        panic!("DecayingInteger::record_event_report:  not supported");
+    }
+
+    fn record_time(&mut self, _sample: i64) {
+        panic!("DecayingInteger::record_time:  not supported");
+    }
+
+    fn record_interval(&mut self, _timer: &mut TimerBox) {
+        panic!("DecayingInteger::record_interval:  not supported");
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn class(&self) -> &str {
+        "decaying"
+    }
+
+    fn count(&self) -> u64 {
+        self.count
+    }
+
+    fn log_mode(&self) -> isize {
+        panic!("DecayingInteger::log_mode:  not supported");
+    }
+
+    fn mean(&self) -> f64 {
+        self.decay.compute().mean
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        self.decay.compute().variance.sqrt()
+    }
+
+    fn variance(&self) -> f64 {
+        self.decay.compute().variance
+    }
+
+    fn skewness(&self) -> f64 {
+        self.decay.compute().skewness
+    }
+
+    fn kurtosis(&self) -> f64 {
+        self.decay.compute().kurtosis
+    }
+
+    fn int_extremes(&self) -> bool {
+        false
+    }
+
+    fn float_extremes(&self) -> bool {
+        false
+    }
+
+    fn min_i64(&self) -> i64 {
+        panic!("DecayingInteger::min_i64:  not supported");
+    }
+
+    fn min_f64(&self) -> f64 {
+        panic!("DecayingInteger::min_f64:  not supported");
+    }
+
+    fn max_i64(&self) -> i64 {
+        panic!("DecayingInteger::max_i64:  not supported");
+    }
+
+    fn max_f64(&self) -> f64 {
+        panic!("DecayingInteger::max_f64:  not supported");
+    }
+
+    fn precompute(&mut self) {
+    }
+
+    fn clear(&mut self) {
+        self.count = 0;
+        self.decay.clear();
+    }
+
+    fn print(&self) {
+        self.print_opts(None, None);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        print_decay(printer_box, title, &self.get_printable(), &self.decay, false);
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = String::from(title);
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+
+    // For internal use only.
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<DecayingInteger>(other.generic()) {
+            std::ptr::eq(self, other)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn export_stats(&self) -> ExportStats {
+        let printable       = self.get_printable();
+        let log_histogram   = None;
+        let float_histogram = None;
+
+        ExportStats { printable, log_histogram, float_histogram }
+    }
+}
+
+/// DecayingFloat keeps exponentially-weighted statistics for
+/// f64 samples.
+
+#[derive(Clone)]
+pub struct DecayingFloat {
+    name:       String,
+    title:      String,
+    id:         usize,
+    count:      u64,
+    nans:       u64,
+    infinities: u64,
+    decay:      Decay,
+    printer:    PrinterBox,
+    units:      Units,
+}
+
+impl DecayingFloat {
+    /// Constructs an instance with the given name, half-life in samples,
+    /// and print options.  The half-life must be positive.
+
+    pub fn new(name: &str, half_life: f64, print_opts: &PrintOption) -> DecayingFloat {
+        let (printer, title, units, _histo_opts) = parse_print_opts(print_opts, name);
+
+        let name       = String::from(name);
+        let id         = usize::MAX;
+        let count      = 0;
+        let nans       = 0;
+        let infinities = 0;
+        let decay      = Decay::new(half_life);
+
+        DecayingFloat { name, title, id, count, nans, infinities, decay, printer, units }
+    }
+
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+    }
+
+    /// Returns the half-life, in samples.
+
+    pub fn half_life(&self) -> f64 {
+        self.decay.half_life
+    }
+
+    /// Returns the decayed sample count.
+
+    pub fn weight(&self) -> f64 {
+        self.decay.weight
+    }
+
+    pub fn nans(&self) -> u64 {
+        self.nans
+    }
+
+    pub fn infinities(&self) -> u64 {
+        self.infinities
+    }
+
+    fn get_printable(&self) -> Printable {
+        make_printable(self.count, self.nans, self.infinities, &self.decay, &self.units)
+    }
+}
+
+impl Rustics for DecayingFloat {
+    fn record_i64(&mut self, _sample: i64) {
+        panic!("DecayingFloat::record_i64:  not supported");
+    }
+
+    fn record_f64(&mut self, sample: f64) {
+        if sample.is_nan() {
+            self.nans += 1;
+            return;
+        }
+
+        if sample.is_infinite() {
+            self.infinities += 1;
+            return;
+        }
+
+        self.count += 1;
+        self.decay.record(sample);
+    }
+
+    fn record_event(&mut self) {
+        panic!("DecayingFloat::record_event:  not supported");
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        panic!("DecayingFloat::record_event_report:  not supported");
+    }
+
+    fn record_time(&mut self, _sample: i64) {
+        panic!("DecayingFloat::record_time:  not supported");
+    }
+
+    fn record_interval(&mut self, _timer: &mut TimerBox) {
+        panic!("DecayingFloat::record_interval:  not supported");
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn class(&self) -> &str {
+        "decaying"
+    }
+
+    fn count(&self) -> u64 {
+        self.count
+    }
+
+    fn log_mode(&self) -> isize {
+        panic!("DecayingFloat::log_mode:  not supported");
+    }
+
+    fn mean(&self) -> f64 {
+        self.decay.compute().mean
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        self.decay.compute().variance.sqrt()
+    }
+
+    fn variance(&self) -> f64 {
+        self.decay.compute().variance
+    }
+
+    fn skewness(&self) -> f64 {
+        self.decay.compute().skewness
+    }
+
+    fn kurtosis(&self) -> f64 {
+        self.decay.compute().kurtosis
+    }
+
+    fn int_extremes(&self) -> bool {
+        false
+    }
+
+    fn float_extremes(&self) -> bool {
+        false
+    }
+
+    fn min_i64(&self) -> i64 {
+        panic!("DecayingFloat::min_i64:  not supported");
+    }
+
+    fn min_f64(&self) -> f64 {
+        panic!("DecayingFloat::min_f64:  not supported");
+    }
+
+    fn max_i64(&self) -> i64 {
+        panic!("DecayingFloat::max_i64:  not supported");
+    }
+
+    fn max_f64(&self) -> f64 {
+        panic!("DecayingFloat::max_f64:  not supported");
+    }
+
+    fn precompute(&mut self) {
+    }
+
+    fn clear(&mut self) {
+        self.count      = 0;
+        self.nans       = 0;
+        self.infinities = 0;
+        self.decay.clear();
+    }
+
+    fn print(&self) {
+        self.print_opts(None, None);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        print_decay(printer_box, title, &self.get_printable(), &self.decay, true);
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = String::from(title);
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+
+    // For internal use only.
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<DecayingFloat>(other.generic()) {
+            std::ptr::eq(self, other)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn export_stats(&self) -> ExportStats {
+        let printable       = self.get_printable();
+        let log_histogram   = None;
+        let float_histogram = None;
+
+        ExportStats { printable, log_histogram, float_histogram }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrintOpts;
+    use crate::running_integer::RunningInteger;
+    use crate::tests::bytes;
+    use crate::tests::check_printer_box;
+
+    fn test_integer() {
+        let mut stats = DecayingInteger::new("Test Decay", 10.0, &None);
+
+        assert!(stats.count()     == 0);
+        assert!(stats.class()     == "decaying");
+        assert!(stats.mean()      == 0.0);
+        assert!(stats.half_life() == 10.0);
+
+        // A constant stream has the constant as the mean and no
+        // variance.
+
+        for _i in 0..100 {
+            stats.record_i64(5);
+        }
+
+        assert!(stats.count() == 100);
+        assert!((stats.mean() - 5.0).abs() < 1.0e-9);
+        assert!(stats.variance() < 1.0e-9);
+
+        // The weight should be close to the limit.
+
+        let limit = 1.0 / (1.0 - 0.5_f64.powf(0.1));
+
+        assert!((stats.weight() - limit).abs() / limit < 0.01);
+
+        // After one half-life, the new samples should have half the
+        // weight.
+
+        for _i in 0..10 {
+            stats.record_i64(15);
+        }
+
+        assert!((stats.mean() - 10.0).abs() < 0.1);
+        assert!((stats.standard_deviation() - 5.0).abs() < 0.1);
+
+        // After many half-lives, the old samples are forgotten.
+
+        for _i in 0..1000 {
+            stats.record_i64(15);
+        }
+
+        assert!((stats.mean() - 15.0).abs() < 1.0e-6);
+
+        stats.clear();
+
+        assert!(stats.count()  == 0);
+        assert!(stats.weight() == 0.0);
+        assert!(stats.mean()   == 0.0);
+    }
+
+    // Check the higher moments against a stream where the weights are
+    // known exactly.  With a half-life of 1, a pair of samples where
+    // the older sample has half the weight of the newer one gives the
+    // weighted distribution { 0:  1/3, 3:  2/3 }.
+
+    fn test_moments() {
+        let mut stats = DecayingFloat::new("Test Moments", 1.0, &None);
+
+        stats.record_f64(0.0);
+        stats.record_f64(3.0);
+
+        let mean     = 2.0;
+        let variance = (4.0 + 2.0) / 3.0_f64;
+        let skewness = (-8.0 + 2.0) / 3.0 / variance.powf(1.5);
+        let kurtosis = (16.0 + 2.0) / 3.0 / variance.powi(2) - 3.0;
+
+        assert!((stats.weight()   - 1.5     ).abs() < 1.0e-9);
+        assert!((stats.mean()     - mean    ).abs() < 1.0e-9);
+        assert!((stats.variance() - variance).abs() < 1.0e-9);
+        assert!((stats.skewness() - skewness).abs() < 1.0e-9);
+        assert!((stats.kurtosis() - kurtosis).abs() < 1.0e-9);
+
+        stats.record_f64(f64::NAN);
+        stats.record_f64(f64::INFINITY);
+
+        assert!(stats.count()      == 2);
+        assert!(stats.nans()       == 1);
+        assert!(stats.infinities() == 1);
+        assert!(stats.class()      == "decaying");
+
+        stats.clear();
+
+        assert!(stats.nans()       == 0);
+        assert!(stats.infinities() == 0);
+    }
+
+    // A large offset shouldn't hide the variance.  With a long
+    // half-life, the weights are nearly equal, so the results should
+    // be close to those of a RunningInteger instance.
+
+    fn test_large_offset() {
+        let mut decaying = DecayingInteger::new("Decaying", 1.0e9, &None);
+        let mut running  = RunningInteger::new("Running", &None);
+
+        for i in 0..3000 {
+            let sample = 1_000_000_000 + i % 3;
+
+            decaying.record_i64(sample);
+            running .record_i64(sample);
+        }
+
+        let expected = 2.0 / 3.0;
+
+        assert!((decaying.variance() - expected).abs() < 1.0e-3);
+        assert!((running .variance() - expected).abs() < 1.0e-3);
+        assert!((decaying.mean() - running.mean()).abs() < 1.0e-3);
+        assert!(decaying.skewness().abs() < 1.0e-3);
+        assert!((decaying.kurtosis() + 1.5).abs() < 1.0e-3);
+    }
+
+    fn test_print_output() {
+        let expected =
+            [
+                "Test Statistics",
+                "    Count                   2 ",
+                "    NaNs                    1 ",
+                "    Infinities              0 ",
+                "    Half-Life        +1.00000 e+0  ",
+                "    Weight           +1.50000 e+0  ",
+                "    Mean             +2.00000 e+0  bytes",
+                "    Std Dev          +1.41421 e+0  bytes",
+                "    Variance         +2.00000 e+0  ",
+                "    Skewness         -7.07106 e-1  ",
+                "    Kurtosis         -1.50000 e+0  ",
+                ""
+            ];
+
+        let     printer    = Some(check_printer_box(&expected, true, false));
+        let     title      = None;
+        let     units      = bytes();
+        let     histo_opts = None;
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
+
+        let mut stats      = DecayingFloat::new("Test Statistics", 1.0, &print_opts);
+
+        stats.record_f64(0.0);
+        stats.record_f64(f64::NAN);
+        stats.record_f64(3.0);
+
+        stats.print();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_half_life() {
+        let _ = DecayingInteger::new("Panic Test", 0.0, &None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_min_i64() {
+        let stats = DecayingInteger::new("Panic Test", 1.0, &None);
+
+        let _ = stats.min_i64();
+    }
+
+    #[test]
+    fn run_tests() {
+        test_integer     ();
+        test_moments     ();
+        test_large_offset();
+        test_print_output();
+    }
+}
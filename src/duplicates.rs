@@ -0,0 +1,215 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * DuplicateFilter
+//!     * DuplicateFilter uses a Bloom filter to count how many samples
+//!       have values that were seen earlier in the sample stream.
+//!
+//!     * The filter is sized for an expected number of distinct values
+//!       and a false-positive rate.  Once more distinct values than that
+//!       have been recorded, the filter overestimates the duplicates.
+//!       A value seen for the first time is counted as a duplicate only
+//!       on a false positive.
+//!
+//!     * RunningInteger instances can enable a filter via the
+//!       enable_duplicates() method.  The filter is cleared along with
+//!       the instance, so a Hier instance that enables detection on
+//!       each level 0 instance gets per-period results.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::running_integer::RunningInteger;
+//!
+//!     let mut lookups = RunningInteger::new("Cache Keys", &None);
+//!
+//!     // Track duplicates for up to 10,000 distinct keys with a 1%
+//!     // false-positive rate.
+//!
+//!     lookups.enable_duplicates(10_000, 0.01);
+//!
+//!     // Look up 1000 keys twice each.
+//!
+//!     for i in 0..2000 {
+//!         lookups.record_i64(i % 1000);
+//!     }
+//!
+//!     let fraction = lookups.duplicate_fraction().unwrap();
+//!
+//!     assert!(fraction >= 0.5 && fraction < 0.51);
+//!
+//!     lookups.print();
+//!```
+
+use super::cardinality::hash_i64;
+
+/// DuplicateFilter is a Bloom filter that counts repeated
+/// sample values.
+
+#[derive(Clone)]
+pub struct DuplicateFilter {
+    bits:       Vec<u64>,
+    bit_count:  u64,
+    hashes:     u32,
+    samples:    u64,
+    duplicates: u64,
+}
+
+impl DuplicateFilter {
+    /// Creates a filter for the given number of distinct values and
+    /// false-positive rate, which must be between 0 and 1.
+
+    pub fn new(capacity: usize, false_positive: f64) -> DuplicateFilter {
+        if capacity == 0 {
+            panic!("DuplicateFilter::new:  The capacity must be positive.");
+        }
+
+        if !(false_positive > 0.0 && false_positive < 1.0) {
+            panic!("DuplicateFilter::new:  The false positive rate is invalid.");
+        }
+
+        // Use the standard formulas for the optimal size and number
+        // of hash functions.
+
+        let ln_2      = std::f64::consts::LN_2;
+        let n         = capacity as f64;
+        let bit_count = (-n * false_positive.ln() / (ln_2 * ln_2)).ceil() as u64;
+        let bit_count = bit_count.max(64);
+        let hashes    = ((bit_count as f64 / n) * ln_2).round().max(1.0) as u32;
+        let words     = bit_count.div_ceil(64) as usize;
+        let bits      = vec![0; words];
+
+        let samples    = 0;
+        let duplicates = 0;
+
+        DuplicateFilter { bits, bit_count, hashes, samples, duplicates }
+    }
+
+    /// Records a sample, returning true if the value probably has
+    /// been seen before.
+
+    pub fn record(&mut self, sample: i64) -> bool {
+        // Derive the bit indices via double hashing.
+
+        let     hash_1 = hash_i64(sample);
+        let     hash_2 = hash_i64(hash_1 as i64) | 1;
+        let mut seen   = true;
+
+        for i in 0..self.hashes as u64 {
+            let bit  = hash_1.wrapping_add(i.wrapping_mul(hash_2)) % self.bit_count;
+            let word = (bit / 64) as usize;
+            let mask = 1_u64 << (bit % 64);
+
+            if self.bits[word] & mask == 0 {
+                seen = false;
+                self.bits[word] |= mask;
+            }
+        }
+
+        self.samples += 1;
+
+        if seen {
+            self.duplicates += 1;
+        }
+
+        seen
+    }
+
+    /// Returns the number of samples recorded.
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Returns the number of samples whose values were seen earlier.
+
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// Returns the fraction of the samples that were duplicates, or
+    /// zero if no samples have been recorded.
+
+    pub fn duplicate_fraction(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            self.duplicates as f64 / self.samples as f64
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+        self.samples    = 0;
+        self.duplicates = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_simple() {
+        let mut filter = DuplicateFilter::new(1000, 0.01);
+
+        assert!(filter.duplicate_fraction() == 0.0);
+
+        assert!(!filter.record(1));
+        assert!( filter.record(1));
+        assert!(!filter.record(2));
+
+        assert!(filter.samples()    == 3);
+        assert!(filter.duplicates() == 1);
+
+        filter.clear();
+
+        assert!(filter.samples()    == 0);
+        assert!(filter.duplicates() == 0);
+        assert!(!filter.record(1));
+    }
+
+    // Check that the false positive rate is roughly as configured.
+
+    fn test_false_positives() {
+        let     capacity = 10_000;
+        let mut filter   = DuplicateFilter::new(capacity, 0.01);
+
+        for i in 0..capacity as i64 {
+            filter.record(i * 3);
+        }
+
+        let false_positives = filter.duplicates();
+
+        println!("test_false_positives:  {} of {}", false_positives, capacity);
+        assert!(false_positives < capacity as u64 / 50);
+
+        for i in 0..capacity as i64 {
+            assert!(filter.record(i * 3));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_capacity() {
+        let _ = DuplicateFilter::new(0, 0.01);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_rate() {
+        let _ = DuplicateFilter::new(100, 1.0);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_simple         ();
+        test_false_positives();
+    }
+}
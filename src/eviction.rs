@@ -0,0 +1,286 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * EvictionPolicy
+//!     * An RcSet or ArcSet can be given a capacity for its Rustics
+//!       members.  When adding a member would exceed the capacity, the
+//!       set removes a member chosen by the eviction policy.  This keeps
+//!       sets of dynamically-created members, like per-connection
+//!       statistics, from growing without bound.
+//!
+//!     * LeastRecent evicts the member that has gone the longest without
+//!       a new sample.  The sets don't see the record calls, so a member
+//!       is considered used when its count has changed since the set
+//!       last looked, which happens each time a member is added.
+//!
+//!     * Priority evicts the member with the lowest priority, as set by
+//!       set_priority() on the set.  Members start with priority zero.
+//!       Ties are broken as for LeastRecent.
+//!
+//!     * The capacity applies only to the Rustics members of the set
+//!       itself, not to subsets or their members.  A newly-added member
+//!       is never the one evicted.
+//!
+//!     * When a set is over its capacity, it reads the count of each
+//!       member to choose the victim.  For an ArcSet, that means taking
+//!       each member lock, so don't add members or shrink the capacity
+//!       while holding a member lock from the same set.
+//!
+//! * Eviction
+//!     * Eviction keeps the capacity, the policy, and the usage data
+//!       for the members of a set.  It is used by the set
+//!       implementations.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::arc_item_mut;
+//!     use rustics::arc_sets::ArcSet;
+//!     use rustics::rustics_set::RusticsSet;
+//!     use rustics::eviction::EvictionPolicy;
+//!
+//!     let mut set = ArcSet::new("Connections", 4, 0, &None);
+//!
+//!     set.set_capacity(Some(2), EvictionPolicy::LeastRecent);
+//!
+//!     let first  = set.add_counter("Connection 1", None);
+//!     let second = set.add_counter("Connection 2", None);
+//!
+//!     // Connection 1 stays active, so connection 2 is evicted when a
+//!     // third connection arrives.
+//!
+//!     arc_item_mut!(first).record_event();
+//!
+//!     let _third = set.add_counter("Connection 3", None);
+//!
+//!     assert!(set.member_count() == 2);
+//!     assert!(set.evictions()    == 1);
+//!     assert!(set.find("Connection 1").is_some());
+//!     assert!(set.find("Connection 2").is_none());
+//!```
+
+/// EvictionPolicy selects the member removed when a set is full.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum EvictionPolicy {
+    #[default]
+    LeastRecent,
+    Priority,
+}
+
+// Keep the usage data for one member of a set.
+
+#[derive(Clone, Copy, Default)]
+struct Usage {
+    count:      u64,
+    last_used:  u64,
+    priority:   i64,
+}
+
+/// Eviction holds the eviction state for a set.  Its usage list is
+/// kept parallel to the member list of the set.
+
+#[derive(Clone, Default)]
+pub struct Eviction {
+    capacity:   Option<usize>,
+    policy:     EvictionPolicy,
+    epoch:      u64,
+    evictions:  u64,
+    usage:      Vec<Usage>,
+}
+
+impl Eviction {
+    /// Creates an Eviction instance with no capacity limit.
+
+    pub fn new(members_hint: usize) -> Eviction {
+        let capacity  = None;
+        let policy    = EvictionPolicy::default();
+        let epoch     = 0;
+        let evictions = 0;
+        let usage     = Vec::with_capacity(members_hint);
+
+        Eviction { capacity, policy, epoch, evictions, usage }
+    }
+
+    /// Sets the capacity and policy.  None removes the limit.
+
+    pub fn set_capacity(&mut self, capacity: Option<usize>, policy: EvictionPolicy) {
+        self.capacity = capacity;
+        self.policy   = policy;
+    }
+
+    /// Returns the capacity, if any.
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the eviction policy.
+
+    pub fn policy(&self) -> EvictionPolicy {
+        self.policy
+    }
+
+    /// Returns the number of members evicted so far.
+
+    pub fn evictions(&self) -> u64 {
+        self.evictions
+    }
+
+    /// Records that a member was added to the end of the member list.
+
+    pub fn push(&mut self) {
+        self.epoch += 1;
+
+        let count     = 0;
+        let last_used = self.epoch;
+        let priority  = 0;
+
+        self.usage.push(Usage { count, last_used, priority });
+    }
+
+    /// Records that the member at the given index was removed.
+
+    pub fn remove(&mut self, index: usize) {
+        self.usage.remove(index);
+    }
+
+    /// Sets the priority of the member at the given index.
+
+    pub fn set_priority(&mut self, index: usize, priority: i64) {
+        self.usage[index].priority = priority;
+    }
+
+    /// Returns the priority of the member at the given index.
+
+    pub fn priority(&self, index: usize) -> i64 {
+        self.usage[index].priority
+    }
+
+    /// Returns true if a set with the given number of members is over
+    /// its capacity.  The sets check this before gathering the counts
+    /// for victim().
+
+    pub fn is_over(&self, members: usize) -> bool {
+        match self.capacity {
+            Some(capacity) => members > capacity,
+            None           => false,
+        }
+    }
+
+    /// Returns the index of the member to evict, or None if the set is
+    /// within its capacity.  The counts give the current count of each
+    /// member, and are used to update the usage data.  If protect_last
+    /// is set, the last member isn't chosen.  The caller must remove
+    /// the member and call remove().
+
+    pub fn victim(&mut self, counts: &[u64], protect_last: bool) -> Option<usize> {
+        assert!(counts.len() == self.usage.len());
+
+        let capacity = self.capacity?;
+
+        if counts.len() <= capacity {
+            return None;
+        }
+
+        self.epoch += 1;
+
+        for (usage, count) in self.usage.iter_mut().zip(counts.iter()) {
+            if usage.count != *count {
+                usage.count     = *count;
+                usage.last_used = self.epoch;
+            }
+        }
+
+        let candidates =
+            if protect_last {
+                counts.len() - 1
+            } else {
+                counts.len()
+            };
+
+        let key =
+            |usage: &Usage| {
+                match self.policy {
+                    EvictionPolicy::LeastRecent => (0, usage.last_used),
+                    EvictionPolicy::Priority    => (usage.priority, usage.last_used),
+                }
+            };
+
+        let victim = (0..candidates).min_by_key(|i| key(&self.usage[*i]))?;
+
+        self.evictions += 1;
+        Some(victim)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_least_recent() {
+        let mut eviction = Eviction::new(4);
+
+        assert!(eviction.victim(&[], false).is_none());
+        assert!(!eviction.is_over(10));
+
+        eviction.set_capacity(Some(2), EvictionPolicy::LeastRecent);
+
+        eviction.push();
+        eviction.push();
+
+        assert!(eviction.victim(&[ 0, 0 ], false).is_none());
+        assert!(!eviction.is_over(2));
+        assert!( eviction.is_over(3));
+
+        // The first member has been used, so the second is evicted.
+        // The third member is protected.
+
+        eviction.push();
+
+        assert!(eviction.victim(&[ 1, 0, 0 ], true) == Some(1));
+        eviction.remove(1);
+
+        assert!(eviction.evictions() == 1);
+
+        // Now the third member has gone the longest without use.
+
+        eviction.push();
+
+        assert!(eviction.victim(&[ 1, 0, 0 ], true) == Some(1));
+    }
+
+    fn test_priority() {
+        let mut eviction = Eviction::new(4);
+
+        eviction.set_capacity(Some(2), EvictionPolicy::Priority);
+
+        eviction.push();
+        eviction.push();
+        eviction.push();
+
+        eviction.set_priority(0, 5);
+        eviction.set_priority(1, 2);
+
+        assert!(eviction.policy()    == EvictionPolicy::Priority);
+        assert!(eviction.capacity()  == Some(2));
+        assert!(eviction.priority(0) == 5);
+
+        // The unprotected new member has the lowest priority.
+
+        assert!(eviction.victim(&[ 0, 0, 0 ], false) == Some(2));
+        assert!(eviction.victim(&[ 0, 0, 0 ], true ) == Some(1));
+    }
+
+    #[test]
+    fn run_tests() {
+        test_least_recent();
+        test_priority    ();
+    }
+}
@@ -0,0 +1,279 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * ExternalHistogram
+//!     * ExternalHistogram is an adapter that implements the Histogram
+//!       trait over a histogram kept by some other code, for example a
+//!       histogram from another crate.
+//!
+//!     * The foreign histogram is read via a user-supplied closure that
+//!       returns the buckets as a list of (bounds, count) pairs.  The
+//!       bounds are given as a (lower, upper) pair of f64 values.
+//!
+//!     * Each bucket is mapped into a LogHistogram bucket using a single
+//!       representative value:  the midpoint of the bounds when both are
+//!       finite, otherwise the finite bound.  The result thus is an
+//!       approximation when the foreign buckets don't line up with the
+//!       pseudo-log buckets.
+//!
+//!     * The approximation can be printed using the standard Rustics
+//!       formatting and merged into an existing LogHistogram.
+//!
+//! ## Example
+//!```
+//!     use rustics::Histogram;
+//!     use rustics::log_histogram::LogHistogram;
+//!     use rustics::external_histogram::ExternalHistogram;
+//!     use rustics::stdout_printer;
+//!     use rustics::printer_mut;
+//!
+//!     // Pretend that these buckets came from a histogram in some
+//!     // other crate.  The bounds are (lower, upper) pairs.
+//!
+//!     let foreign =
+//!         vec![ ((0.0, 1.0), 10), ((1.0, 2.0), 20), ((64.0, 128.0), 5) ];
+//!
+//!     // Create the adapter.  The closure is invoked every time that
+//!     // the adapter needs the current bucket counts.
+//!
+//!     let adapter = ExternalHistogram::new(move || foreign.clone());
+//!
+//!     assert!(adapter.count() == 35);
+//!
+//!     // Print the histogram using the Rustics format.
+//!
+//!     let printer = stdout_printer();
+//!     let printer = printer_mut!(printer);
+//!
+//!     adapter.print_histogram(printer);
+//!
+//!     // Now merge the data into a LogHistogram.
+//!
+//!     let mut histogram = LogHistogram::new();
+//!
+//!     histogram.record(100);
+//!
+//!     adapter.merge_into(&mut histogram);
+//!
+//!     // The midpoint of the last foreign bucket is 96, so those
+//!     // samples land in the same bucket as our sample of 100.
+//!
+//!     assert!(histogram.positive[7] == 6);
+//!```
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::Histogram;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use super::Printer;
+use super::log_histogram::LogHistogram;
+
+/// BucketBounds gives the (lower, upper) bounds of a bucket in a
+/// foreign histogram.
+
+pub type BucketBounds = (f64, f64);
+
+/// ExternalBucket is one entry returned by the bucket provider.
+
+pub type ExternalBucket = (BucketBounds, u64);
+
+/// ExternalHistogram wraps a closure that returns the buckets of a
+/// histogram kept outside of Rustics.
+
+pub struct ExternalHistogram<F>
+        where F: Fn() -> Vec<ExternalBucket> {
+    provider: F,
+}
+
+/// Computes the value used to place a foreign bucket into a pseudo-log
+/// bucket.  Buckets with no finite bounds are treated as zero.
+
+pub fn representative_value(bounds: BucketBounds) -> i64 {
+    let (lower, upper) = bounds;
+
+    let value =
+        if lower.is_finite() && upper.is_finite() {
+            lower + (upper - lower) / 2.0
+        } else if lower.is_finite() {
+            lower
+        } else if upper.is_finite() {
+            upper
+        } else {
+            0.0
+        };
+
+    // The "as" conversion saturates at the i64 limits.
+
+    value.round() as i64
+}
+
+impl<F> ExternalHistogram<F>
+        where F: Fn() -> Vec<ExternalBucket> {
+    /// Creates a new adapter given a bucket provider.
+
+    pub fn new(provider: F) -> ExternalHistogram<F> {
+        ExternalHistogram { provider }
+    }
+
+    /// Returns the current buckets from the provider.
+
+    pub fn buckets(&self) -> Vec<ExternalBucket> {
+        (self.provider)()
+    }
+
+    /// Returns the total number of samples in the foreign histogram.
+
+    pub fn count(&self) -> u64 {
+        let mut count = 0;
+
+        for (_, bucket_count) in self.buckets().iter() {
+            count += *bucket_count;
+        }
+
+        count
+    }
+
+    /// Adds the current foreign data to the given LogHistogram.
+
+    pub fn merge_into(&self, histogram: &mut LogHistogram) {
+        for (bounds, count) in self.buckets().iter() {
+            histogram.record_count(representative_value(*bounds), *count);
+        }
+    }
+
+    /// Creates a LogHistogram approximation of the foreign data.
+
+    pub fn to_log(&self) -> LogHistogram {
+        let mut histogram = LogHistogram::new();
+
+        self.merge_into(&mut histogram);
+        histogram
+    }
+}
+
+impl<F> Histogram for ExternalHistogram<F>
+        where F: Fn() -> Vec<ExternalBucket> {
+    fn print_histogram(&self, printer: &mut dyn Printer) {
+        self.to_log().print(printer);
+    }
+
+    fn clear_histogram(&mut self) {
+        panic!("ExternalHistogram::clear_histogram:  not supported");
+    }
+
+    fn to_log_histogram(&self) -> Option<LogHistogramBox> {
+        Some(Rc::from(RefCell::new(self.to_log())))
+    }
+
+    fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_count_match;
+    use crate::printer_mut;
+
+    fn test_representative() {
+        assert!(representative_value((  0.0,   1.0)) ==   1);
+        assert!(representative_value((  4.0,   8.0)) ==   6);
+        assert!(representative_value((-16.0,  -8.0)) == -12);
+
+        assert!(representative_value((f64::NEG_INFINITY, -3.0             )) == -3);
+        assert!(representative_value((1000.0,            f64::INFINITY    )) == 1000);
+        assert!(representative_value((f64::NEG_INFINITY, f64::INFINITY    )) == 0);
+        assert!(representative_value((f64::NAN,          f64::NAN         )) == 0);
+    }
+
+    fn test_merge() {
+        let buckets =
+            vec![
+                ((-8.0,    -4.0), 3),
+                (( 0.0,     0.0), 1),
+                (( 4.0,     8.0), 5),
+                ((256.0, 512.0), 7)
+            ];
+
+        let adapter = ExternalHistogram::new(move || buckets.clone());
+
+        assert!(adapter.count() == 16);
+
+        let mut histogram = LogHistogram::new();
+
+        histogram.record(6);
+        adapter.merge_into(&mut histogram);
+
+        assert!(histogram.negative[3] == 3);
+        assert!(histogram.positive[0] == 1);
+        assert!(histogram.positive[3] == 6);
+        assert!(histogram.positive[9] == 7);
+
+        let histogram = adapter.to_log_histogram().unwrap();
+        let histogram = histogram.borrow();
+
+        assert!(histogram.positive[3] == 5);
+        assert!(adapter.to_log().equals(&histogram));
+        assert!(adapter.to_float_histogram().is_none());
+    }
+
+    // The provider is queried each time, so changes in the foreign
+    // histogram appear in the adapter.
+
+    fn test_provider() {
+        let counts  = Rc::from(RefCell::new(0_u64));
+        let source  = counts.clone();
+        let adapter = ExternalHistogram::new(move || vec![ ((1.0, 2.0), *source.borrow()) ]);
+
+        assert!(adapter.count() == 0);
+
+        *counts.borrow_mut() = 42;
+
+        assert!(adapter.count() == 42);
+        assert!(adapter.to_log().positive[1] == 42);
+    }
+
+    fn test_print_output() {
+        let expected =
+            [
+                "  Log Histogram",
+                "  -----------------------",
+                "    0:                 0                10                 0                 0",
+                "    4:                 0                 0                 0             1,000"
+            ];
+
+        let buckets = vec![ ((1.0, 2.0), 10), ((65.0, 127.0), 1000) ];
+        let adapter = ExternalHistogram::new(move || buckets.clone());
+        let printer = check_printer_box(&expected, true, false);
+
+        adapter.print_histogram(printer_mut!(printer));
+
+        assert!(check_printer_count_match(printer));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_clear() {
+        let mut adapter = ExternalHistogram::new(|| vec![ ((1.0, 2.0), 1) ]);
+
+        adapter.clear_histogram();
+    }
+
+    #[test]
+    fn run_tests() {
+        test_representative();
+        test_merge         ();
+        test_provider      ();
+        test_print_output  ();
+    }
+}
@@ -0,0 +1,421 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * FilePrinter
+//!     * FilePrinter implements the Printer trait by appending each line
+//!       to a file.
+//!
+//!     * The file can be rotated when it reaches a given size.  When the
+//!       next line would make the file larger than the limit, the file
+//!       "name" is renamed to "name.1", any "name.1" is renamed to
+//!       "name.2", and so on, and a new, empty file is started.  Only a
+//!       configured number of old files are kept.  A single line longer
+//!       than the limit still is written, so no output is lost.
+//!
+//!     * By default, the output is flushed after every line.  A program
+//!       that prints large reports can set a flush interval to buffer
+//!       the output, and invoke flush() when it is done printing.
+//!
+//!     * The Printer interface can't return errors, so I/O errors are
+//!       counted, and the last error is kept for the caller to examine.
+//!
+//! ## Example
+//!```
+//!     use std::rc::Rc;
+//!     use std::cell::RefCell;
+//!     use rustics::Rustics;
+//!     use rustics::printer_box;
+//!     use rustics::PrintOpts;
+//!     use rustics::file_printer::FilePrinter;
+//!     use rustics::running_integer::RunningInteger;
+//!
+//!     let mut path = std::env::temp_dir();
+//!
+//!     path.push(format!("rustics_example_{}.log", std::process::id()));
+//!
+//!     // Rotate the file when it reaches a megabyte, and keep three
+//!     // old files.
+//!
+//!     let mut printer = FilePrinter::new(&path, Some(1024 * 1024)).unwrap();
+//!
+//!     printer.set_rotations(3);
+//!
+//!     let printer    = printer_box!(printer);
+//!     let print_opts = PrintOpts { printer: Some(printer), ..Default::default() };
+//!     let print_opts = Some(print_opts);
+//!
+//!     let mut sizes = RunningInteger::new("Packet Sizes", &print_opts);
+//!
+//!     for size in 1..=100 {
+//!         sizes.record_i64(size);
+//!     }
+//!
+//!     sizes.print();
+//!
+//!     let output = std::fs::read_to_string(&path).unwrap();
+//!
+//!     assert!(output.starts_with("Packet Sizes\n"));
+//!
+//!     std::fs::remove_file(&path).unwrap();
+//!```
+
+use std::any::Any;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::Printer;
+
+/// FilePrinter writes the output lines to a file, with optional
+/// rotation by size.
+
+pub struct FilePrinter {
+    path:          PathBuf,
+    writer:        Option<BufWriter<File>>,
+    rotate_bytes:  Option<u64>,
+    rotations:     usize,
+    flush_every:   usize,
+    size:          u64,
+    unflushed:     usize,
+    errors:        u64,
+    last_error:    Option<std::io::Error>,
+}
+
+impl FilePrinter {
+    /// Opens the given file for appending, creating it if needed.  If
+    /// rotate_bytes is given, the file is rotated when it would exceed
+    /// that size.  One old file is kept by default.
+
+    pub fn new<P: AsRef<Path>>(path: P, rotate_bytes: Option<u64>) -> std::io::Result<FilePrinter> {
+        if rotate_bytes == Some(0) {
+            panic!("FilePrinter::new:  The rotation size must be positive.");
+        }
+
+        let path        = path.as_ref().to_path_buf();
+        let file        = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size        = file.metadata()?.len();
+        let writer      = Some(BufWriter::new(file));
+        let rotations   = 1;
+        let flush_every = 1;
+        let unflushed   = 0;
+        let errors      = 0;
+        let last_error  = None;
+
+        let printer =
+            FilePrinter {
+                path,         writer,       rotate_bytes,
+                rotations,    flush_every,  size,
+                unflushed,    errors,       last_error
+            };
+
+        Ok(printer)
+    }
+
+    /// Sets the number of old files kept when rotating.  Zero means
+    /// that the old contents are discarded.
+
+    pub fn set_rotations(&mut self, rotations: usize) {
+        self.rotations = rotations;
+    }
+
+    /// Sets the number of lines written between flushes.  Zero means
+    /// that the output is flushed only when the buffer fills, when the
+    /// file is rotated, or when flush() is invoked.
+
+    pub fn set_flush_every(&mut self, lines: usize) {
+        self.flush_every = lines;
+    }
+
+    /// Returns the path of the current output file.
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path used for the given old file.  Old file 1 is
+    /// the newest.
+
+    pub fn rotated_path(&self, which: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+
+        name.push(format!(".{}", which));
+        name.into()
+    }
+
+    /// Returns the size of the current output file, including any
+    /// buffered output.
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the number of I/O errors seen.
+
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// Returns the most recent I/O error, if any.
+
+    pub fn last_error(&self) -> Option<&std::io::Error> {
+        self.last_error.as_ref()
+    }
+
+    /// Writes any buffered output to the file.
+
+    pub fn flush(&mut self) {
+        let result =
+            match &mut self.writer {
+                Some(writer) => { writer.flush() }
+                None         => { Ok(())         }
+            };
+
+        self.unflushed = 0;
+        self.check(result);
+    }
+
+    // Record an error, if there was one.
+
+    fn check(&mut self, result: std::io::Result<()>) {
+        if let Err(error) = result {
+            self.errors     += 1;
+            self.last_error  = Some(error);
+        }
+    }
+
+    // Rename the existing files and start a new one.
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+
+        if self.rotations == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            for which in (1..self.rotations).rev() {
+                let from = self.rotated_path(which);
+
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(which + 1))?;
+                }
+            }
+
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+
+        self.writer    = Some(BufWriter::new(file));
+        self.size      = 0;
+        self.unflushed = 0;
+        Ok(())
+    }
+
+    // Write one line, rotating first if needed.
+
+    fn write_line(&mut self, output: &str) -> std::io::Result<()> {
+        let length = output.len() as u64 + 1;
+
+        if let Some(rotate_bytes) = self.rotate_bytes {
+            if self.size > 0 && self.size + length > rotate_bytes {
+                self.rotate()?;
+            }
+        }
+
+        // Try to reopen the file if an earlier rotation failed.
+
+        if self.writer.is_none() {
+            let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+
+            self.size   = file.metadata()?.len();
+            self.writer = Some(BufWriter::new(file));
+        }
+
+        let writer = self.writer.as_mut().unwrap();
+
+        writeln!(writer, "{}", output)?;
+
+        self.size      += length;
+        self.unflushed += 1;
+
+        if self.flush_every > 0 && self.unflushed >= self.flush_every {
+            self.unflushed = 0;
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Printer for FilePrinter {
+    fn print(&mut self, output: &str) {
+        let result = self.write_line(output);
+
+        self.check(result);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Make a path in the temporary directory for a test.
+
+    fn test_path(name: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+
+        path.push(format!("rustics_{}_{}.log", name, std::process::id()));
+        path
+    }
+
+    fn remove(path: &Path) {
+        let _ = std::fs::remove_file(path);
+    }
+
+    fn test_append() {
+        let path = test_path("append");
+
+        remove(&path);
+
+        let mut printer = FilePrinter::new(&path, None).unwrap();
+
+        printer.print("first");
+        printer.print("second");
+
+        assert!(printer.size()   == 13);
+        assert!(printer.errors() == 0 );
+        assert!(printer.path()   == path);
+
+        drop(printer);
+
+        // Opening the file again appends to it.
+
+        let mut printer = FilePrinter::new(&path, None).unwrap();
+
+        assert!(printer.size() == 13);
+
+        printer.print("third");
+
+        let output = std::fs::read_to_string(&path).unwrap();
+
+        assert!(output == "first\nsecond\nthird\n");
+        remove(&path);
+    }
+
+    fn test_rotation() {
+        let path = test_path("rotate");
+
+        remove(&path);
+
+        let mut printer = FilePrinter::new(&path, Some(20)).unwrap();
+        let     old_1   = printer.rotated_path(1);
+        let     old_2   = printer.rotated_path(2);
+        let     old_3   = printer.rotated_path(3);
+
+        remove(&old_1);
+        remove(&old_2);
+        remove(&old_3);
+
+        printer.set_rotations(2);
+
+        // Each line takes 10 bytes, so each file holds two lines.
+
+        for i in 0..7 {
+            printer.print(&format!("line {:04}", i));
+        }
+
+        printer.flush();
+
+        assert!(printer.errors() == 0 );
+        assert!(printer.size()   == 10);
+
+        assert!(std::fs::read_to_string(&path ).unwrap() == "line 0006\n"           );
+        assert!(std::fs::read_to_string(&old_1).unwrap() == "line 0004\nline 0005\n");
+        assert!(std::fs::read_to_string(&old_2).unwrap() == "line 0002\nline 0003\n");
+        assert!(!old_3.exists());
+
+        // A line longer than the limit still is written.
+
+        let long = "x".repeat(50);
+
+        printer.print(&long);
+        printer.print("short");
+
+        assert!(std::fs::read_to_string(&old_1).unwrap() == format!("{}\n", long));
+        assert!(std::fs::read_to_string(&path ).unwrap() == "short\n");
+
+        remove(&path);
+        remove(&old_1);
+        remove(&old_2);
+    }
+
+    fn test_flush() {
+        let path = test_path("flush");
+
+        remove(&path);
+
+        let mut printer = FilePrinter::new(&path, None).unwrap();
+
+        printer.set_flush_every(0);
+        printer.print("buffered");
+
+        assert!(std::fs::read_to_string(&path).unwrap().is_empty());
+
+        printer.flush();
+
+        assert!(std::fs::read_to_string(&path).unwrap() == "buffered\n");
+
+        printer.set_flush_every(2);
+        printer.print("one");
+
+        assert!(std::fs::read_to_string(&path).unwrap() == "buffered\n");
+
+        printer.print("two");
+
+        assert!(std::fs::read_to_string(&path).unwrap() == "buffered\none\ntwo\n");
+
+        // Discard the old contents when no old files are kept.
+
+        let mut printer = FilePrinter::new(&path, Some(16)).unwrap();
+
+        printer.set_rotations(0);
+        printer.print("three");
+
+        assert!(std::fs::read_to_string(&path).unwrap() == "three\n");
+        assert!(!printer.rotated_path(1).exists());
+        assert!(printer.last_error().is_none());
+
+        remove(&path);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_append  ();
+        test_rotation();
+        test_flush   ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_rotation() {
+        let _ = FilePrinter::new(test_path("zero"), Some(0));
+    }
+}
@@ -184,10 +184,13 @@ use super::hier_box;
 use super::running_float::RunningFloat;
 use crate::running_float::FloatExporter;
 use super::float_window::FloatWindow;
+use super::float_time_window::FloatTimeWindow;
+use super::non_finite::NonFinitePolicy;
 
 use crate::Hier;
 use crate::HierDescriptor;
 use crate::HierConfig;
+use crate::hier::HierWindow;
 use crate::HierGenerator;
 use crate::HierMember;
 use crate::HierExporter;
@@ -227,6 +230,7 @@ impl HierMember for RunningFloat {
 
 #[derive(Default)]
 pub struct FloatHier {
+    non_finite:  NonFinitePolicy,
 }
 
 /// FloatHierConfig is used to pass the constructor parameters
@@ -244,7 +248,14 @@ pub struct FloatHierConfig {
 
 impl FloatHier {
     pub fn new() -> FloatHier  {
-        FloatHier { }
+        FloatHier::new_with_policy(NonFinitePolicy::default())
+    }
+
+    /// Makes a FloatHier structure whose members and window use the
+    /// given handling for NaN and infinite samples.
+
+    pub fn new_with_policy(non_finite: NonFinitePolicy) -> FloatHier {
+        FloatHier { non_finite }
     }
 
     /// new_hier() creates a new Hier instance from the given
@@ -252,18 +263,28 @@ impl FloatHier {
     /// to the RunningFloat type.
 
     pub fn new_hier(configuration: FloatHierConfig) -> Hier {
-        let generator    = FloatHier::new();
+        FloatHier::new_hier_with_policy(configuration, NonFinitePolicy::default())
+    }
+
+    /// Creates a new Hier instance that handles NaN and infinite
+    /// samples as given.  See the non_finite module.  The counts of
+    /// such samples are summed into the upper levels along with the
+    /// other statistics.
+
+    pub fn new_hier_with_policy(configuration: FloatHierConfig, non_finite: NonFinitePolicy)
+            -> Hier {
+        let generator    = FloatHier::new_with_policy(non_finite);
         let generator    = Rc::from(RefCell::new(generator));
         let class        = "float".to_string();
 
         let descriptor   = configuration.descriptor;
         let name         = configuration.name;
         let print_opts   = configuration.print_opts;
-        let window_size  = configuration.window_size;
+        let window       = HierWindow::from(configuration.window_size);
 
         let config =
             HierConfig {
-                descriptor, generator, name, window_size, class, print_opts
+                descriptor, generator, name, window, class, print_opts
             };
 
         Hier::new(config)
@@ -277,16 +298,33 @@ impl FloatHier {
 
 impl HierGenerator for FloatHier {
     fn make_member(&self, name: &str, print_opts: &PrintOption) -> MemberRc {
-        let member = RunningFloat::new(name, print_opts);
+        let mut member = RunningFloat::new(name, print_opts);
 
+        member.set_non_finite_policy(self.non_finite);
         hier_box!(member)
     }
 
-    fn make_window(&self, name: &str, window_size: usize, print_opts: &PrintOption)
+    fn make_window(&self, name: &str, window: &HierWindow, print_opts: &PrintOption)
             -> Box<dyn Rustics> {
-        let window = FloatWindow::new(name, window_size, print_opts);
+        match window {
+            HierWindow::Samples(size) => {
+                let mut window = FloatWindow::new(name, *size, print_opts);
 
-        Box::new(window)
+                window.set_non_finite_policy(self.non_finite);
+                Box::new(window)
+            }
+
+            HierWindow::Time(seconds, timer) => {
+                let mut window = FloatTimeWindow::new(name, *seconds, timer.clone(), print_opts);
+
+                window.set_non_finite_policy(self.non_finite);
+                Box::new(window)
+            }
+
+            HierWindow::None => {
+                panic!("FloatHier::make_window:  No window was configured.");
+            }
+        }
     }
 
     // Make a member from a complete list of exported statistics.
@@ -324,6 +362,33 @@ impl HierGenerator for FloatHier {
     fn hz(&self) -> u128 {
         panic!("FloatHier::hz:  not supported");
     }
+
+    fn checkpoint_member(&self, member: &MemberRc) -> Option<String> {
+        let member = hier_item!(member);
+        let member = member.as_any().downcast_ref::<RunningFloat>()?;
+
+        Some(member.checkpoint())
+    }
+
+    fn restore_member(&self, text: &str, print_opts: &PrintOption) -> Option<MemberRc> {
+        let mut member = RunningFloat::restore(text, print_opts)?;
+
+        member.set_non_finite_policy(self.non_finite);
+        Some(hier_box!(member))
+    }
+
+    fn checkpoint_window(&self, window: &dyn Rustics) -> Option<String> {
+        let window = window.generic().downcast_ref::<FloatWindow>()?;
+
+        Some(window.checkpoint())
+    }
+
+    fn restore_window(&self, text: &str, print_opts: &PrintOption) -> Option<Box<dyn Rustics>> {
+        let mut window = FloatWindow::restore(text, print_opts)?;
+
+        window.set_non_finite_policy(self.non_finite);
+        Some(Box::new(window))
+    }
 }
 
 #[cfg(test)]
@@ -598,7 +663,7 @@ mod tests {
         let     title      = None;
         let     units      = bytes();
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 
         let     name       = "Test Statistics";
         let mut stats      = RunningFloat::new(&name, &print_opts);
@@ -705,11 +770,59 @@ mod tests {
         assert!(verify_float_histogram(&export_histo, &expected_histo));
     }
 
+    fn make_policy_hier(non_finite: NonFinitePolicy) -> Hier {
+        let dimension_0   = HierDimension::new(4, 4);
+        let dimension_1   = HierDimension::new(2, 2);
+        let dimensions    = vec![ dimension_0, dimension_1 ];
+        let descriptor    = HierDescriptor::new(dimensions, Some(4));
+        let name          = "policy hier".to_string();
+        let print_opts    = None;
+        let window_size   = Some(8);
+
+        let configuration =
+            FloatHierConfig { descriptor, name, window_size, print_opts };
+
+        FloatHier::new_hier_with_policy(configuration, non_finite)
+    }
+
+    fn test_non_finite() {
+        // The default counts the bad samples.
+
+        let mut hier = make_policy_hier(NonFinitePolicy::CountAndIgnore);
+
+        hier.record_f64(1.0);
+        hier.record_f64(f64::NAN);
+        hier.record_f64(3.0);
+
+        let printable = hier.export_stats().printable;
+
+        assert!(printable.n    == 2);
+        assert!(printable.nans == 1);
+
+        // Ignore applies to the members created later, too.
+
+        let mut hier = make_policy_hier(NonFinitePolicy::Ignore);
+
+        for i in 0..10 {
+            hier.record_f64(i as f64);
+            hier.record_f64(f64::INFINITY);
+        }
+
+        // The window holds only the last eight finite samples.
+
+        let printable = hier.export_stats().printable;
+
+        assert!(printable.n          == 8  );
+        assert!(printable.min_f64    == 2.0);
+        assert!(printable.rejected() == 0  );
+    }
+
     #[test]
     fn run_tests() {
         test_simple_generator();
         test_window          ();
         test_print_output    ();
         test_float_sum       ();
+        test_non_finite      ();
     }
 }
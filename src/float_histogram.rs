@@ -23,6 +23,10 @@
 //!   * f64::NEG_INFINITY samples go into the smallest bucket, and into a count
 //!     of infinite values.
 //!
+//!   * Setting bar_chart in the HistoOpts prints the buckets as a horizontal
+//!     bar chart, with the largest count scaled to bar_width characters.
+//!     LogHistogram supports the same options.
+//!
 //! ## Example
 //!```
 //!     use rustics::float_histogram::FloatHistogram;
@@ -33,18 +37,15 @@
 //!     use rustics::stdout_printer;
 //!     use rustics::printer_mut;
 //!
-//!     // Create a HistoOpts for new().
-//!
-//!     let merge_min    = 0;  // not implemented yet
-//!     let merge_max    = 0;  // not implemented yet
-//!     let no_zero_rows = false;
+//!     // Create a HistoOpts for new().  The builder starts with the
+//!     // defaults.
 //!
-//!     let histo_opts   = HistoOpts { merge_min, merge_max, no_zero_rows };
+//!     let histo_opts   = HistoOpts::builder().no_zero_rows(false).build();
 //!     let histo_opts   = Some(histo_opts);
 //!     let printer      = None;
 //!     let title        = None;
 //!     let units        = None;
-//!     let print_opts   = PrintOpts { printer, title, units, histo_opts };
+//!     let print_opts   = PrintOpts { printer, title, units, histo_opts, ..Default::default() };
 //!
 //!     // Create a histogram.
 //!
@@ -94,20 +95,30 @@ use super::FloatHistogramBox;
 use super::PrintOption;
 use super::LogHistogramBox;
 use super::Printer;
+use super::printable::PrintField;
+use super::printable::PrintFields;
 use super::biased_exponent;
 use super::max_biased_exponent;
 use super::exponent_bias;
 use super::sign;
 use super::parse_histo_opts;
+use super::checkpoint::Checkpoint;
+use super::checkpoint::CheckpointReader;
 
 /// The HistoOpts struct is used to specify options on how to print
-/// a histogram.
+/// a histogram.  Options are added from time to time, so instances
+/// are created via HistoOpts::default() or HistoOpts::builder().
 
 #[derive(Clone, Copy)]
+#[non_exhaustive]
 pub struct HistoOpts {
     pub merge_min:     isize,   // not yet implemented
     pub merge_max:     isize,   // not yet implemented
     pub no_zero_rows:  bool,    // suppress any rows that are all zeros
+    pub no_histogram:  bool,    // don't print the histogram at all
+    pub print_cdf:     bool,    // print bucket counts with the cumulative distribution
+    pub bar_chart:     bool,    // print the buckets as a bar chart
+    pub bar_width:     usize,   // the width of the longest bar in the chart
 }
 
 impl Default for HistoOpts {
@@ -115,8 +126,151 @@ impl Default for HistoOpts {
         let merge_min    = 0;
         let merge_max    = 0;
         let no_zero_rows = false;
+        let no_histogram = false;
+        let print_cdf    = false;
+        let bar_chart    = false;
+        let bar_width    = 60;
 
-        HistoOpts { merge_min, merge_max, no_zero_rows }
+        HistoOpts {
+            merge_min, merge_max, no_zero_rows, no_histogram, print_cdf, bar_chart, bar_width
+        }
+    }
+}
+
+impl HistoOpts {
+    /// Returns a builder for a HistoOpts instance.  Any option that
+    /// isn't set keeps its default value.
+    ///
+    ///```
+    ///     use rustics::PrintOpts;
+    ///     use rustics::float_histogram::HistoOpts;
+    ///
+    ///     let histo_opts = HistoOpts::builder().no_zero_rows(true).print_cdf(true).build();
+    ///     let print_opts = PrintOpts::with_histo_opts(histo_opts);
+    ///```
+
+    pub fn builder() -> HistoOptsBuilder {
+        HistoOptsBuilder::new()
+    }
+
+    /// Returns true if the histogram should be printed, i.e., if it
+    /// isn't suppressed by no_histogram or by the given field set.
+
+    pub fn print_histogram(&self, fields: PrintFields) -> bool {
+        !self.no_histogram && fields.contains(PrintField::Histogram)
+    }
+}
+
+/// HistoOptsBuilder builds a HistoOpts instance one option at a
+/// time.  See HistoOpts::builder().
+
+#[derive(Clone, Copy, Default)]
+pub struct HistoOptsBuilder {
+    histo_opts: HistoOpts,
+}
+
+impl HistoOptsBuilder {
+    pub fn new() -> HistoOptsBuilder {
+        let histo_opts = HistoOpts::default();
+
+        HistoOptsBuilder { histo_opts }
+    }
+
+    /// Sets the lower bound for merging rows.  Merging is not yet
+    /// implemented.
+
+    pub fn merge_min(mut self, merge_min: isize) -> HistoOptsBuilder {
+        self.histo_opts.merge_min = merge_min;
+        self
+    }
+
+    /// Sets the upper bound for merging rows.  Merging is not yet
+    /// implemented.
+
+    pub fn merge_max(mut self, merge_max: isize) -> HistoOptsBuilder {
+        self.histo_opts.merge_max = merge_max;
+        self
+    }
+
+    /// Suppresses rows that are all zeros.
+
+    pub fn no_zero_rows(mut self, no_zero_rows: bool) -> HistoOptsBuilder {
+        self.histo_opts.no_zero_rows = no_zero_rows;
+        self
+    }
+
+    /// Suppresses the histogram entirely.
+
+    pub fn no_histogram(mut self, no_histogram: bool) -> HistoOptsBuilder {
+        self.histo_opts.no_histogram = no_histogram;
+        self
+    }
+
+    /// Prints the cumulative distribution with the bucket counts.
+
+    pub fn print_cdf(mut self, print_cdf: bool) -> HistoOptsBuilder {
+        self.histo_opts.print_cdf = print_cdf;
+        self
+    }
+
+    /// Prints the buckets as a bar chart.
+
+    pub fn bar_chart(mut self, bar_chart: bool) -> HistoOptsBuilder {
+        self.histo_opts.bar_chart = bar_chart;
+        self
+    }
+
+    /// Sets the width of the longest bar in a bar chart.
+
+    pub fn bar_width(mut self, bar_width: usize) -> HistoOptsBuilder {
+        self.histo_opts.bar_width = bar_width;
+        self
+    }
+
+    /// Returns the HistoOpts instance.
+
+    pub fn build(self) -> HistoOpts {
+        self.histo_opts
+    }
+}
+
+/// Prints histogram buckets as a horizontal bar chart.  Each row
+/// holds a label, a count, and a bar scaled so that the largest
+/// count fills bar_width characters.  Any non-zero count gets at
+/// least one character.  The empty buckets before the first and
+/// after the last non-empty bucket are not printed, and other empty
+/// buckets are skipped if no_zero_rows is set.
+///
+/// This function is used by the LogHistogram and FloatHistogram
+/// print_opts() methods when bar_chart is set.
+
+pub fn print_bar_chart(printer: &mut dyn Printer, rows: &[(String, u64)], histo_opts: &HistoOpts) {
+    let first = rows.iter().position(|(_, count)| *count != 0);
+    let last  = rows.iter().rposition(|(_, count)| *count != 0);
+
+    let (first, last) =
+        match (first, last) {
+            (Some(first), Some(last)) => (first, last),
+            _                         => return,
+        };
+
+    let rows: Vec<_> =
+        rows[first..=last].iter()
+            .filter(|(_, count)| *count != 0 || !histo_opts.no_zero_rows)
+            .collect();
+
+    let max    = rows.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    let width  = histo_opts.bar_width.max(1) as u128;
+    let column = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+
+    for (label, count) in rows {
+        // Round up so that small counts remain visible.
+
+        let length = (*count as u128 * width).div_ceil(max as u128) as usize;
+        let bar    = "*".repeat(length);
+
+        printer.print(&format!("    {:>column$}:  {:>14}  |{}",
+            label, Printable::commas_u64(*count), bar));
     }
 }
 
@@ -125,6 +279,7 @@ impl Default for HistoOpts {
 /// broken into groups of 16.  For example, exponents 2^1 through
 /// 2^16 form one bucket.
 
+#[derive(Clone)]
 pub struct FloatHistogram {
     pub negative:   Vec<u64>,
     pub positive:   Vec<u64>,
@@ -142,6 +297,67 @@ pub fn bucket_divisor() -> isize {
     16
 }
 
+/// Returns the bound of the given bucket that is furthest toward
+/// positive infinity.  The "negative" parameter selects the array for
+/// negative samples.
+
+pub fn bucket_max(index: usize, negative: bool) -> f64 {
+    let exponent =
+        if negative {
+            index as isize * bucket_divisor() - exponent_bias()
+        } else {
+            (index as isize + 1) * bucket_divisor() - exponent_bias()
+        };
+
+    let max = 2.0_f64.powi(exponent as i32);
+
+    if negative {
+        -max
+    } else {
+        max
+    }
+}
+
+/// Returns the index of the bucket for a sample, and whether the
+/// bucket is in the array for negative samples.  Infinities go into
+/// the extreme buckets.  NaNs aren't recorded into a bucket, and
+/// return the index for zero.
+
+pub fn bucket_index(sample: f64) -> (usize, bool) {
+    let index =
+        if sample.is_infinite() {
+            max_biased_exponent() / bucket_divisor()
+        } else if sample == 0.0 || sample.is_nan() {
+            exponent_bias() / bucket_divisor()
+        } else {
+            biased_exponent(sample) / bucket_divisor()
+        };
+
+    (index as usize, sign(sample) < 0)
+}
+
+/// Returns the bound of the given bucket that is closest to negative
+/// infinity.  Zero is recorded into the positive bucket whose lower
+/// bound is the smallest power of two in the bucket, so it lies below
+/// the lower bound returned for that bucket.
+
+pub fn bucket_min(index: usize, negative: bool) -> f64 {
+    let exponent =
+        if negative {
+            (index as isize + 1) * bucket_divisor() - exponent_bias()
+        } else {
+            index as isize * bucket_divisor() - exponent_bias()
+        };
+
+    let min = 2.0_f64.powi(exponent as i32);
+
+    if negative {
+        -min
+    } else {
+        min
+    }
+}
+
 // Define the number of buckets printed per row.  This actually
 // is hard-coded in the actual format statement.
 
@@ -159,7 +375,7 @@ fn buckets() -> isize {
 // Do covered division.
 
 fn roundup(value: usize, multiple: usize) -> usize {
-    ((value + multiple - 1) / multiple) * multiple
+    value.div_ceil(multiple) * multiple
 }
 
 impl FloatHistogram {
@@ -182,6 +398,13 @@ impl FloatHistogram {
         }
     }
 
+    /// Records an f32 sample.  Every f32 value converts exactly to
+    /// f64, so the result is the same as recording the f64 value.
+
+    pub fn record_f32(&mut self, sample: f32) {
+        self.record(sample as f64);
+    }
+
     /// Records one f64 sample into its bucket.
 
     pub fn record(&mut self, sample: f64) {
@@ -192,39 +415,48 @@ impl FloatHistogram {
             return;
         }
 
-        // Get the index into the histogram.
-        //
+        if sample.is_infinite() {
+            self.infinities += 1;
+        }
+
         // We have two separate arrays for positive and negative
-        // values, so keep track of the sign.
+        // values.
 
-        let index =
-            if sample.is_infinite() {
-                self.infinities += 1;
+        let (index, negative) = bucket_index(sample);
 
-                let index = max_biased_exponent() / bucket_divisor();
+        if negative {
+            self.negative[index] += 1;
+        } else {
+            self.positive[index] += 1;
+        }
 
-                index as usize
-            } else if sample == 0.0 {
-                let index = exponent_bias() / bucket_divisor();
+        self.samples += 1;
+    }
 
-                index as usize
-            } else {
-                let index = biased_exponent(sample) / bucket_divisor();
+    /// Removes a finite sample that was recorded earlier, as when a
+    /// sample leaves a window.  The NaN and infinity counts can't be
+    /// reduced.
 
-                index as usize
-            };
+    pub fn remove(&mut self, sample: f64) {
+        if !sample.is_finite() {
+            panic!("FloatHistogram::remove:  The sample {} is not finite.", sample);
+        }
 
-        let sign = sign(sample);
+        let (index, negative) = bucket_index(sample);
 
-        // Now index into the appropriate array.
+        let bucket =
+            if negative {
+                &mut self.negative[index]
+            } else {
+                &mut self.positive[index]
+            };
 
-        if sign < 0 {
-            self.negative[index] += 1;
-        } else {
-            self.positive[index] += 1;
+        if *bucket == 0 {
+            panic!("FloatHistogram::remove:  The sample {} was not recorded.", sample);
         }
 
-        self.samples += 1;
+        *bucket      -= 1;
+        self.samples -= 1;
     }
 
     /// This function returns the unbiased IEEE binary64
@@ -389,11 +621,127 @@ impl FloatHistogram {
                 self.nans, self.infinities, self.samples);
 
         printer.print(&header);
+
+        if histo_opts.bar_chart {
+            self.print_bars(printer, histo_opts);
+            return;
+        }
+
         self.print_negative(printer, histo_opts);
         printer.print("  -----------------------");
         self.print_positive(printer, histo_opts);
     }
 
+    // Prints the buckets as a bar chart.  Each row is labeled with
+    // the bound of the bucket furthest from zero, as a power of two.
+
+    fn print_bars(&self, printer: &mut dyn Printer, histo_opts: &HistoOpts) {
+        let mut rows = Vec::with_capacity(self.negative.len() + self.positive.len());
+
+        for i in (0..self.negative.len()).rev() {
+            let exponent = (i as isize + 1) * bucket_divisor() - exponent_bias();
+
+            rows.push((format!("-2^{}", exponent), self.negative[i]));
+        }
+
+        for i in 0..self.positive.len() {
+            let exponent = (i as isize + 1) * bucket_divisor() - exponent_bias();
+
+            rows.push((format!("2^{}", exponent), self.positive[i]));
+        }
+
+        print_bar_chart(printer, &rows, histo_opts);
+    }
+
+    /// Returns the cumulative distribution of the samples as a list
+    /// of (bucket maximum, cumulative percentage) pairs.  There is one
+    /// entry for each non-empty bucket, in increasing order.
+
+    pub fn cdf(&self) -> Vec<(f64, f64)> {
+        self.cdf_rows(100.0).iter().map(|(bound, _, percent)| (*bound, *percent)).collect()
+    }
+
+    /// Returns the cumulative distribution as (bucket maximum,
+    /// cumulative fraction) pairs, with fractions from 0 to 1, for
+    /// plotting.  The entries match those returned by cdf().
+
+    pub fn cdf_fractions(&self) -> Vec<(f64, f64)> {
+        self.cdf_rows(1.0).iter().map(|(bound, _, fraction)| (*bound, *fraction)).collect()
+    }
+
+    // Compute a (bucket maximum, count, cumulative fraction * scale)
+    // row for each non-empty bucket.
+
+    fn cdf_rows(&self, scale: f64) -> Vec<(f64, u64, f64)> {
+        let     total: u64 = self.negative.iter().sum::<u64>() + self.positive.iter().sum::<u64>();
+        let mut result     = Vec::new();
+        let mut sum        = 0;
+
+        if total == 0 {
+            return result;
+        }
+
+        for i in (0..self.negative.len()).rev() {
+            if self.negative[i] > 0 {
+                sum += self.negative[i];
+                result.push((bucket_max(i, true), self.negative[i], sum as f64 * scale / total as f64));
+            }
+        }
+
+        for i in 0..self.positive.len() {
+            if self.positive[i] > 0 {
+                sum += self.positive[i];
+                result.push((bucket_max(i, false), self.positive[i], sum as f64 * scale / total as f64));
+            }
+        }
+
+        result
+    }
+
+    /// Estimates the given percentile, which is in the range 0 to 100.
+    /// The result is the bound of the bucket containing the percentile
+    /// that is furthest toward positive infinity, so it's very coarse.
+    /// An empty histogram returns zero.
+
+    pub fn percentile(&self, percentile: f64) -> f64 {
+        Printable::float_percentile(self, percentile)
+    }
+
+    /// Returns an iterator over all the buckets in increasing order of
+    /// value.  Each item is a (lower bound, upper bound, count) tuple.
+    /// The bounds are powers of two.  See bucket_min() for a note on
+    /// zero values.
+
+    pub fn iter_buckets(&self) -> impl Iterator<Item = (f64, f64, u64)> + '_ {
+        let negative =
+            (0..self.negative.len()).rev()
+                .map(|i| (bucket_min(i, true), bucket_max(i, true), self.negative[i]));
+
+        let positive =
+            (0..self.positive.len())
+                .map(|i| (bucket_min(i, false), bucket_max(i, false), self.positive[i]));
+
+        negative.chain(positive)
+    }
+
+    /// Prints the cumulative distribution as a table giving the count
+    /// in each non-empty bucket next to the percentage of the samples
+    /// at or below the bucket boundary.  The boundaries are powers of
+    /// two, as in the histogram rows.
+
+    pub fn print_cdf(&self, printer: &mut dyn Printer) {
+        printer.print("  Cumulative Distribution");
+
+        for (value, count, percent) in self.cdf_rows(100.0) {
+            let sign     = if value < 0.0 { "-" } else { "" };
+            let exponent = value.abs().log2().round() as isize;
+            let value    = format!("<= {}2^{}", sign, exponent);
+            let count    = Printable::commas_u64(count);
+
+            printer.print(&format!("    {:>26}    {:>14}    {:>8.3}%", value, count, percent));
+        }
+    }
+
     /// Resets the histogram to its initial state.
 
     pub fn clear(&mut self) {
@@ -404,6 +752,24 @@ impl FloatHistogram {
         self.infinities = 0;
     }
 
+    /// Saves the counts as a string.  See the checkpoint module.
+
+    pub fn checkpoint(&self) -> String {
+        let mut checkpoint = Checkpoint::new("float_histogram");
+
+        checkpoint.push_float_histogram("buckets", self);
+        checkpoint.text()
+    }
+
+    /// Restores a histogram saved by checkpoint().  The print options
+    /// aren't saved, so they must be supplied.
+
+    pub fn restore(text: &str, print_opts: &PrintOption) -> Option<FloatHistogram> {
+        let reader = CheckpointReader::new_kind(text, "float_histogram")?;
+
+        reader.float_histogram("buckets", print_opts)
+    }
+
     /// Returns the number of samples that were NaN and the number that
     /// were non-finite.
 
@@ -474,18 +840,27 @@ mod tests {
     use crate::min_exponent;
     use crate::PrintOpts;
     use crate::printer_mut;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_counters;
     use super::*;
 
     fn simple_test() {
         let     merge_min    = min_exponent();
         let     merge_max    = min_exponent();
         let     no_zero_rows = true;
+        let     no_histogram = false;
+        let     print_cdf    = false;
+        let     bar_chart    = false;
+        let     bar_width    = 60;
         let     printer      = None;
         let     title        = None;
         let     units        = None;
-        let     histo_opts   = HistoOpts { merge_min, merge_max, no_zero_rows };
+        let     histo_opts   =
+            HistoOpts {
+                merge_min, merge_max, no_zero_rows, no_histogram, print_cdf, bar_chart, bar_width
+            };
         let     histo_opts   = Some(histo_opts);
-        let     print_opts   = PrintOpts { printer, title, units, histo_opts };
+        let     print_opts   = PrintOpts { printer, title, units, histo_opts, ..Default::default() };
         let mut histogram    = FloatHistogram::new(&Some(print_opts));
         let     max_index    = max_biased_exponent() / bucket_divisor();
 
@@ -575,12 +950,19 @@ mod tests {
         let merge_min    = 10;  // not implemented yet
         let merge_max    = 11;  // not implemented yet
         let no_zero_rows = false;
-        let histo_opts   = HistoOpts { merge_min, merge_max, no_zero_rows };
+        let no_histogram = false;
+        let print_cdf    = false;
+        let bar_chart    = false;
+        let bar_width    = 60;
+        let histo_opts   =
+            HistoOpts {
+                merge_min, merge_max, no_zero_rows, no_histogram, print_cdf, bar_chart, bar_width
+            };
         let printer      = None;
         let title        = None;
         let units        = None;
         let histo_opts   = Some(histo_opts);
-        let print_opts   = PrintOpts { printer, title, units, histo_opts };
+        let print_opts   = PrintOpts { printer, title, units, histo_opts, ..Default::default() };
 
         // Create a histogram and accept the default output format.
 
@@ -653,13 +1035,20 @@ mod tests {
         let merge_min    = 0;  // not implemented yet
         let merge_max    = 0;  // not implemented yet
         let no_zero_rows = false;
-        let histo_opts   = HistoOpts { merge_min, merge_max, no_zero_rows };
+        let no_histogram = false;
+        let print_cdf    = false;
+        let bar_chart    = false;
+        let bar_width    = 60;
+        let histo_opts   =
+            HistoOpts {
+                merge_min, merge_max, no_zero_rows, no_histogram, print_cdf, bar_chart, bar_width
+            };
 
         let printer      = None;
         let title        = None;
         let units        = None;
         let histo_opts   = Some(histo_opts);
-        let print_opts   = PrintOpts { printer, title, units, histo_opts };
+        let print_opts   = PrintOpts { printer, title, units, histo_opts, ..Default::default() };
 
         // Create a histogram and accept the default output format.
 
@@ -739,11 +1128,159 @@ mod tests {
         let _         = histogram.to_float_histogram().unwrap();
     }
 
+    fn test_cdf() {
+        let mut histogram = FloatHistogram::new(&None);
+
+        assert!(histogram.cdf().is_empty());
+
+        histogram.record(-1.0);
+        histogram.record( 1.0);
+        histogram.record( 1.5);
+        histogram.record( 3.0);
+
+        let cdf = histogram.cdf();
+
+        assert!(cdf.len() == 3);
+        assert!(cdf[0] == (bucket_max(63, true ),  25.0));
+        assert!(cdf[1] == (bucket_max(63, false),  75.0));
+        assert!(cdf[2] == (bucket_max(64, false), 100.0));
+
+        let fractions = histogram.cdf_fractions();
+
+        assert!(fractions.len() == 3);
+        assert!(fractions[0] == (bucket_max(63, true ), 0.25));
+        assert!(fractions[1] == (bucket_max(63, false), 0.75));
+        assert!(fractions[2] == (bucket_max(64, false), 1.0 ));
+
+        let expected =
+            [
+                "  Cumulative Distribution",
+                "                     <= -2^-15                 1      25.000%",
+                "                        <= 2^1                 2      75.000%",
+                "                       <= 2^17                 1     100.000%",
+            ];
+
+        let printer = check_printer_box(&expected, true, false);
+        let printer = printer_mut!(printer);
+
+        histogram.print_cdf(printer);
+    }
+
+    fn test_checkpoint() {
+        let mut histogram = FloatHistogram::new(&None);
+
+        for i in -100..1000 {
+            histogram.record(i as f64 / 3.0);
+        }
+
+        histogram.record(f64::NAN);
+        histogram.record(f64::INFINITY);
+
+        let checkpoint = histogram.checkpoint();
+        let restored   = FloatHistogram::restore(&checkpoint, &None).unwrap();
+
+        assert!(restored.equals(&histogram));
+        assert!(restored.nans       == 1);
+        assert!(restored.infinities == 1);
+        assert!(restored.samples    == histogram.samples);
+
+        assert!(FloatHistogram::restore("rustics float_histogram\n", &None).is_none());
+    }
+
+    fn test_bar_chart() {
+        let mut histogram = FloatHistogram::new(&None);
+
+        for sample in [ -1.5, 1.5, 1.25, 1.0e10 ] {
+            histogram.record(sample);
+        }
+
+        let expected =
+            [
+                "  Float Histogram:  (0 NaN, 0 infinite, 4 samples)",
+                "    -2^1:               1  |**",
+                "     2^1:               2  |****",
+                "    2^49:               1  |**",
+            ];
+
+        let histo_opts =
+            HistoOpts { bar_chart: true, bar_width: 4, no_zero_rows: true, ..Default::default() };
+
+        let printer = check_printer_box(&expected, true, false);
+
+        histogram.print_opts(printer_mut!(printer), &histo_opts);
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+
+        // Without no_zero_rows, the empty buckets between the first
+        // and last non-empty buckets are printed.
+
+        let histo_opts = HistoOpts { no_zero_rows: false, ..histo_opts };
+        let printer    = check_printer_box(&[], false, false);
+
+        histogram.print_opts(printer_mut!(printer), &histo_opts);
+
+        let (lines, _) = check_printer_counters(printer);
+
+        assert!(lines == 1 + 64 + 67);
+    }
+
+    fn test_buckets() {
+        let mut histogram = FloatHistogram::new(&None);
+        let     values    = [ -1.0e300, -3.0, -0.5, 1.0e-10, 0.75, 1.0, 100.0, 1.0e200 ];
+
+        for value in values.iter() {
+            histogram.record(*value);
+        }
+
+        let buckets: Vec<(f64, f64, u64)> = histogram.iter_buckets().collect();
+
+        assert!(buckets.len() == histogram.negative.len() + histogram.positive.len());
+
+        for i in 1..buckets.len() {
+            assert!(buckets[i].0 >= buckets[i - 1].1);
+            assert!(buckets[i].0 <  buckets[i].1    );
+        }
+
+        // Each value must lie within the bounds of a bucket with a
+        // non-zero count.
+
+        for value in values.iter() {
+            let found =
+                buckets.iter().any(|(min, max, count)| {
+                    *count > 0 && *min <= *value && *value <= *max
+                });
+
+            assert!(found);
+        }
+
+        let total: u64 = buckets.iter().map(|(_, _, count)| count).sum();
+
+        assert!(total == values.len() as u64);
+
+        // Check the percentile interface.
+
+        let empty = FloatHistogram::new(&None);
+
+        assert!(empty.percentile(50.0) == 0.0);
+
+        let median = histogram.percentile(50.0);
+
+        assert!(median == Printable::float_percentile(&histogram, 50.0));
+        assert!(median >= 1.0e-10);
+        assert!(histogram.percentile(100.0) >= 1.0e200);
+    }
+
     #[test]
     fn run_tests() {
         simple_test       ();
         test_documentation();
         test_log_mode     ();
         test_float_equals ();
+        test_cdf          ();
+        test_checkpoint   ();
+        test_buckets      ();
+        test_bar_chart    ();
     }
 }
@@ -0,0 +1,729 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * FloatTimeWindow
+//!     * FloatTimeWindow maintains a set consisting of the f64 samples
+//!       recorded during the last n seconds, as measured by a Timer
+//!       instance.  FloatWindow, in contrast, keeps the last n samples,
+//!       no matter when they were recorded.  This type is the f64
+//!       counterpart of TimeBoundedWindow, and is useful for gauges
+//!       like temperatures or queue depths.
+//!
+//!     * Samples older than the period are evicted lazily whenever a
+//!       sample is recorded or a statistic is queried, so the summary
+//!       statistics, including the minimum and maximum, always reflect
+//!       only the current period.
+//!
+//!     * The timer is read via finish() to determine the current time,
+//!       so it should not be shared with other users.
+//!
+//!     * Like FloatWindow, this type maintains a float histogram that
+//!       contains counts of all the samples seen, not just the samples
+//!       in the current period.  NaNs are counted by the histogram but
+//!       are not kept in the window.
+//!
+//! ## Example
+//!```
+//!    use rustics::Rustics;
+//!    use rustics::time::DurationTimer;
+//!    use rustics::float_time_window::FloatTimeWindow;
+//!
+//!    // Create an instance that keeps the temperatures seen in the
+//!    // last 5 minutes of wall-clock time.
+//!
+//!    let timer = DurationTimer::new_box();
+//!
+//!    let mut temperature =
+//!        FloatTimeWindow::new("Temperature", 300, timer, &None);
+//!
+//!    for i in 0..100 {
+//!       temperature.record_f64(20.0 + i as f64 / 100.0);
+//!    }
+//!
+//!    // All the samples are recent, so they are all in the window.
+//!
+//!    assert!(temperature.count()   == 100 );
+//!    assert!(temperature.min_f64() == 20.0);
+//!    assert!(temperature.max_f64() == 20.99);
+//!
+//!    temperature.print();
+//!```
+
+use std::any::Any;
+use std::rc::Rc;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use super::Rustics;
+use super::copy_float_histogram;
+use super::Printer;
+use super::PrinterBox;
+use super::ExportStats;
+use super::PrinterOption;
+use super::PrintOption;
+use super::Units;
+use super::HistoOpts;
+use super::TimerBox;
+use super::Histogram;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use crate::printable::Printable;
+use crate::non_finite::NonFinitePolicy;
+use crate::float_histogram::FloatHistogram;
+use super::integer_window::Crunched;
+use super::printer_mut;
+use super::timer_mut;
+use super::stats_math::compute_variance;
+use super::stats_math::compute_skewness;
+use super::stats_math::compute_kurtosis;
+use super::sum::kbk_sum;
+use super::sum::kbk_sum_sort;
+use super::parse_print_opts;
+use super::parse_fields;
+use super::printable::PrintFields;
+
+/// A FloatTimeWindow instance collects f64 data samples recorded
+/// during a fixed period of time.  It also maintains a histogram
+/// based on all the samples seen.
+///
+/// See the module documentation for sample code.
+
+pub struct FloatTimeWindow {
+    name:           String,
+    title:          String,
+    id:             usize,
+    seconds:        u64,
+    period:         i64,
+    timer:          TimerBox,
+
+    // The current time, in ticks since the instance was created, and
+    // the samples in the window, with the time each was recorded.
+    // The queries update these fields, and so need interior
+    // mutability.  Reading the timer restarts it, so the current
+    // time is shared with any clones, along with the timer.
+
+    now:            Rc<Cell<i64>>,
+    samples:        RefCell<VecDeque<(i64, f64)>>,
+
+    histogram:      FloatHistogramBox,
+
+    printer:        PrinterBox,
+    units:          Units,
+    histo_opts:     HistoOpts,
+    fields:         PrintFields,
+
+    nans:           u64,
+    infinities:     u64,
+    non_finite:     NonFinitePolicy,
+}
+
+// Clone makes a deep copy of the samples and the histogram.  The
+// printer is shared, as are the timer and the current time:  each
+// read of the timer consumes the elapsed ticks, so the copies must
+// keep one clock between them for both to see time advance.
+
+impl Clone for FloatTimeWindow {
+    fn clone(&self) -> FloatTimeWindow {
+        let histogram = copy_float_histogram(&self.histogram);
+
+        FloatTimeWindow {
+            name:       self.name.clone(),
+            title:      self.title.clone(),
+            id:         self.id,
+            seconds:    self.seconds,
+            period:     self.period,
+            timer:      self.timer.clone(),
+            now:        self.now.clone(),
+            samples:    self.samples.clone(),
+            histogram,
+            printer:    self.printer.clone(),
+            units:      self.units.clone(),
+            histo_opts: self.histo_opts,
+            fields:     self.fields,
+            nans:       self.nans,
+            infinities: self.infinities,
+            non_finite: self.non_finite
+        }
+    }
+}
+
+impl FloatTimeWindow {
+    /// Creates an instance that keeps the samples recorded during the
+    /// last "seconds" seconds, as measured by the given timer.
+
+    pub fn new(name: &str, seconds: u64, timer: TimerBox, print_opts: &PrintOption)
+            -> FloatTimeWindow {
+        if seconds == 0 {
+            panic!("FloatTimeWindow::new:  The period is zero.");
+        }
+
+        let hz = timer.borrow().hz();
+
+        if hz > i64::MAX as u128 {
+            panic!("FloatTimeWindow::new:  The timer frequency is too high.");
+        }
+
+        timer_mut!(timer).start();
+
+        let name      = String::from(name);
+        let id        = usize::MAX;
+        let period    = (seconds as i64).saturating_mul(hz as i64);
+        let now       = Rc::new(Cell::new(0));
+        let samples   = RefCell::new(VecDeque::new());
+        let histogram = FloatHistogram::new(print_opts);
+        let histogram = Rc::from(RefCell::new(histogram));
+
+        let (printer, title, units, histo_opts) = parse_print_opts(print_opts, &name);
+
+        let nans       = 0;
+        let infinities = 0;
+        let non_finite = NonFinitePolicy::default();
+        let fields     = parse_fields(print_opts);
+
+        FloatTimeWindow {
+            name,
+            title,
+            id,
+            seconds,
+            period,
+            timer,
+            now,
+            samples,
+            histogram,
+            printer,
+            units,
+            histo_opts,
+            fields,
+            nans,
+            infinities,
+            non_finite
+        }
+    }
+
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+    }
+
+    /// Sets the handling of NaN and infinite samples.  See the
+    /// non_finite module.  Such samples never enter the window.
+
+    pub fn set_non_finite_policy(&mut self, policy: NonFinitePolicy) {
+        self.non_finite = policy;
+    }
+
+    /// Returns the handling of NaN and infinite samples.
+
+    pub fn non_finite_policy(&self) -> NonFinitePolicy {
+        self.non_finite
+    }
+
+    /// Returns the length of the window, in seconds.
+
+    pub fn seconds(&self) -> u64 {
+        self.seconds
+    }
+
+    // Read the timer to advance the current time, and then drop
+    // any samples that have aged out of the window.
+
+    fn evict(&self) {
+        let elapsed = timer_mut!(self.timer).finish();
+        let now     = self.now.get() + elapsed;
+
+        self.now.set(now);
+
+        let mut samples = self.samples.borrow_mut();
+
+        while let Some((time, _)) = samples.front() {
+            if now - *time < self.period {
+                break;
+            }
+
+            samples.pop_front();
+        }
+    }
+
+    /// Returns a copy of the samples currently in the window, oldest
+    /// first.
+
+    pub fn values(&self) -> Vec<f64> {
+        self.evict();
+
+        self.samples.borrow().iter().map(|(_, sample)| *sample).collect()
+    }
+
+    /// Gather the summary information and compute summary statistics
+    /// for the current samples in the window.
+
+    pub fn crunch(&self) -> Crunched {
+        Self::crunch_values(&self.values())
+    }
+
+    // Compute the summary data for a snapshot of the window.  The
+    // clock can advance between queries, so each statistic is
+    // computed from a single snapshot.
+
+    fn crunch_values(values: &[f64]) -> Crunched {
+        let mut samples = values.to_vec();
+
+        if samples.is_empty() {
+            return Crunched::zero();
+        }
+
+        let sum  = kbk_sum_sort(&mut samples);
+        let mean = sum / samples.len() as f64;
+
+        let mut vec_2 = Vec::new();
+        let mut vec_3 = Vec::new();
+        let mut vec_4 = Vec::new();
+
+        for sample in samples.iter() {
+            let distance = *sample - mean;
+            let square   = distance * distance;
+
+            vec_2.push(square           );
+            vec_3.push(square * distance);
+            vec_4.push(square * square  );
+        }
+
+        let moment_2 = kbk_sum(&vec_2);
+        let moment_3 = kbk_sum(&vec_3);
+        let moment_4 = kbk_sum(&vec_4);
+
+        Crunched { mean, sum, moment_2, moment_3, moment_4 }
+    }
+
+    fn min_value(values: &[f64]) -> f64 {
+        values.iter().copied().reduce(f64::min).unwrap_or(0.0)
+    }
+
+    fn max_value(values: &[f64]) -> f64 {
+        values.iter().copied().reduce(f64::max).unwrap_or(0.0)
+    }
+
+    pub fn get_printable(&self) -> Printable {
+        let values     = self.values();
+        let n          = values.len() as u64;
+        let nans       = self.nans;
+        let infinities = self.infinities;
+        let min_i64    = i64::MIN;
+        let max_i64    = i64::MAX;
+        let min_f64    = Self::min_value(&values);
+        let max_f64    = Self::max_value(&values);
+        let log_mode   = 0;
+        let mode_value = self.histogram.borrow().mode_value();
+        let units      = self.units.clone();
+
+        let crunched   = Self::crunch_values(&values);
+        let mean       = crunched.mean;
+        let variance   = compute_variance(n, crunched.moment_2);
+        let skewness   = compute_skewness(n, crunched.moment_2, crunched.moment_3);
+        let kurtosis   = compute_kurtosis(n, crunched.moment_2, crunched.moment_4);
+
+        Printable {
+            n,         nans,  infinities,  min_i64,   max_i64,   min_f64,    max_f64,
+            log_mode,  mean,  variance,    skewness,  kurtosis,  mode_value,  units
+        }
+    }
+}
+
+impl Rustics for FloatTimeWindow {
+    fn record_f64(&mut self, sample: f64) {
+        self.evict();
+
+        // Non-finite samples are kept out of the window, but counted
+        // in the histogram.
+
+        if !sample.is_finite() {
+            if self.non_finite.counts(&self.name, sample) {
+                if sample.is_nan() {
+                    self.nans += 1;
+                } else {
+                    self.infinities += 1;
+                }
+
+                self.histogram.borrow_mut().record(sample);
+            }
+
+            return;
+        }
+
+        self.histogram.borrow_mut().record(sample);
+
+        let now = self.now.get();
+
+        self.samples.borrow_mut().push_back((now, sample));
+    }
+
+    fn record_i64(&mut self, _sample: i64) {
+        panic!("FloatTimeWindow::record_i64:  not supported");
+    }
+
+    fn record_event(&mut self) {
+        panic!("FloatTimeWindow::record_event:  not supported");
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        panic!("FloatTimeWindow::record_event_report:  not supported");
+    }
+
+    fn record_time(&mut self, _sample: i64) {
+        panic!("FloatTimeWindow::record_time:  not supported");
+    }
+
+    fn record_interval(&mut self, _timer: &mut TimerBox) {
+        panic!("FloatTimeWindow::record_interval:  not supported");
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn class(&self) -> &str {
+        "float"
+    }
+
+    fn count(&self) -> u64 {
+        self.evict();
+
+        self.samples.borrow().len() as u64
+    }
+
+    fn log_mode(&self) -> isize {
+        panic!("FloatTimeWindow::log_mode:  not supported");
+    }
+
+    fn mean(&self) -> f64 {
+        self.crunch().mean
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    fn variance(&self) -> f64 {
+        let values   = self.values();
+        let crunched = Self::crunch_values(&values);
+
+        compute_variance(values.len() as u64, crunched.moment_2)
+    }
+
+    fn skewness(&self) -> f64 {
+        let values   = self.values();
+        let crunched = Self::crunch_values(&values);
+
+        compute_skewness(values.len() as u64, crunched.moment_2, crunched.moment_3)
+    }
+
+    fn kurtosis(&self) -> f64 {
+        let values   = self.values();
+        let crunched = Self::crunch_values(&values);
+
+        compute_kurtosis(values.len() as u64, crunched.moment_2, crunched.moment_4)
+    }
+
+    fn int_extremes(&self) -> bool {
+        false
+    }
+
+    fn float_extremes(&self) -> bool {
+        true
+    }
+
+    fn min_i64(&self) -> i64 {
+        panic!("FloatTimeWindow::min_i64:  not supported");
+    }
+
+    fn max_i64(&self) -> i64 {
+        panic!("FloatTimeWindow::max_i64:  not supported");
+    }
+
+    fn min_f64(&self) -> f64 {
+        Self::min_value(&self.values())
+    }
+
+    fn max_f64(&self) -> f64 {
+        Self::max_value(&self.values())
+    }
+
+    // The window contents depend on the current time, so there's
+    // nothing to precompute.
+
+    fn precompute(&mut self) {
+    }
+
+    fn clear(&mut self) {
+        self.samples.borrow_mut().clear();
+        self.histogram.borrow_mut().clear();
+    }
+
+    fn print(&self) {
+        self.print_opts(None, None);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        let printable = self.get_printable();
+        let printer   = printer_mut!(printer_box);
+
+        printer.print(title);
+        printable.print_common_f64_fields  (self.fields, printer);
+        printable.print_common_float_fields(self.fields, printer);
+
+        if self.histo_opts.print_histogram(self.fields) {
+            self.histogram.borrow().print(printer);
+        }
+
+        if self.histo_opts.print_cdf {
+            self.histogram.borrow().print_cdf(printer);
+        }
+
+        printer.print("");
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        Some(self.histogram.clone())
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = String::from(title)
+    }
+
+    // For internal use
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<FloatTimeWindow>(other.generic()) {
+            std::ptr::eq(self, other)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn export_stats(&self) -> ExportStats {
+        let printable       = self.get_printable();
+        let log_histogram   = None;
+        let float_histogram = Some(self.histogram.clone());
+
+        ExportStats { printable, log_histogram, float_histogram }
+    }
+}
+
+impl Histogram for FloatTimeWindow {
+    fn print_histogram(&self, printer: &mut dyn Printer) {
+        self.histogram.borrow().print(printer);
+    }
+
+    fn clear_histogram(&mut self) {
+        self.histogram.borrow_mut().clear();
+    }
+
+    fn to_log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+        Some(self.histogram.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
+
+    const HZ: u64 = 1_000_000_000;
+
+    // Make a window with a clock that only moves when the test
+    // advances it.
+
+    fn make_window(seconds: u64) -> (FloatTimeWindow, Rc<RefCell<TestSimpleClock>>) {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+        let window    = FloatTimeWindow::new("Test Window", seconds, timer, &None);
+
+        (window, clock)
+    }
+
+    fn advance(clock: &Rc<RefCell<TestSimpleClock>>, seconds: u64) {
+        clock.borrow_mut().current += (seconds * HZ) as u128;
+    }
+
+    fn test_eviction() {
+        let (mut stats, clock) = make_window(60);
+
+        assert!(stats.class()   == "float");
+        assert!(stats.seconds() == 60);
+        assert!(stats.count()   == 0);
+        assert!(stats.mean()    == 0.0);
+        assert!(stats.min_f64() == 0.0);
+
+        // Record one sample per second for 100 seconds.  Only the
+        // last 60 should remain.
+
+        for i in 1..=100 {
+            stats.record_f64(i as f64 / 2.0);
+            advance(&clock, 1);
+        }
+
+        // The clock is now at 100 seconds, so the sample recorded at
+        // 40 seconds is exactly a full period old.
+
+        assert!(stats.count()   == 59  );
+        assert!(stats.min_f64() == 21.0);
+        assert!(stats.max_f64() == 50.0);
+        assert!(stats.mean()    == 35.5);
+
+        // Queries evict samples, too.
+
+        advance(&clock, 30);
+
+        assert!(stats.count()   == 29  );
+        assert!(stats.min_f64() == 36.0);
+
+        advance(&clock, 30);
+
+        assert!(stats.count()   == 0);
+        assert!(stats.max_f64() == 0.0);
+
+        // The histogram keeps all the samples.
+
+        let histogram = stats.float_histogram().unwrap();
+
+        assert!(histogram.borrow().samples == 100);
+
+        // NaNs stay out of the window.
+
+        stats.record_f64(f64::NAN);
+        stats.record_f64(-5.0);
+
+        assert!(stats.values() == vec![ -5.0 ]);
+        assert!(histogram.borrow().nans == 1);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.count() == 0);
+    }
+
+    fn test_moments() {
+        let (mut stats, _clock) = make_window(1);
+
+        for sample in [ 2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0 ] {
+            stats.record_f64(sample);
+        }
+
+        assert!(stats.mean() == 5.0);
+        assert!((stats.variance() - 32.0 / 7.0).abs() < 1.0e-9);
+        assert!(stats.standard_deviation() == stats.variance().sqrt());
+
+        let export = stats.export_stats();
+
+        assert!(export.printable.n       == 8  );
+        assert!(export.printable.max_f64 == 9.0);
+        assert!(export.float_histogram.is_some());
+
+        let equal = stats.clone();
+
+        assert!( stats.equals(&stats));
+        assert!(!stats.equals(&equal));
+    }
+
+    // Check that a clone and the original both see the clock advance,
+    // whichever one reads it, and that each keeps its own samples.
+
+    fn test_clone() {
+        let (mut stats, clock) = make_window(10);
+
+        stats.record_f64(1.0);
+
+        let mut copy = stats.clone();
+
+        // Poll the copy every second, as a snapshot user might.
+
+        for _ in 0..15 {
+            advance(&clock, 1);
+            assert!(copy.count() <= 1);
+        }
+
+        assert!(copy.count()  == 0);
+        assert!(stats.count() == 0);
+
+        // New samples go only to the instance that records them.
+
+        stats.record_f64(2.0);
+        advance(&clock, 5);
+        copy.record_f64(3.0);
+
+        assert!(stats.values() == vec![ 2.0 ]);
+        assert!(copy.values()  == vec![ 3.0 ]);
+
+        advance(&clock, 5);
+
+        assert!(stats.count() == 0);
+        assert!(copy.count()  == 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_period() {
+        let _ = make_window(0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_record_i64() {
+        let (mut stats, _clock) = make_window(1);
+
+        stats.record_i64(1);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_eviction();
+        test_moments ();
+        test_clone   ();
+    }
+}
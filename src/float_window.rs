@@ -14,6 +14,18 @@
 //!     * This type also maintains a log histogram that contains counts
 //!       of all events seen, not just the window of n samples.
 //!
+//!     * As with IntegerWindow, enable_window_histogram() adds a second
+//!       histogram that counts only the samples currently in the window.
+//!
+//!     * as_slices() and iter() give access to the samples in the
+//!       window without copying them, from oldest to newest.
+//!
+//!     * is_warmed_up() and set_warm_up_policy() work as for
+//!       IntegerWindow.
+//!
+//!     * As with IntegerWindow, the moments are updated as samples
+//!       enter and leave the window.
+//!
 //! ## Example
 //!```
 //!    use std::rc::Rc;
@@ -83,26 +95,37 @@ use std::rc::Rc;
 use std::cell::RefCell;
 
 use super::Rustics;
+use super::copy_float_histogram;
 use super::ExportStats;
 use super::Printer;
 use super::PrinterBox;
 use super::PrinterOption;
 use super::PrintOption;
 use super::Units;
+use super::Labels;
+use super::HistoOpts;
 use super::Histogram;
 use super::LogHistogramBox;
 use super::float_histogram::FloatHistogram;
 use super::FloatHistogramBox;
 use super::integer_window::Crunched;
+use super::integer_window::WarmUpPolicy;
 use super::TimerBox;
 use super::printer_mut;
 use super::printable::Printable;
-use super::compute_variance;
-use super::compute_skewness;
-use super::compute_kurtosis;
+use super::non_finite::NonFinitePolicy;
+use super::window_moments::WindowMoments;
+use super::collection_time::CollectionTime;
+use super::stats_math::compute_variance;
+use super::stats_math::compute_skewness;
+use super::stats_math::compute_kurtosis;
 use super::sum::kbk_sum;
 use super::sum::kbk_sum_sort;
 use super::parse_print_opts;
+use super::parse_fields;
+use super::printable::PrintFields;
+use super::checkpoint::Checkpoint;
+use super::checkpoint::CheckpointReader;
 
 /// An FloatWindow instance collects f64 data samples into
 /// a fixed-size window. It also maintains a histogram based on
@@ -110,7 +133,6 @@ use super::parse_print_opts;
 ///
 /// See the module documentation for sample code.
 
-#[derive(Clone)]
 pub struct FloatWindow {
     name:           String,
     title:          String,
@@ -121,21 +143,54 @@ pub struct FloatWindow {
 
     vector:         Vec<f64>,
     index:          usize,
-    stats_valid:    bool,
-
-    // The precompute() method sets these fields.  They are
-    // valid when stats_valid is true.
-
-    mean:       f64,
-    sum:        f64,
-    moment_2:   f64,
-    moment_3:   f64,
-    moment_4:   f64,
+    moments:        WindowMoments,
 
     histogram:  FloatHistogramBox,
+    window_histo:   Option<FloatHistogram>,
 
     printer:    PrinterBox,
     units:      Units,
+    histo_opts: HistoOpts,
+    fields:     PrintFields,
+
+    warm_up_policy: WarmUpPolicy,
+    labels:         Labels,
+
+    nans:           u64,
+    infinities:     u64,
+    non_finite:     NonFinitePolicy,
+    collection:     Option<CollectionTime>,
+}
+
+// Clone makes a deep copy of the window and the histogram.  The
+// printer is shared.
+
+impl Clone for FloatWindow {
+    fn clone(&self) -> FloatWindow {
+        let histogram = copy_float_histogram(&self.histogram);
+
+        FloatWindow {
+            name:           self.name.clone(),
+            title:          self.title.clone(),
+            window_size:    self.window_size,
+            id:             self.id,
+            vector:         self.vector.clone(),
+            index:          self.index,
+            moments:        self.moments,
+            histogram,
+            window_histo:   self.window_histo.clone(),
+            printer:        self.printer.clone(),
+            units:          self.units.clone(),
+            histo_opts:     self.histo_opts,
+            fields:         self.fields,
+            warm_up_policy: self.warm_up_policy,
+            labels:         self.labels.clone(),
+            nans:           self.nans,
+            infinities:     self.infinities,
+            non_finite:     self.non_finite,
+            collection:     self.collection.clone()
+        }
+    }
 }
 
 impl FloatWindow {
@@ -147,20 +202,24 @@ impl FloatWindow {
             panic!("The window size is zero.");
         }
 
-        let (printer, title, units, _histo_opts) = parse_print_opts(print_opts, name);
+        let (printer, title, units, histo_opts) = parse_print_opts(print_opts, name);
 
         let name          = String::from(name);
         let id            = usize::MAX;
         let vector        = Vec::with_capacity(window_size);
         let index         = 0;
-        let stats_valid   = false;
-        let mean          = 0.0;
-        let sum           = 0.0;
-        let moment_2      = 0.0;
-        let moment_3      = 0.0;
-        let moment_4      = 0.0;
+        let moments       = WindowMoments::new(window_size);
         let histogram     = FloatHistogram::new(print_opts);
         let histogram     = Rc::from(RefCell::new(histogram));
+        let window_histo  = None;
+
+        let warm_up_policy = WarmUpPolicy::default();
+        let labels         = Labels::new();
+        let nans           = 0;
+        let infinities     = 0;
+        let non_finite     = NonFinitePolicy::default();
+        let collection     = None;
+        let fields         = parse_fields(print_opts);
 
         FloatWindow {
             name,
@@ -169,15 +228,19 @@ impl FloatWindow {
             window_size,
             vector,
             index,
-            stats_valid,
-            mean,
-            sum,
-            moment_2,
-            moment_3,
-            moment_4,
+            moments,
             histogram,
+            window_histo,
             printer,
-            units
+            units,
+            histo_opts,
+            fields,
+            warm_up_policy,
+            labels,
+            nans,
+            infinities,
+            non_finite,
+            collection
         }
     }
 
@@ -185,18 +248,153 @@ impl FloatWindow {
         self.units = units;
     }
 
-    fn sum(&self) -> f64 {
-        let mut sum = 0.0;
+    /// Returns true if the window has been filled at least once since
+    /// it was created or cleared.
+
+    pub fn is_warmed_up(&self) -> bool {
+        self.vector.len() == self.window_size
+    }
+
+    /// Sets the handling of NaN and infinite samples.  See the
+    /// non_finite module.  Such samples never enter the window.
+
+    pub fn set_non_finite_policy(&mut self, policy: NonFinitePolicy) {
+        self.non_finite = policy;
+    }
+
+    /// Returns the handling of NaN and infinite samples.
+
+    pub fn non_finite_policy(&self) -> NonFinitePolicy {
+        self.non_finite
+    }
+
+    /// Returns the number of NaN samples counted.
+
+    pub fn nans(&self) -> u64 {
+        self.nans
+    }
+
+    /// Returns the number of infinite samples counted.
+
+    pub fn infinities(&self) -> u64 {
+        self.infinities
+    }
+
+    /// Sets how print() handles a window that hasn't warmed up.  See
+    /// IntegerWindow::set_warm_up_policy().
+
+    pub fn set_warm_up_policy(&mut self, policy: WarmUpPolicy) {
+        self.warm_up_policy = policy;
+    }
+
+    /// Returns the warm-up policy.
+
+    pub fn warm_up_policy(&self) -> WarmUpPolicy {
+        self.warm_up_policy
+    }
+
+    /// Returns the samples in the window as two slices, without copying.
+    /// The window is a ring buffer, so the samples might wrap around the
+    /// end of the vector.  The first slice holds the oldest samples, and
+    /// the samples in each slice are in the order recorded.
+
+    pub fn as_slices(&self) -> (&[f64], &[f64]) {
+        (&self.vector[self.index..], &self.vector[..self.index])
+    }
+
+    /// Returns an iterator over the samples in the window, from oldest
+    /// to newest.
+
+    pub fn iter(&self) -> impl Iterator<Item = &f64> + '_ {
+        let (older, newer) = self.as_slices();
+
+        older.iter().chain(newer.iter())
+    }
+
+    /// Returns the number of samples in the window.
+
+    pub fn len(&self) -> usize {
+        self.vector.len()
+    }
+
+    /// Returns true if the window holds no samples.
+
+    pub fn is_empty(&self) -> bool {
+        self.vector.is_empty()
+    }
 
-        for sample in self.vector.iter() {
-            sum += *sample;
+    /// Starts keeping a histogram of only the samples currently in the
+    /// window.  Any samples already in the window are included.
+
+    pub fn enable_window_histogram(&mut self) {
+        let print_opts = self.histogram.borrow().print_opts.clone();
+
+        self.window_histo = Some(FloatHistogram::new(&print_opts));
+
+        if let Some(window_histo) = &mut self.window_histo {
+            for sample in self.vector.iter() {
+                window_histo.record(*sample);
+            }
         }
+    }
+
+    /// Returns the histogram of the samples in the window, if one is
+    /// enabled.
 
-        sum
+    pub fn window_histogram(&self) -> Option<&FloatHistogram> {
+        self.window_histo.as_ref()
+    }
+
+    /// Saves the samples in the window and the histogram as a string.
+    /// See the checkpoint module.
+
+    pub fn checkpoint(&self) -> String {
+        let mut checkpoint = Checkpoint::new("float_window");
+        let     samples: Vec<f64> = self.iter().copied().collect();
+
+        checkpoint.push_str ("name",        &self.name             );
+        checkpoint.push_str ("title",       &self.title            );
+        checkpoint.push_u64 ("window_size", self.window_size as u64);
+        checkpoint.push_f64s("samples",     &samples               );
+        checkpoint.push_float_histogram("float", &self.histogram.borrow());
+        checkpoint.text()
+    }
+
+    /// Restores an instance saved by checkpoint().  The result is None
+    /// if the checkpoint is malformed or was made for another type.
+
+    pub fn restore(text: &str, print_opts: &PrintOption) -> Option<FloatWindow> {
+        let reader      = CheckpointReader::new_kind(text, "float_window")?;
+        let name        = reader.string("name" )?;
+        let title       = reader.string("title")?;
+        let window_size = reader.usize("window_size")?;
+        let samples     = reader.f64s("samples")?;
+        let histogram   = reader.float_histogram("float", print_opts)?;
+
+        if window_size == 0 || samples.len() > window_size {
+            return None;
+        }
+
+        let mut window = FloatWindow::new(&name, window_size, print_opts);
+
+        window.title     = title;
+        window.vector    = samples;
+        window.histogram = Rc::from(RefCell::new(histogram));
+
+        window.rebuild_moments();
+        Some(window)
+    }
+
+    // Recompute the incremental moments from the samples in the window.
+
+    fn rebuild_moments(&mut self) {
+        self.moments.rebuild(self.vector.iter().copied());
     }
 
     /// Gather the samples and compute summary statistics
-    /// for the current samples in the window.
+    /// for the current samples in the window.  This function scans the
+    /// whole window, unlike queries like mean(), which use statistics
+    /// kept as samples are recorded.
 
     pub fn crunch(&self) -> Crunched {
         if self.vector.is_empty() {
@@ -272,10 +470,13 @@ impl FloatWindow {
         max
     }
 
+    // The extremes require a scan of the window anyway, so this
+    // function uses the precise moments from crunch().
+
     pub fn get_printable(&self) -> Printable {
         let n          = self.vector.len() as u64;
-        let nans       = 0;
-        let infinities = 0;
+        let nans       = self.nans;
+        let infinities = self.infinities;
         let min_i64    = i64::MIN;
         let max_i64    = i64::MAX;
         let min_f64    = self.compute_min();
@@ -284,24 +485,11 @@ impl FloatWindow {
         let mode_value = self.histogram.borrow().mode_value();
         let units      = self.units.clone();
 
-        let mean;
-        let variance;
-        let skewness;
-        let kurtosis;
-
-        if self.stats_valid {
-            mean     = self.mean();
-            variance = self.variance();
-            skewness = self.skewness();
-            kurtosis = self.kurtosis();
-        } else {
-            let crunched = self.crunch();
-
-            mean     = crunched.mean;
-            variance = compute_variance(n, crunched.moment_2);
-            skewness = compute_skewness(n, crunched.moment_2, crunched.moment_3);
-            kurtosis = compute_kurtosis(n, crunched.moment_2, crunched.moment_4);
-        }
+        let crunched   = self.crunch();
+        let mean       = crunched.mean;
+        let variance   = compute_variance(n, crunched.moment_2);
+        let skewness   = compute_skewness(n, crunched.moment_2, crunched.moment_3);
+        let kurtosis   = compute_kurtosis(n, crunched.moment_2, crunched.moment_4);
 
         Printable {
             n,         nans,  infinities,  min_i64,   max_i64,   min_f64,    max_f64,
@@ -312,19 +500,52 @@ impl FloatWindow {
 
 impl Rustics for FloatWindow {
     fn record_f64(&mut self, sample: f64) {
+        // Non-finite samples are kept out of the window, but counted
+        // in the histogram.
+
+        if !sample.is_finite() {
+            if self.non_finite.counts(&self.name, sample) {
+                if sample.is_nan() {
+                    self.nans += 1;
+                } else {
+                    self.infinities += 1;
+                }
+
+                self.histogram.borrow_mut().record(sample);
+            }
+
+            return;
+        }
+
         if self.vector.len() == self.window_size {
+            let old = self.vector[self.index];
+
+            self.moments.replace(old, sample);
             self.vector[self.index] = sample;
+
+            if let Some(window_histo) = &mut self.window_histo {
+                window_histo.remove(old);
+            }
+
             self.index += 1;
 
             if self.index >= self.window_size {
                 self.index = 0;
             }
         } else {
+            self.moments.add(sample);
             self.vector.push(sample);
         }
 
+        if self.moments.needs_rebuild() {
+            self.rebuild_moments();
+        }
+
         self.histogram.borrow_mut().record(sample);
-        self.stats_valid = false;
+
+        if let Some(window_histo) = &mut self.window_histo {
+            window_histo.record(sample);
+        }
     }
 
     fn record_i64(&mut self, _sample: i64) {
@@ -368,16 +589,7 @@ impl Rustics for FloatWindow {
     }
 
     fn mean(&self) -> f64 {
-        if self.vector.is_empty() {
-            return 0.0;
-        }
-
-        if self.stats_valid {
-            return self.mean;
-        }
-
-        let sample_sum = self.sum();
-        sample_sum / self.vector.len() as f64
+        self.moments.mean()
     }
 
     fn standard_deviation(&self) -> f64 {
@@ -385,27 +597,24 @@ impl Rustics for FloatWindow {
     }
 
     fn variance(&self) -> f64 {
-        let count = self.vector.len() as u64;
-
-        if self.stats_valid {
-            compute_variance(count, self.moment_2)
-        } else {
-            let crunched = self.crunch();
+        let count    = self.vector.len() as u64;
+        let crunched = self.moments.crunched();
 
-            compute_variance(count, crunched.moment_2)
-        }
+        compute_variance(count, crunched.moment_2)
     }
 
     fn skewness(&self) -> f64 {
-        let count = self.vector.len() as u64;
+        let count    = self.vector.len() as u64;
+        let crunched = self.moments.crunched();
 
-        compute_skewness(count, self.moment_2, self.moment_3)
+        compute_skewness(count, crunched.moment_2, crunched.moment_3)
     }
 
     fn kurtosis(&self) -> f64 {
-        let count = self.vector.len() as u64;
+        let count    = self.vector.len() as u64;
+        let crunched = self.moments.crunched();
 
-        compute_kurtosis(count, self.moment_2, self.moment_4)
+        compute_kurtosis(count, crunched.moment_2, crunched.moment_4)
     }
 
     fn int_extremes(&self) -> bool {
@@ -432,27 +641,27 @@ impl Rustics for FloatWindow {
         panic!("FloatWindow::max_i64:  not supported");
     }
 
-    fn precompute(&mut self) {
-        if self.stats_valid {
-            return;
-        }
+    // The statistics are kept up to date as samples are recorded, so
+    // this function just rebuilds them to discard any rounding error.
 
-        let crunched = self.crunch();
-
-        self.mean        = crunched.mean;
-        self.sum         = crunched.sum;
-        self.moment_2    = crunched.moment_2;
-        self.moment_3    = crunched.moment_3;
-        self.moment_4    = crunched.moment_4;
-        self.stats_valid = true;
+    fn precompute(&mut self) {
+        self.rebuild_moments();
     }
 
     fn clear(&mut self) {
-        self.index       = 0;
-        self.stats_valid = false;
+        self.index = 0;
 
+        self.moments.clear();
         self.vector.clear();
         self.histogram.borrow_mut().clear();
+
+        if let Some(window_histo) = &mut self.window_histo {
+            window_histo.clear();
+        }
+
+        if let Some(collection) = &mut self.collection {
+            collection.restart();
+        }
     }
 
     fn print(&self) {
@@ -477,10 +686,40 @@ impl Rustics for FloatWindow {
         let printable = self.get_printable();
         let printer   = printer_mut!(printer_box);
 
+        let policy    = self.warm_up_policy;
+        let warmed_up = self.is_warmed_up();
+
         printer.print(title);
-        printable.print_common_f64(printer);
-        printable.print_common_float(printer);
-        self.histogram.borrow().print(printer);
+
+        if policy.print_statistics(warmed_up) {
+            printable.print_common_f64_fields  (self.fields, printer);
+            printable.print_common_float_fields(self.fields, printer);
+        } else {
+            Printable::print_integer("Count", printable.n as i64, printer);
+        }
+
+        if policy.print_annotation(warmed_up) {
+            WarmUpPolicy::print_fill(printable.n, self.window_size, printer);
+        }
+
+        // The rate counts all the samples in the histogram.
+
+        if let Some(collection) = &self.collection {
+            collection.print(self.histogram.borrow().samples as u64, printer);
+        }
+
+        if self.histo_opts.print_histogram(self.fields) {
+            self.histogram.borrow().print(printer);
+
+            if let Some(window_histo) = &self.window_histo {
+                printer.print("  Window Contents");
+                window_histo.print(printer);
+            }
+        }
+
+        if self.histo_opts.print_cdf {
+            self.histogram.borrow().print_cdf(printer);
+        }
         printer.print("");
     }
 
@@ -492,10 +731,30 @@ impl Rustics for FloatWindow {
         Some(self.histogram.clone())
     }
 
+    fn supports_collection_time(&self) -> bool {
+        true
+    }
+
+    fn set_collection_clock(&mut self, clock: Option<TimerBox>) {
+        self.collection = clock.map(CollectionTime::new);
+    }
+
+    fn collection_seconds(&self) -> Option<f64> {
+        self.collection.as_ref().map(|collection| collection.seconds())
+    }
+
     fn set_title(&mut self, title: &str) {
         self.title = String::from(title)
     }
 
+    fn set_label(&mut self, key: &str, value: &str) {
+        self.labels.insert(key.to_string(), value.to_string());
+    }
+
+    fn labels(&self) -> Labels {
+        self.labels.clone()
+    }
+
     // For internal use
 
     fn equals(&self, other: &dyn Rustics) -> bool {
@@ -555,6 +814,9 @@ mod tests {
     use crate::tests::continuing_box;
     use crate::tests::bytes;
     use crate::tests::check_printer_box;
+    use crate::capture_printer::CapturePrinter;
+    use std::rc::Rc;
+    use std::cell::RefCell;
 
     pub fn test_simple_float_window() {
         let window_size = 100;
@@ -850,7 +1112,7 @@ mod tests {
         let     title      = None;
         let     units      = bytes();
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 
         let     name       = "Test Statistics";
         let     samples    = 1000;
@@ -863,10 +1125,178 @@ mod tests {
         stats.print();
     }
 
+    fn test_checkpoint() {
+        let window_size = 64;
+        let mut window  = FloatWindow::new("Checkpoint Test", window_size, &None);
+
+        for i in 0..100 {
+            window.record_f64((i as f64).sqrt());
+        }
+
+        let     checkpoint = window.checkpoint();
+        let mut restored   = FloatWindow::restore(&checkpoint, &None).unwrap();
+
+        assert!(restored.count()   == window.count()  );
+        assert!(restored.mean()    == window.mean()   );
+        assert!(restored.min_f64() == window.min_f64());
+        assert!(restored.max_f64() == window.max_f64());
+
+        assert!(restored.histogram.borrow().equals(&window.histogram.borrow()));
+
+        // The samples are stored in a different order, so the sums
+        // can differ in the last bits.
+
+        for i in 0..10 {
+            window.record_f64(-i as f64);
+            restored.record_f64(-i as f64);
+        }
+
+        assert!((restored.mean() - window.mean()).abs() <= 1.0e-12 * window.mean().abs());
+        assert!(restored.min_f64() == window.min_f64());
+
+        assert!(FloatWindow::restore(&checkpoint.replace("float_window", "x"), &None).is_none());
+    }
+
+    fn test_iter() {
+        let mut window = FloatWindow::new("Iter Test", 3, &None);
+
+        assert!(window.is_empty());
+
+        for i in 1..=5 {
+            window.record_f64(i as f64);
+        }
+
+        let (older, newer) = window.as_slices();
+
+        assert!(older == [ 3.0 ]);
+        assert!(newer == [ 4.0, 5.0 ]);
+        assert!(window.len() == 3);
+
+        let sum: f64 = window.iter().sum();
+
+        assert!(sum == 12.0);
+    }
+
+    fn test_warm_up() {
+        let     capture = Rc::new(RefCell::new(CapturePrinter::new()));
+        let     printer = capture.clone() as PrinterBox;
+        let mut window  = FloatWindow::new("Warm Up", 2, &None);
+
+        window.set_warm_up_policy(WarmUpPolicy::Annotate);
+        window.record_f64(1.0);
+        window.print_opts(Some(printer.clone()), None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(!window.is_warmed_up());
+        assert!(window.warm_up_policy() == WarmUpPolicy::Annotate);
+        assert!(lines.iter().any(|line| line.contains("Mean")));
+        assert!(lines.iter().any(|line| line.contains("1 of 2 samples")));
+
+        window.record_f64(2.0);
+
+        assert!(window.is_warmed_up());
+
+        window.clear();
+
+        assert!(!window.is_warmed_up());
+    }
+
+
+    fn test_clone() {
+        let mut window = FloatWindow::new("Clone Test", 2, &None);
+
+        window.record_f64(1.0);
+
+        let mut copy = window.clone();
+
+        copy.record_f64(2.0);
+
+        assert!(window.count() == 1);
+        assert!(copy  .count() == 2);
+        assert!(window.float_histogram().unwrap().borrow().samples == 1);
+        assert!(copy  .float_histogram().unwrap().borrow().samples == 2);
+    }
+
+    fn test_non_finite() {
+        let mut window = FloatWindow::new("Non-finite Test", 4, &None);
+
+        assert!(window.non_finite_policy() == NonFinitePolicy::CountAndIgnore);
+
+        window.record_f64(1.0);
+        window.record_f64(f64::NAN);
+        window.record_f64(f64::INFINITY);
+        window.record_f64(3.0);
+
+        // The bad samples stay out of the window.
+
+        assert!(window.count()      == 2  );
+        assert!(window.mean()       == 2.0);
+        assert!(window.nans()       == 1  );
+        assert!(window.infinities() == 1  );
+
+        let printable = window.get_printable();
+
+        assert!(printable.nans       == 1);
+        assert!(printable.infinities == 1);
+        assert!(printable.rejected() == 2);
+
+        // Ignore drops them without counting.
+
+        let mut window = FloatWindow::new("Ignore Test", 4, &None);
+
+        window.set_non_finite_policy(NonFinitePolicy::Ignore);
+        window.record_f64(f64::NEG_INFINITY);
+        window.record_f64(f64::NAN);
+        window.record_f64(5.0);
+
+        assert!(window.count()                   == 1);
+        assert!(window.get_printable().rejected() == 0);
+    }
+
+    fn test_window_histogram() {
+        let mut window = FloatWindow::new("Window Histogram", 4, &None);
+
+        window.record_f64(1.0);
+        window.enable_window_histogram();
+
+        assert!(window.window_histogram().unwrap().samples == 1);
+
+        for i in 2..=10 {
+            window.record_f64(i as f64 * 1.0e6);
+        }
+
+        // The NaN stays out of the window histogram.
+
+        window.record_f64(f64::NAN);
+
+        let     window_histo = window.window_histogram().unwrap();
+        let mut expected     = FloatHistogram::new(&None);
+
+        for sample in window.iter() {
+            expected.record(*sample);
+        }
+
+        assert!(window.histogram.borrow().samples == 10);
+        assert!(window_histo.samples              == 4 );
+        assert!(window_histo.nans                 == 0 );
+        assert!(window_histo.equals(&expected));
+
+        window.clear();
+
+        assert!(window.window_histogram().unwrap().samples == 0);
+    }
+
     #[test]
     fn run_tests() {
         test_casting_functions  ();
         test_simple_float_window();
         test_print_output       ();
+        test_checkpoint         ();
+        test_iter               ();
+        test_warm_up            ();
+        test_clone              ();
+        test_non_finite         ();
+        test_window_histogram   ();
     }
 }
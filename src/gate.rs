@@ -0,0 +1,525 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Types
+//!
+//! * Gate
+//!     * Gate holds a list of thresholds for named members of a set,
+//!       like a maximum mean latency or a minimum throughput.  After a
+//!       benchmark run, the gate is evaluated against the set, and the
+//!       result lists any thresholds that were violated.  This allows
+//!       Rustics-based benchmarks to be used as regression tests in CI.
+//!
+//!     * Members are found by name, as with the rustics_snapshot! macro,
+//!       by traversing the set and its subsets.  The first member with a
+//!       matching name is used.  A threshold for a member that can't be
+//!       found or has no samples counts as a violation.
+//!
+//!     * Percentiles are estimated from the log or float histogram of the
+//!       member, so they are upper bounds.  See Printable::log_percentile.
+//!
+//!     * A throughput threshold divides the count of the member by the
+//!       length of the run, which is given via set_elapsed() in seconds.
+//!
+//! * Threshold
+//!     * Threshold specifies one limit on a statistic.
+//!
+//! * GateReport
+//!     * GateReport is the result of evaluating a Gate.  passed() returns
+//!       true if no threshold was violated, and print() produces a
+//!       summary that lists the violations.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::rc_sets::RcSet;
+//!     use rustics::gate::Gate;
+//!     use rustics::gate::Threshold;
+//!
+//!     let mut set      = RcSet::new("Benchmark", 4, 0, &None);
+//!     let     latency  = set.add_running_integer("Latency", None);
+//!     let     requests = set.add_counter("Requests", None);
+//!
+//!     for i in 1..=1000 {
+//!         latency.borrow_mut().record_i64(i);
+//!         requests.borrow_mut().record_event();
+//!     }
+//!
+//!     // Require a mean under 600, a p99 under 2048, and at least
+//!     // 50 requests per second over a 10 second run.
+//!
+//!     let mut gate = Gate::new();
+//!
+//!     gate.add("Latency",  Threshold::MaxMean(600.0));
+//!     gate.add("Latency",  Threshold::MaxPercentile(99.0, 2048.0));
+//!     gate.add("Requests", Threshold::MinThroughput(50.0));
+//!     gate.set_elapsed(10.0);
+//!
+//!     let report = gate.evaluate_rc(&mut set);
+//!
+//!     assert!(report.passed());
+//!
+//!     // Tighten the mean limit.
+//!
+//!     gate.add("Latency", Threshold::MaxMean(100.0));
+//!
+//!     let report = gate.evaluate_rc(&mut set);
+//!
+//!     assert!(!report.passed());
+//!     assert!(report.violations().len() == 1);
+//!
+//!     report.print(None);
+//!```
+
+use super::Rustics;
+use super::PrinterOption;
+use super::stdout_printer;
+use super::printer_mut;
+use super::printable::Printable;
+use super::rc_sets::RcSet;
+use super::arc_sets::ArcSet;
+use super::rustics_set::RusticsSet;
+
+/// Threshold defines one limit checked by a Gate.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Threshold {
+    MaxMean(f64),
+    MaxMax(f64),
+    MaxPercentile(f64, f64),    // (percentile, limit)
+    MinCount(u64),
+    MinThroughput(f64),         // in samples per second
+}
+
+impl Threshold {
+    /// Returns a short description of the threshold for reports.
+
+    pub fn describe(&self) -> String {
+        match self {
+            Threshold::MaxMean(limit)           => format!("mean <= {}", limit),
+            Threshold::MaxMax(limit)            => format!("max <= {}", limit),
+            Threshold::MaxPercentile(p, limit)  => format!("p{} <= {}", p, limit),
+            Threshold::MinCount(limit)          => format!("count >= {}", limit),
+            Threshold::MinThroughput(limit)     => format!("throughput >= {}/s", limit),
+        }
+    }
+}
+
+/// GateCheck pairs a member name with a threshold.
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct GateCheck {
+    pub name:       String,
+    pub threshold:  Threshold,
+}
+
+/// Violation describes a threshold that wasn't met.  The actual
+/// value is None if it couldn't be computed, for example, because
+/// the member wasn't found.
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Violation {
+    pub name:       String,
+    pub threshold:  Threshold,
+    pub actual:     Option<f64>,
+    pub reason:     String,
+}
+
+/// GateReport holds the result of evaluating a Gate.
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct GateReport {
+    checks:     usize,
+    violations: Vec<Violation>,
+}
+
+impl GateReport {
+    /// Returns true if every threshold was met.
+
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Returns the number of thresholds checked.
+
+    pub fn checks(&self) -> usize {
+        self.checks
+    }
+
+    /// Returns the thresholds that weren't met.
+
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    /// Prints a summary of the result, followed by one line per
+    /// violation.  The default printer is stdout.
+
+    pub fn print(&self, printer: PrinterOption) {
+        let printer = printer.unwrap_or_else(stdout_printer);
+        let printer = printer_mut!(printer);
+
+        let header =
+            if self.passed() {
+                format!("Gate:  passed ({} checks)", self.checks)
+            } else {
+                format!("Gate:  FAILED ({} of {} checks)", self.violations.len(), self.checks)
+            };
+
+        printer.print(&header);
+
+        for violation in self.violations.iter() {
+            let line =
+                format!("    {}:  {} ({})", violation.name, violation.threshold.describe(),
+                    violation.reason);
+
+            printer.print(&line);
+        }
+    }
+}
+
+/// Gate holds a list of thresholds to check against a set.
+
+#[derive(Clone, Default)]
+pub struct Gate {
+    checks:     Vec<GateCheck>,
+    elapsed:    Option<f64>,
+}
+
+impl Gate {
+    /// Creates an empty gate.
+
+    pub fn new() -> Gate {
+        let checks  = Vec::new();
+        let elapsed = None;
+
+        Gate { checks, elapsed }
+    }
+
+    /// Adds a threshold for the set member with the given name.
+
+    pub fn add(&mut self, name: &str, threshold: Threshold) {
+        let name = name.to_string();
+
+        self.checks.push(GateCheck { name, threshold });
+    }
+
+    /// Sets the length of the run in seconds, which is used for the
+    /// throughput thresholds.
+
+    pub fn set_elapsed(&mut self, seconds: f64) {
+        if !seconds.is_finite() || seconds <= 0.0 {
+            panic!("Gate::set_elapsed:  The time must be positive.");
+        }
+
+        self.elapsed = Some(seconds);
+    }
+
+    /// Returns the list of thresholds.
+
+    pub fn checks(&self) -> &[GateCheck] {
+        &self.checks
+    }
+
+    /// Evaluates the gate against a set of either type and its
+    /// subsets.
+
+    pub fn evaluate(&self, set: &dyn RusticsSet) -> GateReport {
+        let mut collector = GateCollector::new(self);
+
+        set.visit_all(&mut |member| collector.collect(member));
+        self.report(collector)
+    }
+
+    /// Evaluates the gate against an RcSet and its subsets.
+
+    pub fn evaluate_rc(&self, set: &mut RcSet) -> GateReport {
+        self.evaluate(set)
+    }
+
+    /// Evaluates the gate against an ArcSet and its subsets.
+
+    pub fn evaluate_arc(&self, set: &mut ArcSet) -> GateReport {
+        self.evaluate(set)
+    }
+
+    fn report(&self, collector: GateCollector) -> GateReport {
+        let     checks     = self.checks.len();
+        let mut violations = Vec::new();
+
+        for (check, result) in self.checks.iter().zip(collector.results) {
+            let name      = check.name.clone();
+            let threshold = check.threshold;
+
+            let (actual, reason) =
+                match result {
+                    None                      => (None, "member not found".to_string()),
+                    Some(Err(reason))         => (None, reason),
+                    Some(Ok((_, true)))       => continue,
+                    Some(Ok((actual, false))) => (Some(actual), format!("actual {}", format_value(actual))),
+                };
+
+            violations.push(Violation { name, threshold, actual, reason });
+        }
+
+        GateReport { checks, violations }
+    }
+}
+
+// Formats a value for a report.
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        Printable::commas_i64(value as i64)
+    } else {
+        format!("{:.3}", value)
+    }
+}
+
+// The result of one check:  the actual value and whether the
+// threshold was met, or a reason that the value couldn't be
+// computed.
+
+type CheckResult = Result<(f64, bool), String>;
+
+// GateCollector finds the members for the checks as the set is
+// traversed.
+
+struct GateCollector<'a> {
+    gate:       &'a Gate,
+    results:    Vec<Option<CheckResult>>,
+}
+
+impl<'a> GateCollector<'a> {
+    fn new(gate: &'a Gate) -> GateCollector<'a> {
+        let results = vec![None; gate.checks.len()];
+
+        GateCollector { gate, results }
+    }
+
+    fn collect(&mut self, member: &dyn Rustics) {
+        let name = member.name();
+
+        for i in 0..self.gate.checks.len() {
+            let check = &self.gate.checks[i];
+
+            if self.results[i].is_none() && check.name == name {
+                self.results[i] = Some(self.evaluate(member, &check.threshold));
+            }
+        }
+    }
+
+    fn evaluate(&self, member: &dyn Rustics, threshold: &Threshold) -> CheckResult {
+        let count = member.count();
+
+        match threshold {
+            Threshold::MinCount(limit) => {
+                return Ok((count as f64, count >= *limit));
+            }
+
+            Threshold::MinThroughput(limit) => {
+                let seconds = self.gate.elapsed.ok_or("no elapsed time set")?;
+                let rate    = count as f64 / seconds;
+
+                return Ok((rate, rate >= *limit));
+            }
+
+            _ => { }
+        }
+
+        if count == 0 {
+            return Err("no samples".to_string());
+        }
+
+        match threshold {
+            Threshold::MaxMean(limit) => {
+                // Some types panic on mean(), so use the exported
+                // value, and only for types that keep a mean.
+
+                if matches!(member.class(), "counter" | "cardinality" | "peak") {
+                    return Err("no mean kept".to_string());
+                }
+
+                let mean = member.export_stats().printable.mean;
+
+                Ok((mean, mean <= *limit))
+            }
+
+            Threshold::MaxMax(limit) => {
+                let max =
+                    if member.int_extremes() {
+                        member.max_i64() as f64
+                    } else if member.float_extremes() {
+                        member.max_f64()
+                    } else {
+                        return Err("no maximum kept".to_string());
+                    };
+
+                Ok((max, max <= *limit))
+            }
+
+            Threshold::MaxPercentile(percentile, limit) => {
+                let value =
+                    if let Some(histogram) = member.log_histogram() {
+                        Printable::log_percentile(&histogram.borrow(), *percentile) as f64
+                    } else if let Some(histogram) = member.float_histogram() {
+                        Printable::float_percentile(&histogram.borrow(), *percentile)
+                    } else {
+                        return Err("no histogram".to_string());
+                    };
+
+                Ok((value, value <= *limit))
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arc_item_mut;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_counters;
+
+    fn make_set() -> RcSet {
+        let mut set      = RcSet::new("Bench", 4, 1, &None);
+        let     latency  = set.add_running_integer("Latency", None);
+        let     requests = set.add_counter("Requests", None);
+        let     subset   = set.add_subset("Sub", 1, 0);
+        let     size     = subset.borrow_mut().add_running_float("Size", None);
+        let     _empty   = set.add_running_integer("Empty", None);
+
+        for i in 1..=100 {
+            latency.borrow_mut().record_i64(i);
+            requests.borrow_mut().record_event();
+            size.borrow_mut().record_f64(i as f64 / 10.0);
+        }
+
+        set
+    }
+
+    fn test_pass() {
+        let mut set  = make_set();
+        let mut gate = Gate::new();
+
+        gate.add("Latency",  Threshold::MaxMean(50.5));
+        gate.add("Latency",  Threshold::MaxMax(100.0));
+        gate.add("Latency",  Threshold::MaxPercentile(50.0, 64.0));
+        gate.add("Requests", Threshold::MinCount(100));
+        gate.add("Requests", Threshold::MinThroughput(10.0));
+        gate.add("Size",     Threshold::MaxMax(10.0));
+
+        gate.set_elapsed(10.0);
+
+        let report = gate.evaluate_rc(&mut set);
+
+        assert!(report.passed());
+        assert!(report.checks()        == 6);
+        assert!(gate.checks().len()    == 6);
+        assert!(report.violations().is_empty());
+    }
+
+    fn test_fail() {
+        let mut set  = make_set();
+        let mut gate = Gate::new();
+
+        gate.add("Latency",  Threshold::MaxMean(50.0));
+        gate.add("Latency",  Threshold::MaxPercentile(99.0, 100.0));
+        gate.add("Requests", Threshold::MinThroughput(10.0));
+        gate.add("Missing",  Threshold::MinCount(1));
+        gate.add("Empty",    Threshold::MaxMean(1.0));
+        gate.add("Requests", Threshold::MinCount(1));
+
+        let report     = gate.evaluate_rc(&mut set);
+        let violations = report.violations();
+
+        assert!(!report.passed());
+        assert!(violations.len() == 5);
+
+        assert!(violations[0].actual == Some(50.5 ));
+        assert!(violations[1].actual == Some(128.0));
+        assert!(violations[2].actual.is_none());
+        assert!(violations[3].reason == "member not found");
+        assert!(violations[4].reason == "no samples");
+
+        let expected =
+            [
+                "Gate:  FAILED (5 of 6 checks)",
+                "    Latency:  mean <= 50 (actual 50.500)",
+                "    Latency:  p99 <= 100 (actual 128)",
+                "    Requests:  throughput >= 10/s (no elapsed time set)",
+                "    Missing:  count >= 1 (member not found)",
+                "    Empty:  mean <= 1 (no samples)"
+            ];
+
+        let printer = check_printer_box(&expected, true, false);
+
+        report.print(Some(printer.clone()));
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+    }
+
+    fn test_arc() {
+        let mut set      = ArcSet::new("Bench", 1, 0, &None);
+        let     requests = set.add_counter("Requests", None);
+        let mut gate     = Gate::new();
+
+        arc_item_mut!(requests).record_event();
+
+        gate.add("Requests", Threshold::MinCount(1));
+
+        assert!(gate.evaluate_arc(&mut set).passed());
+
+        gate.add("Requests", Threshold::MinCount(2));
+
+        let report = gate.evaluate_arc(&mut set);
+
+        assert!(!report.passed());
+        assert!(report.violations()[0].actual == Some(1.0));
+        assert!(gate.evaluate(&set) == report);
+    }
+
+    // A mean threshold on a type that keeps no mean is reported as
+    // a violation rather than a panic.
+
+    fn test_no_mean() {
+        let mut set  = make_set();
+        let mut gate = Gate::new();
+
+        gate.add("Requests", Threshold::MaxMean(1.0));
+        gate.add("Size",     Threshold::MaxMean(5.05));
+
+        let report     = gate.evaluate_rc(&mut set);
+        let violations = report.violations();
+
+        assert!(!report.passed());
+        assert!(violations.len()      == 1);
+        assert!(violations[0].reason  == "no mean kept");
+        assert!(violations[0].actual.is_none());
+    }
+
+    #[test]
+    fn run_tests() {
+        test_pass   ();
+        test_fail   ();
+        test_arc    ();
+        test_no_mean();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_elapsed() {
+        let mut gate = Gate::new();
+
+        gate.set_elapsed(0.0);
+    }
+}
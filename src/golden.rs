@@ -0,0 +1,729 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * GoldenReport
+//!     * GoldenReport holds the lines of a printed report, as captured by
+//!       a CapturePrinter, so that it can be saved to a "golden" file and
+//!       later compared with a new run.  This lets an application guard
+//!       its metrics output in its tests without exact string matches.
+//!
+//!     * compare() matches the lines token by token.  Numbers, including
+//!       integers with commas and the mantissa and exponent pairs printed
+//!       by Printable::print_float, are compared within a tolerance, and
+//!       the other tokens must match exactly.  A change in the time unit
+//!       chosen by print_time() thus counts as a difference.
+//!
+//!     * check_golden() compares a report with a golden file, and records
+//!       the file instead if it doesn't exist yet or if the environment
+//!       variable RUSTICS_UPDATE_GOLDEN is set.
+//!
+//! * Tolerances
+//!     * Tolerances holds the tolerance used for each field.  A field is
+//!       named by the text before the first number on its line, like
+//!       "Mean" or "Std Dev", and fields without an entry use the default
+//!       tolerance, which requires an exact match unless it is set.
+//!
+//! * GoldenDiff
+//!     * GoldenDiff is the result of a comparison.  passed() returns true
+//!       if every line matched, and print() produces a summary followed by
+//!       the expected and actual text of each line that differs.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::running_integer::RunningInteger;
+//!     use rustics::golden::GoldenReport;
+//!     use rustics::golden::Tolerance;
+//!     use rustics::golden::Tolerances;
+//!
+//!     let mut latency = RunningInteger::new("Latency", &None);
+//!
+//!     for i in 1..=1000 {
+//!         latency.record_i64(i);
+//!     }
+//!
+//!     let golden = GoldenReport::capture(&latency);
+//!
+//!     // Record one more sample.  The count and mean change slightly.
+//!
+//!     latency.record_i64(500);
+//!
+//!     let report = GoldenReport::capture(&latency);
+//!     let diff   = golden.compare(&report, &Tolerances::default());
+//!
+//!     assert!(!diff.passed());
+//!
+//!     // Allow the fields to drift by 1 percent.  The skewness is near
+//!     // zero, so give it an absolute tolerance instead.
+//!
+//!     let mut tolerances = Tolerances::new(Tolerance::relative(0.01));
+//!
+//!     tolerances.set("Skewness", Tolerance::absolute(0.001));
+//!
+//!     let diff = golden.compare(&report, &tolerances);
+//!
+//!     assert!(diff.passed());
+//!
+//!     diff.print(None);
+//!```
+
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::Rustics;
+use super::PrinterBox;
+use super::PrinterOption;
+use super::stdout_printer;
+use super::printer_mut;
+use super::capture_printer::CapturePrinter;
+
+/// Tolerance gives the allowed difference between an expected and an
+/// actual number.  A number matches if it is within either limit.
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Tolerance {
+    pub relative:   f64,
+    pub absolute:   f64,
+}
+
+impl Tolerance {
+    /// Returns a tolerance that requires an exact match.
+
+    pub fn exact() -> Tolerance {
+        Tolerance { relative: 0.0, absolute: 0.0 }
+    }
+
+    /// Returns a tolerance relative to the larger magnitude of the two
+    /// numbers, e.g., 0.01 for 1 percent.
+
+    pub fn relative(relative: f64) -> Tolerance {
+        Tolerance { relative, absolute: 0.0 }
+    }
+
+    /// Returns a tolerance given as a fixed difference.
+
+    pub fn absolute(absolute: f64) -> Tolerance {
+        Tolerance { relative: 0.0, absolute }
+    }
+
+    /// Returns true if the actual value is close enough to the expected
+    /// value.
+
+    pub fn accepts(&self, expected: f64, actual: f64) -> bool {
+        if expected == actual {
+            return true;
+        }
+
+        let difference = (expected - actual).abs();
+        let magnitude  = expected.abs().max(actual.abs());
+
+        difference <= self.absolute || difference <= self.relative * magnitude
+    }
+}
+
+/// Tolerances holds the default tolerance and any per-field
+/// overrides.
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Tolerances {
+    default:    Tolerance,
+    fields:     Vec<(String, Tolerance)>,
+}
+
+impl Tolerances {
+    /// Creates a set of tolerances that uses the given default for
+    /// every field.
+
+    pub fn new(default: Tolerance) -> Tolerances {
+        let fields = Vec::new();
+
+        Tolerances { default, fields }
+    }
+
+    /// Sets the tolerance for a field, like "Mean", replacing any
+    /// previous setting.
+
+    pub fn set(&mut self, field: &str, tolerance: Tolerance) {
+        match self.fields.iter_mut().find(|(name, _)| name == field) {
+            Some(entry) => entry.1 = tolerance,
+            None        => self.fields.push((field.to_string(), tolerance)),
+        }
+    }
+
+    /// Returns the tolerance for a field.
+
+    pub fn get(&self, field: &str) -> Tolerance {
+        match self.fields.iter().find(|(name, _)| name == field) {
+            Some((_, tolerance)) => *tolerance,
+            None                 => self.default,
+        }
+    }
+}
+
+/// Mismatch describes one line that differs.  The line number starts
+/// at 1.  The expected or actual text is None if the line is missing
+/// from that report.
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Mismatch {
+    pub line:       usize,
+    pub field:      String,
+    pub expected:   Option<String>,
+    pub actual:     Option<String>,
+    pub reason:     String,
+}
+
+/// GoldenDiff holds the result of comparing a report with a golden
+/// report.
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct GoldenDiff {
+    lines:      usize,
+    mismatches: Vec<Mismatch>,
+}
+
+impl GoldenDiff {
+    /// Returns true if every line matched.
+
+    pub fn passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Returns the number of lines compared.
+
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// Returns the lines that differ.
+
+    pub fn mismatches(&self) -> &[Mismatch] {
+        &self.mismatches
+    }
+
+    /// Returns the summary and the diff as lines of text.
+
+    pub fn diff_lines(&self) -> Vec<String> {
+        let mut result = Vec::new();
+
+        let header =
+            if self.passed() {
+                format!("Golden:  passed ({} lines)", self.lines)
+            } else {
+                format!("Golden:  FAILED ({} of {} lines)", self.mismatches.len(), self.lines)
+            };
+
+        result.push(header);
+
+        for mismatch in self.mismatches.iter() {
+            result.push(format!("    line {}, \"{}\":  {}", mismatch.line, mismatch.field,
+                mismatch.reason));
+
+            if let Some(expected) = &mismatch.expected {
+                result.push(format!("      - {}", expected));
+            }
+
+            if let Some(actual) = &mismatch.actual {
+                result.push(format!("      + {}", actual));
+            }
+        }
+
+        result
+    }
+
+    /// Prints the summary and the diff.  The default printer is stdout.
+
+    pub fn print(&self, printer: PrinterOption) {
+        let printer = printer.unwrap_or_else(stdout_printer);
+        let printer = printer_mut!(printer);
+
+        for line in self.diff_lines() {
+            printer.print(&line);
+        }
+    }
+}
+
+/// GoldenReport holds the lines of a report for comparison.
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct GoldenReport {
+    lines:  Vec<String>,
+}
+
+impl GoldenReport {
+    /// Creates a report from the given lines.
+
+    pub fn new(lines: Vec<String>) -> GoldenReport {
+        GoldenReport { lines }
+    }
+
+    /// Creates a report from text with one line per report line.
+
+    pub fn from_text(text: &str) -> GoldenReport {
+        let lines = text.lines().map(|line| line.to_string()).collect();
+
+        GoldenReport { lines }
+    }
+
+    /// Prints an instance into a new report.
+
+    pub fn capture(rustics: &dyn Rustics) -> GoldenReport {
+        let capture = Rc::new(RefCell::new(CapturePrinter::new()));
+        let printer = Some(capture.clone() as PrinterBox);
+
+        rustics.print_opts(printer, None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        GoldenReport { lines }
+    }
+
+    /// Creates a report from the lines held by a PrinterBox, which
+    /// must contain a CapturePrinter.
+
+    pub fn from_printer(printer_box: &PrinterBox) -> Option<GoldenReport> {
+        let lines = CapturePrinter::lines_from(printer_box)?;
+
+        Some(GoldenReport { lines })
+    }
+
+    /// Returns the lines of the report.
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Returns the report as text.
+
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+
+        for line in self.lines.iter() {
+            text.push_str(line);
+            text.push('\n');
+        }
+
+        text
+    }
+
+    /// Writes the report to a file.
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, self.text())
+    }
+
+    /// Reads a report from a file.
+
+    pub fn load(path: &Path) -> io::Result<GoldenReport> {
+        let text = std::fs::read_to_string(path)?;
+
+        Ok(GoldenReport::from_text(&text))
+    }
+
+    /// Compares a report with this one, which is taken as the expected
+    /// output.
+
+    pub fn compare(&self, actual: &GoldenReport, tolerances: &Tolerances) -> GoldenDiff {
+        let     lines      = std::cmp::max(self.lines.len(), actual.lines.len());
+        let mut mismatches = Vec::new();
+
+        for i in 0..lines {
+            let expected = self.lines.get(i);
+            let found    = actual.lines.get(i);
+
+            let (field, reason) =
+                match (expected, found) {
+                    (Some(expected), Some(found)) => {
+                        match compare_line(expected, found, tolerances) {
+                            Some(result) => result,
+                            None         => continue,
+                        }
+                    }
+
+                    (Some(expected), None) => (field_name(&parse_line(expected)),
+                                                    "the line is missing".to_string()),
+                    (None, Some(found))    => (field_name(&parse_line(found)),
+                                                    "the line is extra".to_string()),
+                    (None, None)           => continue,
+                };
+
+            let line     = i + 1;
+            let expected = expected.cloned();
+            let actual   = found.cloned();
+
+            mismatches.push(Mismatch { line, field, expected, actual, reason });
+        }
+
+        GoldenDiff { lines, mismatches }
+    }
+}
+
+/// Compares a report with the golden report in the given file.  If
+/// the file doesn't exist, or the environment variable
+/// RUSTICS_UPDATE_GOLDEN is set, the report is saved as the new
+/// golden report and the comparison passes.
+
+pub fn check_golden(path: &Path, actual: &GoldenReport, tolerances: &Tolerances)
+        -> io::Result<GoldenDiff> {
+    if !path.exists() || std::env::var_os("RUSTICS_UPDATE_GOLDEN").is_some() {
+        actual.save(path)?;
+
+        let lines      = actual.lines.len();
+        let mismatches = Vec::new();
+
+        return Ok(GoldenDiff { lines, mismatches });
+    }
+
+    let golden = GoldenReport::load(path)?;
+
+    Ok(golden.compare(actual, tolerances))
+}
+
+// A token is either a number, with its text for reports, or other
+// text.
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Number(f64, String),
+    Text(String),
+}
+
+// Parse a token as a number.  Commas are allowed, as printed by
+// Printable::commas, but words like "inf" and "NaN" are left as
+// text.
+
+fn parse_number(token: &str) -> Option<f64> {
+    let digits = token.replace(',', "");
+    let body   = digits.trim_start_matches(['+', '-']);
+
+    if !body.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+
+    digits.parse::<f64>().ok()
+}
+
+// Check for the exponent token printed by Printable::print_float,
+// like "e+3".
+
+fn is_exponent(token: &str) -> bool {
+    match token.strip_prefix('e') {
+        Some(rest) => {
+            let rest = rest.trim_start_matches(['+', '-']);
+
+            !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit())
+        }
+
+        None => false,
+    }
+}
+
+fn parse_line(line: &str) -> Vec<Token> {
+    let     words  = line.split_whitespace().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i      = 0;
+
+    while i < words.len() {
+        let word = words[i];
+
+        match parse_number(word) {
+            Some(value) => {
+                // Join a mantissa with the exponent that follows it.
+
+                if i + 1 < words.len() && is_exponent(words[i + 1]) {
+                    let text  = format!("{} {}", word, words[i + 1]);
+                    let value = format!("{}{}", word, words[i + 1]).parse::<f64>().unwrap_or(value);
+
+                    tokens.push(Token::Number(value, text));
+                    i += 2;
+                    continue;
+                }
+
+                tokens.push(Token::Number(value, word.to_string()));
+            }
+
+            None => tokens.push(Token::Text(word.to_string())),
+        }
+
+        i += 1;
+    }
+
+    tokens
+}
+
+// The field name is the text before the first number, or the whole
+// line if it has no numbers.
+
+fn field_name(tokens: &[Token]) -> String {
+    let mut words = Vec::new();
+
+    for token in tokens.iter() {
+        match token {
+            Token::Text(text) => words.push(text.as_str()),
+            Token::Number(..) => break,
+        }
+    }
+
+    words.join(" ")
+}
+
+// Compare two lines, returning the field and a reason if they
+// differ.
+
+fn compare_line(expected: &str, actual: &str, tolerances: &Tolerances)
+        -> Option<(String, String)> {
+    if expected == actual {
+        return None;
+    }
+
+    let expected_tokens = parse_line(expected);
+    let actual_tokens   = parse_line(actual);
+    let field           = field_name(&expected_tokens);
+
+    if expected_tokens.len() != actual_tokens.len() {
+        return Some((field, "the layout differs".to_string()));
+    }
+
+    let tolerance = tolerances.get(&field);
+
+    for (expected, actual) in expected_tokens.iter().zip(actual_tokens.iter()) {
+        match (expected, actual) {
+            (Token::Text(expected), Token::Text(actual)) => {
+                if expected != actual {
+                    let reason = format!("\"{}\" != \"{}\"", expected, actual);
+
+                    return Some((field, reason));
+                }
+            }
+
+            (Token::Number(expected, expected_text), Token::Number(actual, actual_text)) => {
+                if !tolerance.accepts(*expected, *actual) {
+                    let reason =
+                        format!("{} vs {} is outside the tolerance", expected_text, actual_text);
+
+                    return Some((field, reason));
+                }
+            }
+
+            _ => return Some((field, "the layout differs".to_string())),
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::running_integer::RunningInteger;
+    use crate::running_float::RunningFloat;
+
+    // Make a path in the temporary directory for a test.
+
+    fn test_path(name: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+
+        path.push(format!("rustics_golden_{}_{}.txt", name, std::process::id()));
+        path
+    }
+
+    fn test_parse() {
+        let tokens = parse_line("    Mean         +1.25000 e+3");
+
+        assert!(tokens == vec![ Token::Text("Mean".to_string()),
+                                Token::Number(1250.0, "+1.25000 e+3".to_string()) ]);
+
+        let tokens = parse_line("    Std Dev      1,234,567 nanoseconds");
+
+        assert!(field_name(&tokens) == "Std Dev");
+        assert!(tokens[2] == Token::Number(1234567.0, "1,234,567".to_string()));
+        assert!(tokens[3] == Token::Text("nanoseconds".to_string()));
+
+        let tokens = parse_line("  Log Histogram");
+
+        assert!(field_name(&tokens) == "Log Histogram");
+        assert!(parse_line("    Mean  NaN")[1] == Token::Text("NaN".to_string()));
+        assert!(parse_line("e+3")[0]           == Token::Text("e+3".to_string()));
+    }
+
+    fn test_tolerance() {
+        let exact    = Tolerance::exact();
+        let relative = Tolerance::relative(0.01);
+        let absolute = Tolerance::absolute(2.0);
+
+        assert!( exact   .accepts(1.0,   1.0  ));
+        assert!(!exact   .accepts(1.0,   1.001));
+        assert!( relative.accepts(100.0, 101.0));
+        assert!(!relative.accepts(100.0, 102.0));
+        assert!( absolute.accepts(100.0, 102.0));
+        assert!(!absolute.accepts(100.0, 103.0));
+
+        let mut tolerances = Tolerances::new(relative);
+
+        tolerances.set("Mean", absolute);
+        tolerances.set("Mean", exact   );
+
+        assert!(tolerances.get("Mean")    == exact   );
+        assert!(tolerances.get("Maximum") == relative);
+        assert!(Tolerances::default().get("Mean") == exact);
+    }
+
+    fn test_compare() {
+        let golden = GoldenReport::from_text("Test\n    Count  1,000\n    Mean  +5.00000 e+2\n");
+        let same   = GoldenReport::from_text("Test\n    Count  1,000\n    Mean  +5.00000 e+2\n");
+        let near   = GoldenReport::from_text("Test\n    Count  1,001\n    Mean  +5.00400 e+2\n");
+
+        assert!(golden.lines().len() == 3);
+        assert!(golden.compare(&same, &Tolerances::default()).passed());
+
+        let diff = golden.compare(&near, &Tolerances::default());
+
+        assert!(diff.lines()                 == 3);
+        assert!(diff.mismatches().len()      == 2);
+        assert!(diff.mismatches()[0].line    == 2);
+        assert!(diff.mismatches()[0].field   == "Count");
+        assert!(diff.mismatches()[1].field   == "Mean");
+
+        // Allow the count to drift by 1 and the mean by 1 percent.
+
+        let mut tolerances = Tolerances::default();
+
+        tolerances.set("Count", Tolerance::absolute(1.0));
+
+        let diff = golden.compare(&near, &tolerances);
+
+        assert!(diff.mismatches().len() == 1);
+
+        tolerances.set("Mean", Tolerance::relative(0.01));
+
+        assert!(golden.compare(&near, &tolerances).passed());
+
+        // Text changes and layout changes always fail.
+
+        let renamed = GoldenReport::from_text("Best\n    Count  1,000\n    Mean  +5.00000 e+2\n");
+        let shorter = GoldenReport::from_text("Test\n    Count  1,000\n");
+        let wider   = GoldenReport::from_text("Test\n    Count  1,000 2\n    Mean  +5.00000 e+2");
+
+        let diff = golden.compare(&renamed, &tolerances);
+
+        assert!(diff.mismatches()[0].reason == "\"Test\" != \"Best\"");
+
+        let diff = golden.compare(&shorter, &tolerances);
+
+        assert!(diff.mismatches()[0].line   == 3);
+        assert!(diff.mismatches()[0].actual.is_none());
+        assert!(diff.mismatches()[0].reason == "the line is missing");
+
+        let diff = shorter.compare(&golden, &tolerances);
+
+        assert!(diff.mismatches()[0].expected.is_none());
+        assert!(diff.mismatches()[0].reason   == "the line is extra");
+
+        let diff = golden.compare(&wider, &tolerances);
+
+        assert!(diff.mismatches()[0].reason == "the layout differs");
+    }
+
+    fn test_diff_lines() {
+        let golden = GoldenReport::from_text("Test\n    Mean  +5.00000 e+2");
+        let actual = GoldenReport::from_text("Test\n    Mean  +6.00000 e+2");
+        let diff   = golden.compare(&actual, &Tolerances::default());
+        let lines  = diff.diff_lines();
+
+        assert!(lines[0] == "Golden:  FAILED (1 of 2 lines)");
+        assert!(lines[1] == "    line 2, \"Mean\":  +5.00000 e+2 vs +6.00000 e+2 is outside the tolerance");
+        assert!(lines[2] == "      -     Mean  +5.00000 e+2");
+        assert!(lines[3] == "      +     Mean  +6.00000 e+2");
+
+        let diff  = golden.compare(&golden, &Tolerances::default());
+        let lines = diff.diff_lines();
+
+        assert!(lines == vec![ "Golden:  passed (2 lines)".to_string() ]);
+
+        diff.print(None);
+    }
+
+    fn test_capture() {
+        let mut integer = RunningInteger::new("Golden Integer", &None);
+        let mut float   = RunningFloat::new("Golden Float", &None);
+
+        for i in 1..=1000 {
+            integer.record_i64(i);
+            float  .record_f64(i as f64 / 3.0);
+        }
+
+        let golden_integer = GoldenReport::capture(&integer);
+        let golden_float   = GoldenReport::capture(&float);
+
+        assert!(golden_integer.lines()[0] == "Golden Integer");
+
+        integer.record_i64(1000);
+        float  .record_f64(1000.0 / 3.0);
+
+        let integer_report = GoldenReport::capture(&integer);
+        let float_report   = GoldenReport::capture(&float);
+        let mut tolerances = Tolerances::new(Tolerance::relative(0.01));
+
+        // The skewness is near zero, so it needs an absolute tolerance.
+
+        assert!(!golden_integer.compare(&integer_report, &tolerances).passed());
+
+        tolerances.set("Skewness", Tolerance::absolute(0.001));
+
+        assert!(!golden_integer.compare(&integer_report, &Tolerances::default()).passed());
+        assert!(!golden_float  .compare(&float_report,   &Tolerances::default()).passed());
+        assert!( golden_integer.compare(&integer_report, &tolerances).passed());
+        assert!( golden_float  .compare(&float_report,   &tolerances).passed());
+
+        let capture = Rc::new(RefCell::new(CapturePrinter::new()));
+        let printer = capture.clone() as PrinterBox;
+
+        integer.print_opts(Some(printer.clone()), None);
+
+        let report = GoldenReport::from_printer(&printer).unwrap();
+
+        assert!(report == integer_report);
+    }
+
+    fn test_files() {
+        let path   = test_path("files");
+        let golden = GoldenReport::from_text("Test\n    Count  1,000\n");
+        let actual = GoldenReport::from_text("Test\n    Count  1,010\n");
+
+        let _ = std::fs::remove_file(&path);
+
+        // The first check records the file.
+
+        assert!(check_golden(&path, &golden, &Tolerances::default()).unwrap().passed());
+        assert!(GoldenReport::load(&path).unwrap() == golden);
+
+        let diff = check_golden(&path, &actual, &Tolerances::default()).unwrap();
+
+        assert!(!diff.passed());
+
+        let tolerances = Tolerances::new(Tolerance::relative(0.02));
+
+        assert!(check_golden(&path, &actual, &tolerances).unwrap().passed());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_parse     ();
+        test_tolerance ();
+        test_compare   ();
+        test_diff_lines();
+        test_capture   ();
+        test_files     ();
+    }
+}
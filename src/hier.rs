@@ -20,9 +20,74 @@
 //!
 //!     * Hier implements the Rustics interface, and through that provides statistics from the
 //!       either the current level 0 Rustics instance, i.e., statistics on the newest samples,
-//!       or from an optionally configured window of the last n events, as specified by the
-//!       window_size parameter in HierConfig.  This window is implemented using a type such as
-//!       TimeWindow, FloatWindow, or IntegerWindow.
+//!       or from an optionally configured window, as specified by the window parameter in
+//!       HierConfig.  A HierWindow::Samples window keeps the last n events, and is implemented
+//!       using a type such as TimeWindow, FloatWindow, or IntegerWindow.  A HierWindow::Time
+//!       window keeps the events recorded in the last n seconds, and is implemented using
+//!       TimeBoundedWindow or FloatTimeWindow.  The type-specific configurations like
+//!       IntegerHierConfig take a window_size, which selects a HierWindow::Samples window.
+//!
+//!     * A Hier instance can be given a clock in the form of a TimerBox via
+//!       HierDescriptor::set_clock or Hier::set_clock.  The clock is used for any
+//!       time-based queries, like the age of the current level 0 instance, so tests
+//!       can use a timer like ClockTimer to drive those features deterministically.
+//!       The wall clock is never read directly.
+//!
+//!     * HierDescriptor::set_advance_every configures time-based advancing.  A new level 0
+//!       instance is started each time the given number of clock ticks passes, regardless
+//!       of the number of samples recorded, so the periods stay the same length when the
+//!       traffic rate varies.  The clock is checked when a sample is recorded, and
+//!       Hier::advance_if_due can be invoked to check it when no samples are arriving.
+//!       Periods with no samples produce empty level 0 instances.  After a long gap, at most
+//!       one level 0 retention's worth of empty instances is created.
+//!
+//!     * Hier::member_state tells whether a member is live, has been summarized into the
+//!       next level, or has been retired from the live set of the top level.  Consumers that
+//!       export members from several levels can use it to avoid counting samples twice.
+//!       Hier::member_age gives the age of a member in level 0 periods.
+//!
+//!     * A level can be configured via HierDimension::set_archived to pass each member that
+//!       is evicted from its window to a HierArchiver given to HierDescriptor::set_archiver,
+//!       rather than dropping it.  The Hier instance then can serve as the in-memory front
+//!       of a persistent store.
+//!
+//!     * Hier::prune drops the oldest members at a level beyond a given count, and
+//!       Hier::compact drops every member outside the live sets, so a long-lived process
+//!       with generous retention can shed history under memory pressure.  Live members are
+//!       kept.  Hier::memory_usage estimates the memory used by each level.
+//!
+//!     * Hier::set_member_pool keeps the level 0 members evicted from the window and reuses
+//!       them, cleared, when the instance advances, rather than allocating a new member and
+//!       histogram each period.  This reduces allocator pressure in long-running services
+//!       that advance frequently.  Members that are archived or still referenced elsewhere
+//!       are not recycled.
+//!
+//!     * Hier::indices returns an iterator over the members of every level, giving the
+//!       HierIndex, the sample count, and whether the member is live, so exporters can walk
+//!       the hierarchy without nested loops.
+//!
+//!     * Hier::window returns the configured window, if any, and Hier::window_as returns it
+//!       as its concrete type, so the recent samples can be examined directly.
+//!
+//!     * The upper-level members keep the sums of the histograms of the members that they
+//!       summarize.  Hier::percentile_at estimates a percentile for any member at any level,
+//!       so the tail values for old periods stay available, not just the means and variances.
+//!
+//!     * Hier::save writes the entire hierarchy, including all the levels and any window, in
+//!       the text format of the checkpoint module.  Hier::load restores the data into a new
+//!       instance with the same configuration, so historical data survives a restart.  The
+//!       integer, float, and time hierarchies support this feature.
+//!
+//!     * Hier::set_change_detection enables a check each time the instance advances.  The
+//!       level 0 instance being retired is compared with the previous one, and a change in
+//!       the mean or the count larger than the configured ChangeThresholds is passed to a
+//!       callback as a ChangeReport, or printed as a warning if there is no callback.
+//!
+//!     * Hier::validate is a debugging aid for custom HierGenerator implementations.  It
+//!       recomputes each upper-level member whose inputs still are retained by pooling the
+//!       counts, means, variances, and extremes of those inputs, and returns a HierValidation
+//!       listing any HierDiscrepancy beyond a relative tolerance.  It also checks the number
+//!       of members retained at each level.
 //!
 //! ## Example
 //!```
@@ -208,19 +273,40 @@
 
 use std::rc::Rc;
 use std::any::Any;
+use std::cell::Cell;
+use std::io::Read;
+use std::io::Write;
+use std::io::Error;
+use std::io::ErrorKind;
 
 use super::Rustics;
 use super::Histogram;
 use super::ExportStats;
 use super::Printer;
 use super::PrinterBox;
+use super::Labels;
 use super::PrinterOption;
 use super::PrintOption;
+use super::PrintOpts;
 use super::LogHistogramBox;
 use super::FloatHistogramBox;
 use super::parse_print_opts;
+use super::parse_fields;
+use super::printable::Printable;
+use super::stats_math::PoolData;
+use super::record_hook::RecordHookBox;
+use super::record_hook::HookSample;
+use super::record_hook::call_hook;
+use super::stats_math::pool_moments;
+use super::stats_math::compute_variance;
 use super::TimerBox;
 use super::window::Window;
+use super::checkpoint::Checkpoint;
+use super::checkpoint::CheckpointReader;
+#[cfg(feature = "serde")]
+use super::checkpoint::serialize_checkpoint;
+#[cfg(feature = "serde")]
+use super::checkpoint::deserialize_checkpoint;
 use super::printer_mut;
 use super::timer_mut;
 use std::cell::RefCell;
@@ -228,6 +314,7 @@ use std::cell::RefCell;
 pub type MemberRc    = Rc<RefCell<dyn HierMember   >>;
 pub type GeneratorRc = Rc<RefCell<dyn HierGenerator>>;
 pub type ExporterRc  = Rc<RefCell<dyn HierExporter >>;
+pub type ArchiverBox = Box<dyn HierArchiver>;
 
 /// Converts a Rustics instance in the shareable form
 /// for use by the Hier code.
@@ -253,17 +340,67 @@ macro_rules! hier_item { ($x:expr) => { &*$x.borrow() } }
 pub struct HierDescriptor {
     dimensions:     Vec<HierDimension>,
     auto_next:      i64,
+    level_opts:     Vec<PrintOption>,
+    clock:          Option<TimerBox>,
+    advance_every:  i64,
+    archiver:       Option<ArchiverBox>,
 }
 
 impl HierDescriptor {
     pub fn new(dimensions: Vec<HierDimension>, auto_next: Option<i64>) -> HierDescriptor {
-        let auto_next = auto_next.unwrap_or(0);
+        let auto_next  = auto_next.unwrap_or(0);
+        let level_opts = vec![None; dimensions.len()];
+        let clock      = None;
 
         if auto_next < 0 {
             panic!("HierDescriptor::new:  The auto_next value can't be negative.");
         }
 
-        HierDescriptor { dimensions, auto_next }
+        let advance_every = 0;
+        let archiver      = None;
+
+        HierDescriptor { dimensions, auto_next, level_opts, clock, advance_every, archiver }
+    }
+
+    /// Sets the clock to be used for time-based queries on the Hier
+    /// instance.  Without a clock, those queries return None.
+
+    pub fn set_clock(&mut self, clock: TimerBox) {
+        self.clock = Some(clock);
+    }
+
+    /// Configures the Hier instance to advance each time the given
+    /// interval passes, in ticks of the clock.  A clock must be set
+    /// via set_clock.  An interval of zero disables the feature.
+    /// This option can be combined with auto_next, in which case an
+    /// advance is done when either limit is reached.
+
+    pub fn set_advance_every(&mut self, interval: i64) {
+        if interval < 0 {
+            panic!("HierDescriptor::set_advance_every:  The interval can't be negative.");
+        }
+
+        self.advance_every = interval;
+    }
+
+    /// Sets the archiver that receives the members evicted from the
+    /// levels configured via HierDimension::set_archived.
+
+    pub fn set_archiver(&mut self, archiver: ArchiverBox) {
+        self.archiver = Some(archiver);
+    }
+
+    /// Sets the print options for the members created at the given
+    /// level.  The printer, units, and histogram options override the
+    /// options given for the Hier instance.  The title, if present, is
+    /// appended to the title of the Hier instance as a suffix.
+
+    pub fn set_level_opts(&mut self, level: usize, print_opts: PrintOption) {
+        if level >= self.dimensions.len() {
+            panic!("HierDescriptor::set_level_opts:  That level is not configured.");
+        }
+
+        self.level_opts[level] = print_opts;
     }
 }
 
@@ -282,6 +419,7 @@ impl HierDescriptor {
 pub struct HierDimension {
     period:     usize,   // the number of instances to be summed for the next level
     retention:  usize,   // the number of instances to retain for queries.
+    archived:   bool,    // whether evicted instances are passed to the archiver
 }
 
 impl HierDimension {
@@ -290,7 +428,21 @@ impl HierDimension {
             panic!("HierDimension::new:  The retention count is too small.");
         }
 
-        HierDimension { period, retention }
+        let archived = false;
+
+        HierDimension { period, retention, archived }
+    }
+
+    /// Configures whether the members evicted from this level are
+    /// passed to the HierArchiver for the instance.  The retention
+    /// count still limits the number of members kept in memory.
+
+    pub fn set_archived(&mut self, archived: bool) {
+        self.archived = archived;
+    }
+
+    pub fn archived(&self) -> bool {
+        self.archived
     }
 
     pub fn period(&self) -> usize {
@@ -302,10 +454,51 @@ impl HierDimension {
     }
 }
 
+/// HierWindow selects the window that a Hier instance maintains
+/// for its Rustics reporting, if any.  Samples(n) keeps the last n
+/// samples, while Time(n, timer) keeps the samples recorded in the
+/// last n seconds as measured by the given timer.  The timer should
+/// not be shared with other users, including the Hier clock.
+///
+/// Not all types support every kind of window.  A generator panics
+/// when asked for a window that its type does not support.
+
+#[derive(Clone, Default)]
+pub enum HierWindow {
+    #[default]
+    None,
+    Samples(usize),
+    Time(u64, TimerBox),
+}
+
+impl HierWindow {
+    /// Returns true if no window is configured.
+
+    pub fn is_none(&self) -> bool {
+        match self {
+            HierWindow::None       => { true  }
+            HierWindow::Samples(n) => { *n == 0 }
+            HierWindow::Time(..)   => { false }
+        }
+    }
+}
+
+/// Converts the window_size parameter of the type-specific
+/// configurations into a HierWindow.
+
+impl From<Option<usize>> for HierWindow {
+    fn from(window_size: Option<usize>) -> HierWindow {
+        match window_size {
+            Some(size) => { HierWindow::Samples(size) }
+            None       => { HierWindow::None          }
+        }
+    }
+}
+
 /// HierIndex allows users to specify an index into a Hier
 /// instance in order to look at any Rustics instance therein.
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct HierIndex {
     set:   HierSet,
     level: usize,
@@ -317,12 +510,66 @@ pub struct HierIndex {
 /// choose to look at all of the instances, or only the newest entries,
 /// the live set.
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum HierSet {
     All,
     Live,
 }
 
+/// HierMemberState describes how a member of a Hier instance relates
+/// to the higher levels.  A Summarized member has already been added
+/// into a member at the next level, so exporting both would count its
+/// samples twice.  A Live member is in the live set of its level and
+/// hasn't been summarized yet, so it has data that appears nowhere
+/// else.  A Retired member has left the live set of the top level,
+/// which never is summarized, but still is retained for queries.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HierMemberState {
+    Live,
+    Retired,
+    Summarized,
+}
+
+// The default relative tolerance for Hier::validate.
+
+const VALIDATE_TOLERANCE: f64 = 1.0e-6;
+
+/// HierDiscrepancy describes one inconsistency found by
+/// Hier::validate.  The index is the position of the member in the
+/// HierSet::All set of its level, or None for a check of the level
+/// as a whole.  The quantity names the value that differs.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HierDiscrepancy {
+    pub level:     usize,
+    pub which:     Option<usize>,
+    pub quantity:  &'static str,
+    pub expected:  f64,
+    pub found:     f64,
+}
+
+/// HierValidation holds the results of Hier::validate.  "checked"
+/// counts the upper-level members that were recomputed, and "skipped"
+/// counts those that couldn't be, because some of the members that
+/// they summarize have been discarded, or because the member type
+/// can't be pooled.
+
+#[derive(Clone, Debug, Default)]
+pub struct HierValidation {
+    pub checked:        usize,
+    pub skipped:        usize,
+    pub discrepancies:  Vec<HierDiscrepancy>,
+}
+
+impl HierValidation {
+    /// Returns true if no discrepancies were found.
+
+    pub fn is_valid(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
 /// HierIndex is used to refer to a specific Rustics instance in a
 /// Hier instance.
 
@@ -330,6 +577,99 @@ impl HierIndex {
     pub fn new(set: HierSet, level: usize, which: usize) -> HierIndex {
         HierIndex { set, level, which }
     }
+
+    /// Returns the subset of the level that is indexed.
+
+    pub fn set(&self) -> HierSet {
+        self.set
+    }
+
+    /// Returns the level of the hierarchy.
+
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns the position in the subset, with the oldest member at
+    /// position zero.
+
+    pub fn which(&self) -> usize {
+        self.which
+    }
+}
+
+/// ChangeThresholds configures the change detection done by a Hier
+/// instance when it advances.  The thresholds are percentages of the
+/// values for the previous level 0 period.  A change larger than either
+/// threshold is reported.  Use f64::INFINITY to disable a threshold.
+
+#[derive(Clone, Copy, Debug)]
+pub struct ChangeThresholds {
+    pub mean_percent:   f64,
+    pub count_percent:  f64,
+}
+
+/// ChangeReport describes the change between the level 0 period that
+/// just was retired and the period before it.  The changes are given
+/// in percent.  The mean change is zero if either period is empty.
+
+#[derive(Clone, Debug)]
+pub struct ChangeReport {
+    pub name:            String,
+    pub period:          i64,
+    pub previous_mean:   f64,
+    pub current_mean:    f64,
+    pub mean_change:     f64,
+    pub previous_count:  u64,
+    pub current_count:   u64,
+    pub count_change:    f64,
+    pub mean_exceeded:   bool,
+    pub count_exceeded:  bool,
+}
+
+// MemberPool keeps retired level 0 members for reuse.
+
+struct MemberPool {
+    members:   Vec<MemberRc>,
+    limit:     usize,
+    reused:    u64,
+    recycled:  u64,
+}
+
+/// MemberPoolStats describes the state of the member pool of a Hier
+/// instance.  The reused count gives the number of level 0 members
+/// taken from the pool rather than allocated, and the recycled count
+/// gives the number of retired members added to the pool.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MemberPoolStats {
+    pub pooled:    usize,
+    pub limit:     usize,
+    pub reused:    u64,
+    pub recycled:  u64,
+}
+
+/// A ChangeCallback is invoked with a report for each change that
+/// exceeds the thresholds.
+
+pub type ChangeCallback = Box<dyn FnMut(&ChangeReport)>;
+
+struct ChangeDetector {
+    thresholds:  ChangeThresholds,
+    callback:    Option<ChangeCallback>,
+    detected:    u64,
+}
+
+// Computes the change from previous to current in percent.
+
+fn percent_change(previous: f64, current: f64) -> f64 {
+    if previous == current {
+        0.0
+    } else if previous == 0.0 {
+        f64::INFINITY
+    } else {
+        (current - previous) / previous.abs() * 100.0
+    }
 }
 
 // The exporter needs to be downcast to be used, so
@@ -359,6 +699,32 @@ pub trait HierTraverser {
     fn visit(&mut self, member: &mut dyn Rustics);
 }
 
+/// A HierArchiver receives the members evicted from the levels of a
+/// Hier instance that are configured to be archived.  The members are
+/// passed in the order of eviction, so the oldest member comes first.
+/// An implementation might write the member to a file or database,
+/// for example using the checkpoint code.
+
+pub trait HierArchiver {
+    /// This method is invoked with the level and the member each time
+    /// a member is evicted from an archived level.
+
+    fn archive(&mut self, level: usize, member: MemberRc);
+}
+
+/// HierIndexedTraverser is like HierTraverser, but the visit() method
+/// also receives the index of each member and whether the member is in
+/// the live set of its level.  Export code can use the index to label
+/// each instance with its position in the hierarchy.  See
+/// Hier::traverse_all_indexed() and Hier::traverse_live_indexed().
+
+pub trait HierIndexedTraverser {
+    /// This method is invoked on each Rustics instance in the
+    /// matrix, along with its index.
+
+    fn visit(&mut self, index: HierIndex, live: bool, member: &mut dyn Rustics);
+}
+
 // The HierGenerator trait defines the interface that allows a Rustics
 // type to support hierarchical statistics.  This code connects the Hier
 // impl code with the impl code for the underlying Rustics type.
@@ -382,13 +748,43 @@ pub trait HierGenerator {
     fn make_from_exporter(&self, name: &str, print_opts: &PrintOption, exports: ExporterRc)
             -> MemberRc;
 
-    fn make_window(&self, name: &str, window_size: usize, print_opts: &PrintOption)
+    /// Creates the window for a Hier instance.  This method is
+    /// invoked only if a window is configured.
+
+    fn make_window(&self, name: &str, window: &HierWindow, print_opts: &PrintOption)
             -> Box<dyn Rustics>;
 
     fn make_member   (&self, name: &str, print_opts: &PrintOption) -> MemberRc;
     fn make_exporter (&self) -> ExporterRc;
     fn push          (&self, exports: &mut dyn HierExporter, member: MemberRc);
     fn hz            (&self) -> u128;
+
+    /// Saves a member as a checkpoint.  These methods are used by
+    /// Hier::save and Hier::load.  A generator that doesn't support
+    /// saving a hierarchy returns None.
+
+    fn checkpoint_member(&self, _member: &MemberRc) -> Option<String> {
+        None
+    }
+
+    /// Restores a member saved by checkpoint_member().
+
+    fn restore_member(&self, _text: &str, _print_opts: &PrintOption) -> Option<MemberRc> {
+        None
+    }
+
+    /// Saves the window created by make_window() as a checkpoint.
+
+    fn checkpoint_window(&self, _window: &dyn Rustics) -> Option<String> {
+        None
+    }
+
+    /// Restores a window saved by checkpoint_window().
+
+    fn restore_window(&self, _text: &str, _print_opts: &PrintOption)
+            -> Option<Box<dyn Rustics>> {
+        None
+    }
 }
 
 // The HierMember trait is used to extend a specific type
@@ -430,7 +826,18 @@ pub struct Hier {
     event_count:    i64,
     printer:        PrinterBox,
     print_opts:     PrintOption,
+    level_opts:     Vec<PrintOption>,
+    suffixes:       Vec<String>,
     window:         Option<Box<dyn Rustics>>,
+    clock:          Option<TimerBox>,
+    now:            Cell<i64>,
+    advance_time:   i64,
+    advance_every:  i64,
+    change:         Option<ChangeDetector>,
+    archiver:       Option<ArchiverBox>,
+    pool:           Option<MemberPool>,
+    labels:         Labels,
+    hook:           Option<RecordHookBox>,
 }
 
 /// HierConfig defines the configuration parameters for a Hier
@@ -441,7 +848,7 @@ pub struct HierConfig {
     pub name:        String,
     pub descriptor:  HierDescriptor,
     pub generator:   GeneratorRc,
-    pub window_size: Option<usize>,
+    pub window:      HierWindow,
     pub class:       String,
     pub print_opts:  PrintOption,
 }
@@ -462,34 +869,42 @@ impl Hier {
 
         let     auto_next     = descriptor.auto_next;
         let     dimensions    = descriptor.dimensions;
+        let     user_opts     = descriptor.level_opts;
+        let     clock         = descriptor.clock;
+        let     advance_every = descriptor.advance_every;
+        let     archiver      = descriptor.archiver;
         let     id            = usize::MAX;
         let     advance_count = 0;
         let     event_count   = 0;
+        let     now           = Cell::new(0);
+        let     advance_time  = 0;
+        let     change        = None;
         let mut stats         = Vec::with_capacity(dimensions.len());
 
         if dimensions.is_empty() {
             panic!("Hier::new:  No dimensions were specified.");
         }
 
-        for i in 0..dimensions.len() - 1 {
-            if dimensions[i].period < 2 {
+        if advance_every > 0 && clock.is_none() {
+            panic!("Hier::new:  Time-based advancing requires a clock.");
+        }
+
+        if archiver.is_none() && dimensions.iter().any(|dimension| dimension.archived) {
+            panic!("Hier::new:  Archiving requires an archiver.");
+        }
+
+        for dimension in dimensions.iter().take(dimensions.len() - 1) {
+            if dimension.period < 2 {
                 panic!("Hier::new:  The period must be at least 2.");
             }
         }
 
-        let window_size =
-            if let Some(window_size) = &configuration.window_size {
-                *window_size
-            } else {
-                0
-            };
-
         let (printer, title, _units, _histo_opts) = parse_print_opts(&print_opts, &name);
 
         let window =
-            if window_size > 0 {
+            if !configuration.window.is_none() {
                 let generator = generator.borrow();
-                let window    = generator.make_window(&name, window_size, &print_opts);
+                let window    = generator.make_window(&name, &configuration.window, &print_opts);
 
                 Some(window)
             } else {
@@ -503,21 +918,53 @@ impl Hier {
             stats.push(Window::new(dimension.retention, dimension.period));
         }
 
-        // Make the first Rustics instance so that we are ready to record data.
+        // Merge any per-level print options with the options for the
+        // Hier instance itself.
 
-        let member = generator.borrow_mut().make_member(&name, &print_opts);
+        let mut level_opts = Vec::with_capacity(dimensions.len());
+        let mut suffixes   = Vec::with_capacity(dimensions.len());
 
-        assert!(hier_item!(member).to_rustics().class() == class);
+        for user_opt in &user_opts {
+            let suffix =
+                match user_opt {
+                    Some(PrintOpts { title: Some(suffix), .. }) => suffix.clone(),
+                    _                                           => String::new(),
+                };
+
+            level_opts.push(merge_level_opts(&print_opts, user_opt, &name));
+            suffixes.push(suffix);
+        }
+
+        let labels = Labels::new();
+        let hook   = None;
+        let pool   = None;
+
+        let mut hier =
+            Hier {
+                dimensions,   generator,   stats,
+                name,         title,       id,
+                class,        auto_next,   advance_count,
+                event_count,  printer,     print_opts,
+                level_opts,   suffixes,    window,
+                clock,        now,         advance_time,
+                advance_every, change,     archiver,
+                pool,         labels,      hook
+            };
 
-        stats[0].push(member);
+        // Start the clock, if there is one.
 
-        Hier {
-            dimensions,   generator,   stats,
-            name,         title,       id,
-            class,        auto_next,   advance_count,
-            event_count,  printer,     print_opts,
-            window
+        if let Some(clock) = &hier.clock {
+            timer_mut!(clock).start();
         }
+
+        // Make the first Rustics instance so that we are ready to record data.
+
+        let member = hier.make_level_0();
+
+        assert!(hier_item!(member).to_rustics().class() == hier.class);
+
+        hier.stats[0].push(member);
+        hier
     }
 
     /// The current() method returns the newest Rustics instance
@@ -530,6 +977,23 @@ impl Hier {
         member.clone()
     }
 
+    /// Returns the window configured for the instance, if any.  The
+    /// window contains the same samples as the hierarchy, so it can be
+    /// used to query the recent samples directly.
+
+    pub fn window(&self) -> Option<&dyn Rustics> {
+        self.window.as_deref()
+    }
+
+    /// Returns the window as its concrete type, like IntegerWindow or
+    /// TimeBoundedWindow, for access to type-specific methods such as
+    /// iter().  None is returned if there is no window or if the window
+    /// is of a different type.
+
+    pub fn window_as<T: Any>(&self) -> Option<&T> {
+        self.window.as_ref()?.generic().downcast_ref::<T>()
+    }
+
     /// Prints the given instance.
 
     pub fn print_index_opts(&self, index: HierIndex, printer: PrinterOption, title: Option<&str>) {
@@ -559,6 +1023,13 @@ impl Hier {
     pub fn clear_all(&mut self) {
         self.advance_count = 0;
         self.event_count   = 0;
+        self.advance_time  = 0;
+
+        self.now.set(0);
+
+        if let Some(clock) = &self.clock {
+            timer_mut!(clock).start();
+        }
 
         // Clear all the windows.
 
@@ -568,8 +1039,7 @@ impl Hier {
 
         // Create the first level 0 instance.
 
-        let generator = self.generator.borrow_mut();
-        let member    = generator.make_member(&self.name, &self.print_opts);
+        let member = self.make_level_0();
 
         // Push this level 0 instance into the window.
 
@@ -609,6 +1079,67 @@ impl Hier {
         }
     }
 
+    /// Invokes a user-supplied traverser on every member of the hierarchy,
+    /// passing the index of each member in the HierSet::All subset of its
+    /// level.  The members are visited from level 0 upward, and from the
+    /// oldest to the newest within a level.
+
+    pub fn traverse_all_indexed(&mut self, traverser: &mut dyn HierIndexedTraverser) {
+        for (level, stats) in self.stats.iter().enumerate() {
+            let first_live = stats.all_len() - stats.live_len();
+
+            for (which, member) in stats.iter_all().enumerate() {
+                let index   = HierIndex::new(HierSet::All, level, which);
+                let live    = which >= first_live;
+                let borrow  = hier_item_mut!(member);
+                let rustics = borrow.to_rustics_mut();
+
+                traverser.visit(index, live, rustics);
+            }
+        }
+    }
+
+    /// Invokes a user-supplied traverser on the live members on every
+    /// level, passing the index of each member in the HierSet::Live
+    /// subset of its level.
+
+    pub fn traverse_live_indexed(&mut self, traverser: &mut dyn HierIndexedTraverser) {
+        for (level, stats) in self.stats.iter().enumerate() {
+            for (which, member) in stats.iter_live().enumerate() {
+                let index   = HierIndex::new(HierSet::Live, level, which);
+                let borrow  = hier_item_mut!(member);
+                let rustics = borrow.to_rustics_mut();
+
+                traverser.visit(index, true, rustics);
+            }
+        }
+    }
+
+    /// Returns an iterator over the members in the given set on every
+    /// level, from level 0 upward and from the oldest to the newest
+    /// within a level.  Each item gives the index of a member, its
+    /// sample count, and whether it is in the live set of its level.
+
+    pub fn indices(&self, set: HierSet) -> impl Iterator<Item = (HierIndex, u64, bool)> + '_ {
+        self.stats.iter().enumerate().flat_map(move |(level, stats)| {
+            let first_live = stats.all_len() - stats.live_len();
+
+            let (members, offset) =
+                match set {
+                    HierSet::Live => { (stats.iter_live(), first_live) }
+                    HierSet::All  => { (stats.iter_all (), 0         ) }
+                };
+
+            members.enumerate().map(move |(which, member)| {
+                let index = HierIndex::new(set, level, which);
+                let count = hier_item!(member).to_rustics().count();
+                let live  = which + offset >= first_live;
+
+                (index, count, live)
+            })
+        })
+    }
+
     /// Returns the member at the given index, if such exists.
 
     pub fn index(&self, index: HierIndex) -> Option<MemberRc> {
@@ -662,6 +1193,43 @@ impl Hier {
         (Some(sum), valid)
     }
 
+    /// Sums the members at the given level with indices from start up
+    /// to, but not including, end into a new Rustics instance.  The
+    /// indices are for the HierSet::All set, so index 0 is the oldest
+    /// member retained.  The range is clipped to the members that
+    /// exist.  The result is None if no members are in the range.
+
+    pub fn summarize_range(&self, level: usize, start: usize, end: usize) -> Option<MemberRc> {
+        if level >= self.stats.len() {
+            return None;
+        }
+
+        let end = std::cmp::min(end, self.stats[level].all_len());
+
+        if start >= end {
+            return None;
+        }
+
+        let addends: Vec<HierIndex> =
+            (start..end).map(|which| HierIndex::new(HierSet::All, level, which)).collect();
+
+        let (sum, _count) = self.sum(addends, &self.name);
+
+        sum
+    }
+
+    /// Sums the newest n level 0 members, including the current
+    /// member, into a new Rustics instance.  If fewer than n members
+    /// are retained, all of them are summed.  The result is None if
+    /// n is zero.
+
+    pub fn summarize_last(&self, n: usize) -> Option<MemberRc> {
+        let end   = self.stats[0].all_len();
+        let start = end.saturating_sub(n);
+
+        self.summarize_range(0, start, end)
+    }
+
     /// The advance() method pushes a new level 0 Rustics instance into
     /// the level 0 window.  It also updates the upper levels as needed.
     /// The user can call this directly or use auto_advance.  The code
@@ -669,6 +1237,11 @@ impl Hier {
     /// are used.
 
     pub fn advance(&mut self) {
+        // Compare the retiring level 0 instance with the previous one,
+        // if change detection is enabled.
+
+        self.detect_change();
+
         // Increment the advance op count.  This counts the number
         // of level 0 Rustics instances pushed, and thus tells
         // us when we need to push a new higher-level instance.
@@ -687,1525 +1260,3917 @@ impl Hier {
             advance_point *= self.dimensions[i].period as i64;
 
             if self.advance_count % advance_point == 0 {
-                let exporter = self.make_and_fill_exporter(i);
-                let name     = &self.name;
-                let new_stat = generator.make_from_exporter(name, &self.print_opts, exporter);
+                let exporter   = self.make_and_fill_exporter(i);
+                let name       = &self.name;
+                let print_opts = &self.level_opts[i + 1];
+                let new_stat   = generator.make_from_exporter(name, print_opts, exporter);
 
-                self.stats[i + 1].push(new_stat);
+                self.set_level_title(i + 1, &new_stat);
+
+                let evicted = self.stats[i + 1].push_evict(new_stat);
+
+                Hier::archive(&mut self.archiver, &self.dimensions, i + 1, evicted);
             } else {
                 break;
             }
         }
 
+        drop(generator);
+
         // Create the new Rustics instance to collect data and push it into
         // the level zero window.
 
-        let member = generator.make_member(&self.name, &self.print_opts);
+        let member  = self.make_level_0();
+        let evicted = self.stats[0].push_evict(member);
 
-        self.stats[0].push(member);
+        self.retire(0, evicted);
+
+        if self.clock.is_some() {
+            self.advance_time = self.read_clock();
+        }
     }
 
-    /// Returns the number of live members at the given level.
+    // Pass an evicted member to the archiver, if the level is archived.
+    // This is an associated function so that it can be used while the
+    // generator is borrowed.
 
-    pub fn live_len(&self, level: usize) -> usize {
-        self.stats[level].live_len()
+    fn archive(archiver: &mut Option<ArchiverBox>, dimensions: &[HierDimension], level: usize,
+            evicted: Option<MemberRc>) {
+        if let (Some(member), Some(archiver)) = (evicted, archiver) {
+            if dimensions[level].archived {
+                archiver.archive(level, member);
+            }
+        }
     }
 
-    /// Returns the count of all the members at the given level.
+    // Archive or recycle a member evicted from a level.  Only level 0
+    // members are recycled, and only if nothing else holds a reference.
 
-    pub fn all_len(&self, level: usize) -> usize {
-        self.stats[level].all_len()
+    fn retire(&mut self, level: usize, evicted: Option<MemberRc>) {
+        if self.dimensions[level].archived {
+            Hier::archive(&mut self.archiver, &self.dimensions, level, evicted);
+            return;
+        }
+
+        if let (Some(member), Some(pool)) = (evicted, &mut self.pool) {
+            if level == 0 && pool.members.len() < pool.limit && Rc::strong_count(&member) == 1 {
+                pool.members.push(member);
+                pool.recycled += 1;
+            }
+        }
     }
 
-    /// Returns the total number of statistics samples recorded into
-    /// the Hier instance since its creation or since the the last
-    /// clear_all invocation.
+    /// Enables a pool of up to limit retired level 0 members.  When
+    /// the instance advances, the new level 0 member is taken from the
+    /// pool if possible, and cleared, rather than allocated.  A limit of
+    /// zero disables the pool and frees the members in it.
 
-    pub fn event_count(&self) -> i64 {
-        self.event_count
+    pub fn set_member_pool(&mut self, limit: usize) {
+        if limit == 0 {
+            self.pool = None;
+            return;
+        }
+
+        match &mut self.pool {
+            Some(pool) => {
+                pool.limit = limit;
+                pool.members.truncate(limit);
+            }
+
+            None => {
+                let members  = Vec::with_capacity(limit);
+                let reused   = 0;
+                let recycled = 0;
+
+                self.pool = Some(MemberPool { members, limit, reused, recycled });
+            }
+        }
     }
 
-    pub fn hz(&self) -> u128 {
-        let generator = self.generator.borrow();
+    /// Returns the state of the member pool, or None if the pool isn't
+    /// enabled.
 
-        generator.hz()
+    pub fn member_pool_stats(&self) -> Option<MemberPoolStats> {
+        let pool = self.pool.as_ref()?;
+
+        let pooled   = pool.members.len();
+        let limit    = pool.limit;
+        let reused   = pool.reused;
+        let recycled = pool.recycled;
+
+        Some(MemberPoolStats { pooled, limit, reused, recycled })
     }
 
-    // Prints one Rustics instance using the Rustics trait.  This method
-    // always appends the indices to the title.
+    /// Enables change detection.  Each time the instance advances, the
+    /// level 0 instance being retired is compared with the one before
+    /// it.  If the mean or the count changed by more than the given
+    /// thresholds, the callback is invoked with a ChangeReport.  If no
+    /// callback is given, a warning is printed using the printer for
+    /// this instance.
 
-    fn local_print(&self, index: HierIndex, printer_opt: PrinterOption, title_opt: Option<&str>) {
-        let level = index.level;
-        let which = index.which;
+    pub fn set_change_detection(&mut self, thresholds: ChangeThresholds,
+            callback: Option<ChangeCallback>) {
+        let detected = 0;
 
-        let title =
-            if let Some(title) = title_opt {
-                title
-            } else {
-                &self.title
-            };
+        self.change = Some(ChangeDetector { thresholds, callback, detected });
+    }
 
-        let set =
-            match index.set {
-                HierSet::Live => { "live" }
-                HierSet::All  => { "all"  }
-            };
+    /// Returns the number of changes detected that exceeded the
+    /// thresholds, or None if change detection isn't enabled.
 
-        let title = format!("{}[{}].{}[{}]", title, level, set, which);
+    pub fn changes_detected(&self) -> Option<u64> {
+        self.change.as_ref().map(|change| change.detected)
+    }
 
-        let printer_box =
-            if let Some(printer) = printer_opt.clone() {
-                printer
-            } else {
-                self.printer.clone()
-            };
+    /// Sets the clock used for time-based queries, replacing any clock
+    /// given in the descriptor.  The elapsed time and the age of the
+    /// current level 0 instance are measured from this call.
 
-        if level >= self.stats.len() {
-            let printer = printer_mut!(printer_box);
-            printer.print(&title);
-            printer.print(&format!("  That level ({}) is invalid ({} levels configured).",
-                level, self.stats.len()));
-            return;
-        }
+    pub fn set_clock(&mut self, clock: TimerBox) {
+        timer_mut!(clock).start();
 
-        let target = self.index(index);
+        self.clock        = Some(clock);
+        self.advance_time = 0;
 
-        let target =
-            if let Some(target) = target {
-                target
-            } else {
-                let printer = printer_mut!(printer_box);
+        self.now.set(0);
+    }
 
-                printer.print(&title);
-                printer.print(&format!("  That index ({}) is out of bounds.", which));
-                return;
-            };
+    /// Returns true if a clock has been configured.
 
-        // Downcast to the Rustics trait and print.
-
-        let target = target.borrow();
-        target.to_rustics().print_opts(printer_opt, title_opt);
+    pub fn has_clock(&self) -> bool {
+        self.clock.is_some()
     }
 
-    // Use an exporter to create a list of Rustics instances
-    // to be summed to create a higher-level instance.
+    /// Returns the frequency of the clock, if one has been configured.
 
-    fn make_and_fill_exporter(&self, level: usize) -> ExporterRc {
-        let     generator    = self.generator.borrow();
-        let     exporter_rc  = generator.make_exporter();
-        let mut exporter     = exporter_rc.borrow_mut();
+    pub fn clock_hz(&self) -> Option<u128> {
+        let clock = self.clock.as_ref()?;
+        let hz    = clock.borrow().hz();
 
-        let level = &self.stats[level];
+        Some(hz)
+    }
 
-        // Gather the list of Rustics instances to sum into a new
-        // member.
+    /// Returns the time elapsed since the Hier instance was created or
+    /// cleared, or since the clock was set, in clock ticks.  The result
+    /// is None if there is no clock.
 
-        for stat in level.iter_live() {
-            generator.push(&mut *exporter, stat.clone());
-        }
+    pub fn elapsed(&self) -> Option<i64> {
+        self.clock.as_ref()?;
 
-        drop(exporter);
-        exporter_rc
+        Some(self.read_clock())
     }
 
-    // Check the event count to see whether it's time to push a new
-    // level 0 instance.  This method implements the auto_next
-    // feature.
-
-    fn check_and_advance(&mut self) {
-        // Push a new instance if we've reached the event limit
-        // for the current one.  Do this before we push the next
-        // event so that users see an empty current Rustics instance
-        // only before recording any events at all.
-
-        if
-            self.auto_next != 0
-        &&  self.event_count > 0
-        &&  self.event_count % self.auto_next == 0 {
-            self.advance();
-        }
+    /// Returns the age of the current level 0 Rustics instance, i.e.,
+    /// the time since the last advance, in clock ticks.  The result is
+    /// None if there is no clock.
 
-        // Advance the event count.  This method should only be
-        // called when a statistical value is being recorded.
+    pub fn current_age(&self) -> Option<i64> {
+        self.clock.as_ref()?;
 
-        self.event_count += 1;
+        Some(self.read_clock() - self.advance_time)
     }
 
-    fn title_all(&mut self) {
-        let mut traverser = TitleAll::new(&self.title);
-
-        self.traverse_all(&mut traverser);
+    /// Returns the interval for time-based advancing in clock ticks,
+    /// or None if that feature isn't configured.
 
-        if let Some(window) = &mut self.window {
-            window.set_title(&self.title);
+    pub fn advance_every(&self) -> Option<i64> {
+        if self.advance_every == 0 {
+            return None;
         }
+
+        Some(self.advance_every)
     }
-}
 
-// Implement the Rustics trait for the Hier instance.  Unless
-// a window has been configured, the Rustics code returns data
-// from the newest level 0 instance, which is the only one
-// receiving data.
+    /// Checks the clock and advances once for each interval that has
+    /// passed since the start of the current level 0 period, if
+    /// time-based advancing is configured.  Returns the number of
+    /// advance operations done.  The period boundaries stay aligned
+    /// to the interval, so a late check doesn't shift later periods.
+    ///
+    /// After a long gap, at most one advance per retained level 0
+    /// member is done, since more would only push out empty members,
+    /// and the remaining intervals are skipped.  The upper levels thus
+    /// see fewer empty periods than the clock would imply.
+
+    pub fn advance_if_due(&mut self) -> i64 {
+        if self.advance_every == 0 || self.clock.is_none() {
+            return 0;
+        }
 
-impl Rustics for Hier {
-    fn record_i64(&mut self, value: i64) {
-        self.check_and_advance();
+        let now     = self.read_clock();
+        let elapsed = now - self.advance_time;
 
-        let member  = self.stats[0].newest_mut().unwrap();
-        let borrow  = hier_item_mut!(member);
-        let rustics = borrow.to_rustics_mut();
+        if elapsed < self.advance_every {
+            return 0;
+        }
 
-        rustics.record_i64(value);
+        let due      = elapsed / self.advance_every;
+        let boundary = self.advance_time + due * self.advance_every;
+        let limit    = self.dimensions[0].retention as i64;
+        let advances = std::cmp::min(due, limit);
 
-        if let Some(window) = &mut self.window {
-            window.record_i64(value);
+        for _i in 0..advances {
+            self.advance();
         }
-    }
 
-    fn record_f64(&mut self, sample: f64) {
-        self.check_and_advance();
+        self.advance_time = boundary;
+        advances
+    }
 
-        let current = self.current();
-        let borrow  = hier_item_mut!(current);
-        let rustics = borrow.to_rustics_mut();
+    // Read the clock and return the total elapsed time.  The timer is
+    // restarted on each read, so the intervals are accumulated.
 
-        rustics.record_f64(sample);
+    fn read_clock(&self) -> i64 {
+        if let Some(clock) = &self.clock {
+            let interval = timer_mut!(clock).finish();
 
-        if let Some(window) = &mut self.window {
-            window.record_f64(sample);
+            self.now.set(self.now.get() + interval);
         }
+
+        self.now.get()
     }
 
-    fn record_event(&mut self) {
-        let _ = self.record_event_report();
+    /// Returns the number of live members at the given level.
+
+    pub fn live_len(&self, level: usize) -> usize {
+        self.stats[level].live_len()
     }
 
-    fn record_event_report(&mut self) -> i64 {
-        self.check_and_advance();
+    /// Returns the count of all the members at the given level.
 
-        let member  = self.stats[0].newest_mut().unwrap();
-        let borrow  = hier_item_mut!(member);
-        let rustics = borrow.to_rustics_mut();
+    pub fn all_len(&self, level: usize) -> usize {
+        self.stats[level].all_len()
+    }
 
-        // Now record the event in the window, if one is present.
+    /// Drops the oldest members at the given level so that at most
+    /// keep_last_n remain, to reduce memory usage.  Live members are
+    /// never dropped.  If the level is archived, the dropped members
+    /// are passed to the archiver, as when they are evicted.  Returns
+    /// the number of members dropped.
 
-        let sample = rustics.record_event_report();
+    pub fn prune(&mut self, level: usize, keep_last_n: usize) -> usize {
+        if level >= self.stats.len() {
+            panic!("Hier::prune:  The level {} doesn't exist.", level);
+        }
 
-        if let Some(window) = &mut self.window {
-            assert!(self.class == "time");
-            window.record_time(sample);
+        let pruned = self.stats[level].prune(keep_last_n);
+        let count  = pruned.len();
+
+        for member in pruned {
+            self.retire(level, Some(member));
         }
 
-        sample
+        count
     }
 
-    fn record_time(&mut self, sample: i64) {
-        self.check_and_advance();
-
-        let current = self.current();
-        let borrow  = hier_item_mut!(current);
-        let rustics = borrow.to_rustics_mut();
+    /// Drops all the members beyond the live set at every level.
+    /// Returns the number of members dropped.
 
-        rustics.record_time(sample);
+    pub fn compact(&mut self) -> usize {
+        let mut count = 0;
 
-        if let Some(window) = &mut self.window {
-            window.record_time(sample);
+        for level in 0..self.stats.len() {
+            count += self.prune(level, 0);
         }
+
+        count
     }
 
-    fn record_interval(&mut self, timer: &mut TimerBox) {
-        self.check_and_advance();
+    /// Returns an estimate of the memory used by the members at the
+    /// given level, in bytes.  The estimate includes each member and
+    /// its histogram, but not heap data like names and labels.
 
-        let current = self.current();
-        let borrow  = hier_item_mut!(current);
-        let rustics = borrow.to_rustics_mut();
-        let time    = timer_mut!(timer).finish();
+    pub fn memory_usage(&self, level: usize) -> usize {
+        let mut total = 0;
 
-        rustics.record_time(time);
+        for member in self.stats[level].iter_all() {
+            let member  = hier_item!(member);
+            let rustics = member.to_rustics();
 
-        if let Some(window) = &mut self.window {
-            window.record_time(time);
+            total += std::mem::size_of_val(rustics);
+
+            if let Some(histogram) = rustics.log_histogram() {
+                total += std::mem::size_of_val(&*histogram.borrow());
+            }
+
+            if let Some(histogram) = rustics.float_histogram() {
+                let histogram = histogram.borrow();
+                let buckets   = histogram.negative.len() + histogram.positive.len();
+
+                total += std::mem::size_of_val(&*histogram) + buckets * std::mem::size_of::<u64>();
+            }
         }
+
+        total
     }
 
-    // We return the name and title of the Hier instance itself.
+    /// Returns the total number of statistics samples recorded into
+    /// the Hier instance since its creation or since the the last
+    /// clear_all invocation.
 
-    fn name(&self) -> String {
-        self.name.clone()
+    pub fn event_count(&self) -> i64 {
+        self.event_count
     }
 
-    fn title(&self) -> String {
-        self.title.clone()
-    }
+    /// Returns the number of advance operations since the Hier instance
+    /// was created or last cleared.  This count is the index of the
+    /// period for the current level 0 instance.
 
-    fn class(&self) -> &str {
-        &self.class
+    pub fn advance_count(&self) -> i64 {
+        self.advance_count
     }
 
-    fn count(&self) -> u64 {
-        if let Some(window) = &self.window {
-            window.count()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+    /// Returns the number of samples that can be recorded before the
+    /// next automatic advance, or None if auto_next isn't configured.
+    /// The result is zero when the current level 0 instance is full,
+    /// so the next sample will cause an advance.
 
-            rustics.count()
+    pub fn samples_until_advance(&self) -> Option<i64> {
+        if self.auto_next == 0 {
+            return None;
         }
-    }
 
-    fn log_mode(&self) -> isize {
-        if let Some(window) = &self.window {
-            window.log_mode()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+        let recorded =
+            if self.event_count == 0 {
+                0
+            } else {
+                (self.event_count - 1) % self.auto_next + 1
+            };
 
-            rustics.log_mode()
-        }
+        Some(self.auto_next - recorded)
     }
 
-    fn mean(&self) -> f64 {
-        if let Some(window) = &self.window {
-            window.mean()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+    /// Returns the number of level 0 periods summed into each member
+    /// at the given level.
 
-            rustics.mean()
+    pub fn period_span(&self, level: usize) -> i64 {
+        let mut span = 1;
+
+        for i in 0..level {
+            span *= self.dimensions[i].period as i64;
         }
+
+        span
     }
 
-    fn standard_deviation(&self) -> f64 {
-        if let Some(window) = &self.window {
-            window.standard_deviation()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+    /// Returns the number of members that have been pushed into the
+    /// given level since the Hier instance was created or cleared,
+    /// including those that have been discarded.
 
-            rustics.standard_deviation()
+    pub fn pushed_count(&self, level: usize) -> i64 {
+        if level == 0 {
+            self.advance_count + 1
+        } else {
+            self.advance_count / self.period_span(level)
         }
     }
 
-    fn variance(&self) -> f64 {
-        if let Some(window) = &self.window {
-            window.variance()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+    /// Returns the period index of the member at the given index.  The
+    /// first member pushed into a level has index zero.  A member at
+    /// level n with period index p covers level 0 periods starting at
+    /// p * period_span(n).  The result is None if there is no such
+    /// member.
 
-            rustics.variance()
+    pub fn period_index(&self, index: HierIndex) -> Option<i64> {
+        let level = index.level;
+        let which = index.which;
+
+        if level >= self.stats.len() {
+            return None;
         }
-    }
 
-    fn skewness(&self) -> f64 {
-        if let Some(window) = &self.window {
-            window.skewness()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+        let length =
+            match index.set {
+                HierSet::Live => { self.stats[level].live_len() }
+                HierSet::All  => { self.stats[level].all_len () }
+            };
 
-            rustics.skewness()
+        if which >= length {
+            return None;
         }
+
+        // The windows are indexed from the oldest member.
+
+        Some(self.pushed_count(level) - length as i64 + which as i64)
     }
 
-    fn kurtosis(&self) -> f64 {
-        if let Some(window) = &self.window {
-            window.kurtosis()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+    /// Returns the state of the member at the given index, or None if
+    /// there is no such member.  See HierMemberState.
 
-            rustics.kurtosis()
+    pub fn member_state(&self, index: HierIndex) -> Option<HierMemberState> {
+        let level        = index.level;
+        let period_index = self.period_index(index)?;
+        let top          = self.stats.len() - 1;
+
+        // The top level never is summarized, so its members just age
+        // out of the live set.
+
+        if level == top {
+            let live_start = self.pushed_count(level) - self.stats[level].live_len() as i64;
+
+            if period_index >= live_start {
+                return Some(HierMemberState::Live);
+            } else {
+                return Some(HierMemberState::Retired);
+            }
         }
-    }
 
-    fn int_extremes(&self) -> bool {
-        if let Some(window) = &self.window {
-            window.int_extremes()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+        // At the lower levels, a summary is pushed each time "period"
+        // members have been completed, so the members before the last
+        // multiple of the period have been summarized.  The current
+        // level 0 member isn't complete.
 
-            rustics.int_extremes()
+        let period     = self.dimensions[level].period as i64;
+        let completed  = self.advance_count / self.period_span(level);
+        let summarized = (completed / period) * period;
+
+        if period_index < summarized {
+            Some(HierMemberState::Summarized)
+        } else {
+            Some(HierMemberState::Live)
         }
     }
 
-    fn float_extremes(&self) -> bool {
-        if let Some(window) = &self.window {
-            window.float_extremes()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+    /// Returns the age of the member at the given index in level 0
+    /// periods, measured from the start of the first period that it
+    /// covers.  The current level 0 member has age zero.  The result
+    /// is None if there is no such member.
 
-            rustics.float_extremes()
-        }
+    pub fn member_age(&self, index: HierIndex) -> Option<i64> {
+        let period_index = self.period_index(index)?;
+        let start        = period_index * self.period_span(index.level);
+
+        Some(self.advance_count - start)
     }
 
-    fn min_i64(&self) -> i64  {
-        if let Some(window) = &self.window {
-            window.min_i64()
+    /// Estimates a percentile, in the range 0 to 100, for the member
+    /// at the given level and index.  The index is for the
+    /// HierSet::All set, so index 0 is the oldest member retained.
+    /// The upper-level members hold the sums of the histograms of the
+    /// members that they summarize, so this gives per-period tail
+    /// values at every level.
+    ///
+    /// The estimate comes from the member's histogram, so it is the
+    /// bucket bound described in Printable::log_percentile or
+    /// Printable::float_percentile.  Time values are in clock ticks.
+    /// The result is None if there is no such member or the member
+    /// keeps no histogram.
+
+    pub fn percentile_at(&self, level: usize, which: usize, percentile: f64) -> Option<f64> {
+        let member  = self.index(HierIndex::new(HierSet::All, level, which))?;
+        let member  = member.borrow();
+        let rustics = member.to_rustics();
+
+        if let Some(histogram) = rustics.log_histogram() {
+            Some(Printable::log_percentile(&histogram.borrow(), percentile) as f64)
         } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
-
-            rustics.min_i64()
+            rustics.float_histogram()
+                .map(|histogram| Printable::float_percentile(&histogram.borrow(), percentile))
         }
     }
 
-    fn min_f64(&self) -> f64 {
-        if let Some(window) = &self.window {
-            window.min_f64()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+    /// Checks the hierarchy for internal consistency.  This is a
+    /// debugging aid for custom HierGenerator implementations and for
+    /// unusual configurations.
+    ///
+    /// Each upper-level member whose inputs still are retained at the
+    /// level below is recomputed by pooling the counts, means, and
+    /// variances of those inputs, and by taking their extremes.  The
+    /// results are compared with the member built by the generator,
+    /// and differences larger than a relative tolerance of 1.0e-6 are
+    /// reported.  The number of members retained at each level also is
+    /// checked against the retention limit and the number of members
+    /// pushed.  The check reads every member, so it can be slow for a
+    /// large hierarchy.
+
+    pub fn validate(&self) -> HierValidation {
+        self.validate_tolerance(VALIDATE_TOLERANCE)
+    }
 
-            rustics.min_f64()
+    /// Works like validate() with the given relative tolerance.
+
+    pub fn validate_tolerance(&self, tolerance: f64) -> HierValidation {
+        let mut result = HierValidation::default();
+
+        for level in 0..self.stats.len() {
+            let retention = self.dimensions[level].retention as i64;
+            let expected  = std::cmp::min(retention, self.pushed_count(level));
+            let found     = self.stats[level].all_len() as i64;
+
+            if found != expected {
+                let which    = None;
+                let quantity = "members";
+                let expected = expected as f64;
+                let found    = found    as f64;
+
+                result.discrepancies.push(HierDiscrepancy { level, which, quantity, expected, found });
+            }
+        }
+
+        for level in 1..self.stats.len() {
+            for which in 0..self.stats[level].all_len() {
+                self.validate_member(level, which, tolerance, &mut result);
+            }
         }
+
+        result
     }
 
-    fn max_i64(&self) -> i64 {
-        if let Some(window) = &self.window {
-            window.max_i64()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+    // Recompute one upper-level member from the members that it
+    // summarizes, if they all still are retained.
 
-            rustics.max_i64()
+    fn validate_member(&self, level: usize, which: usize, tolerance: f64,
+            result: &mut HierValidation) {
+        let period       = self.dimensions[level - 1].period as i64;
+        let period_index = self.period_index(HierIndex::new(HierSet::All, level, which)).unwrap();
+        let lower_pushed = self.pushed_count(level - 1);
+        let lower_first  = lower_pushed - self.stats[level - 1].all_len() as i64;
+        let start        = period_index * period;
+        let end          = start + period;
+
+        if start < lower_first || end > lower_pushed {
+            result.skipped += 1;
+            return;
         }
-    }
 
-    fn max_f64(&self) -> f64 {
-        if let Some(window) = &self.window {
-            window.max_f64()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+        let member  = self.stats[level].index_all(which).unwrap().clone();
+        let member  = member.borrow();
+        let rustics = member.to_rustics();
+        let ints    = rustics.int_extremes();
 
-            rustics.max_f64()
+        // Types like Cardinality don't keep extremes, and their counts
+        // don't add, so they can't be pooled.
+
+        if !ints && !rustics.float_extremes() {
+            result.skipped += 1;
+            return;
         }
-    }
 
-    fn precompute(&mut self) {
-        if let Some(window) = &mut self.window {
-            window.precompute();
-        } else {
-            let current = self.current();
-            let borrow  = hier_item_mut!(current);
-            let rustics = borrow.to_rustics_mut();
+        let mut parts = Vec::with_capacity(period as usize);
+        let mut min   = f64::INFINITY;
+        let mut max   = f64::NEG_INFINITY;
 
-            rustics.precompute();
+        for lower in start..end {
+            let input  = self.stats[level - 1].index_all((lower - lower_first) as usize).unwrap();
+            let input  = input.borrow();
+            let stats  = input.to_rustics().export_stats().printable;
+
+            if stats.n > 0 {
+                let (low, high) = extremes(&stats, ints);
+
+                min = min.min(low );
+                max = max.max(high);
+            }
+
+            let n        = stats.n as f64;
+            let mean     = stats.mean;
+            let moment_2 = if stats.n > 1 { stats.variance * (n - 1.0) } else { 0.0 };
+
+            parts.push(PoolData { n, mean, moment_2 });
+        }
+
+        let pooled   = pool_moments(&parts);
+        let count    = pooled.n as u64;
+        let stats    = rustics.export_stats().printable;
+        let variance = compute_variance(count, pooled.moment_2);
+
+        let mut check =
+            |quantity: &'static str, expected: f64, found: f64, scale: f64| {
+                if differs(expected, found, tolerance * scale) {
+                    let which = Some(which);
+
+                    result.discrepancies.push(HierDiscrepancy { level, which, quantity, expected, found });
+                }
+            };
+
+        check("count", count as f64, stats.n as f64, 0.0);
+
+        if count > 0 {
+            let (low, high) = extremes(&stats, ints);
+
+            check("mean", pooled.mean, stats.mean, pooled.mean.abs());
+            check("min",  min,         low,        min.abs()        );
+            check("max",  max,         high,       max.abs()        );
+        }
+
+        // The error in a variance computed from power sums grows with
+        // the square of the mean, so scale the tolerance by that, too.
+
+        if count > 1 {
+            let scale = variance.abs().max(pooled.mean * pooled.mean);
+
+            check("variance", variance, stats.variance, scale);
         }
+
+        result.checked += 1;
     }
 
-    /// Deletes all the statistical data in the Hier object.
+    pub fn hz(&self) -> u128 {
+        let generator = self.generator.borrow();
 
-    fn clear(&mut self) {
-        self.clear_all();
+        generator.hz()
     }
 
-    // Functions for printing
+    /// Writes the state of the hierarchy to the given writer in the
+    /// checkpoint text format.  The clock, printers, and timers are
+    /// not saved.  The result is an error of kind Unsupported if the
+    /// member type doesn't support checkpoints.
+
+    pub fn save(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        let unsupported = || Error::new(ErrorKind::Unsupported, "Hier::save:  not supported");
+
+        let     generator  = self.generator.borrow();
+        let mut checkpoint = Checkpoint::new("hier");
+        let     periods:    Vec<u64> = self.dimensions.iter().map(|d| d.period    as u64).collect();
+        let     retentions: Vec<u64> = self.dimensions.iter().map(|d| d.retention as u64).collect();
+
+        checkpoint.push_str ("name",          &self.name         );
+        checkpoint.push_str ("class",         &self.class        );
+        checkpoint.push_u64s("periods",       &periods           );
+        checkpoint.push_u64s("retentions",    &retentions        );
+        checkpoint.push_i64 ("auto_next",     self.auto_next     );
+        checkpoint.push_i64 ("advance_count", self.advance_count );
+        checkpoint.push_i64 ("event_count",   self.event_count   );
+
+        // Save each level from the oldest member to the newest.
+
+        for (level, window) in self.stats.iter().enumerate() {
+            checkpoint.push_u64(&format!("level_{}", level), window.all_len() as u64);
+
+            for (i, member) in window.iter_all().enumerate() {
+                let text = generator.checkpoint_member(member).ok_or_else(unsupported)?;
+
+                checkpoint.push_str(&format!("level_{}_{}", level, i), &text);
+            }
+        }
 
-    fn print(&self) {
         if let Some(window) = &self.window {
-            window.print_opts(None, Some(&self.title));
-        } else {
-            let index = HierIndex::new(HierSet::Live, 0, self.live_len(0) - 1);
+            let text = generator.checkpoint_window(window.as_ref()).ok_or_else(unsupported)?;
 
-            self.local_print(index, None, None);
+            checkpoint.push_str("window", &text);
         }
+
+        writer.write_all(checkpoint.text().as_bytes())
     }
 
-    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+    /// Restores the state saved by save() into this instance, which
+    /// must have the same configuration as the saved instance.  Any
+    /// data already recorded is replaced.  On an error, the instance
+    /// is not changed.
+
+    pub fn load(&mut self, reader: &mut dyn Read) -> std::io::Result<()> {
+        let mut text = String::new();
+
+        reader.read_to_string(&mut text)?;
+
+        let invalid = || Error::new(ErrorKind::InvalidData, "Hier::load:  invalid data");
+        let reader  = CheckpointReader::new_kind(&text, "hier").ok_or_else(invalid)?;
+
+        let periods:    Vec<u64> = self.dimensions.iter().map(|d| d.period    as u64).collect();
+        let retentions: Vec<u64> = self.dimensions.iter().map(|d| d.retention as u64).collect();
+
+        if
+            reader.string("class"     ).ok_or_else(invalid)? != self.class
+        ||  reader.u64s  ("periods"   ).ok_or_else(invalid)? != periods
+        ||  reader.u64s  ("retentions").ok_or_else(invalid)? != retentions {
+            return Err(invalid());
+        }
+
+        let auto_next     = reader.i64("auto_next"    ).ok_or_else(invalid)?;
+        let advance_count = reader.i64("advance_count").ok_or_else(invalid)?;
+        let event_count   = reader.i64("event_count"  ).ok_or_else(invalid)?;
+
+        // Rebuild the windows by pushing the members in order.
+
+        let mut stats     = Vec::with_capacity(self.dimensions.len());
+        let     generator = self.generator.borrow();
+
+        for (level, dimension) in self.dimensions.iter().enumerate() {
+            let mut window = Window::new(dimension.retention, dimension.period);
+            let     length = reader.usize(&format!("level_{}", level)).ok_or_else(invalid)?;
+
+            if length > dimension.retention {
+                return Err(invalid());
+            }
+
+            for i in 0..length {
+                let text       = reader.string(&format!("level_{}_{}", level, i)).ok_or_else(invalid)?;
+                let print_opts = &self.level_opts[level];
+                let member     = generator.restore_member(&text, print_opts).ok_or_else(invalid)?;
+
+                if hier_item!(member).to_rustics().class() != self.class {
+                    return Err(invalid());
+                }
+
+                window.push(member);
+            }
+
+            stats.push(window);
+        }
+
+        if stats[0].is_empty() {
+            return Err(invalid());
+        }
+
+        let window =
+            match (&self.window, reader.string("window")) {
+                (Some(_), Some(text)) => {
+                    let window = generator.restore_window(&text, &self.print_opts);
+
+                    Some(window.ok_or_else(invalid)?)
+                }
+
+                (None, None) => { None }
+                _            => { return Err(invalid()); }
+            };
+
+        drop(generator);
+
+        self.stats         = stats;
+        self.window        = window;
+        self.auto_next     = auto_next;
+        self.advance_count = advance_count;
+        self.event_count   = event_count;
+
+        if self.clock.is_some() {
+            self.advance_time = self.read_clock();
+        }
+
+        Ok(())
+    }
+
+    // Prints one Rustics instance using the Rustics trait.  This method
+    // always appends the indices to the title.
+
+    fn local_print(&self, index: HierIndex, printer_opt: PrinterOption, title_opt: Option<&str>) {
+        let level = index.level;
+        let which = index.which;
+
         let title =
-            if let Some(title) = title {
+            if let Some(title) = title_opt {
                 title
             } else {
                 &self.title
             };
 
-        if let Some(window) = &self.window {
-            window.print_opts(printer, Some(title));
-        } else {
-            let index = HierIndex::new(HierSet::Live, 0, self.live_len(0) - 1);
+        let set =
+            match index.set {
+                HierSet::Live => { "live" }
+                HierSet::All  => { "all"  }
+            };
 
-            self.local_print(index, printer, Some(title));
-        }
-    }
+        let title = format!("{}[{}].{}[{}]", title, level, set, which);
 
-    fn log_histogram(&self) -> Option<LogHistogramBox> {
-        if let Some(window) = &self.window {
-            window.log_histogram()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+        let printer_box =
+            if let Some(printer) = printer_opt.clone() {
+                printer
+            } else {
+                self.printer.clone()
+            };
 
-            rustics.log_histogram()
+        if level >= self.stats.len() {
+            let printer = printer_mut!(printer_box);
+            printer.print(&title);
+            printer.print(&format!("  That level ({}) is invalid ({} levels configured).",
+                level, self.stats.len()));
+            return;
         }
+
+        let target = self.index(index);
+
+        let target =
+            if let Some(target) = target {
+                target
+            } else {
+                let printer = printer_mut!(printer_box);
+
+                printer.print(&title);
+                printer.print(&format!("  That index ({}) is out of bounds.", which));
+                return;
+            };
+
+        // Downcast to the Rustics trait and print.
+
+        let target = target.borrow();
+        target.to_rustics().print_opts(printer_opt, title_opt);
     }
 
-    fn float_histogram(&self) -> Option<FloatHistogramBox> {
-        if let Some(window) = &self.window {
-            window.float_histogram()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+    // Use an exporter to create a list of Rustics instances
+    // to be summed to create a higher-level instance.
 
-            rustics.float_histogram()
+    fn make_and_fill_exporter(&self, level: usize) -> ExporterRc {
+        let     generator    = self.generator.borrow();
+        let     exporter_rc  = generator.make_exporter();
+        let mut exporter     = exporter_rc.borrow_mut();
+
+        let level = &self.stats[level];
+
+        // Gather the list of Rustics instances to sum into a new
+        // member.
+
+        for stat in level.iter_live() {
+            generator.push(&mut *exporter, stat.clone());
         }
+
+        drop(exporter);
+        exporter_rc
     }
 
-    // The title is kept in the Hier instance.
+    // Check the event count to see whether it's time to push a new
+    // level 0 instance.  This method implements the auto_next
+    // feature.
 
-    /// Sets the title used when printing.  The Hier implementation always
-    /// appends the set indices to the title when printing a member.
+    fn check_and_advance(&mut self) {
+        // Start a new period if the time for the current one has
+        // passed.
 
-    fn set_title(&mut self, title: &str) {
-        self.title = title.to_string();
+        self.advance_if_due();
 
-        self.title_all();
-    }
+        // Push a new instance if we've reached the event limit
+        // for the current one.  Do this before we push the next
+        // event so that users see an empty current Rustics instance
+        // only before recording any events at all.
 
-    // For internal use.
+        if
+            self.auto_next != 0
+        &&  self.event_count > 0
+        &&  self.event_count % self.auto_next == 0 {
+            self.advance();
+        }
 
-    fn set_id(&mut self, id: usize) {
-        self.id = id;
-    }
+        // Advance the event count.  This method should only be
+        // called when a statistical value is being recorded.
 
-    fn id(&self) -> usize {
-        self.id
+        self.event_count += 1;
     }
 
-    fn equals(&self, other: &dyn Rustics) -> bool {
-        if let Some(other) = <dyn Any>::downcast_ref::<Hier>(other.generic()) {
-            std::ptr::eq(self, other)
-        } else {
-            false
+    // Compare the newest level 0 instance with the previous one and
+    // report any change that exceeds the configured thresholds.
+
+    fn detect_change(&mut self) {
+        if self.change.is_none() {
+            return;
         }
-    }
 
-    fn generic(&self) -> &dyn Any {
-        self as &dyn Any
-    }
+        let length = self.stats[0].all_len();
 
-    fn export_stats(&self) -> ExportStats {
-        if let Some(window) = &self.window {
-            window.export_stats()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+        if length < 2 {
+            return;
+        }
+
+        let previous = self.stats[0].index_all(length - 2).unwrap();
+        let current  = self.stats[0].index_all(length - 1).unwrap();
+
+        let (previous_mean, previous_count) = {
+            let member = hier_item!(previous);
+            (member.to_rustics().mean(), member.to_rustics().count())
+        };
+
+        let (current_mean, current_count) = {
+            let member = hier_item!(current);
+            (member.to_rustics().mean(), member.to_rustics().count())
+        };
+
+        let mean_change =
+            if previous_count > 0 && current_count > 0 {
+                percent_change(previous_mean, current_mean)
+            } else {
+                0.0
+            };
+
+        let count_change = percent_change(previous_count as f64, current_count as f64);
+        let change       = self.change.as_mut().unwrap();
+        let thresholds   = change.thresholds;
+
+        let mean_exceeded  = mean_change .abs() > thresholds.mean_percent;
+        let count_exceeded = count_change.abs() > thresholds.count_percent;
+
+        if !mean_exceeded && !count_exceeded {
+            return;
+        }
+
+        change.detected += 1;
+
+        let name   = self.name.clone();
+        let period = self.advance_count;
+
+        let report =
+            ChangeReport {
+                name,            period,
+                previous_mean,   current_mean,    mean_change,
+                previous_count,  current_count,   count_change,
+                mean_exceeded,   count_exceeded
+            };
+
+        if let Some(callback) = &mut change.callback {
+            callback(&report);
+            return;
+        }
+
+        let printer = printer_mut!(self.printer);
+
+        printer.print(&format!("{}:  Change detected in period {}", self.title, period));
+
+        if mean_exceeded {
+            printer.print(&format!("    Mean    {:+.1}%  ({:.4} to {:.4})",
+                mean_change, previous_mean, current_mean));
+        }
+
+        if count_exceeded {
+            printer.print(&format!("    Count   {:+.1}%  ({} to {})",
+                count_change, Printable::commas_u64(previous_count),
+                Printable::commas_u64(current_count)));
+        }
+    }
+
+    // Creates a new level 0 member using the print options for
+    // that level.
+
+    fn make_level_0(&mut self) -> MemberRc {
+        // Reuse a retired member if the pool has one.  It keeps the
+        // title set when it was made.
+
+        if let Some(pool) = &mut self.pool {
+            if let Some(member) = pool.members.pop() {
+                pool.reused += 1;
+                hier_item_mut!(member).to_rustics_mut().clear();
+                return member;
+            }
+        }
+
+        let generator = self.generator.borrow();
+        let member    = generator.make_member(&self.name, &self.level_opts[0]);
+
+        self.set_level_title(0, &member);
+        member
+    }
+
+    // Append the title suffix for the level, if one was configured.
+
+    fn set_level_title(&self, level: usize, member: &MemberRc) {
+        if !self.suffixes[level].is_empty() {
+            let title = format!("{}{}", self.title, self.suffixes[level]);
+
+            hier_item_mut!(member).to_rustics_mut().set_title(&title);
+        }
+    }
+
+    fn title_all(&mut self) {
+        for i in 0..self.stats.len() {
+            let     title     = format!("{}{}", self.title, self.suffixes[i]);
+            let mut traverser = TitleAll::new(&title);
+
+            for member in self.stats[i].iter_all() {
+                traverser.visit(hier_item_mut!(member).to_rustics_mut());
+            }
+        }
+
+        // Pooled members will become level 0 members.
+
+        if let Some(pool) = &self.pool {
+            let     title     = format!("{}{}", self.title, self.suffixes[0]);
+            let mut traverser = TitleAll::new(&title);
+
+            for member in pool.members.iter() {
+                traverser.visit(hier_item_mut!(member).to_rustics_mut());
+            }
+        }
+
+        if let Some(window) = &mut self.window {
+            window.set_title(&self.title);
+        }
+    }
+}
+
+// Merges the print options for a level into the options for the
+// Hier instance.  The title in the level options is a suffix, and
+// is handled by the caller.
+
+fn merge_level_opts(print_opts: &PrintOption, level_opts: &PrintOption, name: &str)
+        -> PrintOption {
+    let level_opts =
+        if let Some(level_opts) = level_opts {
+            level_opts
+        } else {
+            return print_opts.clone();
+        };
+
+    let (printer, title, units, histo_opts) = parse_print_opts(print_opts, name);
+
+    let printer    = Some(level_opts.printer.clone().unwrap_or(printer));
+    let title      = Some(title);
+    let units      = Some(level_opts.units.clone().unwrap_or(units));
+    let histo_opts = Some(level_opts.histo_opts.unwrap_or(histo_opts));
+    let fields     = Some(level_opts.fields.unwrap_or(parse_fields(print_opts)));
+
+    Some(PrintOpts { printer, title, units, histo_opts, fields })
+}
+
+// Serialize a Hier instance in the format used by save().
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Hier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+
+        let mut text = Vec::new();
+
+        self.save(&mut text).map_err(S::Error::custom)?;
+
+        let text = String::from_utf8(text).map_err(S::Error::custom)?;
+
+        serialize_checkpoint(&text, serializer)
+    }
+}
+
+/// HierSeed deserializes the data written by the Serialize
+/// implementation for Hier into an existing instance, which must
+/// have the same configuration, as Hier::load does.
+
+#[cfg(feature = "serde")]
+pub struct HierSeed<'a> {
+    hier:   &'a mut Hier,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> HierSeed<'a> {
+    pub fn new(hier: &'a mut Hier) -> HierSeed<'a> {
+        HierSeed { hier }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::DeserializeSeed<'de> for HierSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        use serde::de::Error;
+
+        let text = deserialize_checkpoint(deserializer)?;
+
+        self.hier.load(&mut text.as_bytes()).map_err(D::Error::custom)
+    }
+}
+
+// Implement the Rustics trait for the Hier instance.  Unless
+// a window has been configured, the Rustics code returns data
+// from the newest level 0 instance, which is the only one
+// receiving data.
+
+impl Rustics for Hier {
+    fn record_i64(&mut self, value: i64) {
+        call_hook(&self.hook, &self.name, HookSample::Integer(value));
+        self.check_and_advance();
+
+        let member  = self.stats[0].newest_mut().unwrap();
+        let borrow  = hier_item_mut!(member);
+        let rustics = borrow.to_rustics_mut();
+
+        rustics.record_i64(value);
+
+        if let Some(window) = &mut self.window {
+            window.record_i64(value);
+        }
+    }
+
+    fn record_f64(&mut self, sample: f64) {
+        call_hook(&self.hook, &self.name, HookSample::Float(sample));
+        self.check_and_advance();
+
+        let current = self.current();
+        let borrow  = hier_item_mut!(current);
+        let rustics = borrow.to_rustics_mut();
+
+        rustics.record_f64(sample);
+
+        if let Some(window) = &mut self.window {
+            window.record_f64(sample);
+        }
+    }
+
+    fn record_f32(&mut self, sample: f32) {
+        call_hook(&self.hook, &self.name, HookSample::Float(sample as f64));
+        self.check_and_advance();
+
+        let current = self.current();
+        let borrow  = hier_item_mut!(current);
+        let rustics = borrow.to_rustics_mut();
+
+        rustics.record_f32(sample);
+
+        if let Some(window) = &mut self.window {
+            window.record_f32(sample);
+        }
+    }
+
+    fn record_event(&mut self) {
+        let _ = self.record_event_report();
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        self.check_and_advance();
+
+        let member  = self.stats[0].newest_mut().unwrap();
+        let borrow  = hier_item_mut!(member);
+        let rustics = borrow.to_rustics_mut();
+
+        // Now record the event in the window, if one is present.
+
+        let sample = rustics.record_event_report();
+
+        if let Some(window) = &mut self.window {
+            assert!(self.class == "time");
+            window.record_time(sample);
+        }
+
+        // Time instances turn events into samples.
+
+        if self.class == "time" {
+            call_hook(&self.hook, &self.name, HookSample::Time(sample));
+        } else {
+            call_hook(&self.hook, &self.name, HookSample::Event);
+        }
+
+        sample
+    }
+
+    fn record_time(&mut self, sample: i64) {
+        call_hook(&self.hook, &self.name, HookSample::Time(sample));
+        self.check_and_advance();
+
+        let current = self.current();
+        let borrow  = hier_item_mut!(current);
+        let rustics = borrow.to_rustics_mut();
+
+        rustics.record_time(sample);
+
+        if let Some(window) = &mut self.window {
+            window.record_time(sample);
+        }
+    }
+
+    fn record_interval(&mut self, timer: &mut TimerBox) {
+        self.check_and_advance();
+
+        let current = self.current();
+        let borrow  = hier_item_mut!(current);
+        let rustics = borrow.to_rustics_mut();
+        let time    = timer_mut!(timer).finish();
+
+        call_hook(&self.hook, &self.name, HookSample::Time(time));
+        rustics.record_time(time);
+
+        if let Some(window) = &mut self.window {
+            window.record_time(time);
+        }
+    }
+
+    // We return the name and title of the Hier instance itself.
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn class(&self) -> &str {
+        &self.class
+    }
+
+    fn count(&self) -> u64 {
+        if let Some(window) = &self.window {
+            window.count()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.count()
+        }
+    }
+
+    fn log_mode(&self) -> isize {
+        if let Some(window) = &self.window {
+            window.log_mode()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.log_mode()
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        if let Some(window) = &self.window {
+            window.mean()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.mean()
+        }
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        if let Some(window) = &self.window {
+            window.standard_deviation()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.standard_deviation()
+        }
+    }
+
+    fn variance(&self) -> f64 {
+        if let Some(window) = &self.window {
+            window.variance()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.variance()
+        }
+    }
+
+    fn skewness(&self) -> f64 {
+        if let Some(window) = &self.window {
+            window.skewness()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.skewness()
+        }
+    }
+
+    fn kurtosis(&self) -> f64 {
+        if let Some(window) = &self.window {
+            window.kurtosis()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.kurtosis()
+        }
+    }
+
+    fn has_median(&self) -> bool {
+        if let Some(window) = &self.window {
+            window.has_median()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.has_median()
+        }
+    }
+
+    fn median(&self) -> f64 {
+        if let Some(window) = &self.window {
+            window.median()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.median()
+        }
+    }
+
+    fn mad(&self) -> f64 {
+        if let Some(window) = &self.window {
+            window.mad()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.mad()
+        }
+    }
+
+    fn int_extremes(&self) -> bool {
+        if let Some(window) = &self.window {
+            window.int_extremes()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.int_extremes()
+        }
+    }
+
+    fn float_extremes(&self) -> bool {
+        if let Some(window) = &self.window {
+            window.float_extremes()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.float_extremes()
+        }
+    }
+
+    fn min_i64(&self) -> i64  {
+        if let Some(window) = &self.window {
+            window.min_i64()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.min_i64()
+        }
+    }
+
+    fn min_f64(&self) -> f64 {
+        if let Some(window) = &self.window {
+            window.min_f64()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.min_f64()
+        }
+    }
+
+    fn max_i64(&self) -> i64 {
+        if let Some(window) = &self.window {
+            window.max_i64()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.max_i64()
+        }
+    }
+
+    fn max_f64(&self) -> f64 {
+        if let Some(window) = &self.window {
+            window.max_f64()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.max_f64()
+        }
+    }
+
+    fn precompute(&mut self) {
+        if let Some(window) = &mut self.window {
+            window.precompute();
+        } else {
+            let current = self.current();
+            let borrow  = hier_item_mut!(current);
+            let rustics = borrow.to_rustics_mut();
+
+            rustics.precompute();
+        }
+    }
+
+    /// Deletes all the statistical data in the Hier object.
+
+    fn clear(&mut self) {
+        self.clear_all();
+    }
+
+    fn supports_reset_extremes(&self) -> bool {
+        let current = self.current();
+        let borrow  = current.borrow();
+
+        borrow.to_rustics().supports_reset_extremes()
+    }
+
+    // Reset the extremes of every live member at every level, so that
+    // the sums made later don't bring back the old extremes.  A window,
+    // if any, keeps its extremes, since they cover only the window.
+
+    fn reset_extremes(&mut self) {
+        if !self.supports_reset_extremes() {
+            panic!("Hier::reset_extremes:  not supported");
+        }
+
+        for window in self.stats.iter() {
+            for member in window.iter_live() {
+                hier_item_mut!(member).to_rustics_mut().reset_extremes();
+            }
+        }
+    }
+
+    // Functions for printing
+
+    fn print(&self) {
+        if let Some(window) = &self.window {
+            window.print_opts(None, Some(&self.title));
+        } else {
+            let index = HierIndex::new(HierSet::Live, 0, self.live_len(0) - 1);
+
+            self.local_print(index, None, None);
+        }
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        if let Some(window) = &self.window {
+            window.print_opts(printer, Some(title));
+        } else {
+            let index = HierIndex::new(HierSet::Live, 0, self.live_len(0) - 1);
+
+            self.local_print(index, printer, Some(title));
+        }
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        if let Some(window) = &self.window {
+            window.log_histogram()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.log_histogram()
+        }
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        if let Some(window) = &self.window {
+            window.float_histogram()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.float_histogram()
+        }
+    }
+
+    // The title is kept in the Hier instance.
+
+    /// Sets the title used when printing.  The Hier implementation always
+    /// appends the set indices to the title when printing a member.
+
+    fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+
+        self.title_all();
+    }
+
+    // The labels belong to the Hier instance as a whole, so they
+    // survive advance() and apply to every level.
+
+    fn supports_record_hook(&self) -> bool {
+        true
+    }
+
+    fn set_record_hook(&mut self, hook: Option<RecordHookBox>) {
+        self.hook = hook;
+    }
+
+    fn record_hook(&self) -> Option<RecordHookBox> {
+        self.hook.clone()
+    }
+
+    fn set_label(&mut self, key: &str, value: &str) {
+        self.labels.insert(key.to_string(), value.to_string());
+    }
+
+    fn labels(&self) -> Labels {
+        self.labels.clone()
+    }
+
+    // For internal use.
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<Hier>(other.generic()) {
+            std::ptr::eq(self, other)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn export_stats(&self) -> ExportStats {
+        if let Some(window) = &self.window {
+            window.export_stats()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.export_stats()
+        }
+    }
+}
+
+// Returns the extremes from a summary as floating point values.
+
+fn extremes(stats: &Printable, ints: bool) -> (f64, f64) {
+    if ints {
+        (stats.min_i64 as f64, stats.max_i64 as f64)
+    } else {
+        (stats.min_f64, stats.max_f64)
+    }
+}
+
+// Checks whether two values differ by more than the given amount.
+
+fn differs(expected: f64, found: f64, limit: f64) -> bool {
+    if expected == found || (expected.is_nan() && found.is_nan()) {
+        return false;
+    }
+
+    let difference = (expected - found).abs();
+
+    difference > limit || difference.is_nan()
+}
+
+struct TitleAll {
+    title:  String,
+}
+
+impl TitleAll {
+    fn new(title: &str) -> TitleAll {
+        let title = title.to_string();
+
+        TitleAll { title }
+    }
+}
+
+impl HierTraverser for TitleAll {
+    fn visit(&mut self, member: &mut dyn Rustics) {
+        member.set_title(&self.title);
+    }
+}
+
+impl Histogram for Hier {
+    fn print_histogram(&self, printer: &mut dyn Printer) {
+        if let Some(log_histogram) = self.log_histogram() {
+            log_histogram.borrow().print(printer);
+        } else if let Some(float_histogram) = self.float_histogram() {
+            float_histogram.borrow().print(printer);
+        }
+    }
+
+    fn clear_histogram(&mut self) {
+        if let Some(log_histogram) = self.log_histogram() {
+            log_histogram.borrow_mut().clear();
+        } else if let Some(float_histogram) = self.float_histogram() {
+            float_histogram.borrow_mut().clear();
+        }
+    }
+
+    fn to_log_histogram(&self) -> Option<LogHistogramBox> {
+        if let Some(window) = &self.window {
+            window.log_histogram()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.log_histogram()
+        }
+    }
+
+    fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+        if let Some(window) = &self.window {
+            window.float_histogram()
+        } else {
+            let current = self.current();
+            let borrow  = current.borrow();
+            let rustics = borrow.to_rustics();
+
+            rustics.float_histogram()
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::tests::run_histogram_tests;
+    use crate::integer_hier::IntegerHier;
+    use crate::integer_hier::IntegerHierConfig;
+    use crate::running_integer::RunningInteger;
+    use crate::time_hier::TimeHier;
+    use crate::time_hier::TimeHierConfig;
+    use crate::running_time::RunningTime;
+    use crate::stdout_printer;
+    use crate::integer_hier::tests::make_test_hier;
+    use crate::tests::continuing_box;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_count_match;
+    use crate::tests::check_printer_counters;
+    use crate::HistoOpts;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
+    use crate::cardinality_hier::CardinalityHier;
+    use crate::cardinality_hier::CardinalityHierConfig;
+    use crate::float_hier::FloatHier;
+    use crate::float_hier::FloatHierConfig;
+    use crate::integer_window::IntegerWindow;
+    use crate::float_window::FloatWindow;
+    use crate::time_bounded_window::TimeBoundedWindow;
+
+    // Make a Hier instance for testing.  The tests use the RunningInteger
+    // implementation via IntegerHier.
+    //
+    // Make a 4-level hierarchical statistics struct for testing.
+
+    pub fn make_hier(level_0_period: usize, auto_next: usize) -> Hier {
+        let     levels      = 4;
+        let     dimension   = HierDimension::new(level_0_period, 3 * level_0_period);
+        let mut dimensions  = Vec::<HierDimension>::with_capacity(levels);
+
+        // Push the level 0 descriptor.
+
+        dimensions.push(dimension);
+
+        // Create a hierarchy.  Use a period of 4 level at level 1,
+        // and just add 2 as we go up.  This will keep all the
+        // periods distinct.  We force the total window size to
+        // 3 times the period, which is fine for testing.  That
+        // might become too costly in actual usage.
+
+        let mut period = 4;
+
+        for _i in 1..levels {
+            let dimension = HierDimension::new(period, 3 * period);
+
+            dimensions.push(dimension);
+
+            period += 2;
+        }
+
+        // Finish creating the Hier description instance.  This
+        // just describes the windows and when the Rustics
+        // instances are created and pushed.
+
+        let auto_next  = Some(auto_next as i64);
+        let descriptor = HierDescriptor::new(dimensions, auto_next);
+
+        // Now create the RunningInteger-based Hier instance via
+        // IntegerHier, which does some of the work for
+        // us.
+
+        let name        = "Hier Test Instance".to_string();
+        let print_opts  = None;
+        let window_size = None;
+
+        // Finally, create the configuration description for the
+        // constructor.
+
+        let configuration =
+            IntegerHierConfig { descriptor, name, window_size, print_opts };
+
+        // Make the actual Hier instance.  new_hier() handles the
+        // parameters specific for using RunningInteger instances.
+
+        IntegerHier::new_hier(configuration)
+    }
+
+    fn compute_events_per_entry(hier_integer: &Hier, level: usize) -> i64 {
+        let mut result = hier_integer.auto_next as i64;
+
+        assert!(result > 0);
+
+        for i in 0..level {
+            result *= hier_integer.dimensions[i].period as i64;
+        }
+
+        result
+    }
+
+    fn roundup(value: i64, multiple: i64) -> i64 {
+        (((value + multiple - 1) / multiple)) * multiple
+    }
+
+    fn compute_len(hier_integer: &Hier, level: usize, set: HierSet, events: i64) -> usize {
+        assert!(events > 0 || level == 0);
+
+        let recorded_events =
+            if level == 0 {
+                let auto_next = hier_integer.auto_next as i64;
+
+                roundup(events, auto_next)
+            } else {
+                events - 1
+            };
+
+        let     events_per_entry = compute_events_per_entry(&hier_integer, level);
+        let     pushes           = recorded_events / events_per_entry;
+        let     period           = hier_integer.dimensions[level].period as i64;
+        let     size_limit       = hier_integer.dimensions[level].retention as i64;
+
+        let mut length =
+            match set {
+                HierSet::Live => { std::cmp::min(pushes, period    ) }
+                HierSet::All  => { std::cmp::min(pushes, size_limit) }
+            };
+
+        if length == 0 && level == 0 {
+            length = 1;
+        }
+
+        length as usize
+    }
+
+    fn check_sizes(hier_integer: &Hier, events: i64, verbose: bool) {
+        for level in 0..hier_integer.stats.len() {
+            let expected_all_len  = compute_len(hier_integer, level, HierSet::All,  events);
+            let expected_live_len = compute_len(hier_integer, level, HierSet::Live, events);
+
+            let actual_all_len    = hier_integer.stats[level].all_len();
+            let actual_live_len   = hier_integer.stats[level].live_len();
+
+            if verbose {
+                println!("check_sizes:  at level {}, events {}", level, events);
+
+                println!("    live {} == {}",
+                    actual_live_len, expected_live_len);
+
+                println!("    all {} == {}",
+                    actual_all_len, expected_all_len);
+            }
+
+            assert!(actual_all_len  == expected_all_len );
+            assert!(actual_live_len == expected_live_len);
+        }
+    }
+
+    // This is a fairly straightforward test that just pushes a lot of
+    // values into a Hier instance.  It is long because it takes a fair
+    // number of operations to force higher-level instances into existence.
+
+    fn simple_hier_test() {
+        let     auto_next      = 4;
+        let     level_0_period = 4;
+        let     signed_auto    = auto_next as i64;
+        let mut events         = 0;
+        let mut sum_of_events  = 0;
+        let mut hier_integer   = make_hier(level_0_period, auto_next);
+        let     hier_integer_2 = make_hier(level_0_period, auto_next);
+
+        let expected_len = compute_len(&hier_integer, 0, HierSet::All,  events);
+        assert!(expected_len == 1);
+
+        // Do a quick sanity test on equals().
+
+        assert!( hier_integer.equals(&hier_integer));
+        assert!(!hier_integer.equals(&hier_integer_2));
+
+        // Check that the instance matches our expectations.
+
+        let live_len  = hier_integer.stats[0].live_len();
+        let all_len   = hier_integer.stats[0].all_len();
+        let period    = hier_integer.dimensions[0].period;
+
+        assert!(signed_auto == hier_integer.auto_next);
+        assert!(all_len     == 1                     );
+        assert!(live_len    == 1                     );
+        assert!(period      == level_0_period        );
+
+        let expected_count = auto_next as u64;
+
+        // Push one full window and see whether the data and
+        // structure matches what we expect as we record each
+        // event.
+
+        for i in 0..signed_auto {
+            hier_integer.record_i64(i);
+
+            events        += 1;
+            sum_of_events += i;
+
+            if i < signed_auto - 1 {
+                let mean  = sum_of_events as f64 / (i + 1) as f64;
+
+                let live_len_0 = hier_integer.stats[0].live_len();
+                let all_len_0  = hier_integer.stats[0].all_len();
+                let count      = (i + 1) as u64;
+
+                assert!(all_len_0              == 1    );
+                assert!(live_len_0             == 1    );
+                assert!(hier_integer.count()   == count);
+                assert!(hier_integer.min_i64() == 0    );
+                assert!(hier_integer.max_i64() == i    );
+                assert!(hier_integer.mean()    == mean );
+
+                check_sizes(&hier_integer, events, false);
+            }
+        }
+
+        let mean = sum_of_events as f64 / events as f64;
+
+        assert!(hier_integer.count() as i64 == signed_auto    );
+        assert!(hier_integer.min_i64()      == 0              );
+        assert!(hier_integer.max_i64()      == signed_auto - 1);
+        assert!(hier_integer.mean()         == mean           );
+
+        check_sizes(&hier_integer, events, true);
+        hier_integer.print();
+
+        assert!(hier_integer.count() == events as u64);
+
+        let mut sum = 0;
+
+        for i in 0..signed_auto {
+            let value = signed_auto + i;
+
+            hier_integer.record_i64(value);
+            sum           += value;
+            events        += 1;
+            sum_of_events += value;
+
+            check_sizes(&hier_integer, events, false);
+        }
+
+        assert!(hier_integer.stats[0].all_len() == 2);
+        hier_integer.print();
+
+        let floating_window = signed_auto as f64;
+        let expected_mean   = (sum as f64) / floating_window;
+
+        assert!(hier_integer.count()   == expected_count     );
+        assert!(hier_integer.min_i64() == signed_auto        );
+        assert!(hier_integer.max_i64() == 2 * signed_auto - 1);
+        assert!(hier_integer.mean()    == expected_mean      );
+
+        // Record two windows worth of events and check that our
+        // size expectations are correct.  Also, keep the sum of
+        // the last n events, where n is the window size, so that
+        // we can check the mean.  Only the last n events should
+        // be used to compute the mean.
+
+        let mut sum = 0;
+
+        for i in 0..2 * signed_auto {
+            let value = -i;
+
+            hier_integer.record_i64(value);
+
+            if i >= signed_auto {
+                sum += value;
+            }
+
+            events        += 1;
+            sum_of_events += value;
+
+            check_sizes(&hier_integer, events, false);
+        }
+
+        hier_integer.print();
+
+        let expected_mean = sum as f64 / floating_window;
+
+        assert!(hier_integer.count()   == expected_count        );
+        assert!(hier_integer.min_i64() == -(2 * signed_auto - 1));
+        assert!(hier_integer.max_i64() == -signed_auto          );
+        assert!(hier_integer.mean()    == expected_mean         );
+
+        // Now force a level 1 stat instance.
+
+        for i in 0..(auto_next * level_0_period) as i64 {
+            hier_integer.record_i64(i);
+            hier_integer.record_i64(-i);
+
+            events += 2;
+
+            check_sizes(&hier_integer, events, false);
+        }
+
+        // Check that we have at least one level 1 instance.  This
+        // is a test of the test itself, for the most part.
+
+        let expected_len = compute_len(&hier_integer, 1, HierSet::All,  events);
+        let actual_len   = hier_integer.stats[1].all_len();
+
+        assert!(expected_len > 0);
+        assert!(actual_len == expected_len);
+
+        // Now force a level 2 instance.
+
+        for i in 0..(auto_next * level_0_period / 2) as i64 {
+            hier_integer.record_i64(i);
+            events        += 1;
+            sum_of_events += i;
+
+            check_sizes(&hier_integer, events, false);
+        }
+
+        for i in 0..(auto_next * level_0_period / 2) as i64 {
+            hier_integer.record_i64(i);
+
+            events        += 1;
+            sum_of_events += i;
+
+            check_sizes(&hier_integer, events, false);
+        }
+
+        // Compute the expected mean once we force level 0 to
+        // be summed.
+
+        let expected_mean = sum_of_events as f64 / events as f64;
+
+        // Force the next push from level 0 by recording an event.
+        // This should produce a level 2 entry.
+
+        let value = 0;
+
+        hier_integer.record_i64(value);
+
+        events        += 1;
+        sum_of_events += value;
+
+        let expected_len = compute_len(&hier_integer, 2, HierSet::All, events);
+
+        assert!(expected_len == 1);
+
+        // Check the length.  Use a hardcode value, too, to check the
+        // sanity of the preceding code.
+
+        assert!(hier_integer.stats[2].all_len() == expected_len);
+
+        let stat_rc      = hier_integer.stats[2].newest().unwrap();
+        let stat_borrow  = stat_rc.borrow();
+        let stat         = stat_borrow.to_rustics();
+
+        // Do a sanity test on equals.
+
+        assert!(!hier_integer.equals(stat));
+
+        stat.print();
+
+        hier_integer.print_all(None, None);
+
+        assert!(stat.mean() == expected_mean);
+
+        println!("simple_hier_test:  {} events, sum {}", events, sum_of_events);
+    }
+
+    struct TestTraverser {
+        count:  i64,
+    }
+
+    impl TestTraverser {
+        pub fn new() -> TestTraverser {
+            let count = 0;
+
+            TestTraverser { count }
+        }
+    }
+
+    impl HierTraverser for TestTraverser {
+        fn visit(&mut self, _member: &mut dyn Rustics) {
+            self.count += 1;
+        }
+    }
+
+    // Shove enough events into the stat to get a level 3 entry.  Check that
+    // the count and the mean of the level 3 stat match our expectations.
+
+    fn long_test() {
+        let     auto_next      = 2;
+        let     level_0_period = 4;
+        let mut hier_integer   = make_hier(level_0_period, auto_next);
+        let mut events         = 0;
+
+        // Record data until there's a level 3 instance.
+
+        while hier_integer.stats[3].all_len()  == 0 {
+            events += 1;
+            hier_integer.record_i64(events);
+
+            check_sizes(&hier_integer, events, false);
+        }
+
+        // Now see that all the sizes, etc, match what we
+        // expect.
+
+        let     dimensions         = &hier_integer.dimensions;
+        let mut events_per_level_3 = auto_next;
+
+        for i in 0..dimensions.len() - 1 {
+            events_per_level_3 *= dimensions[i].period;
+        }
+
+        {
+            let stat_rc        = hier_integer.stats[3].newest().unwrap();
+            let stat_borrow    = stat_rc.borrow();
+            let stat           = stat_borrow.to_rustics();
+
+            let events_in_stat = (events - 1) as f64;
+            let sum            = (events_in_stat * (events_in_stat + 1.0)) / 2.0;
+            let mean           = sum / events_in_stat;
+
+            println!("long_test:  stats.mean() {}, expected {}", stat.mean(), mean);
+
+            assert!(stat.count() as i64 == events - 1               );
+            assert!(stat.count() as i64 == events_per_level_3 as i64);
+            assert!(stat.mean()         == mean                     );
+
+            hier_integer.print_all(None, None);
+        }
+
+        // Do a quick test of traverse_live().  It should see each
+        // "live" Rustics instance in the matrix.
+
+        let mut traverser = TestTraverser::new();
+
+        hier_integer.traverse_live(&mut traverser);
+
+        // Now compute how many members the traverser should have seen.
+
+        let mut predicted = 0;
+
+        for level in 0..hier_integer.dimensions.len() {
+            predicted += hier_integer.live_len(level) as i64;
+        }
+
+        println!("long_test:  traverse_live saw {} stats instances, predicted {}",
+            traverser.count, predicted);
+        assert!(traverser.count == predicted);
+
+        // Do a quick test of traverse_all().  It should see each
+        // instance in the matrix.
+
+        let mut traverser = TestTraverser::new();
+
+        hier_integer.traverse_all(&mut traverser);
+
+        // Now compute how many members the traverser should have seen.
+
+        let mut predicted = 0;
+
+        for level in 0..hier_integer.dimensions.len() {
+            predicted += hier_integer.all_len(level) as i64;
+        }
+
+        println!("long_test:  traverse_all saw {} stats instances, predicted {}",
+            traverser.count, predicted);
+        assert!(traverser.count == predicted);
+
+        // Do a sanity test on the members.
+
+        let member_opt    = hier_integer.stats[1].newest().unwrap();
+        let member_borrow = hier_item!(member_opt);
+        let member        = member_borrow.as_any().downcast_ref::<RunningInteger>();
+
+        // For test debugging.
+        //
+        //let proper_type =
+        //    match member {
+        //        Some(_) => { true  }
+        //        None    => { false }
+        //    };
+        //
+        //assert!(proper_type);
+
+        let rustics = member.unwrap().to_rustics();
+
+        println!("long_test:  got \"{}\" for class", rustics.class());
+
+        assert!(rustics.class() == "integer");
+    }
+
+    fn test_sanity() {
+        let printer      = stdout_printer();
+        let printer      = printer_mut!(printer);
+        let name         = "time_hier sanity test".to_string();
+        let timer        = continuing_box();
+        let print_opts   = None;
+
+        // Create the dimensions.
+
+        let dimension_0  = HierDimension::new(4, 4);
+        let dimension_1  = HierDimension::new(100, 200);
+
+        let dimensions   = vec![ dimension_0.clone(), dimension_1.clone() ];
+
+        let auto_next    = 20;
+        let auto_advance = Some(auto_next);
+        let descriptor   = HierDescriptor::new(dimensions, auto_advance);
+        let window_size  = None;
+
+        // Now make an actual time_hier instance from a configuration.
+
+        let configuration =
+            TimeHierConfig { descriptor, timer, name, window_size, print_opts };
+
+        let mut hier = TimeHier::new_hier(configuration);
+
+        // Now force a level 1 instance.
+
+        let mut events             = 0;
+        let     events_per_level_1 = auto_next * dimension_0.period() as i64;
+        let mut timer              = continuing_box();
+
+        for i in 0..2 * events_per_level_1 {
+            hier.record_time(i + 1);
+            hier.record_interval(&mut timer);
+
+            events += 2;
+        }
+
+        assert!(hier.event_count() == events);
+        hier.print();
+        hier.print_histogram(printer);
+
+        hier.clear_histogram();
+
+        let histogram = hier.to_log_histogram().unwrap();
+        let histogram = histogram.borrow();
+
+        for sample in histogram.positive.iter() {
+            assert!(*sample == 0);
+        }
+
+        // Do a sanity test on the members.
+
+        let member_opt    = hier.stats[1].newest().unwrap();
+        let member_borrow = hier_item!(member_opt);
+        let member        = member_borrow.as_any().downcast_ref::<RunningTime>();
+
+        // For test debugging.
+        //
+        //let proper_type =
+        //    match member {
+        //        Some(_) => { true  }
+        //        None    => { false }
+        //    };
+        //
+        //assert!(proper_type);
+
+        let rustics = member.unwrap().to_rustics();
+
+        assert!(rustics.count() == events_per_level_1 as u64);
+        assert!(rustics.class() == "time");
+
+        println!("test_sanity:  got \"{}\" for class", rustics.class());
+
+        let index   = HierIndex::new(HierSet::Live, 0, 0);
+        let printer = stdout_printer();
+        let title   = "Index Print Title";
+
+        hier.print_index_opts(index, Some(printer), Some(title));
+
+        let export = hier.export_stats();
+
+        assert!(export.printable.n as i64 == auto_next);
+    }
+
+    fn sample_usage() {
+        // Make a descriptor of the first level.  We have chosen to sum 1000
+        // level 0 RunningInteger instances into one level 1 RunningInteger
+        // instance.  This level is large, so we will keep only 1000 level 0
+        // instances in the window.
+
+        let dimension_0 = HierDimension::new(1000, 1000);
+
+        // At level 1, we want to sum 100 level 1 instances into one level 2
+        // instance.  This level is smaller, so let's retain 200
+        // RunningInteger instances here.
+
+        let dimension_1 = HierDimension::new(100, 200);
+
+        // Level two isn't summed, so the period isn't used.  Let's pretend
+        // this level isn't used much, so retain only 100 instances in it.
+
+        let dimension_2 = HierDimension::new(0, 100);
+
+        // Now create the Vec.  Save the dimension instances for future use.
+
+        let dimensions =
+            vec![
+                dimension_0.clone(), dimension_1.clone(), dimension_2.clone()
+            ];
+
+        // Now create the entire descriptor for the hier instance.  Let's
+        // record 2000 events into each level 0 RunningInteger instance.
+
+        let auto_advance = Some(2000);
+        let descriptor   = HierDescriptor::new(dimensions, auto_advance);
+
+        // Now create some items used by Hier to do printing.
+
+        let name        = "test hierarchical integer".to_string();
+        let print_opts  = None;
+        let window_size = None;
+
+        // Finally, create the configuration description for the
+        // constructor.
+
+        let configuration =
+            IntegerHierConfig { descriptor, name, window_size, print_opts };
+
+        // Now make the Hier instance.
+
+        let mut integer_hier = IntegerHier::new_hier(configuration);
+
+        // Now record some events with boring data.
+
+        let mut events   = 0;
+        let auto_advance = auto_advance.unwrap();
+
+        for i in  0..auto_advance {
+            events += 1;
+            integer_hier.record_i64(i + 10);
+        }
+
+        assert!( integer_hier.log_mode() == 11);
+        assert!( integer_hier.int_extremes());
+        assert!(!integer_hier.float_extremes());
+
+        // This is a no-op that shouldn't panic.
+
+        integer_hier.precompute();
+
+        // We have just completed the first level 0 instance, but
+        // the implementation creates the next instance only when
+        // it has data to record, so there should be only one level
+        // zero instance, and nothing at level 1 or level 2.
+
+        assert!(integer_hier.event_count() == events);
+        assert!(integer_hier.count()       == events as u64);
+        assert!(integer_hier.live_len(0)   == 1     );
+        assert!(integer_hier.live_len(1)   == 0     );
+        assert!(integer_hier.live_len(2)   == 0     );
+
+        // Now record some data to force the creation of
+        // the second level 1 instance.
+
+        events += 1;
+        integer_hier.record_i64(10);
+
+        // The new level 0 instance should have only one event
+        // recorded.  The Rustics implementation for Hier returns
+        // the data in the current level 0 instance, so check it.
+
+        assert!(integer_hier.event_count() == events);
+        assert!(integer_hier.count()       == 1     );
+        assert!(integer_hier.live_len(0)   == 2     );
+        assert!(integer_hier.live_len(1)   == 0     );
+        assert!(integer_hier.live_len(2)   == 0     );
+
+        let events_per_level_1 =
+            auto_advance * dimension_0.period() as i64;
+
+        for i in events..events_per_level_1 {
+            integer_hier.record_i64(i);
+            events += 1;
+        }
+
+        // Check the state again.  We need to record one more
+        // event to cause the summation at level 0 into level
+        // 1.
+
+        let expected_live  = dimension_0.period();
+        let expected_count = auto_advance as u64;
+
+        assert!(integer_hier.event_count() == events        );
+        assert!(integer_hier.count()       == expected_count);
+        assert!(integer_hier.live_len(0)   == expected_live );
+        assert!(integer_hier.live_len(1)   == 0             );
+        assert!(integer_hier.live_len(2)   == 0             );
+
+        integer_hier.record_i64(42);
+        events += 1;
+
+        assert!(integer_hier.live_len(1)   == 1     );
+        assert!(integer_hier.event_count() == events);
+
+        // Test the histograms while we have a Hier.
+
+        run_histogram_tests(&mut integer_hier as &mut dyn Rustics);
+
+        // Test clear_all with a window.
+
+        integer_hier.clear_all();
+
+        assert!(integer_hier.count()            == 0);
+        assert!(integer_hier.event_count()      == 0);
+        assert!(integer_hier.stats[0].all_len() == 1);
+        assert!(integer_hier.advance_count      == 0);
+
+        for level in 1..integer_hier.stats.len() {
+            assert!(integer_hier.stats[level].all_len() == 0);
+        }
+
+        // Record some events and check sizes.
+
+        for i in 1..=auto_advance {
+            integer_hier.record_i64(i as i64);
+        }
+
+        assert!(integer_hier.count()            == auto_advance as u64);
+        assert!(integer_hier.event_count()      == auto_advance as i64);
+        assert!(integer_hier.stats[0].all_len() == 1);
+        assert!(integer_hier.stats[1].all_len() == 0);
+
+        // Create one more level 0 Rustics instance.
+
+        for i in 1..=auto_advance {
+            integer_hier.record_i64(i as i64);
+        }
+
+        assert!(integer_hier.count()            == auto_advance as u64);
+        assert!(integer_hier.event_count()      == 2 * auto_advance as i64);
+        assert!(integer_hier.stats[0].all_len() == 2);
+        assert!(integer_hier.stats[1].all_len() == 0);
+
+        // Test printing with bad indices.
+
+        let expected =
+            [
+                "test hierarchical integer[5000].live[0]",
+                "  That level (5000) is invalid (3 levels configured).",
+                "test hierarchical integer[0].live[10000]",
+                "  That index (10000) is out of bounds."
+            ];
+
+        let check_printer = check_printer_box(&expected, true, false);
+
+        let index = HierIndex::new(HierSet::Live, 5000, 0);
+
+        integer_hier.print_index_opts(index, Some(check_printer.clone()), None);
+
+        let index = HierIndex::new(HierSet::Live, 0, 10000);
+
+        integer_hier.print_index_opts(index, Some(check_printer.clone()), None);
+
+        assert!(check_printer_count_match(check_printer));
+    }
+
+    fn test_sum() {
+        let     printer      = stdout_printer();
+        let     printer      = printer_mut!(printer);
+        let     window_size  = 200;
+        let mut integer_hier = make_test_hier(100, Some(200), None);
+
+        for i in 1..=window_size {
+            integer_hier.record_i64(i as i64);
+        }
+
+        integer_hier.print_opts(None, None);
+        integer_hier.print_histogram(printer);
+
+        let count = window_size as f64;
+        let sum   = (count * (count + 1.0)) / 2.0;
+        let mean  = sum / count;
+
+        integer_hier.print();
+
+        assert!(integer_hier.mean()     == mean);
+        assert!(integer_hier.variance() >   0.0);
+        assert!(integer_hier.skewness() ==  0.0);
+        assert!(integer_hier.log_mode() ==  8  );
+        assert!(integer_hier.kurtosis() == -1.2);
+
+        assert!(integer_hier.standard_deviation() >  0.0);
+
+        for i in 1..10000 {
+            integer_hier.record_i64(i as i64);
+        }
+
+        // Create a vector of indices.
+
+        let mut addends      = Vec::new();
+        let     level        = 0;
+        let     addend_count = 1000;
+
+        for i in 0..addend_count {
+            addends.push(HierIndex::new(HierSet::Live, level, i));
+        }
+
+        // Now compute the sum and print it.
+
+        let (sum, count) = integer_hier.sum(addends, "Level 0 Summary");
+        let sum          = sum.unwrap();
+
+        assert!(count > 0);
+        assert!(count < addend_count);
+        assert!(sum.borrow().to_rustics().mean() > 0.0);
+
+        let mut addends = Vec::new();
+
+        for i in 0..addend_count {
+            addends.push(HierIndex::new(HierSet::Live, 500, i));
+        }
+
+        let (_sum, count) = integer_hier.sum(addends, "Level 0 Summary");
+
+        assert!(count == 0);
+
+        integer_hier.clear_all();
+
+        assert!(integer_hier.count() == 0);
+
+        // Test title handling.
+
+        let new_title   = "Hier Test Title";
+        let start_title = integer_hier.title();
+
+        assert!(start_title != new_title);
+
+        integer_hier.set_title(new_title);
+
+        assert!(integer_hier.title() == new_title);
+
+        // This is a no-op that should not panic.
+
+        integer_hier.precompute();
+
+        // Check set_id() and id().
+
+        integer_hier.set_id(1);
+        assert!(integer_hier.id() == 1);
+
+        integer_hier.set_id(2);
+        assert!(integer_hier.id() == 2);
+
+        let export = integer_hier.export_stats();
+
+        assert!(export.printable.n == 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_retention() {
+        let _ = HierDimension::new(8, 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_auto_next() {
+        let _ = HierDescriptor::new(Vec::new(), Some(-2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_float_histogram() {
+        let hier = make_test_hier(100, None, None);
+        let _    = hier.float_histogram().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_float_histogram_window() {
+        let hier = make_test_hier(100, Some(100), None);
+        let _    = hier.float_histogram().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_short_dimensions() {
+        let dimensions   = Vec::new();
+        let auto_advance = Some(2000);
+        let descriptor   = HierDescriptor::new(dimensions, auto_advance);
+        let name         = "Test Hier".to_string();
+        let window_size  = None;
+        let print_opts   = None;
+
+        let config =
+            IntegerHierConfig { descriptor, name, window_size, print_opts };
+
+        let _hier = IntegerHier::new_hier(config);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_period() {
+        let dimension_0 = HierDimension::new(1000, 1000);
+        let dimension_1 = HierDimension::new(   0,  200);
+        let dimension_2 = HierDimension::new(   0,  200);
+
+        let dimensions =
+            vec![ dimension_0, dimension_1, dimension_2 ];
+
+        let auto_advance = Some(2000);
+        let descriptor   = HierDescriptor::new(dimensions, auto_advance);
+
+        let name         = "Test Hier".to_string();
+        let window_size  = None;
+        let print_opts   = None;
+
+        let config =
+            IntegerHierConfig { descriptor, name, window_size, print_opts };
+
+        let _hier = IntegerHier::new_hier(config);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_one_period() {
+        let dimension_0  = HierDimension::new(1000, 1000);
+        let dimension_1  = HierDimension::new(   1,  200);
+        let dimension_2  = HierDimension::new(   0,  200);
+
+        let dimensions =
+            vec![ dimension_0, dimension_1, dimension_2 ];
+
+        let auto_advance = Some(2000);
+        let descriptor   = HierDescriptor::new(dimensions, auto_advance);
+
+        let name         = "Test Hier".to_string();
+        let window_size  = None;
+        let print_opts   = None;
+
+        let config =
+            IntegerHierConfig { descriptor, name, window_size, print_opts };
+
+        let _hier = IntegerHier::new_hier(config);
+    }
+
+    // Check that per-level print options are applied to the members
+    // created at that level.
+
+    fn test_level_opts() {
+        let dimension_0 = HierDimension::new(2, 4);
+        let dimension_1 = HierDimension::new(0, 4);
+        let dimensions  = vec![ dimension_0, dimension_1 ];
+        let auto_next   = Some(2);
+
+        let mut descriptor = HierDescriptor::new(dimensions, auto_next);
+
+        let title      = "Level Test (summary)";
+        let printer    = check_printer_box(&[ title ], false, false);
+        let histo_opts = HistoOpts { no_histogram: true, ..Default::default() };
+
+        let level_printer = Some(printer.clone());
+        let level_title   = Some(" (summary)".to_string());
+        let units         = None;
+        let histo_opts    = Some(histo_opts);
+
+        let level_opts =
+            PrintOpts { printer: level_printer, title: level_title, units, histo_opts, ..Default::default() };
+
+        descriptor.set_level_opts(1, Some(level_opts));
+
+        let name        = "Level Test".to_string();
+        let print_opts  = None;
+        let window_size = None;
+
+        let configuration =
+            IntegerHierConfig { descriptor, name, window_size, print_opts };
+
+        let mut hier = IntegerHier::new_hier(configuration);
+
+        for _i in 0..5 {
+            hier.record_i64(8);
+        }
+
+        assert!(hier.all_len(0) == 3);
+        assert!(hier.all_len(1) == 1);
+
+        let level_0 = hier.index(HierIndex::new(HierSet::All, 0, 0)).unwrap();
+        let level_1 = hier.index(HierIndex::new(HierSet::All, 1, 0)).unwrap();
+
+        assert!(hier_item!(level_0).to_rustics().title() == "Level Test");
+        assert!(hier_item!(level_1).to_rustics().title() == title       );
+
+        // The level 1 member should print the title, the summary lines,
+        // and a blank line, but no histogram.
+
+        hier_item!(level_1).to_rustics().print();
+
+        let (lines, _) = check_printer_counters(printer.clone());
+
+        assert!(lines == 12);
+
+        // Check that set_title keeps the suffix.
+
+        hier.set_title("New Title");
+
+        assert!(hier_item!(level_0).to_rustics().title() == "New Title"          );
+        assert!(hier_item!(level_1).to_rustics().title() == "New Title (summary)");
+    }
+
+    // Check that the time-based queries use the configured clock.
+
+    fn test_clock() {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+
+        let dimensions     = vec![ HierDimension::new(2, 4), HierDimension::new(0, 4) ];
+        let mut descriptor = HierDescriptor::new(dimensions, None);
+
+        descriptor.set_clock(timer);
+
+        let name          = "Clock Test".to_string();
+        let print_opts    = None;
+        let window_size   = None;
+        let configuration = IntegerHierConfig { descriptor, name, window_size, print_opts };
+        let mut hier      = IntegerHier::new_hier(configuration);
+
+        assert!(hier.has_clock());
+        assert!(hier.clock_hz()    == Some(1_000_000_000));
+        assert!(hier.elapsed()     == Some(0));
+        assert!(hier.current_age() == Some(0));
+
+        clock.borrow_mut().current += 100;
+
+        assert!(hier.elapsed()     == Some(100));
+        assert!(hier.current_age() == Some(100));
+
+        // An advance resets the age but not the elapsed time.
+
+        hier.advance();
+
+        clock.borrow_mut().current += 50;
+
+        assert!(hier.elapsed()     == Some(150));
+        assert!(hier.current_age() == Some(50));
+
+        // Clearing the instance resets both.
+
+        hier.clear_all();
+
+        clock.borrow_mut().current += 10;
+
+        assert!(hier.elapsed()     == Some(10));
+        assert!(hier.current_age() == Some(10));
+
+        // Without a clock, the queries return None.
+
+        let mut hier = make_hier(4, 2);
+
+        assert!(!hier.has_clock());
+        assert!(hier.clock_hz()   .is_none());
+        assert!(hier.elapsed()    .is_none());
+        assert!(hier.current_age().is_none());
+
+        // Set a clock directly on the Hier instance.
+
+        let clock = Rc::new(RefCell::new(TestSimpleClock { current: 1000, increment }));
+        let timer = ClockTimer::new_box(clock.clone());
+
+        hier.set_clock(timer);
+
+        clock.borrow_mut().current += 25;
+
+        assert!(hier.elapsed()     == Some(25));
+        assert!(hier.current_age() == Some(25));
+    }
+
+    // Check time-based advancing.
+
+    fn test_advance_every() {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+
+        let dimensions     = vec![ HierDimension::new(4, 8), HierDimension::new(0, 4) ];
+        let mut descriptor = HierDescriptor::new(dimensions, None);
+
+        descriptor.set_clock(timer);
+        descriptor.set_advance_every(100);
+
+        let name          = "Advance Test".to_string();
+        let print_opts    = None;
+        let window_size   = None;
+        let configuration = IntegerHierConfig { descriptor, name, window_size, print_opts };
+        let mut hier      = IntegerHier::new_hier(configuration);
+
+        assert!(hier.advance_every() == Some(100));
+
+        // Heavy traffic in the first period doesn't cause an advance.
+
+        for i in 0..1000 {
+            hier.record_i64(i);
+        }
+
+        assert!(hier.advance_count() == 0   );
+        assert!(hier.count()         == 1000);
+
+        // Move to the middle of the second period.  The next sample
+        // starts a new instance.
+
+        clock.borrow_mut().current += 150;
+
+        hier.record_i64(1);
+
+        assert!(hier.advance_count() == 1 );
+        assert!(hier.count()         == 1 );
+        assert!(hier.current_age()   == Some(50));
+
+        // Skip three periods with no traffic.  The boundaries stay
+        // aligned to the interval.
+
+        clock.borrow_mut().current += 300;
+
+        assert!(hier.advance_if_due() == 3);
+        assert!(hier.advance_count()  == 4);
+        assert!(hier.count()          == 0);
+        assert!(hier.current_age()    == Some(50));
+        assert!(hier.all_len(1)       == 1);
+
+        let level_1 = hier.index(HierIndex::new(HierSet::All, 1, 0)).unwrap();
+        let level_1 = level_1.borrow();
+
+        assert!(level_1.to_rustics().count() == 1001);
+
+        assert!(hier.advance_if_due() == 0);
+
+        // After a long idle gap, the catch-up is limited to the level
+        // 0 retention, and the boundaries stay aligned.
+
+        let advance_count = hier.advance_count();
+
+        clock.borrow_mut().current += 86_400 * 100 + 20;
+
+        hier.record_i64(7);
+
+        assert!(hier.advance_count() == advance_count + 8);
+        assert!(hier.count()         == 1 );
+        assert!(hier.current_age()   == Some(70));
+        assert!(hier.all_len(0)      == 8 );
+
+        // Without the option, nothing happens.
+
+        let mut hier = make_hier(4, 2);
+
+        assert!(hier.advance_every()  .is_none());
+        assert!(hier.advance_if_due() == 0);
+    }
+
+    fn test_percentile_at() {
+        let mut hier = make_hier(2, 0);
+
+        assert!(hier.percentile_at(0, 0, 50.0) == Some(0.0));
+        assert!(hier.percentile_at(0, 1, 50.0).is_none());
+        assert!(hier.percentile_at(9, 0, 50.0).is_none());
+
+        for i in 1..=100 {
+            hier.record_i64(i);
+        }
+
+        hier.advance();
+
+        for _i in 1..=100 {
+            hier.record_i64(1000);
+        }
+
+        hier.advance();
+
+        // The percentiles are the upper bounds of the histogram
+        // buckets.
+
+        assert!(hier.all_len(1) == 1);
+        assert!(hier.percentile_at(0, 0, 50.0) == Some(64.0  ));
+        assert!(hier.percentile_at(0, 0, 99.0) == Some(128.0 ));
+        assert!(hier.percentile_at(0, 1, 50.0) == Some(1024.0));
+
+        // The level 1 member has the samples from both periods.
 
-            rustics.export_stats()
+        assert!(hier.percentile_at(1, 0, 25.0) == Some(64.0  ));
+        assert!(hier.percentile_at(1, 0, 75.0) == Some(1024.0));
+        assert!(hier.percentile_at(1, 1, 50.0).is_none());
+
+        // The float hierarchies work, too.
+
+        let dimensions    = vec![ HierDimension::new(2, 4), HierDimension::new(0, 4) ];
+        let descriptor    = HierDescriptor::new(dimensions, None);
+        let name          = "Float Hier".to_string();
+        let window_size   = None;
+        let print_opts    = None;
+        let configuration = FloatHierConfig { descriptor, name, window_size, print_opts };
+        let mut hier      = FloatHier::new_hier(configuration);
+
+        for i in 1..=100 {
+            hier.record_f64(i as f64);
         }
+
+        hier.advance();
+        hier.advance();
+
+        assert!(hier.percentile_at(1, 0, 50.0) == hier.percentile_at(0, 0, 50.0));
+        assert!(hier.percentile_at(1, 0, 50.0).unwrap() >= 50.0);
     }
-}
 
-struct TitleAll {
-    title:  String,
-}
+    // Check the trait methods that Hier forwards to the current member
+    // or the window.
 
-impl TitleAll {
-    fn new(title: &str) -> TitleAll {
-        let title = title.to_string();
+    fn test_forwarding() {
+        let dimensions    = vec![ HierDimension::new(2, 4), HierDimension::new(0, 4) ];
+        let descriptor    = HierDescriptor::new(dimensions, None);
+        let name          = "Float Hier".to_string();
+        let window_size   = Some(10);
+        let print_opts    = None;
+        let configuration = FloatHierConfig { descriptor, name, window_size, print_opts };
+        let mut hier      = FloatHier::new_hier(configuration);
 
-        TitleAll { title }
-    }
-}
+        hier.record_f32(1.5);
+        hier.record_f32(2.5);
 
-impl HierTraverser for TitleAll {
-    fn visit(&mut self, member: &mut dyn Rustics) {
-        member.set_title(&self.title);
-    }
-}
+        assert!(hier.count() == 2  );
+        assert!(hier.mean()  == 2.0);
 
-impl Histogram for Hier {
-    fn print_histogram(&self, printer: &mut dyn Printer) {
-        if let Some(log_histogram) = self.log_histogram() {
-            log_histogram.borrow().print(printer);
-        } else if let Some(float_histogram) = self.float_histogram() {
-            float_histogram.borrow().print(printer);
-        }
-    }
+        let current = hier.current();
+        let current = current.borrow();
 
-    fn clear_histogram(&mut self) {
-        if let Some(log_histogram) = self.log_histogram() {
-            log_histogram.borrow_mut().clear();
-        } else if let Some(float_histogram) = self.float_histogram() {
-            float_histogram.borrow_mut().clear();
-        }
-    }
+        assert!(current.to_rustics().count() == 2);
 
-    fn to_log_histogram(&self) -> Option<LogHistogramBox> {
-        if let Some(window) = &self.window {
-            window.log_histogram()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+        // The generated members don't keep a median.
 
-            rustics.log_histogram()
-        }
+        assert!(!hier.has_median());
     }
 
-    fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
-        if let Some(window) = &self.window {
-            window.float_histogram()
-        } else {
-            let current = self.current();
-            let borrow  = current.borrow();
-            let rustics = borrow.to_rustics();
+    fn test_reset_extremes() {
+        // Use a level 0 period of 4, and no automatic advance.
 
-            rustics.float_histogram()
+        let mut hier = make_hier(4, 0);
+
+        assert!(hier.supports_reset_extremes());
+
+        hier.record_i64(1_000_000);
+        hier.record_i64(-1_000_000);
+        hier.advance();
+
+        for i in 1..=10 {
+            hier.record_i64(i);
         }
+
+        // The extremes restart in the live members, so the next sum
+        // doesn't bring back the old values.
+
+        hier.reset_extremes();
+
+        assert!(hier.count() == 10);
+
+        hier.record_i64(5);
+        hier.record_i64(7);
+
+        assert!(hier.min_i64() == 5);
+        assert!(hier.max_i64() == 7);
+
+        hier.advance();
+
+        let oldest = hier.index(HierIndex::new(HierSet::Live, 0, 0)).unwrap();
+        let oldest = oldest.borrow();
+
+        assert!(oldest.to_rustics().max_i64() < 1_000_000);
+
+        // The window types don't support the method.
+
+        let window = IntegerWindow::new("Window", 10, &None);
+
+        assert!(!window.supports_reset_extremes());
     }
-}
 
-#[cfg(test)]
-pub mod tests {
-    use super::*;
-    use crate::tests::run_histogram_tests;
-    use crate::integer_hier::IntegerHier;
-    use crate::integer_hier::IntegerHierConfig;
-    use crate::running_integer::RunningInteger;
-    use crate::time_hier::TimeHier;
-    use crate::time_hier::TimeHierConfig;
-    use crate::running_time::RunningTime;
-    use crate::stdout_printer;
-    use crate::integer_hier::tests::make_test_hier;
-    use crate::tests::continuing_box;
-    use crate::tests::check_printer_box;
-    use crate::tests::check_printer_count_match;
+    fn test_member_state() {
+        // The periods are 2, 4, 6, and 8, so the top level, level 3,
+        // has a live set of 8 members.
 
-    // Make a Hier instance for testing.  The tests use the RunningInteger
-    // implementation via IntegerHier.
-    //
-    // Make a 4-level hierarchical statistics struct for testing.
+        let mut hier = make_hier(2, 0);
 
-    pub fn make_hier(level_0_period: usize, auto_next: usize) -> Hier {
-        let     levels      = 4;
-        let     dimension   = HierDimension::new(level_0_period, 3 * level_0_period);
-        let mut dimensions  = Vec::<HierDimension>::with_capacity(levels);
+        let state = |hier: &Hier, set, level, which| {
+            hier.member_state(HierIndex::new(set, level, which))
+        };
 
-        // Push the level 0 descriptor.
+        assert!(state(&hier, HierSet::All, 0, 0) == Some(HierMemberState::Live));
+        assert!(state(&hier, HierSet::All, 0, 1).is_none());
+        assert!(state(&hier, HierSet::All, 9, 0).is_none());
 
-        dimensions.push(dimension);
+        for _i in 0..3 {
+            hier.advance();
+        }
 
-        // Create a hierarchy.  Use a period of 4 level at level 1,
-        // and just add 2 as we go up.  This will keep all the
-        // periods distinct.  We force the total window size to
-        // 3 times the period, which is fine for testing.  That
-        // might become too costly in actual usage.
+        // Periods 0 and 1 were summarized into level 1.  Period 2 is
+        // complete but still live, and period 3 is the current one.
 
-        let mut period = 4;
+        assert!(hier.all_len(0) == 4);
+        assert!(state(&hier, HierSet::All,  0, 0) == Some(HierMemberState::Summarized));
+        assert!(state(&hier, HierSet::All,  0, 1) == Some(HierMemberState::Summarized));
+        assert!(state(&hier, HierSet::All,  0, 2) == Some(HierMemberState::Live      ));
+        assert!(state(&hier, HierSet::All,  0, 3) == Some(HierMemberState::Live      ));
+        assert!(state(&hier, HierSet::Live, 0, 1) == Some(HierMemberState::Live      ));
+        assert!(state(&hier, HierSet::All,  1, 0) == Some(HierMemberState::Live      ));
 
-        for _i in 1..levels {
-            let dimension = HierDimension::new(period, 3 * period);
+        let age = |hier: &Hier, level, which| {
+            hier.member_age(HierIndex::new(HierSet::All, level, which))
+        };
 
-            dimensions.push(dimension);
+        assert!(age(&hier, 0, 0) == Some(3));
+        assert!(age(&hier, 0, 3) == Some(0));
+        assert!(age(&hier, 1, 0) == Some(3));
+        assert!(age(&hier, 0, 9).is_none());
 
-            period += 2;
+        // Level 3 gets a member every 48 advances.  Push 10 of them,
+        // so that 2 of them have left the live set.
+
+        for _i in 3..480 {
+            hier.advance();
         }
 
-        // Finish creating the Hier description instance.  This
-        // just describes the windows and when the Rustics
-        // instances are created and pushed.
+        assert!(hier.all_len(3) == 10);
+        assert!(state(&hier, HierSet::All, 3, 0) == Some(HierMemberState::Retired));
+        assert!(state(&hier, HierSet::All, 3, 1) == Some(HierMemberState::Retired));
+        assert!(state(&hier, HierSet::All, 3, 2) == Some(HierMemberState::Live   ));
+        assert!(state(&hier, HierSet::All, 3, 9) == Some(HierMemberState::Live   ));
 
-        let auto_next  = Some(auto_next as i64);
-        let descriptor = HierDescriptor::new(dimensions, auto_next);
+        // Everything below the top level has been summarized, except
+        // the current level 0 member.
 
-        // Now create the RunningInteger-based Hier instance via
-        // IntegerHier, which does some of the work for
-        // us.
+        for level in 1..3 {
+            for i in 0..hier.all_len(level) {
+                assert!(state(&hier, HierSet::All, level, i) == Some(HierMemberState::Summarized));
+            }
+        }
 
-        let name        = "Hier Test Instance".to_string();
-        let print_opts  = None;
-        let window_size = None;
+        let newest = hier.all_len(0) - 1;
 
-        // Finally, create the configuration description for the
-        // constructor.
+        assert!(state(&hier, HierSet::All, 0, newest - 1) == Some(HierMemberState::Summarized));
+        assert!(state(&hier, HierSet::All, 0, newest    ) == Some(HierMemberState::Live      ));
 
-        let configuration =
-            IntegerHierConfig { descriptor, name, window_size, print_opts };
+        // Check that the members that haven't been summarized cover
+        // every sample exactly once.
 
-        // Make the actual Hier instance.  new_hier() handles the
-        // parameters specific for using RunningInteger instances.
+        let mut hier = make_hier(2, 0);
+        let mut sum  = 0;
 
-        IntegerHier::new_hier(configuration)
-    }
+        for i in 0..37 {
+            hier.record_i64(1);
+            hier.record_i64(i);
+            hier.advance();
+        }
 
-    fn compute_events_per_entry(hier_integer: &Hier, level: usize) -> i64 {
-        let mut result = hier_integer.auto_next as i64;
+        for level in 0..4 {
+            for i in 0..hier.all_len(level) {
+                let index = HierIndex::new(HierSet::All, level, i);
+                let state = hier.member_state(index).unwrap();
 
-        assert!(result > 0);
+                if state != HierMemberState::Summarized {
+                    let member = hier.index(index).unwrap();
+                    let member = member.borrow();
 
-        for i in 0..level {
-            result *= hier_integer.dimensions[i].period as i64;
+                    sum += member.to_rustics().count();
+                }
+            }
         }
 
-        result
+        assert!(sum == 74);
     }
 
-    fn roundup(value: i64, multiple: i64) -> i64 {
-        (((value + multiple - 1) / multiple)) * multiple
+    #[test]
+    #[should_panic]
+    fn test_level_opts_range() {
+        let dimensions     = vec![ HierDimension::new(2, 4) ];
+        let mut descriptor = HierDescriptor::new(dimensions, None);
+
+        descriptor.set_level_opts(1, None);
     }
 
-    fn compute_len(hier_integer: &Hier, level: usize, set: HierSet, events: i64) -> usize {
-        assert!(events > 0 || level == 0);
+    fn test_periods() {
+        let     level_0_period = 2;
+        let     auto_next      = 3;
+        let mut hier           = make_hier(level_0_period, auto_next);
+        let     auto_next      = auto_next as i64;
 
-        let recorded_events =
-            if level == 0 {
-                let auto_next = hier_integer.auto_next as i64;
+        assert!(hier.advance_count()         == 0);
+        assert!(hier.samples_until_advance() == Some(auto_next));
+        assert!(hier.period_span(0)          == 1);
+        assert!(hier.period_span(1)          == 2);
+        assert!(hier.period_span(2)          == 8);
+        assert!(hier.pushed_count(0)         == 1);
+        assert!(hier.pushed_count(1)         == 0);
 
-                roundup(events, auto_next)
-            } else {
-                events - 1
-            };
+        let index = HierIndex::new(HierSet::All, 0, 0);
 
-        let     events_per_entry = compute_events_per_entry(&hier_integer, level);
-        let     pushes           = recorded_events / events_per_entry;
-        let     period           = hier_integer.dimensions[level].period as i64;
-        let     size_limit       = hier_integer.dimensions[level].retention as i64;
+        assert!(hier.period_index(index) == Some(0));
 
-        let mut length =
-            match set {
-                HierSet::Live => { std::cmp::min(pushes, period    ) }
-                HierSet::All  => { std::cmp::min(pushes, size_limit) }
-            };
+        // Record samples and watch the count fall.
 
-        if length == 0 && level == 0 {
-            length = 1;
+        for i in 1..=auto_next {
+            hier.record_i64(i);
+            assert!(hier.samples_until_advance() == Some(auto_next - i));
+            assert!(hier.advance_count()         == 0);
         }
 
-        length as usize
-    }
+        // The next sample forces an advance.
 
-    fn check_sizes(hier_integer: &Hier, events: i64, verbose: bool) {
-        for level in 0..hier_integer.stats.len() {
-            let expected_all_len  = compute_len(hier_integer, level, HierSet::All,  events);
-            let expected_live_len = compute_len(hier_integer, level, HierSet::Live, events);
+        hier.record_i64(1);
 
-            let actual_all_len    = hier_integer.stats[level].all_len();
-            let actual_live_len   = hier_integer.stats[level].live_len();
+        assert!(hier.advance_count()         == 1);
+        assert!(hier.samples_until_advance() == Some(auto_next - 1));
 
-            if verbose {
-                println!("check_sizes:  at level {}, events {}", level, events);
+        // Push enough level 0 instances to overflow the level 0 window,
+        // which retains 6 periods.  Use a Hier instance without auto_next
+        // so that the samples don't cause advances.
 
-                println!("    live {} == {}",
-                    actual_live_len, expected_live_len);
+        let mut hier     = make_hier(level_0_period, 0);
+        let     advances = 20;
 
-                println!("    all {} == {}",
-                    actual_all_len, expected_all_len);
-            }
+        assert!(hier.samples_until_advance().is_none());
 
-            assert!(actual_all_len  == expected_all_len );
-            assert!(actual_live_len == expected_live_len);
+        for i in 0..advances {
+            hier.record_i64(i);
+            hier.advance();
         }
-    }
 
-    // This is a fairly straightforward test that just pushes a lot of
-    // values into a Hier instance.  It is long because it takes a fair
-    // number of operations to force higher-level instances into existence.
+        assert!(hier.advance_count() == advances);
+        assert!(hier.pushed_count(0) == advances + 1);
+        assert!(hier.pushed_count(1) == advances / 2);
+        assert!(hier.pushed_count(2) == advances / 8);
 
-    fn simple_hier_test() {
-        let     auto_next      = 4;
-        let     level_0_period = 4;
-        let     signed_auto    = auto_next as i64;
-        let mut events         = 0;
-        let mut sum_of_events  = 0;
-        let mut hier_integer   = make_hier(level_0_period, auto_next);
-        let     hier_integer_2 = make_hier(level_0_period, auto_next);
+        // Check the level 0 indices.  The oldest retained member is
+        // first.
 
-        let expected_len = compute_len(&hier_integer, 0, HierSet::All,  events);
-        assert!(expected_len == 1);
+        let all_len = hier.all_len(0) as i64;
+        let first   = HierIndex::new(HierSet::All,  0, 0);
+        let newest  = HierIndex::new(HierSet::All,  0, all_len as usize - 1);
+        let live    = HierIndex::new(HierSet::Live, 0, hier.live_len(0) - 1);
+        let missing = HierIndex::new(HierSet::All,  0, all_len as usize);
 
-        // Do a quick sanity test on equals().
+        assert!(hier.period_index(first  ) == Some(advances + 1 - all_len));
+        assert!(hier.period_index(newest ) == Some(advances));
+        assert!(hier.period_index(live   ) == Some(advances));
+        assert!(hier.period_index(missing).is_none());
 
-        assert!( hier_integer.equals(&hier_integer));
-        assert!(!hier_integer.equals(&hier_integer_2));
+        // Now check level 1.
 
-        // Check that the instance matches our expectations.
+        let level_1 = HierIndex::new(HierSet::All, 1, 0);
+        let expect  = advances / 2 - hier.all_len(1) as i64;
 
-        let live_len  = hier_integer.stats[0].live_len();
-        let all_len   = hier_integer.stats[0].all_len();
-        let period    = hier_integer.dimensions[0].period;
+        assert!(hier.period_index(level_1) == Some(expect));
 
-        assert!(signed_auto == hier_integer.auto_next);
-        assert!(all_len     == 1                     );
-        assert!(live_len    == 1                     );
-        assert!(period      == level_0_period        );
+        let bad_level = HierIndex::new(HierSet::All, 4, 0);
 
-        let expected_count = auto_next as u64;
+        assert!(hier.period_index(bad_level).is_none());
 
-        // Push one full window and see whether the data and
-        // structure matches what we expect as we record each
-        // event.
+        // A clear resets the counts.
 
-        for i in 0..signed_auto {
-            hier_integer.record_i64(i);
+        hier.clear_all();
 
-            events        += 1;
-            sum_of_events += i;
+        assert!(hier.advance_count() == 0);
+        assert!(hier.pushed_count(0) == 1);
+    }
 
-            if i < signed_auto - 1 {
-                let mean  = sum_of_events as f64 / (i + 1) as f64;
+    fn test_save_load() {
+        let mut hier = make_test_hier(100, Some(50), None);
 
-                let live_len_0 = hier_integer.stats[0].live_len();
-                let all_len_0  = hier_integer.stats[0].all_len();
-                let count      = (i + 1) as u64;
+        for i in 0..12_345 {
+            hier.record_i64(i % 1000);
+        }
 
-                assert!(all_len_0              == 1    );
-                assert!(live_len_0             == 1    );
-                assert!(hier_integer.count()   == count);
-                assert!(hier_integer.min_i64() == 0    );
-                assert!(hier_integer.max_i64() == i    );
-                assert!(hier_integer.mean()    == mean );
+        let mut saved = Vec::new();
 
-                check_sizes(&hier_integer, events, false);
-            }
-        }
+        hier.save(&mut saved).unwrap();
 
-        let mean = sum_of_events as f64 / events as f64;
+        let mut restored = make_test_hier(100, Some(50), None);
 
-        assert!(hier_integer.count() as i64 == signed_auto    );
-        assert!(hier_integer.min_i64()      == 0              );
-        assert!(hier_integer.max_i64()      == signed_auto - 1);
-        assert!(hier_integer.mean()         == mean           );
+        restored.load(&mut saved.as_slice()).unwrap();
 
-        check_sizes(&hier_integer, events, true);
-        hier_integer.print();
+        assert!(restored.advance_count() == hier.advance_count());
+        assert!(restored.event_count()   == hier.event_count()  );
+        assert!(restored.count()         == hier.count()        );
+        assert!(restored.mean()          == hier.mean()         );
 
-        assert!(hier_integer.count() == events as u64);
+        for level in 0..hier.stats.len() {
+            assert!(restored.all_len (level) == hier.all_len (level));
+            assert!(restored.live_len(level) == hier.live_len(level));
 
-        let mut sum = 0;
+            for which in 0..hier.all_len(level) {
+                let index    = HierIndex::new(HierSet::All, level, which);
+                let expected = hier.index(index).unwrap();
+                let member   = restored.index(index).unwrap();
+                let expected = expected.borrow();
+                let member   = member.borrow();
+                let expected = expected.to_rustics();
+                let member   = member.to_rustics();
 
-        for i in 0..signed_auto {
-            let value = signed_auto + i;
+                assert!(member.count()   == expected.count()  );
+                assert!(member.mean()    == expected.mean()   );
+                assert!(member.max_i64() == expected.max_i64());
+            }
+        }
 
-            hier_integer.record_i64(value);
-            sum           += value;
-            events        += 1;
-            sum_of_events += value;
+        // Both instances should continue in step.
 
-            check_sizes(&hier_integer, events, false);
+        for i in 0..1000 {
+            hier    .record_i64(i);
+            restored.record_i64(i);
         }
 
-        assert!(hier_integer.stats[0].all_len() == 2);
-        hier_integer.print();
+        assert!(restored.advance_count() == hier.advance_count());
+        assert!(restored.all_len(1)      == hier.all_len(1)     );
+        assert!(restored.mean()          == hier.mean()         );
 
-        let floating_window = signed_auto as f64;
-        let expected_mean   = (sum as f64) / floating_window;
+        // A hierarchy with another configuration can't take the data.
 
-        assert!(hier_integer.count()   == expected_count     );
-        assert!(hier_integer.min_i64() == signed_auto        );
-        assert!(hier_integer.max_i64() == 2 * signed_auto - 1);
-        assert!(hier_integer.mean()    == expected_mean      );
+        let mut other  = make_test_hier(100, None, None);
+        let     before = other.event_count();
+        let     result = other.load(&mut saved.as_slice());
 
-        // Record two windows worth of events and check that our
-        // size expectations are correct.  Also, keep the sum of
-        // the last n events, where n is the window size, so that
-        // we can check the mean.  Only the last n events should
-        // be used to compute the mean.
+        assert!(result.unwrap_err().kind() == ErrorKind::InvalidData);
+        assert!(other.event_count() == before);
 
-        let mut sum = 0;
+        let mut other  = make_hier(4, 100);
+        let     result = other.load(&mut "rustics hier\n".as_bytes());
 
-        for i in 0..2 * signed_auto {
-            let value = -i;
+        assert!(result.is_err());
 
-            hier_integer.record_i64(value);
+        // Cardinality members don't support checkpoints.
 
-            if i >= signed_auto {
-                sum += value;
-            }
+        let     dimensions  = vec![ HierDimension::new(4, 4), HierDimension::new(0, 4) ];
+        let     descriptor  = HierDescriptor::new(dimensions, None);
+        let     name        = "Cardinality Save".to_string();
+        let     print_opts  = None;
+        let     config      = CardinalityHierConfig { descriptor, name, print_opts };
+        let     cardinality = CardinalityHier::new_hier(config);
+        let mut saved       = Vec::new();
+        let     result      = cardinality.save(&mut saved);
 
-            events        += 1;
-            sum_of_events += value;
+        assert!(result.unwrap_err().kind() == ErrorKind::Unsupported);
+    }
 
-            check_sizes(&hier_integer, events, false);
-        }
+    fn test_summarize() {
+        let mut hier = make_hier(4, 10);
 
-        hier_integer.print();
+        // Record 10 samples with the same value into each level 0
+        // instance.  Instance i gets the value i.
 
-        let expected_mean = sum as f64 / floating_window;
+        for i in 0..30 {
+            for _j in 0..10 {
+                hier.record_i64(i);
+            }
+        }
 
-        assert!(hier_integer.count()   == expected_count        );
-        assert!(hier_integer.min_i64() == -(2 * signed_auto - 1));
-        assert!(hier_integer.max_i64() == -signed_auto          );
-        assert!(hier_integer.mean()    == expected_mean         );
+        assert!(hier.all_len(0) == 12);
 
-        // Now force a level 1 stat instance.
+        // The oldest retained level 0 instance holds the value 18.
 
-        for i in 0..(auto_next * level_0_period) as i64 {
-            hier_integer.record_i64(i);
-            hier_integer.record_i64(-i);
+        let sum = hier.summarize_range(0, 0, 2).unwrap();
+        let sum = sum.borrow();
 
-            events += 2;
+        assert!(sum.to_rustics().count()   == 20);
+        assert!(sum.to_rustics().min_i64() == 18);
+        assert!(sum.to_rustics().max_i64() == 19);
 
-            check_sizes(&hier_integer, events, false);
-        }
+        // Check the newest members.
 
-        // Check that we have at least one level 1 instance.  This
-        // is a test of the test itself, for the most part.
+        let last = hier.summarize_last(3).unwrap();
+        let last = last.borrow();
 
-        let expected_len = compute_len(&hier_integer, 1, HierSet::All,  events);
-        let actual_len   = hier_integer.stats[1].all_len();
+        assert!(last.to_rustics().count()   == 30);
+        assert!(last.to_rustics().min_i64() == 27);
+        assert!(last.to_rustics().mean()    == 28.0);
 
-        assert!(expected_len > 0);
-        assert!(actual_len == expected_len);
+        // Ranges are clipped.
 
-        // Now force a level 2 instance.
+        let all = hier.summarize_last(1000).unwrap();
 
-        for i in 0..(auto_next * level_0_period / 2) as i64 {
-            hier_integer.record_i64(i);
-            events        += 1;
-            sum_of_events += i;
+        assert!(hier_item!(all).to_rustics().count() == 120);
 
-            check_sizes(&hier_integer, events, false);
-        }
+        let all = hier.summarize_range(1, 0, 1000).unwrap();
 
-        for i in 0..(auto_next * level_0_period / 2) as i64 {
-            hier_integer.record_i64(i);
+        assert!(hier_item!(all).to_rustics().count() == 280);
 
-            events        += 1;
-            sum_of_events += i;
+        assert!(hier.summarize_last(0)           .is_none());
+        assert!(hier.summarize_range(0, 5, 5)    .is_none());
+        assert!(hier.summarize_range(0, 100, 200).is_none());
+        assert!(hier.summarize_range(9, 0, 1)    .is_none());
+    }
 
-            check_sizes(&hier_integer, events, false);
-        }
+    // Check change detection using both a callback and the default
+    // warning.
 
-        // Compute the expected mean once we force level 0 to
-        // be summed.
+    fn test_change_detection() {
+        let mut hier    = make_hier(4, 10);
+        let     reports = Rc::new(RefCell::new(Vec::<ChangeReport>::new()));
+        let     saved   = reports.clone();
 
-        let expected_mean = sum_of_events as f64 / events as f64;
+        assert!(hier.changes_detected().is_none());
 
-        // Force the next push from level 0 by recording an event.
-        // This should produce a level 2 entry.
+        let thresholds = ChangeThresholds { mean_percent: 20.0, count_percent: f64::INFINITY };
+        let callback   = Box::new(move |report: &ChangeReport| saved.borrow_mut().push(report.clone()));
 
-        let value = 0;
+        hier.set_change_detection(thresholds, Some(callback));
 
-        hier_integer.record_i64(value);
+        // Periods 0 through 2 have a mean of 100, period 3 has a mean
+        // of 150, and period 4 has a mean of 160.  Only the change from
+        // period 2 to period 3 exceeds the threshold.
 
-        events        += 1;
-        sum_of_events += value;
+        for mean in [ 100, 100, 100, 150, 160, 160 ] {
+            for _i in 0..10 {
+                hier.record_i64(mean);
+            }
+        }
 
-        let expected_len = compute_len(&hier_integer, 2, HierSet::All, events);
+        assert!(hier.changes_detected() == Some(1));
 
-        assert!(expected_len == 1);
+        let reports = reports.borrow();
+        let report  = &reports[0];
 
-        // Check the length.  Use a hardcode value, too, to check the
-        // sanity of the preceding code.
+        assert!(reports.len()         == 1    );
+        assert!(report.period         == 3    );
+        assert!(report.previous_mean  == 100.0);
+        assert!(report.current_mean   == 150.0);
+        assert!(report.mean_change    == 50.0 );
+        assert!(report.count_change   == 0.0  );
+        assert!(report.current_count  == 10   );
+        assert!(report.mean_exceeded          );
+        assert!(!report.count_exceeded        );
 
-        assert!(hier_integer.stats[2].all_len() == expected_len);
+        // Now check the printed warning.  A drop in the traffic
+        // exceeds the count threshold.
 
-        let stat_rc      = hier_integer.stats[2].newest().unwrap();
-        let stat_borrow  = stat_rc.borrow();
-        let stat         = stat_borrow.to_rustics();
+        let expected =
+            [
+                "Change Test:  Change detected in period 1",
+                "    Count   -50.0%  (10 to 5)"
+            ];
 
-        // Do a sanity test on equals.
+        let dimensions   = vec![ HierDimension::new(4, 8), HierDimension::new(0, 4) ];
+        let descriptor   = HierDescriptor::new(dimensions, None);
+        let printer      = check_printer_box(&expected, true, false);
+        let name         = "Change Test".to_string();
+        let print_opts   = Some(PrintOpts { printer: Some(printer.clone()), ..Default::default() });
+        let window_size  = None;
 
-        assert!(!hier_integer.equals(stat));
+        let configuration =
+            IntegerHierConfig { descriptor, name, window_size, print_opts };
 
-        stat.print();
+        let mut hier       = IntegerHier::new_hier(configuration);
+        let     thresholds = ChangeThresholds { mean_percent: 20.0, count_percent: 25.0 };
 
-        hier_integer.print_all(None, None);
+        hier.set_change_detection(thresholds, None);
 
-        assert!(stat.mean() == expected_mean);
+        for count in [ 10, 5, 6 ] {
+            for _i in 0..count {
+                hier.record_i64(42);
+            }
 
-        println!("simple_hier_test:  {} events, sum {}", events, sum_of_events);
-    }
+            hier.advance();
+        }
 
-    struct TestTraverser {
-        count:  i64,
+        assert!(hier.changes_detected() == Some(1));
+        assert!(check_printer_count_match(printer));
     }
 
-    impl TestTraverser {
-        pub fn new() -> TestTraverser {
-            let count = 0;
+    struct IndexTraverser {
+        visits: Vec<(HierIndex, bool)>,
+    }
 
-            TestTraverser { count }
+    impl HierIndexedTraverser for IndexTraverser {
+        fn visit(&mut self, index: HierIndex, live: bool, _member: &mut dyn Rustics) {
+            self.visits.push((index, live));
         }
     }
 
-    impl HierTraverser for TestTraverser {
-        fn visit(&mut self, _member: &mut dyn Rustics) {
-            self.count += 1;
+    fn test_traverse_indexed() {
+        let mut hier = make_hier(2, 0);
+
+        for _i in 0..7 {
+            hier.record_i64(1);
+            hier.advance();
         }
-    }
 
-    // Shove enough events into the stat to get a level 3 entry.  Check that
-    // the count and the mean of the level 3 stat match our expectations.
+        // Check that every member is visited exactly once with an index
+        // that finds it and a live flag that matches member_state().
 
-    fn long_test() {
-        let     auto_next      = 2;
-        let     level_0_period = 4;
-        let mut hier_integer   = make_hier(level_0_period, auto_next);
-        let mut events         = 0;
+        let mut traverser = IndexTraverser { visits: Vec::new() };
 
-        // Record data until there's a level 3 instance.
+        hier.traverse_all_indexed(&mut traverser);
 
-        while hier_integer.stats[3].all_len()  == 0 {
-            events += 1;
-            hier_integer.record_i64(events);
+        let mut predicted = 0;
 
-            check_sizes(&hier_integer, events, false);
+        for level in 0..hier.dimensions.len() {
+            predicted += hier.all_len(level);
         }
 
-        // Now see that all the sizes, etc, match what we
-        // expect.
+        assert!(traverser.visits.len() == predicted);
 
-        let     dimensions         = &hier_integer.dimensions;
-        let mut events_per_level_3 = auto_next;
+        for (index, live) in &traverser.visits {
+            let state = hier.member_state(*index).unwrap();
 
-        for i in 0..dimensions.len() - 1 {
-            events_per_level_3 *= dimensions[i].period;
+            assert!(index.set() == HierSet::All);
+            assert!(hier.index(*index).is_some());
+            assert!(*live == (state == HierMemberState::Live));
         }
 
-        {
-            let stat_rc        = hier_integer.stats[3].newest().unwrap();
-            let stat_borrow    = stat_rc.borrow();
-            let stat           = stat_borrow.to_rustics();
-
-            let events_in_stat = (events - 1) as f64;
-            let sum            = (events_in_stat * (events_in_stat + 1.0)) / 2.0;
-            let mean           = sum / events_in_stat;
+        // The indices() iterator gives the same indices and live flags.
 
-            println!("long_test:  stats.mean() {}, expected {}", stat.mean(), mean);
-
-            assert!(stat.count() as i64 == events - 1               );
-            assert!(stat.count() as i64 == events_per_level_3 as i64);
-            assert!(stat.mean()         == mean                     );
+        let indices = hier.indices(HierSet::All).map(|(index, _, live)| (index, live));
 
-            hier_integer.print_all(None, None);
-        }
+        assert!(indices.collect::<Vec<_>>() == traverser.visits);
 
-        // Do a quick test of traverse_live().  It should see each
-        // "live" Rustics instance in the matrix.
+        // The first level 0 member is the oldest, and has been summarized.
 
-        let mut traverser = TestTraverser::new();
+        assert!(traverser.visits[0] == (HierIndex::new(HierSet::All, 0, 0), false));
 
-        hier_integer.traverse_live(&mut traverser);
+        let mut traverser = IndexTraverser { visits: Vec::new() };
 
-        // Now compute how many members the traverser should have seen.
+        hier.traverse_live_indexed(&mut traverser);
 
         let mut predicted = 0;
 
-        for level in 0..hier_integer.dimensions.len() {
-            predicted += hier_integer.live_len(level) as i64;
+        for level in 0..hier.dimensions.len() {
+            predicted += hier.live_len(level);
         }
 
-        println!("long_test:  traverse_live saw {} stats instances, predicted {}",
-            traverser.count, predicted);
-        assert!(traverser.count == predicted);
-
-        // Do a quick test of traverse_all().  It should see each
-        // instance in the matrix.
+        assert!(traverser.visits.len() == predicted);
 
-        let mut traverser = TestTraverser::new();
+        for (index, live) in &traverser.visits {
+            assert!(*live);
+            assert!(index.set() == HierSet::Live);
+            assert!(hier.member_state(*index) == Some(HierMemberState::Live));
+        }
 
-        hier_integer.traverse_all(&mut traverser);
+        let live = hier.indices(HierSet::Live).collect::<Vec<_>>();
 
-        // Now compute how many members the traverser should have seen.
+        assert!(live.len() == predicted);
 
-        let mut predicted = 0;
+        for (index, count, is_live) in live {
+            let member = hier.index(index).unwrap();
 
-        for level in 0..hier_integer.dimensions.len() {
-            predicted += hier_integer.all_len(level) as i64;
+            assert!(is_live);
+            assert!(count == hier_item!(member).to_rustics().count());
         }
+    }
 
-        println!("long_test:  traverse_all saw {} stats instances, predicted {}",
-            traverser.count, predicted);
-        assert!(traverser.count == predicted);
+    // Check that a Hier instance can keep a window of the samples
+    // recorded in the last n seconds.
 
-        // Do a sanity test on the members.
+    fn test_time_window() {
+        let hz        = 1_000_000_000;
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
 
-        let member_opt    = hier_integer.stats[1].newest().unwrap();
-        let member_borrow = hier_item!(member_opt);
-        let member        = member_borrow.as_any().downcast_ref::<RunningInteger>();
+        let dimensions = vec![ HierDimension::new(4, 8), HierDimension::new(0, 4) ];
+        let descriptor = HierDescriptor::new(dimensions, Some(10));
+        let generator  = Rc::from(RefCell::new(IntegerHier::new()));
+        let name       = "Time Window".to_string();
+        let class      = "integer".to_string();
+        let print_opts = None;
+        let window     = HierWindow::Time(30, timer);
 
-        // For test debugging.
-        //
-        //let proper_type =
-        //    match member {
-        //        Some(_) => { true  }
-        //        None    => { false }
-        //    };
-        //
-        //assert!(proper_type);
+        let configuration =
+            HierConfig { descriptor, generator, name, window, class, print_opts };
 
-        let rustics = member.unwrap().to_rustics();
+        let mut hier = Hier::new(configuration);
 
-        println!("long_test:  got \"{}\" for class", rustics.class());
+        for i in 1..=100 {
+            hier.record_i64(i);
+            clock.borrow_mut().current += hz;
+        }
 
-        assert!(rustics.class() == "integer");
-    }
+        // The clock is at 100 seconds, so the samples recorded from
+        // 71 seconds onward are in the window.
 
-    fn test_sanity() {
-        let printer      = stdout_printer();
-        let printer      = printer_mut!(printer);
-        let name         = "time_hier sanity test".to_string();
-        let timer        = continuing_box();
-        let print_opts   = None;
+        assert!(hier.count()   == 29 );
+        assert!(hier.min_i64() == 72 );
+        assert!(hier.max_i64() == 100);
 
-        // Create the dimensions.
+        let window = hier.window_as::<TimeBoundedWindow>().unwrap();
 
-        let dimension_0  = HierDimension::new(4, 4);
-        let dimension_1  = HierDimension::new(100, 200);
+        assert!(window.seconds() == 30);
 
-        let dimensions   = vec![ dimension_0.clone(), dimension_1.clone() ];
+        // The hierarchy itself still sees every sample.
 
-        let auto_next    = 20;
-        let auto_advance = Some(auto_next);
-        let descriptor   = HierDescriptor::new(dimensions, auto_advance);
-        let window_size  = None;
+        assert!(hier.event_count() == 100);
 
-        // Now make an actual time_hier instance from a configuration.
+        // Do the same for a float hierarchy.
 
-        let configuration =
-            TimeHierConfig { descriptor, timer, name, window_size, print_opts };
+        let timer      = ClockTimer::new_box(clock.clone());
+        let dimensions = vec![ HierDimension::new(4, 8), HierDimension::new(0, 4) ];
+        let descriptor = HierDescriptor::new(dimensions, Some(10));
+        let generator  = Rc::from(RefCell::new(FloatHier::new()));
+        let name       = "Float Time Window".to_string();
+        let class      = "float".to_string();
+        let print_opts = None;
+        let window     = HierWindow::Time(10, timer);
 
-        let mut hier = TimeHier::new_hier(configuration);
+        let configuration =
+            HierConfig { descriptor, generator, name, window, class, print_opts };
 
-        // Now force a level 1 instance.
+        let mut hier = Hier::new(configuration);
 
-        let mut events             = 0;
-        let     events_per_level_1 = auto_next * dimension_0.period() as i64;
-        let mut timer              = continuing_box();
+        for i in 1..=20 {
+            hier.record_f64(i as f64);
+            clock.borrow_mut().current += hz;
+        }
 
-        for i in 0..2 * events_per_level_1 {
-            hier.record_time(i + 1);
-            hier.record_interval(&mut timer);
+        assert!(hier.count()   == 9   );
+        assert!(hier.max_f64() == 20.0);
 
-            events += 2;
-        }
+        // Check the conversions from the window_size parameter.
 
-        assert!(hier.event_count() == events);
-        hier.print();
-        hier.print_histogram(printer);
+        assert!( HierWindow::from(None   ).is_none());
+        assert!( HierWindow::from(Some(0)).is_none());
+        assert!(!HierWindow::from(Some(8)).is_none());
+    }
 
-        hier.clear_histogram();
+    fn test_window_access() {
+        let dimensions = vec![ HierDimension::new(4, 8), HierDimension::new(0, 4) ];
+        let descriptor = HierDescriptor::new(dimensions, Some(10));
 
-        let histogram = hier.to_log_histogram().unwrap();
-        let histogram = histogram.borrow();
+        let name          = "Window Access".to_string();
+        let print_opts    = None;
+        let window_size   = Some(10);
+        let configuration = IntegerHierConfig { descriptor, name, window_size, print_opts };
+        let mut hier      = IntegerHier::new_hier(configuration);
 
-        for sample in histogram.positive.iter() {
-            assert!(*sample == 0);
+        for i in 1..=25 {
+            hier.record_i64(i);
         }
 
-        // Do a sanity test on the members.
+        // The window holds the last 10 samples.
 
-        let member_opt    = hier.stats[1].newest().unwrap();
-        let member_borrow = hier_item!(member_opt);
-        let member        = member_borrow.as_any().downcast_ref::<RunningTime>();
+        let window = hier.window().unwrap();
 
-        // For test debugging.
-        //
-        //let proper_type =
-        //    match member {
-        //        Some(_) => { true  }
-        //        None    => { false }
-        //    };
-        //
-        //assert!(proper_type);
+        assert!(window.count()   == 10);
+        assert!(window.min_i64() == 16);
+        assert!(window.max_i64() == 25);
 
-        let rustics = member.unwrap().to_rustics();
+        let typed   = hier.window_as::<IntegerWindow>().unwrap();
+        let samples = typed.iter().copied().collect::<Vec<i64>>();
 
-        assert!(rustics.count() == events_per_level_1 as u64);
-        assert!(rustics.class() == "time");
+        assert!(samples == (16..=25).collect::<Vec<i64>>());
+        assert!(hier.window_as::<FloatWindow>().is_none());
 
-        println!("test_sanity:  got \"{}\" for class", rustics.class());
+        // A hierarchy without a window has nothing to return.
 
-        let index   = HierIndex::new(HierSet::Live, 0, 0);
-        let printer = stdout_printer();
-        let title   = "Index Print Title";
+        let hier = make_hier(4, 2);
 
-        hier.print_index_opts(index, Some(printer), Some(title));
+        assert!(hier.window().is_none());
+        assert!(hier.window_as::<IntegerWindow>().is_none());
+    }
 
-        let export = hier.export_stats();
+    struct TestArchiver {
+        archived: Rc<RefCell<Vec<(usize, u64)>>>,
+    }
 
-        assert!(export.printable.n as i64 == auto_next);
+    impl HierArchiver for TestArchiver {
+        fn archive(&mut self, level: usize, member: MemberRc) {
+            let count = hier_item!(member).to_rustics().count();
+
+            self.archived.borrow_mut().push((level, count));
+        }
     }
 
-    fn sample_usage() {
-        // Make a descriptor of the first level.  We have chosen to sum 1000
-        // level 0 RunningInteger instances into one level 1 RunningInteger
-        // instance.  This level is large, so we will keep only 1000 level 0
-        // instances in the window.
+    fn test_archiver() {
+        let mut level_0    = HierDimension::new(2, 4);
+        let mut level_1    = HierDimension::new(0, 4);
+        let     archived   = Rc::new(RefCell::new(Vec::new()));
+        let     archiver   = TestArchiver { archived: archived.clone() };
 
-        let dimension_0 = HierDimension::new(1000, 1000);
+        level_0.set_archived(true);
+        level_1.set_archived(true);
 
-        // At level 1, we want to sum 100 level 1 instances into one level 2
-        // instance.  This level is smaller, so let's retain 200
-        // RunningInteger instances here.
+        assert!(level_0.archived());
 
-        let dimension_1 = HierDimension::new(100, 200);
+        let mut descriptor = HierDescriptor::new(vec![ level_0, level_1 ], None);
 
-        // Level two isn't summed, so the period isn't used.  Let's pretend
-        // this level isn't used much, so retain only 100 instances in it.
+        descriptor.set_archiver(Box::new(archiver));
 
-        let dimension_2 = HierDimension::new(0, 100);
+        let name          = "Archive Test".to_string();
+        let print_opts    = None;
+        let window_size   = None;
+        let configuration = IntegerHierConfig { descriptor, name, window_size, print_opts };
+        let mut hier      = IntegerHier::new_hier(configuration);
 
-        // Now create the Vec.  Save the dimension instances for future use.
+        // Record i samples in period i.
+
+        for i in 0..10 {
+            for _j in 0..i {
+                hier.record_i64(1);
+            }
+
+            hier.advance();
+        }
+
+        // Level 0 has held 11 members and keeps 4, so the first 7 were
+        // archived, oldest first.  Level 1 has held 5 members, and the
+        // first one, which summed periods 0 and 1, was archived.
+
+        let archived = archived.borrow();
+        let level_0  = archived.iter().filter(|(level, _)| *level == 0);
+        let level_0  = level_0.map(|(_, count)| *count).collect::<Vec<u64>>();
+
+        assert!(level_0 == (0..7).collect::<Vec<u64>>());
+        assert!(archived.contains(&(1, 1)));
+        assert!(archived.len() == 8);
+        assert!(hier.all_len(0) == 4);
+        assert!(hier.all_len(1) == 4);
+    }
 
-        let dimensions =
-            vec![
-                dimension_0.clone(), dimension_1.clone(), dimension_2.clone()
-            ];
+    fn test_prune() {
+        // The periods are 2, 4, 6, and 8, and each level retains three
+        // periods.
 
-        // Now create the entire descriptor for the hier instance.  Let's
-        // record 2000 events into each level 0 RunningInteger instance.
+        let mut hier = make_hier(2, 0);
 
-        let auto_advance = Some(2000);
-        let descriptor   = HierDescriptor::new(dimensions, auto_advance);
+        for i in 0..100 {
+            hier.record_i64(i);
+            hier.advance();
+        }
 
-        // Now create some items used by Hier to do printing.
+        assert!(hier.all_len(0) == 6 );
+        assert!(hier.all_len(1) == 12);
+        assert!(hier.all_len(2) == 12);
+        assert!(hier.all_len(3) == 2 );
 
-        let name        = "test hierarchical integer".to_string();
-        let print_opts  = None;
-        let window_size = None;
+        let level_0 = hier.memory_usage(0);
 
-        // Finally, create the configuration description for the
-        // constructor.
+        assert!(level_0 > 0);
 
-        let configuration =
-            IntegerHierConfig { descriptor, name, window_size, print_opts };
+        // Keep four members at level 0.  The oldest ones are dropped.
 
-        // Now make the Hier instance.
+        let oldest = HierIndex::new(HierSet::All, 0, 0);
+        let period = hier.period_index(oldest).unwrap();
 
-        let mut integer_hier = IntegerHier::new_hier(configuration);
+        assert!(hier.prune(0, 4)          == 2                );
+        assert!(hier.all_len(0)           == 4                );
+        assert!(hier.memory_usage(0)      == level_0 / 6 * 4  );
+        assert!(hier.period_index(oldest) == Some(period + 2) );
 
-        // Now record some events with boring data.
+        // Compaction keeps only the live sets.
 
-        let mut events   = 0;
-        let auto_advance = auto_advance.unwrap();
+        assert!(hier.compact() == 2 + 8 + 6);
+        assert!(hier.compact() == 0);
 
-        for i in  0..auto_advance {
-            events += 1;
-            integer_hier.record_i64(i + 10);
+        for level in 0..4 {
+            assert!(hier.all_len(level) == hier.live_len(level));
         }
 
-        assert!( integer_hier.log_mode() == 11);
-        assert!( integer_hier.int_extremes());
-        assert!(!integer_hier.float_extremes());
+        // The instance keeps working.
 
-        // This is a no-op that shouldn't panic.
+        for i in 0..10 {
+            hier.record_i64(i);
+            hier.advance();
+        }
 
-        integer_hier.precompute();
+        assert!(hier.all_len(0) == 6);
+        assert!(hier.current().borrow().to_rustics().count() == 0);
+    }
 
-        // We have just completed the first level 0 instance, but
-        // the implementation creates the next instance only when
-        // it has data to record, so there should be only one level
-        // zero instance, and nothing at level 1 or level 2.
+    fn test_member_pool() {
+        let mut pooled = make_hier(2, 0);
+        let mut plain  = make_hier(2, 0);
 
-        assert!(integer_hier.event_count() == events);
-        assert!(integer_hier.count()       == events as u64);
-        assert!(integer_hier.live_len(0)   == 1     );
-        assert!(integer_hier.live_len(1)   == 0     );
-        assert!(integer_hier.live_len(2)   == 0     );
+        assert!(pooled.member_pool_stats().is_none());
 
-        // Now record some data to force the creation of
-        // the second level 1 instance.
+        pooled.set_member_pool(2);
 
-        events += 1;
-        integer_hier.record_i64(10);
+        // Level 0 retains six members, so the first eviction is at
+        // the sixth advance.
 
-        // The new level 0 instance should have only one event
-        // recorded.  The Rustics implementation for Hier returns
-        // the data in the current level 0 instance, so check it.
+        for i in 0..5 {
+            pooled.record_i64(i);
+            pooled.advance();
+        }
 
-        assert!(integer_hier.event_count() == events);
-        assert!(integer_hier.count()       == 1     );
-        assert!(integer_hier.live_len(0)   == 2     );
-        assert!(integer_hier.live_len(1)   == 0     );
-        assert!(integer_hier.live_len(2)   == 0     );
+        let stats = pooled.member_pool_stats().unwrap();
 
-        let events_per_level_1 =
-            auto_advance * dimension_0.period() as i64;
+        assert!(stats.pooled   == 0);
+        assert!(stats.limit    == 2);
+        assert!(stats.recycled == 0);
 
-        for i in events..events_per_level_1 {
-            integer_hier.record_i64(i);
-            events += 1;
-        }
+        // The member evicted by the sixth advance is reused by the
+        // seventh.
 
-        // Check the state again.  We need to record one more
-        // event to cause the summation at level 0 into level
-        // 1.
+        let oldest = Rc::downgrade(&pooled.index(HierIndex::new(HierSet::All, 0, 0)).unwrap());
 
-        let expected_live  = dimension_0.period();
-        let expected_count = auto_advance as u64;
+        pooled.record_i64(5);
+        pooled.advance();
 
-        assert!(integer_hier.event_count() == events        );
-        assert!(integer_hier.count()       == expected_count);
-        assert!(integer_hier.live_len(0)   == expected_live );
-        assert!(integer_hier.live_len(1)   == 0             );
-        assert!(integer_hier.live_len(2)   == 0             );
+        assert!(pooled.member_pool_stats().unwrap().pooled == 1);
 
-        integer_hier.record_i64(42);
-        events += 1;
+        pooled.record_i64(6);
+        pooled.advance();
 
-        assert!(integer_hier.live_len(1)   == 1     );
-        assert!(integer_hier.event_count() == events);
+        let current = pooled.current();
 
-        // Test the histograms while we have a Hier.
+        assert!(Rc::ptr_eq(&current, &oldest.upgrade().unwrap()));
+        assert!(hier_item!(current).to_rustics().count() == 0);
 
-        run_histogram_tests(&mut integer_hier as &mut dyn Rustics);
+        drop(current);
 
-        // Test clear_all with a window.
+        let stats = pooled.member_pool_stats().unwrap();
 
-        integer_hier.clear_all();
+        assert!(stats.pooled   == 1);
+        assert!(stats.reused   == 1);
+        assert!(stats.recycled == 2);
 
-        assert!(integer_hier.count()            == 0);
-        assert!(integer_hier.event_count()      == 0);
-        assert!(integer_hier.stats[0].all_len() == 1);
-        assert!(integer_hier.advance_count      == 0);
+        // Recycling doesn't change the statistics.
 
-        for level in 1..integer_hier.stats.len() {
-            assert!(integer_hier.stats[level].all_len() == 0);
+        for i in 0..7 {
+            plain.record_i64(i);
+            plain.advance();
         }
 
-        // Record some events and check sizes.
+        for i in 0..100 {
+            pooled.record_i64(i);
+            plain .record_i64(i);
+            pooled.advance();
+            plain .advance();
+        }
 
-        for i in 1..=auto_advance {
-            integer_hier.record_i64(i as i64);
+        for (index, count, _live) in plain.indices(HierSet::All) {
+            let expected = plain .index(index).unwrap();
+            let found    = pooled.index(index).unwrap();
+            let expected = hier_item!(expected);
+            let found    = hier_item!(found);
+
+            assert!(found.to_rustics().count() == count);
+            assert!(found.to_rustics().mean()  == expected.to_rustics().mean());
         }
 
-        assert!(integer_hier.count()            == auto_advance as u64);
-        assert!(integer_hier.event_count()      == auto_advance as i64);
-        assert!(integer_hier.stats[0].all_len() == 1);
-        assert!(integer_hier.stats[1].all_len() == 0);
+        assert!(pooled.member_pool_stats().unwrap().reused == 101);
 
-        // Create one more level 0 Rustics instance.
+        // A member that is still referenced isn't recycled.
 
-        for i in 1..=auto_advance {
-            integer_hier.record_i64(i as i64);
-        }
+        let held = pooled.index(HierIndex::new(HierSet::All, 0, 0)).unwrap();
 
-        assert!(integer_hier.count()            == auto_advance as u64);
-        assert!(integer_hier.event_count()      == 2 * auto_advance as i64);
-        assert!(integer_hier.stats[0].all_len() == 2);
-        assert!(integer_hier.stats[1].all_len() == 0);
+        pooled.advance();
+        pooled.advance();
 
-        // Test printing with bad indices.
+        assert!(pooled.member_pool_stats().unwrap().recycled == 103);
 
-        let expected =
-            [
-                "test hierarchical integer[5000].live[0]",
-                "  That level (5000) is invalid (3 levels configured).",
-                "test hierarchical integer[0].live[10000]",
-                "  That index (10000) is out of bounds."
-            ];
+        drop(held);
 
-        let check_printer = check_printer_box(&expected, true, false);
+        // Pooled members are retitled with the live members.
 
-        let index = HierIndex::new(HierSet::Live, 5000, 0);
+        pooled.set_title("Pool Title");
+        pooled.advance();
 
-        integer_hier.print_index_opts(index, Some(check_printer.clone()), None);
+        let current = pooled.current();
 
-        let index = HierIndex::new(HierSet::Live, 0, 10000);
+        assert!(hier_item!(current).to_rustics().title() == "Pool Title");
 
-        integer_hier.print_index_opts(index, Some(check_printer.clone()), None);
+        pooled.set_member_pool(0);
 
-        assert!(check_printer_count_match(check_printer));
+        assert!(pooled.member_pool_stats().is_none());
     }
 
-    fn test_sum() {
-        let     printer      = stdout_printer();
-        let     printer      = printer_mut!(printer);
-        let     window_size  = 200;
-        let mut integer_hier = make_test_hier(100, Some(200), None);
+    // A generator that drops every third member pushed into an
+    // exporter, so that validate() has something to find.
 
-        for i in 1..=window_size {
-            integer_hier.record_i64(i as i64);
+    struct LossyGenerator {
+        inner:   IntegerHier,
+        pushes:  Cell<usize>,
+    }
+
+    impl HierGenerator for LossyGenerator {
+        fn make_from_exporter(&self, name: &str, print_opts: &PrintOption, exports: ExporterRc)
+                -> MemberRc {
+            self.inner.make_from_exporter(name, print_opts, exports)
         }
 
-        integer_hier.print_opts(None, None);
-        integer_hier.print_histogram(printer);
+        fn make_window(&self, name: &str, window: &HierWindow, print_opts: &PrintOption)
+                -> Box<dyn Rustics> {
+            self.inner.make_window(name, window, print_opts)
+        }
 
-        let count = window_size as f64;
-        let sum   = (count * (count + 1.0)) / 2.0;
-        let mean  = sum / count;
+        fn make_member(&self, name: &str, print_opts: &PrintOption) -> MemberRc {
+            self.inner.make_member(name, print_opts)
+        }
 
-        integer_hier.print();
+        fn make_exporter(&self) -> ExporterRc {
+            self.inner.make_exporter()
+        }
 
-        assert!(integer_hier.mean()     == mean);
-        assert!(integer_hier.variance() >   0.0);
-        assert!(integer_hier.skewness() ==  0.0);
-        assert!(integer_hier.log_mode() ==  8  );
-        assert!(integer_hier.kurtosis() == -1.2);
+        fn push(&self, exports: &mut dyn HierExporter, member: MemberRc) {
+            let pushes = self.pushes.get();
 
-        assert!(integer_hier.standard_deviation() >  0.0);
+            self.pushes.set(pushes + 1);
 
-        for i in 1..10000 {
-            integer_hier.record_i64(i as i64);
+            if pushes % 3 != 2 {
+                self.inner.push(exports, member);
+            }
         }
 
-        // Create a vector of indices.
+        fn hz(&self) -> u128 {
+            self.inner.hz()
+        }
+    }
 
-        let mut addends      = Vec::new();
-        let     level        = 0;
-        let     addend_count = 1000;
+    fn test_validate() {
+        let dimensions    = vec![ HierDimension::new(2, 4), HierDimension::new(3, 6), HierDimension::new(0, 4) ];
+        let descriptor    = HierDescriptor::new(dimensions, Some(5));
+        let name          = "Validate".to_string();
+        let print_opts    = None;
+        let window_size   = None;
+        let configuration = IntegerHierConfig { descriptor, name, window_size, print_opts };
+        let mut hier      = IntegerHier::new_hier(configuration);
 
-        for i in 0..addend_count {
-            addends.push(HierIndex::new(HierSet::Live, level, i));
+        let validation = hier.validate();
+
+        assert!(validation.is_valid());
+        assert!(validation.checked == 0);
+
+        for i in 0..200 {
+            hier.record_i64((i * 37) % 101 - 50);
         }
 
-        // Now compute the sum and print it.
+        // Level 1 retains 6 members and level 2 retains 4, but most of
+        // their inputs have been discarded, so they are skipped.
 
-        let (sum, count) = integer_hier.sum(addends, "Level 0 Summary");
-        let sum          = sum.unwrap();
+        let validation = hier.validate();
 
-        assert!(count > 0);
-        assert!(count < addend_count);
-        assert!(sum.borrow().to_rustics().mean() > 0.0);
+        println!("test_validate:  {:?}", validation);
 
-        let mut addends = Vec::new();
+        assert!(validation.is_valid());
+        assert!(validation.checked == 2);
+        assert!(validation.skipped == 8);
 
-        for i in 0..addend_count {
-            addends.push(HierIndex::new(HierSet::Live, 500, i));
+        // A generator that loses members is caught.
+
+        let     dimensions  = vec![ HierDimension::new(4, 8), HierDimension::new(0, 4) ];
+        let     descriptor  = HierDescriptor::new(dimensions, Some(10));
+        let     inner       = IntegerHier::new();
+        let     pushes      = Cell::new(0);
+        let     generator   = Rc::from(RefCell::new(LossyGenerator { inner, pushes }));
+        let     name        = "Lossy".to_string();
+        let     window      = HierWindow::None;
+        let     class       = "integer".to_string();
+        let     print_opts  = None;
+        let     config      = HierConfig { descriptor, generator, name, window, class, print_opts };
+        let mut lossy       = Hier::new(config);
+
+        for i in 0..80 {
+            lossy.record_i64(i);
         }
 
-        let (_sum, count) = integer_hier.sum(addends, "Level 0 Summary");
+        let validation = lossy.validate();
 
-        assert!(count == 0);
+        assert!(!validation.is_valid());
+        assert!(validation.checked == 1);
 
-        integer_hier.clear_all();
+        let discrepancy = &validation.discrepancies[0];
 
-        assert!(integer_hier.count() == 0);
+        assert!(discrepancy.level    == 1      );
+        assert!(discrepancy.which    == Some(0));
+        assert!(discrepancy.quantity == "count");
+        assert!(discrepancy.expected == 40.0   );
+        assert!(discrepancy.found    == 30.0   );
 
-        // Test title handling.
+        // Cardinality members can't be pooled.
 
-        let new_title   = "Hier Test Title";
-        let start_title = integer_hier.title();
+        let     dimensions  = vec![ HierDimension::new(2, 4), HierDimension::new(0, 4) ];
+        let     descriptor  = HierDescriptor::new(dimensions, Some(4));
+        let     name        = "Cardinality Validate".to_string();
+        let     print_opts  = None;
+        let     config      = CardinalityHierConfig { descriptor, name, print_opts };
+        let mut cardinality = CardinalityHier::new_hier(config);
 
-        assert!(start_title != new_title);
+        for i in 0..20 {
+            cardinality.record_i64(i);
+        }
 
-        integer_hier.set_title(new_title);
+        let validation = cardinality.validate();
 
-        assert!(integer_hier.title() == new_title);
+        assert!(validation.is_valid());
+        assert!(validation.checked == 0);
+        assert!(validation.skipped == 2);
+    }
 
-        // This is a no-op that should not panic.
+    // Serialize a Hier instance with serde and load the result into
+    // another instance.
 
-        integer_hier.precompute();
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        use serde::de::DeserializeSeed;
 
-        // Check set_id() and id().
+        let mut hier = make_test_hier(100, Some(50), None);
 
-        integer_hier.set_id(1);
-        assert!(integer_hier.id() == 1);
+        for i in 0..12_345 {
+            hier.record_i64(i % 1000);
+        }
 
-        integer_hier.set_id(2);
-        assert!(integer_hier.id() == 2);
+        let     json     = serde_json::to_string(&hier).unwrap();
+        let mut restored = make_test_hier(100, Some(50), None);
+        let mut reader   = serde_json::Deserializer::from_str(&json);
 
-        let export = integer_hier.export_stats();
+        HierSeed::new(&mut restored).deserialize(&mut reader).unwrap();
 
-        assert!(export.printable.n == 0);
-    }
+        assert!(restored.advance_count() == hier.advance_count());
+        assert!(restored.event_count()   == hier.event_count()  );
+        assert!(restored.count()         == hier.count()        );
+        assert!(restored.mean()          == hier.mean()         );
 
-    #[test]
-    #[should_panic]
-    fn test_bad_retention() {
-        let _ = HierDimension::new(8, 4);
-    }
+        // An instance without a window doesn't match the saved data.
 
-    #[test]
-    #[should_panic]
-    fn test_bad_auto_next() {
-        let _ = HierDescriptor::new(Vec::new(), Some(-2));
-    }
+        let mut other  = make_test_hier(100, None, None);
+        let mut reader = serde_json::Deserializer::from_str(&json);
 
-    #[test]
-    #[should_panic]
-    fn test_float_histogram() {
-        let hier = make_test_hier(100, None, None);
-        let _    = hier.float_histogram().unwrap();
+        assert!(HierSeed::new(&mut other).deserialize(&mut reader).is_err());
     }
 
     #[test]
-    #[should_panic]
-    fn test_float_histogram_window() {
-        let hier = make_test_hier(100, Some(100), None);
-        let _    = hier.float_histogram().unwrap();
+    fn run_tests() {
+        simple_hier_test     ();
+        long_test            ();
+        test_sanity          ();
+        test_sum             ();
+        sample_usage         ();
+        test_level_opts      ();
+        test_clock           ();
+        test_periods         ();
+        test_save_load       ();
+        test_summarize       ();
+        test_advance_every   ();
+        test_member_state    ();
+        test_percentile_at   ();
+        test_change_detection();
+        test_traverse_indexed();
+        test_time_window     ();
+        test_window_access   ();
+        test_archiver        ();
+        test_validate        ();
+        test_prune           ();
+        test_member_pool     ();
+        test_forwarding      ();
+        test_reset_extremes  ();
+
+        #[cfg(feature = "serde")]
+        test_serde           ();
     }
 
     #[test]
     #[should_panic]
-    fn test_short_dimensions() {
-        let dimensions   = Vec::new();
-        let auto_advance = Some(2000);
-        let descriptor   = HierDescriptor::new(dimensions, auto_advance);
-        let name         = "Test Hier".to_string();
-        let window_size  = None;
-        let print_opts   = None;
+    fn test_advance_every_no_clock() {
+        let dimensions     = vec![ HierDimension::new(2, 4), HierDimension::new(0, 4) ];
+        let mut descriptor = HierDescriptor::new(dimensions, None);
 
-        let config =
-            IntegerHierConfig { descriptor, name, window_size, print_opts };
+        descriptor.set_advance_every(100);
 
-        let _hier = IntegerHier::new_hier(config);
+        let name          = "No Clock".to_string();
+        let print_opts    = None;
+        let window_size   = None;
+        let configuration = IntegerHierConfig { descriptor, name, window_size, print_opts };
+
+        let _ = IntegerHier::new_hier(configuration);
     }
 
     #[test]
     #[should_panic]
-    fn test_zero_period() {
-        let dimension_0 = HierDimension::new(1000, 1000);
-        let dimension_1 = HierDimension::new(   0,  200);
-        let dimension_2 = HierDimension::new(   0,  200);
-
-        let dimensions =
-            vec![ dimension_0, dimension_1, dimension_2 ];
-
-        let auto_advance = Some(2000);
-        let descriptor   = HierDescriptor::new(dimensions, auto_advance);
-
-        let name         = "Test Hier".to_string();
-        let window_size  = None;
-        let print_opts   = None;
+    fn test_time_hier_time_window() {
+        let clock      = TestSimpleClock { current: 0, increment: 0 };
+        let timer      = ClockTimer::new_box(Rc::new(RefCell::new(clock)));
+        let dimensions = vec![ HierDimension::new(4, 8), HierDimension::new(0, 4) ];
+        let descriptor = HierDescriptor::new(dimensions, None);
+        let generator  = Rc::from(RefCell::new(TimeHier::new(timer.clone())));
+        let name       = "Unsupported".to_string();
+        let class      = "time".to_string();
+        let print_opts = None;
+        let window     = HierWindow::Time(10, timer);
 
-        let config =
-            IntegerHierConfig { descriptor, name, window_size, print_opts };
+        let configuration =
+            HierConfig { descriptor, generator, name, window, class, print_opts };
 
-        let _hier = IntegerHier::new_hier(config);
+        let _ = Hier::new(configuration);
     }
 
     #[test]
     #[should_panic]
-    fn test_one_period() {
-        let dimension_0  = HierDimension::new(1000, 1000);
-        let dimension_1  = HierDimension::new(   1,  200);
-        let dimension_2  = HierDimension::new(   0,  200);
+    fn test_no_archiver() {
+        let mut dimension = HierDimension::new(2, 4);
 
-        let dimensions =
-            vec![ dimension_0, dimension_1, dimension_2 ];
-
-        let auto_advance = Some(2000);
-        let descriptor   = HierDescriptor::new(dimensions, auto_advance);
-
-        let name         = "Test Hier".to_string();
-        let window_size  = None;
-        let print_opts   = None;
-
-        let config =
-            IntegerHierConfig { descriptor, name, window_size, print_opts };
+        dimension.set_archived(true);
 
-        let _hier = IntegerHier::new_hier(config);
-    }
+        let descriptor    = HierDescriptor::new(vec![ dimension, HierDimension::new(0, 4) ], None);
+        let name          = "No Archiver".to_string();
+        let print_opts    = None;
+        let window_size   = None;
+        let configuration = IntegerHierConfig { descriptor, name, window_size, print_opts };
 
-    #[test]
-    fn run_tests() {
-        simple_hier_test();
-        long_test       ();
-        test_sanity     ();
-        test_sum        ();
-        sample_usage    ();
+        let _ = IntegerHier::new_hier(configuration);
     }
 }
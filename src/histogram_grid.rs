@@ -0,0 +1,356 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * HistogramGrid
+//!     * HistogramGrid maps a LogHistogram and a FloatHistogram onto a
+//!       common set of buckets, so that an integer stream and a float
+//!       stream measuring the same quantity can be printed side by side
+//!       and differenced.  A typical case is a latency recorded as
+//!       integer nanoseconds in one place and as float seconds in another.
+//!
+//!     * The caller gives the scale that converts the integer units into
+//!       the float units, e.g., 1.0e-9 for nanoseconds to seconds.
+//!
+//!     * The common grid is the FloatHistogram bucket grid, since each
+//!       float bucket covers 16 powers of two, and so can hold whole
+//!       pseudo-log buckets.  A pseudo-log bucket whose scaled range
+//!       crosses a float bucket boundary is assigned to the float bucket
+//!       holding the midpoint of the range, and its samples are counted
+//!       as inexact.  When the scale is a power of two, only the largest
+//!       value in a pseudo-log bucket can fall across a boundary.
+//!
+//!     * The FloatHistogram counts are used as they are.  NaNs aren't in
+//!       any bucket, so they aren't included.
+//!
+//! ## Example
+//!```
+//!     use rustics::printer_mut;
+//!     use rustics::stdout_printer;
+//!     use rustics::log_histogram::LogHistogram;
+//!     use rustics::float_histogram::FloatHistogram;
+//!     use rustics::histogram_grid::HistogramGrid;
+//!
+//!     // Record the same latencies in nanoseconds and in seconds.
+//!
+//!     let mut nanoseconds = LogHistogram::new();
+//!     let mut seconds     = FloatHistogram::new(&None);
+//!
+//!     for latency in [ 1_000, 2_000, 50_000, 1_000_000, 7_000_000_000 ] {
+//!         nanoseconds.record(latency);
+//!         seconds.record(latency as f64 / 1.0e9);
+//!     }
+//!
+//!     let grid = HistogramGrid::new(&nanoseconds, 1.0e-9, &seconds);
+//!
+//!     // The two streams match, bucket by bucket.
+//!
+//!     assert!(grid.integer_samples() == 5);
+//!     assert!(grid.float_samples()   == 5);
+//!
+//!     for row in grid.rows() {
+//!         assert!(row.difference() == 0);
+//!     }
+//!
+//!     let printer = stdout_printer();
+//!     let printer = printer_mut!(printer);
+//!
+//!     grid.print("Nanoseconds", "Seconds", printer);
+//!```
+
+use super::Printer;
+use super::exponent_bias;
+use super::printable::Printable;
+use super::log_histogram::LogHistogram;
+use super::float_histogram::FloatHistogram;
+use super::float_histogram::bucket_divisor;
+use super::float_histogram::bucket_index;
+
+/// GridRow holds the counts for one bucket of the common grid.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridRow {
+    pub min:      f64,
+    pub max:      f64,
+    pub integer:  u64,
+    pub float:    u64,
+}
+
+impl GridRow {
+    /// Returns the float count minus the integer count.
+
+    pub fn difference(&self) -> i64 {
+        self.float as i64 - self.integer as i64
+    }
+}
+
+/// HistogramGrid holds a LogHistogram and a FloatHistogram aligned on
+/// the FloatHistogram buckets.
+
+#[derive(Clone, Debug)]
+pub struct HistogramGrid {
+    rows:     Vec<GridRow>,
+    scale:    f64,
+    inexact:  u64,
+}
+
+impl HistogramGrid {
+    /// Aligns the histograms.  The scale converts a value in the units
+    /// of the LogHistogram into the units of the FloatHistogram, and
+    /// must be positive and finite.
+
+    pub fn new(integer: &LogHistogram, scale: f64, float: &FloatHistogram) -> HistogramGrid {
+        if !scale.is_finite() || scale <= 0.0 {
+            panic!("HistogramGrid::new:  The scale must be positive and finite.");
+        }
+
+        let     buckets = float.negative.len();
+        let mut rows    = Vec::with_capacity(2 * buckets);
+        let mut inexact = 0;
+
+        // Make the rows in increasing order of value, as in
+        // FloatHistogram::iter_buckets.
+
+        for (min, max, count) in float.iter_buckets() {
+            let integer = 0;
+            let float   = count;
+
+            rows.push(GridRow { min, max, integer, float });
+        }
+
+        // Add each pseudo-log bucket to the row holding its range.
+
+        for (min, max, count) in integer.iter_buckets() {
+            if count == 0 {
+                continue;
+            }
+
+            let low    = Self::row(min as f64 * scale, buckets);
+            let high   = Self::row(max as f64 * scale, buckets);
+            let middle = (min as f64 + max as f64) / 2.0 * scale;
+            let middle = Self::row(middle, buckets);
+
+            if low != high {
+                inexact += count;
+            }
+
+            rows[middle].integer += count;
+        }
+
+        HistogramGrid { rows, scale, inexact }
+    }
+
+    // Find the row for a value.
+
+    fn row(value: f64, buckets: usize) -> usize {
+        let (index, negative) = bucket_index(value);
+
+        if negative {
+            buckets - 1 - index
+        } else {
+            buckets + index
+        }
+    }
+
+    /// Returns the rows of the grid in increasing order of value.
+
+    pub fn rows(&self) -> &[GridRow] {
+        &self.rows
+    }
+
+    /// Returns the scale given to new().
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Returns the number of integer samples that were in pseudo-log
+    /// buckets that crossed a row boundary.
+
+    pub fn inexact(&self) -> u64 {
+        self.inexact
+    }
+
+    /// Returns the total of the integer counts.
+
+    pub fn integer_samples(&self) -> u64 {
+        self.rows.iter().map(|row| row.integer).sum()
+    }
+
+    /// Returns the total of the float counts.
+
+    pub fn float_samples(&self) -> u64 {
+        self.rows.iter().map(|row| row.float).sum()
+    }
+
+    /// Returns the row differences, float minus integer, for the rows
+    /// where the counts differ, as (min, max, difference) tuples.
+
+    pub fn differences(&self) -> Vec<(f64, f64, i64)> {
+        self.rows.iter()
+            .filter(|row| row.integer != row.float)
+            .map(|row| (row.min, row.max, row.difference()))
+            .collect()
+    }
+
+    /// Prints the non-empty rows side by side, with the difference.
+    /// The rows are labeled with the bound furthest from zero, as in
+    /// FloatHistogram bar charts.
+
+    pub fn print(&self, integer_name: &str, float_name: &str, printer: &mut dyn Printer) {
+        let buckets = self.rows.len() / 2;
+
+        let inexact = Printable::commas_u64(self.inexact);
+
+        printer.print(&format!("  Aligned Histogram:  ({} inexact)", inexact));
+        printer.print(&format!("    {:<10} {:>14} {:>14} {:>14}",
+            "Bucket", integer_name, float_name, "Difference"));
+
+        for (i, row) in self.rows.iter().enumerate() {
+            if row.integer == 0 && row.float == 0 {
+                continue;
+            }
+
+            let (index, sign) =
+                if i < buckets {
+                    (buckets - 1 - i, "-")
+                } else {
+                    (i - buckets, "")
+                };
+
+            let exponent   = (index as isize + 1) * bucket_divisor() - exponent_bias();
+            let label      = format!("{}2^{}", sign, exponent);
+            let difference = row.difference();
+            let difference = Printable::commas(&format!("{:+}", difference));
+
+            printer.print(&format!("    {:<10} {:>14} {:>14} {:>14}",
+                label, Printable::commas_u64(row.integer), Printable::commas_u64(row.float),
+                difference));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::printer_mut;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_count_match;
+
+    // Record the same values into both histograms using a power of two
+    // as the scale.  Only the values at the top of a pseudo-log bucket
+    // can land in a different row.
+
+    fn test_power_of_two() {
+        let mut integer = LogHistogram::new();
+        let mut float   = FloatHistogram::new(&None);
+        let     scale   = 1.0 / 1024.0;
+
+        for value in [ -1_000_000, -3, 0, 1, 7, 1_000, 65_535, 1_000_000_000 ] {
+            integer.record(value);
+            float.record(value as f64 * scale);
+        }
+
+        let grid = HistogramGrid::new(&integer, scale, &float);
+
+        assert!(grid.scale()           == scale);
+        assert!(grid.integer_samples() == 8    );
+        assert!(grid.float_samples()   == 8    );
+        assert!(grid.inexact()         == 0    );
+        assert!(grid.differences().is_empty()  );
+
+        // The rows are in increasing order and cover the float buckets.
+
+        let rows = grid.rows();
+
+        assert!(rows.len() == float.negative.len() + float.positive.len());
+
+        for pair in rows.windows(2) {
+            assert!(pair[0].max <= pair[1].min);
+        }
+    }
+
+    // Use a scale that makes a pseudo-log bucket cross a row boundary.
+
+    fn test_inexact() {
+        let mut integer = LogHistogram::new();
+        let mut float   = FloatHistogram::new(&None);
+
+        // With a scale of 1.5, the bucket (2^16, 2^17] maps to
+        // (1.5 * 2^16, 1.5 * 2^17], which crosses 2^17, the boundary
+        // between two float buckets.  The midpoint is above 2^17.
+
+        let scale = 1.5;
+        let low   =  70_000;
+        let high  = 120_000;
+
+        integer.record(low);
+        integer.record(high);
+        float  .record(low  as f64 * scale);
+        float  .record(high as f64 * scale);
+
+        let grid = HistogramGrid::new(&integer, scale, &float);
+
+        assert!(grid.inexact()         == 2);
+        assert!(grid.integer_samples() == 2);
+
+        let differences = grid.differences();
+
+        assert!(differences.len() == 2        );
+        assert!(differences[0].2  == 1        );
+        assert!(differences[1].2  == -1       );
+        assert!(differences[0].1  == 131_072.0);
+    }
+
+    fn test_print() {
+        let mut integer = LogHistogram::new();
+        let mut float   = FloatHistogram::new(&None);
+
+        for _i in 0..1500 {
+            integer.record(1000);
+        }
+
+        for _i in 0..1000 {
+            float.record(1000.0);
+        }
+
+        float.record(-1.0);
+
+        let expected =
+            [
+                "  Aligned Histogram:  (0 inexact)",
+                "    Bucket            Integer          Float     Difference",
+                "    -2^1                    0              1             +1",
+                "    2^17                1,500          1,000           -500"
+            ];
+
+        let grid    = HistogramGrid::new(&integer, 1.0, &float);
+        let printer = check_printer_box(&expected, true, false);
+
+        grid.print("Integer", "Float", printer_mut!(printer));
+
+        assert!(check_printer_count_match(printer));
+    }
+
+    #[test]
+    fn run_tests() {
+        test_power_of_two();
+        test_inexact     ();
+        test_print       ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_scale() {
+        let integer = LogHistogram::new();
+        let float   = FloatHistogram::new(&None);
+
+        let _ = HistogramGrid::new(&integer, 0.0, &float);
+    }
+}
@@ -178,10 +178,12 @@ use super::hier_box;
 use super::running_integer::RunningInteger;
 use crate::running_integer::IntegerExporter;
 use super::integer_window::IntegerWindow;
+use super::time_bounded_window::TimeBoundedWindow;
 
 use crate::Hier;
 use crate::HierDescriptor;
 use crate::HierConfig;
+use crate::hier::HierWindow;
 use crate::HierGenerator;
 use crate::HierMember;
 use crate::HierExporter;
@@ -257,9 +259,9 @@ impl IntegerHier {
         let descriptor   = configuration.descriptor;
         let name         = configuration.name;
         let print_opts   = configuration.print_opts;
-        let window_size  = configuration.window_size;
+        let window       = HierWindow::from(configuration.window_size);
 
-        let config = HierConfig { descriptor, generator, name, window_size, class, print_opts };
+        let config = HierConfig { descriptor, generator, name, window, class, print_opts };
 
         Hier::new(config)
     }
@@ -277,11 +279,21 @@ impl HierGenerator for IntegerHier {
         hier_box!(member)
     }
 
-    fn make_window(&self, name: &str, window_size: usize, print_opts: &PrintOption)
+    fn make_window(&self, name: &str, window: &HierWindow, print_opts: &PrintOption)
             -> Box<dyn Rustics> {
-        let window = IntegerWindow::new(name, window_size, print_opts);
+        match window {
+            HierWindow::Samples(size) => {
+                Box::new(IntegerWindow::new(name, *size, print_opts))
+            }
+
+            HierWindow::Time(seconds, timer) => {
+                Box::new(TimeBoundedWindow::new(name, *seconds, timer.clone(), print_opts))
+            }
 
-        Box::new(window)
+            HierWindow::None => {
+                panic!("IntegerHier::make_window:  No window was configured.");
+            }
+        }
     }
 
     // Make a member from a complete list of exported statistics.
@@ -319,6 +331,31 @@ impl HierGenerator for IntegerHier {
     fn hz(&self) -> u128 {
         panic!("IntegerHier::hz:  not supported");
     }
+
+    fn checkpoint_member(&self, member: &MemberRc) -> Option<String> {
+        let member = hier_item!(member);
+        let member = member.as_any().downcast_ref::<RunningInteger>()?;
+
+        Some(member.checkpoint())
+    }
+
+    fn restore_member(&self, text: &str, print_opts: &PrintOption) -> Option<MemberRc> {
+        let member = RunningInteger::restore(text, print_opts)?;
+
+        Some(hier_box!(member))
+    }
+
+    fn checkpoint_window(&self, window: &dyn Rustics) -> Option<String> {
+        let window = window.generic().downcast_ref::<IntegerWindow>()?;
+
+        Some(window.checkpoint())
+    }
+
+    fn restore_window(&self, text: &str, print_opts: &PrintOption) -> Option<Box<dyn Rustics>> {
+        let window = IntegerWindow::restore(text, print_opts)?;
+
+        Some(Box::new(window))
+    }
 }
 
 #[cfg(test)]
@@ -369,9 +406,10 @@ pub mod tests {
         let class      = "integer".to_string();
         let name       = "test hier".to_string();
         let print_opts = print_opts;
+        let window     = HierWindow::from(window_size);
 
         let configuration =
-            HierConfig { descriptor, generator, class, name, window_size, print_opts };
+            HierConfig { descriptor, generator, class, name, window, print_opts };
 
         Hier::new(configuration)
     }
@@ -569,7 +607,7 @@ pub mod tests {
         let     title      = None;
         let     units      = bytes();
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
         let     samples    = 1000;
         let mut stats      = make_test_hier(samples, Some(samples as usize), print_opts);
 
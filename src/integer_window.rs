@@ -14,6 +14,36 @@
 //!     * This type also maintains a log histogram that contains counts
 //!       of all events seen, not just the window of n samples.
 //!
+//!     * enable_window_histogram() adds a second log histogram that
+//!       counts only the samples currently in the window.  Samples are
+//!       removed from it as they leave the window, so it suits
+//!       dashboards that show the recent distribution.  print() shows
+//!       it after the usual histogram.
+//!
+//!     * The count, sum, and moments of the window are updated as
+//!       samples enter and leave it, so queries like mean() and
+//!       variance() take constant time, even for a very large window.
+//!       print() scans the window to find the extremes, so it computes
+//!       the moments precisely during the scan.  See WindowMoments.
+//!
+//!     * The samples in the window can be read without copying via
+//!       as_slices() and iter(), for example to run custom analytics.
+//!       The samples are returned from oldest to newest.
+//!
+//!     * A timestamp can be kept for each sample via enable_timestamps(),
+//!       which takes a Timer instance.  The timestamps support queries on
+//!       the age of samples, like mean_younger_than() and
+//!       age_weighted_mean(), and evict_older_than() can drop samples that
+//!       have aged out.  As with TimeBoundedWindow, the timer is read via
+//!       finish(), so it should not be shared with other users.
+//!
+//!     * is_warmed_up() returns true once the window has been filled at
+//!       least once since it was created or cleared.  Until then, the
+//!       statistics describe only the first few samples, which after a
+//!       restart often aren't typical.  set_warm_up_policy() selects
+//!       whether print() reports the statistics as usual, annotates them
+//!       with a "Warming Up" line, or suppresses them.
+//!
 //! ## Example
 //!```
 //!    use std::rc::Rc;
@@ -76,15 +106,19 @@
 
 use std::any::Any;
 use std::rc::Rc;
+use std::cell::Cell;
 use std::cell::RefCell;
 
 use super::Rustics;
+use super::copy_log_histogram;
 use super::Printer;
 use super::PrinterBox;
 use super::ExportStats;
 use super::PrinterOption;
 use super::PrintOption;
 use super::Units;
+use super::Labels;
+use super::HistoOpts;
 use super::TimerBox;
 use super::Histogram;
 use super::LogHistogramBox;
@@ -92,12 +126,63 @@ use super::FloatHistogramBox;
 use crate::printable::Printable;
 use crate::log_histogram::LogHistogram;
 use super::printer_mut;
-use super::compute_variance;
-use super::compute_skewness;
-use super::compute_kurtosis;
+use super::timer_mut;
+use super::stats_math::compute_variance;
+use super::stats_math::compute_skewness;
+use super::stats_math::compute_kurtosis;
 use super::sum::kbk_sum;
 use super::sum::kbk_sum_sort;
 use super::parse_print_opts;
+use super::printable::PrintFields;
+use super::parse_fields;
+use super::checkpoint::Checkpoint;
+use super::checkpoint::CheckpointReader;
+use super::window_moments::WindowMoments;
+use super::collection_time::CollectionTime;
+use super::merge::Export;
+
+/// WarmUpPolicy controls how a window prints its statistics before
+/// the window has been filled once.  It is used by IntegerWindow,
+/// TimeWindow, and FloatWindow.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WarmUpPolicy {
+    /// Print the statistics as usual.
+    #[default]
+    Report,
+
+    /// Print the statistics with a line giving the window fill.
+    Annotate,
+
+    /// Print only the count and the window fill.  Histograms are
+    /// still printed, since they cover all the samples.
+    Suppress,
+}
+
+impl WarmUpPolicy {
+    /// Returns true if the window statistics should be printed.
+
+    pub fn print_statistics(&self, warmed_up: bool) -> bool {
+        warmed_up || *self != WarmUpPolicy::Suppress
+    }
+
+    /// Returns true if the "Warming Up" line should be printed.
+
+    pub fn print_annotation(&self, warmed_up: bool) -> bool {
+        !warmed_up && *self != WarmUpPolicy::Report
+    }
+
+    /// Prints the line giving the fill of a window that hasn't
+    /// warmed up.
+
+    pub fn print_fill(count: u64, window_size: usize, printer: &mut dyn Printer) {
+        let output =
+            format!("    {:<12} {:>12} of {} samples", "Warming Up",
+                Printable::commas_u64(count), Printable::commas_u64(window_size as u64));
+
+        printer.print(&output);
+    }
+}
 
 /// An IntegerWindow instance collects integer data samples into
 /// a fixed-size window. It also maintains a histogram based on
@@ -105,7 +190,6 @@ use super::parse_print_opts;
 ///
 /// See the module documentation for sample code.
 
-#[derive(Clone)]
 pub struct IntegerWindow {
     name:           String,
     title:          String,
@@ -116,21 +200,74 @@ pub struct IntegerWindow {
     // These fields must be zeroed or reset in clear():
 
     index:          usize,
-    stats_valid:    bool,
-
-    // The precompute() method sets these fields.  They are
-    // valid when stats_valid is true.
-
-    mean:           f64,
-    sum:            f64,
-    moment_2:       f64,
-    moment_3:       f64,
-    moment_4:       f64,
+    warmed_up:      bool,
+    moments:        WindowMoments,
 
     log_histogram:  LogHistogramBox,
+    window_histo:   Option<LogHistogram>,
+    timestamps:     Option<Timestamps>,
 
     printer:        PrinterBox,
     units:          Units,
+    histo_opts:     HistoOpts,
+    fields:         PrintFields,
+    warm_up_policy: WarmUpPolicy,
+    labels:         Labels,
+    collection:     Option<CollectionTime>,
+}
+
+// Clone makes a deep copy of the window and the histogram.  The
+// printer and the timestamp timer, if any, are shared.
+
+impl Clone for IntegerWindow {
+    fn clone(&self) -> IntegerWindow {
+        let log_histogram = copy_log_histogram(&self.log_histogram);
+
+        IntegerWindow {
+            name:           self.name.clone(),
+            title:          self.title.clone(),
+            window_size:    self.window_size,
+            vector:         self.vector.clone(),
+            id:             self.id,
+            index:          self.index,
+            warmed_up:      self.warmed_up,
+            moments:        self.moments,
+            log_histogram,
+            window_histo:   self.window_histo.clone(),
+            timestamps:     self.timestamps.clone(),
+            printer:        self.printer.clone(),
+            units:          self.units.clone(),
+            histo_opts:     self.histo_opts,
+            fields:         self.fields,
+            warm_up_policy: self.warm_up_policy,
+            labels:         self.labels.clone(),
+            collection:     self.collection.clone()
+        }
+    }
+}
+
+// Timestamps holds the time each sample in the window was recorded,
+// in ticks since timestamps were enabled.  The ticks vector is laid
+// out exactly like the sample vector.  The current time is updated
+// by queries, so it needs interior mutability.
+
+#[derive(Clone)]
+struct Timestamps {
+    timer:  TimerBox,
+    now:    Cell<i64>,
+    ticks:  Vec<i64>,
+}
+
+impl Timestamps {
+    // Read the timer to advance the current time.
+
+    fn now(&self) -> i64 {
+        let elapsed = timer_mut!(self.timer).finish();
+        let now     = self.now.get() + elapsed;
+
+        self.now.set(now);
+        now
+    }
 }
 
 // The Crunched structure contains all the data needed to
@@ -170,16 +307,19 @@ impl IntegerWindow {
         let id            = usize::MAX;
         let vector        = Vec::with_capacity(window_size);
         let index         = 0;
-        let stats_valid   = false;
-        let mean          = 0.0;
-        let sum           = 0.0;
-        let moment_2      = 0.0;
-        let moment_3      = 0.0;
-        let moment_4      = 0.0;
+        let warmed_up     = false;
+        let moments       = WindowMoments::new(window_size);
         let log_histogram = LogHistogram::new();
         let log_histogram = Rc::from(RefCell::new(log_histogram));
+        let window_histo  = None;
+        let timestamps    = None;
+
+        let warm_up_policy = WarmUpPolicy::default();
+        let labels         = Labels::new();
+        let collection     = None;
 
-        let (printer, title, units, _histo_opts) = parse_print_opts(print_opts, &name);
+        let (printer, title, units, histo_opts) = parse_print_opts(print_opts, &name);
+        let fields = parse_fields(print_opts);
 
         IntegerWindow {
             name,
@@ -188,15 +328,18 @@ impl IntegerWindow {
             window_size,
             vector,
             index,
-            stats_valid,
-            mean,
-            sum,
-            moment_2,
-            moment_3,
-            moment_4,
+            warmed_up,
+            moments,
             log_histogram,
+            window_histo,
+            timestamps,
             printer,
-            units
+            units,
+            histo_opts,
+            fields,
+            warm_up_policy,
+            labels,
+            collection
         }
     }
 
@@ -204,18 +347,307 @@ impl IntegerWindow {
         self.units = units;
     }
 
-    fn sum(&self) -> f64 {
-        let mut sum = 0.0;
+    /// Returns true if the window has been filled at least once since
+    /// it was created or cleared.
 
-        for sample in self.vector.iter() {
-            sum += *sample as f64;
+    pub fn is_warmed_up(&self) -> bool {
+        self.warmed_up
+    }
+
+    /// Sets how print() handles a window that hasn't warmed up.
+
+    pub fn set_warm_up_policy(&mut self, policy: WarmUpPolicy) {
+        self.warm_up_policy = policy;
+    }
+
+    /// Returns the warm-up policy.
+
+    pub fn warm_up_policy(&self) -> WarmUpPolicy {
+        self.warm_up_policy
+    }
+
+    /// Prints the collection period and the sample rate, if a
+    /// collection clock is attached.  The rate counts all the samples
+    /// in the histogram, not just those in the window.  This function
+    /// is used by TimeWindow, too.
+
+    pub fn print_collection_time(&self, printer: &mut dyn Printer) {
+        if let Some(collection) = &self.collection {
+            collection.print(self.log_histogram.borrow().samples(), printer);
         }
+    }
+
+    /// Returns the size of the window.
+
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Returns the samples in the window as two slices, without copying.
+    /// The window is a ring buffer, so the samples might wrap around the
+    /// end of the vector.  The first slice holds the oldest samples, and
+    /// the samples in each slice are in the order recorded.
+
+    pub fn as_slices(&self) -> (&[i64], &[i64]) {
+        (&self.vector[self.index..], &self.vector[..self.index])
+    }
+
+    /// Returns an iterator over the samples in the window, from oldest
+    /// to newest.
+
+    pub fn iter(&self) -> impl Iterator<Item = &i64> + '_ {
+        let (older, newer) = self.as_slices();
+
+        older.iter().chain(newer.iter())
+    }
+
+    /// Returns the number of samples in the window.
+
+    pub fn len(&self) -> usize {
+        self.vector.len()
+    }
+
+    /// Returns true if the window holds no samples.
+
+    pub fn is_empty(&self) -> bool {
+        self.vector.is_empty()
+    }
+
+    // Return the samples in the window from oldest to newest.
+
+    fn ordered_samples(&self) -> Vec<i64> {
+        self.iter().copied().collect()
+    }
+
+    /// Starts keeping a histogram of only the samples currently in the
+    /// window.  Any samples already in the window are included.
+
+    pub fn enable_window_histogram(&mut self) {
+        self.window_histo = Some(LogHistogram::new());
+        self.rebuild_window_histogram();
+    }
+
+    /// Returns the histogram of the samples in the window, if one is
+    /// enabled.
 
-        sum
+    pub fn window_histogram(&self) -> Option<&LogHistogram> {
+        self.window_histo.as_ref()
+    }
+
+    /// Prints the histogram of the samples in the window, if one is
+    /// enabled.
+
+    pub fn print_window_histogram(&self, printer: &mut dyn Printer) {
+        if let Some(window_histo) = &self.window_histo {
+            printer.print("  Window Contents");
+            window_histo.print_opts(printer, &self.histo_opts);
+        }
+    }
+
+    // Recompute the window histogram, if any, from the samples in the
+    // window.
+
+    fn rebuild_window_histogram(&mut self) {
+        if let Some(window_histo) = &mut self.window_histo {
+            window_histo.clear();
+
+            for sample in self.vector.iter() {
+                window_histo.record(*sample);
+            }
+        }
+    }
+
+    /// Starts keeping a timestamp for each sample, read from the given
+    /// timer.  The window must be empty.
+
+    pub fn enable_timestamps(&mut self, timer: TimerBox) {
+        if !self.vector.is_empty() {
+            panic!("IntegerWindow::enable_timestamps:  The window is not empty.");
+        }
+
+        timer_mut!(timer).start();
+
+        let now   = Cell::new(0);
+        let ticks = Vec::with_capacity(self.window_size);
+
+        self.timestamps = Some(Timestamps { timer, now, ticks });
+    }
+
+    /// Returns true if timestamps are being kept.
+
+    pub fn has_timestamps(&self) -> bool {
+        self.timestamps.is_some()
+    }
+
+    // Return the timestamps, which must be enabled.
+
+    fn timestamps(&self, caller: &str) -> &Timestamps {
+        match &self.timestamps {
+            Some(timestamps) => timestamps,
+            None             => panic!("IntegerWindow::{}:  Timestamps are not enabled.", caller),
+        }
+    }
+
+    /// Returns the age of each sample in the window, in ticks, from
+    /// oldest to newest.  Timestamps must be enabled.
+
+    pub fn ages(&self) -> Vec<i64> {
+        let timestamps = self.timestamps("ages");
+        let now        = timestamps.now();
+        let older      = &timestamps.ticks[self.index..];
+        let newer      = &timestamps.ticks[..self.index];
+
+        older.iter().chain(newer.iter()).map(|tick| now - *tick).collect()
+    }
+
+    /// Returns the number of samples younger than the given age, in
+    /// ticks.  Timestamps must be enabled.
+
+    pub fn count_younger_than(&self, max_age: i64) -> u64 {
+        self.ages().iter().filter(|age| **age < max_age).count() as u64
+    }
+
+    /// Returns the mean of the samples younger than the given age, in
+    /// ticks, or zero if there are none.  Timestamps must be enabled.
+
+    pub fn mean_younger_than(&self, max_age: i64) -> f64 {
+        let mut samples = Vec::new();
+
+        for (sample, age) in self.iter().zip(self.ages().iter()) {
+            if *age < max_age {
+                samples.push(*sample as f64);
+            }
+        }
+
+        if samples.is_empty() {
+            return 0.0;
+        }
+
+        let count = samples.len() as f64;
+
+        kbk_sum_sort(&mut samples) / count
+    }
+
+    /// Returns the mean of the window with each sample weighted by its
+    /// age, so that a sample half_life ticks old counts half as much
+    /// as a new one.  The result is zero for an empty window.
+    /// Timestamps must be enabled.
+
+    pub fn age_weighted_mean(&self, half_life: i64) -> f64 {
+        if half_life <= 0 {
+            panic!("IntegerWindow::age_weighted_mean:  The half-life must be positive.");
+        }
+
+        let mut weights  = Vec::new();
+        let mut weighted = Vec::new();
+
+        for (sample, age) in self.iter().zip(self.ages().iter()) {
+            let weight = 0.5_f64.powf(*age as f64 / half_life as f64);
+
+            weights .push(weight                 );
+            weighted.push(weight * *sample as f64);
+        }
+
+        if weights.is_empty() {
+            return 0.0;
+        }
+
+        kbk_sum_sort(&mut weighted) / kbk_sum_sort(&mut weights)
+    }
+
+    /// Removes the samples that are at least max_age ticks old from
+    /// the window, and returns the number removed.  The histogram
+    /// is not changed, but the window histogram, if any, is.
+    /// Timestamps must be enabled.
+
+    pub fn evict_older_than(&mut self, max_age: i64) -> usize {
+        let ages    = self.ages();
+        let evicted = ages.iter().take_while(|age| **age >= max_age).count();
+
+        if evicted == 0 {
+            return 0;
+        }
+
+        let samples    = self.ordered_samples();
+        let timestamps = self.timestamps.as_mut().unwrap();
+        let ticks      = &timestamps.ticks;
+        let ticks      = [ &ticks[self.index..], &ticks[..self.index] ].concat();
+
+        timestamps.ticks = ticks[evicted..].to_vec();
+        self.vector      = samples[evicted..].to_vec();
+        self.index       = 0;
+
+        self.rebuild_moments();
+        self.rebuild_window_histogram();
+
+        evicted
+    }
+
+    /// Saves the samples in the window and the histogram as a string.
+    /// See the checkpoint module.
+
+    pub fn checkpoint(&self) -> String {
+        self.make_checkpoint("integer_window").text()
+    }
+
+    /// Restores an instance saved by checkpoint().  The result is None
+    /// if the checkpoint is malformed or was made for another type.
+
+    pub fn restore(text: &str, print_opts: &PrintOption) -> Option<IntegerWindow> {
+        let reader = CheckpointReader::new_kind(text, "integer_window")?;
+
+        IntegerWindow::restore_reader(&reader, print_opts)
+    }
+
+    // These functions do the work for checkpoint() and restore(), and
+    // are used by TimeWindow, too.
+
+    pub(crate) fn make_checkpoint(&self, kind: &str) -> Checkpoint {
+        let mut checkpoint = Checkpoint::new(kind);
+
+        checkpoint.push_str ("name",        &self.name                 );
+        checkpoint.push_str ("title",       &self.title                );
+        checkpoint.push_u64 ("window_size", self.window_size as u64    );
+        checkpoint.push_i64s("samples",     &self.ordered_samples()    );
+        checkpoint.push_log_histogram("log", &self.log_histogram.borrow());
+        checkpoint
+    }
+
+    pub(crate) fn restore_reader(reader: &CheckpointReader, print_opts: &PrintOption)
+            -> Option<IntegerWindow> {
+        let name        = reader.string("name" )?;
+        let title       = reader.string("title")?;
+        let window_size = reader.usize("window_size")?;
+        let samples     = reader.i64s("samples")?;
+        let histogram   = reader.log_histogram("log")?;
+
+        if window_size == 0 || samples.len() > window_size {
+            return None;
+        }
+
+        let mut window = IntegerWindow::new(&name, window_size, print_opts);
+
+        window.warmed_up     = samples.len() == window_size;
+        window.title         = title;
+        window.vector        = samples;
+        window.log_histogram = Rc::from(RefCell::new(histogram));
+
+        window.rebuild_moments();
+        Some(window)
+    }
+
+    // Recompute the incremental moments from the samples in the window.
+
+    fn rebuild_moments(&mut self) {
+        let samples = self.vector.iter().map(|sample| *sample as f64);
+
+        self.moments.rebuild(samples);
     }
 
     /// Gather the summary information and compute summary statistics
-    /// for the current samples in the window.
+    /// for the current samples in the window.  This function scans the
+    /// whole window, and is more precise than the statistics kept as
+    /// samples are recorded, which queries like mean() use.
 
     pub fn crunch(&self) -> Crunched {
         if self.vector.is_empty() {
@@ -274,6 +706,52 @@ impl IntegerWindow {
         }
     }
 
+    /// Exports the statistics for the samples in the window, so that
+    /// they can be summed with other instances, as the Hier code does.
+    /// The histogram counts only the samples in the window.
+
+    pub fn export_data(&self) -> Export {
+        let crunched = self.crunch();
+
+        let mut cubed     = Vec::with_capacity(self.vector.len());
+        let mut histogram = LogHistogram::new();
+
+        for sample in self.vector.iter() {
+            cubed.push((*sample as f64).powi(3));
+            histogram.record(*sample);
+        }
+
+        let count           = self.vector.len() as u64;
+        let nans            = 0;
+        let infinities      = 0;
+        let mean            = crunched.mean;
+        let moment_2        = crunched.moment_2;
+        let cubes           = kbk_sum_sort(&mut cubed);
+        let moment_4        = crunched.moment_4;
+        let log_histogram   = Some(Rc::from(RefCell::new(histogram)));
+        let float_histogram = None;
+        let min_f64         = 0.0;
+        let max_f64         = 0.0;
+
+        let (min_i64, max_i64) =
+            if self.vector.is_empty() {
+                (i64::MAX, i64::MIN)
+            } else {
+                (self.compute_min(), self.compute_max())
+            };
+
+        Export {
+            count,           nans,        infinities,
+            mean,            moment_2,    cubes,
+            moment_4,        min_i64,     max_i64,
+            min_f64,         max_f64,     log_histogram,
+            float_histogram
+        }
+    }
+
+    // The extremes require a scan of the window anyway, so this
+    // function uses the precise moments from crunch().
+
     pub fn get_printable(&self) -> Printable {
         let n          = self.vector.len() as u64;
         let nans       = 0;
@@ -286,24 +764,11 @@ impl IntegerWindow {
         let mode_value = 0.0;
         let units      = self.units.clone();
 
-        let mean;
-        let variance;
-        let skewness;
-        let kurtosis;
-
-        if self.stats_valid {
-            mean     = self.mean();
-            variance = self.variance();
-            skewness = self.skewness();
-            kurtosis = self.kurtosis();
-        } else {
-            let crunched = self.crunch();
-
-            mean     = crunched.mean;
-            variance = compute_variance(n, crunched.moment_2);
-            skewness = compute_skewness(n, crunched.moment_2, crunched.moment_3);
-            kurtosis = compute_kurtosis(n, crunched.moment_2, crunched.moment_4);
-        }
+        let crunched   = self.crunch();
+        let mean       = crunched.mean;
+        let variance   = compute_variance(n, crunched.moment_2);
+        let skewness   = compute_skewness(n, crunched.moment_2, crunched.moment_3);
+        let kurtosis   = compute_kurtosis(n, crunched.moment_2, crunched.moment_4);
 
         Printable {
             n,         nans,  infinities,  min_i64,   max_i64,   min_f64,    max_f64,
@@ -382,19 +847,49 @@ pub struct AnalyzeData {
 
 impl Rustics for IntegerWindow {
     fn record_i64(&mut self, sample: i64) {
+        if let Some(timestamps) = &mut self.timestamps {
+            let now = timestamps.now();
+
+            if timestamps.ticks.len() == self.window_size {
+                timestamps.ticks[self.index] = now;
+            } else {
+                timestamps.ticks.push(now);
+            }
+        }
+
         if self.vector.len() == self.window_size {
+            let old = self.vector[self.index];
+
+            self.moments.replace(old as f64, sample as f64);
             self.vector[self.index] = sample;
+
+            if let Some(window_histo) = &mut self.window_histo {
+                window_histo.remove(old);
+            }
+
             self.index += 1;
 
             if self.index >= self.window_size {
                 self.index = 0;
             }
         } else {
+            self.moments.add(sample as f64);
             self.vector.push(sample);
+
+            if self.vector.len() == self.window_size {
+                self.warmed_up = true;
+            }
+        }
+
+        if self.moments.needs_rebuild() {
+            self.rebuild_moments();
         }
 
         self.log_histogram.borrow_mut().record(sample);
-        self.stats_valid = false;
+
+        if let Some(window_histo) = &mut self.window_histo {
+            window_histo.record(sample);
+        }
     }
 
     fn record_f64(&mut self, _sample: f64) {
@@ -438,16 +933,7 @@ impl Rustics for IntegerWindow {
     }
 
     fn mean(&self) -> f64 {
-        if self.vector.is_empty() {
-            return 0.0;
-        }
-
-        if self.stats_valid {
-            return self.mean;
-        }
-
-        let sample_sum = self.sum();
-        sample_sum / self.vector.len() as f64
+        self.moments.mean()
     }
 
     fn standard_deviation(&self) -> f64 {
@@ -455,39 +941,24 @@ impl Rustics for IntegerWindow {
     }
 
     fn variance(&self) -> f64 {
-        let count = self.vector.len() as u64;
-
-        if self.stats_valid {
-            compute_variance(count, self.moment_2)
-        } else {
-            let crunched = self.crunch();
+        let count    = self.vector.len() as u64;
+        let crunched = self.moments.crunched();
 
-            compute_variance(count, crunched.moment_2)
-        }
+        compute_variance(count, crunched.moment_2)
     }
 
     fn skewness(&self) -> f64 {
-        let count = self.vector.len() as u64;
-
-        if self.stats_valid {
-            compute_skewness(count, self.moment_2, self.moment_3)
-        } else {
-            let crunched = self.crunch();
+        let count    = self.vector.len() as u64;
+        let crunched = self.moments.crunched();
 
-            compute_skewness(count, crunched.moment_2, crunched.moment_3)
-        }
+        compute_skewness(count, crunched.moment_2, crunched.moment_3)
     }
 
     fn kurtosis(&self) -> f64 {
-        let count = self.vector.len() as u64;
-
-        if self.stats_valid {
-            compute_kurtosis(count, self.moment_2, self.moment_3)
-        } else {
-            let crunched = self.crunch();
+        let count    = self.vector.len() as u64;
+        let crunched = self.moments.crunched();
 
-            compute_kurtosis(count, crunched.moment_2, crunched.moment_4)
-        }
+        compute_kurtosis(count, crunched.moment_2, crunched.moment_4)
     }
 
     fn int_extremes(&self) -> bool {
@@ -514,27 +985,31 @@ impl Rustics for IntegerWindow {
         self.compute_max()
     }
 
-    fn precompute(&mut self) {
-        if self.stats_valid {
-            return;
-        }
-
-        let crunched = self.crunch();
+    // The statistics are kept up to date as samples are recorded, so
+    // this function just rebuilds them to discard any rounding error.
 
-        self.mean        = crunched.mean;
-        self.sum         = crunched.sum;
-        self.moment_2    = crunched.moment_2;
-        self.moment_3    = crunched.moment_3;
-        self.moment_4    = crunched.moment_4;
-        self.stats_valid = true;
+    fn precompute(&mut self) {
+        self.rebuild_moments();
     }
 
     fn clear(&mut self) {
         self.vector.clear();
-        self.index = 0;
+        self.index     = 0;
+        self.warmed_up = false;
+        self.moments.clear();
         self.log_histogram.borrow_mut().clear();
 
-        self.stats_valid = false;
+        if let Some(window_histo) = &mut self.window_histo {
+            window_histo.clear();
+        }
+
+        if let Some(timestamps) = &mut self.timestamps {
+            timestamps.ticks.clear();
+        }
+
+        if let Some(collection) = &mut self.collection {
+            collection.restart();
+        }
     }
 
     fn print(&self) {
@@ -559,10 +1034,31 @@ impl Rustics for IntegerWindow {
         let printable = self.get_printable();
         let printer   = printer_mut!(printer_box);
 
+        let policy = self.warm_up_policy;
+
         printer.print(title);
-        printable.print_common_i64(printer);
-        printable.print_common_float(printer);
-        self.log_histogram.borrow().print(printer);
+
+        if policy.print_statistics(self.warmed_up) {
+            printable.print_common_i64_fields  (self.fields, printer);
+            printable.print_common_float_fields(self.fields, printer);
+        } else {
+            Printable::print_integer("Count", printable.n as i64, printer);
+        }
+
+        if policy.print_annotation(self.warmed_up) {
+            WarmUpPolicy::print_fill(printable.n, self.window_size, printer);
+        }
+
+        self.print_collection_time(printer);
+
+        if self.histo_opts.print_histogram(self.fields) {
+            self.log_histogram.borrow().print_opts(printer, &self.histo_opts);
+            self.print_window_histogram(printer);
+        }
+
+        if self.histo_opts.print_cdf {
+            self.log_histogram.borrow().print_cdf(printer);
+        }
         printer.print("");
     }
 
@@ -574,10 +1070,30 @@ impl Rustics for IntegerWindow {
         None
     }
 
+    fn supports_collection_time(&self) -> bool {
+        true
+    }
+
+    fn set_collection_clock(&mut self, clock: Option<TimerBox>) {
+        self.collection = clock.map(CollectionTime::new);
+    }
+
+    fn collection_seconds(&self) -> Option<f64> {
+        self.collection.as_ref().map(|collection| collection.seconds())
+    }
+
     fn set_title(&mut self, title: &str) {
         self.title = String::from(title)
     }
 
+    fn set_label(&mut self, key: &str, value: &str) {
+        self.labels.insert(key.to_string(), value.to_string());
+    }
+
+    fn labels(&self) -> Labels {
+        self.labels.clone()
+    }
+
     // For internal use
 
     fn equals(&self, other: &dyn Rustics) -> bool {
@@ -611,7 +1127,7 @@ impl Rustics for IntegerWindow {
 
 impl Histogram for IntegerWindow {
     fn print_histogram(&self, printer: &mut dyn Printer) {
-        self.log_histogram.borrow().print(printer);
+        self.log_histogram.borrow().print_opts(printer, &self.histo_opts);
     }
 
     fn clear_histogram(&mut self) {
@@ -633,9 +1149,13 @@ pub mod tests {
     use crate::PrintOpts;
     use crate::log_histogram::pseudo_log_index;
     use crate::tests::continuing_box;
+    use crate::tests::compute_sum;
     use crate::running_integer::RunningInteger;
     use crate::tests::check_printer_box;
+    use crate::capture_printer::CapturePrinter;
     use crate::tests::bytes;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
 
     pub fn test_simple_stat() {
         let     window_size = 100;
@@ -844,7 +1364,7 @@ pub mod tests {
         let     title      = None;
         let     units      = bytes();
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 
         let     name       = "Test Statistics";
         let     samples    = 1000;
@@ -857,11 +1377,367 @@ pub mod tests {
         stats.print();
     }
 
+    fn test_checkpoint() {
+        let window_size = 100;
+        let mut window  = IntegerWindow::new("Checkpoint Test", window_size, &None);
+
+        // Wrap the window so that the oldest sample isn't at the
+        // start of the vector.
+
+        for i in 0..250 {
+            window.record_i64(i * i);
+        }
+
+        let     checkpoint = window.checkpoint();
+        let mut restored   = IntegerWindow::restore(&checkpoint, &None).unwrap();
+
+        assert!(restored.name()    == window.name()   );
+        assert!(restored.count()   == window.count()  );
+        assert!(restored.mean()    == window.mean()   );
+        assert!(restored.min_i64() == window.min_i64());
+        assert!(restored.max_i64() == window.max_i64());
+
+        assert!(restored.log_histogram.borrow().equals(&window.log_histogram.borrow()));
+
+        // Both windows should evict the same samples.
+
+        for i in 0..50 {
+            window.record_i64(-i);
+            restored.record_i64(-i);
+        }
+
+        assert!(restored.mean()    == window.mean()   );
+        assert!(restored.min_i64() == window.min_i64());
+        assert!(restored.max_i64() == window.max_i64());
+
+        // A window that isn't full should restore, too.
+
+        let mut window = IntegerWindow::new("Partial", window_size, &None);
+
+        window.record_i64(1);
+        window.record_i64(2);
+
+        let restored = IntegerWindow::restore(&window.checkpoint(), &None).unwrap();
+
+        assert!(restored.count() == 2  );
+        assert!(restored.mean()  == 1.5);
+
+        let text = checkpoint.replace("window_size 100", "window_size 10");
+
+        assert!(IntegerWindow::restore(&text, &None).is_none());
+    }
+
+    fn test_iter() {
+        let mut window = IntegerWindow::new("Iter Test", 4, &None);
+
+        assert!(window.is_empty());
+        assert!(window.iter().count() == 0);
+
+        for i in 1..=3 {
+            window.record_i64(i);
+        }
+
+        let (older, newer) = window.as_slices();
+
+        assert!(older == [ 1, 2, 3 ]);
+        assert!(newer.is_empty());
+
+        // Now wrap the ring buffer.
+
+        for i in 4..=6 {
+            window.record_i64(i);
+        }
+
+        let (older, newer) = window.as_slices();
+
+        assert!(older == [ 3, 4 ]);
+        assert!(newer == [ 5, 6 ]);
+        assert!(window.len() == 4);
+
+        let samples: Vec<i64> = window.iter().copied().collect();
+
+        assert!(samples == vec![ 3, 4, 5, 6 ]);
+    }
+
+    fn test_timestamps() {
+        let     current   = 0;
+        let     increment = 0;
+        let     clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let     timer     = ClockTimer::new_box(clock.clone());
+        let     hz        = 1_000_000_000;
+        let mut window    = IntegerWindow::new("Timestamp Test", 4, &None);
+
+        assert!(!window.has_timestamps());
+
+        window.enable_timestamps(timer);
+
+        assert!(window.has_timestamps());
+
+        // Record one sample per second, overflowing the window.
+
+        for i in 1..=6 {
+            window.record_i64(i * 10);
+            clock.borrow_mut().current += hz;
+        }
+
+        let second = hz as i64;
+
+        assert!(window.ages() == vec![ 4 * second, 3 * second, 2 * second, second ]);
+
+        assert!(window.count_younger_than(3 * second) == 2   );
+        assert!(window.mean_younger_than (3 * second) == 55.0);
+        assert!(window.mean_younger_than (0         ) == 0.0 );
+
+        // With a one-second half-life, each sample weighs twice as
+        // much as the one before it.
+
+        let expected = (30.0 + 40.0 * 2.0 + 50.0 * 4.0 + 60.0 * 8.0) / 15.0;
+        let mean     = window.age_weighted_mean(second);
+
+        assert!((mean - expected).abs() < 1.0e-9);
+
+        // Evict the samples at least 3 seconds old.
+
+        assert!(window.evict_older_than(3 * second) == 2);
+        assert!(window.iter().copied().collect::<Vec<i64>>() == vec![ 50, 60 ]);
+        assert!(window.count() == 2);
+        assert!(window.mean()  == 55.0);
+
+        // The window keeps working after the eviction.
+
+        window.record_i64(70);
+
+        assert!(window.count_younger_than(second) == 1);
+
+        window.clear();
+
+        assert!(window.ages().is_empty());
+    }
+
+    fn test_warm_up() {
+        let     capture = Rc::new(RefCell::new(CapturePrinter::new()));
+        let     printer = capture.clone() as PrinterBox;
+        let mut window  = IntegerWindow::new("Warm Up", 4, &None);
+
+        assert!(!window.is_warmed_up());
+        assert!(window.warm_up_policy() == WarmUpPolicy::Report);
+        assert!(window.window_size()    == 4);
+
+        for i in 1..=3 {
+            window.record_i64(i);
+        }
+
+        assert!(!window.is_warmed_up());
+
+        // The default policy prints the statistics without comment.
+
+        window.print_opts(Some(printer.clone()), None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!( lines.iter().any(|line| line.contains("Mean")));
+        assert!(!lines.iter().any(|line| line.contains("Warming Up")));
+
+        // Annotate adds the fill of the window.
+
+        window.set_warm_up_policy(WarmUpPolicy::Annotate);
+        window.print_opts(Some(printer.clone()), None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(lines.iter().any(|line| line.contains("Mean")));
+        assert!(lines.iter().any(|line| line.contains("Warming Up") && line.contains("3 of 4 samples")));
+
+        // Suppress prints only the count and the fill.
+
+        window.set_warm_up_policy(WarmUpPolicy::Suppress);
+        window.print_opts(Some(printer.clone()), None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(!lines.iter().any(|line| line.contains("Mean")));
+        assert!( lines.iter().any(|line| line.contains("Count")));
+        assert!( lines.iter().any(|line| line.contains("Warming Up")));
+
+        // Once the window is full, the statistics are printed.
+
+        window.record_i64(4);
+        window.print_opts(Some(printer.clone()), None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(window.is_warmed_up());
+        assert!( lines.iter().any(|line| line.contains("Mean")));
+        assert!(!lines.iter().any(|line| line.contains("Warming Up")));
+
+        // The window stays warm after an eviction, but not after
+        // clear().
+
+        let     current   = 0;
+        let     increment = 0;
+        let     clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let mut timed     = IntegerWindow::new("Timed", 2, &None);
+
+        timed.enable_timestamps(ClockTimer::new_box(clock.clone()));
+        timed.record_i64(1);
+        timed.record_i64(2);
+        clock.borrow_mut().current += 10;
+
+        assert!(timed.evict_older_than(5) == 2);
+        assert!(timed.is_warmed_up());
+
+        timed.clear();
+
+        assert!(!timed.is_warmed_up());
+
+        // A restored window is warm only if it was full.
+
+        let restored = IntegerWindow::restore(&window.checkpoint(), &None).unwrap();
+
+        assert!(restored.is_warmed_up());
+
+        window.clear();
+        window.record_i64(1);
+
+        let restored = IntegerWindow::restore(&window.checkpoint(), &None).unwrap();
+
+        assert!(!restored.is_warmed_up());
+    }
+
+
+    fn test_clone() {
+        let mut window = IntegerWindow::new("Clone Test", 4, &None);
+
+        for i in 1..=4 {
+            window.record_i64(i);
+        }
+
+        let mut copy = window.clone();
+
+        copy.record_i64(100);
+
+        assert!(window.iter().copied().collect::<Vec<i64>>() == vec![ 1, 2, 3,   4 ]);
+        assert!(copy  .iter().copied().collect::<Vec<i64>>() == vec![ 2, 3, 4, 100 ]);
+        assert!(window.is_warmed_up() && copy.is_warmed_up());
+
+        let original = window.log_histogram().unwrap();
+        let copied   = copy  .log_histogram().unwrap();
+
+        assert!(compute_sum(&original.borrow()) == 4);
+        assert!(compute_sum(&copied  .borrow()) == 5);
+    }
+
+    fn test_incremental() {
+        let     size   = 100;
+        let mut window = IntegerWindow::new("Incremental", size, &None);
+
+        // Slide the window through a rising series, and check the
+        // incremental statistics against a full scan.
+
+        for i in 0..2 * size as i64 + 37 {
+            window.record_i64(1_000_000 + i * i);
+
+            let crunched = window.crunch();
+            let count    = window.count();
+            let variance = compute_variance(count, crunched.moment_2);
+
+            assert!((window.mean() - crunched.mean).abs() <= 1.0e-9 * crunched.mean);
+            assert!((window.variance() - variance).abs() <= 1.0e-6 * variance.max(1.0));
+        }
+
+        // Clearing the window resets the statistics.
+
+        window.clear();
+        window.record_i64(5);
+
+        assert!(window.mean()     == 5.0);
+        assert!(window.variance() == 0.0);
+    }
+
+    fn test_window_histogram() {
+        let     current   = 0;
+        let     increment = 0;
+        let     clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let     timer     = ClockTimer::new_box(clock.clone());
+        let mut window    = IntegerWindow::new("Window Histogram", 4, &None);
+
+        assert!(window.window_histogram().is_none());
+
+        window.enable_timestamps(timer);
+        window.record_i64(1);
+        window.enable_window_histogram();
+
+        // The samples already in the window are counted.
+
+        assert!(window.window_histogram().unwrap().samples() == 1);
+
+        for i in 2..=10 {
+            window.record_i64(i * 100);
+            clock.borrow_mut().current += 1;
+        }
+
+        // The usual histogram counts everything, while the window
+        // histogram counts only the last four samples.
+
+        let window_histo = window.window_histogram().unwrap();
+
+        assert!(window.log_histogram.borrow().samples()       == 10);
+        assert!(window_histo.samples()                        == 4 );
+        assert!(window_histo.positive[pseudo_log_index(1)]    == 0 );
+        assert!(window_histo.positive[pseudo_log_index(1000)] >= 1 );
+
+        let mut expected = LogHistogram::new();
+
+        for sample in window.iter() {
+            expected.record(*sample);
+        }
+
+        assert!(window_histo.equals(&expected));
+
+        // Eviction removes samples, too.
+
+        window.evict_older_than(3);
+
+        assert!(window.len()                                 == 2);
+        assert!(window.window_histogram().unwrap().samples() == 2);
+        assert!(window.log_histogram.borrow().samples()      == 10);
+
+        // print() shows both histograms.
+
+        let capture = Rc::new(RefCell::new(CapturePrinter::new()));
+        let printer = Some(capture.clone() as PrinterBox);
+
+        window.print_opts(printer, None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(lines.iter().any(|line| line == "  Window Contents"));
+
+        window.clear();
+
+        assert!(window.window_histogram().unwrap().samples() == 0);
+    }
+
     #[test]
     fn run_tests() {
-        test_simple_stat ();
-        test_equality    ();
-        test_histogram   ();
-        test_print_output();
+        test_simple_stat     ();
+        test_equality        ();
+        test_histogram       ();
+        test_print_output    ();
+        test_checkpoint      ();
+        test_iter            ();
+        test_timestamps      ();
+        test_warm_up         ();
+        test_clone           ();
+        test_incremental     ();
+        test_window_histogram();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_no_timestamps() {
+        let window = IntegerWindow::new("No Timestamps", 4, &None);
+
+        let _ = window.ages();
     }
 }
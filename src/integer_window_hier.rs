@@ -0,0 +1,377 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * IntegerWindowHier
+//!     * This module provides support to bridge from the Hier code to
+//!       IntegerWindow-specific functions.
+//!
+//!     * See the library comments (lib.rs) for an overview of how
+//!       hierarchical types work.
+//!
+//!     * IntegerWindowHier::new_hier() creates a Hier instance whose
+//!       level 0 members are IntegerWindow instances, so each period
+//!       reports on its last window_size samples rather than on every
+//!       sample recorded during the period.  The higher levels are
+//!       RunningInteger instances that sum the windows they summarize.
+//!
+//!     * The level 0 members already are windows, so a separate Hier
+//!       window is not supported for this type.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::hier::HierDescriptor;
+//!     use rustics::hier::HierDimension;
+//!     use rustics::integer_window_hier::IntegerWindowHier;
+//!     use rustics::integer_window_hier::IntegerWindowHierConfig;
+//!
+//!     // Sum 4 level 0 windows into each level 1 instance, and keep 8
+//!     // instances at each level.  Record 2000 samples into each level
+//!     // 0 instance, which keeps only the last 1000.
+//!
+//!     let dimension_0 = HierDimension::new(4, 8);
+//!     let dimension_1 = HierDimension::new(0, 8);
+//!     let dimensions  = vec![ dimension_0, dimension_1 ];
+//!     let descriptor  = HierDescriptor::new(dimensions, Some(2000));
+//!     let name        = "Latency".to_string();
+//!     let window_size = 1000;
+//!     let print_opts  = None;
+//!
+//!     let configuration =
+//!         IntegerWindowHierConfig { descriptor, name, window_size, print_opts };
+//!
+//!     let mut latency = IntegerWindowHier::new_hier(configuration);
+//!
+//!     for i in 1..=2000 {
+//!         latency.record_i64(i);
+//!     }
+//!
+//!     // The current level 0 instance holds the last 1000 samples.
+//!
+//!     assert!(latency.count() == 1000);
+//!     assert!(latency.mean()  == 1500.5);
+//!
+//!     latency.print();
+//!```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::Rustics;
+use super::Histogram;
+use super::PrintOption;
+use super::hier_box;
+use super::running_integer::RunningInteger;
+use super::running_integer::IntegerExporter;
+use super::integer_window::IntegerWindow;
+
+use crate::Hier;
+use crate::HierDescriptor;
+use crate::HierConfig;
+use crate::hier::HierWindow;
+use crate::HierGenerator;
+use crate::HierMember;
+use crate::HierExporter;
+use crate::ExporterRc;
+use crate::MemberRc;
+use crate::hier_item;
+
+// Provide for downcasting from a Hier member to a Rustics
+// type or "dyn Any" to get to the IntegerWindow code.
+
+impl HierMember for IntegerWindow {
+    fn to_rustics(&self) -> &dyn Rustics {
+        self
+    }
+
+    fn to_rustics_mut(&mut self) -> &mut dyn Rustics {
+        self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn to_histogram(&self) -> &dyn Histogram {
+        self
+    }
+}
+
+/// IntegerWindowHier provides an interface from the Hier code to
+/// the IntegerWindow impl code that is not in methods.  Most users
+/// should construct a Hier instance via new_hier().
+
+pub struct IntegerWindowHier {
+    window_size:  usize,
+}
+
+/// IntegerWindowHierConfig is used to pass the constructor
+/// parameters for a Hier instance that uses IntegerWindow instances
+/// at level 0.  The window_size parameter gives the number of samples
+/// kept by each level 0 instance.
+
+pub struct IntegerWindowHierConfig {
+    pub descriptor:  HierDescriptor,
+    pub name:        String,
+    pub window_size: usize,
+    pub print_opts:  PrintOption,
+}
+
+impl IntegerWindowHier {
+    /// Make a plain IntegerWindowHier structure.  Most users should
+    /// call new_hier() create a complete Hier instance.
+
+    pub fn new(window_size: usize) -> IntegerWindowHier  {
+        if window_size == 0 {
+            panic!("IntegerWindowHier::new:  The window size is zero.");
+        }
+
+        IntegerWindowHier { window_size }
+    }
+
+    /// new_hier() creates a new Hier instance from the given
+    /// configuration.
+
+    pub fn new_hier(configuration: IntegerWindowHierConfig) -> Hier {
+        let generator    = IntegerWindowHier::new(configuration.window_size);
+        let generator    = Rc::from(RefCell::new(generator));
+        let class        = "integer".to_string();
+
+        let descriptor   = configuration.descriptor;
+        let name         = configuration.name;
+        let print_opts   = configuration.print_opts;
+        let window       = HierWindow::None;
+
+        let config = HierConfig { descriptor, generator, name, window, class, print_opts };
+
+        Hier::new(config)
+    }
+
+    /// Returns the number of samples kept by each level 0 instance.
+
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+}
+
+impl HierGenerator for IntegerWindowHier {
+    fn make_member(&self, name: &str, print_opts: &PrintOption) -> MemberRc {
+        let member = IntegerWindow::new(name, self.window_size, print_opts);
+
+        hier_box!(member)
+    }
+
+    fn make_window(&self, _name: &str, _window: &HierWindow, _print_opts: &PrintOption)
+            -> Box<dyn Rustics> {
+        panic!("IntegerWindowHier::make_window:  not supported");
+    }
+
+    // The upper levels are RunningInteger instances.
+
+    fn make_from_exporter(&self, name: &str, print_opts: &PrintOption, exporter: ExporterRc)
+            -> MemberRc {
+        let mut exporter_borrow = exporter.borrow_mut();
+        let     exporter_any    = exporter_borrow.as_any_mut();
+        let     exporter_impl   = exporter_any.downcast_mut::<IntegerExporter>().unwrap();
+        let     member          = exporter_impl.make_member(name, print_opts);
+
+        hier_box!(member)
+    }
+
+    fn make_exporter(&self) -> ExporterRc {
+        let exporter = IntegerExporter::new();
+
+        Rc::from(RefCell::new(exporter))
+    }
+
+    // Push another instance onto the export list.  Level 0 members
+    // are windows, and the others are RunningInteger instances.
+
+    fn push(&self, exporter: &mut dyn HierExporter, member_rc: MemberRc) {
+        let exporter_any    = exporter.as_any_mut();
+        let exporter_impl   = exporter_any.downcast_mut::<IntegerExporter>().unwrap();
+
+        let member_borrow   = hier_item!(member_rc);
+        let member_any      = member_borrow.as_any();
+
+        let export =
+            if let Some(window) = member_any.downcast_ref::<IntegerWindow>() {
+                window.export_data()
+            } else {
+                member_any.downcast_ref::<RunningInteger>().unwrap().export_data()
+            };
+
+        exporter_impl.push(export);
+    }
+
+    fn hz(&self) -> u128 {
+        panic!("IntegerWindowHier::hz:  not supported");
+    }
+
+    fn checkpoint_member(&self, member: &MemberRc) -> Option<String> {
+        let member = hier_item!(member);
+        let member = member.as_any();
+
+        if let Some(window) = member.downcast_ref::<IntegerWindow>() {
+            return Some(window.checkpoint());
+        }
+
+        Some(member.downcast_ref::<RunningInteger>()?.checkpoint())
+    }
+
+    fn restore_member(&self, text: &str, print_opts: &PrintOption) -> Option<MemberRc> {
+        if let Some(window) = IntegerWindow::restore(text, print_opts) {
+            return Some(hier_box!(window));
+        }
+
+        let member = RunningInteger::restore(text, print_opts)?;
+
+        Some(hier_box!(member))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hier::HierDimension;
+    use crate::hier::HierIndex;
+    use crate::hier::HierSet;
+
+    fn make_test_hier(auto_next: i64, window_size: usize) -> Hier {
+        let dimension_0 = HierDimension::new(4, 8);
+        let dimension_1 = HierDimension::new(2, 4);
+        let dimension_2 = HierDimension::new(0, 4);
+        let dimensions  = vec![ dimension_0, dimension_1, dimension_2 ];
+        let descriptor  = HierDescriptor::new(dimensions, Some(auto_next));
+        let name        = "test hier".to_string();
+        let print_opts  = None;
+
+        let configuration = IntegerWindowHierConfig { descriptor, name, window_size, print_opts };
+
+        IntegerWindowHier::new_hier(configuration)
+    }
+
+    fn test_generator() {
+        let generator = IntegerWindowHier::new(4);
+        let member_rc = generator.make_member("test member", &None);
+        let exporter  = generator.make_exporter();
+
+        assert!(generator.window_size() == 4);
+
+        for i in 1..=10 {
+            member_rc.borrow_mut().to_rustics_mut().record_i64(i);
+        }
+
+        generator.push(&mut *exporter.borrow_mut(), member_rc.clone());
+        generator.push(&mut *exporter.borrow_mut(), member_rc);
+
+        // The sum covers two copies of the window, 7 through 10.
+
+        let sum_rc = generator.make_from_exporter("sum", &None, exporter);
+        let sum    = hier_item!(sum_rc);
+        let sum    = sum.as_any().downcast_ref::<RunningInteger>().unwrap();
+
+        assert!(sum.count()   == 8  );
+        assert!(sum.mean()    == 8.5);
+        assert!(sum.min_i64() == 7  );
+        assert!(sum.max_i64() == 10 );
+        assert!((sum.variance() - 10.0 / 7.0).abs() < 1.0e-12);
+
+        let histogram = sum.log_histogram().unwrap();
+
+        assert!(histogram.borrow().samples() == 8);
+    }
+
+    fn test_hier() {
+        let mut hier = make_test_hier(100, 10);
+
+        assert!(hier.class() == "integer");
+
+        // Record 0 through 99 in each period.  Each level 0 window
+        // keeps 90 through 99.
+
+        for _period in 0..8 {
+            for i in 0..100 {
+                hier.record_i64(i);
+            }
+        }
+
+        assert!(hier.count() == 10  );
+        assert!(hier.mean()  == 94.5);
+
+        // The first level 1 instance sums four windows.
+
+        hier.record_i64(0);
+
+        let member  = hier.index(HierIndex::new(HierSet::All, 1, 0)).unwrap();
+        let member  = hier_item!(member);
+        let level_1 = member.to_rustics();
+
+        assert!(level_1.count()   == 40  );
+        assert!(level_1.mean()    == 94.5);
+        assert!(level_1.min_i64() == 90  );
+        assert!(level_1.class()   == "integer");
+
+        assert!(hier.validate().is_valid());
+
+        hier.print();
+    }
+
+    fn test_save_load() {
+        let mut hier = make_test_hier(20, 5);
+
+        for i in 0..200 {
+            hier.record_i64(i);
+        }
+
+        let mut text = Vec::new();
+
+        hier.save(&mut text).unwrap();
+
+        let mut loaded = make_test_hier(20, 5);
+
+        loaded.load(&mut text.as_slice()).unwrap();
+
+        assert!(loaded.count()    == hier.count()   );
+        assert!(loaded.mean()     == hier.mean()    );
+        assert!(loaded.all_len(0) == hier.all_len(0));
+        assert!(loaded.all_len(1) == hier.all_len(1));
+
+        let index = HierIndex::new(HierSet::All, 1, 0);
+
+        let original = hier  .index(index).unwrap();
+        let restored = loaded.index(index).unwrap();
+        let original = hier_item!(original);
+        let restored = hier_item!(restored);
+        let original = original.to_rustics();
+        let restored = restored.to_rustics();
+
+        assert!(restored.count()   == original.count()  );
+        assert!(restored.mean()    == original.mean()   );
+        assert!(restored.max_i64() == original.max_i64());
+    }
+
+    #[test]
+    fn run_tests() {
+        test_generator();
+        test_hier     ();
+        test_save_load();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_window() {
+        let _ = IntegerWindowHier::new(0);
+    }
+}
@@ -29,17 +29,63 @@
 //!
 //!         * It also provides a pseudo-log histogram of the samples.
 //!
+//!         * A SketchHistogram, which gives percentiles with a configurable relative error, can
+//!           be enabled for RunningInteger and RunningFloat instances.
+//!
+//!         * A BucketHistogram, which uses linear or user-specified bucket edges instead of the
+//!           pseudo-log layout, also can be enabled for those types.
+//!
+//!         * Users who need only the moments and extremes can call disable_histogram() on
+//!           RunningInteger, RunningFloat, and RunningTime instances to skip the histogram work
+//!           and memory.  The histogram accessors then return None, and printing omits the
+//!           histogram and the mode.
+//!
 //!     * IntegerWindow
 //!         * IntegerWindow implements a fixed-size window of the last n samples recorded.  Summary
 //!           statistics of the window samples are computed on demand.
 //!
 //!         * Like RunningInteger, it also provides a pseudo-log histogram.  The histogram counts
-//!           all samples seen, not just the current window.
+//!           all samples seen, not just the current window.  enable_window_histogram() adds a
+//!           second histogram that counts only the samples in the window.
+//!
+//!     * TimeBoundedWindow
+//!         * TimeBoundedWindow keeps the samples recorded during the last n seconds, as measured
+//!           by a Timer instance, rather than the last n samples.  Old samples are evicted lazily.
 //!
 //!     * Counter
 //!         * This type implements a simple counter that generates no further statistics.  It can be
 //!           used for counting events, for example.
 //!
+//!     * PeakTracker
+//!         * This type keeps the maximum value seen in each of a series of intervals, like the
+//!           worst queue depth per minute, and prints the recent history of peaks.
+//!
+//!     * Cardinality
+//!         * This type estimates the number of distinct values recorded, like the number of unique
+//!           client ids seen, using the HyperLogLog algorithm.  Instances can be merged.
+//!
+//!     * RunningRatio
+//!         * This type records success and failure outcomes, like cache hits and misses, and
+//!           reports the success ratio with a Wilson score confidence interval.
+//!
+//!     * DuplicateFilter
+//!         * This type uses a Bloom filter to count samples whose values were seen earlier.
+//!           RunningInteger instances can enable a filter to report a duplicate fraction.
+//!
+//!     * WorstTags
+//!         * This type keeps the largest samples along with a tag for each, like a request id.
+//!           RunningInteger and RunningTime instances can enable it and record samples with
+//!           record_i64_tagged() or record_time_tagged() to find the requests behind the tail.
+//!
+//!     * TrimOpts
+//!         * RunningInteger, RunningFloat, and RunningTime can keep a trimmed mean and a count of
+//!           the samples far from the mean, so that a few huge samples don't hide the typical
+//!           behavior.  See enable_trimming().
+//!
+//!     * DecayingInteger and DecayingFloat
+//!         * These types apply an exponential decay with a configurable half-life to the
+//!           count, sum, and moments, so that recent samples dominate the statistics.
+//!
 //! * Basic Time Statistics Types
 //!     * RunningTime
 //!         * This type uses the RunningInteger code to handle time intervals.  Values are printed
@@ -49,6 +95,13 @@
 //!         * This type uses the IntegerWindow code to handle time intervals.  As with the
 //!           RunningTime type, values are printed in units of time.
 //!
+//!         * Both time types can enable a LogLinearHistogram, which splits each pseudo-log bucket
+//!           into linear sub-buckets to show the structure within a range like 1 ms to 2 ms.
+//!
+//!     * SizedTime
+//!         * This type records pairs of a size in bytes and a time interval, and keeps latency,
+//!           size, and time per byte statistics.
+//!
 //! * Basic Floating Point Statistics Types
 //!     * Floating point samples currently are supported only for machines that use IEEE f64 format.
 //!
@@ -60,6 +113,37 @@
 //!         * FloatWindow keeps a fixed-size window of samples, like IntegerWindow.  It creates a
 //!           histogram using FloatHistogram.
 //!
+//!     * FloatTimeWindow
+//!         * FloatTimeWindow keeps the f64 samples recorded during the last n seconds, like
+//!           TimeBoundedWindow.  It is useful for gauges like temperatures or queue depths.
+//!
+//!     * RunningFloat32
+//!         * This type keeps the RunningFloat statistics using f32 arithmetic, for targets where
+//!           f64 is slow.  The summary statistics are less precise.  See the module comments for
+//!           the tradeoffs.  Any type accepts f32 samples via record_f32().
+//!
+//!     * Correlation
+//!         * This type records (x, y) pairs, like request sizes and latencies, and reports the
+//!           covariance, the Pearson correlation, and the slope and intercept of a least-squares
+//!           line without keeping the samples.  See ArcSet::add_correlation().
+//!
+//!     * NonFinitePolicy
+//!         * NaN and infinite samples never enter the statistics.  By default, they are counted
+//!           and reported in the nans and infinities fields of a Printable.  A NonFinitePolicy
+//!           can instead drop them silently or panic.  See the non_finite module.
+//!
+//! * Copying Instances
+//!     * The basic Rustics types implement Clone.  A clone is a deep copy:  it gets its own copy
+//!       of the summary statistics, histograms, and window samples, so recording into the copy
+//!       doesn't change the original.  The name, title, id, and labels are copied.
+//!
+//!     * Printers and timers are shared by the copy and the original, since they are boxed trait
+//!       objects.  For types that read their timer in record_event(), like RunningTime, an
+//!       event recorded on either instance restarts the shared timer.
+//!
+//!     * SyncRustics is a handle, so a clone refers to the same instance.  Sets share their
+//!       members when cloned, and Hier does not implement Clone.
+//!
 //! * Hierarchical Statistics:  The Hier Type
 //!     * A Hier instance uses multiple Rustics instances to maintain statistical information.  This
 //!       approach can reduce accuracy loss over long sample periods and provide historical data.
@@ -116,6 +200,25 @@
 //!           FloatHier::new_hier() for an interface to create a Hier instance.  This type is very
 //!           similar to IntegerHier.
 //!
+//!     * IntegerWindowHier
+//!         * IntegerWindowHier makes a Hier instance whose level 0 members are IntegerWindow
+//!           instances, so each period reports on its last n samples.  The higher levels sum
+//!           the windows into RunningInteger instances.
+//!
+//!     * CardinalityHier
+//!         * CardinalityHier implements Hier for the Cardinality type.  The higher levels estimate
+//!           the number of distinct values over all the periods that they contain.
+//!
+//!     * Replayer
+//!         * The replay module feeds a trace of (timestamp, duration) records into a TimeHier,
+//!           doing an advance each time the trace crosses a period boundary, so that recorded
+//!           workloads can be analyzed offline.
+//!
+//!     * Slo
+//!         * The slo module evaluates a latency objective, like 99% of requests within 100 ms over
+//!           30 days, against the level 0 periods of a TimeHier, and reports the error budget used
+//!           and the burn rate.
+//!
 //! * Creating Sets
 //!     * The "arc_sets" and "rc_sets" modules implement sets that accept Rustics instances and
 //!       other sets as members.  Sets can be printed and cleared recursively by invoking a method
@@ -128,6 +231,92 @@
 //!         * This type implements an Rc-based version of sets.  These sets are faster than
 //!           Arc-based sets, but are not thread-safe.
 //!
+//!     * EvictionPolicy
+//!         * A set can be given a capacity for its Rustics members.  When it is full, adding a
+//!           member evicts the least recently used member or the one with the lowest priority.
+//!
+//!     * RusticsSet
+//!         * This trait is implemented by both set types.  It provides the operations that walk
+//!           a set hierarchy, like snapshots, CSV export, and lookup by path, so code can work
+//!           with either type of set.
+//!
+//!     * Report
+//!         * RusticsSet::report() builds a tree of typed nodes holding the statistics of a set
+//!           hierarchy.  The tree can be filtered or annotated, then rendered as text, JSON, CSV,
+//!           or Prometheus output.
+//!
+//!     * ColumnarBatch
+//!         * The columnar module converts a set snapshot or the samples in a window into typed
+//!           columns laid out like an Arrow record batch, for analysis with standard data tools.
+//!
+//!         * With the "arrow" Cargo feature, a batch converts into an Arrow RecordBatch, and
+//!           with the "parquet" feature, it can be written as a Parquet file.
+//!
+//!     * Labels
+//!         * Rustics instances can carry key/value labels, like a shard id, that stay with the
+//!           instance when it is added to a set or Hier and are emitted by the prometheus module.
+//!
+//!     * rustics_snapshot!
+//!         * This macro declares a struct with a typed snapshot field for each named set member,
+//!           and methods to fill it from an ArcSet or RcSet, so that the field names are checked
+//!           by the compiler.
+//!
+//!     * Aggregate
+//!         * Sets can end their print output with a roll-up block for each class of member,
+//!           e.g., the total of all the counters and the merged statistics of all the time
+//!           instances.  See set_print_aggregates() in either set type.
+//!
+//!     * Reporter
+//!         * This type prints or exports an ArcSet each time a given interval has passed,
+//!           optionally clearing the set after each report.  The application drives it by
+//!           calling tick().
+//!
+//!     * PrintThrottle
+//!         * A set can be given a minimum interval between prints, so that print calls in
+//!           frequently-hit paths don't flood the output.
+//!
+//!     * SpanRecorder
+//!         * This type records the durations of named spans into per-name RunningTime members
+//!           of an ArcSet.  With the "tracing" Cargo feature, it provides a tracing Layer that
+//!           feeds span latencies into the set.
+//!
+//!     * Gate
+//!         * A Gate checks thresholds like a maximum mean or percentile or a minimum throughput
+//!           against named members of a set and reports any violations, so benchmarks can be
+//!           used as regression tests in CI.
+//!
+//!     * GoldenReport
+//!         * The golden module saves a printed report to a file and later compares a new report
+//!           with it, allowing per-field numeric tolerances, and prints a readable diff of the
+//!           lines that differ, so applications can guard their metrics output in tests.
+//!
+//!     * RecordHook
+//!         * A hook can be installed in an instance or a set to receive every raw sample, so
+//!           that the samples can be written to a file for offline analysis while the usual
+//!           summaries are kept.  WriterHook writes the samples as text.
+//!
+//!     * CollectionTime
+//!         * A collection clock can be attached to an instance or to all the members of a set,
+//!           so that print() reports how long the instance has been collecting samples and the
+//!           overall sample rate.  The period restarts when the instance is cleared.
+//!
+//! * Multithreaded Collection
+//!     * ShardedInteger and ShardedTime
+//!         * These types keep a striped array of per-thread shards to avoid lock contention when
+//!           many threads record into one statistic.  The shards are merged on demand into a
+//!           RunningInteger or RunningTime instance.
+//!
+//!     * SharedRustics
+//!         * This trait records through a shared reference, so an instance can be kept in a
+//!           plain Arc without a Mutex.  AtomicCounter and AtomicGauge implement it using atomic
+//!           integers, and ShardedInteger implements it, too.
+//!
+//! * Iterator Pipelines
+//!     * RusticsIterator
+//!         * This extension trait adds record_each() and time_each() adapters to iterators, so
+//!           that each item yielded, or the time between items, is recorded into a Rustics
+//!           instance.
+//!
 //! * Timers
 //!     *  Timer
 //!         * This trait defines the basic abstract timer.  A timer has a frequency and returns
@@ -138,6 +327,11 @@
 //!         * This type is an implementation of Timer that uses the Rust "Duration" struct, which
 //!           measures wall clock time.
 //!
+//!     *  Default Timer
+//!         * The time module keeps a process-wide timer factory, set at most once via
+//!           set_default_timer_factory().  RunningTime::new_default(), TimeWindow::new_default(),
+//!           time_each(), and SpanStats use it, so that their statistics share one hz.
+//!
 //!     *  SimpleClock
 //!         * This trait defines the interface used to query a user-defined clock, which can be
 //!           wrapped using the ClockTimer type, q.v.
@@ -150,7 +344,8 @@
 //!     *  ClockTimer
 //!         * This Timer implementation is a wrapper for instances of trait SimpleClock.  For
 //!           example, a cycle counter like rdtsc on Intel could be wrapped to implement a
-//!           ClockTimer.
+//!           ClockTimer.  TscClock provides such a clock on x86_64, with a calibrated
+//!           frequency.
 //!
 //! * Printing
 //!     *  Printer
@@ -160,6 +355,14 @@
 //!         * See StdioPrinter for a sample implementation.  This type is used as the default
 //!           printer to send output to stdout.
 //!
+//!     *  FilePrinter
+//!         * This Printer implementation appends the output to a file, with optional rotation
+//!           when the file reaches a given size and control over how often the output is
+//!           flushed.
+//!
+//!     *  CapturePrinter
+//!         * This Printer implementation saves the output lines in a vector so that an
+//!           application can embed a report in its own documents by post-processing the lines.
 //!
 //!     *  Printable
 //!         * The Printable type provides standard formatting for printing data and some support
@@ -167,36 +370,145 @@
 //!           forms and integers with commas.  It is of interest mostly to developers creating new
 //!           Rustics implementations.
 //!
+//!     *  PrintFields
+//!         * A PrintFields set in the PrintOpts for an instance selects the summary lines that
+//!           are printed, so that a log can get just the count, mean, and maximum, say, for each
+//!           instance.  See PrintOptsBuilder::fields().
+//!
+//!     *  HistogramGrid
+//!         * HistogramGrid aligns a LogHistogram and a FloatHistogram on a common set of buckets,
+//!           given the scale between their units, so that an integer stream and a float stream
+//!           measuring the same quantity can be printed side by side and differenced.
+//!
+//! * Checkpoints
+//!     *  Checkpoint
+//!         * The running, window, and histogram types provide checkpoint() and restore() functions
+//!           that save their state as text, so that statistics can be kept across a process
+//!           restart and summed later.  See the checkpoint module for the encoding.
+//!
+//!         * With the "serde" Cargo feature, these types and Hier also implement the serde
+//!           Serialize trait, and all but Hier implement Deserialize.  HierSeed loads
+//!           serialized data into an existing Hier instance.
+//!
+//! * Custom Types
+//!     *  custom
+//!         * The custom module provides the bookkeeping that every Rustics type needs, like the
+//!           title and printer plumbing and the moment arithmetic, along with macros that expand
+//!           to the boilerplate methods, so that an application can implement Rustics and
+//!           HierMember for its own types.
+//!
+//! * Moment Math
+//!     *  stats_math
+//!         * The stats_math module provides the functions that convert between power sums and
+//!           moments about the mean, and the variance, skewness, and kurtosis estimators.  It is
+//!           of interest to developers creating new mergeable Rustics types.
+//!
+//!     *  WindowMoments
+//!         * This type keeps the count, sum, and moments of a window of samples as samples enter
+//!           and leave, so that the window types answer queries like mean() in constant time.
+//!
+
+// The tests predate the clippy gate.  They use explicit casts,
+// borrows, indices, and counters to make the expected values easy to
+// follow, so these style lints are off for test builds only.
+
+#![cfg_attr(test, allow(unused_assignments))]
+#![cfg_attr(test, allow(clippy::assign_op_pattern))]
+#![cfg_attr(test, allow(clippy::clone_on_copy))]
+#![cfg_attr(test, allow(clippy::comparison_to_empty))]
+#![cfg_attr(test, allow(clippy::double_parens))]
+#![cfg_attr(test, allow(clippy::explicit_counter_loop))]
+#![cfg_attr(test, allow(clippy::identity_op))]
+#![cfg_attr(test, allow(clippy::inconsistent_digit_grouping))]
+#![cfg_attr(test, allow(clippy::len_zero))]
+#![cfg_attr(test, allow(clippy::let_unit_value))]
+#![cfg_attr(test, allow(clippy::mut_range_bound))]
+#![cfg_attr(test, allow(clippy::needless_borrow))]
+#![cfg_attr(test, allow(clippy::needless_range_loop))]
+#![cfg_attr(test, allow(clippy::neg_multiply))]
+#![cfg_attr(test, allow(clippy::op_ref))]
+#![cfg_attr(test, allow(clippy::single_char_add_str))]
+#![cfg_attr(test, allow(clippy::unnecessary_cast))]
+#![cfg_attr(test, allow(clippy::unnecessary_literal_unwrap))]
+#![cfg_attr(test, allow(clippy::useless_format))]
 
 use std::any::Any;
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::default::Default;
+use std::collections::BTreeMap;
 
 pub mod running_integer;
 pub mod integer_window;
+pub mod time_bounded_window;
 pub mod integer_hier;
+pub mod integer_window_hier;
 
 pub mod running_time;
 pub mod time_window;
 pub mod time_hier;
+pub mod sized_time;
 
 pub mod running_float;
+pub mod running_float32;
+pub mod non_finite;
 pub mod float_window;
+pub mod float_time_window;
 pub mod float_hier;
 
 pub mod counter;
+pub mod peak_tracker;
+pub mod cardinality;
+pub mod cardinality_hier;
+pub mod running_ratio;
+pub mod correlation;
+pub mod duplicates;
+pub mod worst_tags;
+pub mod trim;
+pub mod decaying;
 pub mod arc_sets;
 pub mod rc_sets;
+pub mod rustics_set;
+pub mod eviction;
+pub mod sync_rustics;
+pub mod sharded;
+pub mod shared_rustics;
 pub mod hier;
 pub mod window;
 pub mod time;
 pub mod merge;
 pub mod sum;
+pub mod stats_math;
+pub mod window_moments;
+pub mod custom;
+pub mod median;
 pub mod log_histogram;
+pub mod log_linear_histogram;
 pub mod float_histogram;
+pub mod external_histogram;
+pub mod sketch_histogram;
+pub mod bucket_histogram;
+pub mod histogram_grid;
 
 pub mod printable;
+pub mod file_printer;
+pub mod capture_printer;
+pub mod prometheus;
+pub mod report;
+pub mod snapshot;
+pub mod columnar;
+pub mod checkpoint;
+pub mod aggregate;
+pub mod reporter;
+pub mod gate;
+pub mod golden;
+pub mod throttle;
+pub mod replay;
+pub mod record_hook;
+pub mod collection_time;
+pub mod slo;
+pub mod span_stats;
+pub mod pipeline;
 
 use hier::Hier;
 use hier::HierDescriptor;
@@ -210,7 +522,24 @@ use log_histogram::LogHistogram;
 use float_histogram::FloatHistogram;
 use float_histogram::HistoOpts;
 use printable::Printable;
+use printable::PrintFields;
 use time::Timer;
+use worst_tags::TaggedSample;
+use record_hook::RecordHookBox;
+
+// The moment math used to live here.  These names are kept so that
+// existing users of the crate root continue to build.
+
+pub use stats_math::StatisticsData;
+pub use stats_math::Statistics;
+pub use stats_math::RecoverData;
+pub use stats_math::EstimateData;
+pub use stats_math::compute_statistics;
+pub use stats_math::recover;
+pub use stats_math::estimate_moment_3;
+pub use stats_math::compute_variance;
+pub use stats_math::compute_skewness;
+pub use stats_math::compute_kurtosis;
 
 pub type PrinterBox         = Rc<RefCell<dyn Printer>>;
 // pub type PrinterBox         = Arc<Mutex<dyn Printer>>;
@@ -218,11 +547,45 @@ pub type PrinterOption      = Option<PrinterBox>;
 pub type TitleOption        = Option<String>;
 pub type UnitsOption        = Option<Units>;
 pub type HistoOption        = Option<HistoOpts>;
+pub type FieldsOption       = Option<PrintFields>;
 pub type TimerBox           = Rc<RefCell<dyn Timer>>;
 pub type PrintOption        = Option<PrintOpts>;
 pub type LogHistogramBox    = Rc<RefCell<LogHistogram>>;
 pub type FloatHistogramBox  = Rc<RefCell<FloatHistogram>>;
 
+/// Labels holds the key/value labels of a Rustics instance, like a
+/// shard id or region.  A BTreeMap keeps the exported order stable.
+
+pub type Labels             = BTreeMap<String, String>;
+
+/// Makes a copy of a LogHistogramBox that doesn't share the histogram
+/// with the original.  The Clone implementations use it to make deep
+/// copies.
+
+pub fn copy_log_histogram(histogram: &LogHistogramBox) -> LogHistogramBox {
+    Rc::from(RefCell::new(histogram.borrow().clone()))
+}
+
+/// Makes a copy of a FloatHistogramBox that doesn't share the
+/// histogram with the original.
+
+pub fn copy_float_histogram(histogram: &FloatHistogramBox) -> FloatHistogramBox {
+    Rc::from(RefCell::new(histogram.borrow().clone()))
+}
+
+/// The version of the export formats:  checkpoints, saved Hier
+/// instances, and CSV output.  It is incremented whenever a format
+/// changes in a way that older code can't read.
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Checks whether data written with the given schema version can be
+/// read by this version of the crate.
+
+pub fn schema_compatible(version: u32) -> bool {
+    (1..=SCHEMA_VERSION).contains(&version)
+}
+
 /// Extracts the mantissa from an f64.
 
 pub fn to_mantissa(input: f64) -> i64 {
@@ -332,210 +695,6 @@ pub fn stdout_printer() -> PrinterBox {
     printer_box!(printer)
 }
 
-/// Provides the data for estimating the second and fourth moments
-/// about the mean, as well as the mean itself.
-
-pub struct StatisticsData {
-    pub n:        f64,
-    pub sum:      f64,
-    pub squares:  f64,
-    pub cubes:    f64,
-    pub quads:    f64,
-}
-
-/// Contains the return data for compute_statistics.
-
-pub struct Statistics {
-    pub mean:     f64,
-    pub moment_2: f64,
-    pub moment_4: f64,
-}
-
-/// Computes the second and fourth moments about the mean
-/// given the values in StatisticsData.  This is used when
-/// merging multiple Rustics instances for upper-level
-/// HierMember instances.
-///
-/// The formulae are derived by applying the binomial
-/// theorem to the formulae for the various moments about
-/// the mean.
-
-pub fn compute_statistics(data: StatisticsData) -> Statistics {
-    let n       = data.n;
-    let sum     = data.sum;
-    let squares = data.squares;
-    let cubes   = data.cubes;
-    let quads   = data.quads;
-
-    let mean    = data.sum / n;
-    let mean_1  = mean;
-    let mean_2  = mean.powi(2);
-    let mean_3  = mean.powi(3);
-    let mean_4  = mean.powi(4);
-
-    let moment_2 =
-                       squares
-      - 2.0 * mean_1 * sum
-      +       mean_2 * n;
-
-    let moment_4 =
-                       quads
-      - 4.0 * mean_1 * cubes
-      + 6.0 * mean_2 * squares
-      - 4.0 * mean_3 * sum
-      +       mean_4 * n;
-
-    Statistics { mean, moment_2, moment_4 }
-}
-
-/// Provides the data required to try to recover the sum of
-/// the squares and the sum of the fourth power of each of
-/// the data samples.
-
-pub struct RecoverData {
-    pub n:          f64,
-    pub mean:       f64,
-    pub moment_2:   f64,
-    pub cubes:      f64,
-    pub moment_4:   f64,
-}
-
-/// This routine converts the data in RecoverData into estimators
-/// of the sum of the squares and 4th power of each sample.  This
-/// is used when merging Rustics instances for use in a Hier instance.
-///
-/// The formulae are derived by applying the binomial theorem to
-/// the definition of the various moments about the mean.
-
-pub fn recover(data: RecoverData) -> (f64, f64) {
-    let n        = data.n;
-    let mean     = data.mean;
-    let moment_2 = data.moment_2;
-    let cubes    = data.cubes;
-    let moment_4 = data.moment_4;
-
-    let sum      = n * mean;
-    let squares  = moment_2 + 2.0 * mean * sum - n * mean.powi(2);
-
-    let mean_1   = mean;
-    let mean_2   = mean.powi(2);
-    let mean_3   = mean.powi(3);
-    let mean_4   = mean.powi(4);
-
-    let quads =
-                   moment_4
-          + (4.0 * cubes    * mean_1)
-          - (6.0 * squares  * mean_2)
-          + (4.0 * sum      * mean_3)
-          - (      n        * mean_4);
-
-    (squares, quads)
-}
-
-/// Provides the data required to try to estimate the
-/// third moment about the mean.
-
-pub struct EstimateData {
-    pub n:          f64,
-    pub mean:       f64,
-    pub moment_2:   f64,
-    pub cubes:      f64,
-}
-
-/// Estimates moment 3 about the mean.
-///
-/// I know of no good way to keep a running estimate of
-/// the 3rd moment about the mean, so this is the best
-/// I can do.  The equations for the squares and the
-/// third moment about the mean are from the binomial
-/// theorem applied to the definition of those moments.
-
-pub fn estimate_moment_3(data: EstimateData) -> f64 {
-    let n         = data.n;
-    let mean      = data.mean;
-    let cubes     = data.cubes;
-    let moment_2  = data.moment_2;
-    let sum       = n * mean;
-
-    // Estimate the sums of the squares of each sample.
-
-    let squares =
-        moment_2
-      + 2.0 * sum * mean
-      -       n   * mean.powi(2);
-
-    // Now estimate the third moment about the mean.
-
-    cubes - (3.0 * squares * mean) + 3.0 * (sum * mean.powi(2)) - n * mean.powi(3)
-}
-
-/// Computes a variance estimator.
-
-pub fn compute_variance(count: u64, moment_2: f64) -> f64 {
-    if count < 2 {
-        return 0.0;
-    }
-
-    let n = count as f64;
-
-    moment_2 / (n - 1.0)
-}
-
-/// Computes the sample skewness.
-///
-/// This formula is from brownmath.com.
-
-pub fn compute_skewness(count: u64, moment_2: f64, moment_3: f64) -> f64 {
-    if count < 3 || moment_2 == 0.0 {
-        return 0.0;
-    }
-
-    // Deal with floating point non-finite values.
-
-    // For debugging new Rustics types.
-    //
-    //assert!(moment_2 > 0.0);
-
-    if moment_2 <= 0.0 {
-        return 0.0;
-    }
-
-    let n          = count as f64;
-    let m3         = moment_3 / n;
-    let m2         = moment_2 / n;
-    let skewness   = m3 / m2.powf(1.5);
-    let correction = (n * (n - 1.0)).sqrt() / (n - 2.0);
-
-    skewness * correction
-}
-
-/// Computes the sample kurtosis estimator.
-///
-/// This formula is from brownmath.com.
-
-pub fn compute_kurtosis(count: u64, moment_2: f64, moment_4: f64) -> f64 {
-    if count < 4 || moment_2 == 0.0 {
-        return 0.0;
-    }
-
-    // Deal with floating point non-finite values.
-
-    // For debugging new Rustics types.
-    //
-    // assert!(moment_2 > 0.0 && moment_4 >= 0.0);
-
-    if moment_2 <= 0.0 || moment_4 <= 0.0 {
-        return 0.0;
-    }
-
-    let n               = count as f64;
-    let kurtosis        = moment_4 / (moment_2.powf(2.0) / n) - 3.0;
-    let correction      = (n - 1.0) / ((n - 2.0) * (n - 3.0));
-    let kurtosis_factor = (n + 1.0) * kurtosis + 6.0;
-
-    correction * kurtosis_factor
-}
-
 /// The make_title() function concatenates two strings, inserting the
 /// "=>" marker for set hierarchy specification.  It is probably of
 /// interest only to implementors of new Rustics types.  It does
@@ -593,6 +752,136 @@ pub struct PrintOpts {
     pub title:       TitleOption,
     pub units:       UnitsOption,
     pub histo_opts:  HistoOption,
+    pub fields:      FieldsOption,
+}
+
+impl PrintOpts {
+    /// Returns a builder for a PrintOpts instance.  Any option that
+    /// isn't set is None, so that the default is used.
+    ///
+    ///```
+    ///     use rustics::PrintOpts;
+    ///     use rustics::Units;
+    ///     use rustics::stdout_printer;
+    ///     use rustics::running_integer::RunningInteger;
+    ///
+    ///     let print_opts =
+    ///         PrintOpts::builder()
+    ///             .printer(stdout_printer())
+    ///             .title("Packet Sizes")
+    ///             .units(Units::new("byte", "bytes"))
+    ///             .build();
+    ///
+    ///     let sizes = RunningInteger::new("Sizes", &Some(print_opts));
+    ///```
+
+    pub fn builder() -> PrintOptsBuilder {
+        PrintOptsBuilder::new()
+    }
+
+    /// Creates a PrintOpts that sets only the units.
+
+    pub fn with_units(singular: &str, plural: &str) -> PrintOpts {
+        PrintOpts::builder().units(Units::new(singular, plural)).build()
+    }
+
+    /// Creates a PrintOpts that sets only the title.
+
+    pub fn with_title(title: &str) -> PrintOpts {
+        PrintOpts::builder().title(title).build()
+    }
+
+    /// Creates a PrintOpts that sets only the printer.
+
+    pub fn with_printer(printer: PrinterBox) -> PrintOpts {
+        PrintOpts::builder().printer(printer).build()
+    }
+
+    /// Creates a PrintOpts that sets only the histogram options.
+
+    pub fn with_histo_opts(histo_opts: HistoOpts) -> PrintOpts {
+        PrintOpts::builder().histo_opts(histo_opts).build()
+    }
+}
+
+impl Default for PrintOpts {
+    fn default() -> PrintOpts {
+        let printer    = None;
+        let title      = None;
+        let units      = None;
+        let histo_opts = None;
+        let fields     = None;
+
+        PrintOpts { printer, title, units, histo_opts, fields }
+    }
+}
+
+/// PrintOptsBuilder builds a PrintOpts instance one option at a
+/// time.  See PrintOpts::builder().
+
+#[derive(Clone, Default)]
+pub struct PrintOptsBuilder {
+    print_opts: PrintOpts,
+}
+
+impl PrintOptsBuilder {
+    pub fn new() -> PrintOptsBuilder {
+        let print_opts = PrintOpts::default();
+
+        PrintOptsBuilder { print_opts }
+    }
+
+    /// Sets the printer.
+
+    pub fn printer(mut self, printer: PrinterBox) -> PrintOptsBuilder {
+        self.print_opts.printer = Some(printer);
+        self
+    }
+
+    /// Sets the title.
+
+    pub fn title(mut self, title: &str) -> PrintOptsBuilder {
+        self.print_opts.title = Some(title.to_string());
+        self
+    }
+
+    /// Sets the units.
+
+    pub fn units(mut self, units: Units) -> PrintOptsBuilder {
+        self.print_opts.units = Some(units);
+        self
+    }
+
+    /// Sets the histogram options.
+
+    pub fn histo_opts(mut self, histo_opts: HistoOpts) -> PrintOptsBuilder {
+        self.print_opts.histo_opts = Some(histo_opts);
+        self
+    }
+
+    /// Sets the fields to print.
+    ///
+    ///```
+    ///     use rustics::PrintOpts;
+    ///     use rustics::printable::PrintField;
+    ///     use rustics::printable::PrintFields;
+    ///
+    ///     // Print just three lines per instance.
+    ///
+    ///     let fields     = [ PrintField::Count, PrintField::Mean, PrintField::Maximum ];
+    ///     let print_opts = PrintOpts::builder().fields(PrintFields::only(&fields)).build();
+    ///```
+
+    pub fn fields(mut self, fields: PrintFields) -> PrintOptsBuilder {
+        self.print_opts.fields = Some(fields);
+        self
+    }
+
+    /// Returns the PrintOpts instance.
+
+    pub fn build(self) -> PrintOpts {
+        self.print_opts
+    }
 }
 
 /// The Printer trait allows users to create custom output functions to
@@ -685,6 +974,22 @@ pub fn parse_histo_opts(print_opts: &PrintOption) -> HistoOpts {
     }
 }
 
+/// Returns the set of fields to print in a PrintOption instance, if
+/// present, or all the fields if no set was specified.
+
+pub fn parse_fields(print_opts: &PrintOption) -> PrintFields {
+    match print_opts {
+        Some(print_opts) => {
+            match &print_opts.fields {
+                Some(fields) => { *fields            }
+                None         => { PrintFields::all() }
+            }
+        }
+
+        None => { PrintFields::all() }
+    }
+}
+
 /// Returns the units in a PrintOption instance, if given, or
 /// returns the defaults if no units were specified.
 
@@ -805,6 +1110,14 @@ pub trait Rustics {
 
     fn record_f64(&mut self, sample: f64);
 
+    /// Records an f32 value.  By default, the value is converted to
+    /// f64, which is exact, and passed to record_f64().  RunningFloat32
+    /// keeps its statistics in f32.
+
+    fn record_f32(&mut self, sample: f32) {
+        self.record_f64(sample as f64);
+    }
+
     /// Records an event.  This method is implementation-specific
     /// in meaning.  For the Counter type, it is is equivalent to
     /// record_i64(1).
@@ -845,7 +1158,8 @@ pub trait Rustics {
     fn title(&self)-> String;
 
     /// Returns the class of the statistic.  Currently, "integer", "counter",
-    /// "float", and "time" classes exist.
+    /// "float", "time", "cardinality", "peak", "ratio", "correlation", and
+    /// "decaying" classes exist.
 
     fn class(&self) -> &str;
 
@@ -879,6 +1193,121 @@ pub trait Rustics {
 
     fn kurtosis(&self) -> f64;
 
+    /// Returns a boolean indicating whether the instance supports the
+    /// median() and mad() methods.  RunningInteger and RunningFloat
+    /// support them once enable_median() has been invoked.
+
+    fn has_median(&self) -> bool {
+        false
+    }
+
+    /// Returns an estimate of the median of the samples.  See the
+    /// median module.
+
+    fn median(&self) -> f64 {
+        panic!("Rustics::median:  not supported");
+    }
+
+    /// Returns an estimate of the median absolute deviation of the
+    /// samples.
+
+    fn mad(&self) -> f64 {
+        panic!("Rustics::mad:  not supported");
+    }
+
+    /// Returns a boolean indicating whether the instance keeps a
+    /// trimmed mean and an outlier count.  RunningInteger, RunningFloat,
+    /// and RunningTime do so once enable_trimming() has been invoked.
+
+    fn has_trimming(&self) -> bool {
+        false
+    }
+
+    /// Returns the mean of the samples after dropping the percentage
+    /// given in the TrimOpts from each end.  See the trim module.
+
+    fn trimmed_mean(&self) -> f64 {
+        panic!("Rustics::trimmed_mean:  not supported");
+    }
+
+    /// Returns the number of samples more than the number of standard
+    /// deviations given in the TrimOpts from the mean.
+
+    fn outliers(&self) -> u64 {
+        panic!("Rustics::outliers:  not supported");
+    }
+
+    /// Returns a boolean indicating whether the instance keeps the tags
+    /// of its largest samples.  RunningInteger and RunningTime do so
+    /// once enable_worst_tags() has been invoked.
+
+    fn has_worst_tags(&self) -> bool {
+        false
+    }
+
+    /// Records an i64 sample along with a tag, like a request id.  If
+    /// the sample is among the largest seen, the tag is kept for
+    /// worst_tags().  See the worst_tags module.
+
+    fn record_i64_tagged(&mut self, _sample: i64, _tag: u64) {
+        panic!("Rustics::record_i64_tagged:  not supported");
+    }
+
+    /// Records a time sample in ticks along with a tag.  See
+    /// record_i64_tagged().
+
+    fn record_time_tagged(&mut self, _sample: i64, _tag: u64) {
+        panic!("Rustics::record_time_tagged:  not supported");
+    }
+
+    /// Returns the largest tagged samples, from the largest down.
+
+    fn worst_tags(&self) -> Vec<TaggedSample> {
+        panic!("Rustics::worst_tags:  not supported");
+    }
+
+    /// Returns true if the type can pass its samples to a RecordHook.
+    /// See the record_hook module.
+
+    fn supports_record_hook(&self) -> bool {
+        false
+    }
+
+    /// Installs a hook that receives each sample recorded, or removes
+    /// the hook if None is given.
+
+    fn set_record_hook(&mut self, _hook: Option<RecordHookBox>) {
+        panic!("Rustics::set_record_hook:  not supported");
+    }
+
+    /// Returns the hook installed, if any.
+
+    fn record_hook(&self) -> Option<RecordHookBox> {
+        None
+    }
+
+    /// Returns true if the type can report how long it has been
+    /// collecting samples.  See the collection_time module.
+
+    fn supports_collection_time(&self) -> bool {
+        false
+    }
+
+    /// Attaches a clock that measures the collection period, or
+    /// removes the clock if None is given.  The period starts now,
+    /// and restarts when the instance is cleared.
+
+    fn set_collection_clock(&mut self, _clock: Option<TimerBox>) {
+        panic!("Rustics::set_collection_clock:  not supported");
+    }
+
+    /// Returns the length of the collection period in seconds, if a
+    /// clock is attached.
+
+    fn collection_seconds(&self) -> Option<f64> {
+        None
+    }
+
     /// Returns a boolean indicating whether the underlying type supports
     /// the min_i64() and max_i64() methods.
 
@@ -920,6 +1349,26 @@ pub trait Rustics {
 
     fn clear(&mut self);
 
+    /// Returns true if the type supports reset_extremes().  The window
+    /// types don't, since their extremes cover only the samples in the
+    /// window already.
+
+    fn supports_reset_extremes(&self) -> bool {
+        false
+    }
+
+    /// Resets the minimum and maximum while keeping the count, mean,
+    /// and other summary statistics.  The extremes then cover only the
+    /// samples recorded after the reset, so a long-running instance can
+    /// restart them periodically.  Until a new sample is recorded, the
+    /// extremes have the values of a cleared instance.  The running
+    /// types and Hier instances with running members support this
+    /// method.
+
+    fn reset_extremes(&mut self) {
+        panic!("Rustics::reset_extremes:  not supported");
+    }
+
     /// Returns the statistics for the sample stream.
 
     fn export_stats(&self) -> ExportStats;
@@ -934,6 +1383,23 @@ pub trait Rustics {
 
     fn set_title (&mut self, title: &str);
 
+    /// Sets a key/value label on the instance, replacing any value
+    /// already set for the key.  Labels carry dimensions like a shard
+    /// id without encoding them in the title, and are emitted by the
+    /// prometheus module.  RunningInteger, RunningFloat, RunningTime,
+    /// the window types, Counter, and Hier support labels.
+
+    fn set_label(&mut self, _key: &str, _value: &str) {
+        panic!("Rustics::set_label:  not supported");
+    }
+
+    /// Returns the labels of the instance.  Types without label
+    /// support return an empty map.
+
+    fn labels(&self) -> Labels {
+        Labels::new()
+    }
+
     /// Returns a LogHistogramBox for the histogram if possible.
 
     fn log_histogram  (&self) -> Option<LogHistogramBox>;
@@ -1681,10 +2147,18 @@ mod tests {
         let merge_min    = 24;
         let merge_max    = 28;
         let no_zero_rows = true;
-        let histo_opts   = Some(HistoOpts { merge_min, merge_max, no_zero_rows });
+        let no_histogram = false;
+        let print_cdf    = false;
+        let bar_chart    = false;
+        let bar_width    = 60;
+        let histo_opts   =
+            HistoOpts {
+                merge_min, merge_max, no_zero_rows, no_histogram, print_cdf, bar_chart, bar_width
+            };
+        let histo_opts   = Some(histo_opts);
         let units        = bytes();
 
-        let print_opts = Some(PrintOpts { printer, title, histo_opts, units });
+        let print_opts = Some(PrintOpts { printer, title, histo_opts, units, ..Default::default() });
 
         let _     = parse_printer   (&print_opts);
         let title = parse_title     (&print_opts, "default");
@@ -1702,7 +2176,7 @@ mod tests {
         let title      = None;
         let histo_opts = None;
         let units      = None;
-        let print_opts = Some(PrintOpts { printer, title, histo_opts, units });
+        let print_opts = Some(PrintOpts { printer, title, histo_opts, units, ..Default::default() });
 
         let _          = parse_printer   (&print_opts);
         let title      = parse_title     (&print_opts, "default");
@@ -1717,6 +2191,74 @@ mod tests {
         assert!( title           == "default");
     }
 
+    fn test_print_opts_builder() {
+        let histo_opts = HistoOpts { print_cdf: true, ..Default::default() };
+
+        let print_opts =
+            PrintOpts::builder()
+                .title("Built Title")
+                .units(Units::new("packet", "packets"))
+                .histo_opts(histo_opts)
+                .build();
+
+        let print_opts = Some(print_opts);
+
+        assert!(parse_title(&print_opts, "name")  == "Built Title");
+        assert!(parse_units(&print_opts).singular == "packet"     );
+        assert!(parse_units(&print_opts).plural   == "packets"    );
+        assert!(parse_histo_opts(&print_opts).print_cdf);
+
+        // Unset options take the defaults.
+
+        let print_opts = Some(PrintOpts::with_units("byte", "bytes"));
+
+        assert!(parse_title(&print_opts, "name")  == "name" );
+        assert!(parse_units(&print_opts).plural   == "bytes");
+        assert!(!parse_histo_opts(&print_opts).print_cdf);
+
+        let print_opts = Some(PrintOpts::with_title("Title"));
+
+        assert!(parse_title(&print_opts, "name") == "Title");
+        assert!(parse_units(&print_opts).singular.is_empty());
+
+        let printer    = stdout_printer();
+        let print_opts = PrintOpts::with_printer(printer.clone());
+
+        assert!(Rc::ptr_eq(&print_opts.printer.unwrap(), &printer));
+
+        let print_opts = PrintOpts::with_histo_opts(histo_opts);
+
+        assert!(print_opts.histo_opts.unwrap().print_cdf);
+        assert!(print_opts.title.is_none());
+
+        let print_opts = PrintOpts::default();
+
+        assert!(print_opts.printer.is_none());
+        assert!(print_opts.units.is_none());
+        assert!(print_opts.fields.is_none());
+
+        // The fields are kept apart from the histogram options.
+
+        let fields     = PrintFields::only(&[ printable::PrintField::Count ]);
+        let histo_opts = HistoOpts::builder().no_zero_rows(true).bar_width(20).build();
+
+        let print_opts =
+            PrintOpts::builder()
+                .fields(fields)
+                .histo_opts(histo_opts)
+                .build();
+
+        let print_opts = Some(print_opts);
+        let histo_opts = parse_histo_opts(&print_opts);
+
+        assert!(parse_fields(&print_opts) == fields);
+        assert!(parse_fields(&None) == PrintFields::all());
+        assert!(histo_opts.no_zero_rows);
+        assert!(histo_opts.bar_width == 20);
+        assert!(!histo_opts.print_histogram(fields));
+        assert!(histo_opts.print_histogram(PrintFields::all()));
+    }
+
     fn test_stdio_printer() {
         let mut printer = StdioPrinter::new(StreamKind::Stderr);
 
@@ -1771,14 +2313,6 @@ mod tests {
         assert!(timer_mut!(timer).finish() == hz as i64);
     }
 
-    fn test_math() {
-        assert!(compute_kurtosis(4,  0.0, 0.0) == 0.0);
-        assert!(compute_kurtosis(4,  1.0, 0.0) == 0.0);
-        assert!(compute_kurtosis(4, -1.0, 0.0) == 0.0);
-        assert!(compute_skewness(4,  0.0, 0.0) == 0.0);
-        assert!(compute_skewness(4, -1.0, 0.0) == 0.0);
-    }
-
     fn test_printers() {
         let stdout_box = stdout_printer();
         let stdout     = printer_mut!(stdout_box);
@@ -1836,8 +2370,8 @@ mod tests {
         test_units                ();
         test_parsing              ();
         test_stdio_printer        ();
+        test_print_opts_builder   ();
         test_check_printer_forgive();
-        test_math                 ();
         test_verbose_check_printer();
         test_printers             ();
     }
@@ -19,6 +19,17 @@
 //!
 //!     * The pseudo-log of zero is defined as zero.
 //!
+//!     * print_opts() takes a HistoOpts struct.  When no_zero_rows is set,
+//!       each run of rows containing only zero counts is collapsed into
+//!       a single "..." line, so histograms of narrow distributions don't
+//!       print mostly-zero rows.  The integer statistics types pass their
+//!       histogram options through to this method.
+//!
+//!     * When bar_chart is set, the buckets are printed one per row as a
+//!       horizontal bar chart, with the largest count scaled to bar_width
+//!       characters.  This is easier to read at a glance than the rows
+//!       of counts.
+//!
 //! ## Example
 //!```
 //!
@@ -79,7 +90,11 @@ use super::Histogram;
 use super::LogHistogramBox;
 use super::FloatHistogramBox;
 use super::Printer;
+use super::float_histogram::HistoOpts;
+use super::float_histogram::print_bar_chart;
 use super::printable::Printable;
+use super::checkpoint::Checkpoint;
+use super::checkpoint::CheckpointReader;
 
 // pseudo_log_index() returns an array index to record a log value in
 // a histogram.  Callers are expected to use two arrays, one for
@@ -129,6 +144,42 @@ pub fn pseudo_log_index(value: i64) -> usize {
     log
 }
 
+/// Returns the largest value that is recorded into the given bucket.
+/// The "negative" parameter selects the array for negative samples.
+
+pub fn bucket_max(index: usize, negative: bool) -> i64 {
+    if negative {
+        match index {
+            0 => -1,
+            1 => -2,
+            _ => -(1_i64 << (index - 1)) - 1,
+        }
+    } else if index >= 63 {
+        i64::MAX
+    } else {
+        1_i64 << index
+    }
+}
+
+/// Returns the smallest value that is recorded into the given bucket.
+/// The "negative" parameter selects the array for negative samples.
+
+pub fn bucket_min(index: usize, negative: bool) -> i64 {
+    if negative {
+        match index {
+            0 => -1,
+            _ if index >= 63 => i64::MIN,
+            _ => -(1_i64 << index),
+        }
+    } else {
+        match index {
+            0 => 0,
+            _ if index >= 63 => (1_i64 << 62) + 1,
+            _ => (1_i64 << (index - 1)) + 1,
+        }
+    }
+}
+
 /// LogHistogram is the implementation type for the integer
 /// pseudo-log histogram.
 
@@ -156,9 +207,75 @@ impl LogHistogram {
         }
     }
 
+    /// Removes a sample value that was recorded earlier, as when a
+    /// sample leaves a window.
+
+    pub fn remove(&mut self, sample: i64) {
+        let bucket =
+            if sample < 0 {
+                &mut self.negative[pseudo_log_index(sample)]
+            } else {
+                &mut self.positive[pseudo_log_index(sample)]
+            };
+
+        if *bucket == 0 {
+            panic!("LogHistogram::remove:  The sample {} was not recorded.", sample);
+        }
+
+        *bucket -= 1;
+    }
+
+    /// Records a sample value with a repeat count.  This method is
+    /// used to merge data from other histogram types.
+
+    pub fn record_count(&mut self, sample: i64, count: u64) {
+        if sample < 0 {
+            self.negative[pseudo_log_index(sample)] += count;
+        } else {
+            self.positive[pseudo_log_index(sample)] += count;
+        }
+    }
+
+    // Prints one row of four buckets.  If zero rows are being
+    // suppressed, a run of them is replaced by a single marker line.
+
+    fn print_row(printer: &mut dyn Printer, label: i64, counts: [u64; 4], skip: &mut Option<bool>) {
+        if let Some(skipping) = skip {
+            if counts.iter().all(|count| *count == 0) {
+                if !*skipping {
+                    printer.print("    ...");
+                }
+
+                *skipping = true;
+                return;
+            }
+
+            *skipping = false;
+        }
+
+        printer.print(&format!("  {:>3}:    {:>14}    {:>14}    {:>14}    {:>14}",
+            label,
+            Printable::commas_u64(counts[0]),
+            Printable::commas_u64(counts[1]),
+            Printable::commas_u64(counts[2]),
+            Printable::commas_u64(counts[3])
+        ));
+    }
+
+    // Returns the state for print_row, which is None if all rows are
+    // to be printed.
+
+    fn skip_state(histo_opts: &HistoOpts) -> Option<bool> {
+        if histo_opts.no_zero_rows {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
     // This helper method prints the negative buckets.
 
-    fn print_negative(&self, printer: &mut dyn Printer) {
+    fn print_negative(&self, printer: &mut dyn Printer, histo_opts: &HistoOpts) {
         // Skip printing buckets that would appear before the first non-zero bucket.
         // So find the non-zero bucket with the highest index in the array.
 
@@ -179,18 +296,16 @@ impl LogHistogram {
         let     start_index = ((i + 4) / 4) * 4 - 1;
         let mut i           = start_index + 4;
         let mut rows        = (start_index + 1) / 4;
+        let mut skip        = Self::skip_state(histo_opts);
 
         while rows > 0 {
             assert!(i >= 3 && i < self.negative.len());
             i -= 4;
 
-            printer.print(&format!("  {:>3}:    {:>14}    {:>14}    {:>14}    {:>14}",
-                -(i as i64) + 3,
-                Printable::commas_u64(self.negative[i - 3]),
-                Printable::commas_u64(self.negative[i - 2]),
-                Printable::commas_u64(self.negative[i - 1]),
-                Printable::commas_u64(self.negative[i])
-            ));
+            let counts =
+                [ self.negative[i - 3], self.negative[i - 2], self.negative[i - 1], self.negative[i] ];
+
+            Self::print_row(printer, -(i as i64) + 3, counts, &mut skip);
 
             rows -= 1;
         }
@@ -198,25 +313,24 @@ impl LogHistogram {
 
     // This helper method prints the positive buckets.
 
-    fn print_positive(&self, printer: &mut dyn Printer) {
+    fn print_positive(&self, printer: &mut dyn Printer, histo_opts: &HistoOpts) {
         let mut last = self.positive.len() - 1;
 
         while last > 0 && self.positive[last] == 0 {
             last -= 1;
         }
 
-        let stop_index = last;
-        let mut i = 0;
+        let     stop_index = last;
+        let mut i          = 0;
+        let mut skip       = Self::skip_state(histo_opts);
 
         while i <= stop_index {
             assert!(i <= self.positive.len() - 4);
 
-            printer.print(&format!("  {:>3}:    {:>14}    {:>14}    {:>14}    {:>14}",
-                i,
-                Printable::commas_u64(self.positive[i]),
-                Printable::commas_u64(self.positive[i + 1]),
-                Printable::commas_u64(self.positive[i + 2]),
-                Printable::commas_u64(self.positive[i + 3])));
+            let counts =
+                [ self.positive[i], self.positive[i + 1], self.positive[i + 2], self.positive[i + 3] ];
+
+            Self::print_row(printer, i as i64, counts, &mut skip);
 
             i += 4;
         }
@@ -245,12 +359,126 @@ impl LogHistogram {
         mode
     }
 
+    /// Prints the histogram with the default options.
+
     pub fn print(&self, printer: &mut dyn Printer) {
+        self.print_opts(printer, &HistoOpts::default());
+    }
+
+    /// Prints the histogram using the given options.  Only the
+    /// no_zero_rows, bar_chart, and bar_width options affect the
+    /// output.
+
+    pub fn print_opts(&self, printer: &mut dyn Printer, histo_opts: &HistoOpts) {
         printer.print("  Log Histogram");
-        self.print_negative(printer);
+
+        if histo_opts.bar_chart {
+            self.print_bars(printer, histo_opts);
+            return;
+        }
+
+        self.print_negative(printer, histo_opts);
 
         printer.print("  -----------------------");
-        self.print_positive(printer);
+        self.print_positive(printer, histo_opts);
+    }
+
+    // Prints the buckets as a bar chart.  Each row is labeled with
+    // the bound of the bucket furthest from zero.
+
+    fn print_bars(&self, printer: &mut dyn Printer, histo_opts: &HistoOpts) {
+        let mut rows = Vec::with_capacity(self.negative.len() + self.positive.len());
+
+        for i in (0..self.negative.len()).rev() {
+            rows.push((Printable::commas_i64(bucket_min(i, true)), self.negative[i]));
+        }
+
+        for i in 0..self.positive.len() {
+            rows.push((Printable::commas_i64(bucket_max(i, false)), self.positive[i]));
+        }
+
+        print_bar_chart(printer, &rows, histo_opts);
+    }
+
+    /// Returns the cumulative distribution of the samples as a list
+    /// of (bucket maximum, cumulative percentage) pairs.  There is one
+    /// entry for each non-empty bucket, in increasing order.
+
+    pub fn cdf(&self) -> Vec<(i64, f64)> {
+        self.cdf_rows(100.0).iter().map(|(bound, _, percent)| (*bound, *percent)).collect()
+    }
+
+    /// Returns the cumulative distribution as (bucket maximum,
+    /// cumulative fraction) pairs, with fractions from 0 to 1, for
+    /// plotting.  The entries match those returned by cdf().
+
+    pub fn cdf_fractions(&self) -> Vec<(i64, f64)> {
+        self.cdf_rows(1.0).iter().map(|(bound, _, fraction)| (*bound, *fraction)).collect()
+    }
+
+    // Compute a (bucket maximum, count, cumulative fraction * scale)
+    // row for each non-empty bucket.
+
+    fn cdf_rows(&self, scale: f64) -> Vec<(i64, u64, f64)> {
+        let     total: u64 = self.negative.iter().sum::<u64>() + self.positive.iter().sum::<u64>();
+        let mut result     = Vec::new();
+        let mut sum        = 0;
+
+        if total == 0 {
+            return result;
+        }
+
+        for i in (0..self.negative.len()).rev() {
+            if self.negative[i] > 0 {
+                sum += self.negative[i];
+                result.push((bucket_max(i, true), self.negative[i], sum as f64 * scale / total as f64));
+            }
+        }
+
+        for i in 0..self.positive.len() {
+            if self.positive[i] > 0 {
+                sum += self.positive[i];
+                result.push((bucket_max(i, false), self.positive[i], sum as f64 * scale / total as f64));
+            }
+        }
+
+        result
+    }
+
+    /// Prints the cumulative distribution as a table giving the count
+    /// in each non-empty bucket next to the percentage of the samples
+    /// at or below the bucket boundary.
+
+    pub fn print_cdf(&self, printer: &mut dyn Printer) {
+        printer.print("  Cumulative Distribution");
+
+        for (value, count, percent) in self.cdf_rows(100.0) {
+            let value = format!("<= {}", Printable::commas_i64(value));
+
+            Self::print_cdf_row(printer, &value, count, percent);
+        }
+    }
+
+    /// Prints the cumulative distribution with the bucket boundaries
+    /// converted to times.  The hz parameter gives the clock frequency.
+
+    pub fn print_cdf_times(&self, hz: i64, printer: &mut dyn Printer) {
+        printer.print("  Cumulative Distribution");
+
+        for (value, count, percent) in self.cdf_rows(100.0) {
+            let (time, unit) = Printable::scale_time(value as f64, hz);
+            let  value       = format!("<= {:.3} {}", time, unit);
+
+            Self::print_cdf_row(printer, &value, count, percent);
+        }
+    }
+
+    // Prints one row of the cumulative distribution table.
+
+    fn print_cdf_row(printer: &mut dyn Printer, value: &str, count: u64, percent: f64) {
+        let count = Printable::commas_u64(count);
+
+        printer.print(&format!("    {:>26}    {:>14}    {:>8.3}%", value, count, percent));
     }
 
     /// Clears the histogram back to its initial state.
@@ -260,6 +488,54 @@ impl LogHistogram {
         self.positive = [0; 64];
     }
 
+    /// Saves the bucket counts as a string.  See the checkpoint module.
+
+    pub fn checkpoint(&self) -> String {
+        let mut checkpoint = Checkpoint::new("log_histogram");
+
+        checkpoint.push_log_histogram("buckets", self);
+        checkpoint.text()
+    }
+
+    /// Restores a histogram saved by checkpoint().
+
+    pub fn restore(text: &str) -> Option<LogHistogram> {
+        let reader = CheckpointReader::new_kind(text, "log_histogram")?;
+
+        reader.log_histogram("buckets")
+    }
+
+    /// Estimates the given percentile, which is in the range 0 to 100.
+    /// The result is the largest value that can be recorded into the
+    /// bucket containing the percentile, so it is an upper bound.  An
+    /// empty histogram returns zero.
+
+    pub fn percentile(&self, percentile: f64) -> i64 {
+        Printable::log_percentile(self, percentile)
+    }
+
+    /// Returns an iterator over all the buckets in increasing order of
+    /// value.  Each item is a (lower bound, upper bound, count) tuple,
+    /// and both bounds are inclusive.
+
+    pub fn iter_buckets(&self) -> impl Iterator<Item = (i64, i64, u64)> + '_ {
+        let negative =
+            (0..self.negative.len()).rev()
+                .map(|i| (bucket_min(i, true), bucket_max(i, true), self.negative[i]));
+
+        let positive =
+            (0..self.positive.len())
+                .map(|i| (bucket_min(i, false), bucket_max(i, false), self.positive[i]));
+
+        negative.chain(positive)
+    }
+
+    /// Returns the total number of samples in the histogram.
+
+    pub fn samples(&self) -> u64 {
+        self.negative.iter().sum::<u64>() + self.positive.iter().sum::<u64>()
+    }
+
     pub fn equals(&self, other: &LogHistogram) -> bool {
         for i in 0..other.negative.len() {
             if self.negative[i] != other.negative[i] {
@@ -306,6 +582,8 @@ mod tests {
     use super::*;
     use crate::stdout_printer;
     use crate::printer_mut;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_counters;
 
     pub fn test_log_histogram() {
         let mut histogram = LogHistogram::new();
@@ -375,6 +653,12 @@ mod tests {
         let sum = sum_histogram(&histogram);
 
         assert!(sum == 0);
+
+        histogram.record_count( 5, 3);
+        histogram.record_count(-5, 4);
+
+        assert!(histogram.positive[3] == 3);
+        assert!(histogram.negative[3] == 4);
     }
 
     #[test]
@@ -392,6 +676,21 @@ mod tests {
     }
 
 
+    fn test_bucket_max() {
+        let test = [ 0, 1, 2, 3, 4, 5, 8, 9, 1000, i64::MAX, -1, -2, -3, -4, -5, -1000, i64::MIN ];
+
+        for sample in test.iter() {
+            let index = pseudo_log_index(*sample);
+            let max   = bucket_max(index, *sample < 0);
+
+            assert!(*sample <= max);
+            assert!(pseudo_log_index(max) == index);
+        }
+
+        assert!(bucket_max(10, false) ==  1024);
+        assert!(bucket_max(10, true ) == -513);
+    }
+
     pub fn test_pseudo_log() {
         let test   = [ 1, 0, -1, -4, -3, i64::MIN, 3, 4, 5, 8, i64::MAX ];
         let expect = [ 0, 0,  0,  2,  2,       63, 2, 2, 3, 3,       63 ];
@@ -406,6 +705,157 @@ mod tests {
         }
     }
 
+    fn test_cdf() {
+        let mut histogram = LogHistogram::new();
+
+        assert!(histogram.cdf().is_empty());
+
+        for sample in [ -3, 1, 1, 4 ] {
+            histogram.record(sample);
+        }
+
+        let cdf = histogram.cdf();
+
+        assert!(cdf == vec![ (-3, 25.0), (1, 75.0), (4, 100.0) ]);
+
+        let fractions = histogram.cdf_fractions();
+
+        assert!(fractions == vec![ (-3, 0.25), (1, 0.75), (4, 1.0) ]);
+
+        let expected =
+            [
+                "  Cumulative Distribution",
+                "                         <= -3                 1      25.000%",
+                "                          <= 1                 2      75.000%",
+                "                          <= 4                 1     100.000%",
+            ];
+
+        let printer = check_printer_box(&expected, true, false);
+        let printer = printer_mut!(printer);
+
+        histogram.print_cdf(printer);
+
+        // Check the time form with a 1 GHz clock.
+
+        let expected =
+            [
+                "  Cumulative Distribution",
+                "         <= -3.000 nanoseconds                 1      25.000%",
+                "           <= 1.000 nanosecond                 2      75.000%",
+                "          <= 4.000 nanoseconds                 1     100.000%",
+            ];
+
+        let printer = check_printer_box(&expected, true, false);
+        let printer = printer_mut!(printer);
+
+        histogram.print_cdf_times(1_000_000_000, printer);
+    }
+
+    fn test_zero_rows() {
+        let mut histogram = LogHistogram::new();
+
+        histogram.record(-(1 << 40));
+        histogram.record(1_000_000);
+        histogram.record(1_000_001);
+
+        // The zero rows are collapsed into marker lines.
+
+        let expected =
+            [
+                "  Log Histogram",
+                "  -40:                 1                 0                 0                 0",
+                "    ...",
+                "  -----------------------",
+                "    ...",
+                "   20:                 2                 0                 0                 0",
+            ];
+
+        let histo_opts = HistoOpts { no_zero_rows: true, ..Default::default() };
+        let printer    = check_printer_box(&expected, true, false);
+
+        histogram.print_opts(printer_mut!(printer), &histo_opts);
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+
+        // By default, every row is printed.
+
+        let printer = check_printer_box(&[], false, false);
+
+        histogram.print(printer_mut!(printer));
+
+        let (lines, _) = check_printer_counters(printer);
+
+        assert!(lines == 19);
+    }
+
+    fn test_bar_chart() {
+        let mut histogram = LogHistogram::new();
+
+        histogram.record(-1);
+
+        for sample in [ 2, 4, 4, 100, 100, 100, 100 ] {
+            histogram.record(sample);
+        }
+
+        // The largest count gets the full width, and the empty
+        // buckets at the ends aren't printed.
+
+        let expected =
+            [
+                "  Log Histogram",
+                "     -1:               1  |**",
+                "      1:               0  |",
+                "      2:               1  |**",
+                "      4:               2  |****",
+                "      8:               0  |",
+                "     16:               0  |",
+                "     32:               0  |",
+                "     64:               0  |",
+                "    128:               4  |********",
+            ];
+
+        let histo_opts = HistoOpts { bar_chart: true, bar_width: 8, ..Default::default() };
+        let printer    = check_printer_box(&expected, true, false);
+
+        histogram.print_opts(printer_mut!(printer), &histo_opts);
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+
+        // The empty rows can be skipped.
+
+        let expected =
+            [
+                "  Log Histogram",
+                "     -1:               1  |**",
+                "      2:               1  |**",
+                "      4:               2  |****",
+                "    128:               4  |********",
+            ];
+
+        let histo_opts = HistoOpts { no_zero_rows: true, ..histo_opts };
+        let printer    = check_printer_box(&expected, true, false);
+
+        histogram.print_opts(printer_mut!(printer), &histo_opts);
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+
+        // An empty histogram prints only the header.
+
+        let printer = check_printer_box(&[ "  Log Histogram" ], true, false);
+
+        LogHistogram::new().print_opts(printer_mut!(printer), &histo_opts);
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+    }
+
     fn test_log_equals() {
         let mut histo_1 = LogHistogram::new();
         let mut histo_2 = LogHistogram::new();
@@ -432,11 +882,123 @@ mod tests {
         assert!(histo_1.equals(&histo_2));
     }
 
+    fn test_checkpoint() {
+        let mut histogram = LogHistogram::new();
+
+        for i in -100..1000 {
+            histogram.record(i * 7);
+        }
+
+        let checkpoint = histogram.checkpoint();
+        let restored   = LogHistogram::restore(&checkpoint).unwrap();
+
+        assert!(restored.equals(&histogram));
+
+        let text = checkpoint.replace("log_histogram", "float_histogram");
+
+        assert!(LogHistogram::restore(&text).is_none());
+    }
+
+    fn test_buckets() {
+        let mut histogram = LogHistogram::new();
+
+        // Check that every value near the bucket boundaries is recorded
+        // into the bucket that claims it.
+
+        let mut values = vec![ 0, 1, 2, 3, i64::MAX, i64::MIN, i64::MIN + 1 ];
+
+        for shift in 1..63 {
+            let power = 1_i64 << shift;
+
+            values.push( power - 1);
+            values.push( power    );
+            values.push( power + 1);
+            values.push(-power + 1);
+            values.push(-power    );
+            values.push(-power - 1);
+        }
+
+        for value in values.iter() {
+            let     negative = *value < 0;
+            let     index    = pseudo_log_index(*value);
+
+            assert!(bucket_min(index, negative) <= *value);
+            assert!(bucket_max(index, negative) >= *value);
+
+            histogram.record(*value);
+        }
+
+        // The buckets must be in increasing order without gaps.
+
+        let buckets: Vec<(i64, i64, u64)> = histogram.iter_buckets().collect();
+
+        assert!(buckets.len() == 128);
+        assert!(buckets[0].0  == i64::MIN);
+        assert!(buckets[127].1 == i64::MAX);
+
+        for i in 1..buckets.len() {
+            assert!(buckets[i].0 == buckets[i - 1].1 + 1);
+            assert!(buckets[i].0 <= buckets[i].1);
+        }
+
+        let total: u64 = buckets.iter().map(|(_, _, count)| count).sum();
+
+        assert!(total == values.len() as u64);
+        assert!(total == histogram.samples());
+
+        // Now check the percentiles.
+
+        let mut histogram = LogHistogram::new();
+
+        assert!(histogram.percentile(50.0) == 0);
+
+        for i in 1..=100 {
+            histogram.record(i);
+        }
+
+        assert!(histogram.percentile(  0.0) == 1  );
+        assert!(histogram.percentile( 50.0) == 64 );
+        assert!(histogram.percentile(100.0) == 128);
+
+        assert!(histogram.percentile(50.0) == Printable::log_percentile(&histogram, 50.0));
+    }
+
+    fn test_remove() {
+        let mut histogram = LogHistogram::new();
+
+        histogram.record( 7);
+        histogram.record( 7);
+        histogram.record(-3);
+
+        histogram.remove( 7);
+        histogram.remove(-3);
+
+        assert!(histogram.samples()                      == 1);
+        assert!(histogram.positive[pseudo_log_index( 7)] == 1);
+        assert!(histogram.negative[pseudo_log_index(-3)] == 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_remove_missing() {
+        let mut histogram = LogHistogram::new();
+
+        histogram.record(1);
+        histogram.remove(1000);
+    }
+
     #[test]
     fn run_tests() {
         test_log_histogram();
         test_pseudo_log   ();
         test_default      ();
         test_log_equals   ();
+        test_bucket_max   ();
+        test_cdf          ();
+        test_checkpoint   ();
+        test_buckets      ();
+        test_zero_rows    ();
+        test_bar_chart    ();
+        test_remove       ();
     }
 }
@@ -0,0 +1,400 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * LogLinearHistogram
+//!     * LogLinearHistogram splits each pseudo-log bucket of a LogHistogram
+//!       into k linear sub-buckets, much like the significant digits of an
+//!       HDR histogram.  With the plain pseudo-log buckets, every latency
+//!       from just over 1 ms to 2 ms lands in one bucket, and the structure
+//!       of the distribution in that range is lost.
+//!
+//!     * Pseudo-log bucket b covers the values in (2^(b - 1), 2^b].  Its
+//!       sub-buckets divide that range into k pieces of nearly equal width.
+//!       Buckets narrower than k values have some empty sub-buckets.  Bucket
+//!       0 holds 0 and 1 and isn't divided.
+//!
+//!     * The histogram is meant for time samples, which are never negative.
+//!       Negative samples are counted but otherwise ignored.
+//!
+//!     * RunningTime and TimeWindow instances maintain one of these in
+//!       addition to their usual histogram via enable_log_linear().
+//!
+//! ## Example
+//!```
+//!     use rustics::log_linear_histogram::LogLinearHistogram;
+//!     use rustics::stdout_printer;
+//!     use rustics::printer_mut;
+//!
+//!     // Split each pseudo-log bucket into 4 sub-buckets.
+//!
+//!     let mut histogram = LogLinearHistogram::new(4);
+//!
+//!     // Record some latencies between 1 ms and 2 ms, in nanoseconds.
+//!
+//!     for i in 0..1000 {
+//!         histogram.record(1_000_000 + i * 1000);
+//!     }
+//!
+//!     assert!(histogram.samples() == 1000);
+//!
+//!     // The samples fall into more than one sub-bucket.
+//!
+//!     let used = histogram.counts().iter().filter(|count| **count > 0).count();
+//!
+//!     assert!(used > 1);
+//!
+//!     let printer = stdout_printer();
+//!     let printer = printer_mut!(printer);
+//!
+//!     histogram.print_times(1_000_000_000, printer);
+//!```
+
+use super::Printer;
+use super::printable::Printable;
+use super::log_histogram::pseudo_log_index;
+
+/// LogLinearHistogram counts samples into pseudo-log buckets that are
+/// divided into linear sub-buckets.
+
+#[derive(Clone)]
+pub struct LogLinearHistogram {
+    sub_buckets:    usize,
+    counts:         Vec<u64>,
+    samples:        u64,
+    negatives:      u64,
+}
+
+/// The maximum number of sub-buckets per pseudo-log bucket.
+
+pub const MAX_SUB_BUCKETS: usize = 256;
+
+impl LogLinearHistogram {
+    /// Creates a histogram with the given number of sub-buckets per
+    /// pseudo-log bucket.  The count must be in the range 1 to
+    /// MAX_SUB_BUCKETS.
+
+    pub fn new(sub_buckets: usize) -> LogLinearHistogram {
+        if sub_buckets == 0 || sub_buckets > MAX_SUB_BUCKETS {
+            panic!("LogLinearHistogram::new:  The sub-bucket count is out of range.");
+        }
+
+        let counts    = vec![0; 64 * sub_buckets];
+        let samples   = 0;
+        let negatives = 0;
+
+        LogLinearHistogram { sub_buckets, counts, samples, negatives }
+    }
+
+    /// Returns the number of sub-buckets per pseudo-log bucket.
+
+    pub fn sub_buckets(&self) -> usize {
+        self.sub_buckets
+    }
+
+    /// Returns the counts.  The sub-buckets for pseudo-log bucket b
+    /// start at index b * sub_buckets().
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Returns the number of samples recorded, not including negative
+    /// samples.
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Returns the number of negative samples seen.
+
+    pub fn negatives(&self) -> u64 {
+        self.negatives
+    }
+
+    /// Returns the index into counts() for the given sample, which
+    /// must not be negative.
+
+    pub fn index(&self, sample: i64) -> usize {
+        assert!(sample >= 0);
+
+        let bucket = pseudo_log_index(sample);
+
+        if bucket == 0 {
+            return 0;
+        }
+
+        let base   = 1_u128 << (bucket - 1);
+        let offset = sample as u128 - base - 1;
+        let sub    = offset * self.sub_buckets as u128 / base;
+
+        bucket * self.sub_buckets + sub as usize
+    }
+
+    /// Returns the smallest and largest values that go into the
+    /// sub-bucket at the given index.  The bounds for an empty
+    /// sub-bucket of a narrow bucket might cross.
+
+    pub fn bounds(&self, index: usize) -> (i64, i64) {
+        let bucket = index / self.sub_buckets;
+        let sub    = (index % self.sub_buckets) as u128;
+        let k      = self.sub_buckets as u128;
+
+        if bucket == 0 {
+            return (0, 1);
+        }
+
+        let base = 1_u128 << (bucket - 1);
+        let low  = base + 1 + (sub * base).div_ceil(k);
+        let high = base + ((sub + 1) * base).div_ceil(k);
+
+        (Self::clip(low), Self::clip(high))
+    }
+
+    fn clip(value: u128) -> i64 {
+        value.min(i64::MAX as u128) as i64
+    }
+
+    /// Records a sample.
+
+    pub fn record(&mut self, sample: i64) {
+        if sample < 0 {
+            self.negatives += 1;
+            return;
+        }
+
+        let index = self.index(sample);
+
+        self.counts[index] += 1;
+        self.samples       += 1;
+    }
+
+    /// Estimates the given percentile, which is in the range 0 to 100.
+    /// The result is the largest value of the sub-bucket containing the
+    /// percentile, so it is an upper bound.  An empty histogram returns
+    /// zero.
+
+    pub fn percentile(&self, percentile: f64) -> i64 {
+        if self.samples == 0 {
+            return 0;
+        }
+
+        let     percentile = percentile.clamp(0.0, 100.0);
+        let     rank       = (percentile / 100.0 * self.samples as f64).ceil() as u64;
+        let     rank       = rank.max(1);
+        let mut seen       = 0;
+
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += *count;
+
+            if seen >= rank {
+                return self.bounds(index).1;
+            }
+        }
+
+        self.bounds(self.counts.len() - 1).1
+    }
+
+    /// Merges another histogram into this one.  The sub-bucket counts
+    /// must match.
+
+    pub fn merge(&mut self, other: &LogLinearHistogram) {
+        if self.sub_buckets != other.sub_buckets {
+            panic!("LogLinearHistogram::merge:  The sub-bucket counts don't match.");
+        }
+
+        for (count, addend) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += *addend;
+        }
+
+        self.samples   += other.samples;
+        self.negatives += other.negatives;
+    }
+
+    /// Resets the counts to zero.
+
+    pub fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+
+        self.samples   = 0;
+        self.negatives = 0;
+    }
+
+    /// Prints the non-empty sub-buckets with integer bounds.
+
+    pub fn print(&self, printer: &mut dyn Printer) {
+        self.print_rows(printer, &|value| Printable::commas_i64(value));
+    }
+
+    /// Prints the non-empty sub-buckets with the bounds converted to
+    /// times.  The hz parameter gives the clock frequency.
+
+    pub fn print_times(&self, hz: i64, printer: &mut dyn Printer) {
+        let format =
+            |value: i64| {
+                let (time, unit) = Printable::scale_time(value as f64, hz);
+
+                format!("{:.3} {}", time, unit)
+            };
+
+        self.print_rows(printer, &format);
+    }
+
+    fn print_rows(&self, printer: &mut dyn Printer, format: &dyn Fn(i64) -> String) {
+        let header =
+            format!("  Log-Linear Histogram:  ({} sub-buckets, {} samples)",
+                self.sub_buckets, Printable::commas_u64(self.samples));
+
+        printer.print(&header);
+        printer.print("  -----------------------");
+
+        for (index, count) in self.counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+
+            let (low, high) = self.bounds(index);
+
+            let row =
+                format!("    {:>16} .. {:<16} {:>14}",
+                    format(low), format(high), Printable::commas_u64(*count));
+
+            printer.print(&row);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_counters;
+    use crate::printer_mut;
+
+    fn test_index() {
+        let histogram = LogLinearHistogram::new(4);
+
+        assert!(histogram.index(0) == 0);
+        assert!(histogram.index(1) == 0);
+        assert!(histogram.index(2) == 4);
+
+        // Bucket 10 holds 513 through 1024 in 4 sub-buckets of 128.
+
+        assert!(histogram.index( 513) == 40);
+        assert!(histogram.index( 640) == 40);
+        assert!(histogram.index( 641) == 41);
+        assert!(histogram.index(1024) == 43);
+
+        assert!(histogram.bounds(40) == ( 513,  640));
+        assert!(histogram.bounds(41) == ( 641,  768));
+        assert!(histogram.bounds(43) == ( 897, 1024));
+        assert!(histogram.bounds( 0) == (   0,    1));
+
+        // The values in each sub-bucket are within its bounds, for
+        // every bucket.
+
+        for k in [ 1, 3, 4, 10 ] {
+            let histogram = LogLinearHistogram::new(k);
+
+            for sample in (0..5000).chain([ i64::MAX - 1, i64::MAX ]) {
+                let (low, high) = histogram.bounds(histogram.index(sample));
+
+                assert!(low <= sample && sample <= high);
+            }
+        }
+    }
+
+    fn test_record() {
+        let mut histogram = LogLinearHistogram::new(8);
+
+        // Record samples from about 1 ms to 2 ms.  They go into one
+        // pseudo-log bucket, but spread over its sub-buckets.
+
+        for i in 0..1000 {
+            histogram.record(1_048_577 + i * 1000);
+        }
+
+        histogram.record(-1);
+
+        assert!(histogram.samples()   == 1000);
+        assert!(histogram.negatives() == 1   );
+
+        let used = histogram.counts().iter().filter(|count| **count > 0).count();
+
+        assert!(used == 8);
+
+        let median = histogram.percentile(50.0);
+
+        assert!((1_500_000..=1_600_000).contains(&median));
+        assert!(histogram.percentile(100.0) == 2_097_152);
+
+        let mut other = histogram.clone();
+
+        other.merge(&histogram);
+
+        assert!(other.samples()   == 2000);
+        assert!(other.negatives() == 2   );
+        assert!(other.percentile(50.0) == median);
+
+        other.clear();
+
+        assert!(other.samples() == 0);
+        assert!(other.percentile(50.0) == 0);
+    }
+
+    fn test_print() {
+        let expected =
+            [
+                "  Log-Linear Histogram:  (2 sub-buckets, 3 samples)",
+                "  -----------------------",
+                "                   0 .. 1                             1",
+                "                  65 .. 96                            2",
+            ];
+
+        let mut histogram = LogLinearHistogram::new(2);
+
+        histogram.record(1);
+        histogram.record(70);
+        histogram.record(96);
+
+        let printer = check_printer_box(&expected, true, false);
+
+        histogram.print(printer_mut!(printer));
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+
+        let printer = crate::stdout_printer();
+
+        histogram.print_times(1_000_000_000, printer_mut!(printer));
+    }
+
+    #[test]
+    fn run_tests() {
+        test_index ();
+        test_record();
+        test_print ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_count() {
+        let _ = LogLinearHistogram::new(0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_merge() {
+        let mut first  = LogLinearHistogram::new(2);
+        let     second = LogLinearHistogram::new(4);
+
+        first.merge(&second);
+    }
+}
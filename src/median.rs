@@ -0,0 +1,370 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Types
+//!
+//! * P2Quantile
+//!     * P2Quantile estimates one quantile of a stream of samples using
+//!       the P² algorithm of Jain and Chlamtac, "The P² Algorithm for
+//!       Dynamic Calculation of Quantiles and Histograms Without Storing
+//!       Observations", CACM, October 1985.
+//!
+//!     * The estimator keeps five markers, so it uses constant space no
+//!       matter how many samples are recorded.  The first five samples
+//!       are kept exactly, so small counts give exact results.
+//!
+//!     * P² estimates can't be merged.
+//!
+//! * MedianEstimator
+//!     * MedianEstimator keeps a P² estimate of the median and of the
+//!       median absolute deviation (MAD).  The MAD estimate uses the
+//!       deviation of each sample from the median estimate at the time
+//!       the sample was recorded, so it is an approximation that improves
+//!       as the median estimate settles.
+//!
+//!     * RunningInteger and RunningFloat instances can enable a
+//!       MedianEstimator via enable_median(), and then support the
+//!       median() and mad() methods of the Rustics trait.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::running_integer::RunningInteger;
+//!
+//!     let mut latencies = RunningInteger::new("Latencies", &None);
+//!
+//!     assert!(!latencies.has_median());
+//!
+//!     latencies.enable_median();
+//!
+//!     // Record some samples with a few large outliers.  The outliers
+//!     // move the mean quite a bit, but not the median.
+//!
+//!     for i in 1..=10_000 {
+//!         let sample = if i % 100 == 0 { 1_000_000 } else { 100 + (i * 7919) % 10 };
+//!
+//!         latencies.record_i64(sample);
+//!     }
+//!
+//!     assert!(latencies.has_median());
+//!     assert!(latencies.mean() > 10_000.0);
+//!     assert!((latencies.median() - 104.5).abs() < 2.0);
+//!     assert!(latencies.mad() < 5.0);
+//!```
+
+/// P2Quantile estimates a single quantile of a stream.
+
+#[derive(Clone, Debug)]
+pub struct P2Quantile {
+    quantile:   f64,
+    count:      u64,
+    heights:    [f64; 5],
+    positions:  [f64; 5],
+    desired:    [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    /// Creates an estimator for the given quantile, which must be in
+    /// the range 0 to 1.
+
+    pub fn new(quantile: f64) -> P2Quantile {
+        if !(0.0..=1.0).contains(&quantile) {
+            panic!("P2Quantile::new:  The quantile must be in [0, 1].");
+        }
+
+        let p          = quantile;
+        let count      = 0;
+        let heights    = [0.0; 5];
+        let positions  = [ 0.0, 1.0, 2.0, 3.0, 4.0 ];
+        let desired    = [ 0.0, 2.0 * p, 4.0 * p, 2.0 + 2.0 * p, 4.0 ];
+        let increments = [ 0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0 ];
+
+        P2Quantile { quantile, count, heights, positions, desired, increments }
+    }
+
+    /// Returns the quantile being estimated.
+
+    pub fn quantile(&self) -> f64 {
+        self.quantile
+    }
+
+    /// Returns the number of samples recorded.
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Records a sample.  NaNs are ignored.
+
+    pub fn record(&mut self, sample: f64) {
+        if sample.is_nan() {
+            return;
+        }
+
+        // Keep the first five samples exactly.
+
+        if self.count < 5 {
+            self.heights[self.count as usize] = sample;
+            self.count += 1;
+
+            if self.count == 5 {
+                self.heights.sort_by(|a, b| a.total_cmp(b));
+            }
+
+            return;
+        }
+
+        self.count += 1;
+
+        // Find the cell containing the sample, adjusting the extreme
+        // markers if needed.
+
+        let heights = &mut self.heights;
+
+        let cell =
+            if sample < heights[0] {
+                heights[0] = sample;
+                0
+            } else if sample >= heights[4] {
+                heights[4] = sample;
+                3
+            } else {
+                (1..5).find(|i| sample < heights[*i]).unwrap() - 1
+            };
+
+        for position in self.positions[cell + 1..].iter_mut() {
+            *position += 1.0;
+        }
+
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments.iter()) {
+            *desired += *increment;
+        }
+
+        // Move the middle markers toward their desired positions.
+
+        for i in 1..4 {
+            let delta = self.desired[i] - self.positions[i];
+            let up    = self.positions[i + 1] - self.positions[i];
+            let down  = self.positions[i - 1] - self.positions[i];
+
+            if (delta >= 1.0 && up > 1.0) || (delta <= -1.0 && down < -1.0) {
+                let direction = delta.signum();
+                let parabolic = self.parabolic(i, direction);
+
+                self.heights[i] =
+                    if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                        parabolic
+                    } else {
+                        self.linear(i, direction)
+                    };
+
+                self.positions[i] += direction;
+            }
+        }
+    }
+
+    // Compute the piecewise-parabolic prediction for marker i.
+
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let q = &self.heights;
+        let n = &self.positions;
+
+        let upper = (n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i]);
+        let lower = (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]);
+
+        q[i] + d / (n[i + 1] - n[i - 1]) * (upper + lower)
+    }
+
+    // Compute the linear prediction for marker i.
+
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let q     = &self.heights;
+        let n     = &self.positions;
+        let other = if d > 0.0 { i + 1 } else { i - 1 };
+
+        q[i] + d * (q[other] - q[i]) / (n[other] - n[i])
+    }
+
+    /// Returns the estimate of the quantile, or zero if no samples
+    /// have been recorded.  With fewer than five samples, the result
+    /// is interpolated from the exact samples.
+
+    pub fn estimate(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+
+        if self.count >= 5 {
+            return self.heights[2];
+        }
+
+        let mut samples = self.heights[..self.count as usize].to_vec();
+
+        samples.sort_by(|a, b| a.total_cmp(b));
+
+        let position = (samples.len() - 1) as f64 * self.quantile;
+        let low      = position.floor() as usize;
+        let high     = position.ceil()  as usize;
+        let fraction = position - low as f64;
+
+        samples[low] + fraction * (samples[high] - samples[low])
+    }
+
+    /// Returns the estimator to its initial state.
+
+    pub fn clear(&mut self) {
+        *self = P2Quantile::new(self.quantile);
+    }
+}
+
+/// MedianEstimator estimates the median and the median absolute
+/// deviation of a stream.
+
+#[derive(Clone, Debug)]
+pub struct MedianEstimator {
+    median:     P2Quantile,
+    deviation:  P2Quantile,
+}
+
+impl MedianEstimator {
+    pub fn new() -> MedianEstimator {
+        let median    = P2Quantile::new(0.5);
+        let deviation = P2Quantile::new(0.5);
+
+        MedianEstimator { median, deviation }
+    }
+
+    /// Records a sample.  NaNs are ignored.
+
+    pub fn record(&mut self, sample: f64) {
+        if sample.is_nan() {
+            return;
+        }
+
+        self.median.record(sample);
+
+        let deviation = (sample - self.median.estimate()).abs();
+
+        self.deviation.record(deviation);
+    }
+
+    /// Returns the number of samples recorded.
+
+    pub fn count(&self) -> u64 {
+        self.median.count()
+    }
+
+    /// Returns the estimate of the median.
+
+    pub fn median(&self) -> f64 {
+        self.median.estimate()
+    }
+
+    /// Returns the estimate of the median absolute deviation.
+
+    pub fn mad(&self) -> f64 {
+        self.deviation.estimate()
+    }
+
+    pub fn clear(&mut self) {
+        self.median.clear();
+        self.deviation.clear();
+    }
+}
+
+impl Default for MedianEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generate pseudo-random values in [0, 1).
+
+    fn xorshift(state: &mut u64) -> f64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+
+        (*state >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    fn test_small_counts() {
+        let mut estimator = P2Quantile::new(0.5);
+
+        assert!(estimator.estimate() == 0.0);
+
+        estimator.record(3.0);
+        assert!(estimator.estimate() == 3.0);
+
+        estimator.record(1.0);
+        assert!(estimator.estimate() == 2.0);
+
+        estimator.record(f64::NAN);
+        estimator.record(2.0);
+        assert!(estimator.count()    == 3  );
+        assert!(estimator.estimate() == 2.0);
+
+        estimator.clear();
+        assert!(estimator.count() == 0);
+    }
+
+    fn test_uniform() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+
+        for quantile in [ 0.1, 0.5, 0.9 ] {
+            let mut estimator = P2Quantile::new(quantile);
+
+            for _i in 0..100_000 {
+                estimator.record(xorshift(&mut state) * 1000.0);
+            }
+
+            let error = (estimator.estimate() - quantile * 1000.0).abs();
+
+            assert!(estimator.quantile() == quantile);
+            assert!(error < 10.0);
+        }
+    }
+
+    fn test_median_estimator() {
+        let mut estimator = MedianEstimator::new();
+        let mut state     = 0x9e37_79b9_7f4a_7c15;
+
+        // Uniform on [0, 100) has a median of 50 and a MAD of 25.
+
+        for _i in 0..100_000 {
+            estimator.record(xorshift(&mut state) * 100.0);
+        }
+
+        assert!(estimator.count() == 100_000);
+        assert!((estimator.median() - 50.0).abs() < 1.0);
+        assert!((estimator.mad()    - 25.0).abs() < 1.0);
+
+        estimator.clear();
+
+        assert!(estimator.count()  == 0  );
+        assert!(estimator.median() == 0.0);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_small_counts    ();
+        test_uniform         ();
+        test_median_estimator();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_quantile() {
+        let _ = P2Quantile::new(1.5);
+    }
+}
@@ -18,14 +18,35 @@
 //!     * This module is of use only for implementing new Rustics types and
 //!       so most users will never need to use it directly.
 //!
+//!     * export_from_stats() converts the ExportStats for any Rustics
+//!       instance into an Export, so that instances of different types
+//!       can be summed.
+//!
+//!     * pool_rustics() pools the counts, means, and variances of a set
+//!       of Rustics instances, so the pooled variance and standard
+//!       deviation of their union can be checked from the public API.
+//!       sum_running() uses the same pooling for the mean and variance,
+//!       extended to the fourth moment.  See stats_math::pool_moments()
+//!       and stats_math::pool_higher_moments().
+//!
+//! * Summary
+//!     * A Summary holds the count, mean, variance, and extremes exposed
+//!       by a subsystem that doesn't provide raw samples.  The
+//!       merge_summary() methods of RunningInteger and RunningFloat fold
+//!       a Summary into an instance using export_from_summary().
+//!
 
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use super::RecoverData;
-use super::StatisticsData;
-use super::recover;
-use super::compute_statistics;
+use super::Rustics;
+use super::ExportStats;
+use super::stats_math::EstimateData;
+use super::stats_math::estimate_moment_3;
+use super::stats_math::HigherPoolData;
+use super::stats_math::pool_higher_moments;
+use super::stats_math::PoolData;
+use super::stats_math::pool_moments;
 use super::sum::kbk_sum_sort;
 
 use super::LogHistogramBox;
@@ -83,14 +104,12 @@ pub fn sum_running(exports: &Vec::<Export>) -> Export {
             FloatHistogram::new(&None)
          };
 
-    let mut sum_vec     = Vec::with_capacity(exports.len());
-    let mut squares_vec = Vec::with_capacity(exports.len());
     let mut cubes_vec   = Vec::with_capacity(exports.len());
-    let mut quads_vec   = Vec::with_capacity(exports.len());
+    let mut pool_vec    = Vec::with_capacity(exports.len());
 
     // Iterate through each set of exported data, gather merged
-    // values.  We recover the squares and fourth powers of
-    // each sample from data in the exports.
+    // values.  We estimate the third moment of each export from
+    // its sum of the cubes, for use in pooling the fourth moment.
 
     for export in exports {
         count      += export.count;
@@ -119,45 +138,40 @@ pub fn sum_running(exports: &Vec::<Export>) -> Export {
             sum_float_histogram(&mut float_histogram, &addend);
         }
 
+        // Empty instances contribute nothing to the moments, and
+        // their means might not be valid numbers.
+
+        if export.count == 0 {
+            continue;
+        }
+
         let n        = export.count as f64;
         let mean     = export.mean;
         let moment_2 = export.moment_2;
         let cubes    = export.cubes;
         let moment_4 = export.moment_4;
-        let data     = RecoverData { n, mean, moment_2, cubes, moment_4 };
-
-        // Recreate the sum of the squares of each
-        // sample and the sum of the fourth powers
-        // of each sample.
-
-        let (squares, quads) = recover(data);
-
-        // Now recreate the sum of the samples.
-
-        let sum = export.mean * n;
+        let data     = EstimateData { n, mean, moment_2, cubes };
+        let moment_3 = estimate_moment_3(data);
 
-        sum_vec.push    (sum     );
-        squares_vec.push(squares );
-        cubes_vec.push  (cubes   );
-        quads_vec.push  (quads   );
+        cubes_vec.push(cubes);
+        pool_vec.push (HigherPoolData { n, mean, moment_2, moment_3, moment_4 });
     }
 
-    // Now merge the data that we got.  We get the sums
-    // of the squares, cubes, and fourth power of each
-    // original sample.  From that data, we compute
-    // the merged 2nd and 4th moments about the mean,
-    // as well as the mean.
+    // Now merge the data that we got.  The mean and the
+    // second and fourth moments are pooled pairwise, so
+    // they are consistent with each other.  The sum of
+    // the cubes is kept as a sum, since the running types
+    // estimate the third moment from it.
 
-    let n        = count as f64;
-    let sum      = kbk_sum_sort(&mut sum_vec    [..]);
-    let squares  = kbk_sum_sort(&mut squares_vec[..]);
-    let cubes    = kbk_sum_sort(&mut cubes_vec  [..]);
-    let quads    = kbk_sum_sort(&mut quads_vec  [..]);
-    let data     = StatisticsData { n, sum, squares, cubes, quads };
-    let merged   = compute_statistics(data);
-    let mean     = merged.mean;
-    let moment_2 = merged.moment_2;
-    let moment_4 = merged.moment_4;
+    let cubes  = kbk_sum_sort(&mut cubes_vec[..]);
+    let pooled = pool_higher_moments(&pool_vec);
+
+    let (mean, moment_2, moment_4) =
+        if count > 0 {
+            (pooled.mean, pooled.moment_2, pooled.moment_4)
+        } else {
+            (0.0, 0.0, 0.0)
+        };
 
     // Okay, build the structure from which an instance
     // can be built.  First, box any histograms we have
@@ -184,6 +198,163 @@ pub fn sum_running(exports: &Vec::<Export>) -> Export {
     }
 }
 
+/// export_from_stats() recreates the moments kept by the running types
+/// from the summary statistics in an ExportStats instance.  The
+/// skewness and kurtosis are inverted to recover the third and fourth
+/// moments.  Instances with too few samples for those estimators get
+/// zero skewness and zero excess kurtosis.
+
+pub fn export_from_stats(stats: &ExportStats) -> Export {
+    let printable  = &stats.printable;
+    let count      = printable.n;
+    let nans       = printable.nans;
+    let infinities = printable.infinities;
+    let mean       = printable.mean;
+    let n          = count as f64;
+
+    let moment_2 =
+        if count >= 2 {
+            printable.variance * (n - 1.0)
+        } else {
+            0.0
+        };
+
+    // Invert compute_skewness() to get the third moment about the mean.
+
+    let moment_3 =
+        if count >= 3 && moment_2 > 0.0 {
+            let correction = (n * (n - 1.0)).sqrt() / (n - 2.0);
+            let skewness   = printable.skewness / correction;
+
+            skewness * (moment_2 / n).powf(1.5) * n
+        } else {
+            0.0
+        };
+
+    // Now invert compute_kurtosis() to get the fourth moment.
+
+    let excess =
+        if count >= 4 {
+            let correction = (n - 1.0) / ((n - 2.0) * (n - 3.0));
+
+            (printable.kurtosis / correction - 6.0) / (n + 1.0)
+        } else {
+            0.0
+        };
+
+    let moment_4 =
+        if count > 0 {
+            (excess + 3.0) * moment_2.powi(2) / n
+        } else {
+            0.0
+        };
+
+    // Convert the third moment about the mean into the sum of the
+    // cubes of the samples.
+
+    let sum     = n * mean;
+    let squares = moment_2 + n * mean.powi(2);
+    let cubes   = moment_3 + 3.0 * squares * mean - 3.0 * sum * mean.powi(2) + n * mean.powi(3);
+
+    let min_i64         = printable.min_i64;
+    let max_i64         = printable.max_i64;
+    let min_f64         = printable.min_f64;
+    let max_f64         = printable.max_f64;
+    let log_histogram   = stats.log_histogram.clone();
+    let float_histogram = stats.float_histogram.clone();
+
+    Export {
+        count,       mean,           moment_2,        cubes,    moment_4,
+        min_i64,     max_i64,        min_f64,         max_f64,  nans,
+        infinities,  log_histogram,  float_histogram
+    }
+}
+
+/// Summary holds the aggregate statistics exposed by a subsystem that
+/// doesn't provide raw samples.  The variance is the sample variance,
+/// i.e., the second moment about the mean divided by n - 1.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub count:      u64,
+    pub mean:       f64,
+    pub variance:   f64,
+    pub min:        f64,
+    pub max:        f64,
+}
+
+/// export_from_summary() converts a Summary into the moments kept by
+/// the running types so that it can be merged with sum_running().  A
+/// summary doesn't give the third or fourth moments, so the samples
+/// are assumed to have the skewness and kurtosis of a normal
+/// distribution.  The extremes are rounded for the integer fields.
+/// No histogram is created.
+
+pub fn export_from_summary(summary: &Summary) -> Export {
+    let count      = summary.count;
+    let nans       = 0;
+    let infinities = 0;
+    let mean       = summary.mean;
+    let n          = count as f64;
+
+    let moment_2 =
+        if count >= 2 {
+            summary.variance * (n - 1.0)
+        } else {
+            0.0
+        };
+
+    let moment_4 =
+        if count > 0 {
+            3.0 * moment_2.powi(2) / n
+        } else {
+            0.0
+        };
+
+    // With a third moment of zero, the sum of the cubes depends only
+    // on the first two moments.
+
+    let squares = moment_2 + n * mean.powi(2);
+    let cubes   = 3.0 * squares * mean - 2.0 * n * mean.powi(3);
+
+    let min_i64         = summary.min.round() as i64;
+    let max_i64         = summary.max.round() as i64;
+    let min_f64         = summary.min;
+    let max_f64         = summary.max;
+    let log_histogram   = None;
+    let float_histogram = None;
+
+    Export {
+        count,       mean,           moment_2,        cubes,    moment_4,
+        min_i64,     max_i64,        min_f64,         max_f64,  nans,
+        infinities,  log_histogram,  float_histogram
+    }
+}
+
+/// pool_rustics() pools the summaries of a set of Rustics instances.
+/// The result gives the count, mean, and second moment about the mean
+/// of all the samples recorded into the instances.  Use
+/// compute_variance() on the result, or stats_math::pooled_variance()
+/// on the parts, to get the variance.
+
+pub fn pool_rustics(members: &[&dyn Rustics]) -> PoolData {
+    let parts: Vec<PoolData> = members.iter().map(|member| pool_data(*member)).collect();
+
+    pool_moments(&parts)
+}
+
+/// Converts the count, mean, and variance of a Rustics instance into
+/// the form used for pooling.
+
+pub fn pool_data(member: &dyn Rustics) -> PoolData {
+    let count    = member.count();
+    let n        = count as f64;
+    let mean     = if count > 0 { member.mean() } else { 0.0 };
+    let moment_2 = if count > 1 { member.variance() * (n - 1.0) } else { 0.0 };
+
+    PoolData { n, mean, moment_2 }
+}
+
 /// sum_log_histogram() sums the addend operand into the sum instance.
 
 pub fn sum_log_histogram(sum:  &mut LogHistogram, addend: &LogHistogram) {
@@ -225,6 +396,8 @@ mod tests {
 
     use crate::running_integer::RunningInteger;
     use crate::running_float  ::RunningFloat;
+    use crate::stats_math::compute_variance;
+    use crate::stats_math::compute_kurtosis;
 
     fn test_sum_integer() {
         let mut compare = LogHistogram::new();
@@ -331,9 +504,275 @@ mod tests {
         assert!(sum_histo.equals(&compare));
     }
 
+    fn test_export_from_stats() {
+        let mut stat = RunningInteger::new("export from stats", &None);
+
+        for i in 1..=1000 {
+            stat.record_i64(i * i);
+        }
+
+        let direct    = stat.export_data();
+        let converted = export_from_stats(&stat.export_stats());
+
+        let close = |a: f64, b: f64| (a - b).abs() <= a.abs().max(1.0) * 1e-9;
+
+        assert!(converted.count   == direct.count  );
+        assert!(converted.min_i64 == direct.min_i64);
+        assert!(converted.max_i64 == direct.max_i64);
+
+        assert!(close(converted.mean,     direct.mean    ));
+        assert!(close(converted.moment_2, direct.moment_2));
+        assert!(close(converted.cubes,    direct.cubes   ));
+        assert!(close(converted.moment_4, direct.moment_4));
+
+        // Check the small sample cases.
+
+        let mut stat = RunningFloat::new("small", &None);
+
+        let converted = export_from_stats(&stat.export_stats());
+
+        assert!(converted.count    == 0  );
+        assert!(converted.moment_2 == 0.0);
+        assert!(converted.moment_4 == 0.0);
+
+        stat.record_f64(2.0);
+        stat.record_f64(4.0);
+
+        let converted = export_from_stats(&stat.export_stats());
+
+        assert!(converted.count    == 2  );
+        assert!(converted.mean     == 3.0);
+        assert!(converted.moment_2 == 2.0);
+        assert!(converted.cubes    == 72.0);
+    }
+
+    fn test_sum_empty() {
+        let empty = RunningInteger::new("empty", &None);
+        let sum   = sum_running(&vec![ empty.export_data(), empty.export_data() ]);
+
+        assert!(sum.count    == 0  );
+        assert!(sum.mean     == 0.0);
+        assert!(sum.moment_2 == 0.0);
+        assert!(sum.moment_4 == 0.0);
+
+        // Now sum the sum, which used to give a NaN mean.
+
+        let sum = sum_running(&vec![ sum.clone(), sum ]);
+
+        assert!(sum.mean == 0.0);
+
+        // Mix empty and full instances.
+
+        let mut full = RunningInteger::new("full", &None);
+
+        full.record_i64(4);
+        full.record_i64(6);
+
+        let sum = sum_running(&vec![ empty.export_data(), full.export_data() ]);
+
+        assert!(sum.count    == 2  );
+        assert!(sum.mean     == 5.0);
+        assert!(sum.moment_2 == 2.0);
+    }
+
+    // Compute the sample variance directly, using two passes.
+
+    fn direct_variance(samples: &[f64]) -> f64 {
+        let n    = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+
+        samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)
+    }
+
+    // Pool instances with very different counts and a large mean
+    // compared to the standard deviation, and compare the results with
+    // the statistics for all the samples.
+
+    fn test_pool_rustics() {
+        let mut all    = Vec::new();
+        let mut small  = RunningFloat::new("small", &None);
+        let mut large  = RunningFloat::new("large", &None);
+        let     empty  = RunningFloat::new("empty", &None);
+        let     offset = 1.0e6;
+
+        for i in 0..3 {
+            let sample = offset + 10.0 + i as f64;
+
+            small.record_f64(sample);
+            all.push(sample);
+        }
+
+        for i in 0..200_000 {
+            let sample = offset + ((i * 7919) % 1000) as f64 / 1000.0;
+
+            large.record_f64(sample);
+            all.push(sample);
+        }
+
+        let expected = direct_variance(&all);
+        let mean     = all.iter().sum::<f64>() / all.len() as f64;
+        let close    = |a: f64, b: f64| (a - b).abs() <= b.abs() * 1e-9;
+
+        let pooled   = pool_rustics(&[ &small, &empty, &large ]);
+        let variance = compute_variance(pooled.n as u64, pooled.moment_2);
+
+        assert!(pooled.n == all.len() as f64);
+        assert!(close(pooled.mean, mean    ));
+        assert!(close(variance,    expected));
+
+        // The order of the parts doesn't matter.
+
+        let reversed = pool_rustics(&[ &large, &small ]);
+
+        assert!(close(reversed.moment_2, pooled.moment_2));
+
+        // Summing the exports and merging use the same path.
+
+        let sum = sum_running(&vec![ small.export_data(), large.export_data() ]);
+
+        assert!(close(compute_variance(sum.count, sum.moment_2), expected));
+
+        small.merge(&large);
+
+        assert!(close(small.variance(), expected));
+        assert!(close(small.standard_deviation(), expected.sqrt()));
+
+        // Empty and single-sample instances have no variance.
+
+        let mut single = RunningInteger::new("single", &None);
+
+        single.record_i64(5);
+
+        let pooled = pool_rustics(&[ &empty, &single ]);
+
+        assert!(pooled.n        == 1.0);
+        assert!(pooled.mean     == 5.0);
+        assert!(pooled.moment_2 == 0.0);
+        assert!(pool_rustics(&[]).n == 0.0);
+    }
+
+    // Build an export with the exact moments of a set of samples.
+
+    fn exact_export(samples: &[f64]) -> Export {
+        let count    = samples.len() as u64;
+        let n        = count as f64;
+        let mean     = samples.iter().sum::<f64>() / n;
+        let moment_2 = samples.iter().map(|x| (x - mean).powi(2)).sum();
+        let cubes    = samples.iter().map(|x| x.powi(3)).sum();
+        let moment_4 = samples.iter().map(|x| (x - mean).powi(4)).sum();
+        let min_f64  = samples.iter().cloned().fold(f64::MAX, f64::min);
+        let max_f64  = samples.iter().cloned().fold(f64::MIN, f64::max);
+
+        Export {
+            count,          nans:     0,    infinities:      0,
+            mean,           moment_2,       cubes,
+            moment_4,       min_i64:  0,    max_i64:         0,
+            min_f64,        max_f64,        log_histogram:   None,
+            float_histogram: None
+        }
+    }
+
+    // Sum exports whose counts differ by five orders of magnitude,
+    // and check the kurtosis against the moments of all the samples.
+
+    fn test_sum_kurtosis() {
+        let large: Vec<f64> =
+            (0..1_000_000_u64).map(|i| 1000.0 + ((i * 7919) % 1000) as f64 / 100.0).collect();
+
+        let small: Vec<f64> = (0..10).map(|i| 1100.0 + i as f64).collect();
+
+        let all: Vec<f64> = large.iter().chain(small.iter()).cloned().collect();
+        let all           = exact_export(&all);
+        let expected      = compute_kurtosis(all.count, all.moment_2, all.moment_4);
+        let close         = |a: f64, b: f64| (a - b).abs() <= b.abs() * 1e-9;
+
+        for exports in [ vec![ exact_export(&large), exact_export(&small) ],
+                         vec![ exact_export(&small), exact_export(&large) ] ] {
+            let sum      = sum_running(&exports);
+            let kurtosis = compute_kurtosis(sum.count, sum.moment_2, sum.moment_4);
+
+            assert!(sum.count == all.count);
+            assert!(close(sum.mean,     all.mean    ));
+            assert!(close(sum.moment_2, all.moment_2));
+            assert!(close(sum.moment_4, all.moment_4));
+            assert!(close(kurtosis,     expected    ));
+        }
+    }
+
+    fn test_merge_summary() {
+        let close = |a: f64, b: f64| (a - b).abs() <= a.abs().max(1.0) * 1e-9;
+
+        // Import the second half of the samples as a summary, then
+        // compare against an instance that recorded all of them.
+
+        let mut all      = RunningInteger::new("all",      &None);
+        let mut imported = RunningInteger::new("imported", &None);
+        let mut external = RunningInteger::new("external", &None);
+
+        for i in 1..=100 {
+            all.record_i64(i);
+
+            if i <= 50 {
+                imported.record_i64(i);
+            } else {
+                external.record_i64(i);
+            }
+        }
+
+        let count    = external.count();
+        let mean     = external.mean();
+        let variance = external.variance();
+        let min      = external.min_i64() as f64;
+        let max      = external.max_i64() as f64;
+        let summary  = Summary { count, mean, variance, min, max };
+
+        imported.merge_summary(&summary);
+
+        assert!(imported.count()   == all.count()  );
+        assert!(imported.min_i64() == all.min_i64());
+        assert!(imported.max_i64() == all.max_i64());
+
+        assert!(close(imported.mean(),     all.mean()    ));
+        assert!(close(imported.variance(), all.variance()));
+
+        // The histogram covers only the recorded samples.
+
+        assert!(imported.log_histogram().unwrap().borrow().samples() == 50);
+
+        // Now check the float version, starting from an empty instance.
+
+        let mut floats  = RunningFloat::new("floats", &None);
+        let     summary = Summary { count: 4, mean: 2.5, variance: 5.0 / 3.0, min: 1.0, max: 4.0 };
+
+        floats.merge_summary(&Summary::default());
+        assert!(floats.count() == 0);
+
+        floats.merge_summary(&summary);
+        floats.record_f64(2.5);
+
+        assert!(floats.count()   == 5  );
+        assert!(floats.min_f64() == 1.0);
+        assert!(floats.max_f64() == 4.0);
+
+        assert!(close(floats.mean(),     2.5      ));
+        assert!(close(floats.variance(), 5.0 / 4.0));
+
+        // The summary describes the samples 1 through 4, which are
+        // symmetric, so the sum of the cubes is recovered exactly.
+
+        let export = export_from_summary(&summary);
+
+        assert!(close(export.cubes, 100.0));
+    }
+
     #[test]
     fn run_tests() {
-        test_sum_integer();
-        test_sum_float  ();
+        test_sum_integer      ();
+        test_sum_float        ();
+        test_export_from_stats();
+        test_sum_empty        ();
+        test_pool_rustics     ();
+        test_sum_kurtosis     ();
+        test_merge_summary    ();
     }
 }
@@ -0,0 +1,111 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * NonFinitePolicy
+//!     * NonFinitePolicy selects how RunningFloat, FloatWindow,
+//!       FloatTimeWindow, and FloatHier instances handle NaN and
+//!       infinite samples.  Such samples never are included in the
+//!       statistics, since a single one would make the mean and the
+//!       moments meaningless.
+//!
+//!     * CountAndIgnore, the default, drops the sample but counts it.
+//!       The counts are returned as the nans and infinities fields of
+//!       the Printable in export_stats(), and Printable::rejected()
+//!       gives their sum.  The window types also count the samples in
+//!       their histograms, as before.  The counts are kept across
+//!       clear() operations.
+//!
+//!     * Ignore drops the sample silently.
+//!
+//!     * Panic panics, for applications that consider such a sample a
+//!       bug.
+//!
+//!     * The policy is set via set_non_finite_policy() on the basic
+//!       types, or FloatHier::new_hier_with_policy() for a hierarchy.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::running_float::RunningFloat;
+//!     use rustics::non_finite::NonFinitePolicy;
+//!
+//!     let mut ratios = RunningFloat::new("Hit Ratios", &None);
+//!
+//!     assert!(ratios.non_finite_policy() == NonFinitePolicy::CountAndIgnore);
+//!
+//!     ratios.record_f64(0.5);
+//!     ratios.record_f64(f64::NAN);
+//!     ratios.record_f64(f64::INFINITY);
+//!
+//!     // The bad samples are counted, but they don't affect the mean.
+//!
+//!     let stats = ratios.export_stats();
+//!
+//!     assert!(stats.printable.rejected() == 2);
+//!     assert!(ratios.mean()              == 0.5);
+//!
+//!     // Now drop them without counting.
+//!
+//!     let mut quiet = RunningFloat::new("Quiet Ratios", &None);
+//!
+//!     quiet.set_non_finite_policy(NonFinitePolicy::Ignore);
+//!     quiet.record_f64(f64::NAN);
+//!
+//!     assert!(quiet.export_stats().printable.rejected() == 0);
+//!```
+
+/// NonFinitePolicy selects the handling of NaN and infinite samples.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum NonFinitePolicy {
+    #[default]
+    CountAndIgnore,
+    Ignore,
+    Panic,
+}
+
+impl NonFinitePolicy {
+    /// Applies the policy to a sample that isn't finite.  Returns true
+    /// if the sample should be counted.  The name identifies the
+    /// instance in the panic message.
+
+    pub fn counts(&self, name: &str, sample: f64) -> bool {
+        match self {
+            NonFinitePolicy::CountAndIgnore => true,
+            NonFinitePolicy::Ignore         => false,
+
+            NonFinitePolicy::Panic => {
+                panic!("NonFinitePolicy:  \"{}\" received the sample {}.", name, sample);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_counts() {
+        assert!(NonFinitePolicy::default() == NonFinitePolicy::CountAndIgnore);
+
+        assert!( NonFinitePolicy::CountAndIgnore.counts("Test", f64::NAN     ));
+        assert!(!NonFinitePolicy::Ignore        .counts("Test", f64::INFINITY));
+    }
+
+    #[test]
+    fn run_tests() {
+        test_counts();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_panic() {
+        let _ = NonFinitePolicy::Panic.counts("Test", f64::NEG_INFINITY);
+    }
+}
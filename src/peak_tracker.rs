@@ -0,0 +1,586 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * PeakTracker
+//!     * PeakTracker records the maximum value seen in each of a series
+//!       of fixed intervals, like the worst queue depth or latency per
+//!       minute, and keeps the peaks of the last n intervals.
+//!
+//!     * Intervals are ended by calling tick(), or by a timer set with
+//!       set_interval().  With a timer, the intervals that have ended
+//!       are closed whenever a sample is recorded or the instance is
+//!       queried, and intervals with no samples are kept as empty.  The
+//!       timer is read via finish(), so it should not be shared with
+//!       other users.
+//!
+//!     * print() shows the current peak, the largest peak in the history,
+//!       and the peak for each interval in the history, newest first.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::peak_tracker::PeakTracker;
+//!
+//!     // Keep the peak queue depth for the last 60 intervals.
+//!
+//!     let mut queue_depth = PeakTracker::new("Queue Depth", 60, &None);
+//!
+//!     for i in 1..=10 {
+//!         queue_depth.record_i64(i);
+//!     }
+//!
+//!     // End the interval.  The application might call tick() once a
+//!     // minute, for example.
+//!
+//!     queue_depth.tick();
+//!     queue_depth.record_i64(4);
+//!
+//!     assert!(queue_depth.current_peak() == Some(4));
+//!     assert!(queue_depth.peaks()        == vec![ Some(10) ]);
+//!     assert!(queue_depth.max_peak()     == Some(10));
+//!
+//!     queue_depth.print();
+//!```
+
+use std::any::Any;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use super::Rustics;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use super::ExportStats;
+use super::PrinterBox;
+use super::PrinterOption;
+use super::PrintOption;
+use super::Units;
+use super::TimerBox;
+use super::printable::Printable;
+use super::parse_print_opts;
+use super::printer_mut;
+use super::timer_mut;
+
+// The timer used to end intervals, and the interval length, in
+// ticks.  The elapsed field holds the time since the current
+// interval started.
+
+#[derive(Clone)]
+struct IntervalTimer {
+    timer:      TimerBox,
+    interval:   i64,
+    elapsed:    Cell<i64>,
+}
+
+/// PeakTracker keeps the peak value for each of a series of
+/// intervals.
+
+#[derive(Clone)]
+pub struct PeakTracker {
+    name:           String,
+    title:          String,
+    id:             usize,
+    history_size:   usize,
+    count:          u64,
+    current:        Cell<Option<i64>>,
+    peaks:          RefCell<VecDeque<Option<i64>>>,
+    intervals:      Cell<u64>,
+    timer:          Option<IntervalTimer>,
+    printer:        PrinterBox,
+    units:          Units,
+}
+
+impl PeakTracker {
+    /// Creates an instance that keeps the peaks of the last
+    /// history_size intervals.
+
+    pub fn new(name: &str, history_size: usize, print_opts: &PrintOption) -> PeakTracker {
+        if history_size == 0 {
+            panic!("PeakTracker::new:  The history size is zero.");
+        }
+
+        let (printer, title, units, _histo_opts) = parse_print_opts(print_opts, name);
+
+        let name      = String::from(name);
+        let id        = usize::MAX;
+        let count     = 0;
+        let current   = Cell::new(None);
+        let peaks     = RefCell::new(VecDeque::with_capacity(history_size));
+        let intervals = Cell::new(0);
+        let timer     = None;
+
+        PeakTracker {
+            name,       title,      id,         history_size,
+            count,      current,    peaks,      intervals,
+            timer,      printer,    units
+        }
+    }
+
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+    }
+
+    /// Uses the given timer to end an interval each time the given
+    /// number of seconds passes.  The timer is restarted, so the
+    /// current interval starts with this call.
+
+    pub fn set_interval(&mut self, timer: TimerBox, seconds: u64) {
+        if seconds == 0 {
+            panic!("PeakTracker::set_interval:  The interval is zero.");
+        }
+
+        let hz = timer.borrow().hz();
+
+        if hz > i64::MAX as u128 {
+            panic!("PeakTracker::set_interval:  The timer frequency is too high.");
+        }
+
+        timer_mut!(timer).start();
+
+        let interval = (seconds as i64).saturating_mul(hz as i64);
+        let elapsed  = Cell::new(0);
+
+        self.timer = Some(IntervalTimer { timer, interval, elapsed });
+    }
+
+    /// Returns the number of intervals in the history.
+
+    pub fn history_size(&self) -> usize {
+        self.history_size
+    }
+
+    /// Ends the current interval and starts a new one.
+
+    pub fn tick(&mut self) {
+        self.close_interval();
+    }
+
+    /// Returns the peak for the current interval, or None if no
+    /// samples have been recorded in it.
+
+    pub fn current_peak(&self) -> Option<i64> {
+        self.advance();
+        self.current.get()
+    }
+
+    /// Returns the peaks of the intervals in the history, oldest
+    /// first.  An interval with no samples has a peak of None.
+
+    pub fn peaks(&self) -> Vec<Option<i64>> {
+        self.advance();
+        self.peaks.borrow().iter().copied().collect()
+    }
+
+    /// Returns the largest peak in the history and the current
+    /// interval.
+
+    pub fn max_peak(&self) -> Option<i64> {
+        self.advance();
+
+        let history = self.peaks.borrow().iter().flatten().copied().max();
+
+        history.max(self.current.get())
+    }
+
+    /// Returns the number of intervals that have ended since the
+    /// instance was created or cleared.
+
+    pub fn intervals(&self) -> u64 {
+        self.advance();
+        self.intervals.get()
+    }
+
+    // Move the current peak into the history.
+
+    fn close_interval(&self) {
+        let mut peaks = self.peaks.borrow_mut();
+
+        if peaks.len() == self.history_size {
+            peaks.pop_front();
+        }
+
+        peaks.push_back(self.current.get());
+
+        self.current.set(None);
+        self.intervals.set(self.intervals.get() + 1);
+    }
+
+    // Read the timer, if there is one, and close any intervals that
+    // have ended.
+
+    fn advance(&self) {
+        let timer = match &self.timer {
+            Some(timer) => timer,
+            None        => return,
+        };
+
+        let elapsed = timer.elapsed.get() + timer_mut!(timer.timer).finish();
+        let ended   = elapsed / timer.interval;
+
+        // Only the last history_size intervals can be kept, so
+        // don't loop over more than that.
+
+        let closes = (ended as u64).min(self.history_size as u64 + 1);
+
+        for _i in 0..closes {
+            self.close_interval();
+        }
+
+        let skipped = ended as u64 - closes;
+
+        self.intervals.set(self.intervals.get() + skipped);
+        timer.elapsed.set(elapsed % timer.interval);
+    }
+}
+
+impl Rustics for PeakTracker {
+    fn record_i64(&mut self, sample: i64) {
+        self.advance();
+
+        let peak = self.current.get().map_or(sample, |peak| peak.max(sample));
+
+        self.current.set(Some(peak));
+        self.count += 1;
+    }
+
+    fn record_f64(&mut self, _sample: f64) {
+        panic!("PeakTracker::record_f64:  not supported");
+    }
+
+    fn record_event(&mut self) {
+        panic!("PeakTracker::record_event:  not supported");
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        panic!("PeakTracker::record_event_report:  not supported");
+    }
+
+    fn record_time(&mut self, _sample: i64) {
+        panic!("PeakTracker::record_time:  not supported");
+    }
+
+    fn record_interval(&mut self, _timer: &mut TimerBox) {
+        panic!("PeakTracker::record_interval:  not supported");
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn class(&self) -> &str {
+        "peak"
+    }
+
+    /// Returns the number of samples recorded since the instance
+    /// was created or cleared.
+
+    fn count(&self) -> u64 {
+        self.count
+    }
+
+    fn log_mode(&self) -> isize {
+        panic!("PeakTracker::log_mode:  not supported");
+    }
+
+    fn mean(&self) -> f64 {
+        panic!("PeakTracker::mean:  not supported");
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        panic!("PeakTracker::standard_deviation:  not supported");
+    }
+
+    fn variance(&self) -> f64 {
+        panic!("PeakTracker::variance:  not supported");
+    }
+
+    fn skewness(&self) -> f64 {
+        panic!("PeakTracker::skewness:  not supported");
+    }
+
+    fn kurtosis(&self) -> f64 {
+        panic!("PeakTracker::kurtosis:  not supported");
+    }
+
+    fn int_extremes(&self) -> bool {
+        false
+    }
+
+    fn float_extremes(&self) -> bool {
+        false
+    }
+
+    fn min_i64(&self) -> i64 {
+        panic!("PeakTracker::min_i64:  not supported");
+    }
+
+    fn min_f64(&self) -> f64 {
+        panic!("PeakTracker::min_f64:  not supported");
+    }
+
+    fn max_i64(&self) -> i64 {
+        panic!("PeakTracker::max_i64:  not supported");
+    }
+
+    fn max_f64(&self) -> f64 {
+        panic!("PeakTracker::max_f64:  not supported");
+    }
+
+    fn precompute(&mut self) {
+    }
+
+    fn clear(&mut self) {
+        self.count = 0;
+        self.current.set(None);
+        self.peaks.borrow_mut().clear();
+        self.intervals.set(0);
+
+        if let Some(timer) = &self.timer {
+            timer_mut!(timer.timer).start();
+            timer.elapsed.set(0);
+        }
+    }
+
+    fn print(&self) {
+        self.print_opts(None, None);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        let current = self.current_peak();
+        let maximum = self.max_peak();
+        let peaks   = self.peaks();
+        let printer = printer_mut!(printer_box);
+
+        printer.print(title);
+        Printable::print_integer("Count",     self.count as i64,           printer);
+        Printable::print_integer("Intervals", self.intervals.get() as i64, printer);
+
+        if let Some(current) = current {
+            Printable::print_integer_units("Current", current, printer, &self.units);
+        }
+
+        if let Some(maximum) = maximum {
+            Printable::print_integer_units("Max Peak", maximum, printer, &self.units);
+        }
+
+        if !peaks.is_empty() {
+            printer.print("  Peak History");
+
+            for (i, peak) in peaks.iter().rev().enumerate() {
+                let name = format!("-{}", i + 1);
+
+                match peak {
+                    Some(peak) => Printable::print_integer_units(&name, *peak, printer, &self.units),
+                    None       => printer.print(&format!("    {:<12} {:>12}", name, "-")),
+                }
+            }
+        }
+
+        printer.print("");
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = String::from(title);
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+
+    // For internal use only.
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<PeakTracker>(other.generic()) {
+            std::ptr::eq(self, other)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn export_stats(&self) -> ExportStats {
+        let maximum    = self.max_peak();
+        let n          = self.count;
+        let nans       = 0;
+        let infinities = 0;
+        let min_i64    = i64::MIN;
+        let max_i64    = maximum.unwrap_or(i64::MIN);
+        let min_f64    = f64::MIN;
+        let max_f64    = f64::MAX;
+        let log_mode   = 0;
+        let mode_value = 0.0;
+        let mean       = 0.0;
+        let variance   = 0.0;
+        let skewness   = 0.0;
+        let kurtosis   = 0.0;
+        let units      = self.units.clone();
+
+        let printable =
+            Printable {
+                n,           nans,      infinities,  min_i64,   max_i64,   min_f64,
+                max_f64,     log_mode,  mean,        variance,  skewness,  kurtosis,
+                mode_value,  units
+            };
+
+        let log_histogram   = None;
+        let float_histogram = None;
+
+        ExportStats { printable, log_histogram, float_histogram }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::PrintOpts;
+    use crate::tests::check_printer_box;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
+
+    fn test_tick() {
+        let mut peaks = PeakTracker::new("Tick Test", 3, &None);
+
+        assert!(peaks.class()        == "peak");
+        assert!(peaks.history_size() == 3     );
+        assert!(peaks.current_peak().is_none());
+        assert!(peaks.max_peak    ().is_none());
+
+        for interval in 1..=5 {
+            for i in 0..10 {
+                peaks.record_i64(interval * 100 - i);
+            }
+
+            peaks.tick();
+        }
+
+        peaks.tick();
+
+        assert!(peaks.count()     == 50);
+        assert!(peaks.intervals() == 6 );
+        assert!(peaks.peaks()     == vec![ Some(400), Some(500), None ]);
+        assert!(peaks.max_peak()  == Some(500));
+
+        peaks.record_i64(-5);
+
+        assert!(peaks.current_peak() == Some(-5));
+
+        peaks.clear();
+
+        assert!(peaks.count() == 0);
+        assert!(peaks.peaks().is_empty());
+        assert!(peaks.intervals() == 0);
+    }
+
+    fn test_timer() {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+        let hz        = 1_000_000_000;
+
+        let mut peaks = PeakTracker::new("Timer Test", 4, &None);
+
+        peaks.set_interval(timer, 60);
+
+        // Record a sample every 10 seconds for 5 minutes.
+
+        for i in 1..=30 {
+            peaks.record_i64(i);
+            clock.borrow_mut().current += 10 * hz;
+        }
+
+        assert!(peaks.intervals() == 5);
+        assert!(peaks.peaks()     == vec![ Some(12), Some(18), Some(24), Some(30) ]);
+        assert!(peaks.current_peak().is_none());
+
+        // Skip a long time.  Only the history size matters.
+
+        clock.borrow_mut().current += 3600 * hz;
+
+        assert!(peaks.peaks()     == vec![ None, None, None, None ]);
+        assert!(peaks.intervals() == 65);
+    }
+
+    fn test_print_output() {
+        let expected =
+            [
+                "Peak Test",
+                "    Count                   4 ",
+                "    Intervals               2 ",
+                "    Current                 7 packets",
+                "    Max Peak               20 packets",
+                "  Peak History",
+                "    -1                      -",
+                "    -2                     20 packets",
+                ""
+            ];
+
+        let printer    = check_printer_box(&expected, true, false);
+        let units      = Units::new("packet", "packets");
+        let print_opts = PrintOpts::builder().printer(printer).units(units).build();
+
+        let mut peaks = PeakTracker::new("Peak Test", 4, &Some(print_opts));
+
+        peaks.record_i64(20);
+        peaks.record_i64(10);
+        peaks.tick();
+        peaks.tick();
+        peaks.record_i64(1);
+        peaks.record_i64(7);
+        peaks.print();
+    }
+
+    #[test]
+    fn run_tests() {
+        test_tick        ();
+        test_timer       ();
+        test_print_output();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_history() {
+        let _ = PeakTracker::new("Zero", 0, &None);
+    }
+}
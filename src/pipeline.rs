@@ -0,0 +1,272 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * RusticsIterator
+//!     * RusticsIterator is an extension trait for iterators that adds
+//!       adapters to record the values yielded by an iterator, or the
+//!       times between them, into a Rustics instance.  It is implemented
+//!       for every iterator, so importing the trait is enough to use the
+//!       adapters.
+//!
+//!     * record_each() records each item as a sample.  The item type
+//!       must implement the Sample trait, which is provided for the
+//!       primitive integer and float types and references to them.
+//!       Integers are recorded with record_i64() and floats with
+//!       record_f64().
+//!
+//!     * time_each() records the time between successive items, i.e.,
+//!       the time spent producing an item plus the time the consumer
+//!       spent on the previous item.  The first interval starts at the
+//!       first call to next().  time_each() uses a timer from the
+//!       process-wide factory, so the Rustics instance should use the
+//!       same hz, as RunningTime::new_default() does.  time_each_with()
+//!       takes the timer to use.
+//!
+//!     * The adapters borrow the Rustics instance mutably, so it can be
+//!       printed once the pipeline has been consumed.
+//!
+//! * Sample
+//!     * Sample converts an item into a sample for a Rustics instance.
+//!       Applications can implement it for their own item types.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::time::DurationTimer;
+//!     use rustics::running_integer::RunningInteger;
+//!     use rustics::running_time::RunningTime;
+//!     use rustics::pipeline::RusticsIterator;
+//!
+//!     let mut sizes   = RunningInteger::new("Record Sizes", &None);
+//!     let mut latency = RunningTime::new("Record Latency", DurationTimer::new_box(), &None);
+//!
+//!     // Instrument a pipeline to record the size of each record that
+//!     // passes the filter and the time taken per record.
+//!
+//!     let total: i64 =
+//!         (1..=100)
+//!             .filter(|size| size % 2 == 0)
+//!             .record_each(&mut sizes)
+//!             .time_each(&mut latency)
+//!             .sum();
+//!
+//!     assert!(total           == 2550);
+//!     assert!(sizes.count()   == 50  );
+//!     assert!(sizes.min_i64() == 2   );
+//!     assert!(sizes.max_i64() == 100 );
+//!     assert!(latency.count() == 50  );
+//!
+//!     sizes.print();
+//!     latency.print();
+//!```
+
+use super::Rustics;
+use super::TimerBox;
+use super::timer_mut;
+use super::time::default_timer;
+
+/// Sample defines how an iterator item is recorded into a Rustics
+/// instance by record_each().
+
+pub trait Sample {
+    fn record(&self, rustics: &mut dyn Rustics);
+}
+
+// Implement Sample for the integer types that fit in an i64.
+
+macro_rules! integer_sample {
+    ($($type:ty),*) => {
+        $(
+            impl Sample for $type {
+                fn record(&self, rustics: &mut dyn Rustics) {
+                    rustics.record_i64(*self as i64);
+                }
+            }
+        )*
+    }
+}
+
+integer_sample!(i8, i16, i32, i64, u8, u16, u32);
+
+impl Sample for f32 {
+    fn record(&self, rustics: &mut dyn Rustics) {
+        rustics.record_f64(*self as f64);
+    }
+}
+
+impl Sample for f64 {
+    fn record(&self, rustics: &mut dyn Rustics) {
+        rustics.record_f64(*self);
+    }
+}
+
+impl<T: Sample + ?Sized> Sample for &T {
+    fn record(&self, rustics: &mut dyn Rustics) {
+        (**self).record(rustics);
+    }
+}
+
+impl<T: Sample + ?Sized> Sample for &mut T {
+    fn record(&self, rustics: &mut dyn Rustics) {
+        (**self).record(rustics);
+    }
+}
+
+/// RusticsIterator adds the instrumentation adapters to all
+/// iterators.
+
+pub trait RusticsIterator: Iterator + Sized {
+    /// Records each item into the given Rustics instance as it is
+    /// yielded.
+
+    fn record_each(self, rustics: &mut dyn Rustics) -> RecordEach<'_, Self>
+            where Self::Item: Sample {
+        RecordEach { iter: self, rustics }
+    }
+
+    /// Records the time between successive items into the given
+    /// Rustics instance, using a timer from the process-wide factory.
+
+    fn time_each(self, rustics: &mut dyn Rustics) -> TimeEach<'_, Self> {
+        self.time_each_with(rustics, default_timer())
+    }
+
+    /// Records the time between successive items into the given
+    /// Rustics instance, using the given timer.
+
+    fn time_each_with(self, rustics: &mut dyn Rustics, timer: TimerBox) -> TimeEach<'_, Self> {
+        let started = false;
+
+        TimeEach { iter: self, rustics, timer, started }
+    }
+}
+
+impl<I: Iterator> RusticsIterator for I { }
+
+/// RecordEach is the iterator returned by record_each().
+
+pub struct RecordEach<'a, I: Iterator> {
+    iter:     I,
+    rustics:  &'a mut dyn Rustics,
+}
+
+impl<I: Iterator> Iterator for RecordEach<'_, I> where I::Item: Sample {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let item = self.iter.next()?;
+
+        item.record(self.rustics);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// TimeEach is the iterator returned by time_each() and
+/// time_each_with().
+
+pub struct TimeEach<'a, I: Iterator> {
+    iter:     I,
+    rustics:  &'a mut dyn Rustics,
+    timer:    TimerBox,
+    started:  bool,
+}
+
+impl<I: Iterator> Iterator for TimeEach<'_, I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if !self.started {
+            timer_mut!(self.timer).start();
+            self.started = true;
+        }
+
+        let item = self.iter.next()?;
+
+        self.rustics.record_interval(&mut self.timer);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use crate::running_float::RunningFloat;
+    use crate::running_integer::RunningInteger;
+    use crate::running_time::RunningTime;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
+
+    fn test_record_each() {
+        let mut integers = RunningInteger::new("Integers", &None);
+        let mut floats   = RunningFloat  ::new("Floats",   &None);
+
+        let values  = [ 1_u8, 2, 3, 4 ];
+        let doubled = values.iter().record_each(&mut integers).map(|value| *value as f64 * 2.0);
+        let result  = doubled.record_each(&mut floats).collect::<Vec<f64>>();
+
+        assert!(result == [ 2.0, 4.0, 6.0, 8.0 ]);
+
+        assert!(integers.count()   == 4  );
+        assert!(integers.mean()    == 2.5);
+        assert!(integers.max_i64() == 4  );
+        assert!(floats.count()     == 4  );
+        assert!(floats.max_f64()   == 8.0);
+
+        // Items that aren't consumed aren't recorded.
+
+        let mut partial = RunningInteger::new("Partial", &None);
+        let     first   = (10..20).record_each(&mut partial).take(3).count();
+
+        assert!(first             == 3 );
+        assert!(partial.count()   == 3 );
+        assert!(partial.max_i64() == 12);
+        assert!((0..5).record_each(&mut partial).size_hint() == (5, Some(5)));
+    }
+
+    fn test_time_each() {
+        let     increment = 10;
+        let     current   = 0;
+        let     clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let     timer     = ClockTimer::new_box(clock);
+        let mut times     = RunningTime::new("Times", timer.clone(), &None);
+
+        // Each clock read advances the clock by the increment, and each
+        // item reads the clock once, so every interval is the same.
+
+        let items = [ "a", "b", "c" ].iter().time_each_with(&mut times, timer).count();
+
+        assert!(items           == 3 );
+        assert!(times.count()   == 3 );
+        assert!(times.min_i64() == 10);
+        assert!(times.max_i64() == 10);
+
+        // An empty iterator records nothing.
+
+        let mut empty = RunningTime::new_default("Empty", &None);
+
+        assert!(std::iter::empty::<i64>().time_each(&mut empty).count() == 0);
+        assert!(empty.count() == 0);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_record_each();
+        test_time_each  ();
+    }
+}
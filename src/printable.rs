@@ -15,6 +15,31 @@
 //!     * This module provides helper functions for formatting integers
 //!       and time values.
 //!
+//!     * The csv_header() and csv_row() functions produce CSV output with
+//!       a column set chosen via the CsvColumn enum.  The set types use
+//!       these functions to implement export_csv() and print_csv().
+//!
+//!     * The deterministic_format Cargo feature rounds floating-point
+//!       values to 15 significant digits before truncating them for
+//!       printing.  Values that differ only in the last bit, as can
+//!       happen across platforms and compiler versions, then print
+//!       the same way, which keeps golden-file tests of reports stable.
+//!       Negative zero is printed as zero.
+//!
+//! * PrintFields
+//!     * PrintFields is a set of PrintField values that selects the summary
+//!       lines that the print functions produce, e.g., just the count, mean,
+//!       and maximum.  It is set in HistoOpts, so it can be given in the
+//!       PrintOpts for an instance.  The histogram can be chosen as a field,
+//!       too.
+//!
+//! * TimeUnit
+//!     * TimeUnit specifies a fixed unit for printing time values.  The
+//!       time types can print the mean, standard deviation, and extremes
+//!       in a secondary TimeUnit in addition to the automatically-chosen
+//!       unit so that values from different instances can be compared
+//!       directly.
+//!
 //! ## Example
 //!```
 //!     use rustics::printable::Printable;
@@ -97,7 +122,176 @@
 // by standard output routines.
 
 use super::Printer;
+use super::Rustics;
 use super::Units;
+use super::SCHEMA_VERSION;
+use super::log_histogram::LogHistogram;
+use super::log_histogram::bucket_max as log_bucket_max;
+use super::float_histogram::FloatHistogram;
+use super::float_histogram::bucket_max as float_bucket_max;
+
+/// CsvColumn selects a column for the CSV output functions.  The
+/// Percentile column takes a percentile in the range 0 to 100, and
+/// is estimated from the histogram of the instance.  The Schema
+/// column gives the schema version of the output.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CsvColumn {
+    Title,
+    Name,
+    Class,
+    Count,
+    Mean,
+    StdDev,
+    Variance,
+    Min,
+    Max,
+    Percentile(f64),
+    Schema,
+}
+
+/// PrintField names one summary line, or the histogram, in the print
+/// output.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PrintField {
+    Count,
+    NaNs,
+    Infinities,
+    Minimum,
+    Maximum,
+    LogMode,
+    ModeValue,
+    Mean,
+    StdDev,
+    Variance,
+    Skewness,
+    Kurtosis,
+    Histogram,
+}
+
+impl PrintField {
+    fn bit(&self) -> u32 {
+        1 << (*self as u32)
+    }
+}
+
+/// PrintFields is the set of fields to print.  The default is all
+/// the fields.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PrintFields {
+    mask:   u32,
+}
+
+impl PrintFields {
+    /// Returns the set of all the fields.
+
+    pub fn all() -> PrintFields {
+        let mask = (PrintField::Histogram.bit() << 1) - 1;
+
+        PrintFields { mask }
+    }
+
+    /// Returns the empty set.
+
+    pub fn none() -> PrintFields {
+        let mask = 0;
+
+        PrintFields { mask }
+    }
+
+    /// Returns the set containing just the given fields.
+
+    pub fn only(fields: &[PrintField]) -> PrintFields {
+        let mut result = PrintFields::none();
+
+        for field in fields {
+            result = result.with(*field);
+        }
+
+        result
+    }
+
+    /// Returns this set with the given field added.
+
+    pub fn with(self, field: PrintField) -> PrintFields {
+        let mask = self.mask | field.bit();
+
+        PrintFields { mask }
+    }
+
+    /// Returns this set with the given field removed.
+
+    pub fn without(self, field: PrintField) -> PrintFields {
+        let mask = self.mask & !field.bit();
+
+        PrintFields { mask }
+    }
+
+    /// Returns true if the given field is in the set.
+
+    pub fn contains(&self, field: PrintField) -> bool {
+        self.mask & field.bit() != 0
+    }
+}
+
+impl Default for PrintFields {
+    fn default() -> Self {
+        PrintFields::all()
+    }
+}
+
+/// TimeUnit selects a fixed unit for printing a time value.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TimeUnit {
+    Nanosecond,
+    Microsecond,
+    Millisecond,
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl TimeUnit {
+    /// Returns the length of the unit in nanoseconds.
+
+    pub fn nanoseconds(&self) -> f64 {
+        match self {
+            TimeUnit::Nanosecond   => 1.0,
+            TimeUnit::Microsecond  => 1_000.0,
+            TimeUnit::Millisecond  => 1_000_000.0,
+            TimeUnit::Second       => 1_000_000_000.0,
+            TimeUnit::Minute       => 60.0 * 1_000_000_000.0,
+            TimeUnit::Hour         => 60.0 * 60.0 * 1_000_000_000.0,
+            TimeUnit::Day          => 24.0 * 60.0 * 60.0 * 1_000_000_000.0,
+        }
+    }
+
+    /// Returns the name of the unit, made plural unless the value
+    /// is exactly one.
+
+    pub fn name(&self, value: f64) -> String {
+        let singular =
+            match self {
+                TimeUnit::Nanosecond   => "nanosecond",
+                TimeUnit::Microsecond  => "microsecond",
+                TimeUnit::Millisecond  => "millisecond",
+                TimeUnit::Second       => "second",
+                TimeUnit::Minute       => "minute",
+                TimeUnit::Hour         => "hour",
+                TimeUnit::Day          => "day",
+            };
+
+        if value == 1.0 {
+            singular.to_string()
+        } else {
+            format!("{}s", singular)
+        }
+    }
+}
 
 /// The Printable struct is used to pass data to the standard print
 /// functions shared by all the code.  Developers who are implementing
@@ -122,6 +316,13 @@ pub struct Printable {
 }
 
 impl Printable {
+    /// Returns the number of NaN and infinite samples that were
+    /// rejected and counted.  See the non_finite module.
+
+    pub fn rejected(&self) -> u64 {
+        self.nans + self.infinities
+    }
+
     /// The commas() function inserts commas into a string
     /// containing the character form of an integer.  This
     /// input string might or might not have a leading "+" or
@@ -310,6 +511,44 @@ impl Printable {
         printer.print(&output);
     }
 
+    // Format a value in scientific notation.  By default, this
+    // produces the shortest string that converts back to the value,
+    // and format_float() truncates the mantissa.  The last bit of a
+    // computed value can vary across platforms and toolchains, which
+    // can change the shortest string enough to change the printed
+    // digits.  With the deterministic_format feature, the value first
+    // is rounded to 15 significant digits, the precision an f64 always
+    // holds, from its exact binary value, so those variations are
+    // absorbed before the mantissa is truncated.
+
+    #[cfg(not(feature = "deterministic_format"))]
+    fn scientific(value: f64) -> String {
+        format!("{:+e}", value)
+    }
+
+    #[cfg(feature = "deterministic_format")]
+    fn scientific(value: f64) -> String {
+        // Print negative zero as zero, so that a computation that
+        // produces either prints the same result.
+
+        let value =
+            if value == 0.0 {
+                0.0
+            } else {
+                value
+            };
+
+        format!("{:+.14e}", value)
+    }
+
+    /// Returns true if the crate was built with the deterministic_format
+    /// feature, which makes the printed floating-point values depend only
+    /// on the values themselves.
+
+    pub fn deterministic_format() -> bool {
+        cfg!(feature = "deterministic_format")
+    }
+
     /// Converts an f64 value into a mantissa and exponent string.
 
     pub fn format_float(value: f64) -> (String, String) {
@@ -318,7 +557,7 @@ impl Printable {
         // line up.
 
         let value =
-             format!("{:+e}", value)
+             Printable::scientific(value)
             .replace('e',   " e+")
             .replace("e+-", " e-") ;
 
@@ -371,7 +610,7 @@ impl Printable {
         // Now extract the exponent and pad to at least 4 bytes.
 
         let     size     = 4;
-        let mut exponent = value.split(' ').last().unwrap().to_string();
+        let mut exponent = value.split(' ').next_back().unwrap().to_string();
 
         if exponent.len() < size {
             for _i in 0..size - exponent.len() {
@@ -395,11 +634,73 @@ impl Printable {
         }
     }
 
+    /// Converts a time interval in clock ticks into the given unit.
+
+    pub fn convert_time(time: f64, hz: i64, unit: TimeUnit) -> f64 {
+        let nanoseconds = time * (1_000_000_000.0 / hz as f64);
+
+        nanoseconds / unit.nanoseconds()
+    }
+
+    /// Prints a time value in human-usable form, followed by the value
+    /// in the given secondary unit.
+
+    pub fn print_time_dual(name: &str, time: f64, hz: i64, secondary: TimeUnit,
+            printer: &mut dyn Printer) {
+        let (scaled_time, unit) = Self::scale_time(time, hz);
+
+        let primary =
+            if scaled_time > 999999.0 {
+                let (mantissa, exponent) = Self::format_float(scaled_time);
+
+                format!("    {:<13}    {} {} {}", name, mantissa, exponent, unit)
+            } else {
+                format!("    {:<12} {:>12.3} {}", name, scaled_time, unit)
+            };
+
+        // Use scientific notation for values that wouldn't be readable
+        // with three decimal places.
+
+        let value = Self::convert_time(time, hz, secondary);
+
+        let value_string =
+            if value == 0.0 || (value.abs() >= 0.001 && value.abs() < 1_000_000.0) {
+                format!("{:.3}", value)
+            } else {
+                format!("{:.3e}", value)
+            };
+
+        let output = format!("{}  ({} {})", primary, value_string, secondary.name(value));
+
+        printer.print(&output);
+    }
+
+    // Print a time, adding the secondary unit if one is given.
+
+    fn print_time_option(name: &str, time: f64, hz: i64, secondary: Option<TimeUnit>,
+            printer: &mut dyn Printer) {
+        match secondary {
+            Some(unit) => Self::print_time_dual(name, time, hz, unit, printer),
+            None       => Self::print_time     (name, time, hz,       printer),
+        }
+    }
+
     /// Prints the common statistics for i64 (integer) samples as passed
     /// in a Printable instance.
 
     pub fn print_common_i64(&self, printer: &mut dyn Printer) {
-        Self::print_integer("Count", self.n as i64, printer);
+        self.print_common_i64_fields(PrintFields::all(), printer);
+    }
+
+    /// Prints the common i64 statistics that are in the given set of
+    /// fields.
+
+    pub fn print_common_i64_fields(&self, fields: PrintFields, printer: &mut dyn Printer) {
+        use PrintField::*;
+
+        if fields.contains(Count) {
+            Self::print_integer("Count", self.n as i64, printer);
+        }
 
         if self.n > 0 {
             let mode_value = 1_i64 << self.log_mode.abs();
@@ -412,10 +713,21 @@ impl Printable {
                     mode_value
                 };
 
-            Self::print_integer_units("Minimum",    self.min_i64,  printer, &self.units);
-            Self::print_integer_units("Maximum",    self.max_i64,  printer, &self.units);
-            Self::print_integer      ("Log Mode",   self.log_mode, printer             );
-            Self::print_integer_units("Mode Value", mode_value,    printer, &self.units);
+            if fields.contains(Minimum) {
+                Self::print_integer_units("Minimum",    self.min_i64,  printer, &self.units);
+            }
+
+            if fields.contains(Maximum) {
+                Self::print_integer_units("Maximum",    self.max_i64,  printer, &self.units);
+            }
+
+            if fields.contains(LogMode) {
+                Self::print_integer      ("Log Mode",   self.log_mode, printer             );
+            }
+
+            if fields.contains(ModeValue) {
+                Self::print_integer_units("Mode Value", mode_value,    printer, &self.units);
+            }
         }
     }
 
@@ -423,14 +735,39 @@ impl Printable {
     /// in a Printable instance.
 
     pub fn print_common_f64(&self, printer: &mut dyn Printer) {
-        Self::print_integer("Count",      self.n          as i64, printer);
-        Self::print_integer("NaNs",       self.nans       as i64, printer);
-        Self::print_integer("Infinities", self.infinities as i64, printer);
+        self.print_common_f64_fields(PrintFields::all(), printer);
+    }
+
+    /// Prints the common f64 statistics that are in the given set of
+    /// fields.
+
+    pub fn print_common_f64_fields(&self, fields: PrintFields, printer: &mut dyn Printer) {
+        use PrintField::*;
+
+        if fields.contains(Count) {
+            Self::print_integer("Count",      self.n          as i64, printer);
+        }
+
+        if fields.contains(NaNs) {
+            Self::print_integer("NaNs",       self.nans       as i64, printer);
+        }
+
+        if fields.contains(Infinities) {
+            Self::print_integer("Infinities", self.infinities as i64, printer);
+        }
 
         if self.n > 0 {
-            Self::print_float_units("Minimum",     self.min_f64,    printer, &self.units);
-            Self::print_float_units("Maximum",     self.max_f64,    printer, &self.units);
-            Self::print_float_units("Mode Value",  self.mode_value, printer, &self.units);
+            if fields.contains(Minimum) {
+                Self::print_float_units("Minimum",     self.min_f64,    printer, &self.units);
+            }
+
+            if fields.contains(Maximum) {
+                Self::print_float_units("Maximum",     self.max_f64,    printer, &self.units);
+            }
+
+            if fields.contains(ModeValue) {
+                Self::print_float_units("Mode Value",  self.mode_value, printer, &self.units);
+            }
         }
     }
 
@@ -438,12 +775,35 @@ impl Printable {
     /// This includes values like the mean.
 
     pub fn print_common_float(&self, printer: &mut dyn Printer) {
+        self.print_common_float_fields(PrintFields::all(), printer);
+    }
+
+    /// Prints the common float statistics that are in the given set
+    /// of fields.
+
+    pub fn print_common_float_fields(&self, fields: PrintFields, printer: &mut dyn Printer) {
+        use PrintField::*;
+
         if self.n > 0 {
-            Self::print_float_units("Mean",     self.mean,            printer, &self.units);
-            Self::print_float_units("Std Dev",  self.variance.sqrt(), printer, &self.units);
-            Self::print_float      ("Variance", self.variance,        printer             );
-            Self::print_float      ("Skewness", self.skewness,        printer             );
-            Self::print_float      ("Kurtosis", self.kurtosis,        printer             );
+            if fields.contains(Mean) {
+                Self::print_float_units("Mean",     self.mean,            printer, &self.units);
+            }
+
+            if fields.contains(StdDev) {
+                Self::print_float_units("Std Dev",  self.variance.sqrt(), printer, &self.units);
+            }
+
+            if fields.contains(Variance) {
+                Self::print_float      ("Variance", self.variance,        printer             );
+            }
+
+            if fields.contains(Skewness) {
+                Self::print_float      ("Skewness", self.skewness,        printer             );
+            }
+
+            if fields.contains(Kurtosis) {
+                Self::print_float      ("Kurtosis", self.kurtosis,        printer             );
+            }
         }
     }
 
@@ -471,28 +831,290 @@ impl Printable {
     /// mode of the pseudo-log is an exception.
 
     pub fn print_common_integer_times(&self, hz: i64, printer: &mut dyn Printer) {
-        Self::print_integer("Count", self.n as i64, printer);
+        self.print_common_integer_times_dual(hz, None, printer);
+    }
+
+    /// Prints integer values that are in time units as actual times,
+    /// adding the extremes in the secondary unit, if one is given.
+
+    pub fn print_common_integer_times_dual(&self, hz: i64, secondary: Option<TimeUnit>,
+            printer: &mut dyn Printer) {
+        self.print_common_integer_times_fields(hz, secondary, PrintFields::all(), printer);
+    }
+
+    /// Prints the integer time statistics that are in the given set
+    /// of fields.
+
+    pub fn print_common_integer_times_fields(&self, hz: i64, secondary: Option<TimeUnit>,
+            fields: PrintFields, printer: &mut dyn Printer) {
+        use PrintField::*;
+
+        if fields.contains(Count) {
+            Self::print_integer("Count", self.n as i64, printer);
+        }
 
         if self.n > 0 {
             let approximation = self.log_mode_to_time();
+            let min           = self.min_i64 as f64;
+            let max           = self.max_i64 as f64;
+
+            if fields.contains(Minimum) {
+                Self::print_time_option("Minimum",    min,           hz, secondary, printer);
+            }
+
+            if fields.contains(Maximum) {
+                Self::print_time_option("Maximum",    max,           hz, secondary, printer);
+            }
 
-            Self::print_time   ("Minimum",    self.min_i64 as f64, hz, printer);
-            Self::print_time   ("Maximum",    self.max_i64 as f64, hz, printer);
-            Self::print_integer("Log Mode",   self.log_mode,           printer);
-            Self::print_time   ("Mode Value", approximation,       hz, printer);
+            if fields.contains(LogMode) {
+                Self::print_integer    ("Log Mode",   self.log_mode,                printer);
+            }
+
+            if fields.contains(ModeValue) {
+                Self::print_time       ("Mode Value", approximation, hz,            printer);
+            }
         }
     }
 
     /// Prints the common f64 summary statistics for time samples.
 
     pub fn print_common_float_times(&self, hz: i64, printer: &mut dyn Printer) {
+        self.print_common_float_times_dual(hz, None, printer);
+    }
+
+    /// Prints the common f64 summary statistics for time samples,
+    /// adding the mean and standard deviation in the secondary unit,
+    /// if one is given.
+
+    pub fn print_common_float_times_dual(&self, hz: i64, secondary: Option<TimeUnit>,
+            printer: &mut dyn Printer) {
+        self.print_common_float_times_fields(hz, secondary, PrintFields::all(), printer);
+    }
+
+    /// Prints the f64 time statistics that are in the given set of
+    /// fields.
+
+    pub fn print_common_float_times_fields(&self, hz: i64, secondary: Option<TimeUnit>,
+            fields: PrintFields, printer: &mut dyn Printer) {
+        use PrintField::*;
+
         if self.n > 0 {
-            Self::print_time ("Mean",     self.mean,            hz, printer);
-            Self::print_time ("Std Dev",  self.variance.sqrt(), hz, printer);
-            Self::print_float("Variance", self.variance,            printer);
-            Self::print_float("Skewness", self.skewness,            printer);
-            Self::print_float("Kurtosis", self.kurtosis,            printer);
+            let std_dev = self.variance.sqrt();
+
+            if fields.contains(Mean) {
+                Self::print_time_option("Mean",     self.mean,     hz, secondary, printer);
+            }
+
+            if fields.contains(StdDev) {
+                Self::print_time_option("Std Dev",  std_dev,       hz, secondary, printer);
+            }
+
+            if fields.contains(Variance) {
+                Self::print_float      ("Variance", self.variance,                printer);
+            }
+
+            if fields.contains(Skewness) {
+                Self::print_float      ("Skewness", self.skewness,                printer);
+            }
+
+            if fields.contains(Kurtosis) {
+                Self::print_float      ("Kurtosis", self.kurtosis,                printer);
+            }
+        }
+    }
+
+    /// Returns the default column set for CSV output.
+
+    pub fn csv_default_columns() -> Vec<CsvColumn> {
+        vec![
+            CsvColumn::Title,
+            CsvColumn::Count,
+            CsvColumn::Mean,
+            CsvColumn::Variance,
+            CsvColumn::Min,
+            CsvColumn::Max,
+            CsvColumn::Schema
+        ]
+    }
+
+    /// Quotes a CSV field if it contains a comma, a quote, or a line
+    /// break.
+
+    pub fn csv_escape(field: &str) -> String {
+        if field.contains(',') || field.contains('"') || field.contains('\n') {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+
+    /// Creates the header line for CSV output with the given columns.
+
+    pub fn csv_header(columns: &[CsvColumn]) -> String {
+        let mut fields = Vec::with_capacity(columns.len());
+
+        for column in columns.iter() {
+            let field =
+                match column {
+                    CsvColumn::Title              => "title".to_string(),
+                    CsvColumn::Name               => "name".to_string(),
+                    CsvColumn::Class              => "class".to_string(),
+                    CsvColumn::Count              => "count".to_string(),
+                    CsvColumn::Mean               => "mean".to_string(),
+                    CsvColumn::StdDev             => "std_dev".to_string(),
+                    CsvColumn::Variance           => "variance".to_string(),
+                    CsvColumn::Min                => "min".to_string(),
+                    CsvColumn::Max                => "max".to_string(),
+                    CsvColumn::Percentile(p)      => format!("p{}", p),
+                    CsvColumn::Schema             => "schema".to_string(),
+                };
+
+            fields.push(field);
+        }
+
+        fields.join(",")
+    }
+
+    /// Creates one line of CSV output for a Rustics instance.  Fields
+    /// that don't apply to the instance, like the mean of a Counter,
+    /// are left empty.
+
+    pub fn csv_row(rustics: &dyn Rustics, columns: &[CsvColumn]) -> String {
+        let export    = rustics.export_stats();
+        let printable = &export.printable;
+        let counter   = matches!(rustics.class(), "counter" | "cardinality");
+        let has_data  = !counter && printable.n > 0;
+
+        let mut fields = Vec::with_capacity(columns.len());
+
+        for column in columns.iter() {
+            let field =
+                match column {
+                    CsvColumn::Title    => Self::csv_escape(&rustics.title()),
+                    CsvColumn::Name     => Self::csv_escape(&rustics.name()),
+                    CsvColumn::Class    => rustics.class().to_string(),
+                    CsvColumn::Count    => format!("{}", printable.n),
+                    CsvColumn::Schema   => format!("{}", SCHEMA_VERSION),
+
+                    CsvColumn::Mean     if has_data => format!("{}", printable.mean),
+                    CsvColumn::StdDev   if has_data => format!("{}", printable.variance.sqrt()),
+                    CsvColumn::Variance if has_data => format!("{}", printable.variance),
+
+                    CsvColumn::Min if has_data && rustics.int_extremes() =>
+                        format!("{}", printable.min_i64),
+
+                    CsvColumn::Max if has_data && rustics.int_extremes() =>
+                        format!("{}", printable.max_i64),
+
+                    CsvColumn::Min if has_data && rustics.float_extremes() =>
+                        format!("{}", printable.min_f64),
+
+                    CsvColumn::Max if has_data && rustics.float_extremes() =>
+                        format!("{}", printable.max_f64),
+
+                    CsvColumn::Percentile(p) if has_data => {
+                        if let Some(histogram) = &export.log_histogram {
+                            format!("{}", Self::log_percentile(&histogram.borrow(), *p))
+                        } else if let Some(histogram) = &export.float_histogram {
+                            format!("{}", Self::float_percentile(&histogram.borrow(), *p))
+                        } else {
+                            String::new()
+                        }
+                    }
+
+                    _ => String::new(),
+                };
+
+            fields.push(field);
+        }
+
+        fields.join(",")
+    }
+
+    // Computes the rank of the sample for the given percentile.
+
+    fn percentile_rank(samples: u64, percentile: f64) -> u64 {
+        let percentile = percentile.clamp(0.0, 100.0);
+        let rank       = (percentile / 100.0 * samples as f64).ceil() as u64;
+
+        rank.max(1)
+    }
+
+    /// Estimates a percentile from a pseudo-log histogram.  The result
+    /// is the largest value that falls into the bucket containing the
+    /// percentile, so it is an upper bound.
+
+    pub fn log_percentile(histogram: &LogHistogram, percentile: f64) -> i64 {
+        let mut samples = 0;
+
+        for count in histogram.negative.iter().chain(histogram.positive.iter()) {
+            samples += *count;
+        }
+
+        if samples == 0 {
+            return 0;
+        }
+
+        let     rank = Self::percentile_rank(samples, percentile);
+        let mut seen = 0;
+
+        // The negative buckets are ordered from the largest magnitude
+        // down, so walk them backwards.
+
+        for i in (0..histogram.negative.len()).rev() {
+            seen += histogram.negative[i];
+
+            if seen >= rank {
+                return log_bucket_max(i, true);
+            }
+        }
+
+        for i in 0..histogram.positive.len() {
+            seen += histogram.positive[i];
+
+            if seen >= rank {
+                return log_bucket_max(i, false);
+            }
+        }
+
+        i64::MAX
+    }
+
+    /// Estimates a percentile from a FloatHistogram.  As with
+    /// log_percentile(), the result is the bound of the bucket
+    /// furthest toward positive infinity, so it's very coarse.
+
+    pub fn float_percentile(histogram: &FloatHistogram, percentile: f64) -> f64 {
+        let mut samples = 0;
+
+        for count in histogram.negative.iter().chain(histogram.positive.iter()) {
+            samples += *count;
+        }
+
+        if samples == 0 {
+            return 0.0;
+        }
+
+        let     rank = Self::percentile_rank(samples, percentile);
+        let mut seen = 0;
+
+        for i in (0..histogram.negative.len()).rev() {
+            seen += histogram.negative[i];
+
+            if seen >= rank {
+                return float_bucket_max(i, true);
+            }
+        }
+
+        for i in 0..histogram.positive.len() {
+            seen += histogram.positive[i];
+
+            if seen >= rank {
+                return float_bucket_max(i, false);
+            }
         }
+
+        f64::INFINITY
     }
 }
 
@@ -500,6 +1122,9 @@ impl Printable {
 mod tests {
     use super::*;
     use crate::tests::CheckPrinter;
+    use crate::running_integer::RunningInteger;
+    use crate::running_float::RunningFloat;
+    use crate::counter::Counter;
 
     pub fn test_commas() {
         let test   = [ 123456, 12, -1, -1234, 4000000, -200, -2000, -20000 ];
@@ -710,7 +1335,23 @@ mod tests {
                 "-1.00000 e+10"
             ];
 
+        // The deterministic_format feature prints negative zero as zero.
+
+        let negative_zero =
+            if Printable::deterministic_format() {
+                "+0.00000 e+0 "
+            } else {
+                "-0.00000 e+0 "
+            };
+
         for i in 0..test_values.len() {
+            let expected =
+                if test_values[i] == 0.0 && test_values[i].is_sign_negative() {
+                    negative_zero
+                } else {
+                    expected[i]
+                };
+
             let (mantissa, exponent) = Printable::format_float(test_values[i]);
 
             let mut result = mantissa.clone();
@@ -719,9 +1360,27 @@ mod tests {
             result.push_str(&exponent);
 
             println!("test_format_float:  got (\"{}\", \"{}\") -> \"{}\", expected \"{}\" for {}",
-                mantissa, exponent, result, expected[i], test_values[i]);
+                mantissa, exponent, result, expected, test_values[i]);
 
-            assert!(result == expected[i]);
+            assert!(result == expected);
+        }
+
+        // Check that a difference in the last bit of a value doesn't
+        // change the output when the deterministic_format feature is
+        // enabled.  By default, the truncated digits can differ.
+
+        let value = 83416.7_f64;
+        let below = f64::from_bits(value.to_bits() - 1);
+
+        let (value_mantissa, _) = Printable::format_float(value);
+        let (below_mantissa, _) = Printable::format_float(below);
+
+        assert!(value_mantissa == "+8.34167");
+
+        if Printable::deterministic_format() {
+            assert!(below_mantissa == value_mantissa);
+        } else {
+            assert!(below_mantissa == "+8.34166");
         }
     }
 
@@ -737,12 +1396,185 @@ mod tests {
         Printable::print_time(">", sample, hz, &mut check_printer);
     }
 
+    fn test_csv() {
+        let columns =
+            [
+                CsvColumn::Title,
+                CsvColumn::Class,
+                CsvColumn::Count,
+                CsvColumn::Mean,
+                CsvColumn::Min,
+                CsvColumn::Max,
+                CsvColumn::Percentile(50.0),
+                CsvColumn::Percentile(99.9)
+            ];
+
+        let header = Printable::csv_header(&columns);
+
+        assert!(header == "title,class,count,mean,min,max,p50,p99.9");
+
+        let mut integer = RunningInteger::new("integer", &None);
+        let mut float   = RunningFloat  ::new("float",   &None);
+        let mut counter = Counter       ::new("counter", &None);
+
+        for i in 1..=100 {
+            integer.record_i64(i);
+            float  .record_f64(-i as f64);
+            counter.record_i64(i);
+        }
+
+        integer.set_title("set, with a comma");
+
+        let row = Printable::csv_row(&integer, &columns);
+
+        assert!(row == "\"set, with a comma\",integer,100,50.5,1,100,64,128");
+
+        let row = Printable::csv_row(&float, &columns);
+
+        assert!(row.starts_with("float,float,100,-50.5,-100,-1,"));
+
+        let row = Printable::csv_row(&counter, &columns);
+
+        assert!(row == "counter,counter,5050,,,,,");
+
+        assert!(Printable::csv_escape("say \"hi\"") == "\"say \"\"hi\"\"\"");
+        assert!(Printable::csv_escape("plain"       ) == "plain"                    );
+
+        let columns = Printable::csv_default_columns();
+        let header  = Printable::csv_header(&columns);
+
+        assert!(header == "title,count,mean,variance,min,max,schema");
+
+        let row = Printable::csv_row(&counter, &[ CsvColumn::Count, CsvColumn::Schema ]);
+
+        assert!(row == format!("5050,{}", SCHEMA_VERSION));
+    }
+
+    fn test_log_percentile() {
+        let mut histogram = LogHistogram::new();
+
+        assert!(Printable::log_percentile(&histogram, 50.0) == 0);
+
+        for i in 1..=100 {
+            histogram.record(i);
+        }
+
+        assert!(Printable::log_percentile(&histogram,   0.0) ==   1);
+        assert!(Printable::log_percentile(&histogram,  50.0) ==  64);
+        assert!(Printable::log_percentile(&histogram, 100.0) == 128);
+
+        let mut histogram = LogHistogram::new();
+
+        histogram.record(-1);
+        histogram.record(-2);
+        histogram.record(-3);
+        histogram.record(-5);
+
+        assert!(Printable::log_percentile(&histogram,  25.0) == -5);
+        assert!(Printable::log_percentile(&histogram,  50.0) == -3);
+        assert!(Printable::log_percentile(&histogram,  75.0) == -2);
+        assert!(Printable::log_percentile(&histogram, 100.0) == -1);
+
+        let mut histogram = FloatHistogram::new(&None);
+
+        assert!(Printable::float_percentile(&histogram, 50.0) == 0.0);
+
+        histogram.record(1.0e10);
+
+        let estimate = Printable::float_percentile(&histogram, 50.0);
+
+        assert!(estimate >= 1.0e10);
+    }
+
+    fn test_print_time_dual() {
+        let expected =
+            [
+                "    Mean              500.600 microseconds  (0.501 milliseconds)",
+                "    Max                 1.000 second  (1000.000 milliseconds)",
+                "    Min                 1.000 nanosecond  (1.000e-6 milliseconds)",
+                "    Zero                0.000 nanoseconds  (0.000 seconds)",
+                "    Long             +1.00000 e+6  days  (8.640e10 seconds)",
+            ];
+
+        let mut check_printer = CheckPrinter::new(&expected, false, false);
+
+        let hz   = 1_000_000_000;
+        let day  = 24.0 * 60.0 * 60.0 * hz as f64;
+        let ms   = TimeUnit::Millisecond;
+        let secs = TimeUnit::Second;
+
+        Printable::print_time_dual("Mean", 500_600.0,     hz, ms,   &mut check_printer);
+        Printable::print_time_dual("Max",  1.0e9,         hz, ms,   &mut check_printer);
+        Printable::print_time_dual("Min",  1.0,           hz, ms,   &mut check_printer);
+        Printable::print_time_dual("Zero", 0.0,           hz, secs, &mut check_printer);
+        Printable::print_time_dual("Long", 1.0e6 * day,   hz, secs, &mut check_printer);
+
+        assert!(Printable::convert_time(2_000.0, 1_000, TimeUnit::Second) == 2.0);
+        assert!(TimeUnit::Minute.name(1.0) == "minute" );
+        assert!(TimeUnit::Minute.name(2.0) == "minutes");
+    }
+
+    fn test_print_fields() {
+        let all = PrintFields::all();
+
+        assert!(all == PrintFields::default());
+        assert!(all.contains(PrintField::Count    ));
+        assert!(all.contains(PrintField::Histogram));
+
+        let fields = PrintFields::only(&[ PrintField::Count, PrintField::Mean ]);
+
+        assert!( fields.contains(PrintField::Mean   ));
+        assert!(!fields.contains(PrintField::Maximum));
+        assert!( fields.with   (PrintField::Maximum).contains(PrintField::Maximum));
+        assert!(!fields.without(PrintField::Mean   ).contains(PrintField::Mean   ));
+        assert!(PrintFields::none().without(PrintField::Count) == PrintFields::none());
+
+        let expected =
+            [
+                "    Count                  10 ",
+                "    Maximum                20 ",
+                "    Mean             +1.50000 e+1  ",
+            ];
+
+        let mut check_printer = CheckPrinter::new(&expected, true, false);
+
+        let n          = 10;
+        let nans       = 0;
+        let infinities = 0;
+        let min_i64    = 10;
+        let max_i64    = 20;
+        let min_f64    = 0.0;
+        let max_f64    = 0.0;
+        let mode_value = 0.0;
+        let log_mode   = 4;
+        let mean       = 15.0;
+        let variance   = 4.0;
+        let skewness   = 0.0;
+        let kurtosis   = 0.0;
+        let units      = Units::empty();
+
+        let printable =
+            Printable {
+                n,         nans,  infinities,  min_i64,   max_i64,   min_f64,     max_f64,
+                log_mode,  mean,  variance,    skewness,  kurtosis,  mode_value,  units
+            };
+
+        let fields = fields.with(PrintField::Maximum);
+
+        printable.print_common_i64_fields  (fields, &mut check_printer);
+        printable.print_common_float_fields(fields, &mut check_printer);
+    }
+
     #[test]
     fn run_tests() {
         test_commas          ();
         test_log_mode_to_time();
         test_format_float    ();
         test_print_time      ();
+        test_print_time_dual ();
+        test_csv             ();
+        test_log_percentile  ();
+        test_print_fields    ();
         documentation        ();
     }
 }
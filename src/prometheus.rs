@@ -0,0 +1,533 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * PrometheusTraverser
+//!     * This module renders Rustics instances in the Prometheus text
+//!       exposition format so that they can be served from a /metrics
+//!       endpoint.
+//!
+//!     * render_arc_set() walks an ArcSet and all its subsets, and
+//!       render_rustics() renders a single Rustics instance.  render_set()
+//!       accepts either type of set.  These build the nodes of a Report
+//!       and render those, and render_report() renders a Report that the
+//!       application has built and possibly edited.
+//!
+//!     * Metric names are created from the hierarchical titles of the
+//!       instances.  Characters that are not valid in a Prometheus
+//!       metric name are replaced with underscores.
+//!
+//!     * Counter instances are exported as counter metric families,
+//!       and Cardinality instances as gauges.  Other instances are
+//!       exported as a histogram metric family built from their
+//!       pseudo-log or float histogram, plus a gauge for the mean.  The
+//!       histogram buckets use the largest value that can be recorded
+//!       into each bucket as the "le" bound.
+//!
+//!     * The window types keep a histogram of all the samples ever
+//!       recorded, while their count covers only the window.  When the
+//!       histogram doesn't match the count, only the "+Inf" bucket is
+//!       exported, so that the buckets stay cumulative.
+//!
+//!     * Time instances are exported in units of timer ticks.
+//!
+//!     * The labels of an instance, as set by set_label(), are added to
+//!       each of its samples.  Label names are converted like metric
+//!       names, and label values are escaped.
+//!
+//! ## Example
+//!```
+//!     use rustics::arc_item_mut;
+//!     use rustics::arc_sets::ArcSet;
+//!     use rustics::prometheus::render_arc_set;
+//!
+//!     let set = ArcSet::new_box("Network", 2, 0, &None);
+//!     let set = arc_item_mut!(set);
+//!
+//!     let sizes   = set.add_running_integer("Packet Size", None);
+//!     let packets = set.add_counter("Packets", None);
+//!
+//!     for size in 1..=100 {
+//!         arc_item_mut!(sizes  ).record_i64(size);
+//!         arc_item_mut!(packets).record_event();
+//!     }
+//!
+//!     let output = render_arc_set(set);
+//!
+//!     assert!(output.contains("# TYPE network_packets counter\n"));
+//!     assert!(output.contains("network_packets 100\n"));
+//!     assert!(output.contains("# TYPE network_packet_size histogram\n"));
+//!     assert!(output.contains("network_packet_size_bucket{le=\"+Inf\"} 100\n"));
+//!     assert!(output.contains("network_packet_size_count 100\n"));
+//!
+//!     print!("{}", output);
+//!```
+
+use super::Rustics;
+use super::Labels;
+use super::arc_sets::ArcSet;
+use super::arc_sets::ArcTraverser;
+use super::rustics_set::RusticsSet;
+use super::report::Report;
+use super::report::MemberNode;
+use super::report::ReportValue;
+
+/// PrometheusTraverser accumulates the exposition text for the
+/// members of an ArcSet.
+
+#[derive(Default)]
+pub struct PrometheusTraverser {
+    pub output: String,
+}
+
+impl PrometheusTraverser {
+    pub fn new() -> PrometheusTraverser {
+        let output = String::new();
+
+        PrometheusTraverser { output }
+    }
+}
+
+impl ArcTraverser for PrometheusTraverser {
+    fn visit_set(&mut self, _set: &mut ArcSet) {
+    }
+
+    fn visit_member(&mut self, member: &mut dyn Rustics) {
+        self.output.push_str(&render_rustics(member));
+    }
+}
+
+/// Renders every Rustics instance in an ArcSet and its subsets.
+
+pub fn render_arc_set(set: &mut ArcSet) -> String {
+    let mut traverser = PrometheusTraverser::new();
+
+    set.traverse(&mut traverser);
+    traverser.output
+}
+
+/// Renders every Rustics instance in a set of either type and its
+/// subsets.
+
+pub fn render_set(set: &dyn RusticsSet) -> String {
+    render_report(&set.report())
+}
+
+/// Converts a title into a valid Prometheus metric name.  Runs of
+/// invalid characters become a single underscore, and the result is
+/// in lower case.
+
+pub fn metric_name(title: &str) -> String {
+    let mut name       = String::with_capacity(title.len());
+    let mut underscore = false;
+
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() || c == ':' {
+            name.push(c.to_ascii_lowercase());
+            underscore = false;
+        } else if !underscore && !name.is_empty() {
+            name.push('_');
+            underscore = true;
+        }
+    }
+
+    while name.ends_with('_') {
+        name.pop();
+    }
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+
+    name
+}
+
+/// Converts a label key into a valid Prometheus label name.  This is
+/// metric_name() without the colon, which is reserved.
+
+pub fn label_name(key: &str) -> String {
+    metric_name(&key.replace(':', "_"))
+}
+
+// Escape a string for use as a label value.
+
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// Format the label set for a sample, adding the "le" label for a
+// histogram bucket.  The result is empty if there are no labels.
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut pairs = Vec::new();
+
+    for (key, value) in labels.iter() {
+        pairs.push(format!("{}=\"{}\"", label_name(key), escape_label_value(value)));
+    }
+
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+// Escape a string for use in a HELP line.
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+// Format a floating point value in a form that Prometheus accepts.
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        format!("{}", value)
+    }
+}
+
+// Convert per-bucket counts into cumulative "le" buckets, skipping the
+// buckets outside the range of the recorded data.
+
+fn cumulative_buckets(buckets: &[(String, u64)]) -> Vec<(String, u64)> {
+    let first = buckets.iter().position(|(_, count)| *count > 0);
+    let last  = buckets.iter().rposition(|(_, count)| *count > 0);
+
+    let mut result = Vec::new();
+
+    if let (Some(first), Some(last)) = (first, last) {
+        let mut total = 0;
+
+        for (bound, count) in buckets[first..=last].iter() {
+            total += *count;
+            result.push((bound.clone(), total));
+        }
+    }
+
+    result
+}
+
+/// Renders a single Rustics instance.
+
+pub fn render_rustics(rustics: &dyn Rustics) -> String {
+    render_member(&MemberNode::from_rustics(rustics))
+}
+
+/// Renders every member of a Report.
+
+pub fn render_report(report: &Report) -> String {
+    let mut output = String::new();
+
+    report.visit_all(&mut |member| output.push_str(&render_member(member)));
+    output
+}
+
+/// Renders one member of a Report.
+
+pub fn render_member(member: &MemberNode) -> String {
+    let     name   = metric_name(&member.title);
+    let     help   = escape_help(&member.title);
+    let     labels = &member.labels;
+    let     plain  = format_labels(labels, None);
+    let     count  = member.count();
+    let mut output = String::new();
+
+    if member.class == "counter" {
+        output.push_str(&format!("# HELP {} {}\n",    name, help));
+        output.push_str(&format!("# TYPE {} counter\n", name));
+        output.push_str(&format!("{}{} {}\n",         name, plain, count));
+
+        return output;
+    }
+
+    // A distinct-value estimate can decrease as old periods are
+    // dropped, so it's a gauge.
+
+    if member.class == "cardinality" {
+        output.push_str(&format!("# HELP {} {}\n",  name, help));
+        output.push_str(&format!("# TYPE {} gauge\n", name));
+        output.push_str(&format!("{}{} {}\n",       name, plain, count));
+
+        return output;
+    }
+
+    // The histogram buckets are in order from the most negative to the
+    // most positive.
+
+    let mut buckets = Vec::new();
+
+    if let Some(histogram) = &member.histogram {
+        for bucket in histogram.buckets.iter() {
+            let bound =
+                match bucket.bound {
+                    ReportValue::Integer(bound) => format!("{}", bound),
+                    ReportValue::Float(bound)   => format_value(bound),
+                };
+
+            buckets.push((bound, bucket.count));
+        }
+    }
+
+    let mean =
+        match member.field("Mean") {
+            Some(mean) => mean.as_f64(),
+            None       => 0.0,
+        };
+
+    let sum = mean * count as f64;
+
+    // A window type keeps a histogram of every sample ever recorded,
+    // but its count covers only the window.  Finite buckets from such a
+    // histogram would exceed the "+Inf" bucket, so they are omitted.
+
+    let histogram_total: u64 = buckets.iter().map(|(_, count)| *count).sum();
+
+    if histogram_total != count {
+        buckets.clear();
+    }
+
+    output.push_str(&format!("# HELP {} {}\n",      name, help));
+    output.push_str(&format!("# TYPE {} histogram\n", name));
+
+    for (bound, total) in cumulative_buckets(&buckets).iter() {
+        let bucket_labels = format_labels(labels, Some(bound));
+
+        output.push_str(&format!("{}_bucket{} {}\n", name, bucket_labels, total));
+    }
+
+    let infinity = format_labels(labels, Some("+Inf"));
+
+    output.push_str(&format!("{}_bucket{} {}\n", name, infinity, count));
+    output.push_str(&format!("{}_sum{} {}\n",    name, plain, format_value(sum)));
+    output.push_str(&format!("{}_count{} {}\n",  name, plain, count));
+
+    output.push_str(&format!("# HELP {}_mean {} (mean)\n", name, help));
+    output.push_str(&format!("# TYPE {}_mean gauge\n",     name));
+    output.push_str(&format!("{}_mean{} {}\n",             name, plain, format_value(mean)));
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arc_item_mut;
+    use crate::rc_item_mut;
+    use crate::rc_sets::RcSet;
+    use crate::counter::Counter;
+    use crate::cardinality::Cardinality;
+    use crate::running_integer::RunningInteger;
+    use crate::running_float::RunningFloat;
+    use crate::integer_window::IntegerWindow;
+    use crate::hier::HierDimension;
+    use crate::hier::HierDescriptor;
+    use crate::integer_hier::IntegerHierConfig;
+
+    fn test_metric_name() {
+        assert!(metric_name("Network ==> Packet Size") == "network_packet_size");
+        assert!(metric_name("  leading space"        ) == "leading_space"      );
+        assert!(metric_name("9 lives"                ) == "_9_lives"           );
+        assert!(metric_name("a:b"                    ) == "a:b"                );
+        assert!(metric_name("==>"                    ) == "_"                  );
+    }
+
+    fn test_integer() {
+        let mut stats = RunningInteger::new("Test Stat", &None);
+
+        for i in 1..=4 {
+            stats.record_i64(i);
+        }
+
+        let output = render_rustics(&stats);
+
+        let expected =
+            [
+                "# HELP test_stat Test Stat",
+                "# TYPE test_stat histogram",
+                "test_stat_bucket{le=\"1\"} 1",
+                "test_stat_bucket{le=\"2\"} 2",
+                "test_stat_bucket{le=\"4\"} 4",
+                "test_stat_bucket{le=\"+Inf\"} 4",
+                "test_stat_sum 10",
+                "test_stat_count 4",
+                "# HELP test_stat_mean Test Stat (mean)",
+                "# TYPE test_stat_mean gauge",
+                "test_stat_mean 2.5",
+                ""
+            ];
+
+        assert!(output == expected.join("\n"));
+    }
+
+    fn test_float_and_counter() {
+        let mut float   = RunningFloat::new("Float", &None);
+        let mut counter = Counter     ::new("Count", &None);
+
+        float.record_f64(-1.0);
+        float.record_f64( 1.0e9);
+        counter.record_i64(42);
+
+        let output = render_rustics(&float);
+
+        assert!(output.contains("# TYPE float histogram\n"));
+        assert!(output.contains("float_bucket{le=\"+Inf\"} 2\n"));
+        assert!(output.contains("float_count 2\n"));
+
+        let output = render_rustics(&counter);
+
+        assert!(output == "# HELP count Count\n# TYPE count counter\ncount 42\n");
+
+        let mut distinct = Cardinality::new("Distinct", &None);
+
+        distinct.record_i64(1);
+        distinct.record_i64(2);
+
+        let output = render_rustics(&distinct);
+
+        assert!(output == "# HELP distinct Distinct\n# TYPE distinct gauge\ndistinct 2\n");
+    }
+
+    fn test_set() {
+        let set    = ArcSet::new_box("Top", 1, 1, &None);
+        let set    = arc_item_mut!(set);
+        let subset = set.add_subset("Sub", 1, 0);
+        let stat   = arc_item_mut!(subset).add_running_integer("Stat", None);
+
+        arc_item_mut!(stat).record_i64(3);
+
+        let output = render_arc_set(set);
+
+        assert!(output.starts_with("# HELP top_sub_stat Top ==> Sub ==> Stat\n"));
+        assert!(output.contains("top_sub_stat_bucket{le=\"4\"} 1\n"));
+        assert!(render_set(set) == output);
+
+        // An RcSet renders the same way.
+
+        let mut rc_set = RcSet::new("Top", 1, 1, &None);
+        let     subset = rc_set.add_subset("Sub", 1, 0);
+        let     stat   = rc_item_mut!(subset).add_running_integer("Stat", None);
+
+        rc_item_mut!(stat).record_i64(3);
+
+        assert!(render_set(&rc_set) == output);
+    }
+
+    fn test_labels() {
+        let mut stats = RunningInteger::new("Latency", &None);
+
+        stats.set_label("shard",     "3");
+        stats.set_label("Region:Id", "us \"east\"");
+        stats.set_label("shard",     "4");
+        stats.record_i64(2);
+
+        assert!(stats.labels().len() == 2);
+        assert!(stats.labels()["shard"] == "4");
+
+        let output = render_rustics(&stats);
+        let labels = "region_id=\"us \\\"east\\\"\",shard=\"4\"";
+
+        assert!(output.contains(&format!("latency_bucket{{{},le=\"2\"}} 1\n", labels)));
+        assert!(output.contains(&format!("latency_count{{{}}} 1\n",         labels)));
+        assert!(output.contains(&format!("latency_mean{{{}}} 2\n",          labels)));
+
+        // Labels stay with members of a set and with a Hier instance
+        // across advances.
+
+        let mut set     = RcSet::new("Top", 2, 0, &None);
+        let     counter = set.add_counter("Requests", None);
+        let     levels      = vec![ HierDimension::new(2, 4), HierDimension::new(2, 4) ];
+        let     name        = "Hier".to_string();
+        let     descriptor  = HierDescriptor::new(levels, Some(2));
+        let     print_opts  = None;
+        let     window_size = None;
+        let     config      = IntegerHierConfig { name, descriptor, print_opts, window_size };
+        let     hier        = set.add_integer_hier(config);
+
+        rc_item_mut!(counter).set_label("shard", "1");
+        rc_item_mut!(counter).record_i64(5);
+
+        rc_item_mut!(hier).set_label("shard", "2");
+        rc_item_mut!(hier).record_i64(5);
+        rc_item_mut!(hier).record_i64(6);
+        rc_item_mut!(hier).record_i64(7);
+
+        let output = render_set(&set);
+
+        assert!(output.contains("top_requests{shard=\"1\"} 5\n"));
+        assert!(output.contains("top_hier_count{shard=\"2\"}"));
+
+        assert!(label_name("9 lives") == "_9_lives");
+    }
+
+    fn test_window() {
+        let mut window = IntegerWindow::new("Window", 2, &None);
+
+        for i in 1..=10 {
+            window.record_i64(i);
+        }
+
+        let output = render_rustics(&window);
+
+        assert!(!output.contains("window_bucket{le=\"16\"}"));
+        assert!(output.contains("window_bucket{le=\"+Inf\"} 2\n"));
+        assert!(output.contains("window_count 2\n"));
+
+        // The finite buckets that are present never exceed "+Inf".
+
+        let mut infinity = None;
+        let mut previous = 0;
+
+        for line in output.lines().filter(|line| line.starts_with("window_bucket")) {
+            let total: u64 = line.rsplit(' ').next().unwrap().parse().unwrap();
+
+            assert!(total >= previous);
+
+            previous = total;
+
+            if line.contains("+Inf") {
+                infinity = Some(total);
+            }
+        }
+
+        assert!(infinity == Some(2));
+
+        // Before the window fills, the histogram matches the count, and
+        // the buckets are exported.
+
+        let mut window = IntegerWindow::new("Window", 4, &None);
+
+        window.record_i64(1);
+        window.record_i64(3);
+
+        let output = render_rustics(&window);
+
+        assert!(output.contains("window_bucket{le=\"1\"} 1\n"));
+        assert!(output.contains("window_bucket{le=\"4\"} 2\n"));
+        assert!(output.contains("window_bucket{le=\"+Inf\"} 2\n"));
+    }
+
+    #[test]
+    fn run_tests() {
+        test_metric_name      ();
+        test_integer          ();
+        test_float_and_counter();
+        test_set              ();
+        test_labels           ();
+        test_window           ();
+    }
+}
@@ -121,11 +121,24 @@ use super::PrintOption;
 use super::Units;
 use super::TimerBox;
 use super::counter::Counter;
+use super::peak_tracker::PeakTracker;
+use super::cardinality::Cardinality;
+use super::running_ratio::RunningRatio;
+use super::correlation::Correlation;
 use super::make_title;
+use super::printer_mut;
+use super::aggregate::Aggregate;
+use super::rustics_set::RusticsSet;
+use super::eviction::Eviction;
+use super::eviction::EvictionPolicy;
+use super::throttle::PrintThrottle;
+use super::record_hook::RecordHookBox;
+use super::time::TimerFactory;
 use super::parse_printer;
 use super::parse_title;
 use super::parse_units;
 use super::parse_histo_opts;
+use super::parse_fields;
 
 use super::running_integer::RunningInteger;
 use super::running_time   ::RunningTime;
@@ -145,6 +158,8 @@ use super::float_hier  ::FloatHierConfig;
 pub type RusticsRc = Rc<RefCell<dyn Rustics>>;
 pub type RcSetBox  = Rc<RefCell<RcSet>>;
 
+pub type CorrelationRc = Rc<RefCell<Correlation>>;
+
 /// Creates a shareable instance of an RcSet item.
 
 #[macro_export]
@@ -189,6 +204,11 @@ pub struct RcSet {
     subsets:    Vec<RcSetBox>,
     printer:    PrinterBox,
     print_opts: PrintOption,
+    aggregates: bool,
+    throttle:   Option<Rc<PrintThrottle>>,
+    eviction:   Eviction,
+    hook:       Option<RecordHookBox>,
+    collection: Option<TimerFactory>,
 }
 
 impl RcSet {
@@ -207,7 +227,17 @@ impl RcSet {
         let printer    = parse_printer(print_opts);
         let print_opts = print_opts.clone();
 
-        RcSet { name, title, id, next_id, members, subsets, printer, print_opts }
+        let aggregates = false;
+        let throttle   = None;
+        let eviction   = Eviction::new(rustics);
+        let hook       = None;
+        let collection = None;
+
+        RcSet {
+            name,     title,    id,          next_id,     members,
+            subsets,  printer,  print_opts,  aggregates,  throttle,
+            eviction, hook,     collection
+        }
     }
 
     /// Creates a new RcSet in a box.
@@ -254,10 +284,43 @@ impl RcSet {
     /// with the give printer and title.
 
     pub fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        self.print_filtered(printer, title, None);
+    }
+
+    /// Prints only the members of the given class, like "time" or
+    /// "counter", in the set and its subsets.  The aggregate block is
+    /// not printed.
+
+    pub fn print_class(&self, class: &str) {
+        self.print_class_opts(class, None, None);
+    }
+
+    /// Prints the members of the given class, and overrides the default
+    /// printer and title as desired.
+
+    pub fn print_class_opts(&self, class: &str, printer: PrinterOption, title: Option<&str>) {
+        self.print_filtered(printer, title, Some(class));
+    }
+
+    // Print the set, or only the members of a given class.
+
+    fn print_filtered(&self, printer: PrinterOption, title: Option<&str>, class: Option<&str>) {
+        if let Some(throttle) = &self.throttle {
+            if !throttle.allow() {
+                return;
+            }
+        }
+
         for member in self.members.iter() {
             let member  = rc_item!(**member);
             let printer = printer.clone();
 
+            if let Some(class) = class {
+                if member.class() != class {
+                    continue;
+                }
+            }
+
             if let Some(title) = title {
                 let title = make_title(title, &member.name());
                 let title = Some(title.as_str());
@@ -276,11 +339,66 @@ impl RcSet {
                 let title = make_title(title, &subset.name());
                 let title = Some(title.as_str());
 
-                subset.print_opts(printer, title);
+                subset.print_filtered(printer, title, class);
             } else {
-                subset.print_opts(printer, None);
+                subset.print_filtered(printer, None, class);
             }
         }
+
+        // Add the aggregate block, if requested.
+
+        if self.aggregates && class.is_none() {
+            let printer_box =
+                if let Some(printer) = printer {
+                    printer
+                } else {
+                    self.printer.clone()
+                };
+
+            let title =
+                if let Some(title) = title {
+                    title
+                } else {
+                    &self.title
+                };
+
+            let mut aggregate = Aggregate::new();
+
+            self.aggregate(&mut aggregate);
+
+            let printer = printer_mut!(printer_box);
+
+            aggregate.print(title, printer);
+        }
+    }
+
+    /// Enables or disables printing an aggregate block for each class
+    /// at the end of the print output.  The aggregate covers the set
+    /// and all its subsets.  See the aggregate module.
+
+    pub fn set_print_aggregates(&mut self, aggregates: bool) {
+        self.aggregates = aggregates;
+    }
+
+    /// Returns whether the aggregate blocks are printed.
+
+    pub fn print_aggregates(&self) -> bool {
+        self.aggregates
+    }
+
+    /// Limits how often print() and print_opts() produce output for
+    /// this set.  Prints arriving within the throttle interval of the
+    /// last print are dropped.  Clones of the set share the throttle.
+    /// See the throttle module.
+
+    pub fn set_print_throttle(&mut self, throttle: PrintThrottle) {
+        self.throttle = Some(Rc::new(throttle));
+    }
+
+    /// Returns the print throttle, if one is set.
+
+    pub fn print_throttle(&self) -> Option<&PrintThrottle> {
+        self.throttle.as_deref()
     }
 
     /// Returns the current title for the set.
@@ -326,18 +444,161 @@ impl RcSet {
         }
     }
 
+    /// Clears the members of the given class, like "time", in the set
+    /// and its subsets.
+
+    pub fn clear_class(&mut self, class: &str) {
+        for subset in self.subsets.iter() {
+            let subset = rc_item_mut!(**subset);
+
+            subset.clear_class(class);
+        }
+
+        for member in self.members.iter() {
+            let member = rc_item_mut!(**member);
+
+            if member.class() == class {
+                member.clear();
+            }
+        }
+    }
+
+    /// Installs a hook that receives the samples recorded into the
+    /// members of the set and its subsets, or removes the hook if None
+    /// is given.  Members that don't support hooks are skipped.  The
+    /// hook also is installed in members and subsets added later.  See
+    /// the record_hook module.
+
+    pub fn set_record_hook(&mut self, hook: Option<RecordHookBox>) {
+        for subset in self.subsets.iter() {
+            let subset = rc_item_mut!(**subset);
+
+            subset.set_record_hook(hook.clone());
+        }
+
+        for member in self.members.iter() {
+            let member = rc_item_mut!(**member);
+
+            if member.supports_record_hook() {
+                member.set_record_hook(hook.clone());
+            }
+        }
+
+        self.hook = hook;
+    }
+
+    /// Returns the hook for the set, if any.
+
+    pub fn record_hook(&self) -> Option<RecordHookBox> {
+        self.hook.clone()
+    }
+
+    /// Attaches a collection clock made by the given factory to each
+    /// member of the set and its subsets, or removes the clocks if None
+    /// is given.  Members that don't support a collection clock are
+    /// skipped.  Members and subsets added later get clocks, too.  See
+    /// the collection_time module.
+
+    pub fn set_collection_clocks(&mut self, factory: Option<TimerFactory>) {
+        for subset in self.subsets.iter() {
+            let subset = rc_item_mut!(**subset);
+
+            subset.set_collection_clocks(factory);
+        }
+
+        for member in self.members.iter() {
+            let member = rc_item_mut!(**member);
+
+            if member.supports_collection_time() {
+                member.set_collection_clock(factory.map(|factory| factory()));
+            }
+        }
+
+        self.collection = factory;
+    }
+
     /// Adds a RusticsRc instance to the set.
 
     pub fn add_member(&mut self, member: RusticsRc) {
-        let work   = member.clone();
-        let stat   = rc_item_mut!(work);
-        let title  = make_title(&self.title, &stat.name());
+        {
+            let stat   = rc_item_mut!(member);
+            let title  = make_title(&self.title, &stat.name());
 
-        stat.set_title(&title);
-        stat.set_id(self.next_id);
-        self.next_id += 1;
+            stat.set_title(&title);
+            stat.set_id(self.next_id);
+            self.next_id += 1;
+
+            if self.hook.is_some() && stat.supports_record_hook() {
+                stat.set_record_hook(self.hook.clone());
+            }
+
+            if let Some(factory) = self.collection {
+                if stat.supports_collection_time() {
+                    stat.set_collection_clock(Some(factory()));
+                }
+            }
+        }
+
+        // The member borrow must be released before evicting.
 
         self.members.push(member);
+        self.eviction.push();
+        self.evict(true);
+    }
+
+    /// Limits the number of Rustics instances in the set.  When adding
+    /// a member would exceed the capacity, a member chosen by the policy
+    /// is removed.  If the set already is over the new capacity, members
+    /// are evicted immediately.  None removes the limit.  See the
+    /// eviction module.
+
+    pub fn set_capacity(&mut self, capacity: Option<usize>, policy: EvictionPolicy) {
+        self.eviction.set_capacity(capacity, policy);
+        self.evict(false);
+    }
+
+    /// Returns the capacity of the set, if any.
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.eviction.capacity()
+    }
+
+    /// Returns the number of members evicted from the set.
+
+    pub fn evictions(&self) -> u64 {
+        self.eviction.evictions()
+    }
+
+    /// Sets the eviction priority of a member.  Members with the lowest
+    /// priority are evicted first under EvictionPolicy::Priority.
+    /// Returns false if the member isn't in the set.
+
+    pub fn set_priority(&mut self, target: &RusticsRc, priority: i64) -> bool {
+        match self.members.iter().position(|member| Rc::ptr_eq(member, target)) {
+            Some(i) => {
+                self.eviction.set_priority(i, priority);
+                true
+            }
+
+            None => false
+        }
+    }
+
+    // Remove members until the set is within its capacity.
+
+    fn evict(&mut self, protect_last: bool) {
+        while self.eviction.is_over(self.members.len()) {
+            let counts: Vec<u64> = self.members.iter().map(|member| rc_item!(**member).count()).collect();
+
+            match self.eviction.victim(&counts, protect_last) {
+                Some(i) => {
+                    self.members.remove(i);
+                    self.eviction.remove(i);
+                }
+
+                None => break
+            }
+        }
     }
 
     /// Creates a RunningInteger instance and adds it to the set.
@@ -481,6 +742,59 @@ impl RcSet {
         member
     }
 
+    /// Creates a PeakTracker instance and adds it to the set.
+
+    pub fn add_peak_tracker(&mut self, name: &str, history_size: usize, units: Option<Units>)
+            -> RusticsRc {
+        let member = PeakTracker::new(name, history_size, &self.print_opts);
+        let member = rc_box!(member);
+
+        if let Some(units) = units {
+            rc_item_mut!(member).set_units(units);
+        }
+
+        self.add_member(member.clone());
+        member
+    }
+
+    /// Creates a Cardinality instance and adds it to the set.
+
+    pub fn add_cardinality(&mut self, name: &str, units: Option<Units>) -> RusticsRc {
+        let member = Cardinality::new(name, &self.print_opts);
+        let member = rc_box!(member);
+
+        if let Some(units) = units {
+            rc_item_mut!(member).set_units(units);
+        }
+
+        self.add_member(member.clone());
+        member
+    }
+
+    /// Creates a RunningRatio instance and adds it to the set.
+    /// Record a success with record_i64(1) and a failure with
+    /// record_i64(0).
+
+    pub fn add_running_ratio(&mut self, name: &str) -> RusticsRc {
+        let member = RunningRatio::new(name, &self.print_opts);
+        let member = rc_box!(member);
+
+        self.add_member(member.clone());
+        member
+    }
+
+    /// Creates a Correlation instance and adds it to the set.  The
+    /// instance is returned with its own type so that pairs can be
+    /// recorded via record_pair().
+
+    pub fn add_correlation(&mut self, name: &str) -> CorrelationRc {
+        let member = Correlation::new(name, &self.print_opts);
+        let member: CorrelationRc = rc_box!(member);
+
+        self.add_member(member.clone());
+        member
+    }
+
     /// Removes a Rustics instance from the set.
 
     pub fn remove_stat(&mut self, target: RusticsRc) -> bool {
@@ -505,6 +819,7 @@ impl RcSet {
 
         if found {
             self.members.remove(i);
+            self.eviction.remove(i);
         }
 
         found
@@ -518,6 +833,8 @@ impl RcSet {
 
         subset.set_title(&title);
         subset.set_id(self.next_id);
+        subset.set_record_hook(self.hook.clone());
+        subset.set_collection_clocks(self.collection);
         self.next_id += 1;
 
         let subset = rc_box!(subset);
@@ -555,6 +872,53 @@ impl RcSet {
         found
     }
 
+    /// Finds a member given a path of names relative to this set,
+    /// for example "Network ==> Query Latency".  Each name but the
+    /// last selects a subset, and the last name selects a Rustics
+    /// instance.  If there are duplicate names, the first match is
+    /// used.
+
+    pub fn find(&self, path: &str) -> Option<RusticsRc> {
+        match path.rsplit_once("==>") {
+            Some((subset_path, name)) => {
+                let subset = self.find_subset(subset_path)?;
+                let subset = rc_item!(*subset);
+
+                subset.find_member(name.trim())
+            }
+
+            None => self.find_member(path.trim())
+        }
+    }
+
+    /// Finds a subset given a path of subset names relative to this
+    /// set, for example "Network ==> Server".
+
+    pub fn find_subset(&self, path: &str) -> Option<RcSetBox> {
+        let mut names   = path.split("==>").map(|name| name.trim());
+        let mut current = self.find_child(names.next()?)?;
+
+        for name in names {
+            let next = rc_item!(*current).find_child(name)?;
+
+            current = next;
+        }
+
+        Some(current)
+    }
+
+    // Find a Rustics instance in this set by name.
+
+    fn find_member(&self, name: &str) -> Option<RusticsRc> {
+        self.members.iter().find(|member| rc_item!(**member).name() == name).cloned()
+    }
+
+    // Find a subset of this set by name.
+
+    fn find_child(&self, name: &str) -> Option<RcSetBox> {
+        self.subsets.iter().find(|subset| rc_item!(**subset).name() == name).cloned()
+    }
+
     // The following methods are for internal use only.
 
     fn set_id(&mut self, id: usize) {
@@ -570,12 +934,39 @@ impl RcSet {
         let title      = Some(make_title(&self.title, name));
         let units      = Some(parse_units(print_opts));
         let histo_opts = Some(parse_histo_opts(print_opts));
-        let print_opts = PrintOpts { printer, title, units, histo_opts };
+        let fields     = Some(parse_fields(print_opts));
+        let print_opts = PrintOpts { printer, title, units, histo_opts, fields };
 
         Some(print_opts)
     }
 }
 
+impl RusticsSet for RcSet {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn printer(&self) -> PrinterBox {
+        self.printer.clone()
+    }
+
+    fn visit_members(&self, visitor: &mut dyn FnMut(&dyn Rustics)) {
+        for member in self.members.iter() {
+            visitor(rc_item!(**member));
+        }
+    }
+
+    fn visit_subsets(&self, visitor: &mut dyn FnMut(&dyn RusticsSet)) {
+        for subset in self.subsets.iter() {
+            visitor(rc_item!(**subset));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -590,6 +981,10 @@ mod tests {
     use crate::tests::check_printer_count_match;
     use crate::tests::bytes;
     use crate::arc_sets::tests::title_to_print_option;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
+    use crate::printable::CsvColumn;
+    use crate::capture_printer::CapturePrinter;
 
     struct TestTraverser {
         pub members:  i64,
@@ -1333,11 +1728,216 @@ mod tests {
         assert!(check_printer_count_match(printer.clone()));
     }
 
+    fn test_csv() {
+        let mut set     = RcSet::new("top", 1, 1, &None);
+        let     running  = set.add_running_integer("running", None);
+        let     distinct = set.add_cardinality("distinct", None);
+        let     subset   = set.add_subset("subset", 1, 0);
+        let     window   = subset.borrow_mut().add_integer_window("window", 10, None);
+
+        for i in 1..=4 {
+            running .borrow_mut().record_i64(i);
+            distinct.borrow_mut().record_i64(i % 3);
+            window  .borrow_mut().record_i64(-i);
+        }
+
+        let columns  = [ CsvColumn::Name, CsvColumn::Title, CsvColumn::Count ];
+        let expected =
+            [
+                "name,title,count",
+                "running,top ==> running,4",
+                "distinct,top ==> distinct,3",
+                "window,top ==> subset ==> window,4"
+            ];
+
+        let rows = set.export_csv(&columns);
+
+        assert!(rows == expected);
+
+        let printer = check_printer_box(&expected, true, false);
+
+        set.print_csv(Some(printer.clone()), &columns);
+        assert!(check_printer_count_match(printer));
+    }
+
+    fn test_throttle() {
+        let expected =
+            [
+                "Top ==> Events",
+                "    Count                   1 ",
+                "",
+                "Top ==> Events",
+                "    Count                   3 ",
+                ""
+            ];
+
+        let     current   = 0;
+        let     increment = 0;
+        let     clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let     timer     = ClockTimer::new_box(clock.clone());
+        let     printer   = check_printer_box(&expected, true, false);
+        let mut set       = RcSet::new("Top", 1, 0, &None);
+        let     events    = set.add_counter("Events", None);
+
+        assert!(set.print_throttle().is_none());
+
+        set.set_print_throttle(PrintThrottle::new(1000, timer));
+
+        // Only the first and the last prints get through.
+
+        for _i in 0..3 {
+            events.borrow_mut().record_event();
+            set.print_opts(Some(printer.clone()), None);
+            clock.borrow_mut().current += 600;
+        }
+
+        let throttle = set.print_throttle().unwrap();
+
+        assert!(throttle.allowed()    == 2);
+        assert!(throttle.suppressed() == 1);
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+    }
+
+    fn test_ratio() {
+        let mut set   = RcSet::new("Top", 1, 0, &None);
+        let     ratio = set.add_running_ratio("Hits");
+
+        for i in 0..10 {
+            ratio.borrow_mut().record_i64(i % 2);
+        }
+
+        assert!(ratio.borrow().class() == "ratio");
+        assert!(ratio.borrow().count() == 10     );
+        assert!(ratio.borrow().mean()  == 0.5    );
+        assert!(ratio.borrow().title() == "Top ==> Hits");
+    }
+
+    fn test_eviction() {
+        let mut set    = RcSet::new("Top", 4, 0, &None);
+        let     first  = set.add_counter("First",  None);
+        let     second = set.add_counter("Second", None);
+
+        set.set_capacity(Some(2), EvictionPolicy::Priority);
+
+        assert!(set.set_priority(&first, 5));
+
+        // The lowest priority member is evicted, and the new member
+        // is kept.
+
+        let third = set.add_counter("Third", None);
+
+        assert!(set.evictions()    == 1);
+        assert!(set.member_count() == 2);
+        assert!( set.visit_path("First",  &mut |_| { }));
+        assert!(!set.visit_path("Second", &mut |_| { }));
+        assert!( set.visit_path("Third",  &mut |_| { }));
+        assert!(!set.set_priority(&second, 1));
+
+        // Under LeastRecent, the member that has recorded a sample
+        // most recently survives.
+
+        rc_item_mut!(first).record_event();
+        set.set_capacity(Some(1), EvictionPolicy::LeastRecent);
+
+        assert!(set.capacity()     == Some(1));
+        assert!(set.member_count() == 1      );
+        assert!(set.evictions()    == 2      );
+        assert!( set.visit_path("First", &mut |_| { }));
+        assert!(!set.set_priority(&third, 1));
+    }
+
+    fn test_find() {
+        let mut set     = RcSet::new("Top", 1, 1, &None);
+        let     latency = set.add_running_integer("Latency", None);
+        let     network = set.add_subset("Network", 1, 1);
+        let     server  = rc_item_mut!(network).add_subset("Server", 1, 0);
+        let     query   = rc_item_mut!(network).add_running_integer("Query Latency", None);
+        let     bytes   = rc_item_mut!(server).add_counter("Bytes", None);
+
+        assert!(Rc::ptr_eq(&set.find("Latency").unwrap(), &latency));
+        assert!(Rc::ptr_eq(&set.find("Network ==> Query Latency").unwrap(), &query));
+        assert!(Rc::ptr_eq(&set.find("Network==>Server==>Bytes").unwrap(), &bytes));
+        assert!(Rc::ptr_eq(&set.find_subset("Network").unwrap(), &network));
+        assert!(Rc::ptr_eq(&set.find_subset("Network ==> Server").unwrap(), &server));
+
+        assert!(set.find("Query Latency"           ).is_none());
+        assert!(set.find("Server ==> Bytes"        ).is_none());
+        assert!(set.find("Network"                 ).is_none());
+        assert!(set.find_subset("Network ==> Bytes").is_none());
+
+        rc_item_mut!(set.find("Network ==> Query Latency").unwrap()).record_i64(42);
+
+        assert!(rc_item!(query).max_i64() == 42);
+    }
+
+    fn test_class() {
+        let     capture  = Rc::new(RefCell::new(CapturePrinter::new()));
+        let     printer  = Some(capture.clone() as PrinterBox);
+        let mut set      = RcSet::new("Top", 3, 1, &None);
+        let     latency  = set.add_running_time("Latency", continuing_box());
+        let     requests = set.add_counter("Requests", None);
+        let     pairs    = set.add_correlation("Pairs");
+        let     network  = set.add_subset("Network", 2, 0);
+        let     query    = rc_item_mut!(network).add_running_time("Query", continuing_box());
+        let     bytes    = rc_item_mut!(network).add_counter("Bytes", None);
+
+        for member in [ &latency, &query ] {
+            rc_item_mut!(member).record_time(100);
+        }
+
+        for member in [ &requests, &bytes ] {
+            rc_item_mut!(member).record_i64(10);
+        }
+
+        rc_item_mut!(pairs).record_pair(1.0, 2.0);
+
+        assert!(rc_item!(pairs).class() == "correlation");
+        assert!(rc_item!(pairs).count() == 1);
+
+        set.set_print_aggregates(true);
+
+        // Print only the counters.  The aggregate block is skipped.
+
+        set.print_class_opts("counter", printer.clone(), None);
+
+        let lines = capture.borrow_mut().take_lines();
+        let names = lines.iter().filter(|line| line.starts_with("Top ==> "));
+        let names = names.cloned().collect::<Vec<String>>();
+
+        assert!(names == [ "Top ==> Requests", "Top ==> Network ==> Bytes" ]);
+
+        // Clear the latency statistics and keep the counters.
+
+        set.clear_class("time");
+
+        assert!(rc_item!(latency ).count() == 0 );
+        assert!(rc_item!(query   ).count() == 0 );
+        assert!(rc_item!(requests).count() == 10);
+        assert!(rc_item!(bytes   ).count() == 10);
+
+        // An unknown class matches nothing.
+
+        set.clear_class("none");
+        set.print_class_opts("none", printer.clone(), None);
+
+        assert!(capture.borrow().is_empty());
+        assert!(rc_item!(bytes).count() == 10);
+    }
+
     #[test]
     pub fn run_tests() {
         simple_test     ();
         sample_usage    ();
         test_hier       ();
         test_rc_printing();
+        test_csv        ();
+        test_throttle   ();
+        test_ratio      ();
+        test_eviction   ();
+        test_find       ();
+        test_class      ();
     }
 }
@@ -0,0 +1,436 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * RecordHook
+//!     * A RecordHook is a tap on the record methods of a Rustics
+//!       instance.  It receives every raw sample along with the name of
+//!       the instance, so the samples can be written to a file or sent
+//!       to another sink for offline analysis while the instance keeps
+//!       its usual summaries.  No call sites need to change.
+//!
+//!     * A hook is installed via set_record_hook() in the Rustics trait.
+//!       RunningInteger, RunningFloat, RunningTime, Counter, and Hier
+//!       support hooks, as does SyncRustics for those types.  The
+//!       supports_record_hook() method tells whether an instance does.
+//!       A Hier instance calls its hook once per sample, not once per
+//!       level.
+//!
+//!     * RcSet and ArcSet provide set_record_hook(), which installs the
+//!       hook in each member that supports it, in each subset, and in any
+//!       members or subsets added later.
+//!
+//!     * The hook is invoked before any filtering, so RunningFloat passes
+//!       NaN and infinite samples, too.
+//!
+//! * HookSample
+//!     * A HookSample is one sample as passed to a hook.  The variant
+//!       tells which record method was used.  An Event is a call to
+//!       record_event() on a type that doesn't turn events into values,
+//!       like a Counter.  Time samples are in clock ticks.
+//!
+//! * WriterHook
+//!     * WriterHook writes each sample as a line of text to a Write
+//!       implementation, like a File.  Each line contains the kind of
+//!       sample, the value, and the name of the instance, separated by
+//!       spaces.  parse_samples() reads the lines back.
+//!
+//! ## Example
+//!```
+//!     use std::rc::Rc;
+//!     use std::cell::RefCell;
+//!     use rustics::Rustics;
+//!     use rustics::running_integer::RunningInteger;
+//!     use rustics::record_hook::HookSample;
+//!     use rustics::record_hook::WriterHook;
+//!     use rustics::record_hook::parse_samples;
+//!
+//!     // Tee the samples into a Vec.  A File works the same way.
+//!
+//!     let hook = Rc::new(RefCell::new(WriterHook::new(Vec::new())));
+//!
+//!     let mut queue = RunningInteger::new("Queue Length", &None);
+//!
+//!     queue.set_record_hook(Some(hook.clone()));
+//!
+//!     for i in 1..=3 {
+//!         queue.record_i64(i * 10);
+//!     }
+//!
+//!     // The instance keeps its summary as usual.
+//!
+//!     assert!(queue.count() == 3);
+//!     assert!(queue.mean()  == 20.0);
+//!
+//!     // Read the samples back.
+//!
+//!     let text    = String::from_utf8(hook.borrow().writer().clone()).unwrap();
+//!     let samples = parse_samples(&text).unwrap();
+//!
+//!     assert!(samples.len() == 3);
+//!     assert!(samples[2] == ("Queue Length".to_string(), HookSample::Integer(30)));
+//!```
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::io::Write;
+
+/// HookSample holds one sample passed to a RecordHook.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HookSample {
+    Integer(i64),
+    Float(f64),
+    Time(i64),
+    Event,
+}
+
+impl HookSample {
+    /// Returns the name of the kind of sample, as used by WriterHook.
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            HookSample::Integer(_) => "integer",
+            HookSample::Float(_)   => "float",
+            HookSample::Time(_)    => "time",
+            HookSample::Event      => "event",
+        }
+    }
+}
+
+/// The RecordHook trait is implemented by the sinks for samples.
+
+pub trait RecordHook {
+    /// Receives one sample recorded into the named instance.
+
+    fn record(&mut self, name: &str, sample: HookSample);
+}
+
+pub type RecordHookBox = Rc<RefCell<dyn RecordHook>>;
+
+/// Passes a sample to a hook, if one is installed.  This is used by
+/// the Rustics implementations that support hooks.
+
+pub fn call_hook(hook: &Option<RecordHookBox>, name: &str, sample: HookSample) {
+    if let Some(hook) = hook {
+        hook.borrow_mut().record(name, sample);
+    }
+}
+
+/// WriterHook writes each sample as a line of text.
+
+pub struct WriterHook<W: Write> {
+    writer:   W,
+    samples:  u64,
+    errors:   u64,
+}
+
+impl<W: Write> WriterHook<W> {
+    /// Creates a hook that writes to the given writer.
+
+    pub fn new(writer: W) -> WriterHook<W> {
+        let samples = 0;
+        let errors  = 0;
+
+        WriterHook { writer, samples, errors }
+    }
+
+    /// Returns the number of samples received.
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Returns the number of samples that couldn't be written.  Write
+    /// errors aren't returned to the record methods, so they are
+    /// counted here.
+
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// Flushes the writer.
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the writer.
+
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns the writer for modification.
+
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+impl<W: Write> RecordHook for WriterHook<W> {
+    fn record(&mut self, name: &str, sample: HookSample) {
+        let kind = sample.kind();
+
+        let result =
+            match sample {
+                HookSample::Integer(value) => writeln!(self.writer, "{} {} {}", kind, value, name),
+                HookSample::Float  (value) => writeln!(self.writer, "{} {} {}", kind, value, name),
+                HookSample::Time   (value) => writeln!(self.writer, "{} {} {}", kind, value, name),
+                HookSample::Event          => writeln!(self.writer, "{} - {}",  kind,        name),
+            };
+
+        self.samples += 1;
+
+        if result.is_err() {
+            self.errors += 1;
+        }
+    }
+}
+
+/// Parses the output of a WriterHook into (name, sample) pairs.
+/// Returns None if any line is malformed.
+
+pub fn parse_samples(text: &str) -> Option<Vec<(String, HookSample)>> {
+    let mut result = Vec::new();
+
+    for line in text.lines() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut fields = line.splitn(3, ' ');
+
+        let kind   = fields.next()?;
+        let value  = fields.next()?;
+        let name   = fields.next()?.to_string();
+
+        let sample =
+            match kind {
+                "integer" => HookSample::Integer(value.parse::<i64>().ok()?),
+                "float"   => HookSample::Float  (value.parse::<f64>().ok()?),
+                "time"    => HookSample::Time   (value.parse::<i64>().ok()?),
+                "event"   => HookSample::Event,
+                _         => return None,
+            };
+
+        result.push((name, sample));
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rustics;
+    use crate::arc_item_mut;
+    use crate::running_integer::RunningInteger;
+    use crate::running_float::RunningFloat;
+    use crate::running_time::RunningTime;
+    use crate::counter::Counter;
+    use crate::rc_sets::RcSet;
+    use crate::arc_sets::ArcSet;
+    use crate::hier::HierDescriptor;
+    use crate::hier::HierDimension;
+    use crate::integer_hier::IntegerHier;
+    use crate::integer_hier::IntegerHierConfig;
+    use crate::time::DurationTimer;
+
+    // Keep the samples passed to the hook.
+
+    struct CaptureHook {
+        samples:  Vec<(String, HookSample)>,
+    }
+
+    impl RecordHook for CaptureHook {
+        fn record(&mut self, name: &str, sample: HookSample) {
+            self.samples.push((name.to_string(), sample));
+        }
+    }
+
+    fn capture_hook() -> Rc<RefCell<CaptureHook>> {
+        let samples = Vec::new();
+
+        Rc::new(RefCell::new(CaptureHook { samples }))
+    }
+
+    fn take_samples(hook: &Rc<RefCell<CaptureHook>>) -> Vec<(String, HookSample)> {
+        std::mem::take(&mut hook.borrow_mut().samples)
+    }
+
+    fn sample(name: &str, sample: HookSample) -> (String, HookSample) {
+        (name.to_string(), sample)
+    }
+
+    fn test_types() {
+        let hook = capture_hook();
+
+        let mut integer = RunningInteger::new("Integer", &None);
+        let mut float   = RunningFloat  ::new("Float",   &None);
+        let mut time    = RunningTime   ::new("Time",    DurationTimer::new_box(), &None);
+        let mut counter = Counter       ::new("Counter", &None);
+
+        assert!(integer.supports_record_hook());
+        assert!(integer.record_hook().is_none());
+
+        integer.set_record_hook(Some(hook.clone()));
+        float  .set_record_hook(Some(hook.clone()));
+        time   .set_record_hook(Some(hook.clone()));
+        counter.set_record_hook(Some(hook.clone()));
+
+        assert!(integer.record_hook().is_some());
+
+        integer.record_i64(-4);
+        float  .record_f64(1.5);
+        float  .record_f64(f64::NAN);
+        time   .record_time(1000);
+        counter.record_event();
+        counter.record_i64(3);
+
+        // The NaN is passed to the hook, but not counted.
+
+        let samples = take_samples(&hook);
+
+        assert!(float.count() == 1);
+        assert!(samples.len() == 6);
+        assert!(samples[0] == sample("Integer", HookSample::Integer(-4  )));
+        assert!(samples[1] == sample("Float",   HookSample::Float  (1.5 )));
+        assert!(samples[3] == sample("Time",    HookSample::Time   (1000)));
+        assert!(samples[4] == sample("Counter", HookSample::Event        ));
+        assert!(samples[5] == sample("Counter", HookSample::Integer(3   )));
+
+        // A clone shares the hook, and None removes it.
+
+        let mut clone = integer.clone();
+
+        clone.record_i64(5);
+        integer.set_record_hook(None);
+        integer.record_i64(6);
+
+        assert!(take_samples(&hook) == vec![ sample("Integer", HookSample::Integer(5)) ]);
+    }
+
+    fn test_hier() {
+        let hook          = capture_hook();
+        let dimensions    = vec![ HierDimension::new(2, 4), HierDimension::new(0, 4) ];
+        let descriptor    = HierDescriptor::new(dimensions, Some(2));
+        let name          = "Hier Hook".to_string();
+        let print_opts    = None;
+        let window_size   = Some(4);
+        let configuration = IntegerHierConfig { descriptor, name, window_size, print_opts };
+        let mut hier      = IntegerHier::new_hier(configuration);
+
+        hier.set_record_hook(Some(hook.clone()));
+
+        // The hook sees each sample once, not once per level or once
+        // for the window.
+
+        for i in 0..10 {
+            hier.record_i64(i);
+        }
+
+        let samples = take_samples(&hook);
+
+        assert!(samples.len() == 10);
+        assert!(samples[9] == sample("Hier Hook", HookSample::Integer(9)));
+
+        // The statistics come from the window.
+
+        assert!(hier.count() == 4);
+    }
+
+    fn test_sets() {
+        let hook = capture_hook();
+
+        let mut set     = RcSet::new("Set", 4, 4, &None);
+        let     before  = set.add_running_integer("Before", None);
+        let     window  = set.add_integer_window("Window", 10, None);
+        let     subset  = set.add_subset("Subset", 4, 4);
+
+        set.set_record_hook(Some(hook.clone()));
+
+        let after  = set.add_running_integer("After", None);
+        let nested = subset.borrow_mut().add_counter("Nested", None);
+
+        assert!(set.record_hook().is_some());
+        assert!(window.borrow().record_hook().is_none());
+
+        before.borrow_mut().record_i64(1);
+        window.borrow_mut().record_i64(2);
+        after .borrow_mut().record_i64(3);
+        nested.borrow_mut().record_event();
+
+        let samples = take_samples(&hook);
+
+        assert!(samples.len() == 3);
+        assert!(samples[0] == sample("Before", HookSample::Integer(1)));
+        assert!(samples[1] == sample("After",  HookSample::Integer(3)));
+        assert!(samples[2] == sample("Nested", HookSample::Event     ));
+
+        set.set_record_hook(None);
+        nested.borrow_mut().record_event();
+
+        assert!(take_samples(&hook).is_empty());
+
+        // Check an ArcSet, too.
+
+        let mut set    = ArcSet::new("Arc Set", 4, 4, &None);
+        let     subset = set.add_subset("Arc Subset", 4, 4);
+
+        set.set_record_hook(Some(hook.clone()));
+
+        let member = arc_item_mut!(subset).add_running_time("Arc Time", DurationTimer::new_box());
+
+        arc_item_mut!(member).record_time(7);
+
+        assert!(take_samples(&hook) == vec![ sample("Arc Time", HookSample::Time(7)) ]);
+    }
+
+    fn test_writer_hook() {
+        let mut hook = WriterHook::new(Vec::new());
+
+        hook.record("Integer",     HookSample::Integer(-12  ));
+        hook.record("Float Value", HookSample::Float  (0.125));
+        hook.record("Time",        HookSample::Time   (5000 ));
+        hook.record("Events",      HookSample::Event         );
+
+        assert!(hook.samples() == 4);
+        assert!(hook.errors()  == 0);
+        assert!(hook.flush().is_ok());
+
+        let text = String::from_utf8(hook.writer().clone()).unwrap();
+
+        assert!(text.lines().nth(1) == Some("float 0.125 Float Value"));
+        assert!(text.lines().nth(3) == Some("event - Events"));
+
+        let samples = parse_samples(&text).unwrap();
+
+        assert!(samples.len() == 4);
+        assert!(samples[0] == sample("Integer",     HookSample::Integer(-12  )));
+        assert!(samples[1] == sample("Float Value", HookSample::Float  (0.125)));
+        assert!(samples[2] == sample("Time",        HookSample::Time   (5000 )));
+        assert!(samples[3] == sample("Events",      HookSample::Event          ));
+
+        hook.writer_mut().clear();
+
+        assert!(hook.writer().is_empty());
+
+        assert!(parse_samples("integer x Name\n").is_none());
+        assert!(parse_samples("bogus 1 Name\n"  ).is_none());
+        assert!(parse_samples("integer 1\n"     ).is_none());
+    }
+
+    #[test]
+    fn run_tests() {
+        test_types      ();
+        test_hier       ();
+        test_sets       ();
+        test_writer_hook();
+    }
+}
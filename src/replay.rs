@@ -0,0 +1,373 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Types
+//!
+//! * TraceRecord
+//!     * A TraceRecord is one (timestamp, duration) pair from a trace of
+//!       a workload.  Both values are in clock ticks.
+//!
+//!     * parse_trace() reads a trace in text form.  Each line contains
+//!       a timestamp and a duration separated by white space or a comma.
+//!       Blank lines and lines starting with "#" are ignored.
+//!
+//! * Replayer
+//!     * Replayer feeds a trace into a Hier instance, usually a TimeHier,
+//!       so that a recorded workload can be analyzed using the hierarchy
+//!       configuration used in production.
+//!
+//!     * The advance operations are simulated from the timestamps.  An
+//!       advance is done each time the trace crosses a period boundary,
+//!       so empty periods in the trace produce empty level 0 instances.
+//!       The Hier instance should be configured without auto_next.
+//!
+//!     * The replayer installs a clock in the Hier instance that follows
+//!       the trace timestamps, so the elapsed() and current_age() queries
+//!       give the trace time.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::hier::HierDescriptor;
+//!     use rustics::hier::HierDimension;
+//!     use rustics::time_hier::TimeHier;
+//!     use rustics::time_hier::TimeHierConfig;
+//!     use rustics::time::DurationTimer;
+//!     use rustics::replay::Replayer;
+//!     use rustics::replay::parse_trace;
+//!
+//!     // Make a TimeHier with 4 level 0 periods per level 1 period.
+//!
+//!     let dimensions  = vec![ HierDimension::new(4, 8), HierDimension::new(0, 8) ];
+//!     let descriptor  = HierDescriptor::new(dimensions, None);
+//!     let name        = "Replay".to_string();
+//!     let window_size = None;
+//!     let timer       = DurationTimer::new_box();
+//!     let print_opts  = None;
+//!     let config      = TimeHierConfig { name, descriptor, window_size, timer, print_opts };
+//!     let mut hier    = TimeHier::new_hier(config);
+//!
+//!     // The trace is in nanoseconds.  Use a one-second level 0
+//!     // period.
+//!
+//!     let trace =
+//!         "# timestamp   duration\n\
+//!          0             1000    \n\
+//!          500000000     2000    \n\
+//!          1200000000    3000    \n\
+//!          3100000000    4000    \n";
+//!
+//!     let     records  = parse_trace(trace).unwrap();
+//!     let     period   = 1_000_000_000;
+//!     let     hz       = 1_000_000_000;
+//!     let mut replayer = Replayer::new(period, hz);
+//!
+//!     replayer.replay(&mut hier, &records);
+//!
+//!     // The trace spans 4 periods, so there were 3 advances.
+//!
+//!     assert!(replayer.advances() == 3);
+//!     assert!(hier.event_count()  == 4);
+//!     assert!(hier.elapsed()      == Some(3_100_000_000));
+//!```
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::hier::Hier;
+use super::time::SimpleClock;
+use super::time::ClockTimer;
+use super::Rustics;
+
+/// TraceRecord holds one entry of a trace.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TraceRecord {
+    pub timestamp:  i64,
+    pub duration:   i64,
+}
+
+/// Parses a trace in text form.  Returns None if any line is
+/// malformed.
+
+pub fn parse_trace(text: &str) -> Option<Vec<TraceRecord>> {
+    let mut result = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> =
+            line.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|field| !field.is_empty())
+                .collect();
+
+        if fields.len() != 2 {
+            return None;
+        }
+
+        let timestamp = fields[0].parse::<i64>().ok()?;
+        let duration  = fields[1].parse::<i64>().ok()?;
+
+        result.push(TraceRecord { timestamp, duration });
+    }
+
+    Some(result)
+}
+
+// The clock installed in the Hier instance.  The time is set by the
+// replayer from the trace timestamps.
+
+struct ReplayClock {
+    current:    u128,
+    hz:         u128,
+}
+
+impl SimpleClock for ReplayClock {
+    fn get_time(&mut self) -> u128 {
+        self.current
+    }
+
+    fn hz(&self) -> u128 {
+        self.hz
+    }
+}
+
+/// Replayer feeds trace records into a Hier instance.
+
+pub struct Replayer {
+    period:     i64,
+    start:      Option<i64>,
+    last:       i64,
+    boundary:   i64,
+    clock:      Rc<RefCell<ReplayClock>>,
+    records:    u64,
+    advances:   u64,
+}
+
+impl Replayer {
+    /// Creates a replayer.  The period is the length of a level 0
+    /// period in ticks, and hz is the frequency of the trace clock.
+
+    pub fn new(period: i64, hz: u128) -> Replayer {
+        if period <= 0 {
+            panic!("Replayer::new:  The period must be positive.");
+        }
+
+        let start    = None;
+        let last     = 0;
+        let boundary = 0;
+        let current  = 0;
+        let clock    = Rc::new(RefCell::new(ReplayClock { current, hz }));
+        let records  = 0;
+        let advances = 0;
+
+        Replayer { period, start, last, boundary, clock, records, advances }
+    }
+
+    /// Replays one record.  The timestamps must not decrease.
+
+    pub fn replay_record(&mut self, hier: &mut Hier, record: &TraceRecord) {
+        let timestamp = record.timestamp;
+
+        // The first record starts the clock for the Hier instance.
+
+        let start =
+            if let Some(start) = self.start {
+                start
+            } else {
+                self.start    = Some(timestamp);
+                self.last     = timestamp;
+                self.boundary = timestamp + self.period;
+
+                self.set_time(0);
+                hier.set_clock(ClockTimer::new_box(self.clock.clone()));
+                timestamp
+            };
+
+        if timestamp < self.last {
+            panic!("Replayer::replay_record:  The timestamps are out of order.");
+        }
+
+        // Do an advance for each period boundary crossed, with the
+        // clock set to the boundary.
+
+        while timestamp >= self.boundary {
+            self.set_time(self.boundary - start);
+            hier.advance();
+
+            self.boundary += self.period;
+            self.advances += 1;
+        }
+
+        self.set_time(timestamp - start);
+        hier.record_time(record.duration);
+
+        self.last     = timestamp;
+        self.records += 1;
+    }
+
+    /// Replays a list of records.
+
+    pub fn replay(&mut self, hier: &mut Hier, records: &[TraceRecord]) {
+        for record in records.iter() {
+            self.replay_record(hier, record);
+        }
+    }
+
+    /// Returns the number of records replayed.
+
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// Returns the number of advance operations done.
+
+    pub fn advances(&self) -> u64 {
+        self.advances
+    }
+
+    fn set_time(&self, time: i64) {
+        self.clock.borrow_mut().current = time as u128;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hier::HierDescriptor;
+    use crate::hier::HierDimension;
+    use crate::hier::HierIndex;
+    use crate::hier::HierSet;
+    use crate::time_hier::TimeHier;
+    use crate::time_hier::TimeHierConfig;
+    use crate::tests::continuing_box;
+
+    fn make_hier() -> Hier {
+        let dimensions  =
+            vec![ HierDimension::new(2, 4), HierDimension::new(2, 4), HierDimension::new(0, 4) ];
+
+        let descriptor  = HierDescriptor::new(dimensions, None);
+        let name        = "Replay Test".to_string();
+        let window_size = None;
+        let timer       = continuing_box();
+        let print_opts  = None;
+        let config      = TimeHierConfig { name, descriptor, window_size, timer, print_opts };
+
+        TimeHier::new_hier(config)
+    }
+
+    fn test_parse() {
+        let trace = "# comment\n\n10 20\n30,40\n  50 ,  60  \n";
+        let trace = parse_trace(trace).unwrap();
+
+        assert!(trace.len() == 3);
+        assert!(trace[0] == TraceRecord { timestamp: 10, duration: 20 });
+        assert!(trace[1] == TraceRecord { timestamp: 30, duration: 40 });
+        assert!(trace[2] == TraceRecord { timestamp: 50, duration: 60 });
+
+        assert!(parse_trace("10"      ).is_none());
+        assert!(parse_trace("10 20 30").is_none());
+        assert!(parse_trace("10 x"    ).is_none());
+        assert!(parse_trace(""        ) == Some(Vec::new()));
+    }
+
+    fn test_replay() {
+        let mut hier     = make_hier();
+        let mut replayer = Replayer::new(100, 1_000_000_000);
+
+        // The trace starts at 1000.  Periods are [1000, 1100),
+        // [1100, 1200), and so on.
+
+        let records =
+            [
+                TraceRecord { timestamp: 1000, duration: 1 },
+                TraceRecord { timestamp: 1050, duration: 2 },
+                TraceRecord { timestamp: 1099, duration: 3 },
+                TraceRecord { timestamp: 1100, duration: 4 },
+                TraceRecord { timestamp: 1450, duration: 5 },
+            ];
+
+        replayer.replay(&mut hier, &records[0..3]);
+
+        assert!(replayer.records()  == 3);
+        assert!(replayer.advances() == 0);
+        assert!(hier.elapsed()      == Some(99));
+        assert!(hier.count()        == 3);
+
+        // Crossing the boundary advances.
+
+        replayer.replay_record(&mut hier, &records[3]);
+
+        assert!(replayer.advances()  == 1);
+        assert!(hier.count()         == 1);
+        assert!(hier.current_age()   == Some(0));
+
+        // Skip several periods.  There should be empty level 0
+        // instances.
+
+        replayer.replay_record(&mut hier, &records[4]);
+
+        assert!(replayer.advances()  == 4);
+        assert!(hier.advance_count() == 4);
+        assert!(hier.elapsed()       == Some(450));
+        assert!(hier.current_age()   == Some(50));
+        assert!(hier.count()         == 1);
+
+        // Level 1 got two instances:  the first holds the first two
+        // periods, the second holds two empty periods.
+
+        assert!(hier.all_len(1) == 2);
+
+        let index = HierIndex::new(HierSet::All, 1, 0);
+        let first = hier.index(index).unwrap();
+        let first = first.borrow();
+
+        assert!(first.to_rustics().count() == 4);
+
+        // Now skip enough periods to sum empty instances at the upper
+        // levels.
+
+        let record = TraceRecord { timestamp: 3000, duration: 6 };
+
+        replayer.replay_record(&mut hier, &record);
+
+        assert!(replayer.advances() == 20);
+        assert!(hier.count()        == 1 );
+    }
+
+    #[test]
+    fn run_tests() {
+        test_parse ();
+        test_replay();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_order() {
+        let mut hier     = make_hier();
+        let mut replayer = Replayer::new(100, 1_000_000_000);
+
+        let records =
+            [
+                TraceRecord { timestamp: 1000, duration: 1 },
+                TraceRecord { timestamp:  999, duration: 2 },
+            ];
+
+        replayer.replay(&mut hier, &records);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_period() {
+        let _ = Replayer::new(0, 1_000_000_000);
+    }
+}
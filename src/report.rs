@@ -0,0 +1,664 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * Report
+//!     * A Report is a structured copy of the statistics in a set
+//!       hierarchy.  Building the report walks the set once and produces
+//!       a tree of typed nodes, and the renderers then turn that tree
+//!       into text, JSON, CSV, or the Prometheus exposition format.
+//!       Applications can filter or annotate the tree before rendering
+//!       it.
+//!
+//!     * RusticsSet::report() builds a Report for either set type, and
+//!       Report::from_rustics() builds one for a single instance.
+//!
+//! * SetNode
+//!     * A SetNode holds the name and title of a set, a MemberNode for
+//!       each of its Rustics instances, and a SetNode for each subset.
+//!
+//! * MemberNode
+//!     * A MemberNode holds the title, class, and labels of a Rustics
+//!       instance, its summary fields, and its histogram, if it has one.
+//!
+//!     * The fields are kept in printing order.  Counters and instances
+//!       with no samples have only a count.  The field names match the
+//!       printed reports, e.g., "Std Dev".
+//!
+//! * HistogramNode
+//!     * A HistogramNode holds the buckets of a pseudo-log or float
+//!       histogram from the most negative to the most positive, each
+//!       with the largest value that can be recorded into it.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::printer_mut;
+//!     use rustics::stdout_printer;
+//!     use rustics::rc_item_mut;
+//!     use rustics::rc_sets::RcSet;
+//!     use rustics::rustics_set::RusticsSet;
+//!     use rustics::report::ReportValue;
+//!
+//!     let mut set     = RcSet::new("Server", 2, 0, &None);
+//!     let     latency = set.add_running_integer("Latency", None);
+//!     let     errors  = set.add_counter("Errors", None);
+//!
+//!     for i in 1..=100 {
+//!         rc_item_mut!(latency).record_i64(i);
+//!     }
+//!
+//!     rc_item_mut!(errors).record_event();
+//!
+//!     // Build the report and keep only the members with a histogram
+//!     // before rendering it.
+//!
+//!     let mut report = set.report();
+//!
+//!     report.root.members.retain(|member| member.histogram.is_some());
+//!
+//!     let member = &report.root.members[0];
+//!
+//!     assert!(member.title == "Server ==> Latency");
+//!     assert!(member.field("Maximum") == Some(ReportValue::Integer(100)));
+//!
+//!     // Render it in a few forms.
+//!
+//!     let json = report.to_json();
+//!     let csv  = report.to_csv();
+//!
+//!     assert!(json.contains("\"title\":\"Server ==> Latency\""));
+//!     assert!(!json.contains("Errors"));
+//!     assert!(csv.len() == 2);
+//!
+//!     let printer = stdout_printer();
+//!     let printer = printer_mut!(printer);
+//!
+//!     report.print(printer);
+//!```
+
+use super::Rustics;
+use super::Labels;
+use super::Printer;
+use super::printable::Printable;
+use super::log_histogram::bucket_max as log_bucket_max;
+use super::float_histogram::bucket_max as float_bucket_max;
+
+/// ReportValue is the value of a summary field or a bucket bound.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportValue {
+    Integer(i64),
+    Float(f64),
+}
+
+impl ReportValue {
+    /// Returns the value as an f64.
+
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            ReportValue::Integer(value) => *value as f64,
+            ReportValue::Float(value)   => *value,
+        }
+    }
+
+    // Format the value for JSON.  JSON has no representation for the
+    // non-finite values, so they become null.
+
+    fn json(&self) -> String {
+        match self {
+            ReportValue::Integer(value)                   => format!("{}", value),
+            ReportValue::Float(value) if value.is_finite() => format!("{}", value),
+            ReportValue::Float(_)                          => "null".to_string(),
+        }
+    }
+
+    // Format the value for CSV.
+
+    fn csv(&self) -> String {
+        match self {
+            ReportValue::Integer(value) => format!("{}", value),
+            ReportValue::Float(value)   => format!("{}", value),
+        }
+    }
+}
+
+/// ReportField is one summary field of a member.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReportField {
+    pub name:   String,
+    pub value:  ReportValue,
+}
+
+/// HistogramKind gives the type of histogram a HistogramNode came
+/// from.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistogramKind {
+    Log,
+    Float,
+}
+
+/// BucketNode is one histogram bucket.  The bound is the largest
+/// value that can be recorded into the bucket.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BucketNode {
+    pub bound:  ReportValue,
+    pub count:  u64,
+}
+
+/// HistogramNode holds the buckets of a histogram in order from the
+/// most negative to the most positive.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistogramNode {
+    pub kind:     HistogramKind,
+    pub buckets:  Vec<BucketNode>,
+}
+
+impl HistogramNode {
+    /// Returns the buckets from the first to the last that has any
+    /// samples.
+
+    pub fn used_buckets(&self) -> &[BucketNode] {
+        let first = self.buckets.iter().position (|bucket| bucket.count > 0);
+        let last  = self.buckets.iter().rposition(|bucket| bucket.count > 0);
+
+        match (first, last) {
+            (Some(first), Some(last)) => &self.buckets[first..=last],
+            _                         => &[],
+        }
+    }
+}
+
+/// MemberNode holds the statistics of one Rustics instance.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemberNode {
+    pub name:       String,
+    pub title:      String,
+    pub class:      String,
+    pub labels:     Labels,
+    pub fields:     Vec<ReportField>,
+    pub histogram:  Option<HistogramNode>,
+}
+
+/// The names of the summary fields, in the order they are kept.
+
+pub const FIELD_NAMES: [&str; 8] =
+    [
+        "Count",    "Minimum",  "Maximum",   "Mean",
+        "Std Dev",  "Variance", "Skewness",  "Kurtosis"
+    ];
+
+impl MemberNode {
+    /// Copies the statistics of a Rustics instance.
+
+    pub fn from_rustics(rustics: &dyn Rustics) -> MemberNode {
+        let name      = rustics.name();
+        let title     = rustics.title();
+        let class     = rustics.class().to_string();
+        let labels    = rustics.labels();
+        let export    = rustics.export_stats();
+        let printable = &export.printable;
+
+        let mut fields = Vec::new();
+
+        let mut push =
+            |name: &str, value: ReportValue| {
+                fields.push(ReportField { name: name.to_string(), value });
+            };
+
+        push("Count", ReportValue::Integer(printable.n as i64));
+
+        let summarized = !matches!(rustics.class(), "counter" | "cardinality");
+
+        if summarized && printable.n > 0 {
+            if rustics.int_extremes() {
+                push("Minimum", ReportValue::Integer(printable.min_i64));
+                push("Maximum", ReportValue::Integer(printable.max_i64));
+            } else if rustics.float_extremes() {
+                push("Minimum", ReportValue::Float(printable.min_f64));
+                push("Maximum", ReportValue::Float(printable.max_f64));
+            }
+
+            push("Mean",     ReportValue::Float(printable.mean           ));
+            push("Std Dev",  ReportValue::Float(printable.variance.sqrt()));
+            push("Variance", ReportValue::Float(printable.variance       ));
+            push("Skewness", ReportValue::Float(printable.skewness       ));
+            push("Kurtosis", ReportValue::Float(printable.kurtosis       ));
+        }
+
+        let mut buckets = Vec::new();
+
+        let histogram =
+            if let Some(histogram) = &export.log_histogram {
+                let histogram = histogram.borrow();
+
+                for i in (0..histogram.negative.len()).rev() {
+                    let bound = ReportValue::Integer(log_bucket_max(i, true));
+
+                    buckets.push(BucketNode { bound, count: histogram.negative[i] });
+                }
+
+                for i in 0..histogram.positive.len() {
+                    let bound = ReportValue::Integer(log_bucket_max(i, false));
+
+                    buckets.push(BucketNode { bound, count: histogram.positive[i] });
+                }
+
+                Some(HistogramNode { kind: HistogramKind::Log, buckets })
+            } else if let Some(histogram) = &export.float_histogram {
+                let histogram = histogram.borrow();
+
+                for i in (0..histogram.negative.len()).rev() {
+                    let bound = ReportValue::Float(float_bucket_max(i, true));
+
+                    buckets.push(BucketNode { bound, count: histogram.negative[i] });
+                }
+
+                for i in 0..histogram.positive.len() {
+                    let bound = ReportValue::Float(float_bucket_max(i, false));
+
+                    buckets.push(BucketNode { bound, count: histogram.positive[i] });
+                }
+
+                Some(HistogramNode { kind: HistogramKind::Float, buckets })
+            } else {
+                None
+            };
+
+        MemberNode { name, title, class, labels, fields, histogram }
+    }
+
+    /// Returns the value of the named field, if the member has it.
+
+    pub fn field(&self, name: &str) -> Option<ReportValue> {
+        self.fields.iter().find(|field| field.name == name).map(|field| field.value)
+    }
+
+    /// Returns the sample count.
+
+    pub fn count(&self) -> u64 {
+        match self.field("Count") {
+            Some(ReportValue::Integer(count)) => count as u64,
+            _                                 => 0,
+        }
+    }
+
+    /// Prints the member in the style of the Rustics print() output,
+    /// followed by the histogram buckets that have samples.
+
+    pub fn print(&self, printer: &mut dyn Printer) {
+        printer.print(&self.title);
+
+        for field in self.fields.iter() {
+            match field.value {
+                ReportValue::Integer(value) => Printable::print_integer(&field.name, value, printer),
+                ReportValue::Float(value)   => Printable::print_float  (&field.name, value, printer),
+            }
+        }
+
+        if let Some(histogram) = &self.histogram {
+            for bucket in histogram.used_buckets().iter() {
+                let bound =
+                    match bucket.bound {
+                        ReportValue::Integer(value) => Printable::commas_i64(value),
+                        ReportValue::Float(value)   => format!("{:e}", value),
+                    };
+
+                printer.print(&format!("    <= {:>26}  {:>16}", bound, Printable::commas_u64(bucket.count)));
+            }
+        }
+
+        printer.print("");
+    }
+
+    // Append the member as a JSON object.
+
+    fn push_json(&self, output: &mut String) {
+        output.push_str(&format!("{{\"name\":{},",  json_string(&self.name )));
+        output.push_str(&format!("\"title\":{},",   json_string(&self.title)));
+        output.push_str(&format!("\"class\":{},",   json_string(&self.class)));
+
+        let labels: Vec<String> =
+            self.labels.iter()
+                .map(|(key, value)| format!("{}:{}", json_string(key), json_string(value)))
+                .collect();
+
+        output.push_str(&format!("\"labels\":{{{}}},", labels.join(",")));
+
+        let fields: Vec<String> =
+            self.fields.iter()
+                .map(|field| format!("{}:{}", json_string(&field.name), field.value.json()))
+                .collect();
+
+        output.push_str(&format!("\"fields\":{{{}}},", fields.join(",")));
+
+        match &self.histogram {
+            Some(histogram) => {
+                let kind =
+                    match histogram.kind {
+                        HistogramKind::Log   => "log",
+                        HistogramKind::Float => "float",
+                    };
+
+                let buckets: Vec<String> =
+                    histogram.used_buckets().iter()
+                        .map(|bucket| format!("[{},{}]", bucket.bound.json(), bucket.count))
+                        .collect();
+
+                output.push_str(&format!("\"histogram\":{{\"kind\":\"{}\",\"buckets\":[{}]}}}}",
+                    kind, buckets.join(",")));
+            }
+
+            None => {
+                output.push_str("\"histogram\":null}");
+            }
+        }
+    }
+}
+
+/// SetNode holds the members and subsets of one set.
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SetNode {
+    pub name:     String,
+    pub title:    String,
+    pub members:  Vec<MemberNode>,
+    pub subsets:  Vec<SetNode>,
+}
+
+impl SetNode {
+    /// Invokes the visitor on each member of this set and its subsets.
+    /// The members of a set are visited before its subsets, as with
+    /// RusticsSet::visit_all().
+
+    pub fn visit_all(&self, visitor: &mut dyn FnMut(&MemberNode)) {
+        for member in self.members.iter() {
+            visitor(member);
+        }
+
+        for subset in self.subsets.iter() {
+            subset.visit_all(visitor);
+        }
+    }
+
+    /// Removes the members for which the predicate returns false from
+    /// this set and its subsets.
+
+    pub fn retain_all(&mut self, predicate: &mut dyn FnMut(&MemberNode) -> bool) {
+        self.members.retain(|member| predicate(member));
+
+        for subset in self.subsets.iter_mut() {
+            subset.retain_all(predicate);
+        }
+    }
+
+    // Append the set as a JSON object.
+
+    fn push_json(&self, output: &mut String) {
+        output.push_str(&format!("{{\"name\":{},",  json_string(&self.name )));
+        output.push_str(&format!("\"title\":{},",   json_string(&self.title)));
+        output.push_str("\"members\":[");
+
+        for (i, member) in self.members.iter().enumerate() {
+            if i > 0 {
+                output.push(',');
+            }
+
+            member.push_json(output);
+        }
+
+        output.push_str("],\"subsets\":[");
+
+        for (i, subset) in self.subsets.iter().enumerate() {
+            if i > 0 {
+                output.push(',');
+            }
+
+            subset.push_json(output);
+        }
+
+        output.push_str("]}");
+    }
+}
+
+/// Report is the root of a report tree.
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    pub root:  SetNode,
+}
+
+impl Report {
+    /// Builds a report containing a single Rustics instance.  The root
+    /// set is given the name and title of the instance.
+
+    pub fn from_rustics(rustics: &dyn Rustics) -> Report {
+        let name    = rustics.name();
+        let title   = rustics.title();
+        let members = vec![ MemberNode::from_rustics(rustics) ];
+        let subsets = Vec::new();
+
+        Report { root: SetNode { name, title, members, subsets } }
+    }
+
+    /// Invokes the visitor on every member in the report.
+
+    pub fn visit_all(&self, visitor: &mut dyn FnMut(&MemberNode)) {
+        self.root.visit_all(visitor);
+    }
+
+    /// Prints every member in the report.
+
+    pub fn print(&self, printer: &mut dyn Printer) {
+        self.visit_all(&mut |member| member.print(printer));
+    }
+
+    /// Returns the report as a JSON object.
+
+    pub fn to_json(&self) -> String {
+        let mut output = String::new();
+
+        self.root.push_json(&mut output);
+        output
+    }
+
+    /// Returns the report in CSV form, one line per member, with a
+    /// header line first.  The columns are the title, the class, and
+    /// the fields in FIELD_NAMES.  Fields that a member doesn't have
+    /// are left empty.
+
+    pub fn to_csv(&self) -> Vec<String> {
+        let mut header = vec![ "title".to_string(), "class".to_string() ];
+
+        for name in FIELD_NAMES.iter() {
+            header.push(Printable::csv_escape(name));
+        }
+
+        let mut rows = vec![ header.join(",") ];
+
+        self.visit_all(&mut |member| {
+            let mut row = vec![ Printable::csv_escape(&member.title), Printable::csv_escape(&member.class) ];
+
+            for name in FIELD_NAMES.iter() {
+                match member.field(name) {
+                    Some(value) => row.push(value.csv()),
+                    None        => row.push(String::new()),
+                }
+            }
+
+            rows.push(row.join(","));
+        });
+
+        rows
+    }
+}
+
+// Quote and escape a string for JSON.
+
+fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+
+    result.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"'  => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c    => result.push(c),
+        }
+    }
+
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rc_item_mut;
+    use crate::arc_item;
+    use crate::rc_sets::RcSet;
+    use crate::arc_sets::ArcSet;
+    use crate::rustics_set::RusticsSet;
+    use crate::running_float::RunningFloat;
+    use crate::capture_printer::CapturePrinter;
+
+    fn test_member() {
+        let mut floats = RunningFloat::new("Floats", &None);
+
+        for i in 1..=4 {
+            floats.record_f64(i as f64 / 2.0);
+        }
+
+        floats.set_label("host", "a\"b");
+
+        let report = Report::from_rustics(&floats);
+        let member = &report.root.members[0];
+
+        assert!(report.root.name == "Floats");
+        assert!(member.count()          == 4);
+        assert!(member.field("Minimum") == Some(ReportValue::Float(0.5)));
+        assert!(member.field("Maximum") == Some(ReportValue::Float(2.0)));
+        assert!(member.field("Mean"   ) == Some(ReportValue::Float(1.25)));
+        assert!(member.field("Missing").is_none());
+
+        let histogram = member.histogram.as_ref().unwrap();
+        let used      = histogram.used_buckets();
+
+        assert!(histogram.kind == HistogramKind::Float);
+        assert!(used.iter().map(|bucket| bucket.count).sum::<u64>() == 4);
+
+        let json = report.to_json();
+
+        println!("test_member:  {}", json);
+
+        assert!(json.starts_with("{\"name\":\"Floats\""));
+        assert!(json.contains("\"labels\":{\"host\":\"a\\\"b\"}"));
+        assert!(json.contains("\"Count\":4,"));
+        assert!(json.contains("\"kind\":\"float\""));
+
+        // Printing uses the usual formatting.
+
+        let mut printer = CapturePrinter::new();
+
+        report.print(&mut printer);
+
+        let lines = printer.take_lines();
+
+        assert!(lines[0] == "Floats");
+        assert!(lines[1].contains("Count") && lines[1].contains('4'));
+        assert!(lines.last().unwrap().is_empty());
+    }
+
+    fn test_rc_set() {
+        let mut set      = RcSet::new("Top", 2, 1, &None);
+        let     counter  = set.add_counter("Events", None);
+        let     subset   = set.add_subset("Lower", 1, 0);
+        let     integers = rc_item_mut!(subset).add_running_integer("Sizes", None);
+
+        rc_item_mut!(counter).record_i64(7);
+
+        for i in -2..=5 {
+            rc_item_mut!(integers).record_i64(i);
+        }
+
+        let report = set.report();
+
+        assert!(report.root.members.len() == 1);
+        assert!(report.root.subsets.len() == 1);
+
+        let mut titles = Vec::new();
+
+        report.visit_all(&mut |member| titles.push(member.title.clone()));
+        assert!(titles == [ "Top ==> Events", "Top ==> Lower ==> Sizes" ]);
+
+        // A counter has only a count.
+
+        let events = &report.root.members[0];
+
+        assert!(events.fields.len() == 1);
+        assert!(events.count()      == 7);
+
+        let sizes = &report.root.subsets[0].members[0];
+
+        assert!(sizes.field("Minimum") == Some(ReportValue::Integer(-2)));
+        assert!(sizes.histogram.as_ref().unwrap().kind == HistogramKind::Log);
+
+        let csv = report.to_csv();
+
+        assert!(csv.len() == 3);
+        assert!(csv[0]    == "title,class,Count,Minimum,Maximum,Mean,Std Dev,Variance,Skewness,Kurtosis");
+        assert!(csv[1]    == "Top ==> Events,counter,7,,,,,,,");
+        assert!(csv[2].starts_with("Top ==> Lower ==> Sizes,integer,8,-2,5,1.5,"));
+
+        // Filter the tree.
+
+        let mut filtered = report.clone();
+
+        filtered.root.retain_all(&mut |member| member.class != "counter");
+
+        assert!(filtered.root.members.is_empty());
+        assert!(filtered.to_csv().len() == 2);
+    }
+
+    fn test_arc_set() {
+        let mut set   = ArcSet::new("Arc", 1, 0, &None);
+        let     empty = set.add_running_integer("Empty", None);
+        let     json  = set.report().to_json();
+
+        // A member with no samples has only a count.
+
+        assert!(arc_item!(empty).count() == 0);
+        assert!(json.contains("\"fields\":{\"Count\":0}"));
+        assert!(json.ends_with("\"subsets\":[]}"));
+    }
+
+    fn test_json_string() {
+        assert!(json_string("plain")      == "\"plain\"");
+        assert!(json_string("a\\b\n")     == "\"a\\\\b\\n\"");
+        assert!(json_string("\u{1}")      == "\"\\u0001\"");
+        assert!(ReportValue::Float(f64::NAN).json() == "null");
+    }
+
+    #[test]
+    fn run_tests() {
+        test_member     ();
+        test_rc_set     ();
+        test_arc_set    ();
+        test_json_string();
+    }
+}
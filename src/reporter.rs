@@ -0,0 +1,481 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * Reporter
+//!     * Reporter prints or exports an ArcSet at a fixed interval.  The
+//!       application calls tick() from its own scheduler or main loop,
+//!       and a report is produced each time the interval has elapsed.
+//!
+//!     * A report is either the normal print output for the set or the
+//!       CSV output for a given list of columns.  The set optionally can
+//!       be cleared after each report, so that each report covers only
+//!       the last interval.  Members with a clear policy of Never are
+//!       kept, so lifetime statistics can live in the same set.
+//!
+//!     * Each tick also clears any set members that have a clear policy
+//!       of Every(n) and whose period of n seconds has passed.  See
+//!       ArcSet::set_clear_policy().
+//!
+//!     * The time is read from a Timer instance, so the interval is
+//!       given in the ticks of that timer.  Use DurationTimer for the
+//!       wall clock.
+//!
+//!     * The reporter doesn't start a thread.  The sets use Rc-based
+//!       printers, so an ArcSet can't be moved to another thread.  run()
+//!       provides a loop that sleeps between ticks for applications that
+//!       dedicate a thread to reporting.
+//!
+//! ## Example
+//!```
+//!     use std::rc::Rc;
+//!     use std::cell::RefCell;
+//!     use std::sync::Arc;
+//!     use std::sync::Mutex;
+//!     use rustics::Rustics;
+//!     use rustics::arc_sets::ArcSet;
+//!     use rustics::arc_item_mut;
+//!     use rustics::time::DurationTimer;
+//!     use rustics::reporter::Reporter;
+//!
+//!     let set      = ArcSet::new_box("Server", 4, 0, &None);
+//!     let requests = arc_item_mut!(set).add_counter("Requests", None);
+//!
+//!     // Report once per minute, and clear the set after each report.
+//!     // DurationTimer works in nanoseconds.
+//!
+//!     let     timer    = DurationTimer::new_box();
+//!     let     interval = 60 * 1_000_000_000;
+//!     let mut reporter = Reporter::new(set.clone(), interval, timer);
+//!
+//!     reporter.set_clear(true);
+//!
+//!     arc_item_mut!(requests).record_event();
+//!
+//!     // The main loop of the application calls tick() periodically.
+//!     // The interval hasn't passed yet, so there is no report.
+//!
+//!     assert!(!reporter.tick());
+//!     assert!(reporter.reports() == 0);
+//!
+//!     // A report can be forced at any time.
+//!
+//!     reporter.report();
+//!
+//!     assert!(reporter.reports() == 1);
+//!     assert!(arc_item_mut!(requests).count() == 0);
+//!```
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use super::TimerBox;
+use super::PrinterOption;
+use super::arc_sets::ArcSetBox;
+use super::rustics_set::RusticsSet;
+use super::printable::CsvColumn;
+use super::arc_item_mut;
+use super::timer;
+use super::timer_mut;
+
+/// ReportFormat selects the output produced for each report.
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ReportFormat {
+    Print,
+    Csv(Vec<CsvColumn>),
+}
+
+/// Reporter produces periodic reports for an ArcSet.
+
+pub struct Reporter {
+    set:        ArcSetBox,
+    interval:   i64,
+    timer:      TimerBox,
+    elapsed:    i64,
+    now:        i64,
+    format:     ReportFormat,
+    printer:    PrinterOption,
+    clear:      bool,
+    reports:    u64,
+}
+
+impl Reporter {
+    /// Creates a Reporter for the given set.  The interval is given
+    /// in ticks of the timer, which is restarted by this call.
+
+    pub fn new(set: ArcSetBox, interval: i64, timer: TimerBox) -> Reporter {
+        if interval <= 0 {
+            panic!("Reporter::new:  The interval must be positive.");
+        }
+
+        timer_mut!(timer).start();
+
+        let elapsed = 0;
+        let now     = 0;
+        let format  = ReportFormat::Print;
+        let printer = None;
+        let clear   = false;
+        let reports = 0;
+
+        Reporter { set, interval, timer, elapsed, now, format, printer, clear, reports }
+    }
+
+    /// Sets the output format for the reports.
+
+    pub fn set_format(&mut self, format: ReportFormat) {
+        self.format = format;
+    }
+
+    /// Sets the printer used for the reports.  By default, the set's
+    /// printer is used.
+
+    pub fn set_printer(&mut self, printer: PrinterOption) {
+        self.printer = printer;
+    }
+
+    /// Enables or disables clearing the set after each report.
+
+    pub fn set_clear(&mut self, clear: bool) {
+        self.clear = clear;
+    }
+
+    /// Returns the number of reports produced.
+
+    pub fn reports(&self) -> u64 {
+        self.reports
+    }
+
+    /// Returns the interval in timer ticks.
+
+    pub fn interval(&self) -> i64 {
+        self.interval
+    }
+
+    /// Reads the timer and produces a report if the interval has passed.
+    /// Returns true if a report was made.  If more than one interval has
+    /// passed, only one report is made.  Any members with a scheduled
+    /// clear policy that are due are cleared after the report.
+
+    pub fn tick(&mut self) -> bool {
+        let ticks = timer_mut!(self.timer).finish();
+
+        self.elapsed += ticks;
+        self.now     += ticks;
+
+        let report = self.elapsed >= self.interval;
+
+        if report {
+            self.report();
+        }
+
+        let hz = timer!(self.timer).hz();
+
+        arc_item_mut!(self.set).clear_due(self.now, hz);
+        report
+    }
+
+    /// Returns the time in ticks until the next report is due, as of
+    /// the last tick() invocation.
+
+    pub fn remaining(&self) -> i64 {
+        std::cmp::max(self.interval - self.elapsed, 0)
+    }
+
+    /// Produces a report immediately and starts a new interval.
+
+    pub fn report(&mut self) {
+        let set = arc_item_mut!(self.set);
+
+        match &self.format {
+            ReportFormat::Print        => { set.print_opts(self.printer.clone(), None);    }
+            ReportFormat::Csv(columns) => { set.print_csv (self.printer.clone(), columns); }
+        }
+
+        if self.clear {
+            set.clear_interval();
+        }
+
+        self.elapsed  = 0;
+        self.reports += 1;
+    }
+
+    /// Invokes tick() repeatedly until the stop flag is set, sleeping
+    /// for the given time between ticks.  This blocks the calling
+    /// thread.
+
+    pub fn run(&mut self, pause: Duration, stop: &AtomicBool) {
+        while !stop.load(Ordering::Relaxed) {
+            self.tick();
+            std::thread::sleep(pause);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use crate::arc_sets::ArcSet;
+    use crate::arc_sets::ClearPolicy;
+    use crate::capture_printer::CapturePrinter;
+    use crate::PrinterBox;
+    use crate::printer_box;
+    use crate::arc_item;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_counters;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
+
+    fn test_tick() {
+        let expected =
+            [
+                "Server ==> Requests",
+                "    Count                   5 ",
+                "",
+                "Server ==> Requests",
+                "    Count                   3 ",
+                ""
+            ];
+
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+
+        let set      = ArcSet::new_box("Server", 4, 0, &None);
+        let requests = arc_item_mut!(set).add_counter("Requests", None);
+        let printer  = check_printer_box(&expected, true, true);
+
+        let mut reporter = Reporter::new(set.clone(), 1000, timer);
+
+        reporter.set_printer(Some(printer.clone()));
+        reporter.set_clear(true);
+
+        assert!(reporter.interval()  == 1000);
+        assert!(reporter.remaining() == 1000);
+
+        for _i in 0..5 {
+            arc_item_mut!(requests).record_event();
+        }
+
+        // Move the clock forward, but not far enough.
+
+        clock.borrow_mut().current += 600;
+
+        assert!(!reporter.tick());
+        assert!(reporter.remaining() == 400);
+
+        // Now finish the interval.
+
+        clock.borrow_mut().current += 400;
+
+        assert!(reporter.tick());
+        assert!(reporter.reports()   == 1   );
+        assert!(reporter.remaining() == 1000);
+
+        // The set was cleared.
+
+        assert!(arc_item!(requests).count() == 0);
+
+        // Several intervals give only one report.
+
+        for _i in 0..3 {
+            arc_item_mut!(requests).record_event();
+        }
+
+        clock.borrow_mut().current += 5000;
+
+        assert!( reporter.tick());
+        assert!(!reporter.tick());
+        assert!(reporter.reports() == 2);
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+    }
+
+    fn test_csv() {
+        let expected =
+            [
+                "title,count",
+                "Server ==> Requests,2",
+                "title,count",
+                "Server ==> Requests,4"
+            ];
+
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+
+        let set      = ArcSet::new_box("Server", 4, 0, &None);
+        let requests = arc_item_mut!(set).add_counter("Requests", None);
+        let printer  = check_printer_box(&expected, true, true);
+        let columns  = vec![ CsvColumn::Title, CsvColumn::Count ];
+        let format   = ReportFormat::Csv(columns);
+
+        let mut reporter = Reporter::new(set.clone(), 10, timer);
+
+        reporter.set_printer(Some(printer.clone()));
+        reporter.set_format(format);
+
+        // Without clearing, the counts accumulate.
+
+        for _i in 0..2 {
+            arc_item_mut!(requests).record_event();
+            arc_item_mut!(requests).record_event();
+
+            clock.borrow_mut().current += 10;
+
+            assert!(reporter.tick());
+        }
+
+        assert!(arc_item!(requests).count() == 4);
+
+        let (lines, expected) = check_printer_counters(printer);
+
+        assert!(lines == expected);
+    }
+
+    fn test_run() {
+        let current   = 0;
+        let increment = 1;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+        let set       = ArcSet::new_box("Empty", 0, 0, &None);
+        let stop      = AtomicBool::new(true);
+
+        let mut reporter = Reporter::new(set, 10, timer);
+
+        // The stop flag is already set, so run() returns at once.
+
+        reporter.run(Duration::from_millis(1), &stop);
+
+        assert!(reporter.reports() == 0);
+    }
+
+    fn test_clear_policy() {
+        let hz        = 1_000_000_000_u128;
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+
+        let set       = ArcSet::new_box("Server", 4, 0, &None);
+        let interval  = arc_item_mut!(set).add_counter("Interval",  None);
+        let lifetime  = arc_item_mut!(set).add_counter("Lifetime",  None);
+        let printed   = arc_item_mut!(set).add_counter("Printed",   None);
+        let scheduled = arc_item_mut!(set).add_counter("Scheduled", None);
+        let printer   = Some(printer_box!(CapturePrinter::new()) as PrinterBox);
+
+        {
+            let set = arc_item_mut!(set);
+
+            assert!(set.clear_policy(&interval) == Some(ClearPolicy::Manual));
+
+            assert!(set.set_clear_policy(&lifetime,  ClearPolicy::Never     ));
+            assert!(set.set_clear_policy(&printed,   ClearPolicy::AfterPrint));
+            assert!(set.set_clear_policy(&scheduled, ClearPolicy::Every(3)  ));
+
+            assert!(set.clear_policy(&scheduled) == Some(ClearPolicy::Every(3)));
+        }
+
+        // Report every 2 seconds, and clear the set after each report.
+
+        let mut reporter = Reporter::new(set.clone(), 2 * hz as i64, timer);
+
+        reporter.set_printer(printer.clone());
+        reporter.set_clear(true);
+
+        let record = || {
+            for member in [ &interval, &lifetime, &printed, &scheduled ] {
+                arc_item_mut!(member).record_event();
+            }
+        };
+
+        // The first tick starts the period for the scheduled member.
+
+        record();
+        assert!(!reporter.tick());
+
+        // Printing the set clears only the AfterPrint member.
+
+        arc_item!(set).print_opts(printer.clone(), None);
+
+        assert!(arc_item!(printed  ).count() == 0);
+        assert!(arc_item!(interval ).count() == 1);
+        assert!(arc_item!(scheduled).count() == 1);
+
+        // After the first report, the lifetime member keeps its count.
+
+        record();
+        clock.borrow_mut().current += 2 * hz;
+
+        assert!(reporter.tick());
+
+        assert!(arc_item!(interval ).count() == 0);
+        assert!(arc_item!(lifetime ).count() == 2);
+        assert!(arc_item!(printed  ).count() == 0);
+        assert!(arc_item!(scheduled).count() == 2);
+
+        // The scheduled member is cleared at 3 seconds, between the
+        // reports.
+
+        record();
+        clock.borrow_mut().current += hz;
+
+        assert!(!reporter.tick());
+
+        assert!(arc_item!(interval ).count() == 1);
+        assert!(arc_item!(lifetime ).count() == 3);
+        assert!(arc_item!(scheduled).count() == 0);
+
+        // An explicit clear still clears everything.
+
+        arc_item_mut!(set).clear();
+
+        assert!(arc_item!(lifetime).count() == 0);
+
+        // Members that aren't in the set have no policy.
+
+        let other = ArcSet::new_box("Other", 1, 0, &None);
+        let other = arc_item_mut!(other).add_counter("Other", None);
+
+        assert!(arc_item!(set).clear_policy(&other).is_none());
+        assert!(!arc_item_mut!(set).set_clear_policy(&other, ClearPolicy::Never));
+
+        // Removing a member drops its policy, too.
+
+        assert!(arc_item_mut!(set).remove_stat(lifetime.member()));
+        assert!(arc_item!(set).clear_policy(&printed) == Some(ClearPolicy::AfterPrint));
+    }
+
+    #[test]
+    fn run_tests() {
+        test_tick        ();
+        test_csv         ();
+        test_run         ();
+        test_clear_policy();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_interval() {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock);
+        let set       = ArcSet::new_box("Bad", 0, 0, &None);
+
+        let _ = Reporter::new(set, 0, timer);
+    }
+}
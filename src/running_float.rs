@@ -27,18 +27,15 @@
 //!     // option.  See the RunningInteger comments for an example of
 //!     // how to set the other print options.
 //!
-//!     // Create a HistoOpts for new().
+//!     // Create a HistoOpts for new().  Any option not set keeps its
+//!     // default value.
 //!
-//!     let merge_min    = 0;  // not implemented yet
-//!     let merge_max    = 0;  // not implemented yet
-//!     let no_zero_rows = true;
-//!
-//!     let histo_opts = HistoOpts { merge_min, merge_max, no_zero_rows };
+//!     let histo_opts = HistoOpts::builder().no_zero_rows(true).build();
 //!     let histo_opts = Some(histo_opts);
 //!     let printer    = None;
 //!     let title      = None;
 //!     let units      = None;
-//!     let print_opts = PrintOpts { printer, title, units, histo_opts };
+//!     let print_opts = PrintOpts { printer, title, units, histo_opts, ..Default::default() };
 //!     let print_opts = Some(print_opts);
 //!
 //!     let mut float = RunningFloat::new("Test Statistic", &print_opts);
@@ -106,6 +103,8 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use super::Rustics;
+use super::copy_float_histogram;
+use super::Labels;
 use super::Histogram;
 use super::Printer;
 use super::hier::HierExporter;
@@ -113,23 +112,43 @@ use super::LogHistogramBox;
 use super::TimerBox;
 use super::ExportStats;
 use super::Printable;
+use super::printable::PrintField;
+use super::printable::PrintFields;
 use super::PrintOption;
 use super::PrinterOption;
 use super::PrinterBox;
 use super::Units;
+use super::HistoOpts;
 use super::parse_print_opts;
-use super::compute_variance;
-use super::EstimateData;
-use super::estimate_moment_3;
-use super::compute_skewness;
-use super::compute_kurtosis;
+use super::parse_fields;
+use super::stats_math::compute_variance;
+use super::stats_math::EstimateData;
+use super::stats_math::estimate_moment_3;
+use super::stats_math::compute_skewness;
+use super::stats_math::compute_kurtosis;
 use super::FloatHistogram;
 use super::FloatHistogramBox;
 use super::printer_mut;
 use super::min_f64;
 use super::max_f64;
 use super::merge::Export;
+use super::sum::CompensatedSum;
 use super::merge::sum_running;
+use super::merge::sum_float_histogram;
+use super::merge::Summary;
+use super::merge::export_from_summary;
+use super::sketch_histogram::SketchHistogram;
+use super::median::MedianEstimator;
+use super::trim::TrimOpts;
+use super::trim::Trimmer;
+use super::record_hook::RecordHookBox;
+use super::non_finite::NonFinitePolicy;
+use super::record_hook::HookSample;
+use super::record_hook::call_hook;
+use super::collection_time::CollectionTime;
+use super::bucket_histogram::BucketHistogram;
+use super::checkpoint::Checkpoint;
+use super::checkpoint::CheckpointReader;
 
 // FloatExporter instances are used to export statistics from a
 // RunningFloat instance so that multiple RunningFloat instances can
@@ -199,16 +218,63 @@ pub struct RunningFloat {
     count:      u64,
     nans:       u64,
     infinities: u64,
-    mean:       f64,
-    moment_2:   f64,
-    cubes:      f64,
-    moment_4:   f64,
+    mean:       CompensatedSum,
+    moment_2:   CompensatedSum,
+    cubes:      CompensatedSum,
+    moment_4:   CompensatedSum,
     min:        f64,
     max:        f64,
     title:      String,
     units:      Units,
-    histogram:  FloatHistogramBox,
+    histogram:  Option<FloatHistogramBox>,
     printer:    PrinterBox,
+    histo_opts: HistoOpts,
+    fields:     PrintFields,
+    sketch:     Option<SketchHistogram>,
+    buckets:    Option<BucketHistogram>,
+    median:     Option<MedianEstimator>,
+    trim:       Option<Trimmer>,
+    labels:     Labels,
+    hook:       Option<RecordHookBox>,
+    non_finite: NonFinitePolicy,
+    collection: Option<CollectionTime>,
+}
+
+// Clone makes a deep copy:  the copy gets its own histogram and
+// optional data.  The printer is shared.
+
+impl Clone for RunningFloat {
+    fn clone(&self) -> RunningFloat {
+        let histogram = self.histogram.as_ref().map(copy_float_histogram);
+
+        RunningFloat {
+            name:       self.name.clone(),
+            id:         self.id,
+            count:      self.count,
+            nans:       self.nans,
+            infinities: self.infinities,
+            mean:       self.mean,
+            moment_2:   self.moment_2,
+            cubes:      self.cubes,
+            moment_4:   self.moment_4,
+            min:        self.min,
+            max:        self.max,
+            title:      self.title.clone(),
+            units:      self.units.clone(),
+            histogram,
+            printer:    self.printer.clone(),
+            histo_opts: self.histo_opts,
+            fields:     self.fields,
+            sketch:     self.sketch.clone(),
+            buckets:    self.buckets.clone(),
+            median:     self.median.clone(),
+            trim:       self.trim.clone(),
+            labels:     self.labels.clone(),
+            hook:       self.hook.clone(),
+            non_finite: self.non_finite,
+            collection: self.collection.clone()
+        }
+    }
 }
 
 impl RunningFloat {
@@ -217,7 +283,7 @@ impl RunningFloat {
     /// which sends the output to stdout.
 
     pub fn new(name: &str, print_opts: &PrintOption) -> RunningFloat {
-        let (printer, title, units, _histo_opts) = parse_print_opts(print_opts, name);
+        let (printer, title, units, histo_opts) = parse_print_opts(print_opts, name);
 
         let name        = name.to_string();
         let id          = usize::MAX;
@@ -226,17 +292,27 @@ impl RunningFloat {
         let infinities  = 0;
         let min         = f64::MAX;
         let max         = f64::MIN;
-        let mean        = 0.0;
-        let moment_2    = 0.0;
-        let cubes       = 0.0;
-        let moment_4    = 0.0;
+        let mean        = CompensatedSum::new(0.0);
+        let moment_2    = CompensatedSum::new(0.0);
+        let cubes       = CompensatedSum::new(0.0);
+        let moment_4    = CompensatedSum::new(0.0);
         let histogram   = FloatHistogram::new(print_opts);
-        let histogram   = Rc::from(RefCell::new(histogram));
+        let histogram   = Some(Rc::from(RefCell::new(histogram)));
+        let sketch      = None;
+        let buckets     = None;
+        let median      = None;
+        let trim        = None;
+        let labels      = Labels::new();
+        let hook        = None;
+        let non_finite  = NonFinitePolicy::default();
+        let collection  = None;
+        let fields      = parse_fields(print_opts);
 
         RunningFloat {
             name,      id,        count,    nans,   infinities,  mean,   moment_2,
             cubes,     moment_4,  max,      min,    title,       units,  printer,
-            histogram
+            histogram, histo_opts, sketch, buckets, median,      trim,   labels,
+            hook,      non_finite, collection, fields
         }
     }
 
@@ -245,7 +321,7 @@ impl RunningFloat {
 
     pub fn new_from_exporter(name: &str, title: &str, print_opts: &PrintOption, import: Export)
             -> RunningFloat {
-        let (printer, _title, units, _histo_opts) = parse_print_opts(print_opts, name);
+        let (printer, _title, units, histo_opts) = parse_print_opts(print_opts, name);
 
         let name       = String::from(name);
         let title      = title.to_string();
@@ -253,20 +329,33 @@ impl RunningFloat {
         let count      = import.count;
         let nans       = import.nans;
         let infinities = import.infinities;
-        let mean       = import.mean;
-        let moment_2   = import.moment_2;
-        let cubes      = import.cubes;
-        let moment_4   = import.moment_4;
+        let mean       = CompensatedSum::new(import.mean);
+        let moment_2   = CompensatedSum::new(import.moment_2);
+        let cubes      = CompensatedSum::new(import.cubes);
+        let moment_4   = CompensatedSum::new(import.moment_4);
         let min        = import.min_f64;
         let max        = import.max_f64;
-        let histogram  = import.float_histogram.unwrap();
+        let histogram  = import.float_histogram;
+        let sketch     = None;
+        let buckets    = None;
+        let median     = None;
+        let trim       = None;
+        let labels     = Labels::new();
+        let hook       = None;
+        let non_finite = NonFinitePolicy::default();
+        let collection = None;
+        let fields     = parse_fields(print_opts);
 
         RunningFloat {
             name,       title,      id,
             count,      mean,       moment_2,
             cubes,      moment_4,   histogram,
             min,        max,        printer,
-            units,      nans,       infinities
+            units,      nans,       infinities,
+            histo_opts, sketch,     buckets,
+            median,     trim,       labels,
+            hook,       non_finite, collection,
+            fields
         }
     }
 
@@ -278,9 +367,9 @@ impl RunningFloat {
         let max_i64    = i64::MAX;
         let min_f64    = self.min;
         let max_f64    = self.max;
-        let mode_value = self.histogram.borrow().mode_value();
+        let mode_value = self.mode_value();
         let log_mode   = 0;
-        let mean       = self.mean;
+        let mean       = self.mean.value();
         let variance   = self.variance();
         let skewness   = self.skewness();
         let kurtosis   = self.kurtosis();
@@ -292,6 +381,19 @@ impl RunningFloat {
         }
     }
 
+    /// Sets the handling of NaN and infinite samples.  See the
+    /// non_finite module.
+
+    pub fn set_non_finite_policy(&mut self, policy: NonFinitePolicy) {
+        self.non_finite = policy;
+    }
+
+    /// Returns the handling of NaN and infinite samples.
+
+    pub fn non_finite_policy(&self) -> NonFinitePolicy {
+        self.non_finite
+    }
+
     pub fn nans(&self) -> u64 {
         self.nans
     }
@@ -307,11 +409,11 @@ impl RunningFloat {
         let count           = self.count;
         let nans            = self.nans;
         let infinities      = self.infinities;
-        let mean            = self.mean;
-        let moment_2        = self.moment_2;
-        let cubes           = self.cubes;
-        let moment_4        = self.moment_4;
-        let float_histogram = Some(self.histogram.clone());
+        let mean            = self.mean.value();
+        let moment_2        = self.moment_2.value();
+        let cubes           = self.cubes.value();
+        let moment_4        = self.moment_4.value();
+        let float_histogram = self.histogram.clone();
         let log_histogram   = None;
         let min_i64         = 0;
         let max_i64         = 0;
@@ -327,9 +429,189 @@ impl RunningFloat {
         }
     }
 
+    /// Saves the statistics as a string so that they can be restored
+    /// later.  See the checkpoint module.  Any sketch is not saved.
+
+    pub fn checkpoint(&self) -> String {
+        let mut checkpoint = Checkpoint::new("running_float");
+
+        checkpoint.push_str("name",  &self.name );
+        checkpoint.push_str("title", &self.title);
+        checkpoint.push_export(&self.export_data());
+        checkpoint.text()
+    }
+
+    /// Restores an instance saved by checkpoint().  The result is None
+    /// if the checkpoint is malformed or was made for another type.
+
+    pub fn restore(text: &str, print_opts: &PrintOption) -> Option<RunningFloat> {
+        let reader = CheckpointReader::new_kind(text, "running_float")?;
+        let name   = reader.string("name" )?;
+        let title  = reader.string("title")?;
+        let export = reader.export(print_opts)?;
+
+        if export.log_histogram.is_some() {
+            return None;
+        }
+
+        Some(RunningFloat::new_from_exporter(&name, &title, print_opts, export))
+    }
+
+    /// Merges the statistics from another instance into this one.  The
+    /// counts, moments, extremes, and histogram are combined.  The
+    /// sketches are merged if both instances have one.  If this
+    /// instance has no histogram, none is created.
+
+    pub fn merge(&mut self, other: &RunningFloat) {
+        if other.count == 0 && other.nans == 0 && other.infinities == 0 {
+            return;
+        }
+
+        let exports = vec![ self.export_data(), other.export_data() ];
+        let sum     = sum_running(&exports);
+
+        self.count      = sum.count;
+        self.nans       = sum.nans;
+        self.infinities = sum.infinities;
+        self.mean       = CompensatedSum::new(sum.mean);
+        self.moment_2   = CompensatedSum::new(sum.moment_2);
+        self.cubes      = CompensatedSum::new(sum.cubes);
+        self.moment_4   = CompensatedSum::new(sum.moment_4);
+        self.min        = sum.min_f64;
+        self.max        = sum.max_f64;
+
+        // Sum into the existing histogram, so that handles returned
+        // by float_histogram() stay valid.  The addend is copied first,
+        // in case both instances share a histogram.
+
+        if let (Some(histogram), Some(addend)) = (&self.histogram, &other.histogram) {
+            let addend = addend.borrow().clone();
+
+            sum_float_histogram(&mut histogram.borrow_mut(), &addend);
+        }
+
+        if let (Some(sketch), Some(addend)) = (&mut self.sketch, &other.sketch) {
+            sketch.merge(addend);
+        }
+
+        if let (Some(buckets), Some(addend)) = (&mut self.buckets, &other.buckets) {
+            buckets.merge(addend);
+        }
+
+        if let (Some(trim), Some(addend)) = (&mut self.trim, &other.trim) {
+            trim.merge(addend);
+        }
+    }
+
+    /// Folds an external summary of samples into this instance, as if
+    /// the samples had been recorded.  The count, moments, and extremes
+    /// are merged, but the histogram, sketch, and any other optional
+    /// data don't include the imported samples.  See Summary and
+    /// export_from_summary() in the merge module.
+
+    pub fn merge_summary(&mut self, summary: &Summary) {
+        if summary.count == 0 {
+            return;
+        }
+
+        let exports = vec![ self.export_data(), export_from_summary(summary) ];
+        let sum     = sum_running(&exports);
+
+        self.count      = sum.count;
+        self.mean       = CompensatedSum::new(sum.mean);
+        self.moment_2   = CompensatedSum::new(sum.moment_2);
+        self.cubes      = CompensatedSum::new(sum.cubes);
+        self.moment_4   = CompensatedSum::new(sum.moment_4);
+        self.min        = sum.min_f64;
+        self.max        = sum.max_f64;
+    }
+
+    /// Disables the float histogram.  The histogram is discarded,
+    /// samples no longer are recorded into it, and float_histogram()
+    /// returns None.  The mode value is not printed, and is reported
+    /// as zero.
+
+    pub fn disable_histogram(&mut self) {
+        self.histogram = None;
+    }
+
+    /// Returns whether the float histogram is kept.
+
+    pub fn has_histogram(&self) -> bool {
+        self.histogram.is_some()
+    }
+
+    // Returns the mode value from the histogram, if there is one.
+
+    fn mode_value(&self) -> f64 {
+        match &self.histogram {
+            Some(histogram) => histogram.borrow().mode_value(),
+            None            => 0.0,
+        }
+    }
+
+    // Returns the fields to print, omitting the mode value if there
+    // is no histogram.
+
+    fn print_fields(&self) -> PrintFields {
+        if self.histogram.is_some() {
+            self.fields
+        } else {
+            self.fields.without(PrintField::ModeValue)
+        }
+    }
+
     pub fn set_units(&mut self, units: Units) {
         self.units = units;
     }
+
+    /// Enables a SketchHistogram with the given relative error to
+    /// provide accurate percentiles.  Any samples already recorded
+    /// are not included.  Instances created by summing, as in a Hier
+    /// instance, don't have a sketch.
+
+    pub fn enable_sketch(&mut self, relative_error: f64) {
+        self.sketch = Some(SketchHistogram::new(relative_error));
+    }
+
+    /// Returns the sketch, if one is enabled.
+
+    pub fn sketch(&self) -> Option<&SketchHistogram> {
+        self.sketch.as_ref()
+    }
+
+    /// Enables a BucketHistogram with user-specified edges, which is
+    /// printed after the usual histogram.  Any samples already recorded
+    /// are not included.  Instances created by summing don't have a
+    /// bucket histogram.
+
+    pub fn enable_buckets(&mut self, histogram: BucketHistogram) {
+        self.buckets = Some(histogram);
+    }
+
+    /// Returns the bucket histogram, if one is enabled.
+
+    pub fn buckets(&self) -> Option<&BucketHistogram> {
+        self.buckets.as_ref()
+    }
+
+    /// Enables a MedianEstimator, so that the median() and mad()
+    /// methods are supported.  Any samples already recorded are not
+    /// included.  The estimates can't be merged, so merge() leaves
+    /// them unchanged, and instances created by summing don't have
+    /// an estimator.
+
+    pub fn enable_median(&mut self) {
+        self.median = Some(MedianEstimator::new());
+    }
+
+    /// Enables a trimmed mean and an outlier count.  See the trim
+    /// module.  Any samples already recorded are not included.  merge()
+    /// combines the data if both instances keep it.
+
+    pub fn enable_trimming(&mut self, opts: TrimOpts) {
+        self.trim = Some(Trimmer::new(opts));
+    }
 }
 
 impl Rustics for RunningFloat {
@@ -337,51 +619,74 @@ impl Rustics for RunningFloat {
         panic!("RunningFloat::record_i64: not supported");
     }
 
-    /// Records an f64 sample.  NaN and infinite values are counted
-    /// but otherwise ignored.
+    /// Records an f64 sample.  NaN and infinite values are handled
+    /// as set by the NonFinitePolicy, and by default are counted but
+    /// otherwise ignored.
 
     fn record_f64(&mut self, sample: f64) {
-        // Ignore NaNs for now.
+        call_hook(&self.hook, &self.name, HookSample::Float(sample));
 
-        if sample.is_nan() {
-            self.nans += 1;
-            return;
-        }
+        if !sample.is_finite() {
+            if !self.non_finite.counts(&self.name, sample) {
+                return;
+            }
 
-        // Ignore non-finite values, too.
+            if sample.is_nan() {
+                self.nans += 1;
+            } else {
+                self.infinities += 1;
+            }
 
-        if sample.is_infinite() {
-            self.infinities += 1;
             return;
         }
 
         self.count += 1;
 
         if self.count == 1 {
-            self.mean     = sample;
-            self.moment_2 = 0.0;
-            self.cubes    = 0.0;
-            self.moment_4 = 0.0;
+            self.mean     = CompensatedSum::new(sample);
+            self.moment_2 = CompensatedSum::new(0.0);
+            self.cubes    = CompensatedSum::new(0.0);
+            self.moment_4 = CompensatedSum::new(0.0);
             self.min      = sample;
             self.max      = sample;
         } else {
-            let distance_mean     = sample - self.mean;
-            let new_mean          = self.mean + distance_mean / self.count as f64;
-            let distance_new_mean = sample - new_mean;
+            // The moments are kept as compensated sums, so that the
+            // rounding errors don't accumulate over long runs.
+
+            let distance_mean = sample - self.mean.value();
+
+            self.mean.add(distance_mean / self.count as f64);
+
+            let distance_new_mean = sample - self.mean.value();
             let square_estimate   = distance_mean * distance_new_mean;
-            let new_moment_2      = self.moment_2 + square_estimate;
-            let new_cubes         = self.cubes + sample.powi(3);
-            let new_moment_4      = self.moment_4 + square_estimate * square_estimate;
-
-            self.mean             = new_mean;
-            self.moment_2         = new_moment_2;
-            self.cubes            = new_cubes;
-            self.moment_4         = new_moment_4;
+
+            self.moment_2.add(square_estimate);
+            self.cubes   .add(sample.powi(3));
+            self.moment_4.add(square_estimate * square_estimate);
+
             self.min              = min_f64(self.min, sample);
             self.max              = max_f64(self.max, sample);
         }
 
-        self.histogram.borrow_mut().record(sample);
+        if let Some(histogram) = &self.histogram {
+            histogram.borrow_mut().record(sample);
+        }
+
+        if let Some(sketch) = &mut self.sketch {
+            sketch.record(sample);
+        }
+
+        if let Some(buckets) = &mut self.buckets {
+            buckets.record(sample);
+        }
+
+        if let Some(median) = &mut self.median {
+            median.record(sample);
+        }
+
+        if let Some(trim) = &mut self.trim {
+            trim.record(sample);
+        }
     }
 
     fn record_event(&mut self) {
@@ -421,7 +726,7 @@ impl Rustics for RunningFloat {
     }
 
     fn mean(&self) -> f64 {
-        self.mean
+        self.mean.value()
     }
 
     fn standard_deviation(&self) -> f64 {
@@ -429,23 +734,23 @@ impl Rustics for RunningFloat {
     }
 
     fn variance(&self) -> f64 {
-        compute_variance(self.count, self.moment_2)
+        compute_variance(self.count, self.moment_2.value())
     }
 
     fn skewness(&self) -> f64 {
         let n        = self.count as f64;
-        let mean     = self.mean;
-        let moment_2 = self.moment_2;
-        let cubes    = self.cubes;
+        let mean     = self.mean.value();
+        let moment_2 = self.moment_2.value();
+        let cubes    = self.cubes.value();
         let data     = EstimateData { n, mean, moment_2, cubes };
 
         let moment_3 = estimate_moment_3(data);
 
-        compute_skewness(self.count, self.moment_2, moment_3)
+        compute_skewness(self.count, moment_2, moment_3)
     }
 
     fn kurtosis(&self) -> f64 {
-        compute_kurtosis(self.count, self.moment_2, self.moment_4)
+        compute_kurtosis(self.count, self.moment_2.value(), self.moment_4.value())
     }
 
     fn int_extremes(&self) -> bool {
@@ -477,14 +782,45 @@ impl Rustics for RunningFloat {
 
     fn clear(&mut self) {
         self.count    = 0;
-        self.mean     = 0.0;
-        self.moment_2 = 0.0;
-        self.cubes    = 0.0;
-        self.moment_4 = 0.0;
+        self.mean     = CompensatedSum::new(0.0);
+        self.moment_2 = CompensatedSum::new(0.0);
+        self.cubes    = CompensatedSum::new(0.0);
+        self.moment_4 = CompensatedSum::new(0.0);
         self.min      = f64::MAX;
         self.max      = f64::MIN;
 
-        self.histogram.borrow_mut().clear();
+        if let Some(histogram) = &self.histogram {
+            histogram.borrow_mut().clear();
+        }
+
+        if let Some(sketch) = &mut self.sketch {
+            sketch.clear();
+        }
+
+        if let Some(buckets) = &mut self.buckets {
+            buckets.clear();
+        }
+
+        if let Some(median) = &mut self.median {
+            median.clear();
+        }
+
+        if let Some(trim) = &mut self.trim {
+            trim.clear();
+        }
+
+        if let Some(collection) = &mut self.collection {
+            collection.restart();
+        }
+    }
+
+    fn supports_reset_extremes(&self) -> bool {
+        true
+    }
+
+    fn reset_extremes(&mut self) {
+        self.min = f64::MAX;
+        self.max = f64::MIN;
     }
 
     fn print(&self) {
@@ -507,12 +843,46 @@ impl Rustics for RunningFloat {
             };
 
         let printable = self.get_printable();
+        let fields    = self.print_fields();
         let printer   = printer_mut!(printer);
 
         printer.print(title);
-        printable.print_common_f64(printer);
-        printable.print_common_float(printer);
-        self.histogram.borrow().print(printer);
+        printable.print_common_f64_fields  (fields, printer);
+        printable.print_common_float_fields(fields, printer);
+
+        if let Some(median) = &self.median {
+            Printable::print_float("Median", median.median(), printer);
+            Printable::print_float("MAD",    median.mad(),    printer);
+        }
+
+        if let Some(trim) = &self.trim {
+            Printable::print_float  ("Trimmed Mean", trim.trimmed_mean(),     printer);
+            Printable::print_integer("Outliers",     self.outliers() as i64, printer);
+        }
+
+        if let Some(collection) = &self.collection {
+            collection.print(self.count, printer);
+        }
+
+        if self.histo_opts.print_histogram(self.fields) {
+            if let Some(histogram) = &self.histogram {
+                histogram.borrow().print(printer);
+            }
+
+            if let Some(sketch) = &self.sketch {
+                sketch.print(printer);
+            }
+
+            if let Some(buckets) = &self.buckets {
+                buckets.print(printer);
+            }
+        }
+
+        if self.histo_opts.print_cdf {
+            if let Some(histogram) = &self.histogram {
+                histogram.borrow().print_cdf(printer);
+            }
+        }
         printer.print("");
     }
 
@@ -520,12 +890,80 @@ impl Rustics for RunningFloat {
         self.title = title.to_string();
     }
 
+    fn set_label(&mut self, key: &str, value: &str) {
+        self.labels.insert(key.to_string(), value.to_string());
+    }
+
+    fn labels(&self) -> Labels {
+        self.labels.clone()
+    }
+
+    fn has_median(&self) -> bool {
+        self.median.is_some()
+    }
+
+    fn median(&self) -> f64 {
+        match &self.median {
+            Some(median) => median.median(),
+            None         => panic!("RunningFloat::median:  not enabled"),
+        }
+    }
+
+    fn mad(&self) -> f64 {
+        match &self.median {
+            Some(median) => median.mad(),
+            None         => panic!("RunningFloat::mad:  not enabled"),
+        }
+    }
+
+    fn supports_record_hook(&self) -> bool {
+        true
+    }
+
+    fn set_record_hook(&mut self, hook: Option<RecordHookBox>) {
+        self.hook = hook;
+    }
+
+    fn record_hook(&self) -> Option<RecordHookBox> {
+        self.hook.clone()
+    }
+
+    fn supports_collection_time(&self) -> bool {
+        true
+    }
+
+    fn set_collection_clock(&mut self, clock: Option<TimerBox>) {
+        self.collection = clock.map(CollectionTime::new);
+    }
+
+    fn collection_seconds(&self) -> Option<f64> {
+        self.collection.as_ref().map(|collection| collection.seconds())
+    }
+
+    fn has_trimming(&self) -> bool {
+        self.trim.is_some()
+    }
+
+    fn trimmed_mean(&self) -> f64 {
+        match &self.trim {
+            Some(trim) => trim.trimmed_mean(),
+            None       => panic!("RunningFloat::trimmed_mean:  not enabled"),
+        }
+    }
+
+    fn outliers(&self) -> u64 {
+        match &self.trim {
+            Some(trim) => trim.outliers(self.mean(), self.standard_deviation()),
+            None       => panic!("RunningFloat::outliers:  not enabled"),
+        }
+    }
+
     fn log_histogram(&self) -> Option<LogHistogramBox> {
         None
     }
 
     fn float_histogram(&self) -> Option<FloatHistogramBox> {
-        Some(self.histogram.clone())
+        self.histogram.clone()
     }
 
     // Methods for internal use.
@@ -553,7 +991,7 @@ impl Rustics for RunningFloat {
     fn export_stats(&self) -> ExportStats {
         let printable       = self.get_printable();
         let log_histogram   = None;
-        let float_histogram = Some(self.histogram.clone());
+        let float_histogram = self.histogram.clone();
 
         ExportStats {printable, log_histogram, float_histogram }
     }
@@ -561,11 +999,15 @@ impl Rustics for RunningFloat {
 
 impl Histogram for RunningFloat {
     fn print_histogram(&self, printer: &mut dyn Printer) {
-        self.histogram.borrow().print(printer);
+        if let Some(histogram) = &self.histogram {
+            histogram.borrow().print(printer);
+        }
     }
 
     fn clear_histogram(&mut self) {
-        self.histogram.borrow_mut().clear();
+        if let Some(histogram) = &self.histogram {
+            histogram.borrow_mut().clear();
+        }
     }
 
     fn to_log_histogram  (&self) -> Option<LogHistogramBox> {
@@ -573,13 +1015,14 @@ impl Histogram for RunningFloat {
     }
 
     fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
-        Some(self.histogram.clone())
+        self.histogram.clone()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::trim::TrimOpts;
     use crate::PrintOpts;
     use crate::stdout_printer;
     use crate::tests::continuing_box;
@@ -834,7 +1277,7 @@ mod tests {
         let     title      = None;
         let     units      = bytes();
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 
         let     name       = "Test Statistics";
         let mut stats      = RunningFloat::new(&name, &print_opts);
@@ -847,6 +1290,338 @@ mod tests {
         stats.print();
     }
 
+    fn test_buckets() {
+        let mut stats = RunningFloat::new("Bucket Test", &None);
+
+        stats.enable_buckets(BucketHistogram::new(&[ 1.0, 1.1, 1.2 ]));
+
+        for i in 0..100 {
+            stats.record_f64(1.0 + i as f64 / 500.0);
+        }
+
+        stats.record_f64(f64::NAN);
+
+        let buckets = stats.buckets().unwrap();
+
+        assert!(buckets.counts()  == [ 0, 50, 50, 0 ]);
+        assert!(buckets.samples() == 100);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.buckets().unwrap().samples() == 0);
+    }
+
+    fn test_sketch() {
+        let mut stats = RunningFloat::new("Sketch Test", &None);
+
+        assert!(stats.sketch().is_none());
+
+        stats.enable_sketch(0.01);
+
+        for i in 1..=1000 {
+            stats.record_f64(i as f64 / 10.0);
+        }
+
+        stats.record_f64(f64::NAN);
+
+        let sketch = stats.sketch().unwrap();
+        let p90    = sketch.percentile(90.0);
+
+        assert!(sketch.samples() == 1000);
+        assert!((p90 - 90.0).abs() <= 0.9);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.sketch().unwrap().samples() == 0);
+    }
+
+    // Check that merging two instances matches recording all the
+    // samples into one instance.
+
+    fn test_merge() {
+        let mut first    = RunningFloat::new("Merge First",    &None);
+        let mut second   = RunningFloat::new("Merge Second",   &None);
+        let mut expected = RunningFloat::new("Merge Expected", &None);
+
+        for i in 1..=1000 {
+            let sample = (i as f64).sqrt() - 10.0;
+
+            if i % 3 == 0 {
+                first.record_f64(sample);
+            } else {
+                second.record_f64(sample);
+            }
+
+            expected.record_f64(sample);
+        }
+
+        second.record_f64(f64::NAN);
+        second.record_f64(f64::INFINITY);
+
+        // A handle to the histogram taken before the merge sees the
+        // merged data.
+
+        let handle = first.float_histogram().unwrap();
+
+        first.merge(&second);
+
+        assert!(handle.borrow().positive == expected.float_histogram().unwrap().borrow().positive);
+        assert!(handle.borrow().negative == expected.float_histogram().unwrap().borrow().negative);
+
+        assert!(first.count()      == expected.count()  );
+        assert!(first.min_f64()    == expected.min_f64());
+        assert!(first.max_f64()    == expected.max_f64());
+        assert!(first.nans()       == 1);
+        assert!(first.infinities() == 1);
+
+        assert!((first.mean    () - expected.mean    ()).abs() <= 1.0e-9 * expected.mean().abs());
+        assert!((first.variance() - expected.variance()).abs() <= 1.0e-9 * expected.variance());
+
+        // The third moment is estimated, so the skewness and kurtosis
+        // can differ slightly.
+
+        assert!((first.skewness() - expected.skewness()).abs() <= 0.02);
+        assert!((first.kurtosis() - expected.kurtosis()).abs() <= 0.05);
+
+        let merged    = first.to_float_histogram().unwrap();
+        let reference = expected.to_float_histogram().unwrap();
+
+        assert!(merged.borrow().positive == reference.borrow().positive);
+        assert!(merged.borrow().negative == reference.borrow().negative);
+    }
+
+    fn test_checkpoint() {
+        let mut stats = RunningFloat::new("Checkpoint Test", &None);
+
+        for i in 1..=1000 {
+            stats.record_f64(1.0 / i as f64);
+        }
+
+        stats.record_f64(f64::NAN);
+        stats.record_f64(f64::NEG_INFINITY);
+
+        let checkpoint = stats.checkpoint();
+        let restored   = RunningFloat::restore(&checkpoint, &None).unwrap();
+
+        assert!(restored.count()      == stats.count()     );
+        assert!(restored.nans()       == stats.nans()      );
+        assert!(restored.infinities() == stats.infinities());
+        assert!(restored.mean()       == stats.mean()      );
+        assert!(restored.variance()   == stats.variance()  );
+        assert!(restored.min_f64()    == stats.min_f64()   );
+        assert!(restored.max_f64()    == stats.max_f64()   );
+
+        let restored_histogram = restored.histogram.as_ref().unwrap().borrow();
+        let stats_histogram    = stats   .histogram.as_ref().unwrap().borrow();
+
+        assert!(restored_histogram.equals(&stats_histogram));
+
+        let text = checkpoint.replace("rustics running_float", "rustics running_integer");
+
+        assert!(RunningFloat::restore(&text, &None).is_none());
+    }
+
+    fn test_median() {
+        let mut stats = RunningFloat::new("Median Test", &None);
+
+        assert!(!stats.has_median());
+
+        stats.enable_median();
+
+        for i in 0..1001 {
+            stats.record_f64(((i * 367) % 1001) as f64 / 10.0);
+        }
+
+        stats.record_f64(f64::NAN);
+
+        assert!(stats.has_median());
+        assert!((stats.median() - 50.0).abs() < 1.0);
+        assert!((stats.mad()    - 25.0).abs() < 1.0);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.median() == 0.0);
+    }
+
+    fn test_no_histogram() {
+        let expected =
+            [
+                "No Histogram",
+                "    Count               1,000 ",
+                "    NaNs                    0 ",
+                "    Infinities              0 ",
+                "    Minimum          +1.00000 e+0  byte",
+                "    Maximum          +1.00000 e+3  bytes",
+                "    Mean             +5.00500 e+2  bytes",
+                "    Std Dev          +2.88819 e+2  bytes",
+                "    Variance         +8.34166 e+4  ",
+                "    Skewness         -4.16317 e-11 ",
+                "    Kurtosis         -1.19999 e+0  ",
+                ""
+            ];
+
+        let     printer    = Some(check_printer_box(&expected, true, false));
+        let     title      = None;
+        let     units      = bytes();
+        let     histo_opts = None;
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
+        let mut stats      = RunningFloat::new("No Histogram", &print_opts);
+
+        assert!(stats.has_histogram());
+
+        stats.disable_histogram();
+
+        for i in 1..=1000 {
+            stats.record_f64(i as f64);
+        }
+
+        assert!(!stats.has_histogram());
+        assert!(stats.float_histogram   ().is_none());
+        assert!(stats.to_float_histogram().is_none());
+        assert!(stats.export_stats      ().float_histogram.is_none());
+
+        stats.print();
+
+        // Merging keeps the histogram disabled.
+
+        let mut other = RunningFloat::new("Other", &None);
+
+        other.record_f64(2000.0);
+        stats.merge(&other);
+
+        assert!(stats.count()   == 1001  );
+        assert!(stats.max_f64() == 2000.0);
+        assert!(!stats.has_histogram());
+
+        // So does a checkpoint.
+
+        let restored = RunningFloat::restore(&stats.checkpoint(), &None).unwrap();
+
+        assert!(restored.count() == 1001);
+        assert!(!restored.has_histogram());
+
+        stats.clear_histogram();
+        stats.clear();
+
+        assert!(stats.count() == 0);
+    }
+
+    fn test_reset_extremes() {
+        let mut stats = RunningFloat::new("Reset Extremes", &None);
+
+        stats.record_f64(-1.0e6);
+
+        for i in 1..=10 {
+            stats.record_f64(i as f64);
+        }
+
+        let mean = stats.mean();
+
+        assert!(stats.min_f64() == -1.0e6);
+
+        // The count and moments survive, but the extremes restart.
+
+        stats.reset_extremes();
+
+        assert!(stats.count() == 11);
+        assert!(stats.mean()  == mean);
+
+        stats.record_f64(2.5);
+        stats.record_f64(3.5);
+
+        assert!(stats.count()   == 13 );
+        assert!(stats.min_f64() == 2.5);
+        assert!(stats.max_f64() == 3.5);
+    }
+
+
+    fn test_clone() {
+        let mut stats = RunningFloat::new("Clone Test", &None);
+
+        stats.enable_median();
+
+        for i in 1..=9 {
+            stats.record_f64(i as f64);
+        }
+
+        let mut copy = stats.clone();
+
+        copy.record_f64(1.0e6);
+
+        assert!(stats.count()   == 9  );
+        assert!(stats.max_f64() == 9.0);
+        assert!(stats.median()  == 5.0);
+        assert!(copy .count()   == 10 );
+        assert!(copy .max_f64() == 1.0e6);
+
+        let original = stats.float_histogram().unwrap();
+        let copied   = copy .float_histogram().unwrap();
+
+        assert!(original.borrow().samples == 9 );
+        assert!(copied  .borrow().samples == 10);
+    }
+
+    fn test_trimming() {
+        let mut stats = RunningFloat::new("Trimming", &None);
+
+        assert!(!stats.has_trimming());
+
+        stats.enable_trimming(TrimOpts::default());
+
+        for i in 1..=99 {
+            stats.record_f64(i as f64);
+        }
+
+        stats.record_f64(1.0e6);
+
+        // The huge sample is trimmed, and it's the only outlier.
+
+        let trimmed = stats.trimmed_mean();
+
+        println!("test_trimming:  mean {}, trimmed {}", stats.mean(), trimmed);
+
+        assert!(stats.has_trimming());
+        assert!(stats.mean() > 10_000.0);
+        assert!((trimmed - 50.0).abs() < 50.0 * 0.01);
+        assert!(stats.outliers() == 1);
+
+        // Merging combines the samples.
+
+        let mut other = stats.clone();
+
+        other.clear();
+        other.record_f64(1.0e6);
+        stats.merge(&other);
+
+        assert!(stats.outliers() == 2);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.trimmed_mean() == 0.0);
+    }
+
+    fn test_non_finite() {
+        let mut stats = RunningFloat::new("Ignore Test", &None);
+
+        stats.set_non_finite_policy(NonFinitePolicy::Ignore);
+
+        assert!(stats.non_finite_policy() == NonFinitePolicy::Ignore);
+
+        stats.record_f64(2.0);
+        stats.record_f64(f64::NAN);
+        stats.record_f64(f64::INFINITY);
+
+        let printable = stats.export_stats().printable;
+
+        assert!(printable.n          == 1  );
+        assert!(printable.rejected() == 0  );
+        assert!(stats.mean()         == 2.0);
+    }
+
     #[test]
     fn run_tests() {
         simple_float_test      ();
@@ -855,5 +1630,32 @@ mod tests {
         test_title             ();
         test_histogram         ();
         test_print_output      ();
+        test_sketch            ();
+        test_buckets           ();
+        test_merge             ();
+        test_checkpoint        ();
+        test_median            ();
+        test_no_histogram      ();
+        test_reset_extremes    ();
+        test_clone             ();
+        test_trimming          ();
+        test_non_finite        ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_non_finite_panic() {
+        let mut stats = RunningFloat::new("Panic Test", &None);
+
+        stats.set_non_finite_policy(NonFinitePolicy::Panic);
+        stats.record_f64(f64::NAN);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_no_median() {
+        let stats = RunningFloat::new("No Median", &None);
+
+        let _ = stats.median();
     }
 }
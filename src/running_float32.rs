@@ -0,0 +1,611 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//! * RunningFloat32
+//!   * RunningFloat32 provides the RunningFloat statistics for samples
+//!     of type f32, and does its arithmetic in f32.  This type is meant
+//!     for embedded and DSP targets where f64 arithmetic is slow.
+//!
+//!   * The histogram is a FloatHistogram, shared with the f64 types.
+//!     Every f32 value converts exactly to f64, so the histogram counts
+//!     are the same as for RunningFloat.
+//!
+//!   * The f32 format has a 24-bit mantissa, about 7 decimal digits, so
+//!     the summary statistics are less precise than those of RunningFloat.
+//!     In particular:
+//!       * The mean is kept as a running estimate, so its relative error
+//!         is roughly 1.0e-7 times the log of the sample count, but the
+//!         contribution of each sample shrinks as 1/n, and after about
+//!         2^24 samples, samples close to the mean stop moving it.
+//!
+//!       * The variance is accumulated as a sum of squared distances
+//!         from the mean.  The relative error grows with the count, so
+//!         long runs should be split, for example with a Hier instance.
+//!
+//!       * The skewness uses a sum of cubes, which overflows to infinity
+//!         for samples larger than about 7.0e12 in magnitude.
+//!
+//!       * f64 samples given to record_f64() are rounded to f32.  Values
+//!         too large for f32 are counted as infinities.
+//!
+//!   * Values are converted to f64 when exported or printed, so an
+//!     instance can be summed with RunningFloat instances.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::running_float32::RunningFloat32;
+//!
+//!     let mut float = RunningFloat32::new("Sensor Reading", &None);
+//!
+//!     for i in 1..=1000 {
+//!         float.record_f32(i as f32);
+//!     }
+//!
+//!     assert!(float.count()   == 1000  );
+//!     assert!(float.min_f64() == 1.0   );
+//!     assert!(float.max_f64() == 1000.0);
+//!
+//!     // The mean is close to, but not necessarily exactly, the
+//!     // value computed with f64 arithmetic.
+//!
+//!     assert!((float.mean() - 500.5).abs() < 0.01);
+//!
+//!     // NaNs and infinite values are counted, but not used for the
+//!     // summary statistics.  An f64 value too large for f32 becomes
+//!     // infinite.
+//!
+//!     float.record_f32(f32::NAN);
+//!     float.record_f64(1.0e300);
+//!
+//!     assert!(float.count()      == 1000);
+//!     assert!(float.nans()       == 1   );
+//!     assert!(float.infinities() == 1   );
+//!
+//!     float.print();
+//!```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::Rustics;
+use super::copy_float_histogram;
+use super::Histogram;
+use super::Printer;
+use super::LogHistogramBox;
+use super::TimerBox;
+use super::ExportStats;
+use super::Printable;
+use super::PrintOption;
+use super::PrinterOption;
+use super::PrinterBox;
+use super::Units;
+use super::HistoOpts;
+use super::parse_print_opts;
+use super::printable::PrintFields;
+use super::parse_fields;
+use super::stats_math::compute_variance;
+use super::stats_math::EstimateData;
+use super::stats_math::estimate_moment_3;
+use super::stats_math::compute_skewness;
+use super::stats_math::compute_kurtosis;
+use super::FloatHistogram;
+use super::FloatHistogramBox;
+use super::printer_mut;
+use super::merge::Export;
+
+/// This type implements a simple set of statistics for a
+/// sequence of f32 values, using f32 arithmetic.
+
+pub struct RunningFloat32 {
+    name:       String,
+    id:         usize,
+    count:      u64,
+    nans:       u64,
+    infinities: u64,
+    mean:       f32,
+    moment_2:   f32,
+    cubes:      f32,
+    moment_4:   f32,
+    min:        f32,
+    max:        f32,
+    title:      String,
+    units:      Units,
+    histogram:  FloatHistogramBox,
+    printer:    PrinterBox,
+    histo_opts: HistoOpts,
+    fields:     PrintFields,
+}
+
+// Clone makes a deep copy:  the copy gets its own histogram.  The
+// printer is shared.
+
+impl Clone for RunningFloat32 {
+    fn clone(&self) -> RunningFloat32 {
+        let histogram = copy_float_histogram(&self.histogram);
+
+        RunningFloat32 {
+            name:       self.name.clone(),
+            id:         self.id,
+            count:      self.count,
+            nans:       self.nans,
+            infinities: self.infinities,
+            mean:       self.mean,
+            moment_2:   self.moment_2,
+            cubes:      self.cubes,
+            moment_4:   self.moment_4,
+            min:        self.min,
+            max:        self.max,
+            title:      self.title.clone(),
+            units:      self.units.clone(),
+            histogram,
+            printer:    self.printer.clone(),
+            histo_opts: self.histo_opts,
+            fields:     self.fields
+        }
+    }
+}
+
+impl RunningFloat32 {
+    /// Constructs a new instance.  print_opts configures the
+    /// output of print functions.  "None" will accept the defaults,
+    /// which sends the output to stdout.
+
+    pub fn new(name: &str, print_opts: &PrintOption) -> RunningFloat32 {
+        let (printer, title, units, histo_opts) = parse_print_opts(print_opts, name);
+
+        let name        = name.to_string();
+        let id          = usize::MAX;
+        let count       = 0;
+        let nans        = 0;
+        let infinities  = 0;
+        let min         = f32::MAX;
+        let max         = f32::MIN;
+        let mean        = 0.0;
+        let moment_2    = 0.0;
+        let cubes       = 0.0;
+        let moment_4    = 0.0;
+        let histogram   = FloatHistogram::new(print_opts);
+        let histogram   = Rc::from(RefCell::new(histogram));
+        let fields      = parse_fields(print_opts);
+
+        RunningFloat32 {
+            name,      id,        count,    nans,   infinities,  mean,   moment_2,
+            cubes,     moment_4,  max,      min,    title,       units,  printer,
+            histogram, histo_opts, fields
+        }
+    }
+
+    pub fn get_printable(&self) -> Printable {
+        let n          = self.count;
+        let nans       = self.nans;
+        let infinities = self.infinities;
+        let min_i64    = i64::MIN;
+        let max_i64    = i64::MAX;
+        let min_f64    = self.min as f64;
+        let max_f64    = self.max as f64;
+        let mode_value = self.histogram.borrow().mode_value();
+        let log_mode   = 0;
+        let mean       = self.mean as f64;
+        let variance   = self.variance();
+        let skewness   = self.skewness();
+        let kurtosis   = self.kurtosis();
+        let units      = self.units.clone();
+
+        Printable {
+            n,         nans,  infinities,  min_i64,   max_i64,   min_f64,  max_f64,
+            log_mode,  mean,  variance,    skewness,  kurtosis,  units,    mode_value
+        }
+    }
+
+    pub fn nans(&self) -> u64 {
+        self.nans
+    }
+
+    pub fn infinities(&self) -> u64 {
+        self.infinities
+    }
+
+    /// Exports all the statistics kept for a given instance.  The
+    /// values are converted to f64, so the result can be summed with
+    /// RunningFloat exports.
+
+    pub fn export_data(&self) -> Export {
+        let count           = self.count;
+        let nans            = self.nans;
+        let infinities      = self.infinities;
+        let mean            = self.mean     as f64;
+        let moment_2        = self.moment_2 as f64;
+        let cubes           = self.cubes    as f64;
+        let moment_4        = self.moment_4 as f64;
+        let float_histogram = Some(self.histogram.clone());
+        let log_histogram   = None;
+        let min_i64         = 0;
+        let max_i64         = 0;
+        let min_f64         = self.min as f64;
+        let max_f64         = self.max as f64;
+
+        Export {
+            count,            nans,           infinities,
+            mean,             moment_2,       cubes,
+            moment_4,         min_i64,        max_i64,
+            min_f64,          max_f64,
+            float_histogram,  log_histogram
+        }
+    }
+
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+    }
+}
+
+impl Rustics for RunningFloat32 {
+    fn record_i64(&mut self, _sample: i64) {
+        panic!("RunningFloat32::record_i64: not supported");
+    }
+
+    /// Records an f64 sample after rounding it to f32.
+
+    fn record_f64(&mut self, sample: f64) {
+        self.record_f32(sample as f32);
+    }
+
+    /// Records an f32 sample.  NaN and infinite values are counted
+    /// but otherwise ignored.
+
+    fn record_f32(&mut self, sample: f32) {
+        if sample.is_nan() {
+            self.nans += 1;
+            return;
+        }
+
+        if sample.is_infinite() {
+            self.infinities += 1;
+            return;
+        }
+
+        self.count += 1;
+
+        if self.count == 1 {
+            self.mean     = sample;
+            self.moment_2 = 0.0;
+            self.cubes    = 0.0;
+            self.moment_4 = 0.0;
+            self.min      = sample;
+            self.max      = sample;
+        } else {
+            let distance_mean     = sample - self.mean;
+            let new_mean          = self.mean + distance_mean / self.count as f32;
+            let distance_new_mean = sample - new_mean;
+            let square_estimate   = distance_mean * distance_new_mean;
+
+            self.mean             = new_mean;
+            self.moment_2        += square_estimate;
+            self.cubes           += sample.powi(3);
+            self.moment_4        += square_estimate * square_estimate;
+            self.min              = self.min.min(sample);
+            self.max              = self.max.max(sample);
+        }
+
+        self.histogram.borrow_mut().record_f32(sample);
+    }
+
+    fn record_event(&mut self) {
+        panic!("RunningFloat32::record_event: not supported");
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        panic!("RunningFloat32::record_event_report: not supported");
+    }
+
+    fn record_time(&mut self, _sample: i64) {
+        panic!("RunningFloat32::record_time: not supported");
+    }
+
+    fn record_interval(&mut self, _timer: &mut TimerBox) {
+        panic!("RunningFloat32::record_interval: not supported");
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self)-> String {
+        self.title.clone()
+    }
+
+    fn class(&self) -> &str {
+        "float"
+    }
+
+    fn count(&self) -> u64 {
+        self.count
+    }
+
+    fn log_mode(&self) -> isize {
+        panic!("RunningFloat32::log_mode:  not supported");
+    }
+
+    fn mean(&self) -> f64 {
+        self.mean as f64
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    fn variance(&self) -> f64 {
+        compute_variance(self.count, self.moment_2 as f64)
+    }
+
+    fn skewness(&self) -> f64 {
+        let n        = self.count    as f64;
+        let mean     = self.mean     as f64;
+        let moment_2 = self.moment_2 as f64;
+        let cubes    = self.cubes    as f64;
+        let data     = EstimateData { n, mean, moment_2, cubes };
+
+        let moment_3 = estimate_moment_3(data);
+
+        compute_skewness(self.count, moment_2, moment_3)
+    }
+
+    fn kurtosis(&self) -> f64 {
+        compute_kurtosis(self.count, self.moment_2 as f64, self.moment_4 as f64)
+    }
+
+    fn int_extremes(&self) -> bool {
+        false
+    }
+
+    fn float_extremes(&self) -> bool {
+        true
+    }
+
+    fn min_i64(&self) -> i64 {
+        panic!("RunningFloat32::min_i64: not supported");
+    }
+
+    fn min_f64(&self) -> f64 {
+        self.min as f64
+    }
+
+    fn max_i64(&self) -> i64 {
+        panic!("RunningFloat32::max_i64: not supported");
+    }
+
+    fn max_f64(&self) -> f64 {
+        self.max as f64
+    }
+
+    fn precompute(&mut self) {
+    }
+
+    fn clear(&mut self) {
+        self.count    = 0;
+        self.mean     = 0.0;
+        self.moment_2 = 0.0;
+        self.cubes    = 0.0;
+        self.moment_4 = 0.0;
+        self.min      = f32::MAX;
+        self.max      = f32::MIN;
+
+        self.histogram.borrow_mut().clear();
+    }
+
+    fn supports_reset_extremes(&self) -> bool {
+        true
+    }
+
+    fn reset_extremes(&mut self) {
+        self.min = f32::MAX;
+        self.max = f32::MIN;
+    }
+
+    fn print(&self) {
+        self.print_opts(None, None);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let printer =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        let printable = self.get_printable();
+        let printer   = printer_mut!(printer);
+
+        printer.print(title);
+        printable.print_common_f64_fields  (self.fields, printer);
+        printable.print_common_float_fields(self.fields, printer);
+
+        if self.histo_opts.print_histogram(self.fields) {
+            self.histogram.borrow().print(printer);
+        }
+
+        if self.histo_opts.print_cdf {
+            self.histogram.borrow().print_cdf(printer);
+        }
+
+        printer.print("");
+    }
+
+    fn set_title (&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        Some(self.histogram.clone())
+    }
+
+    // Methods for internal use.
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<RunningFloat32>(other.generic()) {
+            std::ptr::eq(self, other)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn export_stats(&self) -> ExportStats {
+        let printable       = self.get_printable();
+        let log_histogram   = None;
+        let float_histogram = Some(self.histogram.clone());
+
+        ExportStats {printable, log_histogram, float_histogram }
+    }
+}
+
+impl Histogram for RunningFloat32 {
+    fn print_histogram(&self, printer: &mut dyn Printer) {
+        self.histogram.borrow().print(printer);
+    }
+
+    fn clear_histogram(&mut self) {
+        self.histogram.borrow_mut().clear();
+    }
+
+    fn to_log_histogram  (&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+        Some(self.histogram.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::running_float::RunningFloat;
+    use crate::merge::sum_running;
+
+    fn test_simple() {
+        let mut float32 = RunningFloat32::new("Float32", &None);
+        let mut float64 = RunningFloat::new  ("Float64", &None);
+
+        for i in 1..=1000 {
+            let sample = (i as f32).sqrt() - 10.0;
+
+            float32.record_f32(sample);
+            float64.record_f32(sample);
+        }
+
+        assert!(float32.count()   == float64.count()  );
+        assert!(float32.min_f64() == float64.min_f64());
+        assert!(float32.max_f64() == float64.max_f64());
+        assert!(float32.class()   == "float"          );
+
+        let tolerance = 1.0e-4;
+
+        assert!((float32.mean    () - float64.mean    ()).abs() < tolerance * float64.mean().abs());
+        assert!((float32.variance() - float64.variance()).abs() < tolerance * float64.variance());
+
+        // The histograms are built by the same code from the same
+        // values.
+
+        let histo_32 = float32.to_float_histogram().unwrap();
+        let histo_64 = float64.to_float_histogram().unwrap();
+
+        assert!(histo_32.borrow().equals(&histo_64.borrow()));
+
+        float32.clear();
+
+        assert!(float32.count() == 0  );
+        assert!(float32.mean()  == 0.0);
+    }
+
+    fn test_special_values() {
+        let mut float = RunningFloat32::new("Special Values", &None);
+
+        float.record_f32(f32::NAN);
+        float.record_f32(f32::INFINITY);
+        float.record_f64(f64::MAX);
+        float.record_f64(1.5);
+
+        assert!(float.count()      == 1  );
+        assert!(float.nans()       == 1  );
+        assert!(float.infinities() == 2  );
+        assert!(float.mean()       == 1.5);
+
+        let stats = float.export_stats();
+
+        assert!(stats.printable.nans       == 1);
+        assert!(stats.printable.infinities == 2);
+    }
+
+    // Check that an export can be summed with RunningFloat exports.
+
+    fn test_export() {
+        let mut float32 = RunningFloat32::new("Export 32", &None);
+        let mut float64 = RunningFloat::new  ("Export 64", &None);
+
+        for i in 1..=100 {
+            float32.record_f64(i as f64);
+            float64.record_f64(-i as f64);
+        }
+
+        let exports = vec![ float32.export_data(), float64.export_data() ];
+        let sum     = sum_running(&exports);
+
+        assert!(sum.count   == 200    );
+        assert!(sum.min_f64 == -100.0 );
+        assert!(sum.max_f64 ==  100.0 );
+        assert!(sum.mean.abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_simple        ();
+        test_special_values();
+        test_export        ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_record_i64() {
+        let mut float = RunningFloat32::new("Test Statistic", &None);
+
+        float.record_i64(1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_min_i64() {
+        let float = RunningFloat32::new("Test Statistic", &None);
+        let _     = float.min_i64();
+    }
+}
@@ -32,7 +32,7 @@
 //!    let units      = Some(Units::new("byte", "bytes"));
 //!    let histo_opts = None;
 //!
-//!    let print_opts = PrintOpts { printer, title, units, histo_opts };
+//!    let print_opts = PrintOpts { printer, title, units, histo_opts, ..Default::default() };
 //!
 //!    let mut packet_sizes =
 //!        RunningInteger::new("Packet Sizes", &Some(print_opts));
@@ -113,6 +113,7 @@ use std::cmp::min;
 use std::cmp::max;
 
 use super::Rustics;
+use super::copy_log_histogram;
 use super::Histogram;
 use super::TimerBox;
 use super::Printer;
@@ -123,45 +124,114 @@ use super::PrintOption;
 use super::LogHistogramBox;
 use super::FloatHistogramBox;
 use super::Units;
+use super::Labels;
+use super::HistoOpts;
 use super::printer_mut;
 use super::printable::Printable;
-use super::EstimateData;
-use super::estimate_moment_3;
-use super::compute_variance;
-use super::compute_skewness;
-use super::compute_kurtosis;
+use super::printable::PrintField;
+use super::printable::PrintFields;
+use super::stats_math::EstimateData;
+use super::stats_math::estimate_moment_3;
+use super::stats_math::compute_variance;
+use super::stats_math::compute_skewness;
+use super::stats_math::compute_kurtosis;
 use super::merge::Export;
+use super::sum::CompensatedSum;
 use super::merge::sum_running;
+use super::merge::sum_log_histogram;
+use super::merge::Summary;
+use super::merge::export_from_summary;
+use super::duplicates::DuplicateFilter;
+use super::sketch_histogram::SketchHistogram;
+use super::median::MedianEstimator;
+use super::worst_tags::WorstTags;
+use super::worst_tags::TaggedSample;
+use super::trim::TrimOpts;
+use super::trim::Trimmer;
+use super::record_hook::RecordHookBox;
+use super::record_hook::HookSample;
+use super::record_hook::call_hook;
+use super::collection_time::CollectionTime;
+use super::bucket_histogram::BucketHistogram;
+use super::checkpoint::Checkpoint;
+use super::checkpoint::CheckpointReader;
 
 use crate::hier::HierExporter;
 use crate::LogHistogram;
 
 use super::parse_print_opts;
+use super::parse_fields;
 
 /// RunningInteger provides basic statistics on a
 /// stream of integer data samples.
 ///
 /// See the module comments for a sample program.
 
-#[derive(Clone)]
 pub struct RunningInteger {
     name:       String,
     title:      String,
     id:         usize,
 
     count:      u64,
-    mean:       f64,
-    moment_2:   f64,
-    cubes:      f64,
-    moment_4:   f64,
+    mean:       CompensatedSum,
+    moment_2:   CompensatedSum,
+    cubes:      CompensatedSum,
+    moment_4:   CompensatedSum,
 
     min:        i64,
     max:        i64,
 
-    histogram:  LogHistogramBox,
+    histogram:  Option<LogHistogramBox>,
 
     printer:    PrinterBox,
     units:      Units,
+    histo_opts: HistoOpts,
+    fields:     PrintFields,
+    duplicates: Option<DuplicateFilter>,
+    sketch:     Option<SketchHistogram>,
+    buckets:    Option<BucketHistogram>,
+    median:     Option<MedianEstimator>,
+    worst:      Option<WorstTags>,
+    trim:       Option<Trimmer>,
+    labels:     Labels,
+    hook:       Option<RecordHookBox>,
+    collection: Option<CollectionTime>,
+}
+
+// Clone makes a deep copy:  the copy gets its own histogram and
+// optional data.  The printer is shared.
+
+impl Clone for RunningInteger {
+    fn clone(&self) -> RunningInteger {
+        let histogram = self.histogram.as_ref().map(copy_log_histogram);
+
+        RunningInteger {
+            name:       self.name.clone(),
+            title:      self.title.clone(),
+            id:         self.id,
+            count:      self.count,
+            mean:       self.mean,
+            moment_2:   self.moment_2,
+            cubes:      self.cubes,
+            moment_4:   self.moment_4,
+            min:        self.min,
+            max:        self.max,
+            histogram,
+            printer:    self.printer.clone(),
+            units:      self.units.clone(),
+            histo_opts: self.histo_opts,
+            fields:     self.fields,
+            duplicates: self.duplicates.clone(),
+            sketch:     self.sketch.clone(),
+            buckets:    self.buckets.clone(),
+            median:     self.median.clone(),
+            worst:      self.worst.clone(),
+            trim:       self.trim.clone(),
+            labels:     self.labels.clone(),
+            hook:       self.hook.clone(),
+            collection: self.collection.clone()
+        }
+    }
 }
 
 // IntegerExporter instances are used to export statistics from a
@@ -234,26 +304,39 @@ impl RunningInteger {
     /// an optional set of print options.
 
     pub fn new(name: &str, print_opts: &PrintOption) -> RunningInteger {
-        let (printer, title, units, _histo_opts) = parse_print_opts(print_opts, name);
-
-        let name      = name.to_string();
-        let id        = usize::MAX;
-        let count     = 0;
-        let mean      = 0.0;
-        let moment_2  = 0.0;
-        let cubes     = 0.0;
-        let moment_4  = 0.0;
-        let min       = i64::MAX;
-        let max       = i64::MIN;
-        let histogram = LogHistogram::new();
-        let histogram = Rc::from(RefCell::new(histogram));
+        let (printer, title, units, histo_opts) = parse_print_opts(print_opts, name);
+
+        let name       = name.to_string();
+        let id         = usize::MAX;
+        let count      = 0;
+        let mean       = CompensatedSum::new(0.0);
+        let moment_2   = CompensatedSum::new(0.0);
+        let cubes      = CompensatedSum::new(0.0);
+        let moment_4   = CompensatedSum::new(0.0);
+        let min        = i64::MAX;
+        let max        = i64::MIN;
+        let histogram  = LogHistogram::new();
+        let histogram  = Some(Rc::from(RefCell::new(histogram)));
+        let duplicates = None;
+        let sketch     = None;
+        let buckets    = None;
+        let median     = None;
+        let worst      = None;
+        let trim       = None;
+        let labels     = Labels::new();
+        let hook       = None;
+        let collection = None;
+        let fields     = parse_fields(print_opts);
 
         RunningInteger {
             name,       title,      id,
             count,      mean,       moment_2,
             cubes,      moment_4,   histogram,
             min,        max,        printer,
-            units
+            units,      histo_opts, duplicates,
+            sketch,     buckets,    median,
+            worst,      trim,       labels,
+            hook,       collection, fields
         }
     }
 
@@ -262,26 +345,39 @@ impl RunningInteger {
 
     pub fn new_from_exporter(name: &str, title: &str, print_opts: &PrintOption, import: Export)
             -> RunningInteger {
-        let (printer, _title, units, _histo_opts) = parse_print_opts(print_opts, name);
-
-        let name      = String::from(name);
-        let title     = title.to_string();
-        let id        = usize::MAX;
-        let count     = import.count;
-        let mean      = import.mean;
-        let moment_2  = import.moment_2;
-        let cubes     = import.cubes;
-        let moment_4  = import.moment_4;
-        let min       = import.min_i64;
-        let max       = import.max_i64;
-        let histogram = import.log_histogram.unwrap();
+        let (printer, _title, units, histo_opts) = parse_print_opts(print_opts, name);
+
+        let name       = String::from(name);
+        let title      = title.to_string();
+        let id         = usize::MAX;
+        let count      = import.count;
+        let mean       = CompensatedSum::new(import.mean);
+        let moment_2   = CompensatedSum::new(import.moment_2);
+        let cubes      = CompensatedSum::new(import.cubes);
+        let moment_4   = CompensatedSum::new(import.moment_4);
+        let min        = import.min_i64;
+        let max        = import.max_i64;
+        let histogram  = import.log_histogram;
+        let duplicates = None;
+        let sketch     = None;
+        let buckets    = None;
+        let median     = None;
+        let worst      = None;
+        let trim       = None;
+        let labels     = Labels::new();
+        let hook       = None;
+        let collection = None;
+        let fields     = parse_fields(print_opts);
 
         RunningInteger {
             name,       title,      id,
             count,      mean,       moment_2,
             cubes,      moment_4,   histogram,
             min,        max,        printer,
-            units
+            units,      histo_opts, duplicates,
+            sketch,     buckets,    median,
+            worst,      trim,       labels,
+            hook,       collection, fields
         }
     }
 
@@ -292,11 +388,11 @@ impl RunningInteger {
         let count           = self.count;
         let nans            = 0;
         let infinities      = 0;
-        let mean            = self.mean;
-        let moment_2        = self.moment_2;
-        let cubes           = self.cubes;
-        let moment_4        = self.moment_4;
-        let log_histogram   = Some(self.histogram.clone());
+        let mean            = self.mean.value();
+        let moment_2        = self.moment_2.value();
+        let cubes           = self.cubes.value();
+        let moment_4        = self.moment_4.value();
+        let log_histogram   = self.histogram.clone();
         let float_histogram = None;
         let min_i64         = self.min;
         let max_i64         = self.max;
@@ -312,10 +408,236 @@ impl RunningInteger {
         }
     }
 
+    /// Saves the statistics as a string so that they can be restored
+    /// later, possibly by another process.  See the checkpoint module.
+    /// Any duplicate filter or sketch is not saved.
+
+    pub fn checkpoint(&self) -> String {
+        let mut checkpoint = Checkpoint::new("running_integer");
+
+        checkpoint.push_str("name",  &self.name );
+        checkpoint.push_str("title", &self.title);
+        checkpoint.push_export(&self.export_data());
+        checkpoint.text()
+    }
+
+    /// Restores an instance saved by checkpoint().  The result is None
+    /// if the checkpoint is malformed or was made for another type.
+
+    pub fn restore(text: &str, print_opts: &PrintOption) -> Option<RunningInteger> {
+        let reader = CheckpointReader::new_kind(text, "running_integer")?;
+        let name   = reader.string("name" )?;
+        let title  = reader.string("title")?;
+        let export = reader.export(print_opts)?;
+
+        if export.float_histogram.is_some() {
+            return None;
+        }
+
+        Some(RunningInteger::new_from_exporter(&name, &title, print_opts, export))
+    }
+
+    /// Merges the statistics from another instance into this one.  The
+    /// counts, moments, extremes, and histograms are combined, so the
+    /// result matches an instance that recorded the samples from both.
+    /// The sketches are merged if both instances have one, but the
+    /// duplicate counts are not changed.  If this instance has no
+    /// histogram, none is created.
+
+    pub fn merge(&mut self, other: &RunningInteger) {
+        if other.count == 0 {
+            return;
+        }
+
+        let exports = vec![ self.export_data(), other.export_data() ];
+        let sum     = sum_running(&exports);
+
+        self.count     = sum.count;
+        self.mean      = CompensatedSum::new(sum.mean);
+        self.moment_2  = CompensatedSum::new(sum.moment_2);
+        self.cubes     = CompensatedSum::new(sum.cubes);
+        self.moment_4  = CompensatedSum::new(sum.moment_4);
+        self.min       = sum.min_i64;
+        self.max       = sum.max_i64;
+
+        // Sum into the existing histogram, so that handles returned
+        // by log_histogram() stay valid.  The addend is copied first,
+        // in case both instances share a histogram.
+
+        if let (Some(histogram), Some(addend)) = (&self.histogram, &other.histogram) {
+            let addend = addend.borrow().clone();
+
+            sum_log_histogram(&mut histogram.borrow_mut(), &addend);
+        }
+
+        if let (Some(sketch), Some(addend)) = (&mut self.sketch, &other.sketch) {
+            sketch.merge(addend);
+        }
+
+        if let (Some(buckets), Some(addend)) = (&mut self.buckets, &other.buckets) {
+            buckets.merge(addend);
+        }
+
+        if let (Some(worst), Some(addend)) = (&mut self.worst, &other.worst) {
+            worst.merge(addend);
+        }
+
+        if let (Some(trim), Some(addend)) = (&mut self.trim, &other.trim) {
+            trim.merge(addend);
+        }
+    }
+
+    /// Folds an external summary of samples into this instance, as if
+    /// the samples had been recorded.  The count, moments, and extremes
+    /// are merged, but the histogram, sketch, and any other optional
+    /// data don't include the imported samples.  See Summary and
+    /// export_from_summary() in the merge module.
+
+    pub fn merge_summary(&mut self, summary: &Summary) {
+        if summary.count == 0 {
+            return;
+        }
+
+        let exports = vec![ self.export_data(), export_from_summary(summary) ];
+        let sum     = sum_running(&exports);
+
+        self.count     = sum.count;
+        self.mean      = CompensatedSum::new(sum.mean);
+        self.moment_2  = CompensatedSum::new(sum.moment_2);
+        self.cubes     = CompensatedSum::new(sum.cubes);
+        self.moment_4  = CompensatedSum::new(sum.moment_4);
+        self.min       = sum.min_i64;
+        self.max       = sum.max_i64;
+    }
+
+    /// Disables the pseudo-log histogram.  The histogram is discarded,
+    /// samples no longer are recorded into it, and log_histogram()
+    /// returns None.  The log mode and mode value are not printed,
+    /// and the log mode is reported as zero.  This saves time and
+    /// space for users who need only the moments and extremes.
+
+    pub fn disable_histogram(&mut self) {
+        self.histogram = None;
+    }
+
+    /// Returns whether the pseudo-log histogram is kept.
+
+    pub fn has_histogram(&self) -> bool {
+        self.histogram.is_some()
+    }
+
+    // Returns the fields to print, omitting the ones computed from the
+    // histogram if there is none.
+
+    fn print_fields(&self) -> PrintFields {
+        if self.histogram.is_some() {
+            self.fields
+        } else {
+            self.fields.without(PrintField::LogMode).without(PrintField::ModeValue)
+        }
+    }
+
     pub fn set_units(&mut self, units: Units) {
         self.units = units;
     }
 
+    /// Enables counting of samples whose values have been recorded
+    /// before.  See DuplicateFilter for the meaning of the parameters.
+    /// Any samples already recorded are not included.
+
+    pub fn enable_duplicates(&mut self, capacity: usize, false_positive: f64) {
+        self.duplicates = Some(DuplicateFilter::new(capacity, false_positive));
+    }
+
+    /// Returns the number of duplicate samples if detection is enabled.
+
+    pub fn duplicates(&self) -> Option<u64> {
+        self.duplicates.as_ref().map(|filter| filter.duplicates())
+    }
+
+    /// Returns the fraction of samples that were duplicates if
+    /// detection is enabled.
+
+    pub fn duplicate_fraction(&self) -> Option<f64> {
+        self.duplicates.as_ref().map(|filter| filter.duplicate_fraction())
+    }
+
+    /// Enables a SketchHistogram with the given relative error to
+    /// provide accurate percentiles.  Any samples already recorded
+    /// are not included.  Instances created by summing, as in a Hier
+    /// instance, don't have a sketch.
+
+    pub fn enable_sketch(&mut self, relative_error: f64) {
+        self.sketch = Some(SketchHistogram::new(relative_error));
+    }
+
+    /// Returns the sketch, if one is enabled.
+
+    pub fn sketch(&self) -> Option<&SketchHistogram> {
+        self.sketch.as_ref()
+    }
+
+    /// Enables a BucketHistogram with user-specified edges, which is
+    /// printed after the usual histogram.  Any samples already recorded
+    /// are not included.  Instances created by summing don't have a
+    /// bucket histogram.
+
+    pub fn enable_buckets(&mut self, histogram: BucketHistogram) {
+        self.buckets = Some(histogram);
+    }
+
+    /// Returns the bucket histogram, if one is enabled.
+
+    pub fn buckets(&self) -> Option<&BucketHistogram> {
+        self.buckets.as_ref()
+    }
+
+    /// Enables a MedianEstimator, so that the median() and mad()
+    /// methods are supported.  Any samples already recorded are not
+    /// included.  The estimates can't be merged, so merge() leaves
+    /// them unchanged, and instances created by summing don't have
+    /// an estimator.
+
+    pub fn enable_median(&mut self) {
+        self.median = Some(MedianEstimator::new());
+    }
+
+    /// Keeps the tags of the "limit" largest samples recorded with
+    /// record_i64_tagged().  See the worst_tags module.  Any samples
+    /// already recorded are not included.  merge() combines the tags
+    /// if both instances keep them.
+
+    pub fn enable_worst_tags(&mut self, limit: usize) {
+        self.worst = Some(WorstTags::new(limit));
+    }
+
+    /// Enables a trimmed mean and an outlier count.  See the trim
+    /// module.  Any samples already recorded are not included.  merge()
+    /// combines the data if both instances keep it.
+
+    pub fn enable_trimming(&mut self, opts: TrimOpts) {
+        self.trim = Some(Trimmer::new(opts));
+    }
+
+    /// Prints the collection period and the sample rate, if a
+    /// collection clock is attached.  This function is used by
+    /// RunningTime, too.
+
+    pub fn print_collection_time(&self, printer: &mut dyn Printer) {
+        if let Some(collection) = &self.collection {
+            collection.print(self.count, printer);
+        }
+    }
+
+    // Offer a tag for a sample that already has been recorded.  This
+    // is used by RunningTime, too.
+
+    pub(crate) fn offer_tag(&mut self, sample: i64, tag: u64) {
+        if let Some(worst) = &mut self.worst {
+            worst.record(sample, tag);
+        }
+    }
+
     pub fn get_printable(&self) -> Printable {
         let n           = self.count;
         let nans        = 0;
@@ -324,9 +646,9 @@ impl RunningInteger {
         let max_i64     = self.max;
         let min_f64     = f64::MIN;
         let max_f64     = f64::MAX;
-        let log_mode    = self.histogram.borrow().log_mode() as i64;
+        let log_mode    = self.log_mode() as i64;
         let mode_value  = 0.0;
-        let mean        = self.mean;
+        let mean        = self.mean.value();
         let variance    = self.variance();
         let skewness    = self.skewness();
         let kurtosis    = self.kurtosis();
@@ -339,39 +661,78 @@ impl RunningInteger {
     }
 }
 
-// The formula for computing the second moment for the variance (moment_2)
-// is from D. E. Knuth, The Art of Computer Programming.
+/// Updates the running moments for a new sample.  The count includes
+/// the new sample.  IntegerShard uses this routine, as well, so that
+/// a shard and a RunningInteger instance compute the same values.
+///
+/// The moments are kept as compensated sums, so that the rounding
+/// errors don't accumulate over long runs.  The formula for computing
+/// the second moment for the variance (moment_2) is from D. E. Knuth,
+/// The Art of Computer Programming.
+
+pub(crate) fn update_moments(count: u64, sample: i64, mean: &mut CompensatedSum,
+        moment_2: &mut CompensatedSum, cubes: &mut CompensatedSum, moment_4: &mut CompensatedSum) {
+    let sample_f64 = sample as f64;
+
+    if count == 1 {
+        *mean     = CompensatedSum::new(sample_f64);
+        *moment_2 = CompensatedSum::new(0.0);
+        *cubes    = CompensatedSum::new(0.0);
+        *moment_4 = CompensatedSum::new(0.0);
+        return;
+    }
+
+    let distance_mean = sample_f64 - mean.value();
+
+    mean.add(distance_mean / count as f64);
+
+    let distance_new_mean = sample_f64 - mean.value();
+    let square_estimate   = (distance_mean * distance_new_mean).abs();
+
+    moment_2.add(square_estimate);
+    cubes   .add(sample_f64.powi(3));
+    moment_4.add(square_estimate * square_estimate);
+}
 
 impl Rustics for RunningInteger {
     fn record_i64(&mut self, sample: i64) {
+        call_hook(&self.hook, &self.name, HookSample::Integer(sample));
+
         self.count += 1;
 
-        self.histogram.borrow_mut().record(sample);
+        if let Some(histogram) = &self.histogram {
+            histogram.borrow_mut().record(sample);
+        }
+
+        if let Some(filter) = &mut self.duplicates {
+            filter.record(sample);
+        }
 
-        let sample_f64 = sample as f64;
+        if let Some(sketch) = &mut self.sketch {
+            sketch.record_i64(sample);
+        }
+
+        if let Some(buckets) = &mut self.buckets {
+            buckets.record_i64(sample);
+        }
+
+        if let Some(median) = &mut self.median {
+            median.record(sample as f64);
+        }
+
+        if let Some(trim) = &mut self.trim {
+            trim.record(sample as f64);
+        }
+
+        update_moments(self.count, sample, &mut self.mean, &mut self.moment_2,
+            &mut self.cubes, &mut self.moment_4);
 
         if self.count == 1 {
-            self.mean     = sample_f64;
-            self.moment_2 = 0.0;
-            self.cubes    = 0.0;
-            self.moment_4 = 0.0;
-            self.min      = sample;
-            self.max      = sample;
+            self.min = sample;
+            self.max = sample;
         } else {
-            let distance_mean     = sample_f64 - self.mean;
-            let new_mean          = self.mean + (distance_mean / self.count as f64);
-            let distance_new_mean = sample_f64 - new_mean;
-            let square_estimate   = (distance_mean * distance_new_mean).abs();
-            let new_moment_2      = self.moment_2 + square_estimate;
-            let new_cubes         = self.cubes + sample_f64.powi(3);
-            let new_moment_4      = self.moment_4 + square_estimate * square_estimate;
-
-            self.mean             = new_mean;
-            self.moment_2         = new_moment_2;
-            self.cubes            = new_cubes;
-            self.moment_4         = new_moment_4;
-            self.min              = min(self.min, sample);
-            self.max              = max(self.max, sample);
+            self.min = min(self.min, sample);
+            self.max = max(self.max, sample);
         }
     }
 
@@ -412,11 +773,14 @@ impl Rustics for RunningInteger {
     }
 
     fn log_mode(&self) -> isize {
-        self.histogram.borrow().log_mode()
+        match &self.histogram {
+            Some(histogram) => histogram.borrow().log_mode(),
+            None            => 0,
+        }
     }
 
     fn mean(&self) -> f64 {
-        self.mean
+        self.mean.value()
     }
 
     fn standard_deviation(&self) -> f64 {
@@ -424,23 +788,23 @@ impl Rustics for RunningInteger {
     }
 
     fn variance(&self) -> f64 {
-        compute_variance(self.count, self.moment_2)
+        compute_variance(self.count, self.moment_2.value())
     }
 
     fn skewness(&self) -> f64 {
         let n        = self.count as f64;
-        let mean     = self.mean;
-        let moment_2 = self.moment_2;
-        let cubes    = self.cubes;
+        let mean     = self.mean.value();
+        let moment_2 = self.moment_2.value();
+        let cubes    = self.cubes.value();
         let data     = EstimateData { n, mean, moment_2, cubes };
 
         let moment_3 = estimate_moment_3(data);
 
-        compute_skewness(self.count, self.moment_2, moment_3)
+        compute_skewness(self.count, moment_2, moment_3)
     }
 
     fn kurtosis(&self) -> f64 {
-        compute_kurtosis(self.count, self.moment_2, self.moment_4)
+        compute_kurtosis(self.count, self.moment_2.value(), self.moment_4.value())
     }
 
     fn precompute(&mut self) {
@@ -472,18 +836,133 @@ impl Rustics for RunningInteger {
 
     fn clear(&mut self) {
         self.count    = 0;
-        self.mean     = 0.0;
-        self.moment_2 = 0.0;
-        self.cubes    = 0.0;
-        self.moment_4 = 0.0;
+        self.mean     = CompensatedSum::new(0.0);
+        self.moment_2 = CompensatedSum::new(0.0);
+        self.cubes    = CompensatedSum::new(0.0);
+        self.moment_4 = CompensatedSum::new(0.0);
         self.min      = i64::MAX;
         self.max      = i64::MIN;
 
-        self.histogram.borrow_mut().clear();
+        if let Some(histogram) = &self.histogram {
+            histogram.borrow_mut().clear();
+        }
+
+        if let Some(filter) = &mut self.duplicates {
+            filter.clear();
+        }
+
+        if let Some(sketch) = &mut self.sketch {
+            sketch.clear();
+        }
+
+        if let Some(buckets) = &mut self.buckets {
+            buckets.clear();
+        }
+
+        if let Some(median) = &mut self.median {
+            median.clear();
+        }
+
+        if let Some(worst) = &mut self.worst {
+            worst.clear();
+        }
+
+        if let Some(trim) = &mut self.trim {
+            trim.clear();
+        }
+
+        if let Some(collection) = &mut self.collection {
+            collection.restart();
+        }
+    }
+
+    fn supports_reset_extremes(&self) -> bool {
+        true
+    }
+
+    fn reset_extremes(&mut self) {
+        self.min = i64::MAX;
+        self.max = i64::MIN;
+    }
+
+    fn has_median(&self) -> bool {
+        self.median.is_some()
+    }
+
+    fn median(&self) -> f64 {
+        match &self.median {
+            Some(median) => median.median(),
+            None         => panic!("RunningInteger::median:  not enabled"),
+        }
+    }
+
+    fn mad(&self) -> f64 {
+        match &self.median {
+            Some(median) => median.mad(),
+            None         => panic!("RunningInteger::mad:  not enabled"),
+        }
+    }
+
+    fn has_trimming(&self) -> bool {
+        self.trim.is_some()
+    }
+
+    fn trimmed_mean(&self) -> f64 {
+        match &self.trim {
+            Some(trim) => trim.trimmed_mean(),
+            None       => panic!("RunningInteger::trimmed_mean:  not enabled"),
+        }
+    }
+
+    fn outliers(&self) -> u64 {
+        match &self.trim {
+            Some(trim) => trim.outliers(self.mean(), self.standard_deviation()),
+            None       => panic!("RunningInteger::outliers:  not enabled"),
+        }
+    }
+
+    fn supports_record_hook(&self) -> bool {
+        true
+    }
+
+    fn set_record_hook(&mut self, hook: Option<RecordHookBox>) {
+        self.hook = hook;
+    }
+
+    fn record_hook(&self) -> Option<RecordHookBox> {
+        self.hook.clone()
+    }
+
+    fn supports_collection_time(&self) -> bool {
+        true
+    }
+
+    fn set_collection_clock(&mut self, clock: Option<TimerBox>) {
+        self.collection = clock.map(CollectionTime::new);
+    }
+
+    fn collection_seconds(&self) -> Option<f64> {
+        self.collection.as_ref().map(|collection| collection.seconds())
+    }
+
+    fn has_worst_tags(&self) -> bool {
+        self.worst.is_some()
+    }
+
+    fn record_i64_tagged(&mut self, sample: i64, tag: u64) {
+        self.record_i64(sample);
+        self.offer_tag(sample, tag);
+    }
+
+    fn worst_tags(&self) -> Vec<TaggedSample> {
+        match &self.worst {
+            Some(worst) => worst.tags(),
+            None        => panic!("RunningInteger::worst_tags:  not enabled"),
+        }
     }
 
     fn log_histogram(&self) -> Option<LogHistogramBox> {
-        Some(self.histogram.clone())
+        self.histogram.clone()
     }
 
     fn float_histogram(&self) -> Option<FloatHistogramBox> {
@@ -510,12 +989,49 @@ impl Rustics for RunningInteger {
             };
 
         let printable = self.get_printable();
+        let fields    = self.print_fields();
         let printer   = printer_mut!(printer_box);
 
         printer.print(title);
-        printable.print_common_i64(printer);
-        printable.print_common_float(printer);
-        self.histogram.borrow().print(printer);
+        printable.print_common_i64_fields  (fields, printer);
+        printable.print_common_float_fields(fields, printer);
+
+        if let Some(filter) = &self.duplicates {
+            Printable::print_integer("Duplicates",   filter.duplicates() as i64, printer);
+            Printable::print_float  ("Dup Fraction", filter.duplicate_fraction(), printer);
+        }
+
+        if let Some(median) = &self.median {
+            Printable::print_float("Median", median.median(), printer);
+            Printable::print_float("MAD",    median.mad(),    printer);
+        }
+
+        if let Some(trim) = &self.trim {
+            Printable::print_float  ("Trimmed Mean", trim.trimmed_mean(),     printer);
+            Printable::print_integer("Outliers",     self.outliers() as i64, printer);
+        }
+
+        self.print_collection_time(printer);
+
+        if self.histo_opts.print_histogram(self.fields) {
+            if let Some(histogram) = &self.histogram {
+                histogram.borrow().print_opts(printer, &self.histo_opts);
+            }
+
+            if let Some(sketch) = &self.sketch {
+                sketch.print(printer);
+            }
+
+            if let Some(buckets) = &self.buckets {
+                buckets.print(printer);
+            }
+        }
+
+        if self.histo_opts.print_cdf {
+            if let Some(histogram) = &self.histogram {
+                histogram.borrow().print_cdf(printer);
+            }
+        }
         printer.print("");
     }
 
@@ -523,6 +1039,14 @@ impl Rustics for RunningInteger {
         self.title = String::from(title)
     }
 
+    fn set_label(&mut self, key: &str, value: &str) {
+        self.labels.insert(key.to_string(), value.to_string());
+    }
+
+    fn labels(&self) -> Labels {
+        self.labels.clone()
+    }
+
     // For internal use
 
     fn equals(&self, other: &dyn Rustics) -> bool {
@@ -547,7 +1071,7 @@ impl Rustics for RunningInteger {
 
     fn export_stats(&self) -> ExportStats {
         let printable       = self.get_printable();
-        let log_histogram   = Some(self.histogram.clone());
+        let log_histogram   = self.histogram.clone();
         let float_histogram = None;
 
         ExportStats { printable, log_histogram, float_histogram }
@@ -556,15 +1080,19 @@ impl Rustics for RunningInteger {
 
 impl Histogram for RunningInteger {
     fn print_histogram(&self, printer: &mut dyn Printer) {
-        self.histogram.borrow().print(printer);
+        if let Some(histogram) = &self.histogram {
+            histogram.borrow().print_opts(printer, &self.histo_opts);
+        }
     }
 
     fn clear_histogram(&mut self) {
-        self.histogram.borrow_mut().clear();
+        if let Some(histogram) = &self.histogram {
+            histogram.borrow_mut().clear();
+        }
     }
 
     fn to_log_histogram(&self) -> Option<LogHistogramBox> {
-        Some(self.histogram.clone())
+        self.histogram.clone()
     }
 
     fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
@@ -575,6 +1103,7 @@ impl Histogram for RunningInteger {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::trim::TrimOpts;
     use crate::PrintOpts;
     use crate::stdout_printer;
     use crate::counter::Counter;
@@ -582,13 +1111,15 @@ mod tests {
     use crate::tests::continuing_box;
     use crate::tests::bytes;
     use crate::tests::check_printer_box;
+    use crate::sum::kbk_sum_sort;
+    use crate::running_float::RunningFloat;
 
     pub fn test_simple_stat() {
         let     printer    = None;
         let     title      = None;
         let     units      = bytes();
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 
         let     name       = "Test Statistics";
         let     id         = 42;
@@ -784,7 +1315,7 @@ mod tests {
         let     title      = None;
         let     units      = bytes();
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 
         let     name       = "Test Statistics";
         let mut stats      = RunningInteger::new(&name, &print_opts);
@@ -797,10 +1328,472 @@ mod tests {
         stats.print();
     }
 
+    fn test_duplicates() {
+        let mut stats = RunningInteger::new("Duplicate Test", &None);
+
+        assert!(stats.duplicates        ().is_none());
+        assert!(stats.duplicate_fraction().is_none());
+
+        stats.enable_duplicates(1000, 0.01);
+
+        for i in 0..100 {
+            stats.record_i64(i % 10);
+        }
+
+        assert!(stats.duplicates().unwrap() == 90);
+        assert!(stats.duplicate_fraction().unwrap() == 0.9);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.duplicates().unwrap() == 0);
+
+        stats.record_i64(1);
+        assert!(stats.duplicates().unwrap() == 0);
+    }
+
+    fn test_buckets() {
+        let mut stats = RunningInteger::new("Bucket Test", &None);
+
+        assert!(stats.buckets().is_none());
+
+        stats.enable_buckets(BucketHistogram::linear_i64(100, 10, 5));
+
+        for i in 0..200 {
+            stats.record_i64(100 + i % 50);
+        }
+
+        let buckets = stats.buckets().unwrap();
+
+        assert!(buckets.counts()  == [ 0, 40, 40, 40, 40, 40, 0 ]);
+        assert!(buckets.samples() == 200);
+
+        let mut other = RunningInteger::new("Bucket Other", &None);
+
+        other.enable_buckets(BucketHistogram::linear_i64(100, 10, 5));
+        other.record_i64(1000);
+
+        stats.merge(&other);
+
+        assert!(stats.buckets().unwrap().counts()[6] == 1);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.buckets().unwrap().samples() == 0);
+    }
+
+    fn test_sketch() {
+        let mut stats = RunningInteger::new("Sketch Test", &None);
+
+        assert!(stats.sketch().is_none());
+
+        stats.enable_sketch(0.01);
+
+        for i in 1..=10_000 {
+            stats.record_i64(i);
+        }
+
+        let sketch = stats.sketch().unwrap();
+        let p99    = sketch.percentile(99.0);
+
+        assert!(sketch.samples() == 10_000);
+        assert!((p99 - 9900.0).abs() <= 99.0);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.sketch().unwrap().samples() == 0);
+    }
+
+    // Check that merging two instances matches recording all the
+    // samples into one instance.
+
+    fn test_merge() {
+        let mut first    = RunningInteger::new("Merge First",    &None);
+        let mut second   = RunningInteger::new("Merge Second",   &None);
+        let mut expected = RunningInteger::new("Merge Expected", &None);
+
+        first .enable_sketch(0.01);
+        second.enable_sketch(0.01);
+
+        for i in -50..=200 {
+            let sample = i * 3;
+
+            if i < 100 {
+                first.record_i64(sample);
+            } else {
+                second.record_i64(sample);
+            }
+
+            expected.record_i64(sample);
+        }
+
+        // Merging an empty instance changes nothing.
+
+        let empty = RunningInteger::new("Merge Empty", &None);
+
+        first.merge(&empty);
+        assert!(first.count() == 150);
+
+        // A handle to the histogram taken before the merge sees the
+        // merged data.
+
+        let handle = first.log_histogram().unwrap();
+
+        first.merge(&second);
+
+        assert!(handle.borrow().positive == expected.log_histogram().unwrap().borrow().positive);
+        assert!(handle.borrow().negative == expected.log_histogram().unwrap().borrow().negative);
+
+        assert!(first.count()   == expected.count()  );
+        assert!(first.min_i64() == expected.min_i64());
+        assert!(first.max_i64() == expected.max_i64());
+
+        assert!((first.mean    () - expected.mean    ()).abs() <= 1.0e-9 * expected.mean    ());
+        assert!((first.variance() - expected.variance()).abs() <= 1.0e-9 * expected.variance());
+
+        // The third moment is estimated, so the skewness and kurtosis
+        // can differ slightly.
+
+        assert!((first.skewness() - expected.skewness()).abs() <= 0.02);
+        assert!((first.kurtosis() - expected.kurtosis()).abs() <= 0.05);
+
+        let merged    = first.to_log_histogram().unwrap();
+        let reference = expected.to_log_histogram().unwrap();
+
+        assert!(merged.borrow().positive == reference.borrow().positive);
+        assert!(merged.borrow().negative == reference.borrow().negative);
+
+        assert!(first.sketch().unwrap().samples() == 251);
+
+        // The merged instance keeps recording normally.
+
+        first.record_i64(-1000);
+
+        assert!(first.count()   == 252);
+        assert!(first.min_i64() == -1000);
+    }
+
+    fn test_checkpoint() {
+        let mut stats = RunningInteger::new("Checkpoint Test", &None);
+
+        stats.set_title("Checkpoint Title");
+
+        for i in -100..=1000 {
+            stats.record_i64(i * 3);
+        }
+
+        let checkpoint = stats.checkpoint();
+        let restored   = RunningInteger::restore(&checkpoint, &None).unwrap();
+
+        assert!(restored.name()     == stats.name()    );
+        assert!(restored.title()    == stats.title()   );
+        assert!(restored.count()    == stats.count()   );
+        assert!(restored.mean()     == stats.mean()    );
+        assert!(restored.variance() == stats.variance());
+        assert!(restored.skewness() == stats.skewness());
+        assert!(restored.kurtosis() == stats.kurtosis());
+        assert!(restored.min_i64()  == stats.min_i64() );
+        assert!(restored.max_i64()  == stats.max_i64() );
+
+        let restored_histogram = restored.histogram.as_ref().unwrap().borrow();
+        let stats_histogram    = stats   .histogram.as_ref().unwrap().borrow();
+
+        assert!(restored_histogram.equals(&stats_histogram));
+
+        // Check checkpoints of the wrong type and missing fields.
+
+        let counter = RunningFloat::new("Float", &None).checkpoint();
+        let missing = checkpoint.replace("moment_2", "moment_x");
+
+        assert!(RunningInteger::restore(&counter, &None).is_none());
+        assert!(RunningInteger::restore(&missing, &None).is_none());
+    }
+
+    fn test_median() {
+        let mut stats = RunningInteger::new("Median Test", &None);
+
+        assert!(!stats.has_median());
+
+        stats.enable_median();
+
+        // Record 1 through 1001 in a scrambled order, so the median is
+        // 501, and the median absolute deviation is 250.
+
+        for i in 0..1001 {
+            stats.record_i64((i * 367) % 1001 + 1);
+        }
+
+        assert!(stats.has_median());
+        assert!((stats.median() - 501.0).abs() < 10.0);
+        assert!((stats.mad()    - 250.0).abs() < 10.0);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.median() == 0.0);
+    }
+
+    fn test_no_histogram() {
+        let expected =
+            [
+                "No Histogram",
+                "    Count               1,000 ",
+                "    Minimum                 1 byte",
+                "    Maximum             1,000 bytes",
+                "    Mean             +5.00500 e+2  bytes",
+                "    Std Dev          +2.88819 e+2  bytes",
+                "    Variance         +8.34166 e+4  ",
+                "    Skewness         -4.16317 e-11 ",
+                "    Kurtosis         -1.19999 e+0  ",
+                ""
+            ];
+
+        let     printer    = Some(check_printer_box(&expected, true, false));
+        let     title      = None;
+        let     units      = bytes();
+        let     histo_opts = None;
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
+        let mut stats      = RunningInteger::new("No Histogram", &print_opts);
+
+        assert!(stats.has_histogram());
+
+        stats.disable_histogram();
+
+        for i in 1..=1000 {
+            stats.record_i64(i);
+        }
+
+        assert!(!stats.has_histogram());
+        assert!(stats.log_histogram   ().is_none());
+        assert!(stats.to_log_histogram().is_none());
+        assert!(stats.export_stats    ().log_histogram.is_none());
+        assert!(stats.log_mode() == 0);
+
+        stats.print();
+
+        // Merging keeps the histogram disabled.
+
+        let mut other = RunningInteger::new("Other", &None);
+
+        other.record_i64(2000);
+        stats.merge(&other);
+
+        assert!(stats.count()   == 1001);
+        assert!(stats.max_i64() == 2000);
+        assert!(!stats.has_histogram());
+
+        // So does a checkpoint.
+
+        let restored = RunningInteger::restore(&stats.checkpoint(), &None).unwrap();
+
+        assert!(restored.count() == 1001);
+        assert!(!restored.has_histogram());
+
+        stats.clear_histogram();
+        stats.clear();
+
+        assert!(stats.count() == 0);
+    }
+
+    fn test_reset_extremes() {
+        let mut stats = RunningInteger::new("Reset Extremes", &None);
+
+        stats.record_i64(1_000_000);
+
+        for i in 1..=10 {
+            stats.record_i64(i);
+        }
+
+        let mean = stats.mean();
+
+        assert!(stats.max_i64() == 1_000_000);
+
+        // The count and moments survive, but the extremes restart.
+
+        stats.reset_extremes();
+
+        assert!(stats.count() == 11);
+        assert!(stats.mean()  == mean);
+
+        stats.record_i64(5);
+        stats.record_i64(7);
+
+        assert!(stats.count()   == 13);
+        assert!(stats.min_i64() == 5);
+        assert!(stats.max_i64() == 7);
+    }
+
+    fn test_worst_tags() {
+        let mut stats = RunningInteger::new("Worst Tags", &None);
+
+        assert!(!stats.has_worst_tags());
+
+        // Tags are ignored until they are enabled.
+
+        stats.record_i64_tagged(1000, 1);
+        stats.enable_worst_tags(2);
+
+        for i in 1..=100 {
+            stats.record_i64_tagged(i * 7 % 101, 1000 + i as u64);
+        }
+
+        let worst = stats.worst_tags();
+
+        assert!(stats.has_worst_tags());
+        assert!(stats.count()   == 101 );
+        assert!(stats.max_i64() == 1000);
+        assert!(worst.len()     == 2   );
+        assert!(worst[0].sample == 100 );
+        assert!(worst[0].tag    == 1000 + 72);
+        assert!(worst[1].sample == 99  );
+
+        // Merging combines the tags.
+
+        let mut other = RunningInteger::new("Other", &None);
+
+        other.enable_worst_tags(1);
+        other.record_i64_tagged(500, 7);
+        stats.merge(&other);
+
+        assert!(stats.worst_tags()[0].tag == 7);
+        assert!(stats.worst_tags()[1].tag == 1000 + 72);
+
+        stats.clear();
+
+        assert!(stats.worst_tags().is_empty());
+    }
+
+    fn test_clone() {
+        let mut stats = RunningInteger::new("Clone Test", &None);
+
+        stats.set_label("shard", "1");
+
+        for i in 1..=10 {
+            stats.record_i64(i);
+        }
+
+        let mut copy = stats.clone();
+
+        assert!(copy.count()   == 10);
+        assert!(copy.title()   == stats.title());
+        assert!(copy.labels()  == stats.labels());
+
+        // The copy has its own histogram.
+
+        copy.record_i64(1000);
+
+        assert!(stats.count()   == 10);
+        assert!(stats.max_i64() == 10);
+        assert!(copy .max_i64() == 1000);
+
+        let original = stats.log_histogram().unwrap();
+        let copied   = copy .log_histogram().unwrap();
+
+        assert!(!Rc::ptr_eq(&original, &copied));
+        assert!(!original.borrow().equals(&copied.borrow()));
+    }
+
+    // Record one large sample followed by many small ones.  A naive
+    // running mean loses the small updates to rounding, but the
+    // compensated sums keep the mean and variance accurate.
+
+    fn test_precision() {
+        let mut stats = RunningInteger::new("Precision", &None);
+        let     large = 1_000_000_000;
+        let     count = 1_000_000;
+
+        stats.disable_histogram();
+        stats.record_i64(large);
+
+        for i in 0..count {
+            stats.record_i64(i % 2);
+        }
+
+        let n    = (count + 1) as f64;
+        let mean = (large as f64 + (count / 2) as f64) / n;
+
+        println!("test_precision:  mean {}, expected {}", stats.mean(), mean);
+        assert!(stats.mean() == mean);
+
+        // Compute the variance directly.
+
+        let mut squares = vec![ (large as f64 - mean).powi(2) ];
+
+        for i in 0..count {
+            squares.push(((i % 2) as f64 - mean).powi(2));
+        }
+
+        let variance = kbk_sum_sort(&mut squares) / (n - 1.0);
+
+        assert!(((stats.variance() - variance) / variance).abs() < 1.0e-14);
+    }
+
+    fn test_trimming() {
+        let mut stats = RunningInteger::new("Trimming", &None);
+
+        assert!(!stats.has_trimming());
+
+        stats.enable_trimming(TrimOpts::default());
+
+        for i in 1..=99 {
+            stats.record_i64(i);
+        }
+
+        stats.record_i64(1_000_000);
+
+        // The huge sample is trimmed, and it's the only outlier.
+
+        let trimmed = stats.trimmed_mean();
+
+        println!("test_trimming:  mean {}, trimmed {}", stats.mean(), trimmed);
+
+        assert!(stats.has_trimming());
+        assert!(stats.mean() > 10_000.0);
+        assert!((trimmed - 50.0).abs() < 50.0 * 0.01);
+        assert!(stats.outliers() == 1);
+
+        // Merging combines the samples.
+
+        let mut other = stats.clone();
+
+        other.clear();
+        other.record_i64(1_000_000);
+        stats.merge(&other);
+
+        assert!(stats.outliers() == 2);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.trimmed_mean() == 0.0);
+    }
+
     #[test]
     fn run_tests() {
-        test_simple_stat ();
-        test_equality    ();
-        test_print_output();
+        test_simple_stat   ();
+        test_equality      ();
+        test_print_output  ();
+        test_duplicates    ();
+        test_sketch        ();
+        test_buckets       ();
+        test_merge         ();
+        test_checkpoint    ();
+        test_median        ();
+        test_no_histogram  ();
+        test_reset_extremes();
+        test_worst_tags    ();
+        test_clone         ();
+        test_precision     ();
+        test_trimming      ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_no_median() {
+        let stats = RunningInteger::new("No Median", &None);
+
+        let _ = stats.median();
     }
 }
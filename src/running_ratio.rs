@@ -0,0 +1,611 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * RunningRatio
+//!     * RunningRatio records a stream of boolean outcomes, like cache
+//!       hits and misses or request successes and errors, and reports
+//!       the success ratio.
+//!
+//!     * The record_success() and record_failure() methods record one
+//!       outcome.  Via the Rustics interface, record_i64() records a
+//!       success for any non-zero sample and a failure for zero, so an
+//!       instance in a set can be driven through a RusticsArc or
+//!       RusticsRc.
+//!
+//!     * The print output includes a confidence interval for the ratio
+//!       computed using the Wilson score method, which behaves well for
+//!       small sample counts and for ratios near 0 or 1.  The default
+//!       z value of 1.96 gives a 95% interval.
+//!
+//!     * count() returns the number of outcomes, and mean() returns the
+//!       success ratio.  The variance is that of the 0/1 samples.  The
+//!       other summary statistics don't apply and panic if invoked.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::running_ratio::RunningRatio;
+//!
+//!     let mut hits = RunningRatio::new("Cache Hits", &None);
+//!
+//!     // Record 90 hits and 10 misses.
+//!
+//!     for i in 0..100 {
+//!         if i % 10 == 0 {
+//!             hits.record_failure();
+//!         } else {
+//!             hits.record_success();
+//!         }
+//!     }
+//!
+//!     assert!(hits.count()     == 100);
+//!     assert!(hits.successes() == 90 );
+//!     assert!(hits.ratio()     == Some(0.9));
+//!
+//!     // The 95% confidence interval contains the observed ratio.
+//!
+//!     let (low, high) = hits.wilson_interval().unwrap();
+//!
+//!     assert!(low < 0.9 && 0.9 < high);
+//!
+//!     hits.print();
+//!```
+
+use std::any::Any;
+
+use super::Rustics;
+use super::Histogram;
+use super::Printer;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use super::ExportStats;
+use super::PrinterBox;
+use super::PrinterOption;
+use super::PrintOption;
+use super::Units;
+use super::TimerBox;
+use super::printable::Printable;
+use super::parse_print_opts;
+use super::printer_mut;
+
+/// RunningRatio counts successes and failures and reports the
+/// success ratio.
+
+#[derive(Clone)]
+pub struct RunningRatio {
+    name:       String,
+    title:      String,
+    id:         usize,
+    successes:  u64,
+    failures:   u64,
+    z:          f64,
+    printer:    PrinterBox,
+}
+
+impl RunningRatio {
+    /// Constructs an instance with the given name and print options.
+
+    pub fn new(name: &str, print_opts: &PrintOption) -> RunningRatio {
+        let (printer, title, _units, _histo_opts) = parse_print_opts(print_opts, name);
+
+        let name      = String::from(name);
+        let id        = usize::MAX;
+        let successes = 0;
+        let failures  = 0;
+        let z         = 1.96;
+
+        RunningRatio { name, title, id, successes, failures, z, printer }
+    }
+
+    /// Records one successful outcome.
+
+    pub fn record_success(&mut self) {
+        self.successes += 1;
+    }
+
+    /// Records one failed outcome.
+
+    pub fn record_failure(&mut self) {
+        self.failures += 1;
+    }
+
+    /// Records one outcome.
+
+    pub fn record_bool(&mut self, success: bool) {
+        if success {
+            self.record_success();
+        } else {
+            self.record_failure();
+        }
+    }
+
+    /// Records a number of successes and failures at once.
+
+    pub fn record_counts(&mut self, successes: u64, failures: u64) {
+        self.successes += successes;
+        self.failures  += failures;
+    }
+
+    /// Returns the number of successes.
+
+    pub fn successes(&self) -> u64 {
+        self.successes
+    }
+
+    /// Returns the number of failures.
+
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    /// Returns the fraction of the outcomes that were successes, or
+    /// None if nothing has been recorded.
+
+    pub fn ratio(&self) -> Option<f64> {
+        let total = self.successes + self.failures;
+
+        if total == 0 {
+            return None;
+        }
+
+        Some(self.successes as f64 / total as f64)
+    }
+
+    /// Sets the z value used for the confidence interval.  For
+    /// example, 1.96 gives a 95% interval, and 2.576 gives a 99%
+    /// interval.
+
+    pub fn set_z(&mut self, z: f64) {
+        if !z.is_finite() || z <= 0.0 {
+            panic!("RunningRatio::set_z:  The z value must be positive.");
+        }
+
+        self.z = z;
+    }
+
+    /// Returns the z value used for the confidence interval.
+
+    pub fn z(&self) -> f64 {
+        self.z
+    }
+
+    /// Returns the Wilson score interval for the success ratio as a
+    /// (low, high) pair, or None if nothing has been recorded.
+
+    pub fn wilson_interval(&self) -> Option<(f64, f64)> {
+        let p      = self.ratio()?;
+        let n      = (self.successes + self.failures) as f64;
+        let z2     = self.z * self.z;
+        let scale  = 1.0 + z2 / n;
+        let center = (p + z2 / (2.0 * n)) / scale;
+        let margin = self.z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / scale;
+
+        let low  = (center - margin).max(0.0);
+        let high = (center + margin).min(1.0);
+
+        Some((low, high))
+    }
+
+    /// Merges the counts from another instance into this one.
+
+    pub fn merge(&mut self, other: &RunningRatio) {
+        self.successes += other.successes;
+        self.failures  += other.failures;
+    }
+
+    fn variance_value(&self) -> f64 {
+        if let Some(p) = self.ratio() {
+            p * (1.0 - p)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Rustics for RunningRatio {
+    /// Records a success for a non-zero sample and a failure for zero.
+
+    fn record_i64(&mut self, sample: i64) {
+        self.record_bool(sample != 0);
+    }
+
+    fn record_f64(&mut self, _sample: f64) {
+        panic!("RunningRatio::record_f64:  not supported");
+    }
+
+    fn record_event(&mut self) {
+        panic!("RunningRatio::record_event:  not supported");
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        panic!("RunningRatio::record_event_report:  not supported");
+    }
+
+    fn record_time(&mut self, _sample: i64) {
+        panic!("RunningRatio::record_time:  not supported");
+    }
+
+    fn record_interval(&mut self, _timer: &mut TimerBox) {
+        panic!("RunningRatio::record_interval:  not supported");
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn class(&self) -> &str {
+        "ratio"
+    }
+
+    /// Returns the number of outcomes recorded.
+
+    fn count(&self) -> u64 {
+        self.successes + self.failures
+    }
+
+    fn log_mode(&self) -> isize {
+        panic!("RunningRatio::log_mode:  not supported");
+    }
+
+    /// Returns the success ratio, or zero if nothing has been
+    /// recorded.
+
+    fn mean(&self) -> f64 {
+        self.ratio().unwrap_or(0.0)
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        self.variance_value().sqrt()
+    }
+
+    fn variance(&self) -> f64 {
+        self.variance_value()
+    }
+
+    fn skewness(&self) -> f64 {
+        panic!("RunningRatio::skewness:  not supported");
+    }
+
+    fn kurtosis(&self) -> f64 {
+        panic!("RunningRatio::kurtosis:  not supported");
+    }
+
+    fn int_extremes(&self) -> bool {
+        false
+    }
+
+    fn float_extremes(&self) -> bool {
+        false
+    }
+
+    fn min_i64(&self) -> i64 {
+        panic!("RunningRatio::min_i64:  not supported");
+    }
+
+    fn min_f64(&self) -> f64 {
+        panic!("RunningRatio::min_f64:  not supported");
+    }
+
+    fn max_i64(&self) -> i64 {
+        panic!("RunningRatio::max_i64:  not supported");
+    }
+
+    fn max_f64(&self) -> f64 {
+        panic!("RunningRatio::max_f64:  not supported");
+    }
+
+    fn precompute(&mut self) {
+    }
+
+    fn clear(&mut self) {
+        self.successes = 0;
+        self.failures  = 0;
+    }
+
+    fn print(&self) {
+        self.print_opts(None, None);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        let printer = printer_mut!(printer_box);
+
+        printer.print(title);
+        Printable::print_integer("Count",     self.count()     as i64, printer);
+        Printable::print_integer("Successes", self.successes   as i64, printer);
+        Printable::print_integer("Failures",  self.failures    as i64, printer);
+
+        if let Some(ratio) = self.ratio() {
+            let (low, high) = self.wilson_interval().unwrap();
+
+            Printable::print_float("Ratio",      ratio, printer);
+            Printable::print_float("Ratio Low",  low,   printer);
+            Printable::print_float("Ratio High", high,  printer);
+        }
+
+        printer.print("");
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = String::from(title);
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+
+    // For internal use only.
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<RunningRatio>(other.generic()) {
+            std::ptr::eq(self, other)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    // The samples are treated as 0 or 1 values, so the mean is the
+    // ratio.
+
+    fn export_stats(&self) -> ExportStats {
+        let n          = self.count();
+        let nans       = 0;
+        let infinities = 0;
+        let min_i64    = i64::MIN;
+        let max_i64    = i64::MAX;
+        let min_f64    = f64::MIN;
+        let max_f64    = f64::MAX;
+        let log_mode   = 0;
+        let mode_value = 0.0;
+        let mean       = self.mean();
+        let variance   = self.variance_value();
+        let skewness   = 0.0;
+        let kurtosis   = 0.0;
+        let units      = Units::empty();
+
+        let printable =
+            Printable {
+                n,           nans,      infinities,  min_i64,   max_i64,   min_f64,
+                max_f64,     log_mode,  mean,        variance,  skewness,  kurtosis,
+                mode_value,  units
+            };
+
+        let log_histogram   = None;
+        let float_histogram = None;
+
+        ExportStats { printable, log_histogram, float_histogram }
+    }
+}
+
+// A RunningRatio instance has no histogram, so these are null
+// operations.
+
+impl Histogram for RunningRatio {
+    fn print_histogram(&self, _printer: &mut dyn Printer) {
+    }
+
+    fn clear_histogram(&mut self) {
+    }
+
+    fn to_log_histogram(&self) -> Option<LogHistogramBox> {
+        None
+    }
+
+    fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrintOpts;
+    use crate::counter::Counter;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_count_match;
+
+    fn test_ratio() {
+        let mut ratio = RunningRatio::new("Test Ratio", &None);
+
+        assert!(ratio.class()           == "ratio");
+        assert!(ratio.count()           == 0      );
+        assert!(ratio.mean()            == 0.0    );
+        assert!(ratio.ratio()          .is_none() );
+        assert!(ratio.wilson_interval().is_none() );
+
+        for i in 0..40 {
+            ratio.record_i64(i % 4);
+        }
+
+        ratio.record_bool(true);
+        ratio.record_counts(9, 10);
+
+        assert!(ratio.count()     == 60  );
+        assert!(ratio.successes() == 40  );
+        assert!(ratio.failures()  == 20  );
+        assert!((ratio.variance() - 2.0 / 9.0).abs() < 1.0e-12);
+
+        let mut other = ratio.clone();
+
+        other.merge(&ratio);
+
+        assert!(other.successes() == 80 );
+        assert!(other.failures()  == 40 );
+
+        other.clear();
+
+        assert!(other.count() == 0);
+
+        // Check the Wilson interval against a known value:  for 81
+        // successes in 263 trials, the 95% interval is about
+        // (0.2553, 0.3662).
+
+        let mut ratio = RunningRatio::new("Wilson", &None);
+
+        ratio.record_counts(81, 182);
+
+        let (low, high) = ratio.wilson_interval().unwrap();
+
+        assert!((low  - 0.2553).abs() < 0.0001);
+        assert!((high - 0.3662).abs() < 0.0001);
+
+        // A wider z gives a wider interval.
+
+        ratio.set_z(2.576);
+
+        let (wide_low, wide_high) = ratio.wilson_interval().unwrap();
+
+        assert!(ratio.z()  == 2.576);
+        assert!(wide_low   <  low  );
+        assert!(wide_high  >  high );
+
+        // The interval stays within [0, 1] at the extremes.
+
+        let mut all = RunningRatio::new("All", &None);
+
+        all.record_counts(5, 0);
+
+        let (low, high) = all.wilson_interval().unwrap();
+
+        assert!(low > 0.5 && high == 1.0);
+    }
+
+    fn test_rustics() {
+        let mut ratio = RunningRatio::new("Rustics", &None);
+
+        ratio.record_success();
+        ratio.record_failure();
+        ratio.precompute();
+        ratio.set_title("New Title");
+        ratio.set_id(42);
+
+        assert!(ratio.title() == "New Title");
+        assert!(ratio.name () == "Rustics"  );
+        assert!(ratio.id   () == 42         );
+
+        assert!(!ratio.int_extremes  ());
+        assert!(!ratio.float_extremes());
+
+        assert!(ratio.log_histogram  ().is_none());
+        assert!(ratio.float_histogram().is_none());
+
+        let export = ratio.export_stats();
+
+        assert!(export.printable.n    == 2  );
+        assert!(export.printable.mean == 0.5);
+        assert!(ratio.standard_deviation() == 0.5);
+
+        let counter = Counter::new("Counter", &None);
+
+        assert!( ratio.equals(&ratio  ));
+        assert!(!ratio.equals(&counter));
+    }
+
+    fn test_print_output() {
+        let expected =
+            [
+                "Test Statistics",
+                "    Count                 100 ",
+                "    Successes              75 ",
+                "    Failures               25 ",
+                "    Ratio            +7.50000 e-1  ",
+                "    Ratio Low        +6.56953 e-1  ",
+                "    Ratio High       +8.24549 e-1  ",
+                ""
+            ];
+
+        let printer    = Some(check_printer_box(&expected, true, false));
+        let title      = None;
+        let units      = None;
+        let histo_opts = None;
+        let print_opts = Some(PrintOpts { printer: printer.clone(), title, units, histo_opts, ..Default::default() });
+
+        let mut ratio = RunningRatio::new("Test Statistics", &print_opts);
+
+        ratio.record_counts(75, 25);
+        ratio.print();
+
+        assert!(check_printer_count_match(printer.unwrap()));
+
+        // An empty instance prints only the counts.
+
+        let expected =
+            [
+                "Empty",
+                "    Count                   0 ",
+                "    Successes               0 ",
+                "    Failures                0 ",
+                ""
+            ];
+
+        let printer = check_printer_box(&expected, true, false);
+        let ratio   = RunningRatio::new("Empty", &None);
+
+        ratio.print_opts(Some(printer.clone()), None);
+
+        assert!(check_printer_count_match(printer));
+    }
+
+    #[test]
+    fn run_tests() {
+        test_ratio       ();
+        test_rustics     ();
+        test_print_output();
+    }
+
+    #[test]
+    #[should_panic]
+    fn record_f64_panic_test() {
+        let mut ratio = RunningRatio::new("test", &None);
+
+        ratio.record_f64(1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bad_z_panic_test() {
+        let mut ratio = RunningRatio::new("test", &None);
+
+        ratio.set_z(0.0);
+    }
+}
@@ -106,6 +106,7 @@ use std::any::Any;
 
 use super::Rustics;
 use super::Units;
+use super::HistoOpts;
 use super::Histogram;
 use super::Printer;
 use super::ExportStats;
@@ -115,12 +116,29 @@ use super::PrintOption;
 use super::LogHistogramBox;
 use super::FloatHistogramBox;
 use super::parse_print_opts;
+use super::parse_fields;
 use super::TimerBox;
 use super::printer_mut;
 use super::timer_mut;
 use super::timer_box_hz;
+use super::time::default_timer;
 use super::running_integer::RunningInteger;
+use super::log_linear_histogram::LogLinearHistogram;
+use super::log_histogram::LogHistogram;
+use super::merge::sum_log_histogram;
 use super::merge::Export;
+use super::Labels;
+use super::worst_tags::TaggedSample;
+use super::trim::TrimOpts;
+use super::record_hook::RecordHookBox;
+use super::record_hook::HookSample;
+use super::record_hook::call_hook;
+use super::printable::Printable;
+use super::printable::TimeUnit;
+use super::printable::PrintField;
+use super::printable::PrintFields;
+use super::checkpoint::Checkpoint;
+use super::checkpoint::CheckpointReader;
 
 /// A RunningTime instance accumulates statistics on a stream
 /// of integer data samples representing time intervals.
@@ -136,6 +154,12 @@ pub struct RunningTime {
     hz:                 i64,
 
     printer:            PrinterBox,
+    histo_opts:         HistoOpts,
+    fields:             PrintFields,
+    secondary_unit:     Option<TimeUnit>,
+    log_linear:         Option<LogLinearHistogram>,
+    nanoseconds:        Option<LogHistogram>,
+    hook:               Option<RecordHookBox>,
 }
 
 impl RunningTime {
@@ -144,7 +168,7 @@ impl RunningTime {
     pub fn new(name: &str, timer: TimerBox, print_opts: &PrintOption) -> RunningTime {
         let hz = timer_box_hz(&timer);
 
-        let (printer, _title, _units, _histo_opts) = parse_print_opts(print_opts, name);
+        let (printer, _title, _units, histo_opts) = parse_print_opts(print_opts, name);
 
         if hz > i64::MAX as u128 {
             panic!("Rustics::RunningTime:  The timer hz value is too large.");
@@ -152,8 +176,23 @@ impl RunningTime {
 
         let hz              = hz as i64;
         let running_integer = Box::new(RunningInteger::new(name, print_opts));
+        let secondary_unit  = None;
+        let log_linear      = None;
+        let nanoseconds     = None;
+        let hook            = None;
+        let fields          = parse_fields(print_opts);
+
+        RunningTime {
+            printer,         running_integer,  timer,       hz,  histo_opts,
+            secondary_unit,  log_linear,       nanoseconds, hook, fields
+        }
+    }
+
+    /// Creates a new RunningTime instance using a timer from the
+    /// process-wide factory.  See time::default_timer().
 
-        RunningTime { printer, running_integer, timer, hz }
+    pub fn new_default(name: &str, print_opts: &PrintOption) -> RunningTime {
+        RunningTime::new(name, default_timer(), print_opts)
     }
 
     /// Creates a RunningTime instance from a RunningInteger.  This function
@@ -161,15 +200,23 @@ impl RunningTime {
 
     pub fn from_integer(timer: TimerBox, print_opts: &PrintOption, mut running: RunningInteger)
             -> RunningTime {
-        let (printer, title, _units, _histo_opts) = parse_print_opts(print_opts, &running.name());
+        let (printer, title, _units, histo_opts) = parse_print_opts(print_opts, &running.name());
 
         running.set_title(&title);
         running.set_units(Units::empty());
 
         let hz              = timer_box_hz(&timer) as i64;
         let running_integer = Box::new(running);
-
-        RunningTime { running_integer, timer, hz, printer }
+        let secondary_unit  = None;
+        let log_linear      = None;
+        let nanoseconds     = None;
+        let hook            = None;
+        let fields          = parse_fields(print_opts);
+
+        RunningTime {
+            running_integer,  timer,       hz,          printer,  histo_opts,
+            secondary_unit,   log_linear,  nanoseconds, hook,  fields
+        }
     }
 
     /// Exports the statistics for this instance.
@@ -177,6 +224,166 @@ impl RunningTime {
     pub fn export(&self) -> Export {
         self.running_integer.export_data()
     }
+
+    /// Sets a fixed secondary unit for printing.  When set, the mean,
+    /// standard deviation, and extremes are also printed in this unit
+    /// so that instances with different automatically-chosen units can
+    /// be compared directly.
+
+    pub fn set_secondary_unit(&mut self, unit: Option<TimeUnit>) {
+        self.secondary_unit = unit;
+    }
+
+    /// Returns the secondary unit for printing, if any.
+
+    pub fn secondary_unit(&self) -> Option<TimeUnit> {
+        self.secondary_unit
+    }
+
+    /// Enables a LogLinearHistogram that splits each pseudo-log bucket
+    /// into the given number of linear sub-buckets.  It is printed
+    /// after the usual histogram.  Any samples already recorded are
+    /// not included.
+
+    pub fn enable_log_linear(&mut self, sub_buckets: usize) {
+        self.log_linear = Some(LogLinearHistogram::new(sub_buckets));
+    }
+
+    /// Returns the log-linear histogram, if one is enabled.
+
+    pub fn log_linear(&self) -> Option<&LogLinearHistogram> {
+        self.log_linear.as_ref()
+    }
+
+    /// Enables a second pseudo-log histogram that buckets the samples
+    /// after converting them from timer ticks to nanoseconds.  The
+    /// usual histogram buckets ticks, so its shape depends on the timer
+    /// frequency, while this one can be compared across machines with
+    /// different timers.  It is printed after the usual histogram.  Any
+    /// samples already recorded are not included.
+
+    pub fn enable_nanosecond_histogram(&mut self) {
+        self.nanoseconds = Some(LogHistogram::new());
+    }
+
+    /// Keeps the tags of the "limit" largest times recorded with
+    /// record_time_tagged().  See the worst_tags module.
+
+    pub fn enable_worst_tags(&mut self, limit: usize) {
+        self.running_integer.enable_worst_tags(limit);
+    }
+
+    /// Enables the median() and mad() estimates, in ticks.  See the
+    /// median module.
+
+    pub fn enable_median(&mut self) {
+        self.running_integer.enable_median();
+    }
+
+    /// Enables a trimmed mean and an outlier count.  See the trim
+    /// module.  The trimmed mean is printed as a time.
+
+    pub fn enable_trimming(&mut self, opts: TrimOpts) {
+        self.running_integer.enable_trimming(opts);
+    }
+
+    /// Returns the nanosecond histogram, if one is enabled.
+
+    pub fn nanosecond_histogram(&self) -> Option<&LogHistogram> {
+        self.nanoseconds.as_ref()
+    }
+
+    /// Disables the pseudo-log histogram.  See
+    /// RunningInteger::disable_histogram().  A log-linear histogram
+    /// still can be enabled.
+
+    pub fn disable_histogram(&mut self) {
+        self.running_integer.disable_histogram();
+    }
+
+    /// Returns whether the pseudo-log histogram is kept.
+
+    pub fn has_histogram(&self) -> bool {
+        self.running_integer.has_histogram()
+    }
+
+    // Record a sample into the statistics and any optional histograms.
+
+    fn record_sample(&mut self, sample: i64) {
+        call_hook(&self.hook, &self.running_integer.name(), HookSample::Time(sample));
+
+        self.running_integer.record_i64(sample);
+
+        if let Some(log_linear) = &mut self.log_linear {
+            log_linear.record(sample);
+        }
+
+        if let Some(nanoseconds) = &mut self.nanoseconds {
+            let sample = sample as i128 * 1_000_000_000 / self.hz as i128;
+
+            nanoseconds.record(sample.min(i64::MAX as i128) as i64);
+        }
+    }
+
+    /// Returns the frequency of the timer for this instance.
+
+    pub fn hz(&self) -> i64 {
+        self.hz
+    }
+
+    /// Saves the statistics as a string so that they can be restored
+    /// later.  See the checkpoint module.
+
+    pub fn checkpoint(&self) -> String {
+        let mut checkpoint = Checkpoint::new("running_time");
+
+        checkpoint.push_str("name",  &self.running_integer.name() );
+        checkpoint.push_str("title", &self.running_integer.title());
+        checkpoint.push_i64("hz",    self.hz                      );
+        checkpoint.push_export(&self.export());
+        checkpoint.text()
+    }
+
+    /// Restores an instance saved by checkpoint().  The timer must have
+    /// the same frequency as the one used by the saved instance.  The
+    /// result is None if the checkpoint doesn't match.
+
+    pub fn restore(text: &str, timer: TimerBox, print_opts: &PrintOption) -> Option<RunningTime> {
+        let reader = CheckpointReader::new_kind(text, "running_time")?;
+        let name   = reader.string("name" )?;
+        let title  = reader.string("title")?;
+        let hz     = reader.i64("hz")?;
+        let export = reader.export(print_opts)?;
+
+        if hz as u128 != timer_box_hz(&timer) || export.float_histogram.is_some() {
+            return None;
+        }
+
+        let     running = RunningInteger::new_from_exporter(&name, &title, print_opts, export);
+        let mut result  = RunningTime::from_integer(timer, print_opts, running);
+
+        result.set_title(&title);
+        Some(result)
+    }
+
+    /// Merges the statistics from another instance into this one.  The
+    /// timers must have the same frequency.
+
+    pub fn merge(&mut self, other: &RunningTime) {
+        if self.hz != other.hz {
+            panic!("RunningTime::merge:  The timer frequencies don't match.");
+        }
+
+        self.running_integer.merge(&other.running_integer);
+
+        if let (Some(log_linear), Some(addend)) = (&mut self.log_linear, &other.log_linear) {
+            log_linear.merge(addend);
+        }
+
+        if let (Some(nanoseconds), Some(addend)) = (&mut self.nanoseconds, &other.nanoseconds) {
+            sum_log_histogram(nanoseconds, addend);
+        }
+    }
 }
 
 impl Rustics for RunningTime {
@@ -193,23 +400,84 @@ impl Rustics for RunningTime {
     }
 
     fn record_event_report(&mut self) -> i64 {
-        let timer    = timer_mut!(*self.timer);
-        let interval = timer.finish();  // read and restart the timer
+        let interval = timer_mut!(*self.timer).finish();  // read and restart the timer
 
-        self.running_integer.record_i64(interval);
+        self.record_sample(interval);
         interval
     }
 
     fn record_time(&mut self, sample: i64) {
         assert!(sample >= 0);
-        self.running_integer.record_i64(sample);
+        self.record_sample(sample);
     }
 
     fn record_interval(&mut self, timer: &mut TimerBox) {
         let timer    = timer_mut!(*timer);
         let interval = timer.finish();
 
-        self.running_integer.record_i64(interval);
+        self.record_sample(interval);
+    }
+
+    fn record_time_tagged(&mut self, sample: i64, tag: u64) {
+        assert!(sample >= 0);
+        self.record_sample(sample);
+        self.running_integer.offer_tag(sample, tag);
+    }
+
+    fn supports_record_hook(&self) -> bool {
+        true
+    }
+
+    fn set_record_hook(&mut self, hook: Option<RecordHookBox>) {
+        self.hook = hook;
+    }
+
+    fn record_hook(&self) -> Option<RecordHookBox> {
+        self.hook.clone()
+    }
+
+    fn supports_collection_time(&self) -> bool {
+        true
+    }
+
+    fn set_collection_clock(&mut self, clock: Option<TimerBox>) {
+        self.running_integer.set_collection_clock(clock);
+    }
+
+    fn collection_seconds(&self) -> Option<f64> {
+        self.running_integer.collection_seconds()
+    }
+
+    fn has_median(&self) -> bool {
+        self.running_integer.has_median()
+    }
+
+    fn median(&self) -> f64 {
+        self.running_integer.median()
+    }
+
+    fn mad(&self) -> f64 {
+        self.running_integer.mad()
+    }
+
+    fn has_trimming(&self) -> bool {
+        self.running_integer.has_trimming()
+    }
+
+    fn trimmed_mean(&self) -> f64 {
+        self.running_integer.trimmed_mean()
+    }
+
+    fn outliers(&self) -> u64 {
+        self.running_integer.outliers()
+    }
+
+    fn has_worst_tags(&self) -> bool {
+        self.running_integer.has_worst_tags()
+    }
+
+    fn worst_tags(&self) -> Vec<TaggedSample> {
+        self.running_integer.worst_tags()
     }
 
     fn name(&self) -> String {
@@ -281,7 +549,23 @@ impl Rustics for RunningTime {
     }
 
     fn clear(&mut self) {
-        self.running_integer.clear()
+        self.running_integer.clear();
+
+        if let Some(log_linear) = &mut self.log_linear {
+            log_linear.clear();
+        }
+
+        if let Some(nanoseconds) = &mut self.nanoseconds {
+            nanoseconds.clear();
+        }
+    }
+
+    fn supports_reset_extremes(&self) -> bool {
+        true
+    }
+
+    fn reset_extremes(&mut self) {
+        self.running_integer.reset_extremes();
     }
 
     // Functions for printing
@@ -308,10 +592,42 @@ impl Rustics for RunningTime {
         let printable = self.running_integer.get_printable();
         let printer   = printer_mut!(printer_box);
 
+        let fields =
+            if self.has_histogram() {
+                self.fields
+            } else {
+                self.fields.without(PrintField::LogMode).without(PrintField::ModeValue)
+            };
+
         printer.print(title);
-        printable.print_common_integer_times(self.hz, printer);
-        printable.print_common_float_times(self.hz, printer);
-        self.running_integer.print_histogram(printer);
+        printable.print_common_integer_times_fields(self.hz, self.secondary_unit, fields, printer);
+        printable.print_common_float_times_fields  (self.hz, self.secondary_unit, fields, printer);
+
+        if self.has_trimming() {
+            Printable::print_time   ("Trimmed Mean", self.trimmed_mean(),   self.hz, printer);
+            Printable::print_integer("Outliers",     self.outliers() as i64,          printer);
+        }
+
+        self.running_integer.print_collection_time(printer);
+
+        if self.histo_opts.print_histogram(self.fields) {
+            self.running_integer.print_histogram(printer);
+
+            if let Some(log_linear) = &self.log_linear {
+                log_linear.print_times(self.hz, printer);
+            }
+
+            if let Some(nanoseconds) = &self.nanoseconds {
+                printer.print("  Nanosecond Histogram");
+                nanoseconds.print_opts(printer, &self.histo_opts);
+            }
+        }
+
+        if self.histo_opts.print_cdf {
+            if let Some(histogram) = self.running_integer.to_log_histogram() {
+                histogram.borrow().print_cdf_times(self.hz, printer);
+            }
+        }
         printer.print("");
     }
 
@@ -319,6 +635,14 @@ impl Rustics for RunningTime {
         self.running_integer.set_title(title);
     }
 
+    fn set_label(&mut self, key: &str, value: &str) {
+        self.running_integer.set_label(key, value);
+    }
+
+    fn labels(&self) -> Labels {
+        self.running_integer.labels()
+    }
+
     fn log_histogram(&self) -> Option<LogHistogramBox> {
         self.running_integer.log_histogram()
     }
@@ -379,8 +703,11 @@ pub mod tests {
     use crate::stdout_printer;
     use crate::timer_box;
     use crate::tests::continuing_box;
+    use crate::tests::ContinuingTimer;
     use crate::tests::compute_sum;
     use crate::tests::check_printer_box;
+    use crate::printable::PrintField;
+    use crate::printable::PrintFields;
     use crate::hier::HierMember;
     use crate::counter::Counter;
     use crate::time::Timer;
@@ -590,7 +917,7 @@ pub mod tests {
                 "    Mean              500.500 microseconds",
                 "    Std Dev           288.819 microseconds",
                 "    Variance         +8.34166 e+10 ",
-                "    Skewness         -4.16336 e-11 ",
+                "    Skewness         -4.16309 e-11 ",
                 "    Kurtosis         -1.19999 e+0  ",
                 "  Log Histogram",
                 "  -----------------------",
@@ -608,7 +935,7 @@ pub mod tests {
         let     title      = None;
         let     units      = None;
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 
         let     name       = "Test Statistics";
         let mut stats      = RunningTime::new(&name, timer, &print_opts);
@@ -621,12 +948,276 @@ pub mod tests {
         stats.print();
     }
 
+    fn test_merge() {
+        let mut first    = RunningTime::new("Merge First",    continuing_box(), &None);
+        let mut second   = RunningTime::new("Merge Second",   continuing_box(), &None);
+        let mut expected = RunningTime::new("Merge Expected", continuing_box(), &None);
+
+        for i in 1..=100 {
+            if i <= 30 {
+                first.record_time(i);
+            } else {
+                second.record_time(i);
+            }
+
+            expected.record_time(i);
+        }
+
+        first.merge(&second);
+
+        assert!(first.count()   == expected.count());
+        assert!(first.min_i64() == 1                );
+        assert!(first.max_i64() == 100              );
+        assert!(first.mean()    == expected.mean()  );
+
+        assert!((first.variance() - expected.variance()).abs() <= 1.0e-9 * expected.variance());
+    }
+
+    fn test_checkpoint() {
+        let mut stats = RunningTime::new("Checkpoint Test", continuing_box(), &None);
+
+        for i in 1..=100 {
+            stats.record_time(i * 1_000);
+        }
+
+        let checkpoint = stats.checkpoint();
+        let restored   = RunningTime::restore(&checkpoint, continuing_box(), &None).unwrap();
+
+        assert!(restored.title()    == stats.title()   );
+        assert!(restored.count()    == stats.count()   );
+        assert!(restored.mean()     == stats.mean()    );
+        assert!(restored.variance() == stats.variance());
+        assert!(restored.max_i64()  == stats.max_i64() );
+
+        let timer = timer_box!(ContinuingTimer::new(1000));
+
+        assert!(RunningTime::restore(&checkpoint, timer, &None).is_none());
+    }
+
+    fn test_log_linear() {
+        let mut first  = RunningTime::new("Log-Linear First",  continuing_box(), &None);
+        let mut second = RunningTime::new("Log-Linear Second", continuing_box(), &None);
+
+        assert!(first.log_linear().is_none());
+
+        first.enable_log_linear(8);
+        second.enable_log_linear(8);
+
+        // Record latencies from about 1 ms to 2 ms.
+
+        for i in 0..1000 {
+            first.record_time(1_048_577 + i * 1000);
+        }
+
+        second.record_time(1_048_577);
+
+        let log_linear = first.log_linear().unwrap();
+        let used       = log_linear.counts().iter().filter(|count| **count > 0).count();
+
+        assert!(log_linear.samples()     == 1000);
+        assert!(log_linear.sub_buckets() == 8   );
+        assert!(used == 8);
+
+        first.print();
+        first.merge(&second);
+
+        assert!(first.log_linear().unwrap().samples() == 1001);
+
+        first.clear();
+
+        assert!(first.log_linear().unwrap().samples() == 0);
+    }
+
+    fn test_nanosecond_histogram() {
+        let     slow_timer = timer_box!(ContinuingTimer::new(1_000_000_000));
+        let     fast_timer = timer_box!(ContinuingTimer::new(3_000_000_000));
+        let mut slow       = RunningTime::new("Slow Timer", slow_timer, &None);
+        let mut fast       = RunningTime::new("Fast Timer", fast_timer, &None);
+
+        assert!(slow.nanosecond_histogram().is_none());
+
+        slow.enable_nanosecond_histogram();
+        fast.enable_nanosecond_histogram();
+
+        // Record the same latencies, from 1 us to 1 ms, with each timer.
+
+        for i in 1..=1000 {
+            slow.record_time(i * 1000);
+            fast.record_time(i * 3000);
+        }
+
+        // The tick histograms differ, but the nanosecond histograms
+        // match.
+
+        let slow_ticks = slow.log_histogram().unwrap();
+        let fast_ticks = fast.log_histogram().unwrap();
+
+        assert!(!slow_ticks.borrow().equals(&fast_ticks.borrow()));
+
+        let slow_nanoseconds = slow.nanosecond_histogram().unwrap();
+        let fast_nanoseconds = fast.nanosecond_histogram().unwrap();
+
+        assert!(slow_nanoseconds.equals(fast_nanoseconds));
+        assert!(slow_nanoseconds.equals(&slow_ticks.borrow()));
+        assert!(fast_nanoseconds.samples() == 1000);
+
+        fast.print();
+
+        let other = fast.clone();
+
+        fast.merge(&other);
+        assert!(fast.nanosecond_histogram().unwrap().samples() == 2000);
+
+        fast.clear();
+        assert!(fast.nanosecond_histogram().unwrap().samples() == 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_merge_hz() {
+        let     timer  = timer_box!(ContinuingTimer::new(1000));
+        let mut first  = RunningTime::new("Merge First",  continuing_box(), &None);
+        let     second = RunningTime::new("Merge Second", timer,            &None);
+
+        first.merge(&second);
+    }
+
+    fn test_secondary_unit() {
+        let expected =
+            [
+                "Test Statistics",
+                "    Count               1,000 ",
+                "    Minimum             1.000 microsecond  (0.001 milliseconds)",
+                "    Maximum             1.000 millisecond  (1.000 millisecond)",
+                "    Log Mode               20 ",
+                "    Mode Value        786.432 microseconds",
+                "    Mean              500.500 microseconds  (0.500 milliseconds)",
+                "    Std Dev           288.819 microseconds  (0.289 milliseconds)",
+                "    Variance         +8.34166 e+10 ",
+                "    Skewness         -4.16309 e-11 ",
+                "    Kurtosis         -1.19999 e+0  ",
+                ""
+            ];
+
+        let     timer      = continuing_box();
+        let     printer    = Some(check_printer_box(&expected, true, false));
+        let     title      = None;
+        let     units      = None;
+        let     histo_opts = Some(HistoOpts { no_histogram: true, ..Default::default() });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
+
+        let     name       = "Test Statistics";
+        let     samples    = 1000;
+        let mut stats      = RunningTime::new(name, timer, &print_opts);
+
+        assert!(stats.secondary_unit().is_none());
+
+        stats.set_secondary_unit(Some(TimeUnit::Millisecond));
+
+        assert!(stats.secondary_unit() == Some(TimeUnit::Millisecond));
+
+        for _i in 1..=samples {
+            stats.record_event();
+        }
+
+        stats.print();
+    }
+
+    fn test_print_fields() {
+        let expected =
+            [
+                "Field Test",
+                "    Count               1,000 ",
+                "    Maximum             1.000 millisecond",
+                "    Mean              500.500 microseconds",
+                ""
+            ];
+
+        let fields     = [ PrintField::Count, PrintField::Maximum, PrintField::Mean ];
+        let fields     = PrintFields::only(&fields);
+        let printer    = check_printer_box(&expected, true, false);
+        let print_opts = PrintOpts::builder().printer(printer).fields(fields).build();
+        let mut stats  = RunningTime::new("Field Test", continuing_box(), &Some(print_opts));
+
+        for _i in 1..=1000 {
+            stats.record_event();
+        }
+
+        stats.print();
+    }
+
+    fn test_no_histogram() {
+        let expected =
+            [
+                "No Histogram",
+                "    Count               1,000 ",
+                "    Minimum             1.000 microsecond",
+                "    Maximum             1.000 millisecond",
+                "    Mean              500.500 microseconds",
+                "    Std Dev           288.819 microseconds",
+                "    Variance         +8.34166 e+10 ",
+                "    Skewness         -4.16309 e-11 ",
+                "    Kurtosis         -1.19999 e+0  ",
+                ""
+            ];
+
+        let     printer    = Some(check_printer_box(&expected, true, false));
+        let     print_opts = Some(PrintOpts { printer, ..Default::default() });
+        let mut stats      = RunningTime::new("No Histogram", continuing_box(), &print_opts);
+
+        stats.disable_histogram();
+
+        for _i in 1..=1000 {
+            stats.record_event();
+        }
+
+        assert!(!stats.has_histogram());
+        assert!(stats.log_histogram().is_none());
+
+        stats.print();
+
+        let restored = RunningTime::restore(&stats.checkpoint(), continuing_box(), &None).unwrap();
+
+        assert!(!restored.has_histogram());
+    }
+
+    fn test_median() {
+        let mut stats = RunningTime::new("Median", continuing_box(), &None);
+
+        assert!(!stats.has_median());
+
+        stats.enable_median();
+
+        for i in 1..=101 {
+            stats.record_time(i * 1000);
+        }
+
+        assert!(stats.has_median());
+        assert!((stats.median() - 51_000.0).abs() < 2_000.0);
+        assert!(stats.mad() > 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_median_disabled() {
+        let stats = RunningTime::new("Median", continuing_box(), &None);
+        let _     = stats.median();
+    }
+
     #[test]
     fn run_tests() {
-        simple_test      ();
-        test_equality    ();
-        test_histogram   ();
-        test_large_timer ();
-        test_print_output();
+        simple_test              ();
+        test_equality            ();
+        test_histogram           ();
+        test_large_timer         ();
+        test_print_output        ();
+        test_merge               ();
+        test_secondary_unit      ();
+        test_checkpoint          ();
+        test_log_linear          ();
+        test_print_fields        ();
+        test_no_histogram        ();
+        test_nanosecond_histogram();
+        test_median              ();
     }
 }
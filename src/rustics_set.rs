@@ -0,0 +1,298 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * RusticsSet
+//!     * RusticsSet is the interface shared by RcSet and ArcSet.  Each
+//!       set type supplies a few primitives to visit its members and
+//!       subsets, and the trait builds the features that only walk the
+//!       hierarchy, like snapshots, aggregates, CSV export, and lookup
+//!       by path, on top of those primitives.  A feature added here
+//!       exists in both set types.
+//!
+//!     * The visitors are given shared references.  An ArcSet holds the
+//!       lock for each member or subset while the visitor runs, so the
+//!       visitor must not try to lock that item again.
+//!
+//!     * report() builds a structured Report of the whole hierarchy,
+//!       which can be rendered as text, JSON, CSV, or Prometheus output.
+//!
+//!     * Code that works on either set type can take a `&dyn RusticsSet`.
+//!       The Gate type and the prometheus module do this.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::rc_item_mut;
+//!     use rustics::arc_item_mut;
+//!     use rustics::rc_sets::RcSet;
+//!     use rustics::arc_sets::ArcSet;
+//!     use rustics::rustics_set::RusticsSet;
+//!
+//!     // Sum the counts of every member of any kind of set.
+//!
+//!     fn total_count(set: &dyn RusticsSet) -> u64 {
+//!         let mut total = 0;
+//!
+//!         set.visit_all(&mut |member| total += member.count());
+//!         total
+//!     }
+//!
+//!     let mut rc_set  = RcSet ::new("Rc Set",  1, 0, &None);
+//!     let mut arc_set = ArcSet::new("Arc Set", 1, 0, &None);
+//!
+//!     let rc_counter  = rc_set .add_counter("Requests", None);
+//!     let arc_counter = arc_set.add_counter("Requests", None);
+//!
+//!     rc_item_mut! (rc_counter ).record_i64(3);
+//!     arc_item_mut!(arc_counter).record_i64(5);
+//!
+//!     assert!(total_count(&rc_set ) == 3);
+//!     assert!(total_count(&arc_set) == 5);
+//!
+//!     // The same lookup works on both types.
+//!
+//!     let mut found = 0;
+//!
+//!     assert!(arc_set.visit_path("Requests", &mut |member| found = member.count()));
+//!     assert!(found == 5);
+//!```
+
+use super::Rustics;
+use super::PrinterBox;
+use super::PrinterOption;
+use super::printer_mut;
+use super::printable::Printable;
+use super::printable::CsvColumn;
+use super::aggregate::Aggregate;
+use super::snapshot::SetSnapshot;
+use super::report::Report;
+use super::report::SetNode;
+use super::report::MemberNode;
+
+/// RusticsSet defines the operations common to all the set types.
+/// Implementations provide the required methods, and the rest are
+/// built from them.
+
+pub trait RusticsSet {
+    /// Returns the name of the set.
+
+    fn name(&self) -> String;
+
+    /// Returns the current title of the set.
+
+    fn title(&self) -> String;
+
+    /// Returns the default printer for the set.
+
+    fn printer(&self) -> PrinterBox;
+
+    /// Invokes the visitor on each Rustics instance in this set, but
+    /// not on the members of subsets.
+
+    fn visit_members(&self, visitor: &mut dyn FnMut(&dyn Rustics));
+
+    /// Invokes the visitor on each direct subset of this set.
+
+    fn visit_subsets(&self, visitor: &mut dyn FnMut(&dyn RusticsSet));
+
+    /// Invokes the visitor on each Rustics instance in the set and
+    /// its subsets.  The members of a set are visited before its
+    /// subsets.
+
+    fn visit_all(&self, visitor: &mut dyn FnMut(&dyn Rustics)) {
+        self.visit_members(visitor);
+        self.visit_subsets(&mut |subset| subset.visit_all(visitor));
+    }
+
+    /// Finds a member given a path of names relative to this set, for
+    /// example "Network ==> Query Latency", and invokes the visitor on
+    /// it.  Each name but the last selects a subset, and the last name
+    /// selects a Rustics instance.  If there are duplicate names, the
+    /// first match is used.  Returns false if no member was found.
+
+    fn visit_path(&self, path: &str, visitor: &mut dyn FnMut(&dyn Rustics)) -> bool {
+        let mut found = false;
+
+        match path.split_once("==>") {
+            Some((subset, rest)) => {
+                let subset = subset.trim();
+
+                self.visit_subsets(&mut |set| {
+                    if !found && set.name() == subset {
+                        found = set.visit_path(rest, visitor);
+                    }
+                });
+            }
+
+            None => {
+                let name = path.trim();
+
+                self.visit_members(&mut |member| {
+                    if !found && member.name() == name {
+                        visitor(member);
+                        found = true;
+                    }
+                });
+            }
+        }
+
+        found
+    }
+
+    /// Returns the number of Rustics instances in the set and its
+    /// subsets.
+
+    fn member_count(&self) -> usize {
+        let mut count = 0;
+
+        self.visit_all(&mut |_| count += 1);
+        count
+    }
+
+    /// Returns an owned copy of the state of every member of the set
+    /// and its subsets.  See the snapshot module for diff().
+
+    fn snapshot(&self) -> SetSnapshot {
+        let mut snapshot = SetSnapshot::new();
+
+        self.visit_all(&mut |member| snapshot.push(member));
+        snapshot
+    }
+
+    /// Builds a structured report of the set and its subsets.  See the
+    /// report module for the renderers.
+
+    fn report(&self) -> Report {
+        Report { root: self.report_node() }
+    }
+
+    /// Builds the report node for this set.  This is used by report()
+    /// to build the tree for the subsets.
+
+    fn report_node(&self) -> SetNode {
+        let     name    = self.name();
+        let     title   = self.title();
+        let mut members = Vec::new();
+        let mut subsets = Vec::new();
+
+        self.visit_members(&mut |member| members.push(MemberNode::from_rustics(member)));
+        self.visit_subsets(&mut |subset| subsets.push(subset.report_node()));
+
+        SetNode { name, title, members, subsets }
+    }
+
+    /// Adds all the members of the set and its subsets to the given
+    /// Aggregate instance.
+
+    fn aggregate(&self, aggregate: &mut Aggregate) {
+        self.visit_all(&mut |member| aggregate.add(member));
+    }
+
+    /// Returns the set in CSV form, one line per Rustics instance in
+    /// the set and its subsets.  The first line is the header.  Use
+    /// CsvColumn::Title to get the hierarchical title as a key.
+
+    fn export_csv(&self, columns: &[CsvColumn]) -> Vec<String> {
+        let mut rows = vec![ Printable::csv_header(columns) ];
+
+        self.visit_all(&mut |member| rows.push(Printable::csv_row(member, columns)));
+        rows
+    }
+
+    /// Prints the set in CSV form.  See export_csv().
+
+    fn print_csv(&self, printer: PrinterOption, columns: &[CsvColumn]) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer()
+            };
+
+        let printer = printer_mut!(printer_box);
+
+        for row in self.export_csv(columns).iter() {
+            printer.print(row);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rc_item_mut;
+    use crate::arc_item_mut;
+    use crate::rc_sets::RcSet;
+    use crate::arc_sets::ArcSet;
+
+    // The tests build the same hierarchy in each set type, then
+    // check the trait methods on it.
+
+    fn check_set(set: &dyn RusticsSet) {
+        assert!(set.member_count() == 3);
+
+        let mut names = Vec::new();
+
+        set.visit_all(&mut |member| names.push(member.name()));
+        assert!(names == [ "Top Counter", "Middle Counter", "Bottom Counter" ]);
+
+        let mut count = 0;
+
+        assert!(set.visit_path("Middle ==> Bottom ==> Bottom Counter", &mut |member| count = member.count()));
+        assert!(count == 3);
+
+        assert!( set.visit_path(" Middle ==>  Middle Counter ", &mut |_| { }));
+        assert!(!set.visit_path("Middle ==> Top Counter",       &mut |_| { }));
+        assert!(!set.visit_path("Missing ==> Bottom Counter",   &mut |_| { }));
+
+        let snapshot = set.snapshot();
+
+        assert!(snapshot.members.len() == 3);
+
+        let rows = set.export_csv(&[ CsvColumn::Title, CsvColumn::Count ]);
+
+        assert!(rows.len() == 4);
+    }
+
+    fn test_rc_set() {
+        let mut set    = RcSet::new("Top", 1, 1, &None);
+        let     top    = set.add_counter("Top Counter", None);
+        let     middle = set.add_subset("Middle", 1, 1);
+        let     bottom = rc_item_mut!(middle).add_subset("Bottom", 1, 0);
+        let     count  = rc_item_mut!(middle).add_counter("Middle Counter", None);
+        let     lowest = rc_item_mut!(bottom).add_counter("Bottom Counter", None);
+
+        rc_item_mut!(top   ).record_i64(1);
+        rc_item_mut!(count ).record_i64(2);
+        rc_item_mut!(lowest).record_i64(3);
+
+        check_set(&set);
+    }
+
+    fn test_arc_set() {
+        let mut set    = ArcSet::new("Top", 1, 1, &None);
+        let     top    = set.add_counter("Top Counter", None);
+        let     middle = set.add_subset("Middle", 1, 1);
+        let     bottom = arc_item_mut!(middle).add_subset("Bottom", 1, 0);
+        let     count  = arc_item_mut!(middle).add_counter("Middle Counter", None);
+        let     lowest = arc_item_mut!(bottom).add_counter("Bottom Counter", None);
+
+        arc_item_mut!(top   ).record_i64(1);
+        arc_item_mut!(count ).record_i64(2);
+        arc_item_mut!(lowest).record_i64(3);
+
+        check_set(&set);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_rc_set ();
+        test_arc_set();
+    }
+}
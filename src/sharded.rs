@@ -0,0 +1,487 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Types
+//!
+//! * ShardedInteger
+//!     * ShardedInteger collects integer samples from many threads with
+//!       little lock contention.  It keeps a striped array of shards, each
+//!       protected by its own mutex, and each thread records into the
+//!       shard chosen for it on its first use.
+//!
+//!     * Recording takes a shared reference, so an instance can be shared
+//!       among threads using a plain Arc.
+//!
+//!     * The shards are merged on demand using the same summation code as
+//!       the Hier implementation.  to_running() returns a RunningInteger
+//!       instance holding the merged statistics, which then can be printed
+//!       or queried like any other Rustics instance.
+//!
+//! * ShardedTime
+//!     * ShardedTime provides the same function for time samples.  The
+//!       samples are recorded in ticks, and to_running() takes a TimerBox
+//!       that gives the frequency for printing.
+//!
+//! ## Example
+//!```
+//!     use std::sync::Arc;
+//!     use std::thread;
+//!     use rustics::Rustics;
+//!     use rustics::sharded::ShardedInteger;
+//!
+//!     // Create an instance with 8 shards, and share it among 4 threads.
+//!
+//!     let     sizes   = Arc::new(ShardedInteger::new("Request Sizes", 8));
+//!     let mut threads = Vec::new();
+//!
+//!     for _i in 0..4 {
+//!         let sizes = sizes.clone();
+//!
+//!         let thread =
+//!             thread::spawn(move || {
+//!                 for sample in 1..=1000 {
+//!                     sizes.record_i64(sample);
+//!                 }
+//!             });
+//!
+//!         threads.push(thread);
+//!     }
+//!
+//!     for thread in threads {
+//!         thread.join().unwrap();
+//!     }
+//!
+//!     // Merge the shards and check the results.
+//!
+//!     let merged = sizes.to_running(&None);
+//!
+//!     assert!(sizes.count()    == 4000);
+//!     assert!(merged.count()   == 4000);
+//!     assert!(merged.mean()    == 500.5);
+//!     assert!(merged.min_i64() == 1);
+//!     assert!(merged.max_i64() == 1000);
+//!
+//!     merged.print();
+//!```
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::cmp::max;
+use std::cmp::min;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use super::PrintOption;
+use super::TimerBox;
+use super::merge::Export;
+use super::merge::sum_running;
+use super::log_histogram::LogHistogram;
+use super::running_integer::RunningInteger;
+use super::running_integer::update_moments;
+use super::sum::CompensatedSum;
+use super::running_time::RunningTime;
+
+// Each thread is given a hint when it first records into any
+// sharded instance.  The hint selects the shard to use.
+
+static NEXT_HINT: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static SHARD_HINT: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+fn shard_hint() -> usize {
+    SHARD_HINT.with(|hint| {
+        if let Some(hint) = hint.get() {
+            hint
+        } else {
+            let next = NEXT_HINT.fetch_add(1, Ordering::Relaxed);
+
+            hint.set(Some(next));
+            next
+        }
+    })
+}
+
+/// IntegerShard holds the running statistics for one shard.  It keeps
+/// the same data as RunningInteger, and updates it with the same
+/// routine, but contains no shared pointers, so that it can be passed
+/// between threads.
+
+#[derive(Clone)]
+pub struct IntegerShard {
+    count:      u64,
+    mean:       CompensatedSum,
+    moment_2:   CompensatedSum,
+    cubes:      CompensatedSum,
+    moment_4:   CompensatedSum,
+    min:        i64,
+    max:        i64,
+    histogram:  LogHistogram,
+}
+
+impl Default for IntegerShard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntegerShard {
+    pub fn new() -> IntegerShard {
+        let count     = 0;
+        let mean      = CompensatedSum::new(0.0);
+        let moment_2  = CompensatedSum::new(0.0);
+        let cubes     = CompensatedSum::new(0.0);
+        let moment_4  = CompensatedSum::new(0.0);
+        let min       = i64::MAX;
+        let max       = i64::MIN;
+        let histogram = LogHistogram::new();
+
+        IntegerShard { count, mean, moment_2, cubes, moment_4, min, max, histogram }
+    }
+
+    /// Records a sample into the shard.
+
+    pub fn record(&mut self, sample: i64) {
+        self.count += 1;
+
+        self.histogram.record(sample);
+
+        update_moments(self.count, sample, &mut self.mean, &mut self.moment_2,
+            &mut self.cubes, &mut self.moment_4);
+
+        if self.count == 1 {
+            self.min = sample;
+            self.max = sample;
+        } else {
+            self.min = min(self.min, sample);
+            self.max = max(self.max, sample);
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn clear(&mut self) {
+        *self = IntegerShard::new();
+    }
+
+    /// Exports the shard data for summation.
+
+    pub fn export_data(&self) -> Export {
+        let count           = self.count;
+        let nans            = 0;
+        let infinities      = 0;
+        let mean            = self.mean.value();
+        let moment_2        = self.moment_2.value();
+        let cubes           = self.cubes.value();
+        let moment_4        = self.moment_4.value();
+        let log_histogram   = Some(Rc::from(RefCell::new(self.histogram.clone())));
+        let float_histogram = None;
+        let min_i64         = self.min;
+        let max_i64         = self.max;
+        let min_f64         = 0.0;
+        let max_f64         = 0.0;
+
+        Export {
+            count,           nans,        infinities,
+            mean,            moment_2,    cubes,
+            moment_4,        min_i64,     max_i64,
+            min_f64,         max_f64,     log_histogram,
+            float_histogram
+        }
+    }
+}
+
+/// ShardedInteger is a striped array of IntegerShard instances
+/// that can be shared by many threads.
+
+pub struct ShardedInteger {
+    name:   String,
+    shards: Vec<Mutex<IntegerShard>>,
+}
+
+impl ShardedInteger {
+    /// Creates a new instance with the given number of shards.  Using
+    /// at least as many shards as recording threads keeps contention
+    /// low.
+
+    pub fn new(name: &str, shards: usize) -> ShardedInteger {
+        if shards == 0 {
+            panic!("ShardedInteger::new:  The shard count must be positive.");
+        }
+
+        let name   = name.to_string();
+        let shards = (0..shards).map(|_| Mutex::new(IntegerShard::new())).collect();
+
+        ShardedInteger { name, shards }
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Returns the number of shards.
+
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Records a sample into the shard for the current thread.
+
+    pub fn record_i64(&self, sample: i64) {
+        let index = shard_hint() % self.shards.len();
+
+        self.shards[index].lock().unwrap().record(sample);
+    }
+
+    /// Returns the total number of samples in all the shards.
+
+    pub fn count(&self) -> u64 {
+        self.shards.iter().map(|shard| shard.lock().unwrap().count()).sum()
+    }
+
+    /// Clears all the shards.
+
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.lock().unwrap().clear();
+        }
+    }
+
+    /// Returns the exported data of all shards that contain
+    /// samples.
+
+    pub fn export_shards(&self) -> Vec<Export> {
+        let mut exports = Vec::with_capacity(self.shards.len());
+
+        for shard in self.shards.iter() {
+            let shard = shard.lock().unwrap();
+
+            if shard.count() > 0 {
+                exports.push(shard.export_data());
+            }
+        }
+
+        exports
+    }
+
+    /// Merges the shards into a RunningInteger instance.  The shards
+    /// are locked one at a time, so samples being recorded concurrently
+    /// might or might not be included.
+
+    pub fn to_running(&self, print_opts: &PrintOption) -> RunningInteger {
+        let exports = self.export_shards();
+
+        if exports.is_empty() {
+            return RunningInteger::new(&self.name, print_opts);
+        }
+
+        let sum = sum_running(&exports);
+
+        RunningInteger::new_from_exporter(&self.name, &self.name, print_opts, sum)
+    }
+}
+
+/// ShardedTime is the time version of ShardedInteger.
+
+pub struct ShardedTime {
+    sharded: ShardedInteger,
+}
+
+impl ShardedTime {
+    /// Creates a new instance with the given number of shards.
+
+    pub fn new(name: &str, shards: usize) -> ShardedTime {
+        let sharded = ShardedInteger::new(name, shards);
+
+        ShardedTime { sharded }
+    }
+
+    pub fn name(&self) -> String {
+        self.sharded.name()
+    }
+
+    pub fn shards(&self) -> usize {
+        self.sharded.shards()
+    }
+
+    /// Records a time in ticks into the shard for the current thread.
+
+    pub fn record_time(&self, sample: i64) {
+        if sample < 0 {
+            panic!("ShardedTime::record_time:  The sample is negative.");
+        }
+
+        self.sharded.record_i64(sample);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.sharded.count()
+    }
+
+    pub fn clear(&self) {
+        self.sharded.clear();
+    }
+
+    /// Merges the shards into a RunningTime instance.  The timer
+    /// provides the frequency used when printing.
+
+    pub fn to_running(&self, timer: TimerBox, print_opts: &PrintOption) -> RunningTime {
+        let running = self.sharded.to_running(print_opts);
+
+        RunningTime::from_integer(timer, print_opts, running)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rustics;
+    use crate::tests::continuing_box;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn test_shard() {
+        let mut shard   = IntegerShard::new();
+        let mut running = RunningInteger::new("Running", &None);
+
+        for i in -100..=1000 {
+            shard.record(i);
+            running.record_i64(i);
+        }
+
+        let export   = shard.export_data();
+        let expected = running.export_data();
+
+        assert!(export.count    == expected.count   );
+        assert!(export.mean     == expected.mean    );
+        assert!(export.moment_2 == expected.moment_2);
+        assert!(export.cubes    == expected.cubes   );
+        assert!(export.moment_4 == expected.moment_4);
+        assert!(export.min_i64  == expected.min_i64 );
+        assert!(export.max_i64  == expected.max_i64 );
+
+        let histogram = export.log_histogram.unwrap();
+        let histogram = histogram.borrow();
+
+        assert!(histogram.equals(&expected.log_histogram.unwrap().borrow()));
+
+        shard.clear();
+        assert!(shard.count() == 0);
+
+        // A single shard merged into a RunningInteger gives the same
+        // statistics as recording the samples directly.
+
+        let     sharded = ShardedInteger::new("Sharded", 1);
+        let mut running = RunningInteger::new("Running", &None);
+
+        for i in -100..=1000 {
+            sharded.record_i64(i * i);
+            running.record_i64(i * i);
+        }
+
+        let merged = sharded.to_running(&None);
+
+        assert!(merged.count()    == running.count()   );
+        assert!(merged.mean()     == running.mean()    );
+        assert!(merged.variance() == running.variance());
+        assert!(merged.skewness() == running.skewness());
+        assert!(merged.kurtosis() == running.kurtosis());
+    }
+
+    fn test_threads() {
+        let sharded = Arc::new(ShardedInteger::new("Sharded", 4));
+        let threads = 8;
+        let samples = 10_000;
+
+        assert!(sharded.shards() == 4);
+        assert!(sharded.to_running(&None).count() == 0);
+
+        let mut handles = Vec::new();
+
+        for t in 0..threads {
+            let sharded = sharded.clone();
+
+            let handle =
+                thread::spawn(move || {
+                    for i in 1..=samples {
+                        sharded.record_i64(t * samples + i);
+                    }
+                });
+
+            handles.push(handle);
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let merged = sharded.to_running(&None);
+        let total  = threads * samples;
+
+        assert!(sharded.count()  == total as u64);
+        assert!(merged.count()   == total as u64);
+        assert!(merged.min_i64() == 1);
+        assert!(merged.max_i64() == total);
+        assert!(merged.name()    == "Sharded");
+
+        let expected = (total + 1) as f64 / 2.0;
+
+        assert!((merged.mean() - expected).abs() < 1.0e-6);
+
+        sharded.clear();
+        assert!(sharded.count() == 0);
+    }
+
+    fn test_time() {
+        let sharded = ShardedTime::new("Time", 2);
+
+        for i in 1..=100 {
+            sharded.record_time(i * 1000);
+        }
+
+        let timer   = continuing_box();
+        let running = sharded.to_running(timer, &None);
+
+        assert!(sharded.name()   == "Time");
+        assert!(sharded.shards() == 2);
+        assert!(running.count()  == 100);
+        assert!(running.class()  == "time");
+        assert!(running.max_i64() == 100_000);
+
+        running.print();
+
+        sharded.clear();
+        assert!(sharded.count() == 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_shards() {
+        let _ = ShardedInteger::new("Zero", 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_time() {
+        let sharded = ShardedTime::new("Time", 1);
+
+        sharded.record_time(-1);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_shard  ();
+        test_threads();
+        test_time   ();
+    }
+}
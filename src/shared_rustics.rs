@@ -0,0 +1,462 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * SharedRustics
+//!     * The Rustics record methods take a mutable reference, so an
+//!       instance shared among threads must be wrapped in a Mutex, as
+//!       ArcSet and SyncRustics do.  SharedRustics is the interface for
+//!       types that record through a shared reference, using atomics or
+//!       per-thread shards internally.  Such an instance can be stored
+//!       in a plain Arc and shared freely.
+//!
+//!     * A SharedRustics type can't hold a PrinterBox, since that type
+//!       isn't Send, so print_opts() takes the printer as a parameter and
+//!       defaults to stdout.
+//!
+//!     * ShardedInteger implements this trait, too.
+//!
+//! * AtomicCounter
+//!     * AtomicCounter is a Counter kept in an atomic integer.
+//!       to_counter() returns a Counter with the current value, so it can
+//!       be added to a set or printed with other instances.
+//!
+//! * AtomicGauge
+//!     * AtomicGauge keeps a current level, like a queue depth, that is
+//!       set or adjusted by many threads.  It also keeps the minimum and
+//!       maximum levels seen and the number of updates.
+//!
+//! ## Example
+//!```
+//!     use std::sync::Arc;
+//!     use std::thread;
+//!     use rustics::shared_rustics::SharedRustics;
+//!     use rustics::shared_rustics::AtomicCounter;
+//!     use rustics::shared_rustics::AtomicGauge;
+//!
+//!     let     requests = Arc::new(AtomicCounter::new("Requests"));
+//!     let     depth    = Arc::new(AtomicGauge::new("Queue Depth"));
+//!     let mut threads  = Vec::new();
+//!
+//!     for _i in 0..4 {
+//!         let requests = requests.clone();
+//!         let depth    = depth.clone();
+//!
+//!         let thread =
+//!             thread::spawn(move || {
+//!                 for _j in 0..1000 {
+//!                     depth.add(1);
+//!                     requests.record_event();
+//!                     depth.add(-1);
+//!                 }
+//!             });
+//!
+//!         threads.push(thread);
+//!     }
+//!
+//!     for thread in threads {
+//!         thread.join().unwrap();
+//!     }
+//!
+//!     assert!(requests.count() == 4000);
+//!     assert!(depth.value()    == 0   );
+//!     assert!(depth.max()      >= 1   );
+//!
+//!     requests.print();
+//!     depth.print();
+//!```
+
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use super::Rustics;
+use super::PrinterOption;
+use super::PrintOption;
+use super::stdout_printer;
+use super::printer_mut;
+use super::counter::Counter;
+use super::sharded::ShardedInteger;
+use super::printable::Printable;
+
+/// SharedRustics is the interface for statistics types that record
+/// through a shared reference.
+
+pub trait SharedRustics: Send + Sync {
+    /// Records an i64 sample.
+
+    fn record_i64(&self, sample: i64);
+
+    /// Records an event.
+
+    fn record_event(&self) {
+        panic!("SharedRustics::record_event:  not supported");
+    }
+
+    /// Returns the name of the instance.
+
+    fn name(&self) -> String;
+
+    /// Returns the number of samples or events recorded.
+
+    fn count(&self) -> u64;
+
+    /// Discards the data recorded.
+
+    fn clear(&self);
+
+    /// Prints the instance using the given printer, or stdout if none
+    /// is given.
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>);
+
+    /// Prints the instance to stdout.
+
+    fn print(&self) {
+        self.print_opts(None, None);
+    }
+}
+
+/// AtomicCounter is a counter that can be shared among threads
+/// without a lock.
+
+#[derive(Debug, Default)]
+pub struct AtomicCounter {
+    name:   String,
+    count:  AtomicU64,
+}
+
+impl AtomicCounter {
+    pub fn new(name: &str) -> AtomicCounter {
+        let name  = name.to_string();
+        let count = AtomicU64::new(0);
+
+        AtomicCounter { name, count }
+    }
+
+    /// Adds the given amount to the counter.
+
+    pub fn add(&self, amount: u64) {
+        self.count.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    /// Returns a Counter holding the current value.
+
+    pub fn to_counter(&self, print_opts: &PrintOption) -> Counter {
+        let mut counter = Counter::new(&self.name, print_opts);
+
+        counter.add(self.count());
+        counter
+    }
+}
+
+impl SharedRustics for AtomicCounter {
+    fn record_i64(&self, sample: i64) {
+        if sample < 0 {
+            panic!("AtomicCounter::record_i64:  The sample is negative.");
+        }
+
+        self.add(sample as u64);
+    }
+
+    fn record_event(&self) {
+        self.add(1);
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    fn clear(&self) {
+        self.count.store(0, Ordering::Relaxed);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        self.to_counter(&None).print_opts(printer, title);
+    }
+}
+
+/// AtomicGauge keeps a level that can be set or adjusted by many
+/// threads without a lock.
+
+#[derive(Debug)]
+pub struct AtomicGauge {
+    name:       String,
+    value:      AtomicI64,
+    min:        AtomicI64,
+    max:        AtomicI64,
+    updates:    AtomicU64,
+}
+
+impl AtomicGauge {
+    pub fn new(name: &str) -> AtomicGauge {
+        let name    = name.to_string();
+        let value   = AtomicI64::new(0);
+        let min     = AtomicI64::new(i64::MAX);
+        let max     = AtomicI64::new(i64::MIN);
+        let updates = AtomicU64::new(0);
+
+        AtomicGauge { name, value, min, max, updates }
+    }
+
+    /// Sets the level.
+
+    pub fn set(&self, value: i64) {
+        self.value.store(value, Ordering::Relaxed);
+        self.update(value);
+    }
+
+    /// Adds the given amount, which might be negative, to the level,
+    /// and returns the new level.
+
+    pub fn add(&self, amount: i64) -> i64 {
+        let value = self.value.fetch_add(amount, Ordering::Relaxed) + amount;
+
+        self.update(value);
+        value
+    }
+
+    // Update the extremes and the update count for a new level.
+
+    fn update(&self, value: i64) {
+        self.min.fetch_min(value, Ordering::Relaxed);
+        self.max.fetch_max(value, Ordering::Relaxed);
+        self.updates.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the current level.
+
+    pub fn value(&self) -> i64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    /// Returns the lowest level set, or zero if there have been no
+    /// updates.
+
+    pub fn min(&self) -> i64 {
+        if self.count() == 0 {
+            return 0;
+        }
+
+        self.min.load(Ordering::Relaxed)
+    }
+
+    /// Returns the highest level set, or zero if there have been no
+    /// updates.
+
+    pub fn max(&self) -> i64 {
+        if self.count() == 0 {
+            return 0;
+        }
+
+        self.max.load(Ordering::Relaxed)
+    }
+}
+
+impl SharedRustics for AtomicGauge {
+    /// Sets the level.
+
+    fn record_i64(&self, sample: i64) {
+        self.set(sample);
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Returns the number of updates to the level.
+
+    fn count(&self) -> u64 {
+        self.updates.load(Ordering::Relaxed)
+    }
+
+    /// Resets the level to zero and discards the extremes.
+
+    fn clear(&self) {
+        self.value  .store(0,        Ordering::Relaxed);
+        self.min    .store(i64::MAX, Ordering::Relaxed);
+        self.max    .store(i64::MIN, Ordering::Relaxed);
+        self.updates.store(0,        Ordering::Relaxed);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                stdout_printer()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.name
+            };
+
+        let printer = printer_mut!(printer_box);
+
+        printer.print(title);
+        Printable::print_integer("Updates", self.count() as i64, printer);
+        Printable::print_integer("Value",   self.value(),        printer);
+
+        if self.count() > 0 {
+            Printable::print_integer("Minimum", self.min(), printer);
+            Printable::print_integer("Maximum", self.max(), printer);
+        }
+
+        printer.print("");
+    }
+}
+
+impl SharedRustics for ShardedInteger {
+    fn record_i64(&self, sample: i64) {
+        ShardedInteger::record_i64(self, sample);
+    }
+
+    fn name(&self) -> String {
+        ShardedInteger::name(self)
+    }
+
+    fn count(&self) -> u64 {
+        ShardedInteger::count(self)
+    }
+
+    fn clear(&self) {
+        ShardedInteger::clear(self);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        self.to_running(&None).print_opts(printer, title);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use std::sync::Arc;
+    use std::thread;
+    use crate::PrinterBox;
+    use crate::capture_printer::CapturePrinter;
+
+    fn test_counter() {
+        let counter = AtomicCounter::new("Atomic Counter");
+
+        counter.record_event();
+        counter.record_i64(9);
+        counter.add(10);
+
+        assert!(counter.count() == 20);
+        assert!(counter.name()  == "Atomic Counter");
+
+        let copy = counter.to_counter(&None);
+
+        assert!(copy.count() == 20);
+        assert!(copy.name()  == "Atomic Counter");
+
+        counter.clear();
+
+        assert!(counter.count() == 0);
+    }
+
+    fn test_gauge() {
+        let gauge   = AtomicGauge::new("Atomic Gauge");
+        let capture = Rc::new(RefCell::new(CapturePrinter::new()));
+        let printer = Some(capture.clone() as PrinterBox);
+
+        assert!(gauge.min() == 0);
+        assert!(gauge.max() == 0);
+
+        gauge.set(5);
+
+        assert!(gauge.add( 3) == 8);
+        assert!(gauge.add(-6) == 2);
+
+        assert!(gauge.value() == 2);
+        assert!(gauge.min()   == 2);
+        assert!(gauge.max()   == 8);
+        assert!(gauge.count() == 3);
+
+        gauge.print_opts(printer, None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(lines[0] == "Atomic Gauge");
+        assert!(lines.iter().any(|line| line.starts_with("    Maximum") && line.contains(" 8")));
+
+        gauge.clear();
+
+        assert!(gauge.value() == 0);
+        assert!(gauge.count() == 0);
+        assert!(gauge.max()   == 0);
+    }
+
+    // Share each type among threads through a plain Arc.
+
+    fn test_threads() {
+        let members: Vec<Arc<dyn SharedRustics>> =
+            vec![
+                Arc::new(AtomicCounter::new("Counter")),
+                Arc::new(AtomicGauge::new("Gauge")),
+                Arc::new(ShardedInteger::new("Sharded", 4))
+            ];
+
+        let mut threads = Vec::new();
+
+        for _i in 0..4 {
+            let members = members.clone();
+
+            let thread =
+                thread::spawn(move || {
+                    for sample in 1..=100 {
+                        for member in members.iter() {
+                            member.record_i64(sample);
+                        }
+                    }
+                });
+
+            threads.push(thread);
+        }
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert!(members[0].count() == 4 * 5050);
+        assert!(members[1].count() == 400);
+        assert!(members[2].count() == 400);
+
+        for member in members.iter() {
+            member.print();
+            member.clear();
+
+            assert!(member.count() == 0);
+        }
+    }
+
+    #[test]
+    fn run_tests() {
+        test_counter();
+        test_gauge  ();
+        test_threads();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_gauge_event() {
+        let gauge = AtomicGauge::new("Event Test");
+
+        gauge.record_event();
+    }
+}
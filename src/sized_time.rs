@@ -0,0 +1,529 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * SizedTime
+//!     * SizedTime records pairs of a transfer size in bytes and the time
+//!       taken for the transfer.  It keeps statistics on the latency and
+//!       the size, as well as on the time per byte, so that operations
+//!       with differing payload sizes can be compared.
+//!
+//!     * The latency is kept in a RunningTime instance, the sizes in a
+//!       RunningInteger instance, and the time per byte, in nanoseconds,
+//!       in a RunningFloat instance.  Each has its own histogram.  The
+//!       accessor functions return references to these instances.
+//!
+//!     * Pairs with a size of zero are included in the latency and size
+//!       statistics, but can't be normalized, so they are only counted
+//!       for the time per byte.
+//!
+//!     * The Rustics trait functions like mean() return the latency
+//!       statistics.  Single samples can't be recorded, so functions
+//!       like record_i64() and record_time() panic.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::time::DurationTimer;
+//!     use rustics::sized_time::SizedTime;
+//!
+//!     let     timer  = DurationTimer::new_box();
+//!     let mut writes = SizedTime::new("Disk Writes", timer, &None);
+//!
+//!     // Record some writes with the time in nanoseconds, since
+//!     // DurationTimer runs at 1 GHz.
+//!
+//!     writes.record_pair(4096,  8_192);
+//!     writes.record_pair(8192, 16_384);
+//!     writes.record_pair(   0,  1_000);
+//!
+//!     // The latency differs, but the time per byte is the same for
+//!     // both non-empty writes.
+//!
+//!     assert!(writes.count()                 == 3  );
+//!     assert!(writes.zero_sizes()            == 1  );
+//!     assert!(writes.normalized().count()    == 2  );
+//!     assert!(writes.normalized().mean()     == 2.0);
+//!     assert!(writes.sizes().max_i64()       == 8192);
+//!
+//!     writes.print();
+//!```
+
+use std::any::Any;
+
+use super::Rustics;
+use super::ExportStats;
+use super::PrinterBox;
+use super::PrinterOption;
+use super::PrintOption;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use super::TimerBox;
+use super::Units;
+use super::make_title;
+use super::printer_mut;
+use super::timer_mut;
+use super::timer_box_hz;
+use super::parse_print_opts;
+use super::printable::Printable;
+use super::running_integer::RunningInteger;
+use super::running_float::RunningFloat;
+use super::running_time::RunningTime;
+
+/// SizedTime keeps statistics on (size, time) pairs.
+
+#[derive(Clone)]
+pub struct SizedTime {
+    name:       String,
+    title:      String,
+    id:         usize,
+    hz:         i64,
+    zero_sizes: u64,
+    latency:    RunningTime,
+    sizes:      RunningInteger,
+    normalized: RunningFloat,
+    printer:    PrinterBox,
+}
+
+impl SizedTime {
+    /// Creates an instance.  The timer determines the units of the
+    /// times recorded.
+
+    pub fn new(name: &str, timer: TimerBox, print_opts: &PrintOption) -> SizedTime {
+        let (printer, title, _units, _histo_opts) = parse_print_opts(print_opts, name);
+
+        let hz = timer_box_hz(&timer);
+
+        if hz > i64::MAX as u128 {
+            panic!("SizedTime::new:  The timer hz value is too large.");
+        }
+
+        let     hz         = hz as i64;
+        let     name       = name.to_string();
+        let     id         = usize::MAX;
+        let     zero_sizes = 0;
+        let     latency    = RunningTime::new   (&make_title(&name, "Latency"), timer, print_opts);
+        let mut sizes      = RunningInteger::new(&make_title(&name, "Size"),           print_opts);
+        let mut normalized = RunningFloat::new  (&make_title(&name, "Time per Byte"),  print_opts);
+
+        sizes.set_units(Units::new("byte", "bytes"));
+        normalized.set_units(Units::new("nanosecond per byte", "nanoseconds per byte"));
+
+        let mut result =
+            SizedTime {
+                name,     title,       id,        hz,       zero_sizes,
+                latency,  sizes,       normalized, printer
+            };
+
+        let title = result.title.clone();
+
+        result.set_title(&title);
+        result
+    }
+
+    /// Records a size in bytes and the time taken, in timer ticks.  The
+    /// size must not be negative.
+
+    pub fn record_pair(&mut self, bytes: i64, time: i64) {
+        if bytes < 0 {
+            panic!("SizedTime::record_pair:  The size is negative.");
+        }
+
+        self.latency.record_time(time);
+        self.sizes.record_i64(bytes);
+
+        if bytes == 0 {
+            self.zero_sizes += 1;
+            return;
+        }
+
+        let nanoseconds = time as f64 * (1_000_000_000.0 / self.hz as f64);
+
+        self.normalized.record_f64(nanoseconds / bytes as f64);
+    }
+
+    /// Reads the timer to get the time for a transfer of the given
+    /// size, and records the pair.
+
+    pub fn record_sized_interval(&mut self, bytes: i64, timer: &mut TimerBox) {
+        let time = timer_mut!(timer).finish();
+
+        self.record_pair(bytes, time);
+    }
+
+    /// Returns the latency statistics.
+
+    pub fn latency(&self) -> &RunningTime {
+        &self.latency
+    }
+
+    /// Returns the size statistics.
+
+    pub fn sizes(&self) -> &RunningInteger {
+        &self.sizes
+    }
+
+    /// Returns the time per byte statistics, in nanoseconds.
+
+    pub fn normalized(&self) -> &RunningFloat {
+        &self.normalized
+    }
+
+    /// Returns the number of pairs recorded with a size of zero.
+
+    pub fn zero_sizes(&self) -> u64 {
+        self.zero_sizes
+    }
+
+    /// Returns the frequency of the timer.
+
+    pub fn hz(&self) -> i64 {
+        self.hz
+    }
+}
+
+impl Rustics for SizedTime {
+    fn record_i64(&mut self, _sample: i64) {
+        panic!("SizedTime::record_i64:  not supported");
+    }
+
+    fn record_f64(&mut self, _sample: f64) {
+        panic!("SizedTime::record_f64:  not supported");
+    }
+
+    fn record_event(&mut self) {
+        panic!("SizedTime::record_event:  not supported");
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        panic!("SizedTime::record_event_report:  not supported");
+    }
+
+    fn record_time(&mut self, _sample: i64) {
+        panic!("SizedTime::record_time:  not supported");
+    }
+
+    fn record_interval(&mut self, _timer: &mut TimerBox) {
+        panic!("SizedTime::record_interval:  not supported");
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn class(&self) -> &str {
+        "time"
+    }
+
+    fn count(&self) -> u64 {
+        self.latency.count()
+    }
+
+    fn log_mode(&self) -> isize {
+        self.latency.log_mode()
+    }
+
+    fn mean(&self) -> f64 {
+        self.latency.mean()
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        self.latency.standard_deviation()
+    }
+
+    fn variance(&self) -> f64 {
+        self.latency.variance()
+    }
+
+    fn skewness(&self) -> f64 {
+        self.latency.skewness()
+    }
+
+    fn kurtosis(&self) -> f64 {
+        self.latency.kurtosis()
+    }
+
+    fn int_extremes(&self) -> bool {
+        true
+    }
+
+    fn float_extremes(&self) -> bool {
+        false
+    }
+
+    fn min_i64(&self) -> i64 {
+        self.latency.min_i64()
+    }
+
+    fn min_f64(&self) -> f64 {
+        self.latency.min_f64()
+    }
+
+    fn max_i64(&self) -> i64 {
+        self.latency.max_i64()
+    }
+
+    fn max_f64(&self) -> f64 {
+        self.latency.max_f64()
+    }
+
+    fn precompute(&mut self) {
+        self.latency.precompute();
+        self.sizes.precompute();
+        self.normalized.precompute();
+    }
+
+    fn clear(&mut self) {
+        self.zero_sizes = 0;
+
+        self.latency.clear();
+        self.sizes.clear();
+        self.normalized.clear();
+    }
+
+    fn supports_reset_extremes(&self) -> bool {
+        true
+    }
+
+    fn reset_extremes(&mut self) {
+        self.latency.reset_extremes();
+        self.sizes.reset_extremes();
+        self.normalized.reset_extremes();
+    }
+
+    // Functions for printing
+
+    fn print(&self) {
+        self.print_opts(None, None);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        // Print a summary, then each of the statistics.  Release the
+        // printer before the members borrow it.
+
+        {
+            let printer = printer_mut!(printer_box);
+
+            printer.print(title);
+            Printable::print_integer("Count",      self.count()    as i64, printer);
+            Printable::print_integer("Zero Sizes", self.zero_sizes as i64, printer);
+            printer.print("");
+        }
+
+        let latency_title    = make_title(title, "Latency"      );
+        let sizes_title      = make_title(title, "Size"         );
+        let normalized_title = make_title(title, "Time per Byte");
+
+        self.latency   .print_opts(Some(printer_box.clone()), Some(&latency_title   ));
+        self.sizes     .print_opts(Some(printer_box.clone()), Some(&sizes_title     ));
+        self.normalized.print_opts(Some(printer_box.clone()), Some(&normalized_title));
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+
+        self.latency   .set_title(&make_title(title, "Latency"      ));
+        self.sizes     .set_title(&make_title(title, "Size"         ));
+        self.normalized.set_title(&make_title(title, "Time per Byte"));
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        self.latency.log_histogram()
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+
+    // For internal use only.
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<SizedTime>(other.generic()) {
+            std::ptr::eq(self, other)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn export_stats(&self) -> ExportStats {
+        self.latency.export_stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrintOpts;
+    use crate::HistoOpts;
+    use crate::timer_box;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use crate::tests::continuing_box;
+    use crate::tests::check_printer_box;
+    use crate::tests::ContinuingTimer;
+
+    fn test_pairs() {
+        let mut stats = SizedTime::new("Pair Test", continuing_box(), &None);
+
+        for i in 1..=100 {
+            stats.record_pair(i * 1000, i * 500);
+        }
+
+        stats.record_pair(0, 100);
+
+        assert!(stats.count()               == 101  );
+        assert!(stats.zero_sizes()          == 1    );
+        assert!(stats.sizes().count()       == 101  );
+        assert!(stats.sizes().max_i64()     == 100_000);
+        assert!(stats.latency().max_i64()   == 50_000);
+        assert!(stats.normalized().count()  == 100  );
+        assert!(stats.normalized().mean()   == 0.5  );
+        assert!(stats.normalized().min_f64() == 0.5 );
+        assert!(stats.max_i64()             == 50_000);
+        assert!(stats.min_i64()             == 100  );
+
+        // Check that the time is converted to nanoseconds.
+
+        let     timer  = timer_box!(ContinuingTimer::new(1_000_000));
+        let mut scaled = SizedTime::new("Scaled Test", timer, &None);
+
+        scaled.record_pair(10, 5);
+
+        assert!(scaled.hz()                == 1_000_000);
+        assert!(scaled.normalized().mean() == 500.0    );
+
+        // Use a timer.
+
+        let mut timer = continuing_box();
+
+        timer_mut!(timer).start();
+
+        scaled.record_sized_interval(100, &mut timer);
+
+        assert!(scaled.count() == 2);
+
+        stats.clear();
+
+        assert!(stats.count()              == 0);
+        assert!(stats.zero_sizes()         == 0);
+        assert!(stats.normalized().count() == 0);
+    }
+
+    fn test_print_output() {
+        let expected =
+            [
+                "Print Test",
+                "    Count                   2 ",
+                "    Zero Sizes              0 ",
+                "",
+                "Print Test ==> Latency",
+                "    Count                   2 ",
+                "    Minimum             1.000 microsecond",
+                "    Maximum             4.000 microseconds",
+                "    Log Mode               10 ",
+                "    Mode Value        768.000 nanoseconds",
+                "    Mean                2.500 microseconds",
+                "    Std Dev             2.121 microseconds",
+                "    Variance         +4.50000 e+6  ",
+                "    Skewness         +0.00000 e+0  ",
+                "    Kurtosis         +0.00000 e+0  ",
+                "",
+                "Print Test ==> Size",
+                "    Count                   2 ",
+                "    Minimum               100 bytes",
+                "    Maximum               200 bytes",
+                "    Log Mode                7 ",
+                "    Mode Value             96 bytes",
+                "    Mean             +1.50000 e+2  bytes",
+                "    Std Dev          +7.07106 e+1  bytes",
+                "    Variance         +5.00000 e+3  ",
+                "    Skewness         +0.00000 e+0  ",
+                "    Kurtosis         +0.00000 e+0  ",
+                "",
+                "Print Test ==> Time per Byte",
+                "    Count                   2 ",
+                "    NaNs                    0 ",
+                "    Infinities              0 ",
+                "    Minimum          +1.00000 e+1  nanoseconds per byte",
+                "    Maximum          +2.00000 e+1  nanoseconds per byte",
+                "    Mode Value       +3.84000 e+2  nanoseconds per byte",
+                "    Mean             +1.50000 e+1  nanoseconds per byte",
+                "    Std Dev          +7.07106 e+0  nanoseconds per byte",
+                "    Variance         +5.00000 e+1  ",
+                "    Skewness         +0.00000 e+0  ",
+                "    Kurtosis         +0.00000 e+0  ",
+                "",
+            ];
+
+        let printer    = Some(check_printer_box(&expected, true, false));
+        let title      = None;
+        let units      = None;
+        let histo_opts = Some(HistoOpts { no_histogram: true, ..Default::default() });
+        let print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
+
+        let mut stats = SizedTime::new("Print Test", continuing_box(), &print_opts);
+
+        stats.record_pair(100, 1_000);
+        stats.record_pair(200, 4_000);
+
+        stats.print();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_size() {
+        let mut stats = SizedTime::new("Panic Test", continuing_box(), &None);
+
+        stats.record_pair(-1, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_record_time() {
+        let mut stats = SizedTime::new("Panic Test", continuing_box(), &None);
+
+        stats.record_time(1);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_pairs       ();
+        test_print_output();
+    }
+}
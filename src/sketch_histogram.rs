@@ -0,0 +1,554 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * SketchHistogram
+//!     * SketchHistogram implements a quantile sketch in the style of
+//!       DDSketch.  Samples are put into logarithmically-spaced buckets
+//!       whose width is set by a relative error, so that any percentile
+//!       returned is within that relative error of the actual sample at
+//!       that rank.
+//!
+//!     * The pseudo-log histograms use power-of-two buckets, which are
+//!       too coarse for reporting tail latencies.  A one-percent sketch
+//!       uses about 35 buckets per power of two, but only the buckets
+//!       that contain samples are stored.
+//!
+//!     * Values with a magnitude below 1.0e-9 are counted as zeros.
+//!       NaNs and infinite values are ignored.
+//!
+//!     * RunningInteger and RunningFloat instances can maintain a sketch
+//!       in addition to their usual histogram via the enable_sketch()
+//!       method.
+//!
+//! ## Example
+//!```
+//!     use rustics::sketch_histogram::SketchHistogram;
+//!     use rustics::stdout_printer;
+//!     use rustics::printer_mut;
+//!
+//!     // Make a sketch with a 1% relative error.
+//!
+//!     let mut sketch = SketchHistogram::new(0.01);
+//!
+//!     for i in 1..=10_000 {
+//!         sketch.record(i as f64);
+//!     }
+//!
+//!     // The result should be within 1% of the actual value.
+//!
+//!     let p99 = sketch.percentile(99.0);
+//!
+//!     assert!(p99 >= 9900.0 * 0.99 && p99 <= 9900.0 * 1.01);
+//!
+//!     let printer = stdout_printer();
+//!     let printer = printer_mut!(printer);
+//!
+//!     sketch.print(printer);
+//!```
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::Histogram;
+use super::Printer;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use super::printable::Printable;
+use super::log_histogram::LogHistogram;
+
+// The smallest magnitude that isn't counted as zero.
+
+const MIN_VALUE: f64 = 1.0e-9;
+
+// These are the percentiles printed.
+
+const PRINT_PERCENTILES: [(&str, f64); 5] =
+    [ ("p50", 50.0), ("p90", 90.0), ("p99", 99.0), ("p99.9", 99.9), ("p99.99", 99.99) ];
+
+/// SketchHistogram is a quantile sketch with a configurable
+/// relative error.
+
+#[derive(Clone)]
+pub struct SketchHistogram {
+    relative_error: f64,
+    gamma:          f64,
+    log_gamma:      f64,
+    negative:       BTreeMap<i32, u64>,
+    positive:       BTreeMap<i32, u64>,
+    zeros:          u64,
+    samples:        u64,
+    min:            f64,
+    max:            f64,
+}
+
+impl SketchHistogram {
+    /// Creates a sketch with the given relative error, which must be
+    /// between 0 and 1.
+
+    pub fn new(relative_error: f64) -> SketchHistogram {
+        if !(relative_error > 0.0 && relative_error < 1.0) {
+            panic!("SketchHistogram::new:  The relative error is invalid.");
+        }
+
+        let gamma     = (1.0 + relative_error) / (1.0 - relative_error);
+        let log_gamma = gamma.ln();
+        let negative  = BTreeMap::new();
+        let positive  = BTreeMap::new();
+        let zeros     = 0;
+        let samples   = 0;
+        let min       = f64::MAX;
+        let max       = f64::MIN;
+
+        SketchHistogram {
+            relative_error, gamma,  log_gamma,  negative,  positive,
+            zeros,          samples, min,       max
+        }
+    }
+
+    /// Returns the relative error given to new().
+
+    pub fn relative_error(&self) -> f64 {
+        self.relative_error
+    }
+
+    /// Returns the number of samples recorded.
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Returns the number of buckets in use, which determines the
+    /// memory used.
+
+    pub fn buckets(&self) -> usize {
+        self.negative.len() + self.positive.len()
+    }
+
+    // Compute the bucket index for a positive magnitude.
+
+    fn index(&self, magnitude: f64) -> i32 {
+        (magnitude.ln() / self.log_gamma).ceil() as i32
+    }
+
+    // Compute the value that represents a bucket.  It is within the
+    // relative error of every value in the bucket.
+
+    fn value(&self, index: i32) -> f64 {
+        2.0 * self.gamma.powi(index) / (self.gamma + 1.0)
+    }
+
+    /// Records an f64 sample.
+
+    pub fn record(&mut self, sample: f64) {
+        self.record_count(sample, 1);
+    }
+
+    /// Records an i64 sample.
+
+    pub fn record_i64(&mut self, sample: i64) {
+        self.record_count(sample as f64, 1);
+    }
+
+    /// Records a sample with a repeat count.
+
+    pub fn record_count(&mut self, sample: f64, count: u64) {
+        if !sample.is_finite() || count == 0 {
+            return;
+        }
+
+        if sample.abs() < MIN_VALUE {
+            self.zeros += count;
+        } else if sample > 0.0 {
+            *self.positive.entry(self.index(sample)).or_insert(0) += count;
+        } else {
+            *self.negative.entry(self.index(-sample)).or_insert(0) += count;
+        }
+
+        self.samples += count;
+        self.min      = self.min.min(sample);
+        self.max      = self.max.max(sample);
+    }
+
+    /// Returns the estimated value at the given percentile, which is
+    /// clamped to the range 0 to 100.  The result is zero if there
+    /// are no samples.
+
+    pub fn percentile(&self, percentile: f64) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+
+        let     percentile = percentile.clamp(0.0, 100.0);
+        let     rank       = (percentile / 100.0 * self.samples as f64).ceil() as u64;
+        let     rank       = rank.max(1);
+        let mut seen       = 0;
+
+        // The extremes are exact, so use them when possible.
+
+        if rank == 1 {
+            return self.min;
+        }
+
+        if rank == self.samples {
+            return self.max;
+        }
+
+        // Walk the buckets from the most negative value to the most
+        // positive.  Clamp the estimate to the extremes.
+
+        for (index, count) in self.negative.iter().rev() {
+            seen += *count;
+
+            if seen >= rank {
+                return (-self.value(*index)).clamp(self.min, self.max);
+            }
+        }
+
+        seen += self.zeros;
+
+        if seen >= rank {
+            return 0.0;
+        }
+
+        for (index, count) in self.positive.iter() {
+            seen += *count;
+
+            if seen >= rank {
+                return self.value(*index).clamp(self.min, self.max);
+            }
+        }
+
+        self.max
+    }
+
+    // Return the buckets in order from the most negative value to the
+    // most positive, as (value, count) pairs.  The values are clamped
+    // to the extremes.
+
+    fn ordered_buckets(&self) -> Vec<(f64, u64)> {
+        let mut result = Vec::with_capacity(self.buckets() + 1);
+
+        for (index, count) in self.negative.iter().rev() {
+            result.push(((-self.value(*index)).clamp(self.min, self.max), *count));
+        }
+
+        if self.zeros > 0 {
+            result.push((0.0, self.zeros));
+        }
+
+        for (index, count) in self.positive.iter() {
+            result.push((self.value(*index).clamp(self.min, self.max), *count));
+        }
+
+        result
+    }
+
+    /// Returns an estimate of the mean of the samples after dropping
+    /// the given percentage of the samples from each end.  Each sample
+    /// is represented by its bucket value, so the result is within the
+    /// relative error of the exact trimmed mean.  If every sample would
+    /// be dropped, the median is returned.  The result is zero if there
+    /// are no samples.
+
+    pub fn trimmed_mean(&self, percent: f64) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+
+        let percent = percent.clamp(0.0, 50.0);
+        let low     = (percent / 100.0 * self.samples as f64).floor() as u64;
+        let high    = self.samples - low;
+
+        if low >= high {
+            return self.percentile(50.0);
+        }
+
+        // Sum the values of the samples with ranks from low up to, but
+        // not including, high.
+
+        let mut seen = 0;
+        let mut sum  = 0.0;
+
+        for (value, count) in self.ordered_buckets() {
+            let start = seen.max(low);
+            let end   = (seen + count).min(high);
+
+            if end > start {
+                sum += value * (end - start) as f64;
+            }
+
+            seen += count;
+        }
+
+        sum / (high - low) as f64
+    }
+
+    /// Returns the number of samples whose bucket values are below low
+    /// or above high.
+
+    pub fn count_outside(&self, low: f64, high: f64) -> u64 {
+        self.ordered_buckets()
+            .iter()
+            .filter(|(value, _)| *value < low || *value > high)
+            .map(|(_, count)| *count)
+            .sum()
+    }
+
+    /// Merges another sketch into this one.  The sketches must have the
+    /// same relative error.
+
+    pub fn merge(&mut self, other: &SketchHistogram) {
+        if self.relative_error != other.relative_error {
+            panic!("SketchHistogram::merge:  The relative errors don't match.");
+        }
+
+        for (index, count) in other.negative.iter() {
+            *self.negative.entry(*index).or_insert(0) += *count;
+        }
+
+        for (index, count) in other.positive.iter() {
+            *self.positive.entry(*index).or_insert(0) += *count;
+        }
+
+        self.zeros   += other.zeros;
+        self.samples += other.samples;
+        self.min      = self.min.min(other.min);
+        self.max      = self.max.max(other.max);
+    }
+
+    /// Prints a table of the common percentiles.
+
+    pub fn print(&self, printer: &mut dyn Printer) {
+        let header =
+            format!("  Sketch Histogram:  ({:.3}% relative error, {} samples)",
+                self.relative_error * 100.0, Printable::commas_u64(self.samples));
+
+        printer.print(&header);
+
+        if self.samples == 0 {
+            return;
+        }
+
+        for (name, percentile) in PRINT_PERCENTILES.iter() {
+            Printable::print_float(name, self.percentile(*percentile), printer);
+        }
+    }
+
+    /// Resets the sketch to its initial state.
+
+    pub fn clear(&mut self) {
+        self.negative.clear();
+        self.positive.clear();
+
+        self.zeros   = 0;
+        self.samples = 0;
+        self.min     = f64::MAX;
+        self.max     = f64::MIN;
+    }
+}
+
+impl Histogram for SketchHistogram {
+    fn print_histogram(&self, printer: &mut dyn Printer) {
+        self.print(printer);
+    }
+
+    fn clear_histogram(&mut self) {
+        self.clear();
+    }
+
+    /// Converts the sketch into a pseudo-log histogram, using the
+    /// value that represents each bucket.
+
+    fn to_log_histogram(&self) -> Option<LogHistogramBox> {
+        let mut histogram = LogHistogram::new();
+
+        for (index, count) in self.negative.iter() {
+            histogram.record_count(-self.value(*index).round() as i64, *count);
+        }
+
+        histogram.record_count(0, self.zeros);
+
+        for (index, count) in self.positive.iter() {
+            histogram.record_count(self.value(*index).round() as i64, *count);
+        }
+
+        Some(Rc::from(RefCell::new(histogram)))
+    }
+
+    fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::printer_mut;
+    use crate::tests::check_printer_box;
+
+    // Check that the percentiles of 1..=n are within the relative
+    // error.
+
+    fn check_accuracy(relative_error: f64) {
+        let mut sketch  = SketchHistogram::new(relative_error);
+        let     samples = 100_000;
+
+        for i in 1..=samples {
+            sketch.record_i64(i);
+        }
+
+        assert!(sketch.samples() == samples as u64);
+
+        for percentile in [ 1.0, 25.0, 50.0, 90.0, 99.0, 99.9, 99.99 ] {
+            let expected = (percentile / 100.0 * samples as f64).ceil();
+            let actual   = sketch.percentile(percentile);
+            let error    = (actual - expected).abs() / expected;
+
+            assert!(error <= relative_error);
+        }
+
+        assert!(sketch.percentile(  0.0) == 1.0);
+        assert!(sketch.percentile(100.0) == samples as f64);
+
+        println!("check_accuracy:  {} buckets for {}", sketch.buckets(), relative_error);
+    }
+
+    fn test_accuracy() {
+        check_accuracy(0.01 );
+        check_accuracy(0.001);
+        check_accuracy(0.1  );
+    }
+
+    fn test_signs() {
+        let mut sketch = SketchHistogram::new(0.01);
+
+        assert!(sketch.percentile(50.0) == 0.0);
+
+        for i in 1..=100 {
+            sketch.record(-(i as f64));
+            sketch.record(0.0);
+            sketch.record(  i as f64 );
+        }
+
+        sketch.record(f64::NAN);
+        sketch.record(f64::INFINITY);
+
+        assert!(sketch.samples() == 300);
+
+        let p10 = sketch.percentile(10.0);
+        let p50 = sketch.percentile(50.0);
+        let p90 = sketch.percentile(90.0);
+
+        assert!((p10 + 71.0).abs() <= 0.71);
+        assert!( p50 == 0.0);
+        assert!((p90 - 70.0).abs() <= 0.7);
+
+        // Check merging.
+
+        let mut other = SketchHistogram::new(0.01);
+
+        for i in 101..=200 {
+            other.record(i as f64);
+        }
+
+        sketch.merge(&other);
+
+        assert!(sketch.samples()         == 400);
+        assert!(sketch.percentile(100.0) == 200.0);
+
+        let log = sketch.to_log_histogram().unwrap();
+        let log = log.borrow();
+
+        let total: u64 = log.positive.iter().chain(log.negative.iter()).sum();
+
+        assert!(total == 400);
+
+        sketch.clear();
+
+        assert!(sketch.samples() == 0);
+        assert!(sketch.buckets() == 0);
+    }
+
+    fn test_print() {
+        let expected =
+            [
+                "  Sketch Histogram:  (1.000% relative error, 1,000 samples)",
+                "    p50              +4.97779 e+2  ",
+                "    p90              +9.07031 e+2  ",
+                "    p99              +9.82577 e+2  ",
+                "    p99.9            +1.00000 e+3  ",
+                "    p99.99           +1.00000 e+3  ",
+            ];
+
+        let     printer = check_printer_box(&expected, true, false);
+        let mut sketch  = SketchHistogram::new(0.01);
+
+        for i in 1..=1000 {
+            sketch.record_i64(i);
+        }
+
+        sketch.print_histogram(printer_mut!(printer));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_error() {
+        let _ = SketchHistogram::new(0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_merge() {
+        let mut sketch = SketchHistogram::new(0.01);
+        let     other  = SketchHistogram::new(0.02);
+
+        sketch.merge(&other);
+    }
+
+    fn test_trimmed_mean() {
+        let mut sketch = SketchHistogram::new(0.01);
+
+        assert!(sketch.trimmed_mean(10.0) == 0.0);
+
+        for i in 1..=990 {
+            sketch.record(i as f64);
+        }
+
+        // Add a few huge samples, which the trimming drops.
+
+        for _i in 0..10 {
+            sketch.record(1.0e9);
+        }
+
+        let trimmed = sketch.trimmed_mean(1.0);
+
+        println!("test_trimmed_mean:  trimmed {}", trimmed);
+
+        assert!((trimmed - 500.5).abs() < 500.5 * 0.01);
+        assert!(sketch.trimmed_mean(0.0) > 1.0e6);
+
+        // Trimming everything gives the median.
+
+        assert!(sketch.trimmed_mean(50.0) == sketch.percentile(50.0));
+
+        assert!(sketch.count_outside(0.0, 1.0e6) == 10  );
+        assert!(sketch.count_outside(0.0, 2.0e9) == 0   );
+        assert!(sketch.count_outside(2.0e9, 0.0) == 1000);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_accuracy    ();
+        test_signs       ();
+        test_print       ();
+        test_trimmed_mean();
+    }
+}
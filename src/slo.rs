@@ -0,0 +1,436 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * Slo
+//!     * Slo describes a latency service level objective, like 99% of
+//!       requests completing within 100 ms over 30 days, and computes
+//!       the error budget consumption and burn rate from the level 0
+//!       members of a TimeHier instance.
+//!
+//!     * Each level 0 member is one period.  The SLO window is given as
+//!       a number of level 0 periods, so with one-hour periods, a 30-day
+//!       window is 720 periods.  Level 0 must retain that many members
+//!       for the whole window to be covered.  If fewer are retained, the
+//!       results cover the periods that are available.
+//!
+//!     * A sample is good if it is at most the threshold.  The counts
+//!       come from the pseudo-log histograms, so a sample is counted as
+//!       good only if its whole histogram bucket is within the threshold.
+//!       The results are exact when the threshold is a power of two, and
+//!       conservative otherwise.  The threshold is in timer ticks.
+//!
+//!     * The burn rate is the error rate divided by the error rate that
+//!       the objective allows.  A burn rate of 1.0 uses the budget up
+//!       exactly at the end of the window, and a burn rate of 10.0 uses
+//!       it up in a tenth of the window.  The burn rate is computed over
+//!       a lookback of the most recent periods, one by default, while
+//!       the budget is computed over the whole window.
+//!
+//! * SloStatus
+//!     * SloStatus holds the event counts for a range of periods, and
+//!       computes the compliance, burn rate, and budget from them.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::hier::HierDescriptor;
+//!     use rustics::hier::HierDimension;
+//!     use rustics::time_hier::TimeHier;
+//!     use rustics::time_hier::TimeHierConfig;
+//!     use rustics::time::DurationTimer;
+//!     use rustics::slo::Slo;
+//!
+//!     // Keep 720 level 0 periods of 1000 samples each.
+//!
+//!     let dimensions = vec![ HierDimension::new(24, 720), HierDimension::new(0, 30) ];
+//!     let descriptor = HierDescriptor::new(dimensions, Some(1000));
+//!
+//!     let name        = "Request Latency".to_string();
+//!     let timer       = DurationTimer::new_box();
+//!     let window_size = None;
+//!     let print_opts  = None;
+//!
+//!     let configuration =
+//!         TimeHierConfig { descriptor, name, window_size, timer, print_opts };
+//!
+//!     let mut latency = TimeHier::new_hier(configuration);
+//!
+//!     // Record 10 periods.  Most requests take 1 ms, but 2% of them
+//!     // take 200 ms.  The timer uses nanoseconds.
+//!
+//!     for i in 0..10_000 {
+//!         if i % 50 == 0 {
+//!             latency.record_time(200_000_000);
+//!         } else {
+//!             latency.record_time(1_000_000);
+//!         }
+//!     }
+//!
+//!     // The objective is 99% within 2^27 ns, about 134 ms, over 720
+//!     // periods.
+//!
+//!     let slo    = Slo::new(0.99, 1 << 27, 720);
+//!     let status = slo.status(&latency);
+//!
+//!     assert!(status.periods    == 10    );
+//!     assert!(status.events     == 10_000);
+//!     assert!(status.bad_events == 200   );
+//!
+//!     // Twice the allowed error rate is a burn rate of 2.
+//!
+//!     assert!((slo.burn_rate(&latency) - 2.0).abs() < 1.0e-9);
+//!     assert!((status.budget_used()    - 2.0).abs() < 1.0e-9);
+//!
+//!     slo.print(&latency, None);
+//!```
+
+use super::Rustics;
+use super::PrinterOption;
+use super::printer_mut;
+use super::stdout_printer;
+use super::printable::Printable;
+use super::hier::Hier;
+use super::hier::HierIndex;
+use super::hier::HierSet;
+use super::log_histogram::LogHistogram;
+use super::log_histogram::bucket_max;
+
+/// SloStatus holds the event counts for a range of periods.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SloStatus {
+    pub objective:   f64,
+    pub periods:     usize,
+    pub events:      u64,
+    pub bad_events:  u64,
+}
+
+impl SloStatus {
+    /// Returns the fraction of events that were good.  The result is
+    /// 1.0 if there were no events.
+
+    pub fn compliance(&self) -> f64 {
+        1.0 - self.error_rate()
+    }
+
+    /// Returns the fraction of events that were bad.
+
+    pub fn error_rate(&self) -> f64 {
+        if self.events == 0 {
+            0.0
+        } else {
+            self.bad_events as f64 / self.events as f64
+        }
+    }
+
+    /// Returns the error rate divided by the error rate allowed by the
+    /// objective.
+
+    pub fn burn_rate(&self) -> f64 {
+        self.error_rate() / (1.0 - self.objective)
+    }
+
+    /// Returns the fraction of the error budget used, i.e., the bad
+    /// events divided by the bad events that the objective allows for
+    /// the events seen.  A value over 1.0 means the objective was
+    /// missed.
+
+    pub fn budget_used(&self) -> f64 {
+        self.burn_rate()
+    }
+
+    /// Returns the fraction of the error budget left.  The result is
+    /// negative if the budget was overspent.
+
+    pub fn budget_left(&self) -> f64 {
+        1.0 - self.budget_used()
+    }
+}
+
+/// Slo defines a latency objective to evaluate against a TimeHier
+/// instance.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Slo {
+    objective:  f64,
+    threshold:  i64,
+    window:     usize,
+    lookback:   usize,
+}
+
+impl Slo {
+    /// Creates an objective that the given fraction of samples, e.g.,
+    /// 0.99, are at most the threshold, in timer ticks, over a window
+    /// of the given number of level 0 periods.
+
+    pub fn new(objective: f64, threshold: i64, window: usize) -> Slo {
+        if !(objective > 0.0 && objective < 1.0) {
+            panic!("Slo::new:  The objective must be between 0 and 1.");
+        }
+
+        if window == 0 {
+            panic!("Slo::new:  The window must be at least one period.");
+        }
+
+        let lookback = 1;
+
+        Slo { objective, threshold, window, lookback }
+    }
+
+    /// Sets the number of periods used to compute the burn rate.
+
+    pub fn set_lookback(&mut self, lookback: usize) {
+        if lookback == 0 {
+            panic!("Slo::set_lookback:  The lookback must be at least one period.");
+        }
+
+        self.lookback = lookback;
+    }
+
+    /// Returns the objective.
+
+    pub fn objective(&self) -> f64 {
+        self.objective
+    }
+
+    /// Returns the threshold in timer ticks.
+
+    pub fn threshold(&self) -> i64 {
+        self.threshold
+    }
+
+    /// Returns the window in level 0 periods.
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns the number of periods used for the burn rate.
+
+    pub fn lookback(&self) -> usize {
+        self.lookback
+    }
+
+    /// Returns the number of good samples in a histogram.
+
+    pub fn good_events(&self, histogram: &LogHistogram) -> u64 {
+        let mut good = histogram.negative.iter().sum::<u64>();
+
+        for (i, count) in histogram.positive.iter().enumerate() {
+            if bucket_max(i, false) <= self.threshold {
+                good += count;
+            }
+        }
+
+        good
+    }
+
+    /// Returns the status of the most recent "periods" level 0 periods,
+    /// including the current one.  Members without a histogram are
+    /// skipped.
+
+    pub fn status_last(&self, hier: &Hier, periods: usize) -> SloStatus {
+        let     objective  = self.objective;
+        let     end        = hier.all_len(0);
+        let     start      = end.saturating_sub(periods);
+        let mut events     = 0;
+        let mut bad_events = 0;
+        let mut periods    = 0;
+
+        for which in start..end {
+            let member = hier.index(HierIndex::new(HierSet::All, 0, which));
+
+            let member =
+                match member {
+                    Some(member) => member,
+                    None         => continue,
+                };
+
+            let member  = member.borrow();
+            let rustics = member.to_rustics();
+
+            if let Some(histogram) = rustics.log_histogram() {
+                let histogram = histogram.borrow();
+                let total     = histogram.negative.iter().sum::<u64>()
+                              + histogram.positive.iter().sum::<u64>();
+
+                events     += total;
+                bad_events += total - self.good_events(&histogram);
+                periods    += 1;
+            }
+        }
+
+        SloStatus { objective, periods, events, bad_events }
+    }
+
+    /// Returns the status for the SLO window.
+
+    pub fn status(&self, hier: &Hier) -> SloStatus {
+        self.status_last(hier, self.window)
+    }
+
+    /// Returns the burn rate over the lookback periods.
+
+    pub fn burn_rate(&self, hier: &Hier) -> f64 {
+        self.status_last(hier, self.lookback).burn_rate()
+    }
+
+    /// Prints the compliance, budget, and burn rate.  The default
+    /// printer is stdout.
+
+    pub fn print(&self, hier: &Hier, printer: PrinterOption) {
+        let printer = printer.unwrap_or_else(stdout_printer);
+        let printer = printer_mut!(printer);
+        let status  = self.status(hier);
+        let title   = format!("{} SLO ({}%)", hier.title(), self.objective * 100.0);
+
+        printer.print(&title);
+
+        Printable::print_time   ("Threshold",   self.threshold as f64,     hier.hz() as i64, printer);
+        Printable::print_integer("Periods",     status.periods as i64,     printer);
+        Printable::print_integer("Events",      status.events as i64,      printer);
+        Printable::print_integer("Bad Events",  status.bad_events as i64,  printer);
+        Printable::print_float  ("Compliance",  status.compliance(),       printer);
+        Printable::print_float  ("Budget Used", status.budget_used(),      printer);
+        Printable::print_float  ("Budget Left", status.budget_left(),      printer);
+        Printable::print_float  ("Burn Rate",   self.burn_rate(hier),      printer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use crate::PrinterBox;
+    use crate::hier::HierDescriptor;
+    use crate::hier::HierDimension;
+    use crate::time_hier::TimeHier;
+    use crate::time_hier::TimeHierConfig;
+    use crate::time::DurationTimer;
+    use crate::capture_printer::CapturePrinter;
+
+    fn make_hier(retention: usize) -> Hier {
+        let dimensions  = vec![ HierDimension::new(4, retention), HierDimension::new(0, 4) ];
+        let descriptor  = HierDescriptor::new(dimensions, Some(100));
+        let name        = "Latency".to_string();
+        let timer       = DurationTimer::new_box();
+        let window_size = None;
+        let print_opts  = None;
+
+        let configuration =
+            TimeHierConfig { descriptor, name, window_size, timer, print_opts };
+
+        TimeHier::new_hier(configuration)
+    }
+
+    fn test_status() {
+        let mut hier = make_hier(8);
+        let     slo  = Slo::new(0.9, 1024, 4);
+
+        assert!(slo.status(&hier).events == 0);
+        assert!(slo.burn_rate(&hier)     == 0.0);
+
+        // The first period has no bad events, and the second has 20.
+
+        for _i in 0..100 {
+            hier.record_time(1024);
+        }
+
+        for i in 0..100 {
+            hier.record_time(if i < 20 { 1025 } else { 10 });
+        }
+
+        let status = slo.status(&hier);
+
+        assert!(status.periods    == 2  );
+        assert!(status.events     == 200);
+        assert!(status.bad_events == 20 );
+        assert!((status.compliance()  - 0.9).abs() < 1.0e-9);
+        assert!((status.budget_used() - 1.0).abs() < 1.0e-9);
+        assert!( status.budget_left().abs()        < 1.0e-9);
+
+        // The burn rate covers just the last period by default.
+
+        assert!((slo.burn_rate(&hier) - 2.0).abs() < 1.0e-9);
+
+        let mut slo = slo;
+
+        slo.set_lookback(2);
+
+        assert!(slo.lookback() == 2);
+        assert!((slo.burn_rate(&hier) - 1.0).abs() < 1.0e-9);
+
+        // Only the window is counted.
+
+        for _i in 0..400 {
+            hier.record_time(1);
+        }
+
+        let status = slo.status(&hier);
+
+        assert!(status.periods    == 4 );
+        assert!(status.bad_events == 0 );
+    }
+
+    fn test_threshold() {
+        let slo           = Slo::new(0.99, 1000, 10);
+        let mut histogram = LogHistogram::new();
+
+        // Values up to 512 are in buckets within the threshold, but the
+        // bucket for 1000 holds values up to 1024.
+
+        histogram.record(-5);
+        histogram.record(512);
+        histogram.record(1000);
+
+        assert!(slo.good_events(&histogram) == 2);
+        assert!(slo.threshold() == 1000);
+        assert!(slo.window()    == 10  );
+        assert!(slo.objective() == 0.99);
+    }
+
+    fn test_print() {
+        let mut hier = make_hier(4);
+        let     slo  = Slo::new(0.99, 1 << 20, 4);
+
+        for i in 0..100 {
+            hier.record_time(i * 1000);
+        }
+
+        let capture = Rc::new(RefCell::new(CapturePrinter::new()));
+        let printer = capture.clone() as PrinterBox;
+
+        slo.print(&hier, Some(printer));
+
+        let lines = capture.borrow_mut().take_lines();
+
+        for line in lines.iter() {
+            println!("{}", line);
+        }
+
+        assert!(lines.len() == 9);
+        assert!(lines[0]    == "Latency SLO (99%)");
+        assert!(lines[1].contains("Threshold"));
+        assert!(lines[3].contains("Events") && lines[3].contains("100"));
+    }
+
+    #[test]
+    fn run_tests() {
+        test_status   ();
+        test_threshold();
+        test_print    ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_objective() {
+        let _ = Slo::new(1.0, 10, 10);
+    }
+}
@@ -0,0 +1,626 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Types
+//!
+//! * MemberSnapshot
+//!     * MemberSnapshot holds the summary statistics of one Rustics
+//!       instance as plain u64 and f64 fields.
+//!
+//! * SnapshotCollector
+//!     * SnapshotCollector implements both RcTraverser and ArcTraverser.
+//!       It searches a set hierarchy for members with the given names
+//!       and takes a snapshot of each one that it finds.
+//!
+//! * SetSnapshot
+//!     * SetSnapshot is an owned copy of the state of every member of a
+//!       set hierarchy, as returned by RcSet::snapshot() and
+//!       ArcSet::snapshot().  Each member is recorded with its full title,
+//!       its count, the sum of its samples, its summary statistics, and a
+//!       copy of its histogram buckets.
+//!
+//!     * diff() compares two snapshots and returns a MemberDelta for each
+//!       member of the newer snapshot, giving the change in the count, the
+//!       sum, and each histogram bucket.  This allows periodic reports of
+//!       the activity in an interval without clearing the live statistics.
+//!       Members are matched by title.  A member that is missing from the
+//!       older snapshot is compared against an empty member.
+//!
+//! * rustics_snapshot!
+//!     * This macro declares a struct with one MemberSnapshot field per
+//!       set member, along with from_rc_set(), from_arc_set(), and
+//!       from_set() methods that fill the struct.  Application code can then use the field
+//!       names, which the compiler checks, instead of looking up members
+//!       by string.
+//!
+//!     * Each field is given the name of the set member that fills it.
+//!       If more than one member has that name, the first one found in
+//!       the traversal is used.  The fill methods return None if any
+//!       member is missing.
+//!
+//! ## Example
+//!```
+//!     use rustics::rustics_snapshot;
+//!     use rustics::Rustics;
+//!     use rustics::arc_sets::ArcSet;
+//!     use rustics::arc_item_mut;
+//!     use rustics::rustics_set::RusticsSet;
+//!
+//!     // Declare a snapshot struct for a set with a counter and a
+//!     // latency statistic.
+//!
+//!     rustics_snapshot! {
+//!         pub struct ServerStats {
+//!             requests: "Requests",
+//!             latency:  "Latency",
+//!         }
+//!     }
+//!
+//!     let mut set      = ArcSet::new("Server", 4, 0, &None);
+//!     let     requests = set.add_counter("Requests", None);
+//!     let     latency  = set.add_running_integer("Latency", None);
+//!
+//!     for i in 1..=100 {
+//!         arc_item_mut!(requests).record_event();
+//!         arc_item_mut!(latency).record_i64(i);
+//!     }
+//!
+//!     let snapshot = ServerStats::from_arc_set(&mut set).unwrap();
+//!
+//!     assert!(snapshot.requests.count == 100   );
+//!     assert!(snapshot.latency.count  == 100   );
+//!     assert!(snapshot.latency.mean   == 50.5  );
+//!     assert!(snapshot.latency.min    == 1.0   );
+//!     assert!(snapshot.latency.max    == 100.0 );
+//!
+//!     assert!(ServerStats::names() == vec![ "Requests", "Latency" ]);
+//!     assert!(ServerStats::from_set(&set) == Some(snapshot));
+//!
+//!     // Now take a snapshot of the whole set, record more data, and
+//!     // see what changed.
+//!
+//!     use rustics::snapshot::diff;
+//!
+//!     let before = set.snapshot();
+//!
+//!     for i in 1..=10 {
+//!         arc_item_mut!(latency).record_i64(i * 10);
+//!     }
+//!
+//!     let after  = set.snapshot();
+//!     let deltas = diff(&before, &after);
+//!     let delta  = deltas.iter().find(|delta| delta.title == "Server ==> Latency").unwrap();
+//!
+//!     // The sum is computed from the mean, so allow for rounding.
+//!
+//!     assert!(delta.count == 10);
+//!     assert!((delta.sum - 550.0).abs() < 1e-6);
+//!```
+
+use super::Rustics;
+use super::rc_sets::RcSet;
+use super::rc_sets::RcTraverser;
+use super::arc_sets::ArcSet;
+use super::arc_sets::ArcTraverser;
+
+/// MemberSnapshot contains the summary statistics of a Rustics
+/// instance.  The min and max fields are zero if the instance
+/// doesn't keep extremes or has no samples.  For counters, only
+/// the count is set.
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct MemberSnapshot {
+    pub count:              u64,
+    pub mean:               f64,
+    pub standard_deviation: f64,
+    pub variance:           f64,
+    pub skewness:           f64,
+    pub kurtosis:           f64,
+    pub min:                f64,
+    pub max:                f64,
+}
+
+impl MemberSnapshot {
+    /// Takes a snapshot of the given Rustics instance.
+
+    pub fn from_rustics(rustics: &dyn Rustics) -> MemberSnapshot {
+        let export    = rustics.export_stats();
+        let printable = export.printable;
+        let count     = printable.n;
+
+        if matches!(rustics.class(), "counter" | "cardinality") || count == 0 {
+            return MemberSnapshot { count, ..Default::default() };
+        }
+
+        let mean               = printable.mean;
+        let standard_deviation = printable.variance.sqrt();
+        let variance           = printable.variance;
+        let skewness           = printable.skewness;
+        let kurtosis           = printable.kurtosis;
+
+        let (min, max) =
+            if rustics.int_extremes() {
+                (printable.min_i64 as f64, printable.max_i64 as f64)
+            } else if rustics.float_extremes() {
+                (printable.min_f64, printable.max_f64)
+            } else {
+                (0.0, 0.0)
+            };
+
+        MemberSnapshot {
+            count,     mean,      standard_deviation,  variance,
+            skewness,  kurtosis,  min,                 max
+        }
+    }
+}
+
+/// MemberState holds an owned copy of the state of one set member.
+/// The histogram bucket vectors are empty if the member has no
+/// histogram.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemberState {
+    pub title:      String,
+    pub class:      String,
+    pub count:      u64,
+    pub sum:        f64,
+    pub summary:    MemberSnapshot,
+    pub negative:   Vec<u64>,
+    pub positive:   Vec<u64>,
+}
+
+impl MemberState {
+    /// Copies the state of the given Rustics instance.
+
+    pub fn from_rustics(rustics: &dyn Rustics) -> MemberState {
+        let title   = rustics.title();
+        let class   = rustics.class().to_string();
+        let summary = MemberSnapshot::from_rustics(rustics);
+        let count   = summary.count;
+
+        // A counter holds a sum, so its count is the sum.
+
+        let sum =
+            if matches!(rustics.class(), "counter" | "cardinality") {
+                count as f64
+            } else {
+                summary.mean * count as f64
+            };
+
+        let (negative, positive) =
+            if let Some(histogram) = rustics.log_histogram() {
+                let histogram = histogram.borrow();
+
+                (histogram.negative.to_vec(), histogram.positive.to_vec())
+            } else if let Some(histogram) = rustics.float_histogram() {
+                let histogram = histogram.borrow();
+
+                (histogram.negative.clone(), histogram.positive.clone())
+            } else {
+                (Vec::new(), Vec::new())
+            };
+
+        MemberState { title, class, count, sum, summary, negative, positive }
+    }
+}
+
+/// SetSnapshot holds the state of every member of a set hierarchy.
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SetSnapshot {
+    pub members:    Vec<MemberState>,
+}
+
+impl SetSnapshot {
+    /// Creates an empty snapshot.
+
+    pub fn new() -> SetSnapshot {
+        let members = Vec::new();
+
+        SetSnapshot { members }
+    }
+
+    /// Adds the state of a Rustics instance to the snapshot.
+
+    pub fn push(&mut self, rustics: &dyn Rustics) {
+        self.members.push(MemberState::from_rustics(rustics));
+    }
+
+    /// Returns the state of the member with the given title, if any.
+
+    pub fn get(&self, title: &str) -> Option<&MemberState> {
+        self.members.iter().find(|member| member.title == title)
+    }
+}
+
+/// MemberDelta holds the changes in a member between two snapshots.
+/// The bucket deltas are indexed like the histogram buckets.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemberDelta {
+    pub title:      String,
+    pub class:      String,
+    pub count:      i64,
+    pub sum:        f64,
+    pub negative:   Vec<i64>,
+    pub positive:   Vec<i64>,
+}
+
+/// Computes the change in each member from the "before" snapshot to
+/// the "after" snapshot.  The result has one entry per member of the
+/// "after" snapshot, in the same order.  The deltas can be negative if
+/// a member was cleared between the snapshots.
+
+pub fn diff(before: &SetSnapshot, after: &SetSnapshot) -> Vec<MemberDelta> {
+    let mut result = Vec::with_capacity(after.members.len());
+
+    for member in after.members.iter() {
+        let title = member.title.clone();
+        let class = member.class.clone();
+
+        let delta =
+            if let Some(old) = before.get(&member.title) {
+                let count    = member.count as i64 - old.count as i64;
+                let sum      = member.sum - old.sum;
+                let negative = bucket_diff(&old.negative, &member.negative);
+                let positive = bucket_diff(&old.positive, &member.positive);
+
+                MemberDelta { title, class, count, sum, negative, positive }
+            } else {
+                let count    = member.count as i64;
+                let sum      = member.sum;
+                let negative = bucket_diff(&[], &member.negative);
+                let positive = bucket_diff(&[], &member.positive);
+
+                MemberDelta { title, class, count, sum, negative, positive }
+            };
+
+        result.push(delta);
+    }
+
+    result
+}
+
+// Subtract two bucket vectors.  A missing bucket is treated as zero.
+
+fn bucket_diff(before: &[u64], after: &[u64]) -> Vec<i64> {
+    let length     = std::cmp::max(before.len(), after.len());
+    let mut result = Vec::with_capacity(length);
+
+    for i in 0..length {
+        let old = before.get(i).copied().unwrap_or(0) as i64;
+        let new = after .get(i).copied().unwrap_or(0) as i64;
+
+        result.push(new - old);
+    }
+
+    result
+}
+
+/// SnapshotCollector takes snapshots of the set members with the
+/// given names.  It is used by the rustics_snapshot! macro.
+
+pub struct SnapshotCollector {
+    names:      Vec<String>,
+    snapshots:  Vec<Option<MemberSnapshot>>,
+}
+
+impl SnapshotCollector {
+    pub fn new(names: &[&str]) -> SnapshotCollector {
+        let names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        let snapshots          = vec![None; names.len()];
+
+        SnapshotCollector { names, snapshots }
+    }
+
+    /// Takes a snapshot of the member if its name is wanted and it
+    /// hasn't been found yet.
+
+    pub fn collect(&mut self, member: &dyn Rustics) {
+        let name = member.name();
+
+        for i in 0..self.names.len() {
+            if self.snapshots[i].is_none() && self.names[i] == name {
+                self.snapshots[i] = Some(MemberSnapshot::from_rustics(member));
+            }
+        }
+    }
+
+    /// Returns the snapshots in the order of the names given to new(),
+    /// or None if any member was not found.
+
+    pub fn snapshots(&self) -> Option<Vec<MemberSnapshot>> {
+        self.snapshots.iter().copied().collect()
+    }
+
+    /// Returns the names of any members that were not found.
+
+    pub fn missing(&self) -> Vec<String> {
+        let mut result = Vec::new();
+
+        for i in 0..self.names.len() {
+            if self.snapshots[i].is_none() {
+                result.push(self.names[i].clone());
+            }
+        }
+
+        result
+    }
+}
+
+impl RcTraverser for SnapshotCollector {
+    fn visit_set(&mut self, _set: &mut RcSet) {
+    }
+
+    fn visit_member(&mut self, member: &mut dyn Rustics) {
+        self.collect(member);
+    }
+}
+
+impl ArcTraverser for SnapshotCollector {
+    fn visit_set(&mut self, _set: &mut ArcSet) {
+    }
+
+    fn visit_member(&mut self, member: &mut dyn Rustics) {
+        self.collect(member);
+    }
+}
+
+/// Declares a struct containing a MemberSnapshot for each of the
+/// named set members.  See the module documentation for an example.
+
+#[macro_export]
+macro_rules! rustics_snapshot {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $snapshot:ident {
+            $( $field:ident : $name:expr ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        $vis struct $snapshot {
+            $( pub $field: $crate::snapshot::MemberSnapshot, )*
+        }
+
+        // Not every user needs both fill methods.
+
+        #[allow(dead_code)]
+        impl $snapshot {
+            /// Returns the names of the set members used to fill the
+            /// struct, in field order.
+
+            pub fn names() -> Vec<&'static str> {
+                vec![ $( $name ),* ]
+            }
+
+            /// Fills the struct from a set hierarchy of either type.
+
+            pub fn from_set(set: &dyn $crate::rustics_set::RusticsSet) -> Option<$snapshot> {
+                let mut collector = $crate::snapshot::SnapshotCollector::new(&Self::names());
+
+                set.visit_all(&mut |member| collector.collect(member));
+                Self::from_collector(&collector)
+            }
+
+            /// Fills the struct from an RcSet hierarchy.
+
+            pub fn from_rc_set(set: &mut $crate::rc_sets::RcSet) -> Option<$snapshot> {
+                Self::from_set(set)
+            }
+
+            /// Fills the struct from an ArcSet hierarchy.
+
+            pub fn from_arc_set(set: &mut $crate::arc_sets::ArcSet) -> Option<$snapshot> {
+                Self::from_set(set)
+            }
+
+            fn from_collector(collector: &$crate::snapshot::SnapshotCollector)
+                    -> Option<$snapshot> {
+                let     snapshots = collector.snapshots()?;
+                let mut snapshots = snapshots.into_iter();
+
+                Some($snapshot { $( $field: snapshots.next().unwrap(), )* })
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rc_item;
+    use crate::rc_item_mut;
+    use crate::arc_item;
+    use crate::arc_item_mut;
+    use crate::rustics_set::RusticsSet;
+
+    rustics_snapshot! {
+        struct TestStats {
+            events:   "Events",
+            sizes:    "Sizes",
+            ratios:   "Ratios",
+        }
+    }
+
+    fn test_rc_set() {
+        let mut set    = RcSet::new("Test Set", 4, 1, &None);
+        let     events = set.add_counter("Events", None);
+        let     subset = set.add_subset("Subset", 4, 0);
+        let     sizes  = rc_item_mut!(subset).add_running_integer("Sizes", None);
+
+        // The ratios member is missing.
+
+        assert!(TestStats::from_rc_set(&mut set).is_none());
+
+        let ratios = rc_item_mut!(subset).add_running_float("Ratios", None);
+
+        for i in 1..=4 {
+            rc_item_mut!(events).record_event();
+            rc_item_mut!(sizes ).record_i64(i);
+            rc_item_mut!(ratios).record_f64(i as f64 / 2.0);
+        }
+
+        let snapshot = TestStats::from_rc_set(&mut set).unwrap();
+
+        assert!(snapshot.events.count == 4);
+        assert!(snapshot.events.mean  == 0.0);
+
+        assert!(snapshot.sizes.count  == 4);
+        assert!(snapshot.sizes.mean   == 2.5);
+        assert!(snapshot.sizes.min    == 1.0);
+        assert!(snapshot.sizes.max    == 4.0);
+
+        assert!(snapshot.ratios.mean  == 1.25);
+        assert!(snapshot.ratios.min   == 0.5);
+        assert!(snapshot.ratios.max   == 2.0);
+
+        let expected = rc_item!(sizes).variance();
+
+        assert!(snapshot.sizes.variance == expected);
+        assert!(snapshot.sizes.standard_deviation == expected.sqrt());
+    }
+
+    fn test_collector() {
+        let mut set       = ArcSet::new("Test Set", 4, 0, &None);
+        let     events    = set.add_counter("Events", None);
+        let     sizes     = set.add_running_integer("Sizes", None);
+        let mut collector = SnapshotCollector::new(&[ "Sizes", "Events", "Missing" ]);
+
+        arc_item_mut!(events).record_event();
+
+        set.traverse(&mut collector);
+
+        assert!(collector.snapshots().is_none());
+        assert!(collector.missing() == vec![ "Missing".to_string() ]);
+
+        // An empty instance gives an all-zero snapshot.
+
+        let empty    = MemberSnapshot::from_rustics(arc_item!(sizes));
+        let expected = MemberSnapshot::default();
+
+        assert!(empty == expected);
+    }
+
+    fn test_set_snapshot() {
+        let mut set    = RcSet::new("Test Set", 4, 1, &None);
+        let     events = set.add_counter("Events", None);
+        let     subset = set.add_subset("Subset", 4, 0);
+        let     sizes  = rc_item_mut!(subset).add_running_integer("Sizes", None);
+        let     ratios = rc_item_mut!(subset).add_running_float("Ratios", None);
+
+        for i in 1..=4 {
+            rc_item_mut!(events).record_event();
+            rc_item_mut!(sizes ).record_i64(i);
+            rc_item_mut!(ratios).record_f64(i as f64);
+        }
+
+        let before = set.snapshot();
+
+        assert!(before.members.len() == 3);
+
+        let state = before.get("Test Set ==> Subset ==> Sizes").unwrap();
+
+        assert!(state.class       == "integer");
+        assert!(state.count       == 4   );
+        assert!(state.sum         == 10.0);
+        assert!(state.positive[0] == 1   );
+        assert!(state.positive[1] == 1   );
+        assert!(state.positive[2] == 2   );
+
+        assert!(before.get("Test Set ==> Events").unwrap().sum == 4.0);
+        assert!(before.get("Missing").is_none());
+
+        // Record more data.  The snapshot must not change.
+
+        rc_item_mut!(events).record_i64(10);
+        rc_item_mut!(sizes ).record_i64(100);
+        rc_item_mut!(ratios).record_f64(-1.0);
+
+        assert!(before.get("Test Set ==> Subset ==> Sizes").unwrap().count == 4);
+
+        let after  = set.snapshot();
+        let deltas = diff(&before, &after);
+
+        assert!(deltas.len() == 3);
+
+        let events = &deltas[0];
+
+        assert!(events.title == "Test Set ==> Events");
+        assert!(events.count == 10  );
+        assert!(events.sum   == 10.0);
+        assert!(events.positive.is_empty());
+
+        let sizes = deltas.iter().find(|delta| delta.class == "integer").unwrap();
+
+        assert!(sizes.count == 1    );
+        assert!(sizes.sum   == 100.0);
+        assert!(sizes.positive.iter().sum::<i64>() == 1);
+        assert!(sizes.positive[7] == 1);
+        assert!(sizes.negative.iter().all(|delta| *delta == 0));
+
+        let ratios = deltas.iter().find(|delta| delta.class == "float").unwrap();
+
+        assert!(ratios.count == 1);
+        assert!(ratios.sum   == -1.0);
+        assert!(ratios.negative.iter().sum::<i64>() == 1);
+        assert!(ratios.positive.iter().sum::<i64>() == 0);
+
+        // A member added after the first snapshot is compared against
+        // an empty member, and a clear gives negative deltas.
+
+        let extra = set.add_running_integer("Extra", None);
+
+        rc_item_mut!(extra).record_i64(7);
+        set.clear();
+
+        let cleared = set.snapshot();
+        let deltas  = diff(&after, &cleared);
+        let extra   = deltas.iter().find(|delta| delta.title == "Test Set ==> Extra").unwrap();
+
+        assert!(extra.count == 0);
+
+        let title = "Test Set ==> Subset ==> Sizes";
+        let sizes = deltas.iter().find(|delta| delta.title == title).unwrap();
+
+        assert!(sizes.count == -5);
+        assert!(sizes.positive.iter().sum::<i64>() == -5);
+
+        let deltas = diff(&SetSnapshot::new(), &after);
+
+        assert!(deltas[1].count == 5);
+
+        // Check the ArcSet version.
+
+        let mut set   = ArcSet::new("Arc Set", 4, 0, &None);
+        let     sizes = set.add_running_integer("Sizes", None);
+        let     empty = set.snapshot();
+
+        arc_item_mut!(sizes).record_i64(3);
+
+        let deltas = diff(&empty, &set.snapshot());
+
+        assert!(deltas[0].title == "Arc Set ==> Sizes");
+        assert!(deltas[0].count == 1  );
+        assert!(deltas[0].sum   == 3.0);
+    }
+
+    fn test_bucket_diff() {
+        assert!(bucket_diff(&[ 1, 2 ], &[ 3, 2, 5 ]) == vec![ 2, 0, 5 ]);
+        assert!(bucket_diff(&[ 4, 2 ], &[ 3 ]      ) == vec![ -1, -2 ]);
+        assert!(bucket_diff(&[],       &[]         ).is_empty());
+    }
+
+    #[test]
+    fn run_tests() {
+        test_rc_set      ();
+        test_collector   ();
+        test_set_snapshot();
+        test_bucket_diff ();
+    }
+}
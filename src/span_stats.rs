@@ -0,0 +1,395 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Types
+//!
+//! * SpanRecorder
+//!     * SpanRecorder records the durations of named spans, like the
+//!       spans of the tracing crate, into an ArcSet.  Each span name
+//!       gets its own RunningTime member, which is created the first
+//!       time the name is seen.
+//!
+//!     * An optional filter selects the span names to record.  Spans
+//!       rejected by the filter are counted but otherwise ignored.
+//!
+//!     * Durations are in nanoseconds.
+//!
+//! * SpanTimer
+//!     * A SpanTimer measures one span, using the standard Instant type.
+//!
+//! * SpanLayer
+//!     * With the "tracing" Cargo feature, SpanRecorder::layer() returns
+//!       a tracing Layer.  The layer starts a SpanTimer when a span is
+//!       created and measures the span when it closes, so code that is
+//!       already instrumented gets latency reports with no changes at
+//!       the call sites.
+//!
+//!     * A Layer must be Send and Sync, but an ArcSet is not, so the
+//!       layer passes the durations to the recorder over a channel.
+//!       collect() records the pending durations into the set, and
+//!       should be invoked before the set is printed.
+//!
+//! ## Example
+//!```
+//!     use rustics::arc_sets::ArcSet;
+//!     use rustics::span_stats::SpanRecorder;
+//!     use rustics::span_stats::SpanTimer;
+//!
+//!     let     set      = ArcSet::new_box("Spans", 4, 0, &None);
+//!     let mut recorder = SpanRecorder::new(set.clone());
+//!
+//!     // Only record the spans for the request handlers.
+//!
+//!     recorder.set_filter(Box::new(|name: &str| name.starts_with("handle_")));
+//!
+//!     // Time a span.
+//!
+//!     let span = SpanTimer::start("handle_get");
+//!
+//!     // ... do the work for the span ...
+//!
+//!     assert!(recorder.finish(span));
+//!
+//!     // Durations measured elsewhere can be recorded directly.
+//!
+//!     assert!( recorder.record("handle_put", 2_500));
+//!     assert!(!recorder.record("parse",      1_000));
+//!
+//!     assert!(recorder.span_names() == vec![ "handle_get", "handle_put" ]);
+//!     assert!(recorder.filtered()   == 1);
+//!
+//!     set.lock().unwrap().print();
+//!```
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+#[cfg(feature = "tracing")]
+use std::sync::mpsc;
+#[cfg(feature = "tracing")]
+use tracing_core::Subscriber;
+#[cfg(feature = "tracing")]
+use tracing_core::span::Attributes;
+#[cfg(feature = "tracing")]
+use tracing_core::span::Id;
+#[cfg(feature = "tracing")]
+use tracing_subscriber::layer::Context;
+#[cfg(feature = "tracing")]
+use tracing_subscriber::layer::Layer;
+#[cfg(feature = "tracing")]
+use tracing_subscriber::registry::LookupSpan;
+
+use super::Rustics;
+use super::arc_sets::ArcSetBox;
+use super::arc_sets::RunningTimeArc;
+use super::time::default_timer;
+
+/// SpanTimer measures the duration of one span.
+
+#[derive(Clone, Debug)]
+pub struct SpanTimer {
+    name:   String,
+    start:  Instant,
+}
+
+impl SpanTimer {
+    /// Starts timing a span.
+
+    pub fn start(name: &str) -> SpanTimer {
+        let name  = name.to_string();
+        let start = Instant::now();
+
+        SpanTimer { name, start }
+    }
+
+    /// Returns the name of the span.
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the time since the span started, in nanoseconds.
+
+    pub fn elapsed(&self) -> i64 {
+        let elapsed = self.start.elapsed().as_nanos();
+
+        elapsed.min(i64::MAX as u128) as i64
+    }
+}
+
+/// A SpanFilter selects the span names to record.
+
+pub type SpanFilter = Box<dyn Fn(&str) -> bool>;
+
+/// SpanRecorder keeps a RunningTime member in an ArcSet for each
+/// span name recorded.
+
+pub struct SpanRecorder {
+    set:        ArcSetBox,
+    filter:     Option<SpanFilter>,
+    members:    BTreeMap<String, RunningTimeArc>,
+    filtered:   u64,
+
+    #[cfg(feature = "tracing")]
+    receiver:   Option<mpsc::Receiver<(String, i64)>>,
+}
+
+impl SpanRecorder {
+    /// Creates a recorder that adds its members to the given set.
+
+    pub fn new(set: ArcSetBox) -> SpanRecorder {
+        let filter   = None;
+        let members  = BTreeMap::new();
+        let filtered = 0;
+
+        #[cfg(feature = "tracing")]
+        let receiver = None;
+
+        SpanRecorder {
+            set,
+            filter,
+            members,
+            filtered,
+
+            #[cfg(feature = "tracing")]
+            receiver,
+        }
+    }
+
+    /// Sets the filter for span names.  Only the spans for which the
+    /// filter returns true are recorded.
+
+    pub fn set_filter(&mut self, filter: SpanFilter) {
+        self.filter = Some(filter);
+    }
+
+    /// Records a span duration, in nanoseconds.  Returns false if the
+    /// filter rejected the span.
+
+    pub fn record(&mut self, name: &str, duration: i64) -> bool {
+        if let Some(filter) = &self.filter {
+            if !filter(name) {
+                self.filtered += 1;
+                return false;
+            }
+        }
+
+        let member = self.member_for(name);
+
+        member.lock_typed().record_time(duration.max(0));
+        true
+    }
+
+    /// Records the duration of a span timed by a SpanTimer.
+
+    pub fn finish(&mut self, span: SpanTimer) -> bool {
+        self.record(span.name(), span.elapsed())
+    }
+
+    /// Returns the member for the given span name, if the span
+    /// has been recorded.
+
+    pub fn member(&self, name: &str) -> Option<RunningTimeArc> {
+        self.members.get(name).cloned()
+    }
+
+    /// Returns the names of the spans recorded, in sorted order.
+
+    pub fn span_names(&self) -> Vec<String> {
+        self.members.keys().cloned().collect()
+    }
+
+    /// Returns the number of spans rejected by the filter.
+
+    pub fn filtered(&self) -> u64 {
+        self.filtered
+    }
+
+    /// Creates a tracing Layer that sends the durations of the spans
+    /// to this recorder.  Only the layer returned by the latest
+    /// invocation is connected to the recorder.
+
+    #[cfg(feature = "tracing")]
+    pub fn layer(&mut self) -> SpanLayer {
+        let (sender, receiver) = mpsc::channel();
+
+        self.receiver = Some(receiver);
+        SpanLayer { sender }
+    }
+
+    /// Records the span durations sent by the layer since the last
+    /// invocation.  Returns the number of spans recorded, which
+    /// doesn't include the spans rejected by the filter.
+
+    #[cfg(feature = "tracing")]
+    pub fn collect(&mut self) -> usize {
+        let spans =
+            match &self.receiver {
+                Some(receiver) => receiver.try_iter().collect::<Vec<(String, i64)>>(),
+                None           => return 0,
+            };
+
+        let mut recorded = 0;
+
+        for (name, duration) in spans {
+            if self.record(&name, duration) {
+                recorded += 1;
+            }
+        }
+
+        recorded
+    }
+
+    // Find the member for a span name, creating it if needed.
+
+    fn member_for(&mut self, name: &str) -> RunningTimeArc {
+        if let Some(member) = self.members.get(name) {
+            return member.clone();
+        }
+
+        let timer  = default_timer();
+        let member = self.set.lock().unwrap().add_running_time(name, timer);
+
+        self.members.insert(name.to_string(), member.clone());
+        member
+    }
+}
+
+/// SpanLayer is a tracing Layer that times each span from its
+/// creation until it closes.  It is created by SpanRecorder::layer().
+
+#[cfg(feature = "tracing")]
+pub struct SpanLayer {
+    sender:     mpsc::Sender<(String, i64)>,
+}
+
+#[cfg(feature = "tracing")]
+impl<S> Layer<S> for SpanLayer where S: Subscriber + for<'a> LookupSpan<'a> {
+    fn on_new_span(&self, _attributes: &Attributes<'_>, id: &Id, context: Context<'_, S>) {
+        if let Some(span) = context.span(id) {
+            span.extensions_mut().insert(SpanTimer::start(span.name()));
+        }
+    }
+
+    fn on_close(&self, id: Id, context: Context<'_, S>) {
+        if let Some(span) = context.span(&id) {
+            if let Some(timer) = span.extensions_mut().remove::<SpanTimer>() {
+                let duration = timer.elapsed();
+
+                // The recorder might be gone, so ignore any error.
+
+                let _ = self.sender.send((timer.name, duration));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arc_sets::ArcSet;
+
+    fn test_record() {
+        let     set      = ArcSet::new_box("Span Test", 4, 0, &None);
+        let mut recorder = SpanRecorder::new(set.clone());
+
+        for i in 1..=10 {
+            assert!(recorder.record("read", i * 1000));
+        }
+
+        assert!(recorder.record("write", 5000));
+        assert!(recorder.record("write",   -1));
+
+        let read  = recorder.member("read" ).unwrap();
+        let write = recorder.member("write").unwrap();
+
+        assert!(read .lock_typed().count()   == 10   );
+        assert!(read .lock_typed().max_i64() == 10000);
+        assert!(write.lock_typed().count()   == 2    );
+        assert!(write.lock_typed().min_i64() == 0    );
+
+        assert!(recorder.member("none").is_none());
+        assert!(recorder.span_names() == vec![ "read", "write" ]);
+
+        // The members were added to the set.
+
+        assert!(set.lock().unwrap().find("read" ).is_some());
+        assert!(set.lock().unwrap().find("write").is_some());
+    }
+
+    fn test_filter() {
+        let     set      = ArcSet::new_box("Filter Test", 4, 0, &None);
+        let mut recorder = SpanRecorder::new(set.clone());
+
+        recorder.set_filter(Box::new(|name: &str| name != "noisy"));
+
+        assert!( recorder.record("quiet", 100));
+        assert!(!recorder.record("noisy", 100));
+        assert!(!recorder.record("noisy", 100));
+
+        assert!(recorder.filtered()   == 2);
+        assert!(recorder.span_names() == vec![ "quiet" ]);
+        assert!(set.lock().unwrap().find("noisy").is_none());
+
+        let span = SpanTimer::start("quiet");
+
+        assert!(span.name() == "quiet");
+        assert!(span.elapsed() >= 0);
+        assert!(recorder.finish(span));
+        assert!(recorder.member("quiet").unwrap().lock_typed().count() == 2);
+    }
+
+    #[cfg(feature = "tracing")]
+    fn test_layer() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let     set      = ArcSet::new_box("Layer Test", 4, 0, &None);
+        let mut recorder = SpanRecorder::new(set.clone());
+
+        assert!(recorder.collect() == 0);
+
+        recorder.set_filter(Box::new(|name: &str| name != "noisy"));
+
+        let subscriber = tracing_subscriber::registry().with(recorder.layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            for _i in 0..3 {
+                let span = tracing::info_span!("handle_get");
+                let _guard = span.enter();
+            }
+
+            let _noisy = tracing::info_span!("noisy");
+            let  outer = tracing::info_span!("handle_put");
+
+            // A span that is still open isn't recorded.
+
+            assert!(recorder.collect() == 3);
+
+            drop(outer);
+        });
+
+        assert!(recorder.collect()    == 1);
+        assert!(recorder.filtered()   == 1);
+        assert!(recorder.collect()    == 0);
+        assert!(recorder.span_names() == vec![ "handle_get", "handle_put" ]);
+
+        let get = recorder.member("handle_get").unwrap();
+
+        assert!(get.lock_typed().count() == 3);
+        assert!(set.lock().unwrap().find("handle_put").is_some());
+    }
+
+    #[test]
+    fn run_tests() {
+        test_record();
+        test_filter();
+
+        #[cfg(feature = "tracing")]
+        test_layer();
+    }
+}
@@ -0,0 +1,703 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Functions
+//!
+//! * Moment Math
+//!     * This module holds the functions that Rustics types use to
+//!       compute and merge the moments about the mean.  It is of
+//!       interest mostly to developers creating new mergeable Rustics
+//!       types.  The names were once defined at the crate root, and
+//!       they are still exported from there.
+//!
+//!     * compute_statistics() converts the power sums of a set of
+//!       samples (the sum and the sums of the squares, cubes, and
+//!       fourth powers) into the mean and the second and fourth
+//!       moments about the mean.
+//!
+//!     * recover() goes the other way.  It converts the mean and the
+//!       moments back into the sums of the squares and fourth powers,
+//!       so that two summaries can be merged by adding power sums.
+//!
+//!     * estimate_moment_3() computes the third moment about the mean
+//!       from the sum of the cubes.
+//!
+//!     * pool_moments() combines the counts, means, and second moments
+//!       of a set of summaries, and pooled_variance() and
+//!       pooled_standard_deviation() give the estimators for the union.
+//!       This path adds the second moments directly, so it avoids the
+//!       cancellation in the power sums described below, and it handles
+//!       summaries with very different counts.
+//!
+//!     * pool_higher_moments() extends the pooling to the third and
+//!       fourth moments about the mean, so that the kurtosis of a union
+//!       is computed on the same footing as its variance.
+//!
+//!     * compute_variance(), compute_skewness(), and compute_kurtosis()
+//!       compute the sample estimators from the moments.  They return
+//!       zero when there are too few samples, or when the moments are
+//!       not positive.
+//!
+//! * Numerical Limits
+//!     * The conversions between power sums and moments subtract
+//!       large, nearly equal terms when the mean is large compared to
+//!       the standard deviation.  The relative error of the k-th moment
+//!       grows roughly as the machine epsilon (about 2.2e-16) times
+//!       (mean / standard deviation) raised to the k-th power.  With a
+//!       ratio of 1,000, the fourth moment keeps only about four good
+//!       digits, and beyond a ratio of about 10,000 the skewness and
+//!       kurtosis of merged summaries are not meaningful.
+//!
+//!     * The power sums overflow to infinity when the fourth powers of
+//!       the samples pass f64::MAX, which happens for magnitudes above
+//!       about 1.0e77.
+//!
+//!     * The estimators do not check for non-finite inputs.  Infinite
+//!       or NaN moments generally produce NaN results.
+//!
+//! ## Example
+//!```
+//!     use rustics::stats_math::StatisticsData;
+//!     use rustics::stats_math::compute_statistics;
+//!     use rustics::stats_math::compute_variance;
+//!     use rustics::stats_math::PoolData;
+//!     use rustics::stats_math::pool_moments;
+//!     use rustics::stats_math::pooled_variance;
+//!
+//!     // Compute the statistics for the samples 1, 2, 3, and 4 from
+//!     // their power sums.
+//!
+//!     let samples = [ 1.0, 2.0, 3.0, 4.0 ];
+//!
+//!     let n       = samples.len() as f64;
+//!     let sum     = samples.iter().sum();
+//!     let squares = samples.iter().map(|x: &f64| x.powi(2)).sum();
+//!     let cubes   = samples.iter().map(|x: &f64| x.powi(3)).sum();
+//!     let quads   = samples.iter().map(|x: &f64| x.powi(4)).sum();
+//!
+//!     let data       = StatisticsData { n, sum, squares, cubes, quads };
+//!     let statistics = compute_statistics(data);
+//!
+//!     assert!(statistics.mean     == 2.5  );
+//!     assert!(statistics.moment_2 == 5.0  );
+//!     assert!(statistics.moment_4 == 10.25);
+//!
+//!     let variance = compute_variance(samples.len() as u64, statistics.moment_2);
+//!
+//!     assert!(variance == 5.0 / 3.0);
+//!
+//!     // Pool the summaries of { 1 } and { 2, 3, 4 }.  The result
+//!     // matches the summary of all four samples.
+//!
+//!     let first  = PoolData { n: 1.0, mean: 1.0, moment_2: 0.0 };
+//!     let second = PoolData { n: 3.0, mean: 3.0, moment_2: 2.0 };
+//!     let pooled = pool_moments(&[ first, second ]);
+//!
+//!     assert!(pooled.n        == 4.0);
+//!     assert!(pooled.mean     == 2.5);
+//!     assert!(pooled.moment_2 == 5.0);
+//!
+//!     assert!(pooled_variance(&[ first, second ]) == 5.0 / 3.0);
+//!```
+
+/// Provides the data for estimating the second and fourth moments
+/// about the mean, as well as the mean itself.
+
+pub struct StatisticsData {
+    pub n:        f64,
+    pub sum:      f64,
+    pub squares:  f64,
+    pub cubes:    f64,
+    pub quads:    f64,
+}
+
+/// Contains the return data for compute_statistics.
+
+pub struct Statistics {
+    pub mean:     f64,
+    pub moment_2: f64,
+    pub moment_4: f64,
+}
+
+/// Computes the second and fourth moments about the mean
+/// given the values in StatisticsData.  This is used when
+/// merging multiple Rustics instances for upper-level
+/// HierMember instances.
+///
+/// The formulae are derived by applying the binomial
+/// theorem to the formulae for the various moments about
+/// the mean.
+
+pub fn compute_statistics(data: StatisticsData) -> Statistics {
+    let n       = data.n;
+    let sum     = data.sum;
+    let squares = data.squares;
+    let cubes   = data.cubes;
+    let quads   = data.quads;
+
+    let mean    = data.sum / n;
+    let mean_1  = mean;
+    let mean_2  = mean.powi(2);
+    let mean_3  = mean.powi(3);
+    let mean_4  = mean.powi(4);
+
+    let moment_2 =
+                       squares
+      - 2.0 * mean_1 * sum
+      +       mean_2 * n;
+
+    let moment_4 =
+                       quads
+      - 4.0 * mean_1 * cubes
+      + 6.0 * mean_2 * squares
+      - 4.0 * mean_3 * sum
+      +       mean_4 * n;
+
+    Statistics { mean, moment_2, moment_4 }
+}
+
+/// Provides the data required to try to recover the sum of
+/// the squares and the sum of the fourth power of each of
+/// the data samples.
+
+pub struct RecoverData {
+    pub n:          f64,
+    pub mean:       f64,
+    pub moment_2:   f64,
+    pub cubes:      f64,
+    pub moment_4:   f64,
+}
+
+/// This routine converts the data in RecoverData into estimators
+/// of the sum of the squares and 4th power of each sample.  This
+/// is used when merging Rustics instances for use in a Hier instance.
+///
+/// The formulae are derived by applying the binomial theorem to
+/// the definition of the various moments about the mean.
+
+pub fn recover(data: RecoverData) -> (f64, f64) {
+    let n        = data.n;
+    let mean     = data.mean;
+    let moment_2 = data.moment_2;
+    let cubes    = data.cubes;
+    let moment_4 = data.moment_4;
+
+    let sum      = n * mean;
+    let squares  = moment_2 + 2.0 * mean * sum - n * mean.powi(2);
+
+    let mean_1   = mean;
+    let mean_2   = mean.powi(2);
+    let mean_3   = mean.powi(3);
+    let mean_4   = mean.powi(4);
+
+    let quads =
+                   moment_4
+          + (4.0 * cubes    * mean_1)
+          - (6.0 * squares  * mean_2)
+          + (4.0 * sum      * mean_3)
+          - (      n        * mean_4);
+
+    (squares, quads)
+}
+
+/// Provides the data required to try to estimate the
+/// third moment about the mean.
+
+pub struct EstimateData {
+    pub n:          f64,
+    pub mean:       f64,
+    pub moment_2:   f64,
+    pub cubes:      f64,
+}
+
+/// Estimates moment 3 about the mean.
+///
+/// I know of no good way to keep a running estimate of
+/// the 3rd moment about the mean, so this is the best
+/// I can do.  The equations for the squares and the
+/// third moment about the mean are from the binomial
+/// theorem applied to the definition of those moments.
+
+pub fn estimate_moment_3(data: EstimateData) -> f64 {
+    let n         = data.n;
+    let mean      = data.mean;
+    let cubes     = data.cubes;
+    let moment_2  = data.moment_2;
+    let sum       = n * mean;
+
+    // Estimate the sums of the squares of each sample.
+
+    let squares =
+        moment_2
+      + 2.0 * sum * mean
+      -       n   * mean.powi(2);
+
+    // Now estimate the third moment about the mean.
+
+    cubes - (3.0 * squares * mean) + 3.0 * (sum * mean.powi(2)) - n * mean.powi(3)
+}
+
+/// Describes one summary for pooling:  the number of samples, the
+/// mean, and the second moment about the mean.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PoolData {
+    pub n:          f64,
+    pub mean:       f64,
+    pub moment_2:   f64,
+}
+
+/// Pools a set of summaries into one summary of all their samples.
+///
+/// The summaries are combined pairwise using the update of Chan, Golub,
+/// and LeVeque, "Algorithms for Computing the Sample Variance", which
+/// adds the second moments directly rather than going through the sums
+/// of the squares.  The result stays accurate when the counts are very
+/// different, or when the mean is large compared to the standard
+/// deviation.  Empty summaries are ignored.
+
+pub fn pool_moments(parts: &[PoolData]) -> PoolData {
+    let mut n        = 0.0;
+    let mut mean     = 0.0;
+    let mut moment_2 = 0.0;
+
+    for part in parts {
+        if part.n <= 0.0 {
+            continue;
+        }
+
+        let total = n + part.n;
+        let delta = part.mean - mean;
+
+        mean     += delta * (part.n / total);
+        moment_2 += part.moment_2 + delta * delta * (n * part.n / total);
+        n         = total;
+    }
+
+    PoolData { n, mean, moment_2 }
+}
+
+/// Describes one summary for pooling the higher moments:  the number
+/// of samples, the mean, and the second, third, and fourth moments
+/// about the mean.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HigherPoolData {
+    pub n:          f64,
+    pub mean:       f64,
+    pub moment_2:   f64,
+    pub moment_3:   f64,
+    pub moment_4:   f64,
+}
+
+/// Pools a set of summaries into one summary of all their samples,
+/// including the third and fourth moments about the mean.
+///
+/// The pairwise update extends the one used by pool_moments() to the
+/// higher moments, following P. Pebay, "Formulas for Robust, One-Pass
+/// Parallel Computation of Covariances and Arbitrary-Order Statistical
+/// Moments".  The mean and the second moment match the values from
+/// pool_moments().  Empty summaries are ignored.
+
+pub fn pool_higher_moments(parts: &[HigherPoolData]) -> HigherPoolData {
+    let mut n        = 0.0;
+    let mut mean     = 0.0;
+    let mut moment_2 = 0.0;
+    let mut moment_3 = 0.0;
+    let mut moment_4 = 0.0;
+
+    for part in parts {
+        if part.n <= 0.0 {
+            continue;
+        }
+
+        let total   = n + part.n;
+        let delta   = part.mean - mean;
+        let product = n * part.n;
+
+        // Each moment uses the old values of the lower moments, so
+        // update the highest one first.
+
+        moment_4 +=
+              part.moment_4
+            + delta.powi(4) * product * (n * n - product + part.n * part.n) / total.powi(3)
+            + 6.0 * delta.powi(2) * (n * n * part.moment_2 + part.n * part.n * moment_2)
+                / total.powi(2)
+            + 4.0 * delta * (n * part.moment_3 - part.n * moment_3) / total;
+
+        moment_3 +=
+              part.moment_3
+            + delta.powi(3) * product * (n - part.n) / total.powi(2)
+            + 3.0 * delta * (n * part.moment_2 - part.n * moment_2) / total;
+
+        mean     += delta * (part.n / total);
+        moment_2 += part.moment_2 + delta * delta * (n * part.n / total);
+        n         = total;
+    }
+
+    HigherPoolData { n, mean, moment_2, moment_3, moment_4 }
+}
+
+/// Computes the sample variance of the union of a set of summaries.
+
+pub fn pooled_variance(parts: &[PoolData]) -> f64 {
+    let pooled = pool_moments(parts);
+
+    compute_variance(pooled.n as u64, pooled.moment_2)
+}
+
+/// Computes the sample standard deviation of the union of a set of
+/// summaries.
+
+pub fn pooled_standard_deviation(parts: &[PoolData]) -> f64 {
+    pooled_variance(parts).sqrt()
+}
+
+/// Computes a variance estimator.
+
+pub fn compute_variance(count: u64, moment_2: f64) -> f64 {
+    if count < 2 {
+        return 0.0;
+    }
+
+    let n = count as f64;
+
+    moment_2 / (n - 1.0)
+}
+
+/// Computes the sample skewness.
+///
+/// This formula is from brownmath.com.
+
+pub fn compute_skewness(count: u64, moment_2: f64, moment_3: f64) -> f64 {
+    if count < 3 || moment_2 == 0.0 {
+        return 0.0;
+    }
+
+    // Deal with floating point non-finite values.
+
+    // For debugging new Rustics types.
+    //
+    //assert!(moment_2 > 0.0);
+
+    if moment_2 <= 0.0 {
+        return 0.0;
+    }
+
+    let n          = count as f64;
+    let m3         = moment_3 / n;
+    let m2         = moment_2 / n;
+    let skewness   = m3 / m2.powf(1.5);
+    let correction = (n * (n - 1.0)).sqrt() / (n - 2.0);
+
+    skewness * correction
+}
+
+/// Computes the sample kurtosis estimator.
+///
+/// This formula is from brownmath.com.
+
+pub fn compute_kurtosis(count: u64, moment_2: f64, moment_4: f64) -> f64 {
+    if count < 4 || moment_2 == 0.0 {
+        return 0.0;
+    }
+
+    // Deal with floating point non-finite values.
+
+    // For debugging new Rustics types.
+    //
+    // assert!(moment_2 > 0.0 && moment_4 >= 0.0);
+
+    if moment_2 <= 0.0 || moment_4 <= 0.0 {
+        return 0.0;
+    }
+
+    let n               = count as f64;
+    let kurtosis        = moment_4 / (moment_2.powf(2.0) / n) - 3.0;
+    let correction      = (n - 1.0) / ((n - 2.0) * (n - 3.0));
+    let kurtosis_factor = (n + 1.0) * kurtosis + 6.0;
+
+    correction * kurtosis_factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A small generator for test samples, so that each trial uses a
+    // different, but repeatable, set of data.
+
+    struct Samples {
+        state: u64,
+    }
+
+    impl Samples {
+        fn new(seed: u64) -> Samples {
+            let state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+
+            Samples { state }
+        }
+
+        // Return a value in [0, 1).
+
+        fn next(&mut self) -> f64 {
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 7;
+            self.state ^= self.state << 17;
+
+            (self.state >> 11) as f64 / (1_u64 << 53) as f64
+        }
+
+        fn make(&mut self, count: usize, offset: f64, scale: f64) -> Vec<f64> {
+            (0..count).map(|_| offset + scale * (self.next() - 0.5)).collect()
+        }
+    }
+
+    struct Moments {
+        n:        f64,
+        mean:     f64,
+        moment_2: f64,
+        moment_3: f64,
+        moment_4: f64,
+        sum:      f64,
+        squares:  f64,
+        cubes:    f64,
+        quads:    f64,
+    }
+
+    // Compute the moments directly from their definitions.
+
+    fn moments(samples: &[f64]) -> Moments {
+        let n        = samples.len() as f64;
+        let sum      = samples.iter().sum::<f64>();
+        let mean     = sum / n;
+        let moment_2 = samples.iter().map(|x| (x - mean).powi(2)).sum();
+        let moment_3 = samples.iter().map(|x| (x - mean).powi(3)).sum();
+        let moment_4 = samples.iter().map(|x| (x - mean).powi(4)).sum();
+        let squares  = samples.iter().map(|x| x.powi(2)).sum();
+        let cubes    = samples.iter().map(|x| x.powi(3)).sum();
+        let quads    = samples.iter().map(|x| x.powi(4)).sum();
+
+        Moments { n, mean, moment_2, moment_3, moment_4, sum, squares, cubes, quads }
+    }
+
+    fn close(actual: f64, expected: f64, scale: f64) -> bool {
+        (actual - expected).abs() <= 1.0e-9 * scale.abs().max(1.0)
+    }
+
+    fn test_round_trip() {
+        for seed in 1..=200 {
+            let mut generator = Samples::new(seed);
+            let     count     = 4 + (seed as usize % 50);
+            let     offset    = 100.0 * (generator.next() - 0.5);
+            let     scale     = 1.0 + 20.0 * generator.next();
+            let     samples   = generator.make(count, offset, scale);
+            let     direct    = moments(&samples);
+
+            let n       = direct.n;
+            let sum     = direct.sum;
+            let squares = direct.squares;
+            let cubes   = direct.cubes;
+            let quads   = direct.quads;
+
+            let statistics = compute_statistics(StatisticsData { n, sum, squares, cubes, quads });
+
+            assert!(close(statistics.mean,     direct.mean,     direct.mean    ));
+            assert!(close(statistics.moment_2, direct.moment_2, direct.squares ));
+            assert!(close(statistics.moment_4, direct.moment_4, direct.quads   ));
+
+            let mean     = direct.mean;
+            let moment_2 = direct.moment_2;
+            let moment_4 = direct.moment_4;
+
+            let (squares, quads) = recover(RecoverData { n, mean, moment_2, cubes, moment_4 });
+
+            assert!(close(squares, direct.squares, direct.squares));
+            assert!(close(quads,   direct.quads,   direct.quads  ));
+
+            let moment_3 = estimate_moment_3(EstimateData { n, mean, moment_2, cubes });
+
+            assert!(close(moment_3, direct.moment_3, direct.quads));
+        }
+    }
+
+    // Merging two summaries by recovering and adding their power sums
+    // should give the same moments as summarizing all the samples.
+
+    fn test_merge() {
+        for seed in 1..=200 {
+            let mut generator = Samples::new(seed);
+            let     offset    = 10.0 * generator.next();
+            let     first     = generator.make(3 + seed as usize % 17, offset, 5.0);
+            let     second    = generator.make(5 + seed as usize % 23, -offset, 8.0);
+            let     all       = [ first.clone(), second.clone() ].concat();
+            let     expected  = moments(&all);
+
+            let mut sum     = 0.0;
+            let mut squares = 0.0;
+            let mut cubes   = 0.0;
+            let mut quads   = 0.0;
+            let mut n       = 0.0;
+
+            for part in [ &first, &second ] {
+                let part = moments(part);
+
+                let (part_squares, part_quads) =
+                    recover(RecoverData {
+                        n:        part.n,
+                        mean:     part.mean,
+                        moment_2: part.moment_2,
+                        cubes:    part.cubes,
+                        moment_4: part.moment_4
+                    });
+
+                n       += part.n;
+                sum     += part.n * part.mean;
+                squares += part_squares;
+                cubes   += part.cubes;
+                quads   += part_quads;
+            }
+
+            let merged = compute_statistics(StatisticsData { n, sum, squares, cubes, quads });
+
+            assert!(close(merged.mean,     expected.mean,     expected.mean   ));
+            assert!(close(merged.moment_2, expected.moment_2, expected.squares));
+            assert!(close(merged.moment_4, expected.moment_4, expected.quads  ));
+        }
+    }
+
+    // The skewness and kurtosis don't change when the samples are
+    // shifted or scaled, and the skewness changes sign when the
+    // samples are negated.  The variance scales with the square.
+
+    fn test_invariants() {
+        for seed in 1..=100 {
+            let mut generator = Samples::new(seed);
+            let     count     = 4 + seed as usize % 40;
+            let     samples   = generator.make(count, 0.0, 1.0);
+            let     shifted: Vec<f64> = samples.iter().map(|x| 3.0 * x + 7.0).collect();
+            let     negated: Vec<f64> = samples.iter().map(|x| -x).collect();
+
+            let base    = moments(&samples);
+            let shift   = moments(&shifted);
+            let negate  = moments(&negated);
+            let count   = count as u64;
+
+            let variance       = compute_variance(count, base.moment_2);
+            let shift_variance = compute_variance(count, shift.moment_2);
+
+            assert!(close(shift_variance, 9.0 * variance, 9.0 * variance));
+
+            let skewness        = compute_skewness(count, base.moment_2,   base.moment_3  );
+            let shift_skewness  = compute_skewness(count, shift.moment_2,  shift.moment_3 );
+            let negate_skewness = compute_skewness(count, negate.moment_2, negate.moment_3);
+
+            assert!(close(shift_skewness,   skewness, 1.0e3));
+            assert!(close(negate_skewness, -skewness, 1.0e3));
+
+            let kurtosis       = compute_kurtosis(count, base.moment_2,  base.moment_4 );
+            let shift_kurtosis = compute_kurtosis(count, shift.moment_2, shift.moment_4);
+
+            assert!(close(shift_kurtosis, kurtosis, 1.0e3));
+        }
+    }
+
+    fn test_limits() {
+        assert!(compute_variance(0, 1.0) == 0.0);
+        assert!(compute_variance(1, 1.0) == 0.0);
+        assert!(compute_variance(2, 1.0) == 1.0);
+
+        assert!(compute_skewness(2, 1.0, 1.0) == 0.0);
+        assert!(compute_kurtosis(3, 1.0, 1.0) == 0.0);
+
+        assert!(compute_kurtosis(4,  0.0, 0.0) == 0.0);
+        assert!(compute_kurtosis(4,  1.0, 0.0) == 0.0);
+        assert!(compute_kurtosis(4, -1.0, 0.0) == 0.0);
+        assert!(compute_skewness(4,  0.0, 0.0) == 0.0);
+        assert!(compute_skewness(4, -1.0, 0.0) == 0.0);
+
+        // A symmetric set has no skewness.
+
+        let samples = [ -2.0, -1.0, 1.0, 2.0 ];
+        let direct  = moments(&samples);
+
+        assert!(compute_skewness(4, direct.moment_2, direct.moment_3) == 0.0);
+    }
+
+    // Pool summaries with very different counts, and compare the
+    // result with the moments of all the samples.
+
+    fn test_pool() {
+        for seed in 1..=100 {
+            let mut generator = Samples::new(seed);
+            let     offset    = 1.0e4 * generator.next();
+            let mut parts     = Vec::new();
+            let mut higher    = Vec::new();
+            let mut all       = Vec::new();
+
+            for (i, count) in [ 1, 2, 10_000, 3, 50 ].iter().enumerate() {
+                let part_offset = offset + i as f64;
+                let samples     = generator.make(*count, part_offset, 1.0 + i as f64);
+                let direct      = moments(&samples);
+
+                let n        = direct.n;
+                let mean     = direct.mean;
+                let moment_2 = direct.moment_2;
+
+                let moment_3 = direct.moment_3;
+                let moment_4 = direct.moment_4;
+
+                parts.push (PoolData { n, mean, moment_2 });
+                higher.push(HigherPoolData { n, mean, moment_2, moment_3, moment_4 });
+                all.extend(samples);
+            }
+
+            // Add an empty part.
+
+            parts.push(PoolData { n: 0.0, mean: 0.0, moment_2: 0.0 });
+
+            let expected = moments(&all);
+            let pooled   = pool_moments(&parts);
+            let count    = all.len() as u64;
+            let variance = compute_variance(count, expected.moment_2);
+
+            assert!(pooled.n == expected.n);
+            assert!(close(pooled.mean,     expected.mean,     expected.mean    ));
+            assert!(close(pooled.moment_2, expected.moment_2, expected.moment_2));
+
+            assert!(close(pooled_variance(&parts), variance, variance));
+            assert!(close(pooled_standard_deviation(&parts), variance.sqrt(), variance.sqrt()));
+
+            // The higher moments pool to the moments of all the samples,
+            // and the lower moments match pool_moments().
+
+            let pooled_higher = pool_higher_moments(&higher);
+
+            assert!(pooled_higher.n        == pooled.n       );
+            assert!(pooled_higher.mean     == pooled.mean    );
+            assert!(pooled_higher.moment_2 == pooled.moment_2);
+
+            assert!(close(pooled_higher.moment_3, expected.moment_3, expected.moment_2));
+            assert!(close(pooled_higher.moment_4, expected.moment_4, expected.moment_4));
+        }
+
+        let empty = pool_moments(&[]);
+
+        assert!(empty.n == 0.0 && empty.mean == 0.0 && empty.moment_2 == 0.0);
+        assert!(pooled_variance(&[]) == 0.0);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_round_trip();
+        test_merge     ();
+        test_invariants();
+        test_limits    ();
+        test_pool      ();
+    }
+}
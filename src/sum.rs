@@ -59,12 +59,88 @@ pub fn kbk_sum(input: &[f64]) -> f64 {
     sum + cs + ccs
 }
 
+/// CompensatedSum keeps a running sum using the Neumaier variant of
+/// Kahan summation.  The low-order bits lost by each addition are
+/// kept in a compensation term, so the error doesn't grow with the
+/// number of addends.  The running statistics use it to accumulate
+/// their moments, which otherwise lose precision after billions of
+/// samples.
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct CompensatedSum {
+    sum:           f64,
+    compensation:  f64,
+}
+
+impl CompensatedSum {
+    /// Creates a sum with the given starting value.
+
+    pub fn new(value: f64) -> CompensatedSum {
+        let sum          = value;
+        let compensation = 0.0;
+
+        CompensatedSum { sum, compensation }
+    }
+
+    /// Adds a value to the sum.
+
+    pub fn add(&mut self, addend: f64) {
+        let t = self.sum + addend;
+
+        if self.sum.abs() >= addend.abs() {
+            self.compensation += (self.sum - t) + addend;
+        } else {
+            self.compensation += (addend - t) + self.sum;
+        }
+
+        self.sum = t;
+    }
+
+    /// Returns the current value of the sum.
+
+    pub fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn test_compensated_sum() {
+        let mut sum = CompensatedSum::new(1.0);
+
+        // The example from Wikipedia loses both ones with a naive sum.
+
+        let large = 10.0_f64.powi(100);
+
+        sum.add(large);
+        sum.add(1.0);
+        sum.add(-large);
+
+        assert!(sum.value() == 2.0);
+
+        // Add many small values to a large one.  A naive sum rounds
+        // away every addend.
+
+        let mut sum   = CompensatedSum::new(1.0e16);
+        let mut naive = 1.0e16;
+
+        for _i in 0..1000 {
+            sum.add(1.0);
+            naive += 1.0;
+        }
+
+        assert!(sum.value() == 1.0e16 + 1000.0);
+        assert!(naive       == 1.0e16         );
+
+        assert!(CompensatedSum::default().value() == 0.0);
+    }
+
     #[test]
     fn run_tests() {
+        test_compensated_sum();
+
         let     limit  = 16;
         let mut inputs = Vec::<f64>::new();
 
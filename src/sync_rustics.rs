@@ -0,0 +1,460 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * SyncRustics
+//!     * SyncRustics wraps a RusticsArc (an `Arc<Mutex<dyn Rustics>>`) and
+//!       implements the Rustics trait itself.  Each method locks the
+//!       mutex, invokes the method on the underlying instance, and then
+//!       releases the lock, so callers need not use the arc_item_mut!
+//!       macro.
+//!
+//!     * Cloning a SyncRustics instance produces another handle to the
+//!       same underlying instance.
+//!
+//!     * An ArcSet member can be wrapped using SyncRustics::new() on the
+//!       RusticsArc returned by the add_* methods.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::arc_sets::ArcSet;
+//!     use rustics::sync_rustics::SyncRustics;
+//!
+//!     let mut set     = ArcSet::new("Server", 1, 0, &None);
+//!     let     member  = set.add_running_integer("Request Size", None);
+//!     let mut sizes   = SyncRustics::new(member.into());
+//!     let mut handle  = sizes.clone();
+//!
+//!     // Record data directly through the wrapper.
+//!
+//!     for i in 1..=100 {
+//!         sizes.record_i64(i);
+//!     }
+//!
+//!     // The clone shares the same instance.
+//!
+//!     handle.record_i64(101);
+//!
+//!     assert!(sizes.count() == 101);
+//!     assert!(sizes.title() == "Server ==> Request Size");
+//!
+//!     sizes.print();
+//!```
+
+use std::any::Any;
+use std::sync::Arc;
+
+use super::Rustics;
+use super::TimerBox;
+use super::PrinterOption;
+use super::ExportStats;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use super::Labels;
+use super::arc_sets::RusticsArc;
+use super::worst_tags::TaggedSample;
+use super::record_hook::RecordHookBox;
+use super::arc_item;
+use super::arc_item_mut;
+
+/// SyncRustics implements the Rustics trait on a RusticsArc.
+
+#[derive(Clone)]
+pub struct SyncRustics {
+    member: RusticsArc,
+    class:  String,
+}
+
+impl SyncRustics {
+    /// Creates a wrapper for the given Rustics instance.
+
+    pub fn new(member: RusticsArc) -> SyncRustics {
+        // The class is saved here because class() returns a
+        // reference, which can't outlive the lock.
+
+        let class = arc_item!(member).class().to_string();
+
+        SyncRustics { member, class }
+    }
+
+    /// Returns the underlying RusticsArc.
+
+    pub fn member(&self) -> RusticsArc {
+        self.member.clone()
+    }
+}
+
+impl Rustics for SyncRustics {
+    fn record_i64(&mut self, sample: i64) {
+        arc_item_mut!(self.member).record_i64(sample);
+    }
+
+    fn record_f64(&mut self, sample: f64) {
+        arc_item_mut!(self.member).record_f64(sample);
+    }
+
+    fn record_f32(&mut self, sample: f32) {
+        arc_item_mut!(self.member).record_f32(sample);
+    }
+
+    fn record_event(&mut self) {
+        arc_item_mut!(self.member).record_event();
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        arc_item_mut!(self.member).record_event_report()
+    }
+
+    fn record_time(&mut self, sample: i64) {
+        arc_item_mut!(self.member).record_time(sample);
+    }
+
+    fn record_interval(&mut self, timer: &mut TimerBox) {
+        arc_item_mut!(self.member).record_interval(timer);
+    }
+
+    fn record_i64_tagged(&mut self, sample: i64, tag: u64) {
+        arc_item_mut!(self.member).record_i64_tagged(sample, tag);
+    }
+
+    fn record_time_tagged(&mut self, sample: i64, tag: u64) {
+        arc_item_mut!(self.member).record_time_tagged(sample, tag);
+    }
+
+    fn has_worst_tags(&self) -> bool {
+        arc_item!(self.member).has_worst_tags()
+    }
+
+    fn supports_record_hook(&self) -> bool {
+        arc_item!(self.member).supports_record_hook()
+    }
+
+    fn set_record_hook(&mut self, hook: Option<RecordHookBox>) {
+        arc_item_mut!(self.member).set_record_hook(hook);
+    }
+
+    fn record_hook(&self) -> Option<RecordHookBox> {
+        arc_item!(self.member).record_hook()
+    }
+
+    fn supports_collection_time(&self) -> bool {
+        arc_item!(self.member).supports_collection_time()
+    }
+
+    fn set_collection_clock(&mut self, clock: Option<TimerBox>) {
+        arc_item_mut!(self.member).set_collection_clock(clock);
+    }
+
+    fn collection_seconds(&self) -> Option<f64> {
+        arc_item!(self.member).collection_seconds()
+    }
+
+    fn has_median(&self) -> bool {
+        arc_item!(self.member).has_median()
+    }
+
+    fn median(&self) -> f64 {
+        arc_item!(self.member).median()
+    }
+
+    fn mad(&self) -> f64 {
+        arc_item!(self.member).mad()
+    }
+
+    fn has_trimming(&self) -> bool {
+        arc_item!(self.member).has_trimming()
+    }
+
+    fn trimmed_mean(&self) -> f64 {
+        arc_item!(self.member).trimmed_mean()
+    }
+
+    fn outliers(&self) -> u64 {
+        arc_item!(self.member).outliers()
+    }
+
+    fn worst_tags(&self) -> Vec<TaggedSample> {
+        arc_item!(self.member).worst_tags()
+    }
+
+    fn name(&self) -> String {
+        arc_item!(self.member).name()
+    }
+
+    fn title(&self)-> String {
+        arc_item!(self.member).title()
+    }
+
+    fn class(&self) -> &str {
+        &self.class
+    }
+
+    fn count(&self) -> u64 {
+        arc_item!(self.member).count()
+    }
+
+    fn log_mode(&self) -> isize {
+        arc_item!(self.member).log_mode()
+    }
+
+    fn mean(&self) -> f64 {
+        arc_item!(self.member).mean()
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        arc_item!(self.member).standard_deviation()
+    }
+
+    fn variance(&self) -> f64 {
+        arc_item!(self.member).variance()
+    }
+
+    fn skewness(&self) -> f64 {
+        arc_item!(self.member).skewness()
+    }
+
+    fn kurtosis(&self) -> f64 {
+        arc_item!(self.member).kurtosis()
+    }
+
+    fn int_extremes(&self) -> bool {
+        arc_item!(self.member).int_extremes()
+    }
+
+    fn float_extremes(&self) -> bool {
+        arc_item!(self.member).float_extremes()
+    }
+
+    fn min_i64(&self) -> i64 {
+        arc_item!(self.member).min_i64()
+    }
+
+    fn min_f64(&self) -> f64 {
+        arc_item!(self.member).min_f64()
+    }
+
+    fn max_i64(&self) -> i64 {
+        arc_item!(self.member).max_i64()
+    }
+
+    fn max_f64(&self) -> f64 {
+        arc_item!(self.member).max_f64()
+    }
+
+    fn precompute(&mut self) {
+        arc_item_mut!(self.member).precompute();
+    }
+
+    fn clear(&mut self) {
+        arc_item_mut!(self.member).clear();
+    }
+
+    fn supports_reset_extremes(&self) -> bool {
+        arc_item!(self.member).supports_reset_extremes()
+    }
+
+    fn reset_extremes(&mut self) {
+        arc_item_mut!(self.member).reset_extremes();
+    }
+
+    fn export_stats(&self) -> ExportStats {
+        arc_item!(self.member).export_stats()
+    }
+
+    fn print(&self) {
+        arc_item!(self.member).print();
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        arc_item!(self.member).print_opts(printer, title);
+    }
+
+    fn set_title(&mut self, title: &str) {
+        arc_item_mut!(self.member).set_title(title);
+    }
+
+    fn set_label(&mut self, key: &str, value: &str) {
+        arc_item_mut!(self.member).set_label(key, value);
+    }
+
+    fn labels(&self) -> Labels {
+        arc_item!(self.member).labels()
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        arc_item!(self.member).log_histogram()
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        arc_item!(self.member).float_histogram()
+    }
+
+    // For internal use.
+
+    fn set_id(&mut self, id: usize) {
+        arc_item_mut!(self.member).set_id(id);
+    }
+
+    fn id(&self) -> usize {
+        arc_item!(self.member).id()
+    }
+
+    // Two wrappers are equal if they refer to the same instance.
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<SyncRustics>(other.generic()) {
+            Arc::ptr_eq(&self.member, &other.member)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arc_box;
+    use crate::counter::Counter;
+    use crate::running_float::RunningFloat;
+    use crate::running_float32::RunningFloat32;
+    use crate::running_integer::RunningInteger;
+    use crate::arc_sets::ArcSet;
+    use crate::tests::check_printer_box;
+    use crate::tests::check_printer_count_match;
+    use std::sync::Mutex;
+
+    fn test_forwarding() {
+        let     member = arc_box!(RunningFloat::new("Float", &None));
+        let mut stats  = SyncRustics::new(member.clone());
+
+        for i in 1..=4 {
+            stats.record_f64(i as f64);
+        }
+
+        assert!(stats.class()          == "float");
+        assert!(stats.name()           == "Float");
+        assert!(stats.count()          == 4);
+        assert!(stats.mean()           == 2.5);
+        assert!(stats.min_f64()        == 1.0);
+        assert!(stats.max_f64()        == 4.0);
+        assert!(stats.float_extremes());
+        assert!(!stats.int_extremes());
+        assert!(arc_item!(member).count() == 4);
+
+        stats.set_title("New Title");
+        assert!(arc_item!(member).title() == "New Title");
+
+        stats.clear();
+        assert!(arc_item!(member).count() == 0);
+    }
+
+    // Check the methods added to the trait after the basic ones.
+
+    fn test_optional() {
+        let mut integer = RunningInteger::new("Integer", &None);
+
+        integer.enable_median();
+
+        let     member = arc_box!(integer);
+        let mut stats  = SyncRustics::new(member.clone());
+
+        for i in 1..=101 {
+            stats.record_i64(i);
+        }
+
+        assert!(stats.has_median());
+        assert!(stats.supports_reset_extremes());
+        assert!(stats.median() == arc_item!(member).median());
+        assert!(stats.mad()    == arc_item!(member).mad()   );
+        assert!((stats.median() - 51.0).abs() < 2.0);
+
+        // Check that f32 samples reach the member's f32 path.
+
+        let     member = arc_box!(RunningFloat32::new("Float32", &None));
+        let mut stats  = SyncRustics::new(member.clone());
+
+        stats.record_f32(1.5);
+        stats.record_f32(f32::NAN);
+
+        assert!(stats.count() == 1);
+        assert!(stats.mean()  == 1.5);
+        assert!(!stats.has_median());
+    }
+
+    fn test_equals() {
+        let counter = arc_box!(Counter::new("Counter", &None));
+        let other   = arc_box!(Counter::new("Counter", &None));
+        let first   = SyncRustics::new(counter.clone());
+        let second  = first.clone();
+        let third   = SyncRustics::new(counter);
+        let fourth  = SyncRustics::new(other);
+
+        assert!( first.equals(&second));
+        assert!( first.equals(&third ));
+        assert!(!first.equals(&fourth));
+    }
+
+    fn test_set_member() {
+        let mut set     = ArcSet::new("Set", 1, 0, &None);
+        let     member  = set.add_running_integer("Member", None);
+        let mut stats   = SyncRustics::new(member.into());
+
+        stats.record_i64(1);
+        stats.record_i64(3);
+
+        assert!(stats.title() == "Set ==> Member");
+        assert!(stats.max_i64() == 3);
+
+        let expected =
+            [
+                "Set ==> Member",
+                "    Count                   2 ",
+                "    Minimum                 1 ",
+                "    Maximum                 3 ",
+                "    Log Mode                0 ",
+                "    Mode Value              1 ",
+                "    Mean             +2.00000 e+0  ",
+                "    Std Dev          +1.41421 e+0  ",
+                "    Variance         +2.00000 e+0  ",
+                "    Skewness         +0.00000 e+0  ",
+                "    Kurtosis         +0.00000 e+0  ",
+                "  Log Histogram",
+                "  -----------------------",
+                "    0:                 1                 0                 1                 0",
+                ""
+            ];
+
+        let printer = check_printer_box(&expected, true, false);
+
+        stats.print_opts(Some(printer.clone()), None);
+        assert!(check_printer_count_match(printer));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unsupported() {
+        let     member = arc_box!(Counter::new("Counter", &None));
+        let mut stats  = SyncRustics::new(member);
+
+        stats.record_f64(1.0);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_forwarding();
+        test_optional  ();
+        test_equals    ();
+        test_set_member();
+    }
+}
@@ -0,0 +1,180 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//!
+//! ## Type
+//!
+//! * PrintThrottle
+//!     * PrintThrottle limits how often output is produced.  It is used
+//!       to keep print() calls wired into frequently-hit paths, like an
+//!       error handler, from flooding a log.
+//!
+//!     * The time is read from a Timer instance, so the minimum interval
+//!       is given in ticks of that timer.  Tests can use a ClockTimer to
+//!       control the time.
+//!
+//!     * The first request is always allowed.  After that, a request is
+//!       allowed only if the interval has passed since the last allowed
+//!       request.  Other requests are dropped and counted.
+//!
+//!     * RcSet and ArcSet instances accept a throttle via the
+//!       set_print_throttle() method, which applies to print() and
+//!       print_opts().
+//!
+//! ## Example
+//!```
+//!     use rustics::time::DurationTimer;
+//!     use rustics::throttle::PrintThrottle;
+//!
+//!     // Allow one print every 10 seconds.  DurationTimer works in
+//!     // nanoseconds.
+//!
+//!     let interval = 10 * 1_000_000_000;
+//!     let throttle = PrintThrottle::new(interval, DurationTimer::new_box());
+//!
+//!     // The first print is allowed, but an immediate second one isn't.
+//!
+//!     assert!( throttle.allow());
+//!     assert!(!throttle.allow());
+//!     assert!(throttle.suppressed() == 1);
+//!```
+
+use std::cell::Cell;
+
+use super::TimerBox;
+use super::timer_mut;
+
+/// PrintThrottle implements a minimum interval between prints.
+
+pub struct PrintThrottle {
+    interval:   i64,
+    timer:      TimerBox,
+    elapsed:    Cell<i64>,
+    started:    Cell<bool>,
+    allowed:    Cell<u64>,
+    suppressed: Cell<u64>,
+}
+
+impl PrintThrottle {
+    /// Creates a throttle that allows at most one print per interval,
+    /// given in ticks of the timer.  The timer is started by this call.
+
+    pub fn new(interval: i64, timer: TimerBox) -> PrintThrottle {
+        if interval < 0 {
+            panic!("PrintThrottle::new:  The interval must not be negative.");
+        }
+
+        timer_mut!(timer).start();
+
+        let elapsed    = Cell::new(0);
+        let started    = Cell::new(false);
+        let allowed    = Cell::new(0);
+        let suppressed = Cell::new(0);
+
+        PrintThrottle { interval, timer, elapsed, started, allowed, suppressed }
+    }
+
+    /// Checks whether a print should be done now.  If so, a new
+    /// interval is started.
+
+    pub fn allow(&self) -> bool {
+        let elapsed = self.elapsed.get() + timer_mut!(self.timer).finish();
+
+        if self.started.get() && elapsed < self.interval {
+            self.elapsed.set(elapsed);
+            self.suppressed.set(self.suppressed.get() + 1);
+            return false;
+        }
+
+        self.started.set(true);
+        self.elapsed.set(0);
+        self.allowed.set(self.allowed.get() + 1);
+        true
+    }
+
+    /// Returns the minimum interval in timer ticks.
+
+    pub fn interval(&self) -> i64 {
+        self.interval
+    }
+
+    /// Returns the number of prints allowed.
+
+    pub fn allowed(&self) -> u64 {
+        self.allowed.get()
+    }
+
+    /// Returns the number of prints suppressed.
+
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
+
+    fn test_allow() {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+        let throttle  = PrintThrottle::new(100, timer);
+
+        assert!(throttle.interval() == 100);
+
+        assert!( throttle.allow());
+        assert!(!throttle.allow());
+
+        clock.borrow_mut().current += 60;
+
+        assert!(!throttle.allow());
+
+        // The time accumulates across suppressed requests.
+
+        clock.borrow_mut().current += 40;
+
+        assert!( throttle.allow());
+        assert!(!throttle.allow());
+
+        clock.borrow_mut().current += 1000;
+
+        assert!( throttle.allow());
+        assert!(throttle.allowed()    == 3);
+        assert!(throttle.suppressed() == 3);
+
+        // An interval of zero allows everything.
+
+        let timer    = ClockTimer::new_box(clock);
+        let throttle = PrintThrottle::new(0, timer);
+
+        assert!(throttle.allow());
+        assert!(throttle.allow());
+        assert!(throttle.suppressed() == 0);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_allow();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_interval() {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock);
+
+        let _ = PrintThrottle::new(-1, timer);
+    }
+}
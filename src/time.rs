@@ -12,10 +12,23 @@
 //!   * The Timer trait defines the interface for time operations needed by the
 //!     statistics functions.
 //!
+//!   * A timer optionally can support pause() and resume(), so that the time
+//!     spent blocked on another operation can be left out of an interval, and
+//!     elapsed(), which reads the current interval without starting a new one.
+//!     DurationTimer and ClockTimer support these methods.
+//!
 //! * DurationTimer
 //!   * DurationTimer provides a Timer interface to the standard rust Duration type,
 //!     which measures wall-clock time.
 //!
+//! * Default Timer
+//!   * The crate keeps a process-wide timer factory, which is used when a
+//!     constructor like RunningTime::new_default() is given no timer, and
+//!     by time_each() and SpanStats.  Statistics created this way share one
+//!     hz value, so their histograms and merges are directly comparable.
+//!     The factory returns a DurationTimer unless set_default_timer_factory()
+//!     is invoked before the first use.  It can be set only once.
+//!
 //! * SimpleClock
 //!   * The SimpleClock trait is an abstraction that can be used to implement
 //!     platform-specific Timer instances.  Something like a cycle counter would
@@ -26,6 +39,25 @@
 //!     SimpleClock underneath.  It is a standard bridge from
 //!     implementation-specific timers to the Rustics types.
 //!
+//! * CoarseClock and ThreadCpuClock
+//!   * These SimpleClock implementations read CLOCK_MONOTONIC_COARSE and
+//!     CLOCK_THREAD_CPUTIME_ID via clock_gettime.  CoarseClock is available
+//!     on Linux and Android, which define that clock, while ThreadCpuClock
+//!     is available on all Unix platforms.  The coarse clock is much cheaper
+//!     to read than the clock used by DurationTimer, at the cost of a
+//!     resolution of a few milliseconds, which makes it useful for long
+//!     intervals on hot paths.
+//!     The thread clock measures the CPU time used by the calling thread,
+//!     rather than the wall-clock time.  The new_box() functions return a
+//!     ClockTimer wrapping the clock.
+//!
+//! * TscClock
+//!   * TscClock is a SimpleClock that reads the x86_64 time stamp counter via
+//!     rdtsc, which is the cheapest clock available on that platform.  Its
+//!     frequency is calibrated against the wall clock when the clock is
+//!     created, or can be given by the caller.  It assumes an invariant TSC,
+//!     as found on current processors.  It is available only on x86_64.
+//!
 //! ## Example
 //!```
 //!     use rustics::time::SimpleClock;
@@ -76,6 +108,7 @@
 use std::time::Instant;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::sync::OnceLock;
 
 use crate::TimerBox;
 use crate::timer_box;
@@ -105,6 +138,37 @@ pub trait Timer {
     /// hz() returns the frequency of the underlying clock.
 
     fn hz(&self) -> u128;           // get the clock hz
+
+    /// The pause() method stops the timer from counting time until
+    /// resume() is invoked.  The time spent paused is not included in
+    /// the intervals returned by finish() or elapsed().  Pausing a
+    /// paused timer has no effect.  The default implementation panics.
+
+    fn pause(&mut self) {
+        panic!("Timer::pause:  not supported");
+    }
+
+    /// The resume() method restarts a paused timer.  Resuming a timer
+    /// that isn't paused has no effect.  The default implementation
+    /// panics.
+
+    fn resume(&mut self) {
+        panic!("Timer::resume:  not supported");
+    }
+
+    /// Returns whether the timer is paused.
+
+    fn is_paused(&self) -> bool {
+        false
+    }
+
+    /// The elapsed() method returns the time in ticks since the start
+    /// of the current interval, less any time spent paused, without
+    /// starting a new interval.  The default implementation panics.
+
+    fn elapsed(&self) -> i64 {
+        panic!("Timer::elapsed:  not supported");
+    }
 }
 
 /// DurationTimer uses the Rust standard time struct Duration to
@@ -115,23 +179,34 @@ pub trait Timer {
 pub struct DurationTimer {
     start:      Instant,
     previous:   u128,
+    paused:     u128,
+    paused_at:  Option<u128>,
 }
 
 impl Timer for DurationTimer {
     fn start(&mut self) {
-        self.start    = Instant::now();
-        self.previous = 0;
+        self.start     = Instant::now();
+        self.previous  = 0;
+        self.paused    = 0;
+        self.paused_at = None;
     }
 
     // Get the current elapsed time and subtract
     // "previous" from it to get the time since the
-    // last "finish" call.  Then save this current
-    // time as the new "previous".
+    // last "finish" call, then subtract the time spent
+    // paused.  Then save this current time as the new
+    // "previous".  A paused timer stays paused.
 
     fn finish(&mut self) -> i64 {
         let end_time  = self.start.elapsed().as_nanos();
-        let result    = end_time - self.previous;
+        let paused    = paused_ticks(self.paused, self.paused_at, end_time);
+        let result    = end_time - self.previous - paused;
         self.previous = end_time;
+        self.paused   = 0;
+
+        if self.paused_at.is_some() {
+            self.paused_at = Some(end_time);
+        }
 
         std::cmp::min(result, i64::MAX as u128) as i64
     }
@@ -141,14 +216,50 @@ impl Timer for DurationTimer {
     fn hz(&self) -> u128 {
         1_000_000_000
     }
+
+    fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.start.elapsed().as_nanos());
+        }
+    }
+
+    fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused += self.start.elapsed().as_nanos() - paused_at;
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    fn elapsed(&self) -> i64 {
+        let now    = self.start.elapsed().as_nanos();
+        let paused = paused_ticks(self.paused, self.paused_at, now);
+        let result = now - self.previous - paused;
+
+        std::cmp::min(result, i64::MAX as u128) as i64
+    }
+}
+
+// Compute the total time paused in the current interval, including
+// any pause still in progress.
+
+fn paused_ticks(paused: u128, paused_at: Option<u128>, now: u128) -> u128 {
+    match paused_at {
+        Some(paused_at) => { paused + (now - paused_at) }
+        None            => { paused                     }
+    }
 }
 
 impl DurationTimer {
     pub fn new() -> DurationTimer {
-        let start    = Instant::now();
-        let previous = 0;
+        let start     = Instant::now();
+        let previous  = 0;
+        let paused    = 0;
+        let paused_at = None;
 
-        DurationTimer { start, previous }
+        DurationTimer { start, previous, paused, paused_at }
     }
 
     pub fn new_box() -> TimerBox {
@@ -164,6 +275,43 @@ impl Default for DurationTimer {
     }
 }
 
+/// TimerFactory creates the timer for statistics that aren't given one.
+
+pub type TimerFactory = fn() -> TimerBox;
+
+static DEFAULT_TIMER_FACTORY: OnceLock<TimerFactory> = OnceLock::new();
+
+/// Sets the process-wide timer factory.  This function returns false
+/// if the factory has already been set or used, in which case the
+/// existing factory is kept, so that all the default timers in the
+/// process use the same hz.
+
+pub fn set_default_timer_factory(factory: TimerFactory) -> bool {
+    DEFAULT_TIMER_FACTORY.set(factory).is_ok()
+}
+
+/// Returns the process-wide timer factory.  Once this function has
+/// been invoked, the factory can't be changed.
+
+pub fn default_timer_factory() -> TimerFactory {
+    *DEFAULT_TIMER_FACTORY.get_or_init(|| DurationTimer::new_box)
+}
+
+/// Creates a timer using the process-wide factory.
+
+pub fn default_timer() -> TimerBox {
+    default_timer_factory()()
+}
+
+/// Returns the hz of the timers from the process-wide factory.
+
+pub fn default_hz() -> u128 {
+    let timer = default_timer();
+    let hz    = timer.borrow().hz();
+
+    hz
+}
+
 /// SimpleClock can be implemented for platform-specific clocks.
 /// The instances can then be wrapped in a ClockTimer instance.
 
@@ -188,17 +336,27 @@ pub struct ClockTimer {
     start:      u128,
     clock:      Rc<RefCell<dyn SimpleClock>>,
     hz:         u128,
+    paused:     u128,
+    paused_at:  Option<u128>,
 }
 
 impl Timer for ClockTimer {
     fn start(&mut self) {
-        self.start = self.clock.borrow_mut().get_time();
+        self.start     = self.clock.borrow_mut().get_time();
+        self.paused    = 0;
+        self.paused_at = None;
     }
 
     fn finish(&mut self) -> i64 {
         let end_time = self.clock.borrow_mut().get_time();
-        let ticks    = end_time - self.start;
+        let paused   = paused_ticks(self.paused, self.paused_at, end_time);
+        let ticks    = end_time - self.start - paused;
         self.start   = end_time;
+        self.paused  = 0;
+
+        if self.paused_at.is_some() {
+            self.paused_at = Some(end_time);
+        }
 
         ticks as i64
     }
@@ -206,14 +364,39 @@ impl Timer for ClockTimer {
     fn hz(&self) -> u128 {
         self.hz
     }
+
+    fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.clock.borrow_mut().get_time());
+        }
+    }
+
+    fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused += self.clock.borrow_mut().get_time() - paused_at;
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    fn elapsed(&self) -> i64 {
+        let now    = self.clock.borrow_mut().get_time();
+        let paused = paused_ticks(self.paused, self.paused_at, now);
+
+        (now - self.start - paused) as i64
+    }
 }
 
 impl ClockTimer {
     pub fn new(clock: Rc<RefCell<dyn SimpleClock>>) -> ClockTimer {
-        let start = clock.borrow_mut().get_time();
-        let hz    = clock.borrow().hz();
+        let start     = clock.borrow_mut().get_time();
+        let hz        = clock.borrow().hz();
+        let paused    = 0;
+        let paused_at = None;
 
-        ClockTimer { start, clock, hz }
+        ClockTimer { start, clock, hz, paused, paused_at }
     }
 
     pub fn new_box(clock: Rc<RefCell<dyn SimpleClock>>) -> TimerBox {
@@ -223,6 +406,217 @@ impl ClockTimer {
     }
 }
 
+// Read a clock via clock_gettime.  The libc crate supplies the
+// timespec layout for each target, which matters on 32-bit targets
+// where time_t might be 64 bits wide.  Some targets pad the struct
+// with private fields, so it is zeroed rather than built field by
+// field.
+
+#[cfg(unix)]
+mod posix {
+    use libc::clockid_t;
+    use libc::timespec;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const CLOCK_MONOTONIC_COARSE:  clockid_t = libc::CLOCK_MONOTONIC_COARSE;
+    pub const CLOCK_THREAD_CPUTIME_ID: clockid_t = libc::CLOCK_THREAD_CPUTIME_ID;
+
+    fn to_nanos(time: &timespec) -> u128 {
+        time.tv_sec as u128 * 1_000_000_000 + time.tv_nsec as u128
+    }
+
+    // Return the time in nanoseconds.  These clocks are always
+    // available on the supported platforms, so a failure is a bug.
+
+    pub fn read(clock_id: clockid_t) -> u128 {
+        let mut time   = unsafe { std::mem::zeroed::<timespec>() };
+        let     result = unsafe { libc::clock_gettime(clock_id, &mut time) };
+
+        if result != 0 {
+            panic!("posix::read:  clock_gettime failed for clock {}", clock_id);
+        }
+
+        to_nanos(&time)
+    }
+
+    // Return the resolution in nanoseconds.
+
+    pub fn resolution(clock_id: clockid_t) -> u128 {
+        let mut time   = unsafe { std::mem::zeroed::<timespec>() };
+        let     result = unsafe { libc::clock_getres(clock_id, &mut time) };
+
+        if result != 0 {
+            panic!("posix::resolution:  clock_getres failed for clock {}", clock_id);
+        }
+
+        to_nanos(&time)
+    }
+}
+
+/// CoarseClock reads CLOCK_MONOTONIC_COARSE, which is fast to read
+/// but is updated only every few milliseconds.  It works in units of
+/// nanoseconds.
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Clone, Default)]
+pub struct CoarseClock {
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl CoarseClock {
+    pub fn new() -> CoarseClock {
+        CoarseClock { }
+    }
+
+    /// Creates a ClockTimer using a CoarseClock.
+
+    pub fn new_box() -> TimerBox {
+        let clock = Rc::new(RefCell::new(CoarseClock::new()));
+
+        ClockTimer::new_box(clock)
+    }
+
+    /// Returns the resolution of the clock in nanoseconds.
+
+    pub fn resolution(&self) -> u128 {
+        posix::resolution(posix::CLOCK_MONOTONIC_COARSE)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl SimpleClock for CoarseClock {
+    fn get_time(&mut self) -> u128 {
+        posix::read(posix::CLOCK_MONOTONIC_COARSE)
+    }
+
+    fn hz(&self) -> u128 {
+        1_000_000_000
+    }
+}
+
+/// ThreadCpuClock reads CLOCK_THREAD_CPUTIME_ID, which gives the CPU
+/// time used by the calling thread.  A timer using this clock thus
+/// must be read on the thread being measured.  It works in units of
+/// nanoseconds.
+
+#[cfg(unix)]
+#[derive(Clone, Default)]
+pub struct ThreadCpuClock {
+}
+
+#[cfg(unix)]
+impl ThreadCpuClock {
+    pub fn new() -> ThreadCpuClock {
+        ThreadCpuClock { }
+    }
+
+    /// Creates a ClockTimer using a ThreadCpuClock.
+
+    pub fn new_box() -> TimerBox {
+        let clock = Rc::new(RefCell::new(ThreadCpuClock::new()));
+
+        ClockTimer::new_box(clock)
+    }
+
+    /// Returns the resolution of the clock in nanoseconds.
+
+    pub fn resolution(&self) -> u128 {
+        posix::resolution(posix::CLOCK_THREAD_CPUTIME_ID)
+    }
+}
+
+#[cfg(unix)]
+impl SimpleClock for ThreadCpuClock {
+    fn get_time(&mut self) -> u128 {
+        posix::read(posix::CLOCK_THREAD_CPUTIME_ID)
+    }
+
+    fn hz(&self) -> u128 {
+        1_000_000_000
+    }
+}
+
+/// TscClock reads the x86_64 time stamp counter.  The counter runs at
+/// a fixed frequency on processors with an invariant TSC, which is
+/// measured against the wall clock by new().
+
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone)]
+pub struct TscClock {
+    hz:  u128,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl TscClock {
+    /// Creates a clock, calibrating the frequency over 20 milliseconds.
+
+    pub fn new() -> TscClock {
+        let hz = TscClock::calibrate(std::time::Duration::from_millis(20));
+
+        TscClock { hz }
+    }
+
+    /// Creates a clock with a known frequency, avoiding the calibration.
+
+    pub fn with_hz(hz: u128) -> TscClock {
+        if hz == 0 {
+            panic!("TscClock::with_hz:  The frequency must be positive.");
+        }
+
+        TscClock { hz }
+    }
+
+    /// Creates a ClockTimer using a calibrated TscClock.
+
+    pub fn new_box() -> TimerBox {
+        let clock = Rc::new(RefCell::new(TscClock::new()));
+
+        ClockTimer::new_box(clock)
+    }
+
+    /// Measures the frequency of the counter by comparing it with the
+    /// wall clock over the given interval.  A longer interval gives a
+    /// more accurate result.
+
+    pub fn calibrate(interval: std::time::Duration) -> u128 {
+        let start       = Instant::now();
+        let start_ticks = TscClock::read();
+
+        while start.elapsed() < interval {
+            std::hint::spin_loop();
+        }
+
+        let ticks       = TscClock::read() - start_ticks;
+        let nanoseconds = start.elapsed().as_nanos();
+
+        std::cmp::max(ticks * 1_000_000_000 / nanoseconds, 1)
+    }
+
+    fn read() -> u128 {
+        // rdtsc is available on every x86_64 processor.
+
+        unsafe { std::arch::x86_64::_rdtsc() as u128 }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Default for TscClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl SimpleClock for TscClock {
+    fn get_time(&mut self) -> u128 {
+        TscClock::read()
+    }
+
+    fn hz(&self) -> u128 {
+        self.hz
+    }
+}
+
 /// Converts a TimerBox instance into the shareable form,
 /// currently `Rc<RefCell<dyn Timer>>`.
 
@@ -354,11 +748,196 @@ pub mod tests {
         }
     }
 
+    fn test_pause() {
+        let     current      = 0;
+        let     increment    = 0;
+        let     simple_clock = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let mut timer        = ClockTimer::new(simple_clock.clone());
+
+        let advance = |ticks: u128| simple_clock.borrow_mut().current += ticks;
+
+        // Measure a request that spends 30 ticks blocked on another
+        // call.
+
+        advance(10);
+        assert!(timer.elapsed() == 10);
+
+        timer.pause();
+        timer.pause();
+        assert!(timer.is_paused());
+
+        advance(30);
+        assert!(timer.elapsed() == 10);
+
+        timer.resume();
+        timer.resume();
+        assert!(!timer.is_paused());
+
+        advance(5);
+
+        // elapsed() doesn't start a new interval.
+
+        assert!(timer.elapsed() == 15);
+        assert!(timer.finish()  == 15);
+        assert!(timer.elapsed() == 0 );
+
+        // A timer that is paused at finish() stays paused.
+
+        advance(4);
+        timer.pause();
+        advance(6);
+
+        assert!(timer.finish() == 4);
+        assert!(timer.is_paused());
+
+        advance(8);
+        timer.resume();
+        advance(2);
+
+        assert!(timer.finish() == 2);
+
+        // start() clears a pause.
+
+        timer.pause();
+        timer.start();
+        advance(3);
+
+        assert!(!timer.is_paused());
+        assert!(timer.elapsed() == 3);
+
+        // Check that the DurationTimer excludes the paused time.
+
+        let mut timer = DurationTimer::new();
+
+        timer.pause();
+        sleep(Duration::from_millis(50));
+        assert!(timer.elapsed() < 25_000_000);
+        timer.resume();
+
+        let elapsed = timer.elapsed();
+        let finish  = timer.finish();
+
+        assert!(finish >= elapsed);
+        assert!(finish < 25_000_000);
+    }
+
+    // Check that the thread clock counts CPU time and not time spent
+    // sleeping.
+
+    #[cfg(unix)]
+    #[test]
+    fn test_thread_clock() {
+        let     timer = ThreadCpuClock::new_box();
+        let     timer = timer_mut!(timer);
+        let mut sum   = 0_u64;
+
+        assert!(timer.hz() == 1_000_000_000);
+        assert!(ThreadCpuClock::new().resolution() > 0);
+
+        timer.start();
+        sleep(Duration::from_millis(100));
+
+        assert!(timer.finish() < 50_000_000);
+
+        for i in 0..10_000_000_u64 {
+            sum = sum.wrapping_add(std::hint::black_box(i));
+        }
+
+        assert!(sum > 0);
+        assert!(timer.finish() > 0);
+    }
+
+    // Check that the coarse clock follows the wall clock.
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_coarse_clock() {
+        let coarse     = CoarseClock::new_box();
+        let coarse     = timer_mut!(coarse);
+        let resolution = CoarseClock::new().resolution() as i64;
+
+        assert!(resolution > 0);
+
+        coarse.start();
+        sleep(Duration::from_millis(100));
+
+        let interval = coarse.finish();
+
+        assert!(interval >= 100_000_000 - resolution);
+        assert!(interval <  200_000_000 + resolution);
+    }
+
+    // Check that the calibrated frequency gives intervals that roughly
+    // match the wall clock.  The bounds are loose since the test might
+    // be descheduled.
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_tsc_clock() {
+        let clock = TscClock::new();
+        let hz    = clock.hz();
+
+        assert!(hz > 1_000_000);
+        assert!(TscClock::with_hz(hz).hz() == hz);
+
+        let timer = ClockTimer::new_box(Rc::new(RefCell::new(clock)));
+        let timer = timer_mut!(timer);
+
+        timer.start();
+        sleep(Duration::from_millis(50));
+
+        let seconds = timer.finish() as f64 / hz as f64;
+
+        assert!(seconds >= 0.04);
+        assert!(seconds <  1.00);
+
+        let timer = TscClock::new_box();
+
+        assert!(timer_mut!(timer).finish() >= 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_no_pause() {
+        struct PlainTimer { }
+
+        impl Timer for PlainTimer {
+            fn start(&mut self) { }
+
+            fn finish(&mut self) -> i64 {
+                0
+            }
+
+            fn hz(&self) -> u128 {
+                1
+            }
+        }
+
+        let mut timer = PlainTimer { };
+
+        assert!(!timer.is_paused());
+        timer.pause();
+    }
+
+    fn test_default_timer() {
+        let timer = default_timer();
+        let hz    = timer.borrow().hz();
+
+        assert!(hz == default_hz());
+
+        // The factory is fixed once it has been used.
+
+        assert!(!set_default_timer_factory(DurationTimer::new_box));
+        assert!(default_hz() == hz);
+    }
+
     #[test]
     pub fn run_tests() {
         simple_duration_test();
         simple_default_test ();
         simple_test_clock   ();
         sample_usage        ();
+        test_pause          ();
+        test_default_timer  ();
     }
 }
@@ -0,0 +1,662 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * TimeBoundedWindow
+//!     * TimeBoundedWindow maintains a set consisting of the i64 samples
+//!       recorded during the last n seconds, as measured by a Timer
+//!       instance.  IntegerWindow, in contrast, keeps the last n samples,
+//!       no matter when they were recorded.
+//!
+//!     * Samples older than the period are evicted lazily whenever a
+//!       sample is recorded or a statistic is queried, so the summary
+//!       statistics, including the minimum and maximum, always reflect
+//!       only the current period.
+//!
+//!     * The timer is read via finish() to determine the current time,
+//!       so it should not be shared with other users.
+//!
+//!     * Like IntegerWindow, this type maintains a log histogram that
+//!       contains counts of all the samples seen, not just the samples
+//!       in the current period.
+//!
+//! ## Example
+//!```
+//!    use rustics::Rustics;
+//!    use rustics::time::DurationTimer;
+//!    use rustics::time_bounded_window::TimeBoundedWindow;
+//!
+//!    // Create an instance that keeps the queue depths seen in the
+//!    // last 60 seconds of wall-clock time.
+//!
+//!    let timer = DurationTimer::new_box();
+//!
+//!    let mut queue_depth =
+//!        TimeBoundedWindow::new("Queue Depth", 60, timer, &None);
+//!
+//!    for i in 1..=100 {
+//!       queue_depth.record_i64(i);
+//!    }
+//!
+//!    // All the samples are recent, so they are all in the window.
+//!
+//!    assert!(queue_depth.count()   == 100);
+//!    assert!(queue_depth.min_i64() == 1  );
+//!    assert!(queue_depth.max_i64() == 100);
+//!
+//!    queue_depth.print();
+//!```
+
+use std::any::Any;
+use std::rc::Rc;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use super::Rustics;
+use super::copy_log_histogram;
+use super::Printer;
+use super::PrinterBox;
+use super::ExportStats;
+use super::PrinterOption;
+use super::PrintOption;
+use super::Units;
+use super::HistoOpts;
+use super::TimerBox;
+use super::Histogram;
+use super::LogHistogramBox;
+use super::FloatHistogramBox;
+use crate::printable::Printable;
+use crate::log_histogram::LogHistogram;
+use super::integer_window::Crunched;
+use super::printer_mut;
+use super::timer_mut;
+use super::stats_math::compute_variance;
+use super::stats_math::compute_skewness;
+use super::stats_math::compute_kurtosis;
+use super::sum::kbk_sum;
+use super::sum::kbk_sum_sort;
+use super::parse_print_opts;
+use super::parse_fields;
+use super::printable::PrintFields;
+
+/// A TimeBoundedWindow instance collects integer data samples
+/// recorded during a fixed period of time.  It also maintains a
+/// histogram based on all the samples seen.
+///
+/// See the module documentation for sample code.
+
+pub struct TimeBoundedWindow {
+    name:           String,
+    title:          String,
+    id:             usize,
+    seconds:        u64,
+    period:         i64,
+    timer:          TimerBox,
+
+    // The current time, in ticks since the instance was created, and
+    // the samples in the window, with the time each was recorded.
+    // The queries update these fields, and so need interior
+    // mutability.  Reading the timer restarts it, so the current
+    // time is shared with any clones, along with the timer.
+
+    now:            Rc<Cell<i64>>,
+    samples:        RefCell<VecDeque<(i64, i64)>>,
+
+    log_histogram:  LogHistogramBox,
+
+    printer:        PrinterBox,
+    units:          Units,
+    histo_opts:     HistoOpts,
+    fields:         PrintFields,
+}
+
+// Clone makes a deep copy of the samples and the histogram.  The
+// printer is shared, as are the timer and the current time:  each
+// read of the timer consumes the elapsed ticks, so the copies must
+// keep one clock between them for both to see time advance.
+
+impl Clone for TimeBoundedWindow {
+    fn clone(&self) -> TimeBoundedWindow {
+        let log_histogram = copy_log_histogram(&self.log_histogram);
+
+        TimeBoundedWindow {
+            name:          self.name.clone(),
+            title:         self.title.clone(),
+            id:            self.id,
+            seconds:       self.seconds,
+            period:        self.period,
+            timer:         self.timer.clone(),
+            now:           self.now.clone(),
+            samples:       self.samples.clone(),
+            log_histogram,
+            printer:       self.printer.clone(),
+            units:         self.units.clone(),
+            histo_opts:    self.histo_opts,
+            fields:        self.fields
+        }
+    }
+}
+
+impl TimeBoundedWindow {
+    /// Creates an instance that keeps the samples recorded during the
+    /// last "seconds" seconds, as measured by the given timer.
+
+    pub fn new(name: &str, seconds: u64, timer: TimerBox, print_opts: &PrintOption)
+            -> TimeBoundedWindow {
+        if seconds == 0 {
+            panic!("TimeBoundedWindow::new:  The period is zero.");
+        }
+
+        let hz = timer.borrow().hz();
+
+        if hz > i64::MAX as u128 {
+            panic!("TimeBoundedWindow::new:  The timer frequency is too high.");
+        }
+
+        timer_mut!(timer).start();
+
+        let name          = String::from(name);
+        let id            = usize::MAX;
+        let period        = (seconds as i64).saturating_mul(hz as i64);
+        let now           = Rc::new(Cell::new(0));
+        let samples       = RefCell::new(VecDeque::new());
+        let log_histogram = LogHistogram::new();
+        let log_histogram = Rc::from(RefCell::new(log_histogram));
+
+        let (printer, title, units, histo_opts) = parse_print_opts(print_opts, &name);
+        let fields = parse_fields(print_opts);
+
+        TimeBoundedWindow {
+            name,
+            title,
+            id,
+            seconds,
+            period,
+            timer,
+            now,
+            samples,
+            log_histogram,
+            printer,
+            units,
+            histo_opts,
+            fields
+        }
+    }
+
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+    }
+
+    /// Returns the length of the window, in seconds.
+
+    pub fn seconds(&self) -> u64 {
+        self.seconds
+    }
+
+    // Read the timer to advance the current time, and then drop
+    // any samples that have aged out of the window.
+
+    fn evict(&self) {
+        let elapsed = timer_mut!(self.timer).finish();
+        let now     = self.now.get() + elapsed;
+
+        self.now.set(now);
+
+        let mut samples = self.samples.borrow_mut();
+
+        while let Some((time, _)) = samples.front() {
+            if now - *time < self.period {
+                break;
+            }
+
+            samples.pop_front();
+        }
+    }
+
+    /// Returns a copy of the samples currently in the window, oldest
+    /// first.
+
+    pub fn values(&self) -> Vec<i64> {
+        self.evict();
+
+        self.samples.borrow().iter().map(|(_, sample)| *sample).collect()
+    }
+
+    /// Gather the summary information and compute summary statistics
+    /// for the current samples in the window.
+
+    pub fn crunch(&self) -> Crunched {
+        Self::crunch_values(&self.values())
+    }
+
+    // Compute the summary data for a snapshot of the window.  The
+    // clock can advance between queries, so each statistic is
+    // computed from a single snapshot.
+
+    fn crunch_values(values: &[i64]) -> Crunched {
+        let mut samples: Vec<f64> = values.iter().map(|x| *x as f64).collect();
+
+        if samples.is_empty() {
+            return Crunched::zero();
+        }
+
+        let sum  = kbk_sum_sort(&mut samples);
+        let mean = sum / samples.len() as f64;
+
+        let mut vec_2 = Vec::new();
+        let mut vec_3 = Vec::new();
+        let mut vec_4 = Vec::new();
+
+        for sample in samples.iter() {
+            let distance = *sample - mean;
+            let square   = distance * distance;
+
+            vec_2.push(square           );
+            vec_3.push(square * distance);
+            vec_4.push(square * square  );
+        }
+
+        let moment_2 = kbk_sum(&vec_2);
+        let moment_3 = kbk_sum(&vec_3);
+        let moment_4 = kbk_sum(&vec_4);
+
+        Crunched { mean, sum, moment_2, moment_3, moment_4 }
+    }
+
+    fn compute_min(&self) -> i64 {
+        self.values().into_iter().min().unwrap_or(0)
+    }
+
+    fn compute_max(&self) -> i64 {
+        self.values().into_iter().max().unwrap_or(0)
+    }
+
+    pub fn get_printable(&self) -> Printable {
+        let values     = self.values();
+        let n          = values.len() as u64;
+        let nans       = 0;
+        let infinities = 0;
+        let min_i64    = values.iter().copied().min().unwrap_or(0);
+        let max_i64    = values.iter().copied().max().unwrap_or(0);
+        let min_f64    = f64::MIN;
+        let max_f64    = f64::MAX;
+        let log_mode   = self.log_histogram.borrow().log_mode() as i64;
+        let mode_value = 0.0;
+        let units      = self.units.clone();
+
+        let crunched   = Self::crunch_values(&values);
+        let mean       = crunched.mean;
+        let variance   = compute_variance(n, crunched.moment_2);
+        let skewness   = compute_skewness(n, crunched.moment_2, crunched.moment_3);
+        let kurtosis   = compute_kurtosis(n, crunched.moment_2, crunched.moment_4);
+
+        Printable {
+            n,         nans,  infinities,  min_i64,   max_i64,   min_f64,    max_f64,
+            log_mode,  mean,  variance,    skewness,  kurtosis,  mode_value,  units
+        }
+    }
+}
+
+impl Rustics for TimeBoundedWindow {
+    fn record_i64(&mut self, sample: i64) {
+        self.evict();
+
+        let now = self.now.get();
+
+        self.samples.borrow_mut().push_back((now, sample));
+        self.log_histogram.borrow_mut().record(sample);
+    }
+
+    fn record_f64(&mut self, _sample: f64) {
+        panic!("TimeBoundedWindow::record_f64:  not supported");
+    }
+
+    fn record_event(&mut self) {
+        panic!("TimeBoundedWindow::record_event:  not supported");
+    }
+
+    fn record_event_report(&mut self) -> i64 {
+        panic!("TimeBoundedWindow::record_event_report:  not supported");
+    }
+
+    fn record_time(&mut self, _sample: i64) {
+        panic!("TimeBoundedWindow::record_time:  not supported");
+    }
+
+    fn record_interval(&mut self, _timer: &mut TimerBox) {
+        panic!("TimeBoundedWindow::record_interval:  not supported");
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn class(&self) -> &str {
+        "integer"
+    }
+
+    fn count(&self) -> u64 {
+        self.evict();
+
+        self.samples.borrow().len() as u64
+    }
+
+    fn log_mode(&self) -> isize {
+        self.log_histogram.borrow().log_mode()
+    }
+
+    fn mean(&self) -> f64 {
+        self.crunch().mean
+    }
+
+    fn standard_deviation(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    fn variance(&self) -> f64 {
+        let values   = self.values();
+        let crunched = Self::crunch_values(&values);
+
+        compute_variance(values.len() as u64, crunched.moment_2)
+    }
+
+    fn skewness(&self) -> f64 {
+        let values   = self.values();
+        let crunched = Self::crunch_values(&values);
+
+        compute_skewness(values.len() as u64, crunched.moment_2, crunched.moment_3)
+    }
+
+    fn kurtosis(&self) -> f64 {
+        let values   = self.values();
+        let crunched = Self::crunch_values(&values);
+
+        compute_kurtosis(values.len() as u64, crunched.moment_2, crunched.moment_4)
+    }
+
+    fn int_extremes(&self) -> bool {
+        true
+    }
+
+    fn float_extremes(&self) -> bool {
+        false
+    }
+
+    fn min_f64(&self) -> f64 {
+        panic!("TimeBoundedWindow::min_f64:  not supported");
+    }
+
+    fn max_f64(&self) -> f64 {
+        panic!("TimeBoundedWindow::max_f64:  not supported");
+    }
+
+    fn min_i64(&self) -> i64 {
+        self.compute_min()
+    }
+
+    fn max_i64(&self) -> i64 {
+        self.compute_max()
+    }
+
+    // The window contents depend on the current time, so there's
+    // nothing to precompute.
+
+    fn precompute(&mut self) {
+    }
+
+    fn clear(&mut self) {
+        self.samples.borrow_mut().clear();
+        self.log_histogram.borrow_mut().clear();
+    }
+
+    fn print(&self) {
+        self.print_opts(None, None);
+    }
+
+    fn print_opts(&self, printer: PrinterOption, title: Option<&str>) {
+        let printer_box =
+            if let Some(printer) = printer {
+                printer
+            } else {
+                self.printer.clone()
+            };
+
+        let title =
+            if let Some(title) = title {
+                title
+            } else {
+                &self.title
+            };
+
+        let printable = self.get_printable();
+        let printer   = printer_mut!(printer_box);
+
+        printer.print(title);
+        printable.print_common_i64_fields  (self.fields, printer);
+        printable.print_common_float_fields(self.fields, printer);
+
+        if self.histo_opts.print_histogram(self.fields) {
+            self.log_histogram.borrow().print_opts(printer, &self.histo_opts);
+        }
+
+        if self.histo_opts.print_cdf {
+            self.log_histogram.borrow().print_cdf(printer);
+        }
+
+        printer.print("");
+    }
+
+    fn log_histogram(&self) -> Option<LogHistogramBox> {
+        Some(self.log_histogram.clone())
+    }
+
+    fn float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = String::from(title)
+    }
+
+    // For internal use
+
+    fn equals(&self, other: &dyn Rustics) -> bool {
+        if let Some(other) = <dyn Any>::downcast_ref::<TimeBoundedWindow>(other.generic()) {
+            std::ptr::eq(self, other)
+        } else {
+            false
+        }
+    }
+
+    fn generic(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn export_stats(&self) -> ExportStats {
+        let printable       = self.get_printable();
+        let log_histogram   = Some(self.log_histogram.clone());
+        let float_histogram = None;
+
+        ExportStats { printable, log_histogram, float_histogram }
+    }
+}
+
+impl Histogram for TimeBoundedWindow {
+    fn print_histogram(&self, printer: &mut dyn Printer) {
+        self.log_histogram.borrow().print_opts(printer, &self.histo_opts);
+    }
+
+    fn clear_histogram(&mut self) {
+        self.log_histogram.borrow_mut().clear();
+    }
+
+    fn to_log_histogram(&self) -> Option<LogHistogramBox> {
+        Some(self.log_histogram.clone())
+    }
+
+    fn to_float_histogram(&self) -> Option<FloatHistogramBox> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::ClockTimer;
+    use crate::time::tests::TestSimpleClock;
+
+    const HZ: u64 = 1_000_000_000;
+
+    // Make a window with a clock that only moves when the test
+    // advances it.
+
+    fn make_window(seconds: u64) -> (TimeBoundedWindow, Rc<RefCell<TestSimpleClock>>) {
+        let current   = 0;
+        let increment = 0;
+        let clock     = Rc::new(RefCell::new(TestSimpleClock { current, increment }));
+        let timer     = ClockTimer::new_box(clock.clone());
+        let window    = TimeBoundedWindow::new("Test Window", seconds, timer, &None);
+
+        (window, clock)
+    }
+
+    fn advance(clock: &Rc<RefCell<TestSimpleClock>>, seconds: u64) {
+        clock.borrow_mut().current += (seconds * HZ) as u128;
+    }
+
+    fn test_eviction() {
+        let (mut stats, clock) = make_window(60);
+
+        assert!(stats.class()   == "integer");
+        assert!(stats.seconds() == 60);
+        assert!(stats.count()   == 0);
+        assert!(stats.mean()    == 0.0);
+
+        // Record one sample per second for 100 seconds.  Only the
+        // last 60 should remain.
+
+        for i in 1..=100 {
+            stats.record_i64(i);
+            advance(&clock, 1);
+        }
+
+        // The clock is now at 100 seconds, so the sample recorded at
+        // 40 seconds is exactly a full period old.
+
+        assert!(stats.count()   == 59);
+        assert!(stats.min_i64() == 42);
+        assert!(stats.max_i64() == 100);
+        assert!(stats.mean()    == 71.0);
+
+        // Queries evict samples, too.
+
+        advance(&clock, 30);
+
+        assert!(stats.count()   == 29);
+        assert!(stats.min_i64() == 72);
+
+        advance(&clock, 30);
+
+        assert!(stats.count()   == 0);
+        assert!(stats.min_i64() == 0);
+        assert!(stats.max_i64() == 0);
+
+        // The histogram keeps all the samples.
+
+        let histogram = stats.log_histogram().unwrap();
+        let total: u64 = histogram.borrow().positive.iter().sum();
+
+        assert!(total == 100);
+
+        stats.record_i64(-5);
+
+        assert!(stats.values() == vec![ -5 ]);
+
+        stats.print();
+        stats.clear();
+
+        assert!(stats.count() == 0);
+    }
+
+    fn test_moments() {
+        let (mut stats, _clock) = make_window(1);
+
+        for sample in [ 2, 4, 4, 4, 5, 5, 7, 9 ] {
+            stats.record_i64(sample);
+        }
+
+        assert!(stats.mean() == 5.0);
+        assert!((stats.variance() - 32.0 / 7.0).abs() < 1.0e-9);
+
+        let equal = stats.clone();
+
+        assert!( stats.equals(&stats));
+        assert!(!stats.equals(&equal));
+    }
+
+    // Check that a clone and the original both see the clock advance,
+    // whichever one reads it, and that each keeps its own samples.
+
+    fn test_clone() {
+        let (mut stats, clock) = make_window(10);
+
+        stats.record_i64(1);
+
+        let mut copy = stats.clone();
+
+        // Poll the copy every second, as a snapshot user might.
+
+        for _ in 0..15 {
+            advance(&clock, 1);
+            assert!(copy.count() <= 1);
+        }
+
+        assert!(copy.count()  == 0);
+        assert!(stats.count() == 0);
+
+        // New samples go only to the instance that records them.
+
+        stats.record_i64(2);
+        advance(&clock, 5);
+        copy.record_i64(3);
+
+        assert!(stats.values() == vec![ 2 ]);
+        assert!(copy.values()  == vec![ 3 ]);
+
+        advance(&clock, 5);
+
+        assert!(stats.count() == 0);
+        assert!(copy.count()  == 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_period() {
+        let _ = make_window(0);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_eviction();
+        test_moments ();
+        test_clone   ();
+    }
+}
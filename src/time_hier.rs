@@ -201,6 +201,7 @@ use crate::running_integer::IntegerExporter;
 use crate::Hier;
 use crate::HierDescriptor;
 use crate::HierConfig;
+use crate::hier::HierWindow;
 use crate::HierGenerator;
 use crate::HierMember;
 use crate::HierExporter;
@@ -279,10 +280,10 @@ impl TimeHier {
         let descriptor  = configuration.descriptor;
         let name        = configuration.name;
         let print_opts  = configuration.print_opts;
-        let window_size = configuration.window_size;
+        let window      = HierWindow::from(configuration.window_size);
 
         let config =
-            HierConfig { descriptor, generator, name, window_size, class, print_opts };
+            HierConfig { descriptor, generator, name, window, class, print_opts };
 
         Hier::new(config)
     }
@@ -301,11 +302,19 @@ impl HierGenerator for TimeHier {
         hier_box!(member)
     }
 
-    fn make_window(&self, name: &str, window_size: usize, print_opts: &PrintOption)
+    // Only sample-count windows are supported for time samples.
+
+    fn make_window(&self, name: &str, window: &HierWindow, print_opts: &PrintOption)
             -> Box<dyn Rustics> {
-        let window = TimeWindow::new(name, window_size, self.timer.clone(), print_opts);
+        match window {
+            HierWindow::Samples(size) => {
+                Box::new(TimeWindow::new(name, *size, self.timer.clone(), print_opts))
+            }
 
-        Box::new(window)
+            _ => {
+                panic!("TimeHier::make_window:  Only Samples windows are supported.");
+            }
+        }
     }
 
     // Makes a member from a complete list of exported instances.
@@ -348,6 +357,31 @@ impl HierGenerator for TimeHier {
     fn hz(&self) -> u128 {
         self.hz
     }
+
+    fn checkpoint_member(&self, member: &MemberRc) -> Option<String> {
+        let member = hier_item!(member);
+        let member = member.as_any().downcast_ref::<RunningTime>()?;
+
+        Some(member.checkpoint())
+    }
+
+    fn restore_member(&self, text: &str, print_opts: &PrintOption) -> Option<MemberRc> {
+        let member = RunningTime::restore(text, self.timer.clone(), print_opts)?;
+
+        Some(hier_box!(member))
+    }
+
+    fn checkpoint_window(&self, window: &dyn Rustics) -> Option<String> {
+        let window = window.generic().downcast_ref::<TimeWindow>()?;
+
+        Some(window.checkpoint())
+    }
+
+    fn restore_window(&self, text: &str, print_opts: &PrintOption) -> Option<Box<dyn Rustics>> {
+        let window = TimeWindow::restore(text, self.timer.clone(), print_opts)?;
+
+        Some(Box::new(window))
+    }
 }
 
 #[cfg(test)]
@@ -403,9 +437,10 @@ mod tests {
         let class      = "time".to_string();
         let name       = "test hier".to_string();
         let print_opts = None;
+        let window     = HierWindow::from(window_size);
 
         let configuration =
-            HierConfig { descriptor, generator, class, name, window_size, print_opts };
+            HierConfig { descriptor, generator, class, name, window, print_opts };
 
         Hier::new(configuration)
     }
@@ -645,7 +680,7 @@ mod tests {
                 "    Mean              500.500 microseconds",
                 "    Std Dev           288.819 microseconds",
                 "    Variance         +8.34166 e+10 ",
-                "    Skewness         -4.16336 e-11 ",
+                "    Skewness         -4.16309 e-11 ",
                 "    Kurtosis         -1.19999 e+0  ",
                 "  Log Histogram",
                 "  -----------------------",
@@ -663,7 +698,7 @@ mod tests {
         let     title      = None;
         let     units      = None;
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 
         let     name       = "Test Statistics";
         let mut stats      = RunningTime::new(&name, timer, &print_opts);
@@ -676,11 +711,39 @@ mod tests {
         stats.print();
     }
 
+    fn test_save_load() {
+        let     timer     = continuing_box();
+        let     generator = Rc::from(RefCell::new(TimeHier::new(timer)));
+        let mut hier      = make_time_hier(generator, 100, Some(250));
+
+        for i in 1..=2000 {
+            hier.record_time(i * 10);
+        }
+
+        let mut saved = Vec::new();
+
+        hier.save(&mut saved).unwrap();
+
+        let     timer     = continuing_box();
+        let     generator = Rc::from(RefCell::new(TimeHier::new(timer)));
+        let mut restored  = make_time_hier(generator, 100, Some(250));
+
+        restored.load(&mut saved.as_slice()).unwrap();
+
+        assert!(restored.count()     == hier.count()    );
+        assert!(restored.mean()      == hier.mean()     );
+        assert!(restored.max_i64()   == hier.max_i64()  );
+        assert!(restored.all_len(0)  == hier.all_len(0) );
+        assert!(restored.all_len(1)  == hier.all_len(1) );
+        assert!(restored.hz()        == hier.hz()       );
+    }
+
     #[test]
     fn run_tests() {
         test_simple_stat ();
         test_new_hier_arc();
         test_window      ();
         test_print_output();
+        test_save_load   ();
     }
 }
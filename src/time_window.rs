@@ -12,7 +12,10 @@
 //!       intervals recorded into it.
 //!
 //!     * This type uses IntegerWindow internally to record the time
-//!       samples.
+//!       samples.  as_slices() and iter() return the samples in ticks.
+//!
+//!     * is_warmed_up() and set_warm_up_policy() work as for
+//!       IntegerWindow.
 //!
 //! ## Example
 //!```
@@ -85,13 +88,24 @@ use super::PrinterOption;
 use super::PrintOption;
 use super::TimerBox;
 use super::Histogram;
+use super::HistoOpts;
 use super::LogHistogramBox;
 use super::FloatHistogramBox;
 use super::timer_box_hz;
+use super::time::default_timer;
 use super::parse_print_opts;
+use super::parse_fields;
+use super::printable::PrintFields;
 use super::printer_mut;
 use super::timer_mut;
+use super::Labels;
 use super::integer_window::IntegerWindow;
+use super::integer_window::WarmUpPolicy;
+use super::printable::Printable;
+use super::log_linear_histogram::LogLinearHistogram;
+use super::log_histogram::LogHistogram;
+use super::printable::TimeUnit;
+use super::checkpoint::CheckpointReader;
 
 /// TimeWindow implements a Rustics type that retains a
 /// window of the last n samples of a stream of data samples.
@@ -105,6 +119,10 @@ pub struct TimeWindow {
     timer:              TimerBox,
     hz:                 i64,
     printer:            PrinterBox,
+    histo_opts:         HistoOpts,
+    fields:             PrintFields,
+    secondary_unit:     Option<TimeUnit>,
+    log_linear:         Option<LogLinearHistogram>,
     //units:              Units,
 }
 
@@ -113,7 +131,7 @@ impl TimeWindow {
 
     pub fn new(name: &str, window_size: usize, timer: TimerBox, print_opts: &PrintOption)
             -> TimeWindow {
-        let (printer, _title, _units, _histo_opts) = parse_print_opts(print_opts, name);
+        let (printer, _title, _units, histo_opts) = parse_print_opts(print_opts, name);
 
         let hz = timer_box_hz(&timer);
 
@@ -124,16 +142,205 @@ impl TimeWindow {
         let hz             = hz as i64;
         let integer_window = IntegerWindow::new(name, window_size, print_opts);
         let integer_window = Box::new(integer_window);
+        let secondary_unit = None;
+        let log_linear     = None;
+        let fields         = parse_fields(print_opts);
 
-        TimeWindow { printer, integer_window, timer, hz }
+        TimeWindow {
+            printer, integer_window, timer, hz, histo_opts, fields, secondary_unit, log_linear
+        }
    }
 
+    /// Makes a new TimeWindow instance using a timer from the
+    /// process-wide factory.  See time::default_timer().
+
+    pub fn new_default(name: &str, window_size: usize, print_opts: &PrintOption) -> TimeWindow {
+        TimeWindow::new(name, window_size, default_timer(), print_opts)
+    }
+
     /// Returns the frequency of the Timer instance being used
     /// by this instance.
 
     pub fn hz(&self) -> i64 {
         self.hz
     }
+
+    /// Saves the samples in the window and the histogram as a string.
+    /// See the checkpoint module.
+
+    pub fn checkpoint(&self) -> String {
+        let mut checkpoint = self.integer_window.make_checkpoint("time_window");
+
+        checkpoint.push_i64("hz", self.hz);
+        checkpoint.text()
+    }
+
+    /// Restores an instance saved by checkpoint().  The timer must have
+    /// the same frequency as the one used by the saved instance.  The
+    /// result is None if the checkpoint doesn't match.
+
+    pub fn restore(text: &str, timer: TimerBox, print_opts: &PrintOption) -> Option<TimeWindow> {
+        let reader = CheckpointReader::new_kind(text, "time_window")?;
+        let hz     = reader.i64("hz")?;
+
+        if hz as u128 != timer_box_hz(&timer) {
+            return None;
+        }
+
+        let window_size = reader.usize("window_size")?;
+        let name        = reader.string("name")?;
+        let mut result  = TimeWindow::new(&name, window_size, timer, print_opts);
+
+        result.integer_window = Box::new(IntegerWindow::restore_reader(&reader, print_opts)?);
+        Some(result)
+    }
+
+    /// Sets a fixed secondary unit for printing the mean, standard
+    /// deviation, and extremes.  See RunningTime::set_secondary_unit.
+
+    pub fn set_secondary_unit(&mut self, unit: Option<TimeUnit>) {
+        self.secondary_unit = unit;
+    }
+
+    /// Enables a LogLinearHistogram that splits each pseudo-log bucket
+    /// into the given number of linear sub-buckets.  It is printed
+    /// after the usual histogram, and like the usual histogram,
+    /// it counts all the samples seen, not just the window.  Any
+    /// samples already recorded are not included.
+
+    pub fn enable_log_linear(&mut self, sub_buckets: usize) {
+        self.log_linear = Some(LogLinearHistogram::new(sub_buckets));
+    }
+
+    /// Returns the log-linear histogram, if one is enabled.
+
+    pub fn log_linear(&self) -> Option<&LogLinearHistogram> {
+        self.log_linear.as_ref()
+    }
+
+    /// Starts keeping a histogram of only the samples currently in the
+    /// window.  See IntegerWindow::enable_window_histogram().
+
+    pub fn enable_window_histogram(&mut self) {
+        self.integer_window.enable_window_histogram();
+    }
+
+    /// Returns the histogram of the samples in the window, if one is
+    /// enabled.
+
+    pub fn window_histogram(&self) -> Option<&LogHistogram> {
+        self.integer_window.window_histogram()
+    }
+
+    // Record a sample into the window and any log-linear histogram.
+
+    fn record_sample(&mut self, sample: i64) {
+        self.integer_window.record_i64(sample);
+
+        if let Some(log_linear) = &mut self.log_linear {
+            log_linear.record(sample);
+        }
+    }
+
+    /// Returns the samples in the window, in ticks, as two slices.  See
+    /// IntegerWindow::as_slices().
+
+    pub fn as_slices(&self) -> (&[i64], &[i64]) {
+        self.integer_window.as_slices()
+    }
+
+    /// Returns an iterator over the samples in the window, in ticks,
+    /// from oldest to newest.
+
+    pub fn iter(&self) -> impl Iterator<Item = &i64> + '_ {
+        self.integer_window.iter()
+    }
+
+    /// Returns true if the window has been filled at least once since
+    /// it was created or cleared.
+
+    pub fn is_warmed_up(&self) -> bool {
+        self.integer_window.is_warmed_up()
+    }
+
+    /// Sets how print() handles a window that hasn't warmed up.  See
+    /// IntegerWindow::set_warm_up_policy().
+
+    pub fn set_warm_up_policy(&mut self, policy: WarmUpPolicy) {
+        self.integer_window.set_warm_up_policy(policy);
+    }
+
+    /// Returns the warm-up policy.
+
+    pub fn warm_up_policy(&self) -> WarmUpPolicy {
+        self.integer_window.warm_up_policy()
+    }
+
+    /// Returns the number of samples in the window.
+
+    pub fn len(&self) -> usize {
+        self.integer_window.len()
+    }
+
+    /// Returns true if the window holds no samples.
+
+    pub fn is_empty(&self) -> bool {
+        self.integer_window.is_empty()
+    }
+
+    /// Starts keeping a timestamp for each sample.  The timer is used
+    /// only for the timestamps, and should not be the one measuring the
+    /// intervals.  See IntegerWindow::enable_timestamps().
+
+    pub fn enable_timestamps(&mut self, timer: TimerBox) {
+        self.integer_window.enable_timestamps(timer);
+    }
+
+    /// Returns true if timestamps are being kept.
+
+    pub fn has_timestamps(&self) -> bool {
+        self.integer_window.has_timestamps()
+    }
+
+    /// Returns the age of each sample in the window, in ticks of the
+    /// timestamp timer, from oldest to newest.
+
+    pub fn ages(&self) -> Vec<i64> {
+        self.integer_window.ages()
+    }
+
+    /// Returns the number of samples younger than the given age.
+
+    pub fn count_younger_than(&self, max_age: i64) -> u64 {
+        self.integer_window.count_younger_than(max_age)
+    }
+
+    /// Returns the mean of the samples younger than the given age, in
+    /// ticks.  See IntegerWindow::mean_younger_than().
+
+    pub fn mean_younger_than(&self, max_age: i64) -> f64 {
+        self.integer_window.mean_younger_than(max_age)
+    }
+
+    /// Returns the age-weighted mean of the window, in ticks.  See
+    /// IntegerWindow::age_weighted_mean().
+
+    pub fn age_weighted_mean(&self, half_life: i64) -> f64 {
+        self.integer_window.age_weighted_mean(half_life)
+    }
+
+    /// Removes the samples that are at least max_age ticks old.  See
+    /// IntegerWindow::evict_older_than().
+
+    pub fn evict_older_than(&mut self, max_age: i64) -> usize {
+        self.integer_window.evict_older_than(max_age)
+    }
+
+    /// Returns the secondary unit for printing, if any.
+
+    pub fn secondary_unit(&self) -> Option<TimeUnit> {
+        self.secondary_unit
+    }
 }
 
 impl Rustics for TimeWindow {
@@ -157,20 +364,20 @@ impl Rustics for TimeWindow {
     fn record_event_report(&mut self) -> i64 {
         let interval = timer_mut!(*self.timer).finish();
 
-        self.integer_window.record_i64(interval);
+        self.record_sample(interval);
         interval
     }
 
     fn record_time(&mut self, sample: i64) {
         assert!(sample >= 0);
-        self.integer_window.record_i64(sample);
+        self.record_sample(sample);
     }
 
     fn record_interval(&mut self, timer: &mut TimerBox) {
         let timer    = timer_mut!(*timer);
         let interval = timer.finish();
 
-        self.integer_window.record_i64(interval);
+        self.record_sample(interval);
     }
 
     fn name(&self) -> String {
@@ -242,7 +449,11 @@ impl Rustics for TimeWindow {
     }
 
     fn clear(&mut self) {
-        self.integer_window.clear()
+        self.integer_window.clear();
+
+        if let Some(log_linear) = &mut self.log_linear {
+            log_linear.clear();
+        }
     }
 
     // Functions for printing
@@ -268,18 +479,69 @@ impl Rustics for TimeWindow {
 
         let printable = self.integer_window.get_printable();
         let printer   = printer_mut!(printer_box);
+        let fields    = self.fields;
+
+        let policy    = self.warm_up_policy();
+        let warmed_up = self.is_warmed_up();
 
         printer.print(title);
-        printable.print_common_integer_times(self.hz, printer);
-        printable.print_common_float_times(self.hz, printer);
-        self.integer_window.print_histogram(printer);
+
+        if policy.print_statistics(warmed_up) {
+            printable.print_common_integer_times_fields(self.hz, self.secondary_unit, fields, printer);
+            printable.print_common_float_times_fields  (self.hz, self.secondary_unit, fields, printer);
+        } else {
+            Printable::print_integer("Count", printable.n as i64, printer);
+        }
+
+        if policy.print_annotation(warmed_up) {
+            let window_size = self.integer_window.window_size();
+
+            WarmUpPolicy::print_fill(printable.n, window_size, printer);
+        }
+
+        self.integer_window.print_collection_time(printer);
+
+        if self.histo_opts.print_histogram(self.fields) {
+            self.integer_window.print_histogram(printer);
+            self.integer_window.print_window_histogram(printer);
+
+            if let Some(log_linear) = &self.log_linear {
+                log_linear.print_times(self.hz, printer);
+            }
+        }
+
+        if self.histo_opts.print_cdf {
+            let histogram = self.integer_window.to_log_histogram().unwrap();
+
+            histogram.borrow().print_cdf_times(self.hz, printer);
+        }
         printer.print("");
     }
 
+    fn supports_collection_time(&self) -> bool {
+        true
+    }
+
+    fn set_collection_clock(&mut self, clock: Option<TimerBox>) {
+        self.integer_window.set_collection_clock(clock);
+    }
+
+    fn collection_seconds(&self) -> Option<f64> {
+        self.integer_window.collection_seconds()
+    }
+
     fn set_title(&mut self, title: &str) {
         self.integer_window.set_title(title)
     }
 
+    fn set_label(&mut self, key: &str, value: &str) {
+        self.integer_window.set_label(key, value);
+    }
+
+    fn labels(&self) -> Labels {
+        self.integer_window.labels()
+    }
+
     fn log_histogram(&self) -> Option<LogHistogramBox> {
         self.integer_window.log_histogram()
     }
@@ -349,6 +611,7 @@ mod tests {
     use crate::tests::compute_sum;
     use crate::tests::continuing_box;
     use crate::tests::check_printer_box;
+    use crate::capture_printer::CapturePrinter;
     use crate::time::tests::TestSimpleClock;
 
     fn simple_test() {
@@ -531,7 +794,7 @@ mod tests {
         let     title      = None;
         let     units      = None;
         let     histo_opts = None;
-        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
 
         let     name       = "Test Statistics";
         let     samples    = 1000;
@@ -572,12 +835,177 @@ mod tests {
         assert!(time_window.count() == tests as u64 * 2);
     }
 
+    fn test_secondary_unit() {
+        let expected =
+            [
+                "Test Statistics",
+                "    Count               1,000 ",
+                "    Minimum             1.000 microsecond  (0.001 milliseconds)",
+                "    Maximum             1.000 millisecond  (1.000 millisecond)",
+                "    Log Mode               20 ",
+                "    Mode Value        786.432 microseconds",
+                "    Mean              500.500 microseconds  (0.500 milliseconds)",
+                "    Std Dev           288.819 microseconds  (0.289 milliseconds)",
+                "    Variance         +8.34166 e+10 ",
+                "    Skewness         +0.00000 e+0  ",
+                "    Kurtosis         -1.20000 e+0  ",
+                ""
+            ];
+
+        let     timer      = continuing_box();
+        let     printer    = Some(check_printer_box(&expected, true, false));
+        let     title      = None;
+        let     units      = None;
+        let     histo_opts = Some(HistoOpts { no_histogram: true, ..Default::default() });
+        let     print_opts = Some(PrintOpts { printer, title, units, histo_opts, ..Default::default() });
+
+        let     name       = "Test Statistics";
+        let     samples    = 1000;
+        let mut stats      = TimeWindow::new(name, samples, timer, &print_opts);
+
+        assert!(stats.secondary_unit().is_none());
+
+        stats.set_secondary_unit(Some(TimeUnit::Millisecond));
+
+        assert!(stats.secondary_unit() == Some(TimeUnit::Millisecond));
+
+        for _i in 1..=samples {
+            stats.record_event();
+        }
+
+        stats.print();
+    }
+
+    fn test_checkpoint() {
+        let mut window = TimeWindow::new("Checkpoint Test", 50, continuing_box(), &None);
+
+        for i in 1..=80 {
+            window.record_time(i * 1000);
+        }
+
+        let checkpoint = window.checkpoint();
+        let restored   = TimeWindow::restore(&checkpoint, continuing_box(), &None).unwrap();
+
+        assert!(restored.title()   == window.title()  );
+        assert!(restored.count()   == window.count()  );
+        assert!(restored.mean()    == window.mean()   );
+        assert!(restored.min_i64() == window.min_i64());
+        assert!(restored.hz()      == window.hz()     );
+
+        // The timer frequency must match.
+
+        let timer = timer_box!(crate::tests::ContinuingTimer::new(1000));
+
+        assert!(TimeWindow::restore(&checkpoint, timer, &None).is_none());
+    }
+
+    fn test_log_linear() {
+        let mut window = TimeWindow::new("Log-Linear Test", 10, continuing_box(), &None);
+
+        assert!(window.log_linear().is_none());
+
+        window.enable_log_linear(4);
+
+        // The log-linear histogram counts every sample, not just the
+        // ones in the window.
+
+        for i in 0..100 {
+            window.record_time(1_048_577 + i * 10_000);
+        }
+
+        let log_linear = window.log_linear().unwrap();
+        let used       = log_linear.counts().iter().filter(|count| **count > 0).count();
+
+        assert!(log_linear.samples() == 100);
+        assert!(window.count()       == 10 );
+        assert!(used == 4);
+
+        window.print();
+        window.clear();
+
+        assert!(window.log_linear().unwrap().samples() == 0);
+    }
+
+    fn test_iter() {
+        let mut window = TimeWindow::new("Iter Test", 2, continuing_box(), &None);
+
+        assert!(window.is_empty());
+
+        for i in 1..=3 {
+            window.record_time(i * 10);
+        }
+
+        let (older, newer) = window.as_slices();
+
+        assert!(older == [ 20 ]);
+        assert!(newer == [ 30 ]);
+        assert!(window.len() == 2);
+        assert!(window.iter().copied().collect::<Vec<i64>>() == vec![ 20, 30 ]);
+    }
+
+    fn test_warm_up() {
+        let     capture = Rc::new(RefCell::new(CapturePrinter::new()));
+        let     printer = capture.clone() as PrinterBox;
+        let mut window  = TimeWindow::new("Warm Up", 2, continuing_box(), &None);
+
+        window.set_warm_up_policy(WarmUpPolicy::Suppress);
+        window.record_time(10);
+        window.print_opts(Some(printer.clone()), None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(!window.is_warmed_up());
+        assert!(window.warm_up_policy() == WarmUpPolicy::Suppress);
+        assert!(!lines.iter().any(|line| line.contains("Mean")));
+        assert!( lines.iter().any(|line| line.contains("1 of 2 samples")));
+
+        window.record_time(20);
+        window.print_opts(Some(printer), None);
+
+        let lines = capture.borrow_mut().take_lines();
+
+        assert!(window.is_warmed_up());
+        assert!( lines.iter().any(|line| line.contains("Mean")));
+        assert!(!lines.iter().any(|line| line.contains("Warming Up")));
+    }
+
+    fn test_default_timer() {
+        let mut window = TimeWindow::new_default("Default Timer", 4, &None);
+
+        window.record_time(100);
+
+        assert!(window.hz()    == crate::time::default_hz() as i64);
+        assert!(window.count() == 1);
+    }
+
+    fn test_window_histogram() {
+        let mut window = TimeWindow::new("Window Histogram", 3, continuing_box(), &None);
+
+        window.enable_window_histogram();
+
+        for i in 1..=10 {
+            window.record_time(i * 1000);
+        }
+
+        let all = window.log_histogram().unwrap();
+
+        assert!(all.borrow().samples()                       == 10);
+        assert!(window.window_histogram().unwrap().samples() == 3 );
+    }
+
     #[test]
     fn run_tests() {
-        simple_test      ();
-        test_equality    ();
-        test_histogram   ();
-        test_print_output();
-        test_timer_boxes ();
+        simple_test          ();
+        test_equality        ();
+        test_histogram       ();
+        test_print_output    ();
+        test_timer_boxes     ();
+        test_secondary_unit  ();
+        test_checkpoint      ();
+        test_iter            ();
+        test_log_linear      ();
+        test_warm_up         ();
+        test_default_timer   ();
+        test_window_histogram();
     }
 }
@@ -0,0 +1,209 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * TrimOpts
+//!     * TrimOpts configures outlier handling for a Rustics instance.
+//!       A single huge sample, like a stuck request that hits a 30
+//!       second timeout, can dominate the mean of an interval.  The
+//!       trimmed mean drops a percentage of the samples from each end
+//!       of the distribution, so it shows the typical behavior, while
+//!       the outlier count shows how many samples were far from the
+//!       mean.
+//!
+//!     * trim_percent is the percentage of samples dropped from each
+//!       end, so 1.0 drops the lowest 1% and the highest 1%.
+//!
+//!     * outlier_sigmas is the number of standard deviations from the
+//!       mean beyond which a sample is counted as an outlier.
+//!
+//! * Trimmer
+//!     * Trimmer keeps a SketchHistogram with a 1% relative error to
+//!       compute the trimmed mean and the outlier count, so both are
+//!       estimates within that error.  The outlier count uses the mean
+//!       and standard deviation of the instance when it is queried, so
+//!       it covers all the samples recorded.
+//!
+//!     * RunningInteger, RunningFloat, and RunningTime keep a Trimmer
+//!       once enable_trimming() has been invoked.  The results are
+//!       available via trimmed_mean() and outliers() in the Rustics
+//!       trait, and they are printed after the usual summary.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::running_time::RunningTime;
+//!     use rustics::time::DurationTimer;
+//!     use rustics::trim::TrimOpts;
+//!
+//!     let mut latency = RunningTime::new("Request Latency", DurationTimer::new_box(), &None);
+//!
+//!     latency.enable_trimming(TrimOpts { trim_percent: 1.0, outlier_sigmas: 3.0 });
+//!
+//!     // Record 999 requests that take 1 ms and one that hits a 30
+//!     // second timeout.
+//!
+//!     for _i in 0..999 {
+//!         latency.record_time(1_000_000);
+//!     }
+//!
+//!     latency.record_time(30_000_000_000);
+//!
+//!     // The mean is about 31 ms, but the trimmed mean is within 1% of
+//!     // 1 ms.
+//!
+//!     let trimmed = latency.trimmed_mean();
+//!
+//!     assert!(latency.mean() > 30_000_000.0);
+//!     assert!(trimmed >= 990_000.0 && trimmed <= 1_010_000.0);
+//!     assert!(latency.outliers() == 1);
+//!
+//!     latency.print();
+//!```
+
+use super::sketch_histogram::SketchHistogram;
+
+// The relative error for the sketch.
+
+const TRIM_ERROR: f64 = 0.01;
+
+/// TrimOpts sets the trimming percentage and the outlier limit.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TrimOpts {
+    pub trim_percent:    f64,
+    pub outlier_sigmas:  f64,
+}
+
+impl Default for TrimOpts {
+    fn default() -> Self {
+        let trim_percent   = 1.0;
+        let outlier_sigmas = 3.0;
+
+        TrimOpts { trim_percent, outlier_sigmas }
+    }
+}
+
+/// Trimmer computes the trimmed mean and the outlier count.
+
+#[derive(Clone)]
+pub struct Trimmer {
+    opts:    TrimOpts,
+    sketch:  SketchHistogram,
+}
+
+impl Trimmer {
+    /// Creates a Trimmer.  The trim percentage must be at least zero
+    /// and less than 50, and the outlier limit must be positive.
+
+    pub fn new(opts: TrimOpts) -> Trimmer {
+        if !(opts.trim_percent >= 0.0 && opts.trim_percent < 50.0) {
+            panic!("Trimmer::new:  The trim percentage is invalid.");
+        }
+
+        if opts.outlier_sigmas <= 0.0 || opts.outlier_sigmas.is_nan() {
+            panic!("Trimmer::new:  The outlier limit must be positive.");
+        }
+
+        let sketch = SketchHistogram::new(TRIM_ERROR);
+
+        Trimmer { opts, sketch }
+    }
+
+    /// Returns the options given to new().
+
+    pub fn opts(&self) -> TrimOpts {
+        self.opts
+    }
+
+    /// Records a sample.
+
+    pub fn record(&mut self, sample: f64) {
+        self.sketch.record(sample);
+    }
+
+    /// Returns the trimmed mean, or zero if there are no samples.
+
+    pub fn trimmed_mean(&self) -> f64 {
+        self.sketch.trimmed_mean(self.opts.trim_percent)
+    }
+
+    /// Returns the number of samples more than outlier_sigmas standard
+    /// deviations from the given mean.
+
+    pub fn outliers(&self, mean: f64, standard_deviation: f64) -> u64 {
+        let limit = self.opts.outlier_sigmas * standard_deviation;
+
+        self.sketch.count_outside(mean - limit, mean + limit)
+    }
+
+    /// Adds the samples from another Trimmer.  The options must match.
+
+    pub fn merge(&mut self, other: &Trimmer) {
+        if self.opts != other.opts {
+            panic!("Trimmer::merge:  The options don't match.");
+        }
+
+        self.sketch.merge(&other.sketch);
+    }
+
+    /// Discards the samples.
+
+    pub fn clear(&mut self) {
+        self.sketch.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_trimmer() {
+        let mut trimmer = Trimmer::new(TrimOpts::default());
+
+        assert!(trimmer.opts() == TrimOpts { trim_percent: 1.0, outlier_sigmas: 3.0 });
+        assert!(trimmer.trimmed_mean() == 0.0);
+
+        for i in 1..=100 {
+            trimmer.record(i as f64);
+        }
+
+        // The trimmed mean drops 1 and 100.
+
+        let trimmed = trimmer.trimmed_mean();
+
+        assert!((trimmed - 50.5).abs() < 50.5 * TRIM_ERROR);
+
+        // Nothing is more than three standard deviations out.
+
+        assert!(trimmer.outliers(50.5, 29.0) == 0 );
+        assert!(trimmer.outliers(50.5,  5.0) == 70);
+
+        let mut other = trimmer.clone();
+
+        other.record(1.0e6);
+        trimmer.merge(&other);
+
+        assert!(trimmer.outliers(50.5, 29.0) == 1);
+
+        trimmer.clear();
+
+        assert!(trimmer.trimmed_mean() == 0.0);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_trimmer();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_percent() {
+        let _ = Trimmer::new(TrimOpts { trim_percent: 50.0, outlier_sigmas: 3.0 });
+    }
+}
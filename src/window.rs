@@ -138,6 +138,13 @@ impl<T> Window<T> {
     /// Adds a new entry to the window.
 
     pub fn push(&mut self, data:  T) {
+        let _ = self.push_evict(data);
+    }
+
+    /// Adds a new entry to the window, and returns the oldest entry
+    /// if it was evicted to make room.
+
+    pub fn push_evict(&mut self, data:  T) -> Option<T> {
         // If this is the first entry, set the "current_index"
         // index to level 1, since "current_index" always points
         // to the oldest entry or the next empty slot, if the
@@ -152,6 +159,8 @@ impl<T> Window<T> {
         // In all cases, "current_index" wraps back to zero when
         // it reaches the size limit of the queue.
 
+        let mut evicted = None;
+
         if self.data.is_empty() {
             self.data.push(data);
 
@@ -161,7 +170,7 @@ impl<T> Window<T> {
 
             self.current_index += 1;
         } else {
-            self.data[self.current_index] = data;
+            evicted = Some(std::mem::replace(&mut self.data[self.current_index], data));
 
             self.current_index += 1;
         }
@@ -169,6 +178,8 @@ impl<T> Window<T> {
         if self.current_index >= self.size_limit {
             self.current_index = 0;
         }
+
+        evicted
     }
 
     /// Checks whether the window is empty.
@@ -300,6 +311,34 @@ impl<T> Window<T> {
         Some(&self.data[internal_index])
     }
 
+    /// Removes the oldest entries so that at most "keep" entries
+    /// remain, but never removes a live entry.  The removed entries
+    /// are returned, oldest first.
+
+    pub fn prune(&mut self, keep: usize) -> Vec<T> {
+        let keep = std::cmp::max(keep, self.live_len());
+
+        if self.data.len() <= keep {
+            return Vec::new();
+        }
+
+        // Put the entries in order from oldest to newest, so that the
+        // oldest ones are at the front of the vector.
+
+        if self.data.len() == self.size_limit {
+            self.data.rotate_left(self.current_index);
+        }
+
+        let removed = self.data.len() - keep;
+        let pruned  = self.data.drain(0..removed).collect();
+
+        // The window now isn't full, so "current_index" is the index
+        // of the next empty slot.
+
+        self.current_index = self.data.len();
+        pruned
+    }
+
     /// Deletes all data from the window.  This puts it back into
     /// its initial state.
 
@@ -310,13 +349,13 @@ impl<T> Window<T> {
 
     /// Iterates over all the items in the window.
 
-    pub fn iter_all(&self) -> WindowIterator<T> {
+    pub fn iter_all(&self) -> WindowIterator<'_, T> {
         WindowIterator::<T>::new(self, ScanType::All)
     }
 
     /// Iterates over all the live items in the window.
 
-    pub fn iter_live(&self) -> WindowIterator<T> {
+    pub fn iter_live(&self) -> WindowIterator<'_, T> {
         WindowIterator::<T>::new(self, ScanType::Live)
     }
 
@@ -362,8 +401,8 @@ impl<T> Window<T> {
                 result
             };
 
-        assert!(self.data.len() == 0 || oldest      < self.data.len());
-        assert!(self.data.len() == 0 || oldest_live < self.data.len());
+        assert!(self.data.is_empty() || oldest      < self.data.len());
+        assert!(self.data.is_empty() || oldest_live < self.data.len());
 
         (&self.data, oldest, oldest_live)
     }
@@ -378,7 +417,7 @@ pub struct WindowIterator<'a, T> {
 }
 
 impl<'a, T> WindowIterator<'a, T> {
-    pub fn new(window: &'a Window<T>, scan_type: ScanType) -> WindowIterator<T> {
+    pub fn new(window: &'a Window<T>, scan_type: ScanType) -> WindowIterator<'a, T> {
         assert!(window.data.len() <= window.size_limit);
 
         if window.data.is_empty() {
@@ -920,10 +959,60 @@ mod tests {
         }
     }
 
+    fn test_push_evict() {
+        let mut window = Window::<usize>::new(3, 2);
+
+        for i in 0..3 {
+            assert!(window.push_evict(i).is_none());
+        }
+
+        // The oldest entry is returned as each new one arrives.
+
+        for i in 3..10 {
+            assert!(window.push_evict(i) == Some(i - 3));
+            assert!(window.all_len() == 3);
+        }
+    }
+
+    fn test_prune() {
+        let mut window = Window::<usize>::new(6, 2);
+
+        for i in 0..8 {
+            window.push(i);
+        }
+
+        // Keep four entries.  The window has wrapped, so the oldest
+        // entries are in the middle of the vector.
+
+        assert!(window.prune(4) == vec![ 2, 3 ]);
+        assert!(window.all_len() == 4);
+        assert!(window.iter_all ().copied().collect::<Vec<usize>>() == vec![ 4, 5, 6, 7 ]);
+        assert!(window.iter_live().copied().collect::<Vec<usize>>() == vec![ 6, 7 ]);
+        assert!(window.newest() == Some(&7));
+
+        // The live entries are kept.
+
+        assert!(window.prune(0) == vec![ 4, 5 ]);
+        assert!(window.prune(0).is_empty());
+        assert!(window.index_live(0) == Some(&6));
+
+        // The window fills again normally.
+
+        for i in 8..14 {
+            window.push(i);
+        }
+
+        assert!(window.all_len()    == 6);
+        assert!(window.index_all(0) == Some(&8));
+        assert!(window.newest()     == Some(&13));
+    }
+
     #[test]
     fn run_tests() {
         simple_window_test(true);
         sample_usage      (true);
         test_small_window ();
+        test_push_evict   ();
+        test_prune        ();
     }
 }
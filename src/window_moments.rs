@@ -0,0 +1,340 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Type
+//!
+//! * WindowMoments
+//!     * WindowMoments keeps the count, sum, and moments about the mean
+//!       of the samples in a window, updating them as samples enter and
+//!       leave, so that queries like mean() take constant time regardless
+//!       of the window size.  IntegerWindow, TimeWindow, and FloatWindow
+//!       use it.
+//!
+//!     * The sums of the first four powers of the samples are kept
+//!       relative to a shift value, using compensated summation, and
+//!       converted to the moments about the mean on demand.  The shift
+//!       is the mean of the window when the sums were last rebuilt, or
+//!       the first sample before that, so the powers stay small and the
+//!       conversion loses little precision.
+//!
+//!     * Removing a sample from a floating-point sum leaves some rounding
+//!       error behind, so the owner rebuilds the sums from the window
+//!       each time a window's worth of samples has been removed.  The
+//!       rebuild is O(n), but it happens at most once every n updates,
+//!       so the amortized cost of an update is constant.  needs_rebuild()
+//!       tells the owner when to rebuild.
+//!
+//! ## Example
+//!```
+//!     use rustics::window_moments::WindowMoments;
+//!
+//!     let mut moments = WindowMoments::new(3);
+//!
+//!     for sample in [ 1.0, 2.0, 3.0 ] {
+//!         moments.add(sample);
+//!     }
+//!
+//!     // Replace the oldest sample, as a full window does.
+//!
+//!     moments.replace(1.0, 4.0);
+//!
+//!     let crunched = moments.crunched();
+//!
+//!     assert!(moments.count() == 3   );
+//!     assert!(crunched.sum    == 9.0 );
+//!     assert!(crunched.mean   == 3.0 );
+//!     assert!((crunched.moment_2 - 2.0).abs() < 1.0e-12);
+//!
+//!     // The owner rebuilds the sums after a window's worth of
+//!     // removals.
+//!
+//!     assert!(!moments.needs_rebuild());
+//!
+//!     moments.replace(2.0, 5.0);
+//!     moments.replace(3.0, 6.0);
+//!
+//!     assert!(moments.needs_rebuild());
+//!
+//!     moments.rebuild([ 4.0, 5.0, 6.0 ].iter().copied());
+//!
+//!     assert!(moments.crunched().mean == 5.0);
+//!```
+
+use super::sum::CompensatedSum;
+use super::integer_window::Crunched;
+
+/// WindowMoments maintains the summary statistics for a window of
+/// samples incrementally.
+
+#[derive(Clone, Copy, Debug)]
+pub struct WindowMoments {
+    window_size:  usize,
+    count:        u64,
+    removed:      usize,
+    shift:        f64,
+    sum_1:        CompensatedSum,
+    sum_2:        CompensatedSum,
+    sum_3:        CompensatedSum,
+    sum_4:        CompensatedSum,
+}
+
+impl WindowMoments {
+    /// Creates an empty instance for a window of the given size.  The
+    /// size sets how often needs_rebuild() returns true.
+
+    pub fn new(window_size: usize) -> WindowMoments {
+        let count  = 0;
+        let shift  = 0.0;
+        let sum_1  = CompensatedSum::default();
+        let sum_2  = CompensatedSum::default();
+        let sum_3  = CompensatedSum::default();
+        let sum_4  = CompensatedSum::default();
+
+        let window_size = std::cmp::max(window_size, 1);
+        let removed     = 0;
+
+        WindowMoments { window_size, count, removed, shift, sum_1, sum_2, sum_3, sum_4 }
+    }
+
+    /// Returns the number of samples in the window.
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    // Add the powers of a sample with the given sign.
+
+    fn update(&mut self, sample: f64, sign: f64) {
+        let distance = sample - self.shift;
+        let square   = distance * distance;
+
+        self.sum_1.add(sign * distance         );
+        self.sum_2.add(sign * square           );
+        self.sum_3.add(sign * square * distance);
+        self.sum_4.add(sign * square * square  );
+    }
+
+    /// Adds a sample to the window.
+
+    pub fn add(&mut self, sample: f64) {
+        // Use the first sample as the shift until the first rebuild.
+
+        if self.count == 0 {
+            self.shift = sample;
+        }
+
+        self.update(sample, 1.0);
+        self.count += 1;
+    }
+
+    /// Removes a sample that is in the window.
+
+    pub fn remove(&mut self, sample: f64) {
+        if self.count == 0 {
+            panic!("WindowMoments::remove:  The window is empty.");
+        }
+
+        self.update(sample, -1.0);
+        self.count   -= 1;
+        self.removed += 1;
+
+        // An empty window has no rounding error to keep.
+
+        if self.count == 0 {
+            self.clear();
+        }
+    }
+
+    /// Replaces a sample in the window with a new one.
+
+    pub fn replace(&mut self, old: f64, new: f64) {
+        self.remove(old);
+        self.add(new);
+    }
+
+    /// Returns true once a window's worth of samples has been removed
+    /// since the last rebuild.
+
+    pub fn needs_rebuild(&self) -> bool {
+        self.removed >= self.window_size
+    }
+
+    /// Recomputes the sums from the samples in the window, using the
+    /// current mean as the new shift.
+
+    pub fn rebuild(&mut self, samples: impl Iterator<Item = f64> + Clone) {
+        let mut sum   = CompensatedSum::default();
+        let mut count = 0;
+
+        for sample in samples.clone() {
+            sum.add(sample);
+            count += 1;
+        }
+
+        self.clear();
+
+        if count == 0 {
+            return;
+        }
+
+        self.shift = sum.value() / count as f64;
+        self.count = count;
+
+        for sample in samples {
+            self.update(sample, 1.0);
+        }
+    }
+
+    /// Discards all the samples.
+
+    pub fn clear(&mut self) {
+        *self = WindowMoments::new(self.window_size);
+    }
+
+    /// Returns the mean of the samples, or zero if the window is empty.
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+
+        self.shift + self.sum_1.value() / self.count as f64
+    }
+
+    /// Converts the sums into the summary used by the window types.
+
+    pub fn crunched(&self) -> Crunched {
+        if self.count == 0 {
+            return Crunched::zero();
+        }
+
+        let n      = self.count as f64;
+        let sum_1  = self.sum_1.value();
+        let sum_2  = self.sum_2.value();
+        let sum_3  = self.sum_3.value();
+        let sum_4  = self.sum_4.value();
+
+        // Move the sums about the shift to sums about the mean.  The
+        // offset is the distance from the shift to the mean.
+
+        let offset   = sum_1 / n;
+        let offset_2 = offset * offset;
+
+        let moment_2 = sum_2 - offset * sum_1;
+        let moment_3 = sum_3 - 3.0 * offset * sum_2 + 2.0 * n * offset_2 * offset;
+        let moment_4 =
+            sum_4 - 4.0 * offset * sum_3 + 6.0 * offset_2 * sum_2 - 3.0 * n * offset_2 * offset_2;
+
+        // Rounding can leave the even moments slightly negative.
+
+        let moment_2 = moment_2.max(0.0);
+        let moment_4 = moment_4.max(0.0);
+        let mean     = self.shift + offset;
+        let sum      = self.shift * n + sum_1;
+
+        Crunched { mean, sum, moment_2, moment_3, moment_4 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Compute the moments directly for comparison.
+
+    fn exact(samples: &[f64]) -> Crunched {
+        let n        = samples.len() as f64;
+        let sum      = samples.iter().sum::<f64>();
+        let mean     = sum / n;
+        let moment_2 = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>();
+        let moment_3 = samples.iter().map(|x| (x - mean).powi(3)).sum::<f64>();
+        let moment_4 = samples.iter().map(|x| (x - mean).powi(4)).sum::<f64>();
+
+        Crunched { mean, sum, moment_2, moment_3, moment_4 }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1.0e-9 * a.abs().max(b.abs()).max(1.0)
+    }
+
+    fn check(moments: &WindowMoments, samples: &[f64]) {
+        let expected = exact(samples);
+        let found    = moments.crunched();
+
+        assert!(moments.count() == samples.len() as u64);
+        assert!(close(found.mean,     expected.mean    ));
+        assert!(close(found.sum,      expected.sum     ));
+        assert!(close(found.moment_2, expected.moment_2));
+        assert!(close(found.moment_3, expected.moment_3));
+        assert!(close(found.moment_4, expected.moment_4));
+    }
+
+    fn test_sliding() {
+        let     size    = 16;
+        let mut moments = WindowMoments::new(size);
+        let mut window  = Vec::new();
+        let mut index   = 0;
+
+        // Slide a window over a series whose level moves, so that the
+        // shift falls behind the mean between rebuilds.
+
+        for i in 0..1000 {
+            let sample = 1.0e6 + (i as f64) * 10.0 + ((i * 7) % 13) as f64;
+
+            if window.len() < size {
+                window.push(sample);
+                moments.add(sample);
+            } else {
+                moments.replace(window[index], sample);
+                window[index] = sample;
+                index = (index + 1) % size;
+            }
+
+            if moments.needs_rebuild() {
+                moments.rebuild(window.iter().copied());
+            }
+
+            check(&moments, &window);
+        }
+    }
+
+    fn test_empty() {
+        let mut moments = WindowMoments::new(4);
+
+        assert!(moments.mean() == 0.0);
+        assert!(moments.crunched().moment_2 == 0.0);
+
+        moments.add(5.0);
+        moments.add(7.0);
+
+        assert!(moments.mean() == 6.0);
+
+        moments.remove(5.0);
+        moments.remove(7.0);
+
+        assert!(moments.count() == 0  );
+        assert!(moments.mean()  == 0.0);
+
+        moments.rebuild([].iter().copied());
+
+        assert!(moments.count() == 0);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_sliding();
+        test_empty  ();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_remove_empty() {
+        let mut moments = WindowMoments::new(4);
+
+        moments.remove(1.0);
+    }
+}
@@ -0,0 +1,189 @@
+//
+//  Copyright 2024 Jonathan L Bertoni
+//
+//  This code is available under the Berkeley 2-Clause, Berkeley 3-clause,
+//  and MIT licenses.
+//
+
+//! ## Types
+//!
+//! * WorstTags
+//!     * WorstTags keeps the largest samples recorded along with a tag
+//!       for each, like a request id or trace id, so that the requests
+//!       behind the tail of a distribution can be looked up directly.
+//!
+//!     * The number of samples kept is configurable.  With a limit of
+//!       one, the tag of the current maximum is kept.  If samples tie,
+//!       the one recorded first is kept.
+//!
+//!     * RunningInteger and RunningTime keep a WorstTags instance once
+//!       enable_worst_tags() has been invoked.  Samples are given tags
+//!       via record_i64_tagged() or record_time_tagged() in the Rustics
+//!       trait, and worst_tags() returns the results.  Samples recorded
+//!       without a tag are not retained.
+//!
+//! * TaggedSample
+//!     * A TaggedSample is a sample value and its tag.
+//!
+//! ## Example
+//!```
+//!     use rustics::Rustics;
+//!     use rustics::running_time::RunningTime;
+//!     use rustics::time::DurationTimer;
+//!
+//!     let mut latency = RunningTime::new("Query Latency", DurationTimer::new_box(), &None);
+//!
+//!     // Keep the three slowest queries.
+//!
+//!     latency.enable_worst_tags(3);
+//!
+//!     // Record some hypothetical latencies tagged with request ids.
+//!
+//!     for request_id in 1..=100_u64 {
+//!         let latency_ns = (request_id as i64 * 37) % 101 * 1000;
+//!
+//!         latency.record_time_tagged(latency_ns, request_id);
+//!     }
+//!
+//!     let worst = latency.worst_tags();
+//!
+//!     // The results are sorted from the largest sample down.
+//!
+//!     assert!(worst.len() == 3);
+//!     assert!(worst[0].sample == latency.max_i64());
+//!     assert!(worst[0].sample >= worst[1].sample);
+//!
+//!     for tagged in worst.iter() {
+//!         println!("request {} took {} ns", tagged.tag, tagged.sample);
+//!     }
+//!```
+
+/// TaggedSample holds a sample and the tag recorded with it.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TaggedSample {
+    pub sample:  i64,
+    pub tag:     u64,
+}
+
+/// WorstTags keeps the largest tagged samples.
+
+#[derive(Clone, Debug)]
+pub struct WorstTags {
+    limit:    usize,
+    entries:  Vec<TaggedSample>,
+}
+
+impl WorstTags {
+    /// Creates an instance that keeps "limit" samples.
+
+    pub fn new(limit: usize) -> WorstTags {
+        if limit == 0 {
+            panic!("WorstTags::new:  The limit must be positive.");
+        }
+
+        let entries = Vec::with_capacity(limit + 1);
+
+        WorstTags { limit, entries }
+    }
+
+    /// Offers a tagged sample, which is kept if it's among the largest
+    /// seen.
+
+    pub fn record(&mut self, sample: i64, tag: u64) {
+        if self.entries.len() == self.limit && sample <= self.entries[self.limit - 1].sample {
+            return;
+        }
+
+        // Insert after any equal samples, so the oldest tag wins ties.
+
+        let index = self.entries.partition_point(|entry| entry.sample >= sample);
+
+        self.entries.insert(index, TaggedSample { sample, tag });
+        self.entries.truncate(self.limit);
+    }
+
+    /// Adds the samples kept by another instance.
+
+    pub fn merge(&mut self, other: &WorstTags) {
+        for entry in other.entries.iter() {
+            self.record(entry.sample, entry.tag);
+        }
+    }
+
+    /// Returns the samples kept, from the largest down.
+
+    pub fn tags(&self) -> Vec<TaggedSample> {
+        self.entries.clone()
+    }
+
+    /// Returns the number of samples kept at most.
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Discards the samples kept.
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_simple() {
+        let mut worst = WorstTags::new(3);
+
+        assert!(worst.limit() == 3);
+        assert!(worst.tags().is_empty());
+
+        for (sample, tag) in [ (5, 1), (9, 2), (1, 3), (9, 4), (7, 5), (8, 6) ] {
+            worst.record(sample, tag);
+        }
+
+        let tags: Vec<(i64, u64)> = worst.tags().iter().map(|entry| (entry.sample, entry.tag)).collect();
+
+        assert!(tags == vec![ (9, 2), (9, 4), (8, 6) ]);
+
+        worst.clear();
+
+        assert!(worst.tags().is_empty());
+
+        // With a limit of one, the first maximum is kept.
+
+        let mut max = WorstTags::new(1);
+
+        max.record(-5, 10);
+        max.record(-5, 11);
+
+        assert!(max.tags() == vec![ TaggedSample { sample: -5, tag: 10 } ]);
+
+        max.record(0, 12);
+
+        assert!(max.tags()[0].tag == 12);
+
+        // Merging keeps the largest samples of both.
+
+        let mut other = WorstTags::new(2);
+
+        other.record(3, 20);
+        other.record(1, 21);
+        max.merge(&other);
+
+        assert!(max.tags()[0].tag == 20);
+    }
+
+    #[test]
+    fn run_tests() {
+        test_simple();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_limit() {
+        let _ = WorstTags::new(0);
+    }
+}